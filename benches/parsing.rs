@@ -0,0 +1,279 @@
+// Copyright (c) 2023 Anatoly Ikorsky
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Benchmarks for parsing hot paths.
+//!
+//! Run with `cargo bench --bench parsing`.
+
+use std::sync::Arc;
+
+use bytes::BufMut;
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, Criterion, Throughput};
+use mysql_common::{
+    binlog::{consts::BinlogVersion, events::EventData, BinlogFile},
+    constants::{ColumnFlags, ColumnType},
+    io::{BufMutExt, ParseBuf},
+    packets::Column,
+    proto::{Binary, MyDeserialize, Text},
+    row::{convert::FromRow, new_row, RowDeserializer},
+    value::{
+        convert::{from_value, FromValue},
+        ClientSide, Value,
+    },
+};
+
+const BINLOG: &[u8] = include_bytes!("../test-data/binlogs/ver_trunk_row_v2.001");
+
+fn columns() -> Arc<[Column]> {
+    vec![
+        Column::new(ColumnType::MYSQL_TYPE_LONGLONG).with_name(b"id"),
+        Column::new(ColumnType::MYSQL_TYPE_LONG)
+            .with_name(b"counter")
+            .with_flags(ColumnFlags::UNSIGNED_FLAG),
+        Column::new(ColumnType::MYSQL_TYPE_VAR_STRING).with_name(b"name"),
+        Column::new(ColumnType::MYSQL_TYPE_DOUBLE).with_name(b"score"),
+        Column::new(ColumnType::MYSQL_TYPE_DATETIME).with_name(b"created"),
+        Column::new(ColumnType::MYSQL_TYPE_BLOB).with_name(b"payload"),
+    ]
+    .into()
+}
+
+fn values() -> Vec<Value> {
+    vec![
+        Value::Int(1_234_567),
+        Value::UInt(42),
+        Value::Bytes(b"some short name".to_vec()),
+        Value::Double(12.3456789),
+        Value::Date(2023, 6, 19, 12, 30, 0, 123_456),
+        Value::Bytes(br#"{"foo":"bar","baz":42345.6777}"#.to_vec()),
+    ]
+}
+
+fn text_row_payload(values: &[Value]) -> Vec<u8> {
+    let mut payload = Vec::new();
+    for value in values {
+        let text = value.as_sql(true);
+        payload.put_lenenc_str(text.trim_matches('\'').as_bytes());
+    }
+    payload
+}
+
+fn binary_row_payload(values: &[Value]) -> Vec<u8> {
+    // packet header and an empty null-bitmap (offset 2, six columns)
+    let mut payload = vec![0x00, 0x00];
+    for value in values {
+        match *value {
+            Value::Int(x) => payload.put_i64_le(x),
+            Value::UInt(x) => payload.put_u32_le(x as u32),
+            Value::Double(x) => payload.put_f64_le(x),
+            Value::Date(y, m, d, h, i, s, u) => {
+                payload.put_u8(11);
+                payload.put_u16_le(y);
+                payload.put_slice(&[m, d, h, i, s]);
+                payload.put_u32_le(u);
+            }
+            Value::Bytes(ref x) => payload.put_lenenc_str(x),
+            _ => unreachable!(),
+        }
+    }
+    payload
+}
+
+fn bench_binlog(c: &mut Criterion) {
+    let mut group = c.benchmark_group("binlog");
+    group.throughput(Throughput::Bytes(BINLOG.len() as u64));
+
+    group.bench_function("read_events", |b| {
+        b.iter(|| {
+            let file = BinlogFile::new(BinlogVersion::Version4, black_box(BINLOG)).unwrap();
+            let mut count = 0;
+            for event in file {
+                black_box(event.unwrap());
+                count += 1;
+            }
+            count
+        })
+    });
+
+    group.bench_function("read_event_data", |b| {
+        b.iter(|| {
+            let file = BinlogFile::new(BinlogVersion::Version4, black_box(BINLOG)).unwrap();
+            for event in file {
+                black_box(event.unwrap().read_data().unwrap());
+            }
+        })
+    });
+
+    group.bench_function("read_rows", |b| {
+        b.iter(|| {
+            let mut file = BinlogFile::new(BinlogVersion::Version4, black_box(BINLOG)).unwrap();
+            let mut rows = 0;
+            while let Some(event) = file.next() {
+                let event = event.unwrap();
+                if let Some(EventData::RowsEvent(ev)) = event.read_data().unwrap() {
+                    let tme = file.reader().get_tme(ev.table_id()).unwrap();
+                    for row in ev.rows(tme) {
+                        black_box(row.unwrap());
+                        rows += 1;
+                    }
+                }
+            }
+            rows
+        })
+    });
+
+    group.finish();
+}
+
+fn bench_rows(c: &mut Criterion) {
+    let columns = columns();
+    let values = values();
+    let text = text_row_payload(&values);
+    let binary = binary_row_payload(&values);
+
+    let mut group = c.benchmark_group("row");
+
+    group.throughput(Throughput::Bytes(text.len() as u64));
+    group.bench_function("deserialize_text", |b| {
+        b.iter(|| {
+            RowDeserializer::<(), Text>::deserialize(columns.clone(), &mut ParseBuf(&text)).unwrap()
+        })
+    });
+
+    group.throughput(Throughput::Bytes(binary.len() as u64));
+    group.bench_function("deserialize_binary", |b| {
+        b.iter(|| {
+            RowDeserializer::<ClientSide, Binary>::deserialize(
+                columns.clone(),
+                &mut ParseBuf(&binary),
+            )
+            .unwrap()
+        })
+    });
+
+    let row = new_row(values, columns);
+    group.throughput(Throughput::Elements(row.len() as u64));
+    group.bench_function("from_row", |b| {
+        type Tuple = (i64, u32, String, f64, Value, Vec<u8>);
+        b.iter_batched(
+            || row.clone(),
+            <Tuple as FromRow>::from_row,
+            BatchSize::SmallInput,
+        )
+    });
+    group.bench_function("clone", |b| b.iter(|| black_box(&row).clone()));
+
+    group.finish();
+}
+
+fn bench_values(c: &mut Criterion) {
+    fn bench_from_value<T: FromValue>(c: &mut Criterion, name: &str, value: Value) {
+        c.bench_function(&format!("value/from_value/{}", name), |b| {
+            b.iter_batched(|| value.clone(), from_value::<T>, BatchSize::SmallInput)
+        });
+    }
+
+    bench_from_value::<i64>(c, "i64_from_int", Value::Int(-1_234_567));
+    bench_from_value::<i64>(c, "i64_from_bytes", Value::Bytes(b"-1234567".to_vec()));
+    bench_from_value::<f64>(c, "f64_from_bytes", Value::Bytes(b"12.3456789".to_vec()));
+    bench_from_value::<String>(c, "string_from_bytes", Value::Bytes(b"some name".to_vec()));
+    bench_from_value::<std::time::Duration>(
+        c,
+        "duration_from_bytes",
+        Value::Bytes(b"100:30:00.500000".to_vec()),
+    );
+    #[cfg(feature = "time")]
+    bench_from_value::<time::PrimitiveDateTime>(
+        c,
+        "time_datetime_from_bytes",
+        Value::Bytes(b"2023-06-19 12:30:00.123456".to_vec()),
+    );
+    #[cfg(feature = "rust_decimal")]
+    bench_from_value::<rust_decimal::Decimal>(
+        c,
+        "rust_decimal_from_bytes",
+        Value::Bytes(b"12345678.9012345".to_vec()),
+    );
+}
+
+fn bench_lenenc(c: &mut Criterion) {
+    let ints = [
+        0_u64,
+        250,
+        251,
+        0xFFFF,
+        0x10000,
+        0xFFFFFF,
+        0x1000000,
+        u64::MAX,
+    ];
+    let mut encoded_ints = Vec::new();
+    for n in ints.iter() {
+        encoded_ints.put_lenenc_int(*n);
+    }
+
+    let strs: Vec<Vec<u8>> = [0_usize, 16, 300, 70_000]
+        .iter()
+        .map(|len| vec![b'x'; *len])
+        .collect();
+    let mut encoded_strs = Vec::new();
+    for s in strs.iter() {
+        encoded_strs.put_lenenc_str(s);
+    }
+
+    let mut group = c.benchmark_group("lenenc");
+
+    group.throughput(Throughput::Elements(ints.len() as u64));
+    group.bench_function("put_int", |b| {
+        let mut buf = Vec::with_capacity(encoded_ints.len());
+        b.iter(|| {
+            buf.clear();
+            for n in ints.iter() {
+                buf.put_lenenc_int(black_box(*n));
+            }
+        })
+    });
+    group.bench_function("eat_int", |b| {
+        b.iter(|| {
+            let mut buf = ParseBuf(black_box(&encoded_ints));
+            for _ in 0..ints.len() {
+                black_box(buf.eat_lenenc_int());
+            }
+        })
+    });
+
+    group.throughput(Throughput::Bytes(encoded_strs.len() as u64));
+    group.bench_function("put_str", |b| {
+        let mut buf = Vec::with_capacity(encoded_strs.len());
+        b.iter(|| {
+            buf.clear();
+            for s in strs.iter() {
+                buf.put_lenenc_str(black_box(s));
+            }
+        })
+    });
+    group.bench_function("eat_str", |b| {
+        b.iter(|| {
+            let mut buf = ParseBuf(black_box(&encoded_strs));
+            for _ in 0..strs.len() {
+                black_box(buf.eat_lenenc_str());
+            }
+        })
+    });
+
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    bench_binlog,
+    bench_rows,
+    bench_values,
+    bench_lenenc
+);
+criterion_main!(benches);
@@ -0,0 +1,366 @@
+// Copyright (c) 2023 Anatoly Ikorsky
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Asynchronous reading of binlog files and binlog event streams (requires `futures` feature).
+
+use futures_core::Stream;
+use futures_io::AsyncRead;
+
+use std::{
+    future::poll_fn,
+    io::{self, Error, ErrorKind::UnexpectedEof},
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use crate::{io::ParseBuf, proto::MyDeserialize};
+
+use super::{
+    consts::BinlogVersion,
+    events::{BinlogEventHeader, Event},
+    BinlogFileHeader, EventStreamReader,
+};
+
+/// Asynchronous counterpart of the [`EventStreamReader`].
+///
+/// It reads exactly one event from the input and then decodes it with the wrapped
+/// [`EventStreamReader`], so it maintains the same state and applies the same rules.
+///
+/// Partially read event is kept in the reader, so [`AsyncEventStreamReader::read`]
+/// is cancellation safe – dropped future won't lose data, given that the next call
+/// uses the same input.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct AsyncEventStreamReader {
+    reader: EventStreamReader,
+    buf: Vec<u8>,
+    filled: usize,
+}
+
+impl AsyncEventStreamReader {
+    /// Creates a new instance.
+    pub fn new(version: BinlogVersion) -> Self {
+        Self::with_reader(EventStreamReader::new(version))
+    }
+
+    /// Creates a new instance using the given binlog stream reader (and its settings).
+    pub fn with_reader(reader: EventStreamReader) -> Self {
+        Self {
+            reader,
+            buf: Vec::new(),
+            filled: 0,
+        }
+    }
+
+    /// Returns a reference to the underlying binlog stream reader.
+    pub fn reader(&self) -> &EventStreamReader {
+        &self.reader
+    }
+
+    /// Returns a mutable reference to the underlying binlog stream reader.
+    pub fn reader_mut(&mut self) -> &mut EventStreamReader {
+        &mut self.reader
+    }
+
+    /// Returns the underlying binlog stream reader.
+    ///
+    /// Partially read event, if any, is lost.
+    pub fn into_reader(self) -> EventStreamReader {
+        self.reader
+    }
+
+    /// Will read next event from the given stream (see [`EventStreamReader::read`]).
+    pub async fn read<T: AsyncRead + Unpin>(&mut self, mut input: T) -> io::Result<Event<'static>> {
+        poll_fn(|cx| self.poll_read(cx, Pin::new(&mut input))).await
+    }
+
+    /// Polls for the next event from the given stream (see [`AsyncEventStreamReader::read`]).
+    pub fn poll_read<T: AsyncRead + ?Sized>(
+        &mut self,
+        cx: &mut Context<'_>,
+        mut input: Pin<&mut T>,
+    ) -> Poll<io::Result<Event<'static>>> {
+        loop {
+            let raw = match self.poll_raw_event(cx, input.as_mut()) {
+                Poll::Ready(Ok(raw)) => raw,
+                Poll::Ready(Err(err)) => return Poll::Ready(Err(err)),
+                Poll::Pending => return Poll::Pending,
+            };
+            let header = BinlogEventHeader::deserialize((), &mut ParseBuf(&raw[..]))?;
+            if self.reader.skip_body(&header) {
+                continue;
+            }
+            let event = self
+                .reader
+                .read_body(header, &raw[BinlogEventHeader::LEN..])?;
+            if let Some(event) = self.reader.accept(event) {
+                return Poll::Ready(Ok(event));
+            }
+        }
+    }
+
+    /// Reads bytes of the next event.
+    fn poll_raw_event<T: AsyncRead + ?Sized>(
+        &mut self,
+        cx: &mut Context<'_>,
+        mut input: Pin<&mut T>,
+    ) -> Poll<io::Result<Vec<u8>>> {
+        if self.buf.len() < BinlogEventHeader::LEN {
+            self.buf.resize(BinlogEventHeader::LEN, 0);
+        }
+
+        if self.filled < BinlogEventHeader::LEN {
+            match poll_fill(cx, input.as_mut(), &mut self.buf[..], &mut self.filled) {
+                Poll::Ready(Ok(())) => (),
+                Poll::Ready(Err(err)) => return Poll::Ready(Err(self.fail(err))),
+                Poll::Pending => return Poll::Pending,
+            }
+            let header = match BinlogEventHeader::deserialize(
+                (),
+                &mut ParseBuf(&self.buf[..BinlogEventHeader::LEN]),
+            ) {
+                Ok(header) => header,
+                Err(err) => return Poll::Ready(Err(self.fail(err))),
+            };
+            if let Err(err) = self.reader.check_event_size(&header) {
+                return Poll::Ready(Err(self.fail(err)));
+            }
+            self.buf.resize(header.event_size() as usize, 0);
+        }
+
+        match poll_fill(cx, input, &mut self.buf[..], &mut self.filled) {
+            Poll::Ready(Ok(())) => (),
+            Poll::Ready(Err(err)) => return Poll::Ready(Err(self.fail(err))),
+            Poll::Pending => return Poll::Pending,
+        }
+
+        self.filled = 0;
+        Poll::Ready(Ok(std::mem::take(&mut self.buf)))
+    }
+
+    /// Discards a partially read event.
+    fn fail(&mut self, err: io::Error) -> io::Error {
+        self.buf.clear();
+        self.filled = 0;
+        err
+    }
+}
+
+/// Fills the given buffer starting from `filled` bytes.
+///
+/// Emits `UnexpectedEof` if the input is exhausted.
+fn poll_fill<T: AsyncRead + ?Sized>(
+    cx: &mut Context<'_>,
+    mut input: Pin<&mut T>,
+    buf: &mut [u8],
+    filled: &mut usize,
+) -> Poll<io::Result<()>> {
+    while *filled < buf.len() {
+        match input.as_mut().poll_read(cx, &mut buf[*filled..]) {
+            Poll::Ready(Ok(0)) => {
+                return Poll::Ready(Err(Error::new(UnexpectedEof, "unexpected end of stream")))
+            }
+            Poll::Ready(Ok(n)) => *filled += n,
+            Poll::Ready(Err(err)) if err.kind() == io::ErrorKind::Interrupted => (),
+            Poll::Ready(Err(err)) => return Poll::Ready(Err(err)),
+            Poll::Pending => return Poll::Pending,
+        }
+    }
+    Poll::Ready(Ok(()))
+}
+
+/// Asynchronous counterpart of the [`BinlogFile`](super::BinlogFile).
+///
+/// It's a stream of events in a binlog file.
+#[derive(Debug)]
+pub struct AsyncBinlogFile<T> {
+    reader: AsyncEventStreamReader,
+    read: T,
+    header: [u8; BinlogFileHeader::LEN],
+    header_filled: usize,
+}
+
+impl<T: AsyncRead + Unpin> AsyncBinlogFile<T> {
+    /// Creates a new instance.
+    ///
+    /// Binlog file header is checked when the first event is requested.
+    pub fn new(version: BinlogVersion, read: T) -> Self {
+        Self::with_reader(EventStreamReader::new(version), read)
+    }
+
+    /// Creates a new instance using the given binlog stream reader.
+    ///
+    /// Binlog file header is checked when the first event is requested.
+    pub fn with_reader(reader: EventStreamReader, read: T) -> Self {
+        Self {
+            reader: AsyncEventStreamReader::with_reader(reader),
+            read,
+            header: [0; BinlogFileHeader::LEN],
+            header_filled: 0,
+        }
+    }
+
+    /// Returns a reference to the binlog stream reader.
+    pub fn reader(&self) -> &EventStreamReader {
+        self.reader.reader()
+    }
+
+    /// Reads the next event (`None` at the end of the file).
+    pub async fn next_event(&mut self) -> Option<io::Result<Event<'static>>> {
+        poll_fn(|cx| self.poll_next_event(cx)).await
+    }
+
+    fn poll_next_event(
+        &mut self,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<io::Result<Event<'static>>>> {
+        if self.header_filled < BinlogFileHeader::LEN {
+            let mut read = Pin::new(&mut self.read);
+            match poll_fill(cx, read.as_mut(), &mut self.header, &mut self.header_filled) {
+                Poll::Ready(Ok(())) => (),
+                Poll::Ready(Err(err)) => return Poll::Ready(Some(Err(err))),
+                Poll::Pending => return Poll::Pending,
+            }
+            if let Err(err) = BinlogFileHeader::read(&self.header[..]) {
+                return Poll::Ready(Some(Err(err)));
+            }
+        }
+
+        match self.reader.poll_read(cx, Pin::new(&mut self.read)) {
+            Poll::Ready(Ok(event)) => Poll::Ready(Some(Ok(event))),
+            Poll::Ready(Err(err)) if err.kind() == UnexpectedEof => Poll::Ready(None),
+            Poll::Ready(Err(err)) => Poll::Ready(Some(Err(err))),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+impl<T: AsyncRead + Unpin> Stream for AsyncBinlogFile<T> {
+    type Item = io::Result<Event<'static>>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.get_mut().poll_next_event(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures_core::Stream;
+    use futures_io::AsyncRead;
+
+    use std::{
+        fs,
+        future::Future,
+        io,
+        pin::Pin,
+        sync::Arc,
+        task::{Context, Poll, Wake, Waker},
+    };
+
+    use super::{AsyncBinlogFile, AsyncEventStreamReader};
+    use crate::binlog::{consts::BinlogVersion, BinlogFile, BinlogFileHeader};
+
+    struct NoopWaker;
+
+    impl Wake for NoopWaker {
+        fn wake(self: Arc<Self>) {}
+    }
+
+    /// Polls the given future to completion (test inputs never wait for real).
+    fn block_on<F: Future>(future: F) -> F::Output {
+        let waker = Waker::from(Arc::new(NoopWaker));
+        let mut cx = Context::from_waker(&waker);
+        let mut future = Box::pin(future);
+        loop {
+            if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+                return output;
+            }
+        }
+    }
+
+    /// Returns at most `chunk` bytes per read and is pending every other poll.
+    struct Chunked<'a> {
+        data: &'a [u8],
+        chunk: usize,
+        pending: bool,
+    }
+
+    impl AsyncRead for Chunked<'_> {
+        fn poll_read(
+            mut self: Pin<&mut Self>,
+            cx: &mut Context<'_>,
+            buf: &mut [u8],
+        ) -> Poll<io::Result<usize>> {
+            self.pending = !self.pending;
+            if self.pending {
+                cx.waker().wake_by_ref();
+                return Poll::Pending;
+            }
+            let len = self.chunk.min(buf.len()).min(self.data.len());
+            buf[..len].copy_from_slice(&self.data[..len]);
+            self.data = &self.data[len..];
+            Poll::Ready(Ok(len))
+        }
+    }
+
+    #[test]
+    fn should_read_binlog_asynchronously() -> io::Result<()> {
+        let data = fs::read("./test-data/binlogs/write-full-row.binlog")?;
+        let expected =
+            BinlogFile::new(BinlogVersion::Version4, &data[..])?.collect::<io::Result<Vec<_>>>()?;
+        assert!(expected.len() > 2);
+
+        let input = Chunked {
+            data: &data,
+            chunk: 7,
+            pending: false,
+        };
+        let mut file = AsyncBinlogFile::new(BinlogVersion::Version4, input);
+        let mut events = Vec::new();
+        while let Some(event) = block_on(file.next_event()) {
+            events.push(event?);
+        }
+        assert_eq!(events, expected);
+
+        // stream api
+        let mut file = AsyncBinlogFile::new(BinlogVersion::Version4, &data[..]);
+        let mut events = Vec::new();
+        let waker = Waker::from(Arc::new(NoopWaker));
+        let mut cx = Context::from_waker(&waker);
+        while let Poll::Ready(Some(event)) = Pin::new(&mut file).poll_next(&mut cx) {
+            events.push(event?);
+        }
+        assert_eq!(events, expected);
+
+        // dropped futures don't lose data
+        let mut input = Chunked {
+            data: &data[BinlogFileHeader::LEN..],
+            chunk: 3,
+            pending: false,
+        };
+        let mut reader = AsyncEventStreamReader::new(BinlogVersion::Version4);
+        let waker = Waker::from(Arc::new(NoopWaker));
+        let mut cx = Context::from_waker(&waker);
+        let mut events = Vec::new();
+        while events.len() < expected.len() {
+            let mut future = Box::pin(reader.read(&mut input));
+            if let Poll::Ready(event) = future.as_mut().poll(&mut cx) {
+                events.push(event?);
+            }
+        }
+        assert_eq!(events, expected);
+        assert_eq!(
+            block_on(reader.read(&mut input)).unwrap_err().kind(),
+            io::ErrorKind::UnexpectedEof
+        );
+
+        let mut file = AsyncBinlogFile::new(BinlogVersion::Version4, &b"\xfebim"[..]);
+        assert!(block_on(file.next_event()).unwrap().is_err());
+
+        Ok(())
+    }
+}
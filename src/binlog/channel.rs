@@ -0,0 +1,298 @@
+// Copyright (c) 2023 Anatoly Ikorsky
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Delivery of events read by a blocking reader as a [`Stream`] (requires `futures` feature).
+
+use futures_core::Stream;
+
+use std::{
+    io::{self, ErrorKind::UnexpectedEof, Read},
+    pin::Pin,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{sync_channel, Receiver, SyncSender, TryRecvError},
+        Arc, Mutex,
+    },
+    task::{Context, Poll, Waker},
+    thread::{self, JoinHandle},
+};
+
+use super::{events::Event, EventStreamReader};
+
+/// State shared between the stream and the reader thread.
+#[derive(Debug, Default)]
+struct Shared {
+    waker: Mutex<Option<Waker>>,
+    closed: AtomicBool,
+}
+
+impl Shared {
+    fn wake(&self) {
+        if let Some(waker) = self.waker.lock().expect("poisoned").take() {
+            waker.wake();
+        }
+    }
+}
+
+/// Sending half of the channel that notifies the stream when the reader thread exits
+/// (even if it panics).
+struct Producer {
+    sender: Option<SyncSender<io::Result<Event<'static>>>>,
+    shared: Arc<Shared>,
+}
+
+impl Producer {
+    /// Sends the given event. Returns `false` if the stream is dropped.
+    fn send(&self, event: io::Result<Event<'static>>) -> bool {
+        let sent = match self.sender {
+            Some(ref sender) => sender.send(event).is_ok(),
+            None => false,
+        };
+        self.shared.wake();
+        sent
+    }
+}
+
+impl Drop for Producer {
+    fn drop(&mut self) {
+        // the sender is dropped first, so that the stream observes the disconnection
+        self.sender.take();
+        self.shared.wake();
+    }
+}
+
+/// Stream of events read by a blocking reader running on a dedicated thread.
+///
+/// Events are delivered through a bounded channel, so the reader blocks as soon as
+/// `capacity` events (at least one) are waiting to be consumed.
+///
+/// *   The first error ends the stream (it's yielded as the last item), as well as
+///     a panic of the reader (it's yielded as an error of `Other` kind).
+/// *   Dropping the stream shuts the reader down. The reader thread exits as soon
+///     as the current read completes, i.e. it may linger until the next event arrives.
+#[derive(Debug)]
+pub struct EventChannel {
+    shared: Arc<Shared>,
+    receiver: Receiver<io::Result<Event<'static>>>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl EventChannel {
+    /// Spawns a thread that reads the given events (e.g. a [`super::BinlogFile`]).
+    pub fn spawn<I>(capacity: usize, events: I) -> io::Result<Self>
+    where
+        I: IntoIterator<Item = io::Result<Event<'static>>>,
+        I::IntoIter: Send + 'static,
+    {
+        let shared = Arc::new(Shared::default());
+        let (sender, receiver) = sync_channel(capacity.max(1));
+
+        let mut events = events.into_iter();
+        let producer = Producer {
+            sender: Some(sender),
+            shared: shared.clone(),
+        };
+        let handle = thread::Builder::new()
+            .name("binlog-event-reader".into())
+            .spawn(move || {
+                while !producer.shared.closed.load(Ordering::Acquire) {
+                    let event = match events.next() {
+                        Some(event) => event,
+                        None => break,
+                    };
+                    let is_err = event.is_err();
+                    if !producer.send(event) || is_err {
+                        break;
+                    }
+                }
+            })?;
+
+        Ok(Self {
+            shared,
+            receiver,
+            handle: Some(handle),
+        })
+    }
+
+    /// Spawns a thread that reads events from the given input (e.g. a socket)
+    /// using the given binlog stream reader (see [`EventStreamReader::read`]).
+    ///
+    /// The stream ends if the input is exhausted.
+    pub fn spawn_reader<T>(
+        capacity: usize,
+        mut reader: EventStreamReader,
+        mut input: T,
+    ) -> io::Result<Self>
+    where
+        T: Read + Send + 'static,
+    {
+        let events = std::iter::from_fn(move || match reader.read(&mut input) {
+            Ok(event) => Some(Ok(event)),
+            Err(err) if err.kind() == UnexpectedEof => None,
+            Err(err) => Some(Err(err)),
+        });
+        Self::spawn(capacity, events)
+    }
+
+    /// Returns the result of the finished reader thread.
+    fn join(&mut self) -> Option<io::Result<Event<'static>>> {
+        match self.handle.take()?.join() {
+            Ok(()) => None,
+            Err(_) => Some(Err(io::Error::other("binlog event reader thread panicked"))),
+        }
+    }
+}
+
+impl Stream for EventChannel {
+    type Item = io::Result<Event<'static>>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        // registered before checking the channel, so that a wake-up isn't lost
+        *this.shared.waker.lock().expect("poisoned") = Some(cx.waker().clone());
+        match this.receiver.try_recv() {
+            Ok(event) => Poll::Ready(Some(event)),
+            Err(TryRecvError::Empty) => Poll::Pending,
+            Err(TryRecvError::Disconnected) => Poll::Ready(this.join()),
+        }
+    }
+}
+
+impl Drop for EventChannel {
+    fn drop(&mut self) {
+        self.shared.closed.store(true, Ordering::Release);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures_core::Stream;
+
+    use std::{
+        fs, io,
+        pin::Pin,
+        sync::{mpsc, Arc},
+        task::{Context, Poll, Wake, Waker},
+        thread::{self, Thread},
+        time::Duration,
+    };
+
+    use super::EventChannel;
+    use crate::binlog::{
+        consts::BinlogVersion, events::Event, BinlogFile, BinlogFileHeader, EventStreamReader,
+    };
+
+    struct ThreadWaker(Thread);
+
+    impl Wake for ThreadWaker {
+        fn wake(self: Arc<Self>) {
+            self.0.unpark();
+        }
+    }
+
+    /// Blocks until the next item of the given stream is ready.
+    fn next<S: Stream + Unpin>(stream: &mut S) -> Option<S::Item> {
+        let waker = Waker::from(Arc::new(ThreadWaker(thread::current())));
+        let mut cx = Context::from_waker(&waker);
+        loop {
+            match Pin::new(&mut *stream).poll_next(&mut cx) {
+                Poll::Ready(item) => return item,
+                Poll::Pending => thread::park(),
+            }
+        }
+    }
+
+    fn collect(mut stream: EventChannel) -> Vec<io::Result<Event<'static>>> {
+        std::iter::from_fn(|| next(&mut stream)).collect()
+    }
+
+    /// Notifies the test when dropped (i.e. when the reader thread exits).
+    struct Events<I> {
+        events: I,
+        dropped: mpsc::Sender<()>,
+    }
+
+    impl<I: Iterator> Iterator for Events<I> {
+        type Item = I::Item;
+
+        fn next(&mut self) -> Option<Self::Item> {
+            self.events.next()
+        }
+    }
+
+    impl<I> Drop for Events<I> {
+        fn drop(&mut self) {
+            let _ = self.dropped.send(());
+        }
+    }
+
+    #[test]
+    fn should_stream_events_through_channel() -> io::Result<()> {
+        let data = fs::read("./test-data/binlogs/write-full-row.binlog")?;
+        let expected =
+            BinlogFile::new(BinlogVersion::Version4, &data[..])?.collect::<io::Result<Vec<_>>>()?;
+        assert!(expected.len() > 2);
+
+        for capacity in [0, 1, 16] {
+            let binlog_file =
+                BinlogFile::new(BinlogVersion::Version4, io::Cursor::new(data.clone()))?;
+            let events = collect(EventChannel::spawn(capacity, binlog_file)?)
+                .into_iter()
+                .collect::<io::Result<Vec<_>>>()?;
+            assert_eq!(events, expected);
+        }
+
+        let input = io::Cursor::new(data[BinlogFileHeader::LEN..].to_vec());
+        let reader = EventStreamReader::new(BinlogVersion::Version4);
+        let events = collect(EventChannel::spawn_reader(1, reader, input)?)
+            .into_iter()
+            .collect::<io::Result<Vec<_>>>()?;
+        assert_eq!(events, expected);
+
+        // the first error ends the stream
+        let items = vec![
+            Ok(expected[0].clone()),
+            Err(io::Error::new(io::ErrorKind::InvalidData, "broken")),
+            Ok(expected[1].clone()),
+        ];
+        let items = collect(EventChannel::spawn(1, items)?);
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0].as_ref().unwrap(), &expected[0]);
+        assert_eq!(
+            items[1].as_ref().unwrap_err().kind(),
+            io::ErrorKind::InvalidData
+        );
+
+        // a panic is reported
+        let event = expected[0].clone();
+        let mut count = 0;
+        let events = std::iter::from_fn(move || {
+            count += 1;
+            assert!(count < 2, "reader failure");
+            Some(Ok(event.clone()))
+        });
+        let items = collect(EventChannel::spawn(0, events)?);
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[1].as_ref().unwrap_err().kind(), io::ErrorKind::Other);
+
+        // dropped stream shuts the reader down
+        let (dropped, on_drop) = mpsc::channel();
+        let events = Events {
+            events: std::iter::repeat_with(move || Ok(expected[0].clone())),
+            dropped,
+        };
+        let mut stream = EventChannel::spawn(1, events)?;
+        assert!(next(&mut stream).unwrap().is_ok());
+        drop(stream);
+        on_drop
+            .recv_timeout(Duration::from_secs(10))
+            .expect("reader thread should exit");
+
+        Ok(())
+    }
+}
@@ -0,0 +1,149 @@
+// Copyright (c) 2023 Anatoly Ikorsky
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Clock abstraction used to timestamp generated binlog events.
+
+use std::{
+    cell::Cell,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// Source of the current time for generated events.
+///
+/// Builder methods that take a clock (e.g. [`super::events::BinlogEventHeader::with_clock`])
+/// use it instead of the system time, so that tests can generate byte-identical
+/// binlogs using [`FixedClock`].
+pub trait BinlogClock {
+    /// Returns the current time in microseconds since unix epoch.
+    fn now_micros(&self) -> u64;
+
+    /// Returns the current time in seconds since unix epoch
+    /// (saturated to fit binlog timestamps).
+    fn now(&self) -> u32 {
+        let secs = self.now_micros() / 1_000_000;
+        if secs > u32::MAX as u64 {
+            u32::MAX
+        } else {
+            secs as u32
+        }
+    }
+}
+
+impl<T: BinlogClock + ?Sized> BinlogClock for &T {
+    fn now_micros(&self) -> u64 {
+        (**self).now_micros()
+    }
+}
+
+/// The system clock.
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq, Hash)]
+pub struct SystemClock;
+
+impl BinlogClock for SystemClock {
+    fn now_micros(&self) -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|x| x.as_micros() as u64)
+            .unwrap_or(0)
+    }
+}
+
+/// A deterministic clock.
+///
+/// It returns the same time unless it's explicitly advanced
+/// (or unless an auto-increment step is given, see [`FixedClock::with_step`]).
+#[derive(Debug, Default, Clone, Eq, PartialEq)]
+pub struct FixedClock {
+    micros: Cell<u64>,
+    step: u64,
+}
+
+impl FixedClock {
+    /// Creates a new clock stopped at the given time (in microseconds since unix epoch).
+    pub fn new(micros: u64) -> Self {
+        Self {
+            micros: Cell::new(micros),
+            step: 0,
+        }
+    }
+
+    /// Creates a new clock stopped at the given time (in seconds since unix epoch).
+    pub fn from_secs(secs: u32) -> Self {
+        Self::new(secs as u64 * 1_000_000)
+    }
+
+    /// Returns modified `self` that advances by `step` microseconds after every reading.
+    pub fn with_step(mut self, step: u64) -> Self {
+        self.step = step;
+        self
+    }
+
+    /// Advances the clock by the given number of microseconds.
+    pub fn advance(&self, micros: u64) {
+        self.micros.set(self.micros.get().saturating_add(micros));
+    }
+}
+
+impl BinlogClock for FixedClock {
+    fn now_micros(&self) -> u64 {
+        let now = self.micros.get();
+        self.advance(self.step);
+        now
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BinlogClock, FixedClock, SystemClock};
+    use crate::{
+        binlog::{
+            consts::{BinlogVersion, EventFlags, EventType},
+            events::{BinlogEventHeader, FormatDescriptionEvent, GtidEvent},
+        },
+        proto::MySerialize,
+    };
+
+    #[test]
+    fn should_generate_deterministic_events() {
+        fn generate(clock: &impl BinlogClock) -> Vec<u8> {
+            let mut output = Vec::new();
+            FormatDescriptionEvent::new(BinlogVersion::Version4)
+                .with_clock(clock)
+                .serialize(&mut output);
+            BinlogEventHeader::new(0, EventType::GTID_EVENT, 1, 0, 0, EventFlags::empty())
+                .with_clock(clock)
+                .serialize(&mut output);
+            GtidEvent::new([1; 16], 1)
+                .with_clock(clock)
+                .serialize(&mut output);
+            output
+        }
+
+        let clock = FixedClock::from_secs(1_600_000_000);
+        assert_eq!(clock.now(), 1_600_000_000);
+        assert_eq!(
+            generate(&clock),
+            generate(&FixedClock::from_secs(1_600_000_000))
+        );
+
+        let gtid = GtidEvent::new([1; 16], 1).with_clock(&clock);
+        assert_eq!(gtid.immediate_commit_timestamp(), 1_600_000_000_000_000);
+        assert_eq!(gtid.original_commit_timestamp(), 1_600_000_000_000_000);
+
+        let clock = FixedClock::new(1_000_000).with_step(500_000);
+        let header = BinlogEventHeader::new(0, EventType::GTID_EVENT, 1, 0, 0, EventFlags::empty());
+        assert_eq!(header.with_clock(&clock).timestamp(), 1);
+        assert_eq!(header.with_clock(&clock).timestamp(), 1);
+        assert_eq!(header.with_clock(&clock).timestamp(), 2);
+        clock.advance(10_000_000);
+        assert_eq!(header.with_clock(&clock).timestamp(), 12);
+
+        assert!(SystemClock.now() > 1_600_000_000);
+        assert_eq!(FixedClock::new(u64::MAX).now(), u32::MAX);
+    }
+}
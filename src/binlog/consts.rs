@@ -116,8 +116,49 @@ pub enum EventType {
     /// Extension of UPDATE_ROWS_EVENT, allowing partial values according
     /// to binlog_row_value_options.
     PARTIAL_UPDATE_ROWS_EVENT = 0x27,
-    /// Total number of known events.
+    /// Compressed transaction (added in MySQL 8.0.20).
+    TRANSACTION_PAYLOAD_EVENT = 0x28,
+    /// Total number of known MySql events.
     ENUM_END_EVENT,
+    /// MariaDB annotate rows event.
+    ///
+    /// Holds the query that caused the following rows events
+    /// (written if `binlog_annotate_row_events` is set).
+    MARIADB_ANNOTATE_ROWS_EVENT = 0xa0,
+    /// MariaDB binlog checkpoint event.
+    ///
+    /// Names the oldest binlog file that is still needed for XA crash recovery.
+    MARIADB_BINLOG_CHECKPOINT_EVENT = 0xa1,
+    /// MariaDB GTID event.
+    ///
+    /// Starts an event group (transaction) and defines its GTID.
+    MARIADB_GTID_EVENT = 0xa2,
+    /// MariaDB GTID list event.
+    ///
+    /// Written at the start of each binlog file, holds the last GTID of each replication domain
+    /// logged in previous binlog files.
+    MARIADB_GTID_LIST_EVENT = 0xa3,
+    /// MariaDB start encryption event.
+    ///
+    /// Holds encryption parameters of the binlog file. Events that follow it in the file
+    /// are encrypted (replication streams carry events already decrypted by the server).
+    MARIADB_START_ENCRYPTION_EVENT = 0xa4,
+    /// MariaDB compressed query event.
+    ///
+    /// `QUERY_EVENT` with the zlib-compressed query (written if `log_bin_compress` is set).
+    MARIADB_QUERY_COMPRESSED_EVENT = 0xa5,
+    /// MariaDB compressed write rows event v1 (rows data is zlib-compressed).
+    MARIADB_WRITE_ROWS_COMPRESSED_EVENT_V1 = 0xa6,
+    /// MariaDB compressed update rows event v1 (rows data is zlib-compressed).
+    MARIADB_UPDATE_ROWS_COMPRESSED_EVENT_V1 = 0xa7,
+    /// MariaDB compressed delete rows event v1 (rows data is zlib-compressed).
+    MARIADB_DELETE_ROWS_COMPRESSED_EVENT_V1 = 0xa8,
+    /// MariaDB compressed write rows event (rows data is zlib-compressed).
+    MARIADB_WRITE_ROWS_COMPRESSED_EVENT = 0xa9,
+    /// MariaDB compressed update rows event (rows data is zlib-compressed).
+    MARIADB_UPDATE_ROWS_COMPRESSED_EVENT = 0xaa,
+    /// MariaDB compressed delete rows event (rows data is zlib-compressed).
+    MARIADB_DELETE_ROWS_COMPRESSED_EVENT = 0xab,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, thiserror::Error)]
@@ -176,6 +217,19 @@ impl TryFrom<u8> for EventType {
             0x25 => Ok(Self::VIEW_CHANGE_EVENT),
             0x26 => Ok(Self::XA_PREPARE_LOG_EVENT),
             0x27 => Ok(Self::PARTIAL_UPDATE_ROWS_EVENT),
+            0x28 => Ok(Self::TRANSACTION_PAYLOAD_EVENT),
+            0xa0 => Ok(Self::MARIADB_ANNOTATE_ROWS_EVENT),
+            0xa1 => Ok(Self::MARIADB_BINLOG_CHECKPOINT_EVENT),
+            0xa2 => Ok(Self::MARIADB_GTID_EVENT),
+            0xa3 => Ok(Self::MARIADB_GTID_LIST_EVENT),
+            0xa4 => Ok(Self::MARIADB_START_ENCRYPTION_EVENT),
+            0xa5 => Ok(Self::MARIADB_QUERY_COMPRESSED_EVENT),
+            0xa6 => Ok(Self::MARIADB_WRITE_ROWS_COMPRESSED_EVENT_V1),
+            0xa7 => Ok(Self::MARIADB_UPDATE_ROWS_COMPRESSED_EVENT_V1),
+            0xa8 => Ok(Self::MARIADB_DELETE_ROWS_COMPRESSED_EVENT_V1),
+            0xa9 => Ok(Self::MARIADB_WRITE_ROWS_COMPRESSED_EVENT),
+            0xaa => Ok(Self::MARIADB_UPDATE_ROWS_COMPRESSED_EVENT),
+            0xab => Ok(Self::MARIADB_DELETE_ROWS_COMPRESSED_EVENT),
             x => Err(UnknownEventType(x)),
         }
     }
@@ -260,6 +314,47 @@ impl TryFrom<u8> for BinlogChecksumAlg {
     }
 }
 
+/// Compression algorithm of a transaction payload event.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+#[allow(non_camel_case_types)]
+#[repr(u8)]
+pub enum PayloadCompressionType {
+    /// Zstandard compression.
+    ZSTD = 0,
+    /// Payload isn't compressed.
+    NONE = 255,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, thiserror::Error)]
+#[error("Unknown payload compression type {}", _0)]
+#[repr(transparent)]
+pub struct UnknownPayloadCompressionType(pub u64);
+
+impl From<UnknownPayloadCompressionType> for u64 {
+    fn from(x: UnknownPayloadCompressionType) -> Self {
+        x.0
+    }
+}
+
+impl TryFrom<u64> for PayloadCompressionType {
+    type Error = UnknownPayloadCompressionType;
+
+    fn try_from(value: u64) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(Self::ZSTD),
+            255 => Ok(Self::NONE),
+            x => Err(UnknownPayloadCompressionType(x)),
+        }
+    }
+}
+
+/// Maximum number of database names in the `UpdatedDbNames` status variable.
+pub const MAX_DBS_IN_EVENT_MTS: usize = 16;
+
+/// `UpdatedDbNames` status variable count that means that the number of updated databases
+/// exceeds [`MAX_DBS_IN_EVENT_MTS`] (names are omitted in this case).
+pub const OVER_MAX_DBS_IN_EVENT_MTS: u8 = 254;
+
 /// Binlog query event status vars keys.
 #[repr(u8)]
 #[allow(non_camel_case_types)]
@@ -442,6 +537,42 @@ impl TryFrom<u8> for LoadDuplicateHandling {
     }
 }
 
+my_bitflags! {
+    LoadOptFlags,
+    #[error("Unknown flags in the raw value of LoadOptFlags (raw={:b})", _0)]
+    UnknownLoadOptFlags,
+    u8,
+
+    /// Options of a `LOAD DATA` statement stored in `LOAD_EVENT` and `NEW_LOAD_EVENT`.
+    #[derive(PartialEq, Eq, Hash, Debug, Clone, Copy)]
+    pub struct LoadOptFlags: u8 {
+        const DUMPFILE_FLAG = 0x01;
+        /// `OPTIONALLY ENCLOSED BY` is given.
+        const OPT_ENCLOSED_FLAG = 0x02;
+        /// `REPLACE` is given.
+        const REPLACE_FLAG = 0x04;
+        /// `IGNORE` is given.
+        const IGNORE_FLAG = 0x08;
+    }
+}
+
+my_bitflags! {
+    LoadEmptyFlags,
+    #[error("Unknown flags in the raw value of LoadEmptyFlags (raw={:b})", _0)]
+    UnknownLoadEmptyFlags,
+    u8,
+
+    /// Empty flags of a `LOAD_EVENT` (i.e. terminators given as an empty string).
+    #[derive(PartialEq, Eq, Hash, Debug, Clone, Copy)]
+    pub struct LoadEmptyFlags: u8 {
+        const FIELD_TERM_EMPTY = 0x01;
+        const ENCLOSED_EMPTY = 0x02;
+        const LINE_TERM_EMPTY = 0x04;
+        const LINE_START_EMPTY = 0x08;
+        const ESCAPED_EMPTY = 0x10;
+    }
+}
+
 /// Enumerates types of optional metadata fields.
 #[repr(u8)]
 #[allow(non_camel_case_types)]
@@ -709,6 +840,35 @@ my_bitflags! {
     }
 }
 
+my_bitflags! {
+    MariadbGtidFlags,
+    #[error("Unknown flags in the raw value of MariadbGtidFlags (raw={:b})", _0)]
+    UnknownMariadbGtidFlags,
+    u8,
+
+    /// MariaDB Gtid event flags (`flags2`).
+    #[derive(PartialEq, Eq, Hash, Debug, Clone, Copy)]
+    pub struct MariadbGtidFlags: u8 {
+        /// Event group is not transactional and is not followed by a commit event
+        /// (e.g. DDL or non-transactional DML).
+        const STANDALONE = 0x01;
+        /// Event group is a part of a group commit (the commit id is present).
+        const GROUP_COMMIT_ID = 0x02;
+        /// Event group only changes transactional tables.
+        const TRANSACTIONAL = 0x04;
+        /// Event group may be applied in parallel with other event groups.
+        const ALLOW_PARALLEL = 0x08;
+        /// Event group waited for a row lock of another transaction on the master.
+        const WAITED = 0x10;
+        /// Event group is a DDL statement.
+        const DDL = 0x20;
+        /// Event group is the prepare phase of an XA transaction (the XID is present).
+        const PREPARED_XA = 0x40;
+        /// Event group completes an XA transaction (the XID is present).
+        const COMPLETED_XA = 0x80;
+    }
+}
+
 /// Group number of a Gtid event.
 ///
 /// Should be between `MIN_GNO` and `MAX_GNO` for GtidEvent and `0` for AnonymousGtidEvent.
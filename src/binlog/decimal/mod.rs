@@ -36,6 +36,9 @@ pub const DIG_BASE: usize = 1_000_000_000;
 /// Number of bytes required to store given number of decimal digits.
 pub const DIG_TO_BYTES: [u8; DIG_PER_DEC + 1] = [0, 1, 1, 2, 2, 3, 3, 4, 4, 4];
 
+/// Maximum precision of the `DECIMAL` column.
+pub const MAX_PRECISION: usize = 65;
+
 pub const POWERS_10: [i32; DIG_PER_DEC + 1] = [
     1, 10, 100, 1000, 10000, 100000, 1000000, 10000000, 100000000, 1000000000,
 ];
@@ -139,12 +142,67 @@ impl Decimal {
         output.write_all(&out_buf)
     }
 
+    /// Writes binary representation of `self` as stored in a `DECIMAL(precision, scale)` column,
+    /// i.e. `decimal_bin_size(precision, scale)` bytes.
+    ///
+    /// Returns an error if the value doesn't fit the column. Fractional digits beyond `scale`
+    /// are only allowed if they are zeros.
+    pub fn write_bin_with_precision<T: Write>(
+        &self,
+        output: T,
+        precision: usize,
+        scale: usize,
+    ) -> io::Result<()> {
+        check_precision(precision, scale, io::ErrorKind::InvalidInput)?;
+        if precision == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "decimal precision must be positive",
+            ));
+        }
+
+        let string = self.to_string();
+        let (sign, digits) = match string.strip_prefix('-') {
+            Some(digits) => ("-", digits),
+            None => ("", &*string),
+        };
+        let (integral, fractional) = digits.split_once('.').unwrap_or((digits, ""));
+        let integral = integral.trim_start_matches('0');
+        let fractional = fractional.trim_end_matches('0');
+
+        let intg = precision - scale;
+        if integral.len() > intg || fractional.len() > scale {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "decimal {} doesn't fit DECIMAL({}, {})",
+                    self, precision, scale
+                ),
+            ));
+        }
+
+        let padded = format!(
+            "{}{:0>intg$}.{:0<scale$}",
+            sign,
+            integral,
+            fractional,
+            intg = intg,
+            scale = scale
+        );
+        let padded = Decimal::from_str(&padded)
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "decimal is ill-formed"))?;
+
+        padded.write_bin(output)
+    }
+
     pub fn read_bin<T: Read>(
         mut input: T,
         precision: usize,
         scale: usize,
         keep_prec: bool,
     ) -> io::Result<Self> {
+        check_precision(precision, scale, io::ErrorKind::InvalidData)?;
+
         let mut out = Self::default();
 
         let bin_size = decimal_bin_size(precision, scale);
@@ -372,6 +430,21 @@ impl FromStr for Decimal {
     }
 }
 
+/// Checks that `precision` and `scale` describe a valid `DECIMAL` column.
+pub(crate) fn check_precision(
+    precision: usize,
+    scale: usize,
+    kind: io::ErrorKind,
+) -> io::Result<()> {
+    if scale > precision || precision > MAX_PRECISION {
+        return Err(io::Error::new(
+            kind,
+            format!("invalid DECIMAL({}, {}) column", precision, scale),
+        ));
+    }
+    Ok(())
+}
+
 /// Returns binary representation size (in bytes) for given precision and scale.
 #[inline]
 pub fn decimal_bin_size(precision: usize, scale: usize) -> usize {
@@ -192,3 +192,59 @@ proptest! {
         assert_eq!(dec, dec2);
     }
 }
+
+#[test]
+fn decimal_column_roundtrip() {
+    use std::str::FromStr;
+
+    use crate::{binlog::value::BinlogValue, constants::ColumnType, io::ParseBuf, value::Value};
+
+    let cases: &[(&str, usize, usize, &str)] = &[
+        ("0", 1, 0, "0"),
+        ("1", 10, 0, "1"),
+        ("-1", 10, 0, "-1"),
+        ("1234567890.1234", 14, 4, "1234567890.1234"),
+        ("-1234567890.1234", 14, 4, "-1234567890.1234"),
+        ("12.5", 10, 4, "12.5000"),
+        ("-0.5", 5, 5, "-0.50000"),
+        ("0.000", 3, 1, "0.0"),
+        ("1.10", 4, 1, "1.1"),
+        (
+            "-12345678901234567890.123456789012",
+            65,
+            30,
+            "-12345678901234567890.123456789012000000000000000000",
+        ),
+    ];
+
+    for (input, precision, scale, expected) in cases {
+        let dec = super::Decimal::from_str(input).unwrap();
+        let mut bin = Vec::new();
+        dec.write_bin_with_precision(&mut bin, *precision, *scale)
+            .unwrap();
+        assert_eq!(bin.len(), super::decimal_bin_size(*precision, *scale));
+
+        let dec2 = super::Decimal::read_bin(&bin[..], *precision, *scale, false).unwrap();
+        assert_eq!(dec2.to_string(), *expected);
+
+        let meta = [*precision as u8, *scale as u8];
+        let value: BinlogValue<'_> = ParseBuf(&bin)
+            .parse((ColumnType::MYSQL_TYPE_NEWDECIMAL, &meta[..], false, false))
+            .unwrap();
+        assert_eq!(
+            value,
+            BinlogValue::Value(Value::Bytes(expected.as_bytes().to_vec()))
+        );
+    }
+
+    let dec = super::Decimal::from_str("123.45").unwrap();
+    assert!(dec.write_bin_with_precision(Vec::new(), 4, 2).is_err());
+    assert!(dec.write_bin_with_precision(Vec::new(), 5, 1).is_err());
+    assert!(dec.write_bin_with_precision(Vec::new(), 0, 0).is_err());
+    assert!(dec.write_bin_with_precision(Vec::new(), 66, 2).is_err());
+
+    let err = super::Decimal::read_bin(&[0_u8; 8][..], 2, 4, false).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    let err = super::Decimal::read_bin(&[0_u8; 64][..], 66, 0, false).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+}
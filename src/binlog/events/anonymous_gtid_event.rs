@@ -22,7 +22,24 @@ use super::GtidEvent;
 /// Anonymous GTID event.
 #[repr(transparent)]
 #[derive(Debug, Clone, Eq, PartialEq, Hash)]
-pub struct AnonymousGtidEvent(pub GtidEvent);
+pub struct AnonymousGtidEvent(#[doc(hidden)] pub GtidEvent);
+
+impl AnonymousGtidEvent {
+    /// Creates a new instance.
+    pub fn new(event: GtidEvent) -> Self {
+        Self(event)
+    }
+
+    /// Returns the wrapped GTID event (its SID and GNO are meaningless).
+    pub fn gtid_event(&self) -> &GtidEvent {
+        &self.0
+    }
+
+    /// Unwraps the GTID event.
+    pub fn into_inner(self) -> GtidEvent {
+        self.0
+    }
+}
 
 impl<'de> MyDeserialize<'de> for AnonymousGtidEvent {
     const SIZE: Option<usize> = GtidEvent::SIZE;
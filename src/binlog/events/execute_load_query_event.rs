@@ -12,9 +12,10 @@ use saturating::Saturating as S;
 
 use crate::{
     binlog::{
-        consts::{BinlogVersion, EventType, LoadDuplicateHandling},
+        consts::{BinlogVersion, EventType, LoadDuplicateHandling, StatusVarKey},
         BinlogCtx, BinlogEvent, BinlogStruct,
     },
+    constants::SqlMode,
     io::ParseBuf,
     misc::raw::{
         bytes::{BareU8Bytes, EofBytes},
@@ -22,9 +23,10 @@ use crate::{
         Const, RawBytes, RawInt, Skip,
     },
     proto::{MyDeserialize, MySerialize},
+    value::Value,
 };
 
-use super::{BinlogEventHeader, StatusVars};
+use super::{BinlogEventHeader, StatusVarVal, StatusVars};
 
 /// Execute load query event.
 ///
@@ -65,14 +67,14 @@ impl<'a> ExecuteLoadQueryEvent<'a> {
         status_vars: impl Into<Cow<'a, [u8]>>,
         schema: impl Into<Cow<'a, [u8]>>,
     ) -> Self {
-        let status_vars = StatusVars(RawBytes::new(status_vars));
+        let status_vars = StatusVars::new(status_vars);
         let schema = RawBytes::new(schema);
         Self {
             thread_id: Default::default(),
             execution_time: Default::default(),
             schema_len: RawInt::new(schema.len() as u8),
             error_code: Default::default(),
-            status_vars_len: RawInt::new(status_vars.0.len() as u16),
+            status_vars_len: RawInt::new(status_vars.len() as u16),
             file_id: RawInt::new(file_id),
             start_pos: Default::default(),
             end_pos: Default::default(),
@@ -128,8 +130,8 @@ impl<'a> ExecuteLoadQueryEvent<'a> {
 
     /// Sets the `status_vars` value (max length is `u16::MAX).
     pub fn with_status_vars(mut self, status_vars: impl Into<Cow<'a, [u8]>>) -> Self {
-        self.status_vars = StatusVars(RawBytes::new(status_vars));
-        self.status_vars_len.0 = self.status_vars.0.len() as u16;
+        self.status_vars = StatusVars::new(status_vars);
+        self.status_vars_len.0 = self.status_vars.len() as u16;
         self
     }
 
@@ -203,7 +205,7 @@ impl<'a> ExecuteLoadQueryEvent<'a> {
     /// `status_vars` contains zero or more status variables. Each status variable consists of one
     /// byte identifying the variable stored, followed by the value of the variable.
     pub fn status_vars_raw(&'a self) -> &'a [u8] {
-        self.status_vars.0.as_bytes()
+        self.status_vars.as_bytes()
     }
 
     /// Returns an iterator over status variables.
@@ -235,6 +237,54 @@ impl<'a> ExecuteLoadQueryEvent<'a> {
         self.query.as_str()
     }
 
+    /// Returns the effective `LOAD DATA` statement, i.e. the query with the file name part
+    /// (`start_pos..end_pos`) substituted the same way a replica does it.
+    ///
+    /// `file_name` is the name of the file that holds the data loaded by preceding
+    /// `BEGIN_LOAD_QUERY_EVENT` and `APPEND_BLOCK_EVENT`s. It's quoted according to
+    /// the `NO_BACKSLASH_ESCAPES` sql mode stored in status vars. Use `local` to produce
+    /// a `LOAD DATA LOCAL INFILE` statement (i.e. if the file is on the client side).
+    ///
+    /// Returns an `InvalidData` error if `start_pos` and `end_pos` are out of the query bounds.
+    pub fn substituted_query(&self, file_name: &str, local: bool) -> io::Result<Vec<u8>> {
+        let query = self.query.as_bytes();
+        let start_pos = self.start_pos.0 as usize;
+        let end_pos = self.end_pos.0 as usize;
+        if start_pos > end_pos || end_pos > query.len() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "file name position is out of the query bounds",
+            ));
+        }
+
+        let no_backslash_escapes = match self.status_vars.get_status_var(StatusVarKey::SqlMode) {
+            Some(var) => matches!(
+                var.get_value(),
+                Ok(StatusVarVal::SqlMode(sql_mode))
+                    if sql_mode.get().contains(SqlMode::MODE_NO_BACKSLASH_ESCAPES)
+            ),
+            None => false,
+        };
+        let file_name = Value::from(file_name).as_sql(no_backslash_escapes);
+
+        let mut output = Vec::with_capacity(query.len() + file_name.len() + 32);
+        output.extend_from_slice(&query[..start_pos]);
+        if local {
+            output.extend_from_slice(b" LOCAL");
+        }
+        output.extend_from_slice(b" INFILE ");
+        output.extend_from_slice(file_name.as_bytes());
+        match self.dup_handling.0 {
+            LoadDuplicateHandling::LOAD_DUP_ERROR => (),
+            LoadDuplicateHandling::LOAD_DUP_IGNORE => output.extend_from_slice(b" IGNORE"),
+            LoadDuplicateHandling::LOAD_DUP_REPLACE => output.extend_from_slice(b" REPLACE"),
+        }
+        output.extend_from_slice(b" INTO");
+        output.extend_from_slice(&query[end_pos..]);
+
+        Ok(output)
+    }
+
     pub fn into_owned(self) -> ExecuteLoadQueryEvent<'static> {
         ExecuteLoadQueryEvent {
             thread_id: self.thread_id,
@@ -325,7 +375,7 @@ impl<'a> BinlogStruct<'a> for ExecuteLoadQueryEvent<'a> {
         len += S(4); // start_pos
         len += S(4); // end_pos
         len += S(1); // dup_handling_flags
-        len += S(min(self.status_vars.0.len(), u16::MAX as usize - 13)); // status_vars
+        len += S(min(self.status_vars.len(), u16::MAX as usize - 13)); // status_vars
         len += S(min(self.schema.0.len(), u8::MAX as usize)); // db_len
         len += S(1); // null-byte
         len += S(self.query.0.len());
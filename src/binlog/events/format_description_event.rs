@@ -6,12 +6,13 @@
 // option. All files in the project carrying such notice may not be copied,
 // modified, or distributed except according to those terms.
 
-use std::{borrow::Cow, cmp::min, io};
+use std::{borrow::Cow, cmp::min, convert::TryFrom, io};
 
 use saturating::Saturating as S;
 
 use crate::{
     binlog::{
+        clock::BinlogClock,
         consts::{BinlogVersion, EventType},
         BinlogCtx, BinlogEvent, BinlogStruct,
     },
@@ -123,6 +124,18 @@ impl<'a> FormatDescriptionEvent<'a> {
     pub const VIEW_CHANGE_HEADER_LEN: usize = 52;
     /// Length of a xa prepare event post-header.
     pub const XA_PREPARE_HEADER_LEN: usize = 0;
+    /// Length of a transaction payload event post-header.
+    pub const TRANSACTION_PAYLOAD_HEADER_LEN: usize = 0;
+    /// Length of a MariaDB annotate rows event post-header.
+    pub const MARIADB_ANNOTATE_ROWS_HEADER_LEN: usize = 0;
+    /// Length of a MariaDB binlog checkpoint event post-header.
+    pub const MARIADB_BINLOG_CHECKPOINT_HEADER_LEN: usize = 4;
+    /// Length of a MariaDB GTID event post-header.
+    pub const MARIADB_GTID_HEADER_LEN: usize = 19;
+    /// Length of a MariaDB GTID list event post-header.
+    pub const MARIADB_GTID_LIST_HEADER_LEN: usize = 4;
+    /// Length of a MariaDB start encryption event post-header.
+    pub const MARIADB_START_ENCRYPTION_HEADER_LEN: usize = 0;
 
     /// Creates new instance.
     pub fn new(binlog_version: BinlogVersion) -> Self {
@@ -154,7 +167,15 @@ impl<'a> FormatDescriptionEvent<'a> {
         self
     }
 
-    /// Defines the `server_version` field.
+    /// Defines the `create_timestamp` field using the given clock.
+    pub fn with_clock(self, clock: impl BinlogClock) -> Self {
+        self.with_create_timestamp(clock.now())
+    }
+
+    /// Defines the `event_type_header_lengths` field.
+    ///
+    /// Event types not covered by the given lengths will use the defaults
+    /// (see [`FormatDescriptionEvent::default_post_header_lengths`]).
     pub fn with_event_type_header_lengths(
         mut self,
         event_type_header_lengths: impl Into<Cow<'a, [u8]>>,
@@ -163,6 +184,29 @@ impl<'a> FormatDescriptionEvent<'a> {
         self
     }
 
+    /// Overrides the post-header length of the given event type.
+    ///
+    /// The `event_type_header_lengths` field is extended with the defaults, if it's too short
+    /// to contain the given event type. It's a no-op for `UNKNOWN_EVENT`.
+    pub fn with_event_type_header_length(mut self, event_type: EventType, len: u8) -> Self {
+        let index = match (event_type as usize).checked_sub(1) {
+            Some(index) => index,
+            None => return self,
+        };
+
+        let mut lengths = self.event_type_header_lengths.as_bytes().to_vec();
+        while lengths.len() <= index {
+            let len = EventType::try_from(lengths.len() as u8 + 1)
+                .map(|x| Self::default_post_header_length(self.binlog_version(), x))
+                .unwrap_or(0);
+            lengths.push(len);
+        }
+        lengths[index] = len;
+
+        self.event_type_header_lengths = RawBytes::new(lengths);
+        self
+    }
+
     /// Defines the `server_version` field.
     pub fn with_footer(mut self, footer: BinlogEventFooter) -> Self {
         self.footer = footer;
@@ -221,6 +265,10 @@ impl<'a> FormatDescriptionEvent<'a> {
     }
 
     /// Returns header length for the given event type, if defined.
+    ///
+    /// Falls back to the default length for this binlog version if the event type isn't covered
+    /// by the `event_type_header_lengths` field
+    /// (see [`FormatDescriptionEvent::default_post_header_lengths`]).
     pub fn get_event_type_header_length(&self, event_type: EventType) -> u8 {
         if event_type == EventType::UNKNOWN_EVENT {
             return 0;
@@ -230,49 +278,93 @@ impl<'a> FormatDescriptionEvent<'a> {
             .as_bytes()
             .get(usize::from(event_type as u8).saturating_sub(1))
             .copied()
-            .unwrap_or_else(|| match event_type {
-                EventType::UNKNOWN_EVENT => 0,
-                EventType::START_EVENT_V3 => Self::START_V3_HEADER_LEN,
-                EventType::QUERY_EVENT => Self::QUERY_HEADER_LEN,
-                EventType::STOP_EVENT => Self::STOP_HEADER_LEN,
-                EventType::ROTATE_EVENT => Self::ROTATE_HEADER_LEN,
-                EventType::INTVAR_EVENT => Self::INTVAR_HEADER_LEN,
-                EventType::LOAD_EVENT => 0,
-                EventType::SLAVE_EVENT => 0,
-                EventType::CREATE_FILE_EVENT => 0,
-                EventType::APPEND_BLOCK_EVENT => Self::APPEND_BLOCK_HEADER_LEN,
-                EventType::EXEC_LOAD_EVENT => 0,
-                EventType::DELETE_FILE_EVENT => Self::DELETE_FILE_HEADER_LEN,
-                EventType::NEW_LOAD_EVENT => 0,
-                EventType::RAND_EVENT => Self::RAND_HEADER_LEN,
-                EventType::USER_VAR_EVENT => Self::USER_VAR_HEADER_LEN,
-                EventType::FORMAT_DESCRIPTION_EVENT => Self::FORMAT_DESCRIPTION_HEADER_LEN,
-                EventType::XID_EVENT => Self::XID_HEADER_LEN,
-                EventType::BEGIN_LOAD_QUERY_EVENT => Self::BEGIN_LOAD_QUERY_HEADER_LEN,
-                EventType::EXECUTE_LOAD_QUERY_EVENT => Self::EXECUTE_LOAD_QUERY_HEADER_LEN,
-                EventType::TABLE_MAP_EVENT => Self::TABLE_MAP_HEADER_LEN,
-                EventType::PRE_GA_WRITE_ROWS_EVENT => 0,
-                EventType::PRE_GA_UPDATE_ROWS_EVENT => 0,
-                EventType::PRE_GA_DELETE_ROWS_EVENT => 0,
-                EventType::WRITE_ROWS_EVENT_V1 => Self::ROWS_HEADER_LEN_V1,
-                EventType::UPDATE_ROWS_EVENT_V1 => Self::ROWS_HEADER_LEN_V1,
-                EventType::DELETE_ROWS_EVENT_V1 => Self::ROWS_HEADER_LEN_V1,
-                EventType::INCIDENT_EVENT => Self::INCIDENT_HEADER_LEN,
-                EventType::HEARTBEAT_EVENT => 0,
-                EventType::IGNORABLE_EVENT => Self::IGNORABLE_HEADER_LEN,
-                EventType::ROWS_QUERY_EVENT => Self::IGNORABLE_HEADER_LEN,
-                EventType::WRITE_ROWS_EVENT => Self::ROWS_HEADER_LEN_V2,
-                EventType::UPDATE_ROWS_EVENT => Self::ROWS_HEADER_LEN_V2,
-                EventType::DELETE_ROWS_EVENT => Self::ROWS_HEADER_LEN_V2,
-                EventType::GTID_EVENT => Self::GTID_HEADER_LEN,
-                EventType::ANONYMOUS_GTID_EVENT => Self::GTID_HEADER_LEN,
-                EventType::PREVIOUS_GTIDS_EVENT => Self::IGNORABLE_HEADER_LEN,
-                EventType::TRANSACTION_CONTEXT_EVENT => Self::TRANSACTION_CONTEXT_HEADER_LEN,
-                EventType::VIEW_CHANGE_EVENT => Self::VIEW_CHANGE_HEADER_LEN,
-                EventType::XA_PREPARE_LOG_EVENT => Self::XA_PREPARE_HEADER_LEN,
-                EventType::PARTIAL_UPDATE_ROWS_EVENT => Self::ROWS_HEADER_LEN_V2,
-                EventType::ENUM_END_EVENT => 0,
-            } as u8)
+            .unwrap_or_else(|| Self::default_post_header_length(self.binlog_version(), event_type))
+    }
+
+    /// Returns the post-header lengths table a server writes into the format description event
+    /// of the given binlog version.
+    ///
+    /// It's indexed by Binlog Event Type - 1 (see [`Self::event_type_header_lengths`]).
+    /// Binlog versions 1 to 3 only define event types that precede
+    /// the `FORMAT_DESCRIPTION_EVENT`.
+    pub fn default_post_header_lengths(version: BinlogVersion) -> Vec<u8> {
+        let last_event_type = match version {
+            BinlogVersion::Version4 => EventType::ENUM_END_EVENT as u8 - 1,
+            _ => EventType::FORMAT_DESCRIPTION_EVENT as u8 - 1,
+        };
+        (1..=last_event_type)
+            .map(|x| {
+                EventType::try_from(x)
+                    .map(|x| Self::default_post_header_length(version, x))
+                    .unwrap_or(0)
+            })
+            .collect()
+    }
+
+    /// Returns the default post-header length of the given event type.
+    fn default_post_header_length(version: BinlogVersion, event_type: EventType) -> u8 {
+        (match event_type {
+            EventType::UNKNOWN_EVENT => 0,
+            EventType::START_EVENT_V3 => Self::START_V3_HEADER_LEN,
+            EventType::QUERY_EVENT if version != BinlogVersion::Version4 => {
+                Self::QUERY_HEADER_MINIMAL_LEN
+            }
+            EventType::QUERY_EVENT => Self::QUERY_HEADER_LEN,
+            EventType::STOP_EVENT => Self::STOP_HEADER_LEN,
+            EventType::ROTATE_EVENT if version == BinlogVersion::Version1 => 0,
+            EventType::ROTATE_EVENT => Self::ROTATE_HEADER_LEN,
+            EventType::INTVAR_EVENT => Self::INTVAR_HEADER_LEN,
+            EventType::LOAD_EVENT => 0,
+            EventType::SLAVE_EVENT => 0,
+            EventType::CREATE_FILE_EVENT => 0,
+            EventType::APPEND_BLOCK_EVENT => Self::APPEND_BLOCK_HEADER_LEN,
+            EventType::EXEC_LOAD_EVENT => 0,
+            EventType::DELETE_FILE_EVENT => Self::DELETE_FILE_HEADER_LEN,
+            EventType::NEW_LOAD_EVENT => 0,
+            EventType::RAND_EVENT => Self::RAND_HEADER_LEN,
+            EventType::USER_VAR_EVENT => Self::USER_VAR_HEADER_LEN,
+            EventType::FORMAT_DESCRIPTION_EVENT => Self::FORMAT_DESCRIPTION_HEADER_LEN,
+            EventType::XID_EVENT => Self::XID_HEADER_LEN,
+            EventType::BEGIN_LOAD_QUERY_EVENT => Self::BEGIN_LOAD_QUERY_HEADER_LEN,
+            EventType::EXECUTE_LOAD_QUERY_EVENT => Self::EXECUTE_LOAD_QUERY_HEADER_LEN,
+            EventType::TABLE_MAP_EVENT => Self::TABLE_MAP_HEADER_LEN,
+            EventType::PRE_GA_WRITE_ROWS_EVENT => 0,
+            EventType::PRE_GA_UPDATE_ROWS_EVENT => 0,
+            EventType::PRE_GA_DELETE_ROWS_EVENT => 0,
+            EventType::WRITE_ROWS_EVENT_V1 => Self::ROWS_HEADER_LEN_V1,
+            EventType::UPDATE_ROWS_EVENT_V1 => Self::ROWS_HEADER_LEN_V1,
+            EventType::DELETE_ROWS_EVENT_V1 => Self::ROWS_HEADER_LEN_V1,
+            EventType::INCIDENT_EVENT => Self::INCIDENT_HEADER_LEN,
+            EventType::HEARTBEAT_EVENT => 0,
+            EventType::IGNORABLE_EVENT => Self::IGNORABLE_HEADER_LEN,
+            EventType::ROWS_QUERY_EVENT => Self::IGNORABLE_HEADER_LEN,
+            EventType::WRITE_ROWS_EVENT => Self::ROWS_HEADER_LEN_V2,
+            EventType::UPDATE_ROWS_EVENT => Self::ROWS_HEADER_LEN_V2,
+            EventType::DELETE_ROWS_EVENT => Self::ROWS_HEADER_LEN_V2,
+            EventType::GTID_EVENT => Self::GTID_HEADER_LEN,
+            EventType::ANONYMOUS_GTID_EVENT => Self::GTID_HEADER_LEN,
+            EventType::PREVIOUS_GTIDS_EVENT => Self::IGNORABLE_HEADER_LEN,
+            EventType::TRANSACTION_CONTEXT_EVENT => Self::TRANSACTION_CONTEXT_HEADER_LEN,
+            EventType::VIEW_CHANGE_EVENT => Self::VIEW_CHANGE_HEADER_LEN,
+            EventType::XA_PREPARE_LOG_EVENT => Self::XA_PREPARE_HEADER_LEN,
+            EventType::PARTIAL_UPDATE_ROWS_EVENT => Self::ROWS_HEADER_LEN_V2,
+            EventType::TRANSACTION_PAYLOAD_EVENT => Self::TRANSACTION_PAYLOAD_HEADER_LEN,
+            EventType::ENUM_END_EVENT => 0,
+            EventType::MARIADB_ANNOTATE_ROWS_EVENT => Self::MARIADB_ANNOTATE_ROWS_HEADER_LEN,
+            EventType::MARIADB_BINLOG_CHECKPOINT_EVENT => {
+                Self::MARIADB_BINLOG_CHECKPOINT_HEADER_LEN
+            }
+            EventType::MARIADB_GTID_EVENT => Self::MARIADB_GTID_HEADER_LEN,
+            EventType::MARIADB_GTID_LIST_EVENT => Self::MARIADB_GTID_LIST_HEADER_LEN,
+            EventType::MARIADB_START_ENCRYPTION_EVENT => Self::MARIADB_START_ENCRYPTION_HEADER_LEN,
+            EventType::MARIADB_QUERY_COMPRESSED_EVENT => Self::QUERY_HEADER_LEN,
+            EventType::MARIADB_WRITE_ROWS_COMPRESSED_EVENT_V1 => Self::ROWS_HEADER_LEN_V1,
+            EventType::MARIADB_UPDATE_ROWS_COMPRESSED_EVENT_V1 => Self::ROWS_HEADER_LEN_V1,
+            EventType::MARIADB_DELETE_ROWS_COMPRESSED_EVENT_V1 => Self::ROWS_HEADER_LEN_V1,
+            EventType::MARIADB_WRITE_ROWS_COMPRESSED_EVENT => Self::ROWS_HEADER_LEN_V2,
+            EventType::MARIADB_UPDATE_ROWS_COMPRESSED_EVENT => Self::ROWS_HEADER_LEN_V2,
+            EventType::MARIADB_DELETE_ROWS_COMPRESSED_EVENT => Self::ROWS_HEADER_LEN_V2,
+        } as u8)
     }
 
     /// Returns a `'static` version of `self`.
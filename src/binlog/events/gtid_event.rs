@@ -6,12 +6,13 @@
 // option. All files in the project carrying such notice may not be copied,
 // modified, or distributed except according to those terms.
 
-use std::{cmp::min, io};
+use std::{cmp::min, fmt, io};
 
 use saturating::Saturating as S;
 
 use crate::{
     binlog::{
+        clock::BinlogClock,
         consts::{BinlogVersion, EventType, Gno, GtidFlags},
         BinlogCtx, BinlogEvent, BinlogStruct,
     },
@@ -192,6 +193,14 @@ impl GtidEvent {
         self.immediate_commit_timestamp.0
     }
 
+    /// Sets both `immediate_commit_timestamp` and `original_commit_timestamp`
+    /// to the current time of the given clock.
+    pub fn with_clock(self, clock: impl BinlogClock) -> Self {
+        let now = clock.now_micros();
+        self.with_immediate_commit_timestamp(now)
+            .with_original_commit_timestamp(now)
+    }
+
     /// Sets the `original_commit_timestamp` value.
     pub fn with_original_commit_timestamp(mut self, original_commit_timestamp: u64) -> Self {
         self.original_commit_timestamp = RawInt::new(original_commit_timestamp);
@@ -219,30 +228,118 @@ impl GtidEvent {
     }
 
     /// Sets the `original_server_version` value.
-    pub fn with_original_server_version(mut self, original_server_version: u32) -> Self {
-        self.original_server_version = RawInt::new(original_server_version);
+    pub fn with_original_server_version(
+        mut self,
+        original_server_version: impl Into<ServerVersion>,
+    ) -> Self {
+        self.original_server_version = RawInt::new(original_server_version.into().raw());
         self
     }
 
+    /// Returns the raw `original_server_version` value.
+    pub fn original_server_version_raw(&self) -> u32 {
+        self.original_server_version.0
+    }
+
     /// Returns the `original_server_version` value.
     ///
     /// `original_server_version` is the version of the server where the transaction was originally
-    /// executed.
-    pub fn original_server_version(&self) -> u32 {
-        self.original_server_version.0
+    /// executed ([`ServerVersion::UNDEFINED`] for servers prior to 8.0.14).
+    pub fn original_server_version(&self) -> ServerVersion {
+        ServerVersion::from_raw(self.original_server_version.0)
     }
 
     /// Sets the `immediate_server_version` value.
-    pub fn with_immediate_server_version(mut self, immediate_server_version: u32) -> Self {
-        self.immediate_server_version = RawInt::new(immediate_server_version);
+    pub fn with_immediate_server_version(
+        mut self,
+        immediate_server_version: impl Into<ServerVersion>,
+    ) -> Self {
+        self.immediate_server_version = RawInt::new(immediate_server_version.into().raw());
         self
     }
 
+    /// Returns the raw `immediate_server_version` value.
+    pub fn immediate_server_version_raw(&self) -> u32 {
+        self.immediate_server_version.0
+    }
+
     /// Returns the `immediate_server_version` value.
     ///
-    /// `immediate_server_version` is the server version of the immediate server.
-    pub fn immediate_server_version(&self) -> u32 {
-        self.immediate_server_version.0
+    /// `immediate_server_version` is the server version of the immediate server
+    /// ([`ServerVersion::UNDEFINED`] for servers prior to 8.0.14).
+    pub fn immediate_server_version(&self) -> ServerVersion {
+        ServerVersion::from_raw(self.immediate_server_version.0)
+    }
+}
+
+/// Server version as written to a GTID event (`major * 10000 + minor * 100 + patch`).
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct ServerVersion(u32);
+
+impl ServerVersion {
+    /// Version of a server that doesn't write server versions (prior to 8.0.14).
+    pub const UNDEFINED: ServerVersion = ServerVersion(GtidEvent::UNDEFINED_SERVER_VERSION);
+
+    /// Creates a new server version.
+    ///
+    /// `minor` and `patch` are expected to be less than `100`.
+    pub const fn new(major: u8, minor: u8, patch: u8) -> Self {
+        Self(major as u32 * 10_000 + minor as u32 * 100 + patch as u32)
+    }
+
+    /// Wraps the raw value.
+    pub const fn from_raw(raw: u32) -> Self {
+        Self(raw)
+    }
+
+    /// Returns the raw value.
+    pub const fn raw(&self) -> u32 {
+        self.0
+    }
+
+    /// Returns `false` if the version is [`ServerVersion::UNDEFINED`].
+    pub fn is_defined(&self) -> bool {
+        *self != Self::UNDEFINED
+    }
+
+    /// Returns `(major, minor, patch)` triple, or `None` if the version isn't defined.
+    pub fn split(&self) -> Option<(u8, u8, u8)> {
+        if self.is_defined() {
+            Some((
+                (self.0 / 10_000) as u8,
+                (self.0 / 100 % 100) as u8,
+                (self.0 % 100) as u8,
+            ))
+        } else {
+            None
+        }
+    }
+
+    /// Returns `true` if the server is known to support binlog transaction compression
+    /// (i.e. it may write `TRANSACTION_PAYLOAD_EVENT`, MySql 8.0.20+).
+    pub fn supports_transaction_compression(&self) -> bool {
+        self.is_defined() && *self >= Self::new(8, 0, 20)
+    }
+}
+
+impl From<u32> for ServerVersion {
+    fn from(raw: u32) -> Self {
+        Self::from_raw(raw)
+    }
+}
+
+impl From<(u8, u8, u8)> for ServerVersion {
+    fn from((major, minor, patch): (u8, u8, u8)) -> Self {
+        Self::new(major, minor, patch)
+    }
+}
+
+impl fmt::Display for ServerVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.split() {
+            Some((major, minor, patch)) => write!(f, "{}.{}.{}", major, minor, patch),
+            None => f.write_str("undefined"),
+        }
     }
 }
 
@@ -364,6 +461,10 @@ impl<'a> BinlogStruct<'a> for GtidEvent {
         len += S(1); // flags
         len += S(Self::ENCODED_SID_LENGTH); // sid
         len += S(8); // gno
+        if self.lc_typecode.is_none() {
+            // MySql 5.6 does not write TS_TYPE and following fields
+            return len.0;
+        }
         len += S(1); // lc_typecode
         len += S(8); // last_committed
         len += S(8); // sequence_number
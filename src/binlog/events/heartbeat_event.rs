@@ -0,0 +1,87 @@
+// Copyright (c) 2023 Anatoly Ikorsky
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+use std::{borrow::Cow, cmp::min, io};
+
+use crate::{
+    binlog::{
+        consts::{BinlogVersion, EventType},
+        BinlogCtx, BinlogEvent, BinlogStruct,
+    },
+    io::ParseBuf,
+    misc::raw::{bytes::EofBytes, RawBytes},
+    proto::{MyDeserialize, MySerialize},
+};
+
+use super::BinlogEventHeader;
+
+/// Heartbeat event.
+///
+/// Sent by a master to a replica to let it know that the master is still alive.
+/// Never written to a binlog file. The position of the master is stored in the `log_pos`
+/// field of the event header.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct HeartbeatEvent<'a> {
+    /// Name of the current binlog on the master.
+    log_ident: RawBytes<'a, EofBytes>,
+}
+
+impl<'a> HeartbeatEvent<'a> {
+    /// Creates a new instance.
+    pub fn new(log_ident: impl Into<Cow<'a, [u8]>>) -> Self {
+        Self {
+            log_ident: RawBytes::new(log_ident),
+        }
+    }
+
+    /// Returns the raw name of the current binlog on the master.
+    pub fn log_ident_raw(&'a self) -> &'a [u8] {
+        self.log_ident.as_bytes()
+    }
+
+    /// Returns the name of the current binlog on the master as a string (lossy converted).
+    pub fn log_ident(&'a self) -> Cow<'a, str> {
+        self.log_ident.as_str()
+    }
+
+    pub fn into_owned(self) -> HeartbeatEvent<'static> {
+        HeartbeatEvent {
+            log_ident: self.log_ident.into_owned(),
+        }
+    }
+}
+
+impl<'de> MyDeserialize<'de> for HeartbeatEvent<'de> {
+    const SIZE: Option<usize> = None;
+    type Ctx = BinlogCtx<'de>;
+
+    fn deserialize(_ctx: Self::Ctx, buf: &mut ParseBuf<'de>) -> io::Result<Self> {
+        Ok(Self {
+            log_ident: buf.parse(())?,
+        })
+    }
+}
+
+impl MySerialize for HeartbeatEvent<'_> {
+    fn serialize(&self, buf: &mut Vec<u8>) {
+        self.log_ident.serialize(&mut *buf);
+    }
+}
+
+impl<'a> BinlogEvent<'a> for HeartbeatEvent<'a> {
+    const EVENT_TYPE: EventType = EventType::HEARTBEAT_EVENT;
+}
+
+impl<'a> BinlogStruct<'a> for HeartbeatEvent<'a> {
+    fn len(&self, _version: BinlogVersion) -> usize {
+        min(
+            self.log_ident.0.len(),
+            u32::MAX as usize - BinlogEventHeader::LEN,
+        )
+    }
+}
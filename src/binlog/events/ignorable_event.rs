@@ -0,0 +1,80 @@
+// Copyright (c) 2023 Anatoly Ikorsky
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+use std::{borrow::Cow, cmp::min, io};
+
+use crate::{
+    binlog::{
+        consts::{BinlogVersion, EventType},
+        BinlogCtx, BinlogEvent, BinlogStruct,
+    },
+    io::ParseBuf,
+    misc::raw::{bytes::EofBytes, RawBytes},
+    proto::{MyDeserialize, MySerialize},
+};
+
+use super::BinlogEventHeader;
+
+/// Ignorable event.
+///
+/// Base type for events that a replica may safely ignore if it doesn't know them
+/// (see `LOG_EVENT_IGNORABLE_F`). The payload is kept as is.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct IgnorableEvent<'a> {
+    data: RawBytes<'a, EofBytes>,
+}
+
+impl<'a> IgnorableEvent<'a> {
+    /// Creates a new instance.
+    pub fn new(data: impl Into<Cow<'a, [u8]>>) -> Self {
+        Self {
+            data: RawBytes::new(data),
+        }
+    }
+
+    /// Returns the raw event payload.
+    pub fn data(&'a self) -> &'a [u8] {
+        self.data.as_bytes()
+    }
+
+    pub fn into_owned(self) -> IgnorableEvent<'static> {
+        IgnorableEvent {
+            data: self.data.into_owned(),
+        }
+    }
+}
+
+impl<'de> MyDeserialize<'de> for IgnorableEvent<'de> {
+    const SIZE: Option<usize> = None;
+    type Ctx = BinlogCtx<'de>;
+
+    fn deserialize(_ctx: Self::Ctx, buf: &mut ParseBuf<'de>) -> io::Result<Self> {
+        Ok(Self {
+            data: buf.parse(())?,
+        })
+    }
+}
+
+impl MySerialize for IgnorableEvent<'_> {
+    fn serialize(&self, buf: &mut Vec<u8>) {
+        self.data.serialize(&mut *buf);
+    }
+}
+
+impl<'a> BinlogEvent<'a> for IgnorableEvent<'a> {
+    const EVENT_TYPE: EventType = EventType::IGNORABLE_EVENT;
+}
+
+impl<'a> BinlogStruct<'a> for IgnorableEvent<'a> {
+    fn len(&self, _version: BinlogVersion) -> usize {
+        min(
+            self.data.0.len(),
+            u32::MAX as usize - BinlogEventHeader::LEN,
+        )
+    }
+}
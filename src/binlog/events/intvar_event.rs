@@ -41,6 +41,18 @@ impl IntvarEvent {
         }
     }
 
+    /// Creates a new `LAST_INSERT_ID_EVENT` instance, i.e. the value to use
+    /// for the `LAST_INSERT_ID()` function in the next statement.
+    pub fn last_insert_id(value: u64) -> Self {
+        Self::new(IntvarEventType::LAST_INSERT_ID_EVENT, value)
+    }
+
+    /// Creates a new `INSERT_ID_EVENT` instance, i.e. the value to use
+    /// for an `AUTO_INCREMENT` column in the next statement.
+    pub fn insert_id(value: u64) -> Self {
+        Self::new(IntvarEventType::INSERT_ID_EVENT, value)
+    }
+
     /// Returns the `subtype` value.
     ///
     /// `subtype` is a one byte identifying the type of variable stored.
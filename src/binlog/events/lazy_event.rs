@@ -0,0 +1,77 @@
+// Copyright (c) 2023 Anatoly Ikorsky
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+use std::{cell::OnceCell, io};
+
+use super::{BinlogEventHeader, Event, EventData};
+
+/// Binlog event whose data is parsed on first access.
+///
+/// The parsed data is cached, so filter pipelines may inspect the header of every event
+/// and only pay parsing costs for events they actually use.
+#[derive(Debug, Clone)]
+pub struct LazyEvent {
+    event: Event<'static>,
+    data: OnceCell<Option<EventData<'static>>>,
+}
+
+impl LazyEvent {
+    /// Creates a new instance.
+    pub fn new(event: Event<'static>) -> Self {
+        Self {
+            event,
+            data: OnceCell::new(),
+        }
+    }
+
+    /// Returns the underlying event.
+    pub fn event(&self) -> &Event<'static> {
+        &self.event
+    }
+
+    /// Returns the event header.
+    pub fn header(&self) -> BinlogEventHeader {
+        self.event.header()
+    }
+
+    /// Returns `true` if the event data is already parsed.
+    pub fn is_parsed(&self) -> bool {
+        self.data.get().is_some()
+    }
+
+    /// Returns event data, parsing it on the first call (see [`Event::read_data`]).
+    ///
+    /// Parsing errors aren't cached, so the next call will try to parse the data again.
+    pub fn data(&self) -> io::Result<Option<&EventData<'static>>> {
+        if let Some(data) = self.data.get() {
+            return Ok(data.as_ref());
+        }
+
+        let data = self.event.read_data()?.map(EventData::into_owned);
+        Ok(self.data.get_or_init(|| data).as_ref())
+    }
+
+    /// Returns event data, parsing it if it isn't parsed yet.
+    pub fn into_data(self) -> io::Result<Option<EventData<'static>>> {
+        match self.data.into_inner() {
+            Some(data) => Ok(data),
+            None => Ok(self.event.read_data()?.map(EventData::into_owned)),
+        }
+    }
+
+    /// Returns the underlying event.
+    pub fn into_event(self) -> Event<'static> {
+        self.event
+    }
+}
+
+impl From<Event<'static>> for LazyEvent {
+    fn from(event: Event<'static>) -> Self {
+        Self::new(event)
+    }
+}
@@ -0,0 +1,597 @@
+// Copyright (c) 2023 Anatoly Ikorsky
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+use std::{borrow::Cow, cmp::min, io};
+
+use saturating::Saturating as S;
+
+use crate::{
+    binlog::{
+        consts::{BinlogVersion, EventType, LoadEmptyFlags, LoadOptFlags},
+        BinlogCtx, BinlogEvent, BinlogStruct,
+    },
+    io::ParseBuf,
+    misc::raw::{
+        bytes::{BareU8Bytes, EofBytes, U8Bytes},
+        int::*,
+        RawBytes, RawFlags, RawInt, Skip,
+    },
+    proto::{MyDeserialize, MySerialize},
+};
+
+use super::BinlogEventHeader;
+
+/// Field and line options of a `LOAD DATA` statement (`sql_ex`).
+///
+/// `LOAD_EVENT` stores every terminator as a single byte (the rest is truncated on write),
+/// whereas `NEW_LOAD_EVENT` stores them as length-prefixed strings.
+#[derive(Debug, Clone, Eq, PartialEq, Hash, Default)]
+pub struct SqlExchange<'a> {
+    field_term: RawBytes<'a, U8Bytes>,
+    enclosed: RawBytes<'a, U8Bytes>,
+    line_term: RawBytes<'a, U8Bytes>,
+    line_start: RawBytes<'a, U8Bytes>,
+    escaped: RawBytes<'a, U8Bytes>,
+    opt_flags: RawFlags<LoadOptFlags, u8>,
+}
+
+impl<'a> SqlExchange<'a> {
+    /// Length of the old (`LOAD_EVENT`) format.
+    const OLD_LEN: usize = 7;
+
+    /// Empty flags of terminators (in the order of [`Self::terms`]).
+    const EMPTY_FLAGS: [LoadEmptyFlags; 5] = [
+        LoadEmptyFlags::FIELD_TERM_EMPTY,
+        LoadEmptyFlags::ENCLOSED_EMPTY,
+        LoadEmptyFlags::LINE_TERM_EMPTY,
+        LoadEmptyFlags::LINE_START_EMPTY,
+        LoadEmptyFlags::ESCAPED_EMPTY,
+    ];
+
+    /// Creates a new instance with all terminators empty.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the `field_term` value (`FIELDS TERMINATED BY`, max length is `u8::MAX`).
+    pub fn with_field_term(mut self, field_term: impl Into<Cow<'a, [u8]>>) -> Self {
+        self.field_term = RawBytes::new(field_term);
+        self
+    }
+
+    /// Sets the `enclosed` value (`FIELDS ENCLOSED BY`, max length is `u8::MAX`).
+    pub fn with_enclosed(mut self, enclosed: impl Into<Cow<'a, [u8]>>) -> Self {
+        self.enclosed = RawBytes::new(enclosed);
+        self
+    }
+
+    /// Sets the `line_term` value (`LINES TERMINATED BY`, max length is `u8::MAX`).
+    pub fn with_line_term(mut self, line_term: impl Into<Cow<'a, [u8]>>) -> Self {
+        self.line_term = RawBytes::new(line_term);
+        self
+    }
+
+    /// Sets the `line_start` value (`LINES STARTING BY`, max length is `u8::MAX`).
+    pub fn with_line_start(mut self, line_start: impl Into<Cow<'a, [u8]>>) -> Self {
+        self.line_start = RawBytes::new(line_start);
+        self
+    }
+
+    /// Sets the `escaped` value (`FIELDS ESCAPED BY`, max length is `u8::MAX`).
+    pub fn with_escaped(mut self, escaped: impl Into<Cow<'a, [u8]>>) -> Self {
+        self.escaped = RawBytes::new(escaped);
+        self
+    }
+
+    /// Sets the `opt_flags` value.
+    pub fn with_opt_flags(mut self, opt_flags: LoadOptFlags) -> Self {
+        self.opt_flags = RawFlags::new(opt_flags.bits());
+        self
+    }
+
+    /// Returns the `field_term` value (`FIELDS TERMINATED BY`).
+    pub fn field_term_raw(&'a self) -> &'a [u8] {
+        self.field_term.as_bytes()
+    }
+
+    /// Returns the `field_term` value as a string (lossy converted).
+    pub fn field_term(&'a self) -> Cow<'a, str> {
+        self.field_term.as_str()
+    }
+
+    /// Returns the `enclosed` value (`FIELDS ENCLOSED BY`).
+    pub fn enclosed_raw(&'a self) -> &'a [u8] {
+        self.enclosed.as_bytes()
+    }
+
+    /// Returns the `enclosed` value as a string (lossy converted).
+    pub fn enclosed(&'a self) -> Cow<'a, str> {
+        self.enclosed.as_str()
+    }
+
+    /// Returns the `line_term` value (`LINES TERMINATED BY`).
+    pub fn line_term_raw(&'a self) -> &'a [u8] {
+        self.line_term.as_bytes()
+    }
+
+    /// Returns the `line_term` value as a string (lossy converted).
+    pub fn line_term(&'a self) -> Cow<'a, str> {
+        self.line_term.as_str()
+    }
+
+    /// Returns the `line_start` value (`LINES STARTING BY`).
+    pub fn line_start_raw(&'a self) -> &'a [u8] {
+        self.line_start.as_bytes()
+    }
+
+    /// Returns the `line_start` value as a string (lossy converted).
+    pub fn line_start(&'a self) -> Cow<'a, str> {
+        self.line_start.as_str()
+    }
+
+    /// Returns the `escaped` value (`FIELDS ESCAPED BY`).
+    pub fn escaped_raw(&'a self) -> &'a [u8] {
+        self.escaped.as_bytes()
+    }
+
+    /// Returns the `escaped` value as a string (lossy converted).
+    pub fn escaped(&'a self) -> Cow<'a, str> {
+        self.escaped.as_str()
+    }
+
+    /// Returns the raw `opt_flags` value.
+    pub fn opt_flags_raw(&self) -> u8 {
+        self.opt_flags.bits()
+    }
+
+    /// Returns the `opt_flags` value (unknown bits are truncated).
+    pub fn opt_flags(&self) -> LoadOptFlags {
+        self.opt_flags.get()
+    }
+
+    pub fn into_owned(self) -> SqlExchange<'static> {
+        SqlExchange {
+            field_term: self.field_term.into_owned(),
+            enclosed: self.enclosed.into_owned(),
+            line_term: self.line_term.into_owned(),
+            line_start: self.line_start.into_owned(),
+            escaped: self.escaped.into_owned(),
+            opt_flags: self.opt_flags,
+        }
+    }
+
+    fn terms(&self) -> [&RawBytes<'a, U8Bytes>; 5] {
+        [
+            &self.field_term,
+            &self.enclosed,
+            &self.line_term,
+            &self.line_start,
+            &self.escaped,
+        ]
+    }
+
+    /// Parses the old format (a byte per terminator followed by `opt_flags` and `empty_flags`).
+    fn deserialize_old<'de>(buf: &mut ParseBuf<'de>) -> io::Result<SqlExchange<'de>> {
+        let raw = buf.parse::<ParseBuf>(Self::OLD_LEN)?.0;
+        let empty_flags = LoadEmptyFlags::from_bits_truncate(raw[6]);
+        let term = |i: usize| -> RawBytes<'de, U8Bytes> {
+            if empty_flags.contains(Self::EMPTY_FLAGS[i]) {
+                RawBytes::default()
+            } else {
+                RawBytes::new(&raw[i..i + 1])
+            }
+        };
+
+        Ok(SqlExchange {
+            field_term: term(0),
+            enclosed: term(1),
+            line_term: term(2),
+            line_start: term(3),
+            escaped: term(4),
+            opt_flags: RawFlags::new(raw[5]),
+        })
+    }
+
+    /// Parses the new format (length-prefixed terminators followed by `opt_flags`).
+    fn deserialize_new<'de>(buf: &mut ParseBuf<'de>) -> io::Result<SqlExchange<'de>> {
+        Ok(SqlExchange {
+            field_term: buf.parse(())?,
+            enclosed: buf.parse(())?,
+            line_term: buf.parse(())?,
+            line_start: buf.parse(())?,
+            escaped: buf.parse(())?,
+            opt_flags: buf.parse(())?,
+        })
+    }
+
+    fn serialize_old(&self, buf: &mut Vec<u8>) {
+        let mut empty_flags = LoadEmptyFlags::empty();
+        for (term, flag) in self.terms().iter().zip(Self::EMPTY_FLAGS) {
+            match term.as_bytes().first() {
+                Some(x) => buf.push(*x),
+                None => {
+                    empty_flags |= flag;
+                    buf.push(0);
+                }
+            }
+        }
+        self.opt_flags.serialize(&mut *buf);
+        buf.push(empty_flags.bits());
+    }
+
+    fn serialize_new(&self, buf: &mut Vec<u8>) {
+        for term in self.terms() {
+            term.serialize(&mut *buf);
+        }
+        self.opt_flags.serialize(&mut *buf);
+    }
+
+    fn len(&self, new_format: bool) -> usize {
+        if new_format {
+            let terms = self
+                .terms()
+                .iter()
+                .map(|x| 1 + min(x.len(), u8::MAX as usize))
+                .sum::<usize>();
+            terms + 1
+        } else {
+            Self::OLD_LEN
+        }
+    }
+}
+
+/// Common part of `LOAD_EVENT` and `NEW_LOAD_EVENT`.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+struct LoadEventData<'a> {
+    // post-header
+    thread_id: RawInt<LeU32>,
+    execution_time: RawInt<LeU32>,
+    skip_lines: RawInt<LeU32>,
+    table_name_len: RawInt<u8>,
+    schema_len: RawInt<u8>,
+    num_fields: RawInt<LeU32>,
+
+    // payload
+    sql_ex: SqlExchange<'a>,
+    field_names: Vec<RawBytes<'a, BareU8Bytes>>,
+    table_name: RawBytes<'a, BareU8Bytes>,
+    schema: RawBytes<'a, BareU8Bytes>,
+    file_name: RawBytes<'a, EofBytes>,
+}
+
+impl<'a> LoadEventData<'a> {
+    /// Length of the post-header.
+    const POST_HEADER_LEN: usize = 18;
+
+    fn new(
+        table_name: impl Into<Cow<'a, [u8]>>,
+        schema: impl Into<Cow<'a, [u8]>>,
+        file_name: impl Into<Cow<'a, [u8]>>,
+    ) -> Self {
+        let table_name = RawBytes::new(table_name);
+        let schema = RawBytes::new(schema);
+        Self {
+            thread_id: Default::default(),
+            execution_time: Default::default(),
+            skip_lines: Default::default(),
+            table_name_len: RawInt::new(table_name.len() as u8),
+            schema_len: RawInt::new(schema.len() as u8),
+            num_fields: Default::default(),
+            sql_ex: Default::default(),
+            field_names: Vec::new(),
+            table_name,
+            schema,
+            file_name: RawBytes::new(file_name),
+        }
+    }
+
+    fn into_owned(self) -> LoadEventData<'static> {
+        LoadEventData {
+            thread_id: self.thread_id,
+            execution_time: self.execution_time,
+            skip_lines: self.skip_lines,
+            table_name_len: self.table_name_len,
+            schema_len: self.schema_len,
+            num_fields: self.num_fields,
+            sql_ex: self.sql_ex.into_owned(),
+            field_names: self
+                .field_names
+                .into_iter()
+                .map(RawBytes::into_owned)
+                .collect(),
+            table_name: self.table_name.into_owned(),
+            schema: self.schema.into_owned(),
+            file_name: self.file_name.into_owned(),
+        }
+    }
+
+    fn deserialize(buf: &mut ParseBuf<'a>, new_format: bool) -> io::Result<Self> {
+        let mut sbuf: ParseBuf = buf.parse(Self::POST_HEADER_LEN)?;
+        let thread_id = sbuf.parse_unchecked(())?;
+        let execution_time = sbuf.parse_unchecked(())?;
+        let skip_lines = sbuf.parse_unchecked(())?;
+        let table_name_len: RawInt<u8> = sbuf.parse_unchecked(())?;
+        let schema_len: RawInt<u8> = sbuf.parse_unchecked(())?;
+        let num_fields: RawInt<LeU32> = sbuf.parse_unchecked(())?;
+
+        let sql_ex = if new_format {
+            SqlExchange::deserialize_new(&mut *buf)?
+        } else {
+            SqlExchange::deserialize_old(&mut *buf)?
+        };
+
+        let field_lens: ParseBuf = buf.parse(*num_fields as usize)?;
+        let mut field_names = Vec::with_capacity(field_lens.0.len());
+        for len in field_lens.0 {
+            field_names.push(buf.parse(*len as usize)?);
+            buf.parse::<Skip<1>>(())?;
+        }
+
+        let table_name = buf.parse(*table_name_len as usize)?;
+        buf.parse::<Skip<1>>(())?;
+        let schema = buf.parse(*schema_len as usize)?;
+        buf.parse::<Skip<1>>(())?;
+        let file_name = buf.parse(())?;
+
+        Ok(Self {
+            thread_id,
+            execution_time,
+            skip_lines,
+            table_name_len,
+            schema_len,
+            num_fields,
+            sql_ex,
+            field_names,
+            table_name,
+            schema,
+            file_name,
+        })
+    }
+
+    fn serialize(&self, buf: &mut Vec<u8>, new_format: bool) {
+        self.thread_id.serialize(&mut *buf);
+        self.execution_time.serialize(&mut *buf);
+        self.skip_lines.serialize(&mut *buf);
+        self.table_name_len.serialize(&mut *buf);
+        self.schema_len.serialize(&mut *buf);
+        self.num_fields.serialize(&mut *buf);
+
+        if new_format {
+            self.sql_ex.serialize_new(&mut *buf);
+        } else {
+            self.sql_ex.serialize_old(&mut *buf);
+        }
+
+        for name in &self.field_names {
+            buf.push(min(name.len(), u8::MAX as usize) as u8);
+        }
+        for name in &self.field_names {
+            name.serialize(&mut *buf);
+            buf.push(0);
+        }
+        self.table_name.serialize(&mut *buf);
+        buf.push(0);
+        self.schema.serialize(&mut *buf);
+        buf.push(0);
+        self.file_name.serialize(&mut *buf);
+    }
+
+    fn len(&self, new_format: bool) -> usize {
+        let mut len = S(0);
+
+        len += S(Self::POST_HEADER_LEN);
+        len += S(self.sql_ex.len(new_format));
+        for name in &self.field_names {
+            len += S(1); // field_len
+            len += S(min(name.len(), u8::MAX as usize));
+            len += S(1); // null-byte
+        }
+        len += S(min(self.table_name.len(), u8::MAX as usize));
+        len += S(1); // null-byte
+        len += S(min(self.schema.len(), u8::MAX as usize));
+        len += S(1); // null-byte
+        len += S(self.file_name.len());
+
+        min(len.0, u32::MAX as usize - BinlogEventHeader::LEN)
+    }
+}
+
+macro_rules! load_event {
+    ($(#[$meta:meta])* $name:ident, $event_type:ident, $new_format:expr) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, Eq, PartialEq, Hash)]
+        #[repr(transparent)]
+        pub struct $name<'a>(LoadEventData<'a>);
+
+        impl<'a> $name<'a> {
+            /// Creates a new instance.
+            pub fn new(
+                table_name: impl Into<Cow<'a, [u8]>>,
+                schema: impl Into<Cow<'a, [u8]>>,
+                file_name: impl Into<Cow<'a, [u8]>>,
+            ) -> Self {
+                Self(LoadEventData::new(table_name, schema, file_name))
+            }
+
+            /// Sets the `thread_id` value.
+            pub fn with_thread_id(mut self, thread_id: u32) -> Self {
+                self.0.thread_id = RawInt::new(thread_id);
+                self
+            }
+
+            /// Sets the `execution_time` value.
+            pub fn with_execution_time(mut self, execution_time: u32) -> Self {
+                self.0.execution_time = RawInt::new(execution_time);
+                self
+            }
+
+            /// Sets the `skip_lines` value.
+            pub fn with_skip_lines(mut self, skip_lines: u32) -> Self {
+                self.0.skip_lines = RawInt::new(skip_lines);
+                self
+            }
+
+            /// Sets the `sql_ex` value.
+            pub fn with_sql_ex(mut self, sql_ex: SqlExchange<'a>) -> Self {
+                self.0.sql_ex = sql_ex;
+                self
+            }
+
+            /// Sets the `field_names` value (max length of a name is `u8::MAX`).
+            pub fn with_field_names<T>(mut self, field_names: impl IntoIterator<Item = T>) -> Self
+            where
+                T: Into<Cow<'a, [u8]>>,
+            {
+                self.0.field_names = field_names.into_iter().map(RawBytes::new).collect();
+                self.0.num_fields.0 = self.0.field_names.len() as u32;
+                self
+            }
+
+            /// Sets the `table_name` value (max length is `u8::MAX`).
+            pub fn with_table_name(mut self, table_name: impl Into<Cow<'a, [u8]>>) -> Self {
+                self.0.table_name = RawBytes::new(table_name);
+                self.0.table_name_len.0 = self.0.table_name.len() as u8;
+                self
+            }
+
+            /// Sets the `schema` value (max length is `u8::MAX`).
+            pub fn with_schema(mut self, schema: impl Into<Cow<'a, [u8]>>) -> Self {
+                self.0.schema = RawBytes::new(schema);
+                self.0.schema_len.0 = self.0.schema.len() as u8;
+                self
+            }
+
+            /// Sets the `file_name` value.
+            pub fn with_file_name(mut self, file_name: impl Into<Cow<'a, [u8]>>) -> Self {
+                self.0.file_name = RawBytes::new(file_name);
+                self
+            }
+
+            /// Returns the `thread_id` value.
+            ///
+            /// `thread_id` is the ID of the thread that issued this statement.
+            pub fn thread_id(&self) -> u32 {
+                self.0.thread_id.0
+            }
+
+            /// Returns the `execution_time` value.
+            ///
+            /// `execution_time` is the time from when the query started to when it was logged
+            /// in the binlog, in seconds.
+            pub fn execution_time(&self) -> u32 {
+                self.0.execution_time.0
+            }
+
+            /// Returns the `skip_lines` value (`IGNORE <n> LINES`).
+            pub fn skip_lines(&self) -> u32 {
+                self.0.skip_lines.0
+            }
+
+            /// Returns field and line options of the statement.
+            pub fn sql_ex(&self) -> &SqlExchange<'a> {
+                &self.0.sql_ex
+            }
+
+            /// Returns the number of fields in the field list of the statement.
+            pub fn num_fields(&self) -> u32 {
+                self.0.num_fields.0
+            }
+
+            /// Returns an iterator over field names of the statement.
+            pub fn field_names_raw(&'a self) -> impl Iterator<Item = &'a [u8]> + 'a {
+                self.0.field_names.iter().map(RawBytes::as_bytes)
+            }
+
+            /// Returns an iterator over field names of the statement (lossy converted).
+            pub fn field_names(&'a self) -> impl Iterator<Item = Cow<'a, str>> + 'a {
+                self.0.field_names.iter().map(RawBytes::as_str)
+            }
+
+            /// Returns the `table_name` value.
+            pub fn table_name_raw(&'a self) -> &'a [u8] {
+                self.0.table_name.as_bytes()
+            }
+
+            /// Returns the `table_name` value as a string (lossy converted).
+            pub fn table_name(&'a self) -> Cow<'a, str> {
+                self.0.table_name.as_str()
+            }
+
+            /// Returns the `schema` value.
+            pub fn schema_raw(&'a self) -> &'a [u8] {
+                self.0.schema.as_bytes()
+            }
+
+            /// Returns the `schema` value as a string (lossy converted).
+            pub fn schema(&'a self) -> Cow<'a, str> {
+                self.0.schema.as_str()
+            }
+
+            /// Returns the `file_name` value.
+            ///
+            /// `file_name` is the name of the loaded file as given in the statement.
+            pub fn file_name_raw(&'a self) -> &'a [u8] {
+                self.0.file_name.as_bytes()
+            }
+
+            /// Returns the `file_name` value as a string (lossy converted).
+            pub fn file_name(&'a self) -> Cow<'a, str> {
+                self.0.file_name.as_str()
+            }
+
+            pub fn into_owned(self) -> $name<'static> {
+                $name(self.0.into_owned())
+            }
+        }
+
+        impl<'de> MyDeserialize<'de> for $name<'de> {
+            const SIZE: Option<usize> = None;
+            type Ctx = BinlogCtx<'de>;
+
+            fn deserialize(_: Self::Ctx, buf: &mut ParseBuf<'de>) -> io::Result<Self> {
+                LoadEventData::deserialize(buf, $new_format).map(Self)
+            }
+        }
+
+        impl MySerialize for $name<'_> {
+            fn serialize(&self, buf: &mut Vec<u8>) {
+                self.0.serialize(buf, $new_format);
+            }
+        }
+
+        impl<'a> BinlogStruct<'a> for $name<'a> {
+            fn len(&self, _version: BinlogVersion) -> usize {
+                self.0.len($new_format)
+            }
+        }
+
+        impl<'a> BinlogEvent<'a> for $name<'a> {
+            const EVENT_TYPE: EventType = EventType::$event_type;
+        }
+    };
+}
+
+load_event!(
+    /// Load event.
+    ///
+    /// Used for `LOAD DATA INFILE` statements in MySQL 3.23 (superseded by `NEW_LOAD_EVENT`).
+    /// Terminators are stored as a single byte (see [`SqlExchange`]).
+    LoadEvent,
+    LOAD_EVENT,
+    false
+);
+
+load_event!(
+    /// New load event.
+    ///
+    /// Used for `LOAD DATA INFILE` statements in MySQL 4.0 and 4.1 (superseded by
+    /// `BEGIN_LOAD_QUERY_EVENT` and `EXECUTE_LOAD_QUERY_EVENT` as of MySQL 5.0).
+    NewLoadEvent,
+    NEW_LOAD_EVENT,
+    true
+);
@@ -7,7 +7,7 @@
 // modified, or distributed except according to those terms.
 
 use bitvec::prelude::*;
-use byteorder::{LittleEndian, WriteBytesExt};
+use byteorder::{ByteOrder, LittleEndian, WriteBytesExt};
 use bytes::BufMut;
 use saturating::Saturating as S;
 
@@ -24,39 +24,63 @@ pub use self::{
     delete_rows_event_v1::DeleteRowsEventV1,
     execute_load_query_event::ExecuteLoadQueryEvent,
     format_description_event::FormatDescriptionEvent,
-    gtid_event::GtidEvent,
+    gtid_event::{GtidEvent, ServerVersion},
+    heartbeat_event::HeartbeatEvent,
+    ignorable_event::IgnorableEvent,
     incident_event::IncidentEvent,
     intvar_event::IntvarEvent,
+    lazy_event::LazyEvent,
+    load_event::{LoadEvent, NewLoadEvent, SqlExchange},
     partial_update_rows_event::PartialUpdateRowsEvent,
-    query_event::{QueryEvent, StatusVar, StatusVarVal, StatusVars, StatusVarsIterator},
+    previous_gtids_event::PreviousGtidsEvent,
+    query_event::{
+        QueryEvent, QueryEventBuilder, StatusVar, StatusVarVal, StatusVars, StatusVarsBuilder,
+        StatusVarsIterator,
+    },
     rand_event::RandEvent,
     rotate_event::RotateEvent,
-    rows_event::{RowsEvent, RowsEventRows},
+    rows_event::{RowsEvent, RowsEventBuilder, RowsEventCtx, RowsEventRows},
     rows_query_event::RowsQueryEvent,
+    session_snapshot::SessionSnapshot,
+    statement_context::StatementContextBuilder,
+    stop_event::StopEvent,
     table_map_event::*,
+    transaction_context_event::TransactionContextEvent,
+    transaction_payload_event::{NoCompression, PayloadCodec, TransactionPayloadEvent},
     update_rows_event::UpdateRowsEvent,
     update_rows_event_v1::UpdateRowsEventV1,
     user_var_event::UserVarEvent,
+    view_change_event::ViewChangeEvent,
+    visitor::EventVisitor,
     write_rows_event::WriteRowsEvent,
     write_rows_event_v1::WriteRowsEventV1,
+    xa_prepare_event::XaPrepareEvent,
     xid_event::XidEvent,
 };
 
+#[cfg(feature = "zstd")]
+pub use self::transaction_payload_event::ZstdCompression;
+
 use std::{
     any::type_name,
     borrow::Cow,
     cmp::min,
+    convert::TryFrom,
     io::{self, Read, Write},
     u16,
 };
 
 use super::{
+    clock::BinlogClock,
     consts::{
-        BinlogChecksumAlg, BinlogVersion, EventFlags, EventType, UnknownChecksumAlg,
-        UnknownEventType,
+        BinlogChecksumAlg, BinlogVersion, EventFlags, EventType, RowsEventFlags,
+        UnknownChecksumAlg, UnknownEventType,
     },
-    misc::LimitWrite,
-    BinlogCtx, BinlogEvent,
+    mariadb::{AnnotateRowsEvent, BinlogCheckpointEvent, MariadbGtidEvent, MariadbGtidListEvent},
+    misc::{LimitWrite, TruncationPolicy},
+    pool::{EventBufferPool, GlobalBufferPool},
+    projection::Projection,
+    BinlogCtx, BinlogEvent, BinlogStruct,
 };
 
 mod anonymous_gtid_event;
@@ -66,75 +90,273 @@ mod delete_rows_event_v1;
 mod execute_load_query_event;
 mod format_description_event;
 mod gtid_event;
+mod heartbeat_event;
+mod ignorable_event;
 mod incident_event;
 mod intvar_event;
+mod lazy_event;
+mod load_event;
 mod partial_update_rows_event;
+mod previous_gtids_event;
 mod query_event;
 mod rand_event;
 mod rotate_event;
 mod rows_event;
 mod rows_query_event;
+mod session_snapshot;
+mod statement_context;
+mod stop_event;
 mod table_map_event;
+mod transaction_context_event;
+mod transaction_payload_event;
 mod update_rows_event;
 mod update_rows_event_v1;
 mod user_var_event;
+mod view_change_event;
+mod visitor;
 mod write_rows_event;
 mod write_rows_event_v1;
+mod xa_prepare_event;
 mod xid_event;
 
 /// Raw binlog event.
 ///
 /// A binlog event starts with a Binlog Event header and is followed by a Binlog Event Type
 /// specific data part.
+///
+/// Events read from an input (see [`Event::read`]) own their data, whereas events parsed
+/// from a buffer (see [`Event::parse`]) borrow it.
+///
+/// # Invariants
+///
+/// *   `event_size` in the header is the length of the header plus the raw data;
+/// *   [`Event::write`] produces exactly `event_size` bytes;
+/// *   `log_pos` in the header is either `0` or the end position of the event,
+///     so it isn't less than `event_size`.
+///
+/// They are checked by debug assertions for events constructed or modified by this crate,
+/// and by [`Event::validate_consistency`] at any time.
 #[derive(Debug, Clone, Eq, PartialEq)]
-pub struct Event {
+pub struct Event<'a> {
     /// Format description event.
-    fde: FormatDescriptionEvent<'static>,
+    fde: Cow<'a, FormatDescriptionEvent<'a>>,
     /// Common header of an event.
     header: BinlogEventHeader,
-    /// An event-type specific data.
+    /// Raw event data as read from the input (header excluded).
     ///
-    /// Checksum-related suffix is truncated:
+    /// Contains the checksum-related suffix (see [`Event::raw_data`]).
+    raw_data: Cow<'a, [u8]>,
+    /// Length of the event-type specific data within `raw_data`.
+    ///
+    /// Checksum-related suffix is excluded:
     ///
     /// *   checksum algorithm description (for fde) will go to `footer`;
     /// *   checksum will go to `checksum`.
-    data: Vec<u8>,
+    data_len: usize,
     /// Log event footer.
     footer: BinlogEventFooter,
     /// Event checksum.
     ///
     /// Makes sense only if checksum algorithm is defined in `footer`.
     checksum: [u8; BinlogEventFooter::BINLOG_CHECKSUM_LEN],
+    /// Maximum length of a query text retained by parsed query events.
+    max_query_len: Option<usize>,
 }
 
-impl Event {
+impl Event<'static> {
     /// Reads an event from `input`.
-    pub fn read<'a, T: Read>(
+    ///
+    /// Presence of the checksum algorithm description in a format description event
+    /// is detected automatically (see [`FdeFooterMode::Auto`]).
+    pub fn read<'a, T: Read>(fde: &'a FormatDescriptionEvent<'a>, input: T) -> io::Result<Self> {
+        Self::read_with_fde_footer(fde, input, FdeFooterMode::Auto)
+    }
+
+    /// Reads an event from `input` using the given mode to determine the footer
+    /// of a format description event.
+    pub fn read_with_fde_footer<'a, T: Read>(
         fde: &'a FormatDescriptionEvent<'a>,
         mut input: T,
+        fde_footer: FdeFooterMode,
     ) -> io::Result<Self> {
-        let binlog_header_len = BinlogEventHeader::LEN;
-        let mut fde = fde.clone().into_owned();
+        let mut header_buf = [0u8; BinlogEventHeader::LEN];
+        input.read_exact(&mut header_buf)?;
+        let header = BinlogEventHeader::deserialize((), &mut ParseBuf(&header_buf))?;
+
+        Self::read_body(fde, header, input, fde_footer, &GlobalBufferPool)
+    }
 
+    /// Reads an event from `input` into a buffer acquired from the given pool
+    /// (see [`Event::into_raw_data`] to give it back).
+    pub fn read_with_buffer_pool<'a, T: Read>(
+        fde: &'a FormatDescriptionEvent<'a>,
+        mut input: T,
+        pool: &dyn EventBufferPool,
+    ) -> io::Result<Self> {
         let mut header_buf = [0u8; BinlogEventHeader::LEN];
         input.read_exact(&mut header_buf)?;
         let header = BinlogEventHeader::deserialize((), &mut ParseBuf(&header_buf))?;
 
-        let mut data = vec![0_u8; (S(header.event_size() as usize) - S(binlog_header_len)).0];
-        input.read_exact(&mut data).unwrap();
+        Self::read_body(fde, header, input, FdeFooterMode::Auto, pool)
+    }
+
+    /// Reads the body of an event with the given (already read) header from `input`.
+    pub(crate) fn read_body<'a, T: Read>(
+        fde: &'a FormatDescriptionEvent<'a>,
+        header: BinlogEventHeader,
+        mut input: T,
+        fde_footer: FdeFooterMode,
+        pool: &dyn EventBufferPool,
+    ) -> io::Result<Self> {
+        let data_len = (header.event_size() as usize)
+            .checked_sub(BinlogEventHeader::LEN)
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "event size is less than the header size",
+                )
+            })?;
+        let mut data = pool.acquire(data_len);
+        data.resize(data_len, 0);
+        input.read_exact(&mut data)?;
+
+        Event::from_parts(
+            Cow::Owned(fde.clone().into_owned()),
+            header,
+            Cow::Owned(data),
+            fde_footer,
+        )
+    }
+
+    /// Creates an event from the parsed event data (see [`Event::read_data`]).
+    ///
+    /// The header is taken from `header` (so timestamp, server id, log position and flags
+    /// are preserved), except for the event size that is recalculated. The footer is defined
+    /// by `alg` (`None` means that the event is written without a footer), and the checksum
+    /// is calculated accordingly.
+    ///
+    /// Returns an `InvalidInput` error if the event type in `header` doesn't match the data,
+    /// or if the event is too large.
+    pub fn try_from_data(
+        header: BinlogEventHeader,
+        data: &EventData<'_>,
+        fde: &FormatDescriptionEvent<'_>,
+        alg: Option<BinlogChecksumAlg>,
+    ) -> io::Result<Self> {
+        let event_type = data.event_type();
+        if header.event_type.0 != event_type as u8 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "event type {:?} doesn't match the data ({:?})",
+                    header.event_type, event_type
+                ),
+            ));
+        }
+
+        let is_fde = event_type == EventType::FORMAT_DESCRIPTION_EVENT;
+        let footer = BinlogEventFooter {
+            checksum_alg: alg.map(|alg| RawConst::new(alg as u8)),
+        };
+
+        let mut raw_data = Vec::with_capacity(data.serialized_len(fde.binlog_version()));
+        data.serialize(&mut raw_data);
+        let data_len = raw_data.len();
+        if let Some(alg) = alg {
+            if is_fde {
+                raw_data.push(alg as u8);
+            }
+            if is_fde || alg != BinlogChecksumAlg::BINLOG_CHECKSUM_ALG_OFF {
+                raw_data.extend_from_slice(&[0; BinlogEventFooter::BINLOG_CHECKSUM_LEN]);
+            }
+        }
+
+        let event_size = u32::try_from(BinlogEventHeader::LEN + raw_data.len())
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "event is too large"))?;
+
+        let mut event = Self {
+            fde: Cow::Owned(fde.clone().into_owned().with_footer(footer)),
+            header: BinlogEventHeader {
+                event_size: RawInt::new(event_size),
+                ..header
+            },
+            raw_data: Cow::Owned(raw_data),
+            data_len,
+            footer,
+            checksum: [0; BinlogEventFooter::BINLOG_CHECKSUM_LEN],
+            max_query_len: None,
+        };
+        event.update_checksum();
+        debug_assert!(
+            event.validate_consistency().is_ok(),
+            "{:?}",
+            event.validate_consistency()
+        );
+
+        Ok(event)
+    }
+}
+
+impl<'a> Event<'a> {
+    /// Parses an event from the beginning of `buf` without copying its data.
+    ///
+    /// Presence of the checksum algorithm description in a format description event
+    /// is detected automatically (see [`FdeFooterMode::Auto`]).
+    ///
+    /// Bytes that follow the event are ignored, so the next event starts at
+    /// `header().event_size()`. Returns an `UnexpectedEof` error if `buf` is shorter
+    /// than the event. Use [`Event::into_owned`] to get an event that outlives `buf`.
+    pub fn parse(fde: &'a FormatDescriptionEvent<'a>, buf: &'a [u8]) -> io::Result<Self> {
+        Self::parse_with_fde_footer(fde, buf, FdeFooterMode::Auto)
+    }
+
+    /// Parses an event from the beginning of `buf` using the given mode to determine
+    /// the footer of a format description event (see [`Event::parse`]).
+    pub fn parse_with_fde_footer(
+        fde: &'a FormatDescriptionEvent<'a>,
+        buf: &'a [u8],
+        fde_footer: FdeFooterMode,
+    ) -> io::Result<Self> {
+        let header_buf = buf
+            .get(..BinlogEventHeader::LEN)
+            .ok_or_else(|| io::Error::from(io::ErrorKind::UnexpectedEof))?;
+        let header = BinlogEventHeader::deserialize((), &mut ParseBuf(header_buf))?;
+        if (header.event_size() as usize) < BinlogEventHeader::LEN {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "event size is less than the header size",
+            ));
+        }
+        let data = buf
+            .get(BinlogEventHeader::LEN..header.event_size() as usize)
+            .ok_or_else(|| io::Error::from(io::ErrorKind::UnexpectedEof))?;
+
+        Event::from_parts(Cow::Borrowed(fde), header, Cow::Borrowed(data), fde_footer)
+    }
 
+    /// Creates an event given its header and data (header excluded).
+    fn from_parts(
+        mut fde: Cow<'a, FormatDescriptionEvent<'a>>,
+        header: BinlogEventHeader,
+        data: Cow<'a, [u8]>,
+        fde_footer: FdeFooterMode,
+    ) -> io::Result<Self> {
         let is_fde = header.event_type.0 == EventType::FORMAT_DESCRIPTION_EVENT as u8;
         let mut bytes_to_truncate = 0;
         let mut checksum = [0_u8; BinlogEventFooter::BINLOG_CHECKSUM_LEN];
 
         let footer = if is_fde {
-            let footer = BinlogEventFooter::read(&data)?;
+            let footer = match fde_footer {
+                FdeFooterMode::Auto => BinlogEventFooter::detect(&header, &data)?,
+                FdeFooterMode::Present => BinlogEventFooter::read_present(&data)?,
+                FdeFooterMode::Absent => BinlogEventFooter { checksum_alg: None },
+            };
             if footer.checksum_alg.is_some() {
                 // truncate checksum algorithm description
                 bytes_to_truncate += BinlogEventFooter::BINLOG_CHECKSUM_ALG_DESC_LEN;
             }
             // We'll update dummy fde footer
-            fde = fde.with_footer(footer);
+            fde = Cow::Owned(fde.into_owned().with_footer(footer));
             footer
         } else {
             fde.footer()
@@ -147,29 +369,94 @@ impl Event {
         if contains_checksum {
             // truncate checksum
             bytes_to_truncate += BinlogEventFooter::BINLOG_CHECKSUM_LEN;
+        }
+
+        let data_len = data.len().checked_sub(bytes_to_truncate).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "event data is shorter than its footer ({} < {})",
+                    data.len(),
+                    bytes_to_truncate
+                ),
+            )
+        })?;
+
+        if contains_checksum {
             checksum.copy_from_slice(&data[data.len() - BinlogEventFooter::BINLOG_CHECKSUM_LEN..]);
         }
 
-        data.truncate(data.len() - bytes_to_truncate);
+        // catch the mismatched fde early (fde itself defines post-header lengths)
+        if let (false, Ok(event_type)) = (is_fde, header.event_type.get()) {
+            let post_header_len = fde.get_event_type_header_length(event_type) as usize;
+            if data_len < post_header_len {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!(
+                        "{:?} data is shorter than its post-header ({} < {})",
+                        event_type, data_len, post_header_len
+                    ),
+                ));
+            }
+        }
 
         Ok(Self {
             fde,
             header,
-            data,
+            raw_data: data,
+            data_len,
             footer,
             checksum,
+            max_query_len: None,
         })
     }
 
+    /// Returns an owned version of this event (copies the data if it's borrowed).
+    pub fn into_owned(self) -> Event<'static> {
+        Event {
+            fde: Cow::Owned(self.fde.into_owned().into_owned()),
+            header: self.header,
+            raw_data: Cow::Owned(self.raw_data.into_owned()),
+            data_len: self.data_len,
+            footer: self.footer,
+            checksum: self.checksum,
+            max_query_len: self.max_query_len,
+        }
+    }
+
     /// Writes this event into the `output`.
-    pub fn write<T: Write>(&self, version: BinlogVersion, mut output: T) -> io::Result<()> {
+    ///
+    /// Data that exceeds the maximum event length is silently truncated
+    /// (see [`Event::write_with_policy`]).
+    pub fn write<T: Write>(&self, version: BinlogVersion, output: T) -> io::Result<()> {
+        self.write_with_policy(version, output, TruncationPolicy::Truncate)
+    }
+
+    /// Writes this event into the `output` emitting an error if the event is too large
+    /// to be written without truncation.
+    pub fn checked_write<T: Write>(&self, version: BinlogVersion, output: T) -> io::Result<()> {
+        self.write_with_policy(version, output, TruncationPolicy::Error)
+    }
+
+    /// Writes this event into the `output` using the given truncation policy.
+    pub fn write_with_policy<T: Write>(
+        &self,
+        version: BinlogVersion,
+        mut output: T,
+        policy: TruncationPolicy,
+    ) -> io::Result<()> {
         let is_fde = self.header.event_type.0 == EventType::FORMAT_DESCRIPTION_EVENT as u8;
-        let mut output = output.limit(S(self.len(version)));
+        debug_assert_eq!(
+            self.len(version),
+            self.header.event_size() as usize,
+            "serialized length doesn't match the event size"
+        );
+        let mut output = output.limit(S(self.len(version)), policy);
 
         let mut header_buf = Vec::with_capacity(BinlogEventHeader::LEN);
         self.header.serialize(&mut header_buf);
         output.write_all(&header_buf)?;
-        output.write_all(&self.data)?;
+        output.write_all(self.data())?;
 
         if let Ok(Some(alg)) = self.footer.get_checksum_alg() {
             if is_fde {
@@ -185,12 +472,21 @@ impl Event {
 
     /// Returns a length of a serialized representation of this event.
     fn len(&self, _version: BinlogVersion) -> usize {
+        self.serialized_len(self.footer.get_checksum_alg().ok().flatten())
+    }
+
+    /// Returns the on-disk length of this event, i.e. the number of bytes [`Event::write`]
+    /// would produce if the event footer specified the given checksum algorithm.
+    ///
+    /// `None` means that the event is written without a footer. Note that
+    /// the format description event always carries a checksum if the algorithm is specified.
+    pub fn serialized_len(&self, alg: Option<BinlogChecksumAlg>) -> usize {
         let is_fde = self.header.event_type.0 == EventType::FORMAT_DESCRIPTION_EVENT as u8;
         let mut len = S(0);
 
         len += S(BinlogEventHeader::LEN);
-        len += S(self.data.len());
-        if let Ok(Some(alg)) = self.footer.get_checksum_alg() {
+        len += S(self.data_len);
+        if let Some(alg) = alg {
             if is_fde {
                 len += S(BinlogEventFooter::BINLOG_CHECKSUM_ALG_DESC_LEN);
             }
@@ -203,7 +499,7 @@ impl Event {
     }
 
     /// Returns a reference to the corresponding format description event.
-    pub fn fde(&self) -> &FormatDescriptionEvent<'static> {
+    pub fn fde(&self) -> &FormatDescriptionEvent<'a> {
         &self.fde
     }
 
@@ -213,8 +509,37 @@ impl Event {
     }
 
     /// Returns a reference to the event data.
+    ///
+    /// Checksum-related suffix is excluded (see [`Event::raw_data`]).
     pub fn data(&self) -> &[u8] {
-        &self.data
+        &self.raw_data[..self.data_len]
+    }
+
+    /// Returns a reference to the event data as it was read from the input,
+    /// i.e. including the checksum algorithm description (for fde) and the checksum.
+    ///
+    /// The checksum-related suffix starts at `self.data().len()`.
+    pub fn raw_data(&self) -> &[u8] {
+        &self.raw_data
+    }
+
+    /// Returns the event data as it was read from the input (see [`Event::raw_data`]).
+    ///
+    /// Use it to give the buffer back to the pool it was acquired from
+    /// (see [`Event::read_with_buffer_pool`]).
+    pub fn into_raw_data(self) -> Vec<u8> {
+        self.raw_data.into_owned()
+    }
+
+    /// Writes this event into the `output` exactly as it was read from the input.
+    ///
+    /// Unlike [`Event::write`] it won't recalculate the checksum, so it's suitable
+    /// for proxies that must forward events byte-identically.
+    pub fn write_raw<T: Write>(&self, mut output: T) -> io::Result<()> {
+        let mut header_buf = Vec::with_capacity(BinlogEventHeader::LEN);
+        self.header.serialize(&mut header_buf);
+        output.write_all(&header_buf)?;
+        output.write_all(&self.raw_data)
     }
 
     /// Returns a reference to the event footer.
@@ -222,6 +547,35 @@ impl Event {
         self.footer
     }
 
+    /// Returns `true` if this is an artificial rotate event.
+    ///
+    /// Server sends it at the beginning of a binlog stream to tell the client the name
+    /// of the binlog it is streaming from. Such event has a zero timestamp and isn't
+    /// written to a binlog file.
+    pub fn is_artificial_rotate(&self) -> bool {
+        self.header.event_type.0 == EventType::ROTATE_EVENT as u8
+            && (self.header.timestamp() == 0 || self.is_artificial())
+    }
+
+    /// Returns `true` if this event has the `LOG_EVENT_ARTIFICIAL_F` flag.
+    ///
+    /// Such events are generated by a server (e.g. by a replica's SQL thread) rather than
+    /// written by the originating server, so they shouldn't update the source log position.
+    pub fn is_artificial(&self) -> bool {
+        self.header
+            .flags()
+            .contains(EventFlags::LOG_EVENT_ARTIFICIAL_F)
+    }
+
+    /// Returns `true` if this event has the `LOG_EVENT_RELAY_LOG_F` flag.
+    ///
+    /// Such events are created by a replica's IO thread when written to the relay log.
+    pub fn is_relay_log(&self) -> bool {
+        self.header
+            .flags()
+            .contains(EventFlags::LOG_EVENT_RELAY_LOG_F)
+    }
+
     /// Returns the checksum, if it is defined.
     pub fn checksum(&self) -> Option<[u8; BinlogEventFooter::BINLOG_CHECKSUM_LEN]> {
         let contains_checksum = self.footer.checksum_alg.is_some()
@@ -230,12 +584,128 @@ impl Event {
         contains_checksum.then(|| self.checksum)
     }
 
+    /// Returns modified `self` with the given server id in the header.
+    ///
+    /// The checksum (if any) is recalculated, so [`Event::write_raw`] stays valid.
+    /// It's left as is if the checksum algorithm is unknown.
+    pub fn with_server_id(mut self, server_id: u32) -> Self {
+        self.header = self.header.with_server_id(server_id);
+        self.update_checksum();
+        self
+    }
+
+    /// Returns modified `self` with the given timestamp in the header.
+    ///
+    /// The checksum (if any) is recalculated, so [`Event::write_raw`] stays valid.
+    pub fn with_timestamp(mut self, timestamp: u32) -> Self {
+        self.header = self.header.with_timestamp(timestamp);
+        self.update_checksum();
+        self
+    }
+
+    /// Returns modified `self` with the given log position in the header.
+    ///
+    /// The checksum (if any) is recalculated, so [`Event::write_raw`] stays valid.
+    pub fn with_log_pos(mut self, log_pos: u32) -> Self {
+        debug_assert!(
+            log_pos == 0 || log_pos >= self.header.event_size(),
+            "log_pos {} is less than the event size {}",
+            log_pos,
+            self.header.event_size()
+        );
+        self.header = self.header.with_log_pos(log_pos);
+        self.update_checksum();
+        self
+    }
+
+    /// Returns modified `self` with the given flags in the header.
+    ///
+    /// The checksum (if any) is recalculated, so [`Event::write_raw`] stays valid.
+    pub fn with_flags(mut self, flags: EventFlags) -> Self {
+        self.header = self.header.with_flags(flags);
+        self.update_checksum();
+        self
+    }
+
+    /// Returns modified `self` that will limit the query text retained by query events
+    /// parsed from this event (`None`, the default, means unlimited).
+    ///
+    /// Longer queries are truncated by [`Event::read_event`] and [`Event::read_data`],
+    /// but the original length is still available (see [`QueryEvent::query_len`]).
+    /// It reduces the memory used by owned query events (see [`QueryEvent::into_owned`])
+    /// in pipelines that only need metadata. The raw event data is left as is.
+    pub fn with_max_query_len(mut self, max_query_len: Option<usize>) -> Self {
+        self.max_query_len = max_query_len;
+        self
+    }
+
+    /// Returns the maximum length of a query text retained by parsed query events.
+    pub fn max_query_len(&self) -> Option<usize> {
+        self.max_query_len
+    }
+
+    /// Checks invariants of this event (see [`Event`]).
+    ///
+    /// Returns an `InvalidData` error describing the first violated invariant.
+    /// Use it to check events produced by custom builders or transformers
+    /// in release builds, where debug assertions are disabled.
+    pub fn validate_consistency(&self) -> io::Result<()> {
+        let invalid = |msg: String| Err(io::Error::new(io::ErrorKind::InvalidData, msg));
+        let event_size = self.header.event_size() as usize;
+
+        if event_size != BinlogEventHeader::LEN + self.raw_data.len() {
+            return invalid(format!(
+                "event size {} doesn't match the event length {}",
+                event_size,
+                BinlogEventHeader::LEN + self.raw_data.len()
+            ));
+        }
+
+        if let Ok(alg) = self.footer.get_checksum_alg() {
+            let serialized_len = self.serialized_len(alg);
+            if serialized_len != event_size {
+                return invalid(format!(
+                    "serialized length {} doesn't match the event size {}",
+                    serialized_len, event_size
+                ));
+            }
+        }
+
+        let log_pos = self.header.log_pos() as usize;
+        if log_pos != 0 && log_pos < event_size {
+            return invalid(format!(
+                "log_pos {} is less than the event size {}",
+                log_pos, event_size
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Applies `f` to the event data and recalculates the checksum (if any).
+    ///
+    /// `f` must not change the length of the data, so the event size stays the same.
+    pub(crate) fn patch_data(&mut self, f: impl FnOnce(&mut [u8])) {
+        let data_len = self.data_len;
+        f(&mut self.raw_data.to_mut()[..data_len]);
+        self.update_checksum();
+    }
+
+    /// Recalculates the checksum if it's present and the algorithm is known.
+    fn update_checksum(&mut self) {
+        if let (Some(_), Ok(Some(alg))) = (self.checksum(), self.footer.get_checksum_alg()) {
+            self.checksum = self.calc_checksum(alg).to_le_bytes();
+            let offset = self.raw_data.len() - BinlogEventFooter::BINLOG_CHECKSUM_LEN;
+            self.raw_data.to_mut()[offset..].copy_from_slice(&self.checksum);
+        }
+    }
+
     /// Read event-type specific data as a binlog struct.
-    pub fn read_event<'a, T: BinlogEvent<'a>>(&'a self) -> io::Result<T> {
+    pub fn read_event<'b, T: BinlogEvent<'b>>(&'b self) -> io::Result<T> {
         // we'll use data.len() here because of truncated event footer
-        let event_size = BinlogEventHeader::LEN + self.data.len();
-        let event_data = &mut ParseBuf(&self.data);
-        let ctx = BinlogCtx::new(event_size, &self.fde);
+        let event_size = BinlogEventHeader::LEN + self.data_len;
+        let event_data = &mut ParseBuf(self.data());
+        let ctx = BinlogCtx::new(event_size, &self.fde).with_max_query_len(self.max_query_len);
 
         let event = event_data.parse(ctx)?;
 
@@ -253,6 +723,16 @@ impl Event {
         Ok(event)
     }
 
+    /// Reads a MariaDB compressed rows event of the given type (rows data is decompressed).
+    ///
+    /// It's wrapped into the variant of its uncompressed counterpart, but keeps its actual type
+    /// (see [`RowsEventData::event_type`]).
+    fn read_compressed_rows_event(&self, event_type: EventType) -> io::Result<RowsEvent<'_>> {
+        let event_size = BinlogEventHeader::LEN + self.data_len;
+        let ctx = BinlogCtx::new(event_size, &self.fde);
+        ParseBuf(self.data()).parse(RowsEventCtx::new(event_type, ctx))
+    }
+
     /// Reads event data. Returns `None` if event type is unknown.
     pub fn read_data(&self) -> io::Result<Option<EventData<'_>>> {
         use EventType::*;
@@ -264,18 +744,18 @@ impl Event {
 
         let event_data = match event_type {
             ENUM_END_EVENT | UNKNOWN_EVENT => EventData::UnknownEvent,
-            START_EVENT_V3 => EventData::StartEventV3(Cow::Borrowed(&*self.data)),
+            START_EVENT_V3 => EventData::StartEventV3(Cow::Borrowed(self.data())),
             QUERY_EVENT => EventData::QueryEvent(self.read_event()?),
-            STOP_EVENT => EventData::StopEvent,
+            STOP_EVENT => EventData::StopEvent(self.read_event()?),
             ROTATE_EVENT => EventData::RotateEvent(self.read_event()?),
             INTVAR_EVENT => EventData::IntvarEvent(self.read_event()?),
-            LOAD_EVENT => EventData::LoadEvent(Cow::Borrowed(&*self.data)),
+            LOAD_EVENT => EventData::LoadEvent(self.read_event()?),
             SLAVE_EVENT => EventData::SlaveEvent,
-            CREATE_FILE_EVENT => EventData::CreateFileEvent(Cow::Borrowed(&*self.data)),
-            APPEND_BLOCK_EVENT => EventData::AppendBlockEvent(Cow::Borrowed(&*self.data)),
-            EXEC_LOAD_EVENT => EventData::ExecLoadEvent(Cow::Borrowed(&*self.data)),
-            DELETE_FILE_EVENT => EventData::DeleteFileEvent(Cow::Borrowed(&*self.data)),
-            NEW_LOAD_EVENT => EventData::NewLoadEvent(Cow::Borrowed(&*self.data)),
+            CREATE_FILE_EVENT => EventData::CreateFileEvent(Cow::Borrowed(self.data())),
+            APPEND_BLOCK_EVENT => EventData::AppendBlockEvent(Cow::Borrowed(self.data())),
+            EXEC_LOAD_EVENT => EventData::ExecLoadEvent(Cow::Borrowed(self.data())),
+            DELETE_FILE_EVENT => EventData::DeleteFileEvent(Cow::Borrowed(self.data())),
+            NEW_LOAD_EVENT => EventData::NewLoadEvent(self.read_event()?),
             RAND_EVENT => EventData::RandEvent(self.read_event()?),
             USER_VAR_EVENT => EventData::UserVarEvent(self.read_event()?),
             FORMAT_DESCRIPTION_EVENT => {
@@ -288,9 +768,9 @@ impl Event {
             BEGIN_LOAD_QUERY_EVENT => EventData::BeginLoadQueryEvent(self.read_event()?),
             EXECUTE_LOAD_QUERY_EVENT => EventData::ExecuteLoadQueryEvent(self.read_event()?),
             TABLE_MAP_EVENT => EventData::TableMapEvent(self.read_event()?),
-            PRE_GA_WRITE_ROWS_EVENT => EventData::PreGaWriteRowsEvent(Cow::Borrowed(&*self.data)),
-            PRE_GA_UPDATE_ROWS_EVENT => EventData::PreGaUpdateRowsEvent(Cow::Borrowed(&*self.data)),
-            PRE_GA_DELETE_ROWS_EVENT => EventData::PreGaDeleteRowsEvent(Cow::Borrowed(&*self.data)),
+            PRE_GA_WRITE_ROWS_EVENT => EventData::PreGaWriteRowsEvent(Cow::Borrowed(self.data())),
+            PRE_GA_UPDATE_ROWS_EVENT => EventData::PreGaUpdateRowsEvent(Cow::Borrowed(self.data())),
+            PRE_GA_DELETE_ROWS_EVENT => EventData::PreGaDeleteRowsEvent(Cow::Borrowed(self.data())),
             WRITE_ROWS_EVENT_V1 => {
                 EventData::RowsEvent(RowsEventData::WriteRowsEventV1(self.read_event()?))
             }
@@ -301,8 +781,8 @@ impl Event {
                 EventData::RowsEvent(RowsEventData::DeleteRowsEventV1(self.read_event()?))
             }
             INCIDENT_EVENT => EventData::IncidentEvent(self.read_event()?),
-            HEARTBEAT_EVENT => EventData::HeartbeatEvent,
-            IGNORABLE_EVENT => EventData::IgnorableEvent(Cow::Borrowed(&*self.data)),
+            HEARTBEAT_EVENT => EventData::HeartbeatEvent(self.read_event()?),
+            IGNORABLE_EVENT => EventData::IgnorableEvent(self.read_event()?),
             ROWS_QUERY_EVENT => EventData::RowsQueryEvent(self.read_event()?),
             WRITE_ROWS_EVENT => {
                 EventData::RowsEvent(RowsEventData::WriteRowsEvent(self.read_event()?))
@@ -315,15 +795,56 @@ impl Event {
             }
             GTID_EVENT => EventData::GtidEvent(self.read_event()?),
             ANONYMOUS_GTID_EVENT => EventData::AnonymousGtidEvent(self.read_event()?),
-            PREVIOUS_GTIDS_EVENT => EventData::PreviousGtidsEvent(Cow::Borrowed(&*self.data)),
-            TRANSACTION_CONTEXT_EVENT => {
-                EventData::TransactionContextEvent(Cow::Borrowed(&*self.data))
-            }
-            VIEW_CHANGE_EVENT => EventData::ViewChangeEvent(Cow::Borrowed(&*self.data)),
-            XA_PREPARE_LOG_EVENT => EventData::XaPrepareLogEvent(Cow::Borrowed(&*self.data)),
+            PREVIOUS_GTIDS_EVENT => EventData::PreviousGtidsEvent(self.read_event()?),
+            TRANSACTION_CONTEXT_EVENT => EventData::TransactionContextEvent(self.read_event()?),
+            VIEW_CHANGE_EVENT => EventData::ViewChangeEvent(self.read_event()?),
+            XA_PREPARE_LOG_EVENT => EventData::XaPrepareLogEvent(Cow::Borrowed(self.data())),
             PARTIAL_UPDATE_ROWS_EVENT => {
                 EventData::RowsEvent(RowsEventData::PartialUpdateRowsEvent(self.read_event()?))
             }
+            TRANSACTION_PAYLOAD_EVENT => EventData::TransactionPayloadEvent(self.read_event()?),
+            MARIADB_ANNOTATE_ROWS_EVENT => EventData::MariadbAnnotateRowsEvent(self.read_event()?),
+            MARIADB_BINLOG_CHECKPOINT_EVENT => {
+                EventData::MariadbBinlogCheckpointEvent(self.read_event()?)
+            }
+            MARIADB_GTID_EVENT => EventData::MariadbGtidEvent(self.read_event()?),
+            MARIADB_GTID_LIST_EVENT => EventData::MariadbGtidListEvent(self.read_event()?),
+            MARIADB_START_ENCRYPTION_EVENT => {
+                EventData::MariadbStartEncryptionEvent(Cow::Borrowed(self.data()))
+            }
+            MARIADB_QUERY_COMPRESSED_EVENT => {
+                EventData::MariadbQueryCompressedEvent(Cow::Borrowed(self.data()))
+            }
+            MARIADB_WRITE_ROWS_COMPRESSED_EVENT_V1 => {
+                EventData::RowsEvent(RowsEventData::WriteRowsEventV1(WriteRowsEventV1(
+                    self.read_compressed_rows_event(event_type)?,
+                )))
+            }
+            MARIADB_UPDATE_ROWS_COMPRESSED_EVENT_V1 => {
+                EventData::RowsEvent(RowsEventData::UpdateRowsEventV1(UpdateRowsEventV1(
+                    self.read_compressed_rows_event(event_type)?,
+                )))
+            }
+            MARIADB_DELETE_ROWS_COMPRESSED_EVENT_V1 => {
+                EventData::RowsEvent(RowsEventData::DeleteRowsEventV1(DeleteRowsEventV1(
+                    self.read_compressed_rows_event(event_type)?,
+                )))
+            }
+            MARIADB_WRITE_ROWS_COMPRESSED_EVENT => {
+                EventData::RowsEvent(RowsEventData::WriteRowsEvent(WriteRowsEvent(
+                    self.read_compressed_rows_event(event_type)?,
+                )))
+            }
+            MARIADB_UPDATE_ROWS_COMPRESSED_EVENT => {
+                EventData::RowsEvent(RowsEventData::UpdateRowsEvent(UpdateRowsEvent(
+                    self.read_compressed_rows_event(event_type)?,
+                )))
+            }
+            MARIADB_DELETE_ROWS_COMPRESSED_EVENT => {
+                EventData::RowsEvent(RowsEventData::DeleteRowsEvent(DeleteRowsEvent(
+                    self.read_compressed_rows_event(event_type)?,
+                )))
+            }
         };
 
         Ok(Some(event_data))
@@ -349,7 +870,7 @@ impl Event {
         }
         header_struct.serialize(&mut header);
         hasher.update(&header);
-        hasher.update(&self.data);
+        hasher.update(self.data());
         if is_fde {
             hasher.update(&[alg as u8][..]);
         }
@@ -408,6 +929,17 @@ impl BinlogEventHeader {
         self.timestamp.0
     }
 
+    /// Defines the `timestamp` value (in seconds since unix epoch).
+    pub fn with_timestamp(mut self, timestamp: u32) -> Self {
+        self.timestamp = RawInt::new(timestamp);
+        self
+    }
+
+    /// Defines the `timestamp` value using the given clock.
+    pub fn with_clock(self, clock: impl BinlogClock) -> Self {
+        self.with_timestamp(clock.now())
+    }
+
     /// Returns the raw event type.
     pub fn event_type_raw(&self) -> u8 {
         self.event_type.0
@@ -425,6 +957,12 @@ impl BinlogEventHeader {
         self.server_id.0
     }
 
+    /// Defines the server Id of the originating mysql-server.
+    pub fn with_server_id(mut self, server_id: u32) -> Self {
+        self.server_id = RawInt::new(server_id);
+        self
+    }
+
     /// Returns the size of the event (header, post-header, body).
     pub fn event_size(&self) -> u32 {
         self.event_size.0
@@ -435,6 +973,12 @@ impl BinlogEventHeader {
         self.log_pos.0
     }
 
+    /// Defines the position of the next event.
+    pub fn with_log_pos(mut self, log_pos: u32) -> Self {
+        self.log_pos = RawInt::new(log_pos);
+        self
+    }
+
     /// Returns the raw event flags.
     pub fn flags_raw(&self) -> u16 {
         self.flags.0
@@ -444,6 +988,12 @@ impl BinlogEventHeader {
     pub fn flags(&self) -> EventFlags {
         self.flags.get()
     }
+
+    /// Defines the event flags.
+    pub fn with_flags(mut self, flags: EventFlags) -> Self {
+        self.flags = RawFlags::new(flags.bits());
+        self
+    }
 }
 
 impl<'de> MyDeserialize<'de> for BinlogEventHeader {
@@ -474,6 +1024,22 @@ impl MySerialize for BinlogEventHeader {
     }
 }
 
+/// Defines how to determine whether a format description event contains
+/// the checksum algorithm description and the checksum.
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum FdeFooterMode {
+    /// Decide based on the server version and validate the checksum (the default).
+    ///
+    /// See [`BinlogEventFooter::detect`].
+    #[default]
+    Auto,
+    /// Format description event always contains the footer.
+    Present,
+    /// Format description event never contains the footer
+    /// (and so the following events do not contain checksums).
+    Absent,
+}
+
 /// Binlog event footer.
 #[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
 pub struct BinlogEventFooter {
@@ -527,6 +1093,53 @@ impl BinlogEventFooter {
             checksum_alg: checksum_alg.map(RawConst::new),
         })
     }
+
+    /// Detects binlog event footer of the given `FormatDescriptionEvent`.
+    ///
+    /// Unlike [`BinlogEventFooter::read`], it won't trust the server version alone:
+    /// some relay logs and old servers omit the checksum algorithm description,
+    /// so the footer is assumed to be present only if the checksum matches.
+    pub fn detect(header: &BinlogEventHeader, data: &[u8]) -> io::Result<Self> {
+        let footer = Self::read(data)?;
+        if footer.checksum_alg.is_none() {
+            return Ok(footer);
+        }
+
+        let (payload, checksum) = data.split_at(data.len() - Self::BINLOG_CHECKSUM_LEN);
+
+        let mut header = *header;
+        // see `Event::calc_checksum`
+        header.flags.0 &= !(EventFlags::LOG_EVENT_BINLOG_IN_USE_F.bits());
+        let mut header_buf = Vec::with_capacity(BinlogEventHeader::LEN);
+        header.serialize(&mut header_buf);
+
+        let mut hasher = crc32fast::Hasher::new();
+        hasher.update(&header_buf);
+        hasher.update(payload);
+
+        if hasher.finalize() == LittleEndian::read_u32(checksum) {
+            Ok(footer)
+        } else {
+            Ok(Self { checksum_alg: None })
+        }
+    }
+
+    /// Reads binlog event footer from the given `FormatDescriptionEvent` data
+    /// regardless of the server version.
+    fn read_present(buf: &[u8]) -> io::Result<Self> {
+        let offset = buf
+            .len()
+            .checked_sub(Self::BINLOG_CHECKSUM_ALG_DESC_LEN + Self::BINLOG_CHECKSUM_LEN)
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "format description event is too short to contain a footer",
+                )
+            })?;
+        Ok(Self {
+            checksum_alg: Some(RawConst::new(buf[offset])),
+        })
+    }
 }
 
 impl Default for BinlogEventFooter {
@@ -546,11 +1159,10 @@ pub enum EventData<'a> {
     /// Ignored by this implementation
     StartEventV3(Cow<'a, [u8]>),
     QueryEvent(QueryEvent<'a>),
-    StopEvent,
+    StopEvent(StopEvent),
     RotateEvent(RotateEvent<'a>),
     IntvarEvent(IntvarEvent),
-    /// Ignored by this implementation
-    LoadEvent(Cow<'a, [u8]>),
+    LoadEvent(LoadEvent<'a>),
     SlaveEvent,
     CreateFileEvent(Cow<'a, [u8]>),
     /// Ignored by this implementation
@@ -559,8 +1171,7 @@ pub enum EventData<'a> {
     ExecLoadEvent(Cow<'a, [u8]>),
     /// Ignored by this implementation
     DeleteFileEvent(Cow<'a, [u8]>),
-    /// Ignored by this implementation
-    NewLoadEvent(Cow<'a, [u8]>),
+    NewLoadEvent(NewLoadEvent<'a>),
     RandEvent(RandEvent),
     UserVarEvent(UserVarEvent<'a>),
     FormatDescriptionEvent(FormatDescriptionEvent<'a>),
@@ -575,21 +1186,29 @@ pub enum EventData<'a> {
     /// Ignored by this implementation
     PreGaDeleteRowsEvent(Cow<'a, [u8]>),
     IncidentEvent(IncidentEvent<'a>),
-    HeartbeatEvent,
-    IgnorableEvent(Cow<'a, [u8]>),
+    HeartbeatEvent(HeartbeatEvent<'a>),
+    IgnorableEvent(IgnorableEvent<'a>),
     RowsQueryEvent(RowsQueryEvent<'a>),
     GtidEvent(GtidEvent),
     /// Not yet implemented.
     AnonymousGtidEvent(AnonymousGtidEvent),
-    /// Not yet implemented.
-    PreviousGtidsEvent(Cow<'a, [u8]>),
-    /// Not yet implemented.
-    TransactionContextEvent(Cow<'a, [u8]>),
-    /// Not yet implemented.
-    ViewChangeEvent(Cow<'a, [u8]>),
+    PreviousGtidsEvent(PreviousGtidsEvent<'a>),
+    TransactionContextEvent(TransactionContextEvent<'a>),
+    ViewChangeEvent(ViewChangeEvent<'a>),
     /// Not yet implemented.
     XaPrepareLogEvent(Cow<'a, [u8]>),
     RowsEvent(RowsEventData<'a>),
+    TransactionPayloadEvent(TransactionPayloadEvent<'a>),
+    MariadbAnnotateRowsEvent(AnnotateRowsEvent<'a>),
+    MariadbBinlogCheckpointEvent(BinlogCheckpointEvent<'a>),
+    MariadbGtidEvent(MariadbGtidEvent<'a>),
+    MariadbGtidListEvent(MariadbGtidListEvent<'a>),
+    /// MariaDB start encryption event (payload is not parsed).
+    MariadbStartEncryptionEvent(Cow<'a, [u8]>),
+    /// MariaDB compressed query event (payload is not parsed).
+    ///
+    /// Compressed rows events are read as [`EventData::RowsEvent`].
+    MariadbQueryCompressedEvent(Cow<'a, [u8]>),
 }
 
 impl<'a> EventData<'a> {
@@ -598,16 +1217,16 @@ impl<'a> EventData<'a> {
             EventData::UnknownEvent => EventData::UnknownEvent,
             EventData::StartEventV3(ev) => EventData::StartEventV3(Cow::Owned(ev.into_owned())),
             Self::QueryEvent(ev) => EventData::QueryEvent(ev.into_owned()),
-            Self::StopEvent => EventData::StopEvent,
+            Self::StopEvent(ev) => EventData::StopEvent(ev),
             Self::RotateEvent(ev) => EventData::RotateEvent(ev.into_owned()),
             Self::IntvarEvent(ev) => EventData::IntvarEvent(ev),
-            Self::LoadEvent(ev) => EventData::LoadEvent(Cow::Owned(ev.into_owned())),
+            Self::LoadEvent(ev) => EventData::LoadEvent(ev.into_owned()),
             Self::SlaveEvent => EventData::SlaveEvent,
             Self::CreateFileEvent(ev) => EventData::CreateFileEvent(Cow::Owned(ev.into_owned())),
             Self::AppendBlockEvent(ev) => EventData::AppendBlockEvent(Cow::Owned(ev.into_owned())),
             Self::ExecLoadEvent(ev) => EventData::ExecLoadEvent(Cow::Owned(ev.into_owned())),
             Self::DeleteFileEvent(ev) => EventData::DeleteFileEvent(Cow::Owned(ev.into_owned())),
-            Self::NewLoadEvent(ev) => EventData::NewLoadEvent(Cow::Owned(ev.into_owned())),
+            Self::NewLoadEvent(ev) => EventData::NewLoadEvent(ev.into_owned()),
             Self::RandEvent(ev) => EventData::RandEvent(ev),
             Self::UserVarEvent(ev) => EventData::UserVarEvent(ev.into_owned()),
             Self::FormatDescriptionEvent(ev) => EventData::FormatDescriptionEvent(ev.into_owned()),
@@ -625,22 +1244,134 @@ impl<'a> EventData<'a> {
                 EventData::PreGaDeleteRowsEvent(Cow::Owned(ev.into_owned()))
             }
             Self::IncidentEvent(ev) => EventData::IncidentEvent(ev.into_owned()),
-            Self::HeartbeatEvent => EventData::HeartbeatEvent,
-            Self::IgnorableEvent(ev) => EventData::IgnorableEvent(Cow::Owned(ev.into_owned())),
+            Self::HeartbeatEvent(ev) => EventData::HeartbeatEvent(ev.into_owned()),
+            Self::IgnorableEvent(ev) => EventData::IgnorableEvent(ev.into_owned()),
             Self::RowsQueryEvent(ev) => EventData::RowsQueryEvent(ev.into_owned()),
             Self::GtidEvent(ev) => EventData::GtidEvent(ev),
             Self::AnonymousGtidEvent(ev) => EventData::AnonymousGtidEvent(ev),
-            Self::PreviousGtidsEvent(ev) => {
-                EventData::PreviousGtidsEvent(Cow::Owned(ev.into_owned()))
-            }
+            Self::PreviousGtidsEvent(ev) => EventData::PreviousGtidsEvent(ev.into_owned()),
             Self::TransactionContextEvent(ev) => {
-                EventData::TransactionContextEvent(Cow::Owned(ev.into_owned()))
+                EventData::TransactionContextEvent(ev.into_owned())
             }
-            Self::ViewChangeEvent(ev) => EventData::ViewChangeEvent(Cow::Owned(ev.into_owned())),
+            Self::ViewChangeEvent(ev) => EventData::ViewChangeEvent(ev.into_owned()),
             Self::XaPrepareLogEvent(ev) => {
                 EventData::XaPrepareLogEvent(Cow::Owned(ev.into_owned()))
             }
             Self::RowsEvent(ev) => EventData::RowsEvent(ev.into_owned()),
+            Self::TransactionPayloadEvent(ev) => {
+                EventData::TransactionPayloadEvent(ev.into_owned())
+            }
+            Self::MariadbAnnotateRowsEvent(ev) => {
+                EventData::MariadbAnnotateRowsEvent(ev.into_owned())
+            }
+            Self::MariadbBinlogCheckpointEvent(ev) => {
+                EventData::MariadbBinlogCheckpointEvent(ev.into_owned())
+            }
+            Self::MariadbGtidEvent(ev) => EventData::MariadbGtidEvent(ev.into_owned()),
+            Self::MariadbGtidListEvent(ev) => EventData::MariadbGtidListEvent(ev.into_owned()),
+            Self::MariadbStartEncryptionEvent(ev) => {
+                EventData::MariadbStartEncryptionEvent(Cow::Owned(ev.into_owned()))
+            }
+            Self::MariadbQueryCompressedEvent(ev) => {
+                EventData::MariadbQueryCompressedEvent(Cow::Owned(ev.into_owned()))
+            }
+        }
+    }
+
+    /// Returns the type of this event.
+    pub fn event_type(&self) -> EventType {
+        match self {
+            EventData::UnknownEvent => EventType::UNKNOWN_EVENT,
+            EventData::StartEventV3(_) => EventType::START_EVENT_V3,
+            EventData::QueryEvent(_) => EventType::QUERY_EVENT,
+            EventData::StopEvent(_) => EventType::STOP_EVENT,
+            EventData::RotateEvent(_) => EventType::ROTATE_EVENT,
+            EventData::IntvarEvent(_) => EventType::INTVAR_EVENT,
+            EventData::LoadEvent(_) => EventType::LOAD_EVENT,
+            EventData::SlaveEvent => EventType::SLAVE_EVENT,
+            EventData::CreateFileEvent(_) => EventType::CREATE_FILE_EVENT,
+            EventData::AppendBlockEvent(_) => EventType::APPEND_BLOCK_EVENT,
+            EventData::ExecLoadEvent(_) => EventType::EXEC_LOAD_EVENT,
+            EventData::DeleteFileEvent(_) => EventType::DELETE_FILE_EVENT,
+            EventData::NewLoadEvent(_) => EventType::NEW_LOAD_EVENT,
+            EventData::RandEvent(_) => EventType::RAND_EVENT,
+            EventData::UserVarEvent(_) => EventType::USER_VAR_EVENT,
+            EventData::FormatDescriptionEvent(_) => EventType::FORMAT_DESCRIPTION_EVENT,
+            EventData::XidEvent(_) => EventType::XID_EVENT,
+            EventData::BeginLoadQueryEvent(_) => EventType::BEGIN_LOAD_QUERY_EVENT,
+            EventData::ExecuteLoadQueryEvent(_) => EventType::EXECUTE_LOAD_QUERY_EVENT,
+            EventData::TableMapEvent(_) => EventType::TABLE_MAP_EVENT,
+            EventData::PreGaWriteRowsEvent(_) => EventType::PRE_GA_WRITE_ROWS_EVENT,
+            EventData::PreGaUpdateRowsEvent(_) => EventType::PRE_GA_UPDATE_ROWS_EVENT,
+            EventData::PreGaDeleteRowsEvent(_) => EventType::PRE_GA_DELETE_ROWS_EVENT,
+            EventData::IncidentEvent(_) => EventType::INCIDENT_EVENT,
+            EventData::HeartbeatEvent(_) => EventType::HEARTBEAT_EVENT,
+            EventData::IgnorableEvent(_) => EventType::IGNORABLE_EVENT,
+            EventData::RowsQueryEvent(_) => EventType::ROWS_QUERY_EVENT,
+            EventData::GtidEvent(_) => EventType::GTID_EVENT,
+            EventData::AnonymousGtidEvent(_) => EventType::ANONYMOUS_GTID_EVENT,
+            EventData::PreviousGtidsEvent(_) => EventType::PREVIOUS_GTIDS_EVENT,
+            EventData::TransactionContextEvent(_) => EventType::TRANSACTION_CONTEXT_EVENT,
+            EventData::ViewChangeEvent(_) => EventType::VIEW_CHANGE_EVENT,
+            EventData::XaPrepareLogEvent(_) => EventType::XA_PREPARE_LOG_EVENT,
+            EventData::RowsEvent(ev) => ev.event_type(),
+            EventData::TransactionPayloadEvent(_) => EventType::TRANSACTION_PAYLOAD_EVENT,
+            EventData::MariadbAnnotateRowsEvent(_) => EventType::MARIADB_ANNOTATE_ROWS_EVENT,
+            EventData::MariadbBinlogCheckpointEvent(_) => {
+                EventType::MARIADB_BINLOG_CHECKPOINT_EVENT
+            }
+            EventData::MariadbGtidEvent(_) => EventType::MARIADB_GTID_EVENT,
+            EventData::MariadbGtidListEvent(_) => EventType::MARIADB_GTID_LIST_EVENT,
+            EventData::MariadbStartEncryptionEvent(_) => EventType::MARIADB_START_ENCRYPTION_EVENT,
+            EventData::MariadbQueryCompressedEvent(_) => EventType::MARIADB_QUERY_COMPRESSED_EVENT,
+        }
+    }
+
+    /// Returns the length of a serialized representation of this event data
+    /// (without the event header and footer).
+    pub fn serialized_len(&self, version: BinlogVersion) -> usize {
+        match self {
+            EventData::UnknownEvent => 0,
+            EventData::StartEventV3(ev) => ev.len(),
+            EventData::QueryEvent(ev) => ev.len(version),
+            EventData::StopEvent(ev) => ev.len(version),
+            EventData::RotateEvent(ev) => ev.len(version),
+            EventData::IntvarEvent(ev) => ev.len(version),
+            EventData::LoadEvent(ev) => ev.len(version),
+            EventData::SlaveEvent => 0,
+            EventData::CreateFileEvent(ev) => ev.len(),
+            EventData::AppendBlockEvent(ev) => ev.len(),
+            EventData::ExecLoadEvent(ev) => ev.len(),
+            EventData::DeleteFileEvent(ev) => ev.len(),
+            EventData::NewLoadEvent(ev) => ev.len(version),
+            EventData::RandEvent(ev) => ev.len(version),
+            EventData::UserVarEvent(ev) => ev.len(version),
+            EventData::FormatDescriptionEvent(ev) => ev.len(version),
+            EventData::XidEvent(ev) => ev.len(version),
+            EventData::BeginLoadQueryEvent(ev) => ev.len(version),
+            EventData::ExecuteLoadQueryEvent(ev) => ev.len(version),
+            EventData::TableMapEvent(ev) => ev.len(version),
+            EventData::PreGaWriteRowsEvent(ev) => ev.len(),
+            EventData::PreGaUpdateRowsEvent(ev) => ev.len(),
+            EventData::PreGaDeleteRowsEvent(ev) => ev.len(),
+            EventData::IncidentEvent(ev) => ev.len(version),
+            EventData::HeartbeatEvent(ev) => ev.len(version),
+            EventData::IgnorableEvent(ev) => ev.len(version),
+            EventData::RowsQueryEvent(ev) => ev.len(version),
+            EventData::GtidEvent(ev) => ev.len(version),
+            EventData::AnonymousGtidEvent(ev) => ev.len(version),
+            EventData::PreviousGtidsEvent(ev) => ev.len(version),
+            EventData::TransactionContextEvent(ev) => ev.len(version),
+            EventData::ViewChangeEvent(ev) => ev.len(version),
+            EventData::XaPrepareLogEvent(ev) => ev.len(),
+            EventData::RowsEvent(ev) => ev.serialized_len(version),
+            EventData::TransactionPayloadEvent(ev) => ev.len(version),
+            EventData::MariadbAnnotateRowsEvent(ev) => ev.len(version),
+            EventData::MariadbBinlogCheckpointEvent(ev) => ev.len(version),
+            EventData::MariadbGtidEvent(ev) => ev.len(version),
+            EventData::MariadbGtidListEvent(ev) => ev.len(version),
+            EventData::MariadbStartEncryptionEvent(ev) => ev.len(),
+            EventData::MariadbQueryCompressedEvent(ev) => ev.len(),
         }
     }
 }
@@ -651,16 +1382,16 @@ impl MySerialize for EventData<'_> {
             EventData::UnknownEvent => (),
             EventData::StartEventV3(ev) => buf.put_slice(&*ev),
             EventData::QueryEvent(ev) => ev.serialize(buf),
-            EventData::StopEvent => (),
+            EventData::StopEvent(ev) => ev.serialize(buf),
             EventData::RotateEvent(ev) => ev.serialize(buf),
             EventData::IntvarEvent(ev) => ev.serialize(buf),
-            EventData::LoadEvent(ev) => buf.put_slice(&*ev),
+            EventData::LoadEvent(ev) => ev.serialize(buf),
             EventData::SlaveEvent => (),
             EventData::CreateFileEvent(ev) => buf.put_slice(&*ev),
             EventData::AppendBlockEvent(ev) => buf.put_slice(&*ev),
             EventData::ExecLoadEvent(ev) => buf.put_slice(&*ev),
             EventData::DeleteFileEvent(ev) => buf.put_slice(&*ev),
-            EventData::NewLoadEvent(ev) => buf.put_slice(&*ev),
+            EventData::NewLoadEvent(ev) => ev.serialize(buf),
             EventData::RandEvent(ev) => ev.serialize(buf),
             EventData::UserVarEvent(ev) => ev.serialize(buf),
             EventData::FormatDescriptionEvent(ev) => ev.serialize(buf),
@@ -672,16 +1403,23 @@ impl MySerialize for EventData<'_> {
             EventData::PreGaUpdateRowsEvent(ev) => buf.put_slice(&*ev),
             EventData::PreGaDeleteRowsEvent(ev) => buf.put_slice(&*ev),
             EventData::IncidentEvent(ev) => ev.serialize(buf),
-            EventData::HeartbeatEvent => (),
-            EventData::IgnorableEvent(ev) => buf.put_slice(&*ev),
+            EventData::HeartbeatEvent(ev) => ev.serialize(buf),
+            EventData::IgnorableEvent(ev) => ev.serialize(buf),
             EventData::RowsQueryEvent(ev) => ev.serialize(buf),
             EventData::GtidEvent(ev) => ev.serialize(buf),
             EventData::AnonymousGtidEvent(ev) => ev.serialize(buf),
-            EventData::PreviousGtidsEvent(ev) => buf.put_slice(&*ev),
-            EventData::TransactionContextEvent(ev) => buf.put_slice(&*ev),
-            EventData::ViewChangeEvent(ev) => buf.put_slice(&*ev),
+            EventData::PreviousGtidsEvent(ev) => ev.serialize(buf),
+            EventData::TransactionContextEvent(ev) => ev.serialize(buf),
+            EventData::ViewChangeEvent(ev) => ev.serialize(buf),
             EventData::XaPrepareLogEvent(ev) => buf.put_slice(&*ev),
             EventData::RowsEvent(ev) => ev.serialize(buf),
+            EventData::TransactionPayloadEvent(ev) => ev.serialize(buf),
+            EventData::MariadbAnnotateRowsEvent(ev) => ev.serialize(buf),
+            EventData::MariadbBinlogCheckpointEvent(ev) => ev.serialize(buf),
+            EventData::MariadbGtidEvent(ev) => ev.serialize(buf),
+            EventData::MariadbGtidListEvent(ev) => ev.serialize(buf),
+            EventData::MariadbStartEncryptionEvent(ev) => buf.put_slice(ev),
+            EventData::MariadbQueryCompressedEvent(ev) => buf.put_slice(ev),
         }
     }
 }
@@ -699,6 +1437,19 @@ pub enum RowsEventData<'a> {
 }
 
 impl<'a> RowsEventData<'a> {
+    /// Returns the type of this rows event.
+    pub fn event_type(&self) -> EventType {
+        match self {
+            RowsEventData::WriteRowsEventV1(ev) => ev.event_type(),
+            RowsEventData::UpdateRowsEventV1(ev) => ev.event_type(),
+            RowsEventData::DeleteRowsEventV1(ev) => ev.event_type(),
+            RowsEventData::WriteRowsEvent(ev) => ev.event_type(),
+            RowsEventData::UpdateRowsEvent(ev) => ev.event_type(),
+            RowsEventData::DeleteRowsEvent(ev) => ev.event_type(),
+            RowsEventData::PartialUpdateRowsEvent(_) => EventType::PARTIAL_UPDATE_ROWS_EVENT,
+        }
+    }
+
     /// Returns the number that identifies the table (see `TableMapEvent`).
     pub fn table_id(&self) -> u64 {
         match self {
@@ -712,6 +1463,46 @@ impl<'a> RowsEventData<'a> {
         }
     }
 
+    /// Defines the table identifier.
+    pub fn with_table_id(self, table_id: u64) -> Self {
+        match self {
+            RowsEventData::WriteRowsEventV1(ev) => {
+                RowsEventData::WriteRowsEventV1(ev.with_table_id(table_id))
+            }
+            RowsEventData::UpdateRowsEventV1(ev) => {
+                RowsEventData::UpdateRowsEventV1(ev.with_table_id(table_id))
+            }
+            RowsEventData::DeleteRowsEventV1(ev) => {
+                RowsEventData::DeleteRowsEventV1(ev.with_table_id(table_id))
+            }
+            RowsEventData::WriteRowsEvent(ev) => {
+                RowsEventData::WriteRowsEvent(ev.with_table_id(table_id))
+            }
+            RowsEventData::UpdateRowsEvent(ev) => {
+                RowsEventData::UpdateRowsEvent(ev.with_table_id(table_id))
+            }
+            RowsEventData::DeleteRowsEvent(ev) => {
+                RowsEventData::DeleteRowsEvent(ev.with_table_id(table_id))
+            }
+            RowsEventData::PartialUpdateRowsEvent(ev) => {
+                RowsEventData::PartialUpdateRowsEvent(ev.with_table_id(table_id))
+            }
+        }
+    }
+
+    /// Returns rows event flags. Unknown bits will be truncated.
+    pub fn flags(&self) -> RowsEventFlags {
+        match self {
+            RowsEventData::WriteRowsEventV1(ev) => ev.flags(),
+            RowsEventData::UpdateRowsEventV1(ev) => ev.flags(),
+            RowsEventData::DeleteRowsEventV1(ev) => ev.flags(),
+            RowsEventData::WriteRowsEvent(ev) => ev.flags(),
+            RowsEventData::UpdateRowsEvent(ev) => ev.flags(),
+            RowsEventData::DeleteRowsEvent(ev) => ev.flags(),
+            RowsEventData::PartialUpdateRowsEvent(ev) => ev.flags(),
+        }
+    }
+
     /// Returns the number of columns in the table.
     pub fn num_columns(&self) -> u64 {
         match self {
@@ -781,6 +1572,30 @@ impl<'a> RowsEventData<'a> {
         }
     }
 
+    /// Returns an iterator over event's rows that decodes only columns of the given projection
+    /// (see [`RowsEventRows::with_projection`]).
+    pub fn rows_projected(
+        &'a self,
+        table_map_event: &'a TableMapEvent<'a>,
+        projection: &'a Projection,
+    ) -> RowsEventRows<'a> {
+        self.rows(table_map_event).with_projection(projection)
+    }
+
+    /// Returns the length of a serialized representation of this rows event data
+    /// (without the event header and footer).
+    pub fn serialized_len(&self, version: BinlogVersion) -> usize {
+        match self {
+            RowsEventData::WriteRowsEventV1(ev) => ev.len(version),
+            RowsEventData::UpdateRowsEventV1(ev) => ev.len(version),
+            RowsEventData::DeleteRowsEventV1(ev) => ev.len(version),
+            RowsEventData::WriteRowsEvent(ev) => ev.len(version),
+            RowsEventData::UpdateRowsEvent(ev) => ev.len(version),
+            RowsEventData::DeleteRowsEvent(ev) => ev.len(version),
+            RowsEventData::PartialUpdateRowsEvent(ev) => ev.len(version),
+        }
+    }
+
     pub fn into_owned(self) -> RowsEventData<'static> {
         match self {
             Self::WriteRowsEventV1(ev) => RowsEventData::WriteRowsEventV1(ev.into_owned()),
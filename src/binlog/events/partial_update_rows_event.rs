@@ -12,7 +12,7 @@ use std::io::{self};
 
 use crate::{
     binlog::{
-        consts::{BinlogVersion, EventType},
+        consts::{BinlogVersion, EventType, RowsEventFlags},
         BinlogCtx, BinlogEvent, BinlogStruct,
     },
     io::ParseBuf,
@@ -34,6 +34,21 @@ impl<'a> PartialUpdateRowsEvent<'a> {
         self.0.table_id()
     }
 
+    /// Defines the table identifier.
+    pub fn with_table_id(self, table_id: u64) -> Self {
+        Self(self.0.with_table_id(table_id))
+    }
+
+    /// Returns the raw rows event flags.
+    pub fn flags_raw(&self) -> u16 {
+        self.0.flags_raw()
+    }
+
+    /// Returns rows event flags. Unknown bits will be truncated.
+    pub fn flags(&self) -> RowsEventFlags {
+        self.0.flags()
+    }
+
     /// Returns the number of columns in the table.
     pub fn num_columns(&self) -> u64 {
         self.0.num_columns()
@@ -0,0 +1,80 @@
+// Copyright (c) 2023 Anatoly Ikorsky
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+use std::{cmp::min, convert::TryFrom, io};
+
+use crate::{
+    binlog::{
+        consts::{BinlogVersion, EventType},
+        BinlogCtx, BinlogEvent, BinlogStruct,
+    },
+    io::ParseBuf,
+    packets::GtidSet,
+    proto::{MyDeserialize, MySerialize},
+};
+
+use super::BinlogEventHeader;
+
+/// Previous GTIDs event (added in MySql 5.6).
+///
+/// Written at the beginning of every binlog file, it contains the set of GTIDs
+/// that were executed before the file was started.
+#[derive(Debug, Default, Clone, Eq, PartialEq, Hash)]
+pub struct PreviousGtidsEvent<'a> {
+    gtid_set: GtidSet<'a>,
+}
+
+impl<'a> PreviousGtidsEvent<'a> {
+    /// Creates a new instance.
+    pub fn new(gtid_set: GtidSet<'a>) -> Self {
+        Self { gtid_set }
+    }
+
+    /// Returns the set of GTIDs executed before this binlog file.
+    pub fn gtid_set(&self) -> &GtidSet<'a> {
+        &self.gtid_set
+    }
+
+    /// Returns the set of GTIDs executed before this binlog file.
+    pub fn into_gtid_set(self) -> GtidSet<'a> {
+        self.gtid_set
+    }
+
+    /// Returns a `'static` version of `self`.
+    pub fn into_owned(self) -> PreviousGtidsEvent<'static> {
+        PreviousGtidsEvent {
+            gtid_set: self.gtid_set.into_owned(),
+        }
+    }
+}
+
+impl<'de> MyDeserialize<'de> for PreviousGtidsEvent<'de> {
+    const SIZE: Option<usize> = None;
+    type Ctx = BinlogCtx<'de>;
+
+    fn deserialize(_ctx: Self::Ctx, buf: &mut ParseBuf<'de>) -> io::Result<Self> {
+        buf.parse(()).map(Self::new)
+    }
+}
+
+impl MySerialize for PreviousGtidsEvent<'_> {
+    fn serialize(&self, buf: &mut Vec<u8>) {
+        self.gtid_set.serialize(buf);
+    }
+}
+
+impl<'a> BinlogEvent<'a> for PreviousGtidsEvent<'a> {
+    const EVENT_TYPE: EventType = EventType::PREVIOUS_GTIDS_EVENT;
+}
+
+impl<'a> BinlogStruct<'a> for PreviousGtidsEvent<'a> {
+    fn len(&self, _version: BinlogVersion) -> usize {
+        let len = usize::try_from(self.gtid_set.encoded_len()).unwrap_or(usize::MAX);
+        min(len, u32::MAX as usize - BinlogEventHeader::LEN)
+    }
+}
@@ -12,18 +12,24 @@ use std::{
     convert::TryFrom,
     fmt,
     io::{self, Read},
+    ops::Range,
+    str::Utf8Error,
 };
 
-use byteorder::{LittleEndian, ReadBytesExt};
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
 use saturating::Saturating as S;
 
 use crate::{
     binlog::{
-        consts::{BinlogVersion, EventType, StatusVarKey},
+        consts::{
+            BinlogVersion, EventType, StatusVarKey, MAX_DBS_IN_EVENT_MTS, OVER_MAX_DBS_IN_EVENT_MTS,
+        },
+        misc::check_identifier,
+        xa::XaStatement,
         BinlogCtx, BinlogEvent, BinlogStruct,
     },
-    constants::{Flags2, SqlMode},
-    io::ParseBuf,
+    constants::{Flags2, SqlMode, UTF8MB4_GENERAL_CI},
+    io::{BufMutExt, ParseBuf},
     misc::{
         raw::{
             bytes::{BareU16Bytes, BareU8Bytes, EofBytes, NullBytes, U8Bytes},
@@ -64,24 +70,34 @@ pub struct QueryEvent<'a> {
     schema: RawBytes<'a, BareU8Bytes>,
     __skip: Skip<1>,
     /// The SQL query.
+    ///
+    /// Might be truncated (see [`BinlogCtx::max_query_len`]).
     query: RawBytes<'a, EofBytes>,
+    /// Length of the SQL query before truncation.
+    query_len: usize,
 }
 
 impl<'a> QueryEvent<'a> {
+    /// Returns a builder for a query event (see [`QueryEventBuilder`]).
+    pub fn builder() -> QueryEventBuilder<'a> {
+        QueryEventBuilder::default()
+    }
+
     /// Creates a new instance.
     pub fn new(status_vars: impl Into<Cow<'a, [u8]>>, schema: impl Into<Cow<'a, [u8]>>) -> Self {
-        let status_vars = StatusVars(RawBytes::new(status_vars));
+        let status_vars = StatusVars::new(status_vars);
         let schema = RawBytes::new(schema);
         Self {
             thread_id: Default::default(),
             execution_time: Default::default(),
             schema_len: RawInt::new(schema.len() as u8),
             error_code: Default::default(),
-            status_vars_len: RawInt::new(status_vars.0.len() as u16),
+            status_vars_len: RawInt::new(status_vars.len() as u16),
             status_vars,
             schema,
             __skip: Default::default(),
             query: Default::default(),
+            query_len: 0,
         }
     }
 
@@ -105,8 +121,8 @@ impl<'a> QueryEvent<'a> {
 
     /// Sets the `status_vars` value (max length is `u16::MAX).
     pub fn with_status_vars(mut self, status_vars: impl Into<Cow<'a, [u8]>>) -> Self {
-        self.status_vars = StatusVars(RawBytes::new(status_vars));
-        self.status_vars_len.0 = self.status_vars.0.len() as u16;
+        self.status_vars = StatusVars::new(status_vars);
+        self.status_vars_len.0 = self.status_vars.len() as u16;
         self
     }
 
@@ -120,6 +136,7 @@ impl<'a> QueryEvent<'a> {
     /// Sets the `query` value.
     pub fn with_query(mut self, query: impl Into<Cow<'a, [u8]>>) -> Self {
         self.query = RawBytes::new(query);
+        self.query_len = self.query.len();
         self
     }
 
@@ -152,7 +169,7 @@ impl<'a> QueryEvent<'a> {
     /// `status_vars` contains zero or more status variables. Each status variable consists of one
     /// byte identifying the variable stored, followed by the value of the variable.
     pub fn status_vars_raw(&'a self) -> &'a [u8] {
-        self.status_vars.0.as_bytes()
+        self.status_vars.as_bytes()
     }
 
     /// Returns an iterator over status variables.
@@ -172,6 +189,11 @@ impl<'a> QueryEvent<'a> {
         self.schema.as_str()
     }
 
+    /// Returns the `schema` value as a string (fails if it isn't a valid utf-8 string).
+    pub fn schema_str(&'a self) -> Result<&'a str, Utf8Error> {
+        std::str::from_utf8(self.schema.as_bytes())
+    }
+
     /// Returns the `query` value.
     ///
     /// `query` is the corresponding LOAD DATA INFILE statement.
@@ -184,6 +206,52 @@ impl<'a> QueryEvent<'a> {
         self.query.as_str()
     }
 
+    /// Returns the `query` value decoded using the client character set of the statement
+    /// (see [`QueryEvent::charset_client`]).
+    ///
+    /// `utf8mb4` is assumed if there is no `Charset` status variable. Returns an error
+    /// if the query can't be decoded (see [`crate::misc::charset::decode_text`]).
+    pub fn query_decoded(&'a self) -> io::Result<Cow<'a, str>> {
+        let collation_id = self.charset_client().unwrap_or(UTF8MB4_GENERAL_CI);
+        self.query.decode(collation_id as u32)
+    }
+
+    /// Returns the `charset_client` value of the `Charset` status variable (if it's present).
+    ///
+    /// It's the collation of the client connection that issued the statement.
+    pub fn charset_client(&'a self) -> Option<u16> {
+        match self
+            .status_vars
+            .get_status_var(StatusVarKey::Charset)?
+            .get_value()
+        {
+            Ok(StatusVarVal::Charset { charset_client, .. }) => Some(charset_client),
+            _ => None,
+        }
+    }
+
+    /// Parses the query as an XA statement (e.g. `XA COMMIT X'6162',X'',1`).
+    ///
+    /// Returns `None` if the query isn't an XA statement.
+    pub fn xa_statement(&'a self) -> Option<XaStatement<'static>> {
+        XaStatement::parse(&self.query())
+    }
+
+    /// Returns the length of the query as it was logged.
+    ///
+    /// It's greater than the length of [`QueryEvent::query_raw`] if the query was truncated
+    /// while parsing (see [`crate::binlog::events::Event::with_max_query_len`]).
+    pub fn query_len(&self) -> usize {
+        self.query_len
+    }
+
+    /// Returns `true` if the query was truncated while parsing.
+    ///
+    /// Serialization of a truncated event won't round-trip.
+    pub fn is_query_truncated(&self) -> bool {
+        self.query.len() < self.query_len
+    }
+
     pub fn into_owned(self) -> QueryEvent<'static> {
         QueryEvent {
             thread_id: self.thread_id,
@@ -195,7 +263,82 @@ impl<'a> QueryEvent<'a> {
             schema: self.schema.into_owned(),
             __skip: self.__skip,
             query: self.query.into_owned(),
+            query_len: self.query_len,
+        }
+    }
+}
+
+/// Builder of a [`QueryEvent`].
+///
+/// Unlike `QueryEvent::with_*` methods it won't silently truncate lengths of fields
+/// that don't fit the event.
+#[derive(Debug, Default, Clone, Eq, PartialEq, Hash)]
+pub struct QueryEventBuilder<'a> {
+    thread_id: u32,
+    execution_time: u32,
+    error_code: u16,
+    status_vars: Cow<'a, [u8]>,
+    schema: Cow<'a, [u8]>,
+    query: Cow<'a, [u8]>,
+}
+
+impl<'a> QueryEventBuilder<'a> {
+    /// Sets the ID of the thread that issued the statement (defaults to `0`).
+    pub fn with_thread_id(mut self, thread_id: u32) -> Self {
+        self.thread_id = thread_id;
+        self
+    }
+
+    /// Sets the statement execution time in seconds (defaults to `0`).
+    pub fn with_execution_time(mut self, execution_time: u32) -> Self {
+        self.execution_time = execution_time;
+        self
+    }
+
+    /// Sets the error code generated by the statement (defaults to `0`).
+    pub fn with_error_code(mut self, error_code: u16) -> Self {
+        self.error_code = error_code;
+        self
+    }
+
+    /// Sets raw status variables (defaults to empty, see [`StatusVars::builder`]).
+    pub fn with_status_vars(mut self, status_vars: impl Into<Cow<'a, [u8]>>) -> Self {
+        self.status_vars = status_vars.into();
+        self
+    }
+
+    /// Sets the default database of the statement (defaults to empty).
+    pub fn with_schema(mut self, schema: impl Into<Cow<'a, [u8]>>) -> Self {
+        self.schema = schema.into();
+        self
+    }
+
+    /// Sets the SQL text.
+    pub fn with_query(mut self, query: impl Into<Cow<'a, [u8]>>) -> Self {
+        self.query = query.into();
+        self
+    }
+
+    /// Builds the event.
+    ///
+    /// Returns an `InvalidInput` error if the schema isn't a valid identifier (a utf-8 string
+    /// of at most [`NAME_CHAR_LEN`] characters) or if status variables are longer
+    /// than `u16::MAX` bytes.
+    ///
+    /// [`NAME_CHAR_LEN`]: crate::binlog::misc::NAME_CHAR_LEN
+    pub fn build(self) -> io::Result<QueryEvent<'a>> {
+        check_identifier(&self.schema, "schema name")?;
+        if self.status_vars.len() > u16::MAX as usize {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "status variables are too long",
+            ));
         }
+        Ok(QueryEvent::new(self.status_vars, self.schema)
+            .with_thread_id(self.thread_id)
+            .with_execution_time(self.execution_time)
+            .with_error_code(self.error_code)
+            .with_query(self.query))
     }
 }
 
@@ -219,7 +362,12 @@ impl<'de> MyDeserialize<'de> for QueryEvent<'de> {
         let status_vars = buf.parse(*status_vars_len)?;
         let schema = buf.parse(*schema_len as usize)?;
         let __skip = buf.parse(())?;
-        let query = buf.parse(())?;
+        let query = buf.eat_all();
+        let query_len = query.len();
+        let query = match ctx.max_query_len {
+            Some(max_query_len) if max_query_len < query_len => &query[..max_query_len],
+            _ => query,
+        };
 
         Ok(Self {
             thread_id,
@@ -230,7 +378,8 @@ impl<'de> MyDeserialize<'de> for QueryEvent<'de> {
             status_vars,
             schema,
             __skip,
-            query,
+            query: RawBytes::new(query),
+            query_len,
         })
     }
 }
@@ -262,7 +411,7 @@ impl<'a> BinlogStruct<'a> for QueryEvent<'a> {
         len += S(1);
         len += S(2);
         len += S(2);
-        len += S(min(self.status_vars.0.len(), u16::MAX as usize));
+        len += S(min(self.status_vars.len(), u16::MAX as usize));
         len += S(min(self.schema.0.len(), u8::MAX as usize));
         len += S(1);
         len += S(self.query.0.len());
@@ -299,6 +448,7 @@ pub enum StatusVarVal<'a> {
         username: RawBytes<'a, U8Bytes>,
         hostname: RawBytes<'a, U8Bytes>,
     },
+    /// Will be empty if the number of databases exceeds [`MAX_DBS_IN_EVENT_MTS`].
     UpdatedDbNames(Vec<RawBytes<'a, NullBytes>>),
     Microseconds(u32),
     /// Ignored.
@@ -409,20 +559,24 @@ impl StatusVar<'_> {
             }
             StatusVarKey::UpdatedDbNames => {
                 let mut read = self.value;
-                let count = read.read_u8().map_err(|_| self.value)? as usize;
-                let mut names = Vec::with_capacity(count);
+                let count = read.read_u8().map_err(|_| self.value)?;
+                if count == OVER_MAX_DBS_IN_EVENT_MTS {
+                    return Ok(StatusVarVal::UpdatedDbNames(Vec::new()));
+                }
+                let mut names = Vec::with_capacity(count as usize);
 
                 for _ in 0..count {
                     let index = read.iter().position(|x| *x == 0).ok_or(self.value)?;
                     names.push(RawBytes::new(&read[..index]));
-                    read = &read[index..];
+                    read = &read[index + 1..];
                 }
 
                 Ok(StatusVarVal::UpdatedDbNames(names))
             }
             StatusVarKey::Microseconds => {
-                let mut read = self.value;
-                let val = read.read_u32::<LittleEndian>().map_err(|_| self.value)?;
+                let val = ParseBuf(self.value)
+                    .checked_eat_u24_le()
+                    .ok_or(self.value)?;
                 Ok(StatusVarVal::Microseconds(val))
             }
             StatusVarKey::CommitTs => Ok(StatusVarVal::CommitTs(self.value)),
@@ -467,9 +621,37 @@ impl fmt::Debug for StatusVar<'_> {
 
 /// Status variables of a QueryEvent.
 #[derive(Clone, Eq, PartialEq, Hash)]
-pub struct StatusVars<'a>(pub RawBytes<'a, BareU16Bytes>);
+pub struct StatusVars<'a>(#[doc(hidden)] pub RawBytes<'a, BareU16Bytes>);
 
 impl<'a> StatusVars<'a> {
+    /// Creates status variables from their serialized representation
+    /// (see [`StatusVars::builder`] to construct it).
+    ///
+    /// Bytes beyond `u16::MAX` are truncated during serialization.
+    pub fn new(bytes: impl Into<Cow<'a, [u8]>>) -> Self {
+        Self(RawBytes::new(bytes))
+    }
+
+    /// Returns the serialized representation of status variables.
+    pub fn as_bytes(&self) -> &[u8] {
+        self.0.as_bytes()
+    }
+
+    /// Returns the length of the serialized representation.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Returns `true` if there are no status variables.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Returns a builder of status variables.
+    pub fn builder() -> StatusVarsBuilder<'a> {
+        StatusVarsBuilder::default()
+    }
+
     /// Returns an iterator over QueryEvent status variables.
     pub fn iter(&'a self) -> StatusVarsIterator<'a> {
         StatusVarsIterator::new(self.0.as_bytes())
@@ -481,11 +663,296 @@ impl<'a> StatusVars<'a> {
             .find_map(|var| if var.key == needle { Some(var) } else { None })
     }
 
+    /// Returns the range of a status variable value within status vars (if it's present).
+    pub(crate) fn status_var_range(&'a self, needle: StatusVarKey) -> Option<Range<usize>> {
+        let mut iter = self.iter();
+        while let Some(var) = iter.next() {
+            if var.key == needle {
+                return Some(iter.pos - var.value.len()..iter.pos);
+            }
+        }
+        None
+    }
+
     pub fn into_owned(self) -> StatusVars<'static> {
         StatusVars(self.0.into_owned())
     }
 }
 
+impl<'a> From<StatusVars<'a>> for Cow<'a, [u8]> {
+    fn from(status_vars: StatusVars<'a>) -> Self {
+        status_vars.0.into_inner()
+    }
+}
+
+/// `(username, hostname)` pair.
+type Invoker<'a> = (Cow<'a, [u8]>, Cow<'a, [u8]>);
+
+/// Builder of [`StatusVars`].
+///
+/// Variables are written in the order of their keys. Unset variables are omitted,
+/// so the replica will use its defaults for them.
+#[derive(Debug, Default, Clone, Eq, PartialEq, Hash)]
+pub struct StatusVarsBuilder<'a> {
+    flags2: Option<Flags2>,
+    sql_mode: Option<SqlMode>,
+    auto_increment: Option<(u16, u16)>,
+    charset: Option<(u16, u16, u16)>,
+    time_zone: Option<Cow<'a, [u8]>>,
+    catalog: Option<Cow<'a, [u8]>>,
+    lc_time_names: Option<u16>,
+    charset_database: Option<u16>,
+    table_map_for_update: Option<u64>,
+    invoker: Option<Invoker<'a>>,
+    updated_db_names: Option<Vec<Cow<'a, [u8]>>>,
+    microseconds: Option<u32>,
+    explicit_defaults_for_timestamp: Option<bool>,
+    ddl_logged_with_xid: Option<u64>,
+    default_collation_for_utf8mb4: Option<u16>,
+    sql_require_primary_key: Option<u8>,
+    default_table_encryption: Option<u8>,
+}
+
+impl<'a> StatusVarsBuilder<'a> {
+    /// Sets the `Flags2` status variable.
+    pub fn with_flags2(mut self, flags2: Flags2) -> Self {
+        self.flags2 = Some(flags2);
+        self
+    }
+
+    /// Sets the `SqlMode` status variable.
+    pub fn with_sql_mode(mut self, sql_mode: SqlMode) -> Self {
+        self.sql_mode = Some(sql_mode);
+        self
+    }
+
+    /// Sets the `AutoIncrement` status variable
+    /// (`auto_increment_increment` and `auto_increment_offset`).
+    pub fn with_auto_increment(mut self, increment: u16, offset: u16) -> Self {
+        self.auto_increment = Some((increment, offset));
+        self
+    }
+
+    /// Sets the `Charset` status variable
+    /// (`character_set_client`, `collation_connection` and `collation_server`).
+    pub fn with_charset(
+        mut self,
+        charset_client: u16,
+        collation_connection: u16,
+        collation_server: u16,
+    ) -> Self {
+        self.charset = Some((charset_client, collation_connection, collation_server));
+        self
+    }
+
+    /// Sets the `TimeZone` status variable (max length is `u8::MAX`).
+    pub fn with_time_zone(mut self, time_zone: impl Into<Cow<'a, [u8]>>) -> Self {
+        self.time_zone = Some(time_zone.into());
+        self
+    }
+
+    /// Sets the catalog (written as the `CatalogNz` status variable, max length is `u8::MAX`).
+    pub fn with_catalog(mut self, catalog: impl Into<Cow<'a, [u8]>>) -> Self {
+        self.catalog = Some(catalog.into());
+        self
+    }
+
+    /// Sets the `LcTimeNames` status variable.
+    pub fn with_lc_time_names(mut self, lc_time_names: u16) -> Self {
+        self.lc_time_names = Some(lc_time_names);
+        self
+    }
+
+    /// Sets the `CharsetDatabase` status variable (`collation_database`).
+    pub fn with_charset_database(mut self, charset_database: u16) -> Self {
+        self.charset_database = Some(charset_database);
+        self
+    }
+
+    /// Sets the `TableMapForUpdate` status variable.
+    pub fn with_table_map_for_update(mut self, table_map_for_update: u64) -> Self {
+        self.table_map_for_update = Some(table_map_for_update);
+        self
+    }
+
+    /// Sets the `Invoker` status variable (max length of both values is `u8::MAX`).
+    pub fn with_invoker(
+        mut self,
+        username: impl Into<Cow<'a, [u8]>>,
+        hostname: impl Into<Cow<'a, [u8]>>,
+    ) -> Self {
+        self.invoker = Some((username.into(), hostname.into()));
+        self
+    }
+
+    /// Sets the `UpdatedDbNames` status variable.
+    ///
+    /// Names are omitted if there are more than [`MAX_DBS_IN_EVENT_MTS`] of them
+    /// (this is what the server does).
+    pub fn with_updated_db_names<T>(mut self, names: impl IntoIterator<Item = T>) -> Self
+    where
+        T: Into<Cow<'a, [u8]>>,
+    {
+        self.updated_db_names = Some(names.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Sets the `Microseconds` status variable (must be less than `1_000_000`).
+    pub fn with_microseconds(mut self, microseconds: u32) -> Self {
+        self.microseconds = Some(microseconds);
+        self
+    }
+
+    /// Sets the `ExplicitDefaultsForTimestamp` status variable.
+    pub fn with_explicit_defaults_for_timestamp(mut self, value: bool) -> Self {
+        self.explicit_defaults_for_timestamp = Some(value);
+        self
+    }
+
+    /// Sets the `DdlLoggedWithXid` status variable.
+    pub fn with_ddl_logged_with_xid(mut self, xid: u64) -> Self {
+        self.ddl_logged_with_xid = Some(xid);
+        self
+    }
+
+    /// Sets the `DefaultCollationForUtf8mb4` status variable.
+    pub fn with_default_collation_for_utf8mb4(mut self, collation: u16) -> Self {
+        self.default_collation_for_utf8mb4 = Some(collation);
+        self
+    }
+
+    /// Sets the `SqlRequirePrimaryKey` status variable.
+    pub fn with_sql_require_primary_key(mut self, value: u8) -> Self {
+        self.sql_require_primary_key = Some(value);
+        self
+    }
+
+    /// Sets the `DefaultTableEncryption` status variable.
+    pub fn with_default_table_encryption(mut self, value: u8) -> Self {
+        self.default_table_encryption = Some(value);
+        self
+    }
+
+    /// Serializes status variables.
+    ///
+    /// Returns an `InvalidInput` error if some value doesn't fit its status variable
+    /// or if the result is longer than `u16::MAX` bytes.
+    pub fn build(self) -> io::Result<StatusVars<'static>> {
+        fn put_u8_bytes(buf: &mut Vec<u8>, bytes: &[u8], what: &str) -> io::Result<()> {
+            let len = u8::try_from(bytes.len()).map_err(|_| {
+                io::Error::new(io::ErrorKind::InvalidInput, format!("{} is too long", what))
+            })?;
+            buf.push(len);
+            buf.extend_from_slice(bytes);
+            Ok(())
+        }
+
+        let mut buf = Vec::new();
+
+        if let Some(flags2) = self.flags2 {
+            buf.push(StatusVarKey::Flags2 as u8);
+            buf.write_u32::<LittleEndian>(flags2.bits())?;
+        }
+        if let Some(sql_mode) = self.sql_mode {
+            buf.push(StatusVarKey::SqlMode as u8);
+            buf.write_u64::<LittleEndian>(sql_mode.bits())?;
+        }
+        if let Some((increment, offset)) = self.auto_increment {
+            buf.push(StatusVarKey::AutoIncrement as u8);
+            buf.write_u16::<LittleEndian>(increment)?;
+            buf.write_u16::<LittleEndian>(offset)?;
+        }
+        if let Some((client, connection, server)) = self.charset {
+            buf.push(StatusVarKey::Charset as u8);
+            buf.write_u16::<LittleEndian>(client)?;
+            buf.write_u16::<LittleEndian>(connection)?;
+            buf.write_u16::<LittleEndian>(server)?;
+        }
+        if let Some(time_zone) = self.time_zone {
+            buf.push(StatusVarKey::TimeZone as u8);
+            put_u8_bytes(&mut buf, &time_zone, "time zone")?;
+        }
+        if let Some(catalog) = self.catalog {
+            buf.push(StatusVarKey::CatalogNz as u8);
+            put_u8_bytes(&mut buf, &catalog, "catalog")?;
+        }
+        if let Some(lc_time_names) = self.lc_time_names {
+            buf.push(StatusVarKey::LcTimeNames as u8);
+            buf.write_u16::<LittleEndian>(lc_time_names)?;
+        }
+        if let Some(charset_database) = self.charset_database {
+            buf.push(StatusVarKey::CharsetDatabase as u8);
+            buf.write_u16::<LittleEndian>(charset_database)?;
+        }
+        if let Some(table_map_for_update) = self.table_map_for_update {
+            buf.push(StatusVarKey::TableMapForUpdate as u8);
+            buf.write_u64::<LittleEndian>(table_map_for_update)?;
+        }
+        if let Some((username, hostname)) = self.invoker {
+            buf.push(StatusVarKey::Invoker as u8);
+            put_u8_bytes(&mut buf, &username, "invoker username")?;
+            put_u8_bytes(&mut buf, &hostname, "invoker hostname")?;
+        }
+        if let Some(names) = self.updated_db_names {
+            buf.push(StatusVarKey::UpdatedDbNames as u8);
+            if names.len() > MAX_DBS_IN_EVENT_MTS {
+                buf.push(OVER_MAX_DBS_IN_EVENT_MTS);
+            } else {
+                buf.push(names.len() as u8);
+                for name in names {
+                    if name.contains(&0) {
+                        return Err(io::Error::new(
+                            io::ErrorKind::InvalidInput,
+                            "database name contains a NULL byte",
+                        ));
+                    }
+                    buf.extend_from_slice(&name);
+                    buf.push(0);
+                }
+            }
+        }
+        if let Some(microseconds) = self.microseconds {
+            if microseconds >= 1_000_000 {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "microseconds value is out of range",
+                ));
+            }
+            buf.push(StatusVarKey::Microseconds as u8);
+            buf.put_u24_le(microseconds);
+        }
+        if let Some(value) = self.explicit_defaults_for_timestamp {
+            buf.push(StatusVarKey::ExplicitDefaultsForTimestamp as u8);
+            buf.push(value as u8);
+        }
+        if let Some(xid) = self.ddl_logged_with_xid {
+            buf.push(StatusVarKey::DdlLoggedWithXid as u8);
+            buf.write_u64::<LittleEndian>(xid)?;
+        }
+        if let Some(collation) = self.default_collation_for_utf8mb4 {
+            buf.push(StatusVarKey::DefaultCollationForUtf8mb4 as u8);
+            buf.write_u16::<LittleEndian>(collation)?;
+        }
+        if let Some(value) = self.sql_require_primary_key {
+            buf.push(StatusVarKey::SqlRequirePrimaryKey as u8);
+            buf.push(value);
+        }
+        if let Some(value) = self.default_table_encryption {
+            buf.push(StatusVarKey::DefaultTableEncryption as u8);
+            buf.push(value);
+        }
+
+        if buf.len() > u16::MAX as usize {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "status variables are too long",
+            ));
+        }
+
+        Ok(StatusVars::new(buf))
+    }
+}
+
 impl<'de> MyDeserialize<'de> for StatusVars<'de> {
     const SIZE: Option<usize> = None;
     type Ctx = u16;
@@ -573,7 +1040,10 @@ impl<'a> Iterator for StatusVarsIterator<'a> {
             }
             StatusVarKey::UpdatedDbNames => {
                 let mut total = 1;
-                let count = *self.status_vars.get(self.pos)? as usize;
+                let mut count = *self.status_vars.get(self.pos)?;
+                if count == OVER_MAX_DBS_IN_EVENT_MTS {
+                    count = 0;
+                }
                 for _ in 0..count {
                     while *self.status_vars.get(self.pos + total)? != 0x00 {
                         total += 1;
@@ -32,6 +32,16 @@ pub struct RandEvent {
     pub seed2: RawInt<LeU64>,
 }
 
+impl RandEvent {
+    /// Creates a new instance.
+    pub fn new(seed1: u64, seed2: u64) -> Self {
+        Self {
+            seed1: RawInt::new(seed1),
+            seed2: RawInt::new(seed2),
+        }
+    }
+}
+
 impl<'de> MyDeserialize<'de> for RandEvent {
     const SIZE: Option<usize> = Some(16);
     type Ctx = BinlogCtx<'de>;
@@ -57,6 +67,6 @@ impl<'a> BinlogEvent<'a> for RandEvent {
 
 impl<'a> BinlogStruct<'a> for RandEvent {
     fn len(&self, _version: BinlogVersion) -> usize {
-        8
+        16
     }
 }
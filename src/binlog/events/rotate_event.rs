@@ -13,7 +13,7 @@ use saturating::Saturating as S;
 use crate::{
     binlog::{
         consts::{BinlogVersion, EventType},
-        BinlogCtx, BinlogEvent, BinlogStruct,
+        BinlogCtx, BinlogEvent, BinlogFileHeader, BinlogStruct,
     },
     io::ParseBuf,
     misc::raw::{bytes::EofBytes, int::*, RawBytes},
@@ -70,6 +70,36 @@ impl<'a> RotateEvent<'a> {
         self.name.as_str()
     }
 
+    /// Returns the name of the binlog to rotate to and the position to continue from.
+    ///
+    /// Position defaults to `4` (right after the binlog file header) if the event
+    /// doesn't define it (binlog version 1).
+    ///
+    /// Returns an error if the name is empty, isn't a valid UTF-8 string or contains
+    /// path separators or NUL bytes.
+    pub fn next_file(&self) -> io::Result<(String, u64)> {
+        let name = std::str::from_utf8(self.name.as_bytes()).map_err(|err| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("invalid binlog name in rotate event: {}", err),
+            )
+        })?;
+
+        if name.is_empty() || name.contains(&['/', '\\', '\0'][..]) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("invalid binlog name in rotate event: {:?}", name),
+            ));
+        }
+
+        let position = match self.position() {
+            0 => BinlogFileHeader::LEN as u64,
+            position => position,
+        };
+
+        Ok((name.to_owned(), position))
+    }
+
     pub fn into_owned(self) -> RotateEvent<'static> {
         RotateEvent {
             position: self.position,
@@ -15,7 +15,10 @@ use saturating::Saturating as S;
 use crate::{
     binlog::{
         consts::{BinlogVersion, EventType, RowsEventFlags},
-        row::BinlogRow,
+        mariadb::decompress_event_data,
+        projection::Projection,
+        row::{BinlogRow, EnumAndSetNames, GeneratedColumn, SharedColumns},
+        value::serialize_value,
         BinlogCtx,
     },
     io::ParseBuf,
@@ -28,9 +31,13 @@ use crate::{
         unexpected_buf_eof,
     },
     proto::{MyDeserialize, MySerialize},
+    value::Value,
 };
 
-use super::{BinlogEventHeader, TableMapEvent};
+use super::{
+    BinlogEventHeader, DeleteRowsEvent, RowsEventData, TableMapEvent, UpdateRowsEvent,
+    WriteRowsEvent,
+};
 
 /// Common base structure for all row-containing binary log events.
 #[derive(Debug, Clone, Eq, PartialEq, Hash)]
@@ -63,6 +70,8 @@ pub struct RowsEvent<'a> {
     columns_after_image: Option<RawBytes<'a, BareBytes<0x2000000000000000>>>,
     /// A sequence of zero or more rows. The end is determined by the size of the event.
     ///
+    /// It's compressed for MariaDB compressed rows events (see `uncompressed_rows_data`).
+    ///
     /// Each row has the following format:
     ///
     /// *   A Bit-field indicating whether each field in the row is NULL. Only columns that
@@ -75,6 +84,8 @@ pub struct RowsEvent<'a> {
     ///     is equal to the number of zero bits in the previous field. (not counting padding
     ///     bits in the last byte).
     rows_data: RawBytes<'a, EofBytes>,
+    /// Decompressed `rows_data` of a MariaDB compressed rows event.
+    uncompressed_rows_data: Option<Vec<u8>>,
 }
 
 impl<'a> RowsEvent<'a> {
@@ -88,6 +99,22 @@ impl<'a> RowsEvent<'a> {
         self.table_id.0
     }
 
+    /// Defines the table identifier.
+    pub fn with_table_id(mut self, table_id: u64) -> Self {
+        self.table_id = RawInt::new(table_id);
+        self
+    }
+
+    /// Returns the raw rows event flags.
+    pub fn flags_raw(&self) -> u16 {
+        self.flags.0
+    }
+
+    /// Returns rows event flags. Unknown bits will be truncated.
+    pub fn flags(&self) -> RowsEventFlags {
+        self.flags.get()
+    }
+
     /// Returns the number of columns in the table.
     pub fn num_columns(&self) -> u64 {
         self.num_columns.0
@@ -119,9 +146,18 @@ impl<'a> RowsEvent<'a> {
         }
     }
 
-    /// Returns raw rows data.
+    /// Returns `true` if this is a MariaDB compressed rows event
+    /// (e.g. [`EventType::MARIADB_WRITE_ROWS_COMPRESSED_EVENT`]).
+    pub fn is_compressed(&self) -> bool {
+        uncompressed_event_type(self.event_type) != self.event_type
+    }
+
+    /// Returns raw rows data (decompressed, if this is a MariaDB compressed rows event).
     pub fn rows_data(&'a self) -> &'a [u8] {
-        self.rows_data.as_bytes()
+        match self.uncompressed_rows_data {
+            Some(ref data) => data,
+            None => self.rows_data.as_bytes(),
+        }
     }
 
     /// Returns length of this event in bytes.
@@ -132,8 +168,10 @@ impl<'a> RowsEvent<'a> {
 
         len += S(6); // table_id
         len += S(2); // flags
-        len += S(2); // extra-data len
-        len += S(min(self.extra_data.len(), u16::MAX as usize - 2)); // extra data
+        if self.has_extra_data() {
+            len += S(2); // extra-data len
+            len += S(min(self.extra_data.len(), u16::MAX as usize - 2)); // extra data
+        }
         len += S(crate::misc::lenenc_int_len(self.num_columns()) as usize); // number of columns
         let bitmap_len = (self.num_columns() as usize + 7) / 8;
         if self.columns_before_image.is_some() {
@@ -147,13 +185,28 @@ impl<'a> RowsEvent<'a> {
         min(len.0, u32::MAX as usize - BinlogEventHeader::LEN)
     }
 
+    /// Whether the serialized representation contains the extra data (only for v2 events).
+    fn has_extra_data(&self) -> bool {
+        let event_type = uncompressed_event_type(self.event_type);
+        event_type == EventType::WRITE_ROWS_EVENT
+            || event_type == EventType::UPDATE_ROWS_EVENT
+            || event_type == EventType::DELETE_ROWS_EVENT
+            || event_type == EventType::PARTIAL_UPDATE_ROWS_EVENT
+    }
+
     /// Returns an iterator over event's rows given the corresponding `TableMapEvent`.
     pub fn rows<'b>(&'b self, table_map_event: &'b TableMapEvent<'b>) -> RowsEventRows<'b> {
-        RowsEventRows {
-            rows_event: self,
-            table_map_event,
-            rows_data: ParseBuf(self.rows_data.as_bytes()),
-        }
+        RowsEventRows::new(self, table_map_event, ParseBuf(self.rows_data()))
+    }
+
+    /// Returns an iterator over event's rows that decodes only columns of the given projection
+    /// (see [`RowsEventRows::with_projection`]).
+    pub fn rows_projected<'b>(
+        &'b self,
+        table_map_event: &'b TableMapEvent<'b>,
+        projection: &'b Projection,
+    ) -> RowsEventRows<'b> {
+        self.rows(table_map_event).with_projection(projection)
     }
 
     pub fn into_owned(self) -> RowsEvent<'static> {
@@ -166,7 +219,145 @@ impl<'a> RowsEvent<'a> {
             columns_before_image: self.columns_before_image.map(|x| x.into_owned()),
             columns_after_image: self.columns_after_image.map(|x| x.into_owned()),
             rows_data: self.rows_data.into_owned(),
+            uncompressed_rows_data: self.uncompressed_rows_data,
+        }
+    }
+}
+
+/// Returns the type of the uncompressed counterpart of a MariaDB compressed rows event
+/// (other types are returned as is).
+fn uncompressed_event_type(event_type: EventType) -> EventType {
+    match event_type {
+        EventType::MARIADB_WRITE_ROWS_COMPRESSED_EVENT_V1 => EventType::WRITE_ROWS_EVENT_V1,
+        EventType::MARIADB_UPDATE_ROWS_COMPRESSED_EVENT_V1 => EventType::UPDATE_ROWS_EVENT_V1,
+        EventType::MARIADB_DELETE_ROWS_COMPRESSED_EVENT_V1 => EventType::DELETE_ROWS_EVENT_V1,
+        EventType::MARIADB_WRITE_ROWS_COMPRESSED_EVENT => EventType::WRITE_ROWS_EVENT,
+        EventType::MARIADB_UPDATE_ROWS_COMPRESSED_EVENT => EventType::UPDATE_ROWS_EVENT,
+        EventType::MARIADB_DELETE_ROWS_COMPRESSED_EVENT => EventType::DELETE_ROWS_EVENT,
+        other => other,
+    }
+}
+
+/// `(before-image, after-image)` pair.
+type RowImages = (Option<Vec<Value>>, Option<Vec<Value>>);
+
+/// Builder of a rows event (see [`WriteRowsEvent::builder`], [`UpdateRowsEvent::builder`]
+/// and [`DeleteRowsEvent::builder`]).
+///
+/// Values are encoded according to column types and metadata of the given table map event
+/// (see [`crate::binlog::value::BinlogValue::serialize_bin`]). Every row must contain a value
+/// for every column of the table, i.e. it's a full row image.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RowsEventBuilder<'a> {
+    event_type: EventType,
+    table_map_event: &'a TableMapEvent<'a>,
+    flags: RowsEventFlags,
+    rows: Vec<RowImages>,
+}
+
+impl<'a> RowsEventBuilder<'a> {
+    pub(super) fn new(event_type: EventType, table_map_event: &'a TableMapEvent<'a>) -> Self {
+        Self {
+            event_type,
+            table_map_event,
+            flags: RowsEventFlags::STMT_END,
+            rows: Vec::new(),
+        }
+    }
+
+    /// Defines rows event flags (defaults to `STMT_END`).
+    pub fn with_flags(mut self, flags: RowsEventFlags) -> Self {
+        self.flags = flags;
+        self
+    }
+
+    /// Adds a row to insert (for a WRITE event) or to delete (for a DELETE event).
+    pub fn with_row(mut self, row: impl IntoIterator<Item = Value>) -> Self {
+        let row = Some(row.into_iter().collect());
+        if self.event_type == EventType::DELETE_ROWS_EVENT {
+            self.rows.push((row, None));
+        } else {
+            self.rows.push((None, row));
+        }
+        self
+    }
+
+    /// Adds an updated row (for an UPDATE event).
+    pub fn with_update(
+        mut self,
+        before: impl IntoIterator<Item = Value>,
+        after: impl IntoIterator<Item = Value>,
+    ) -> Self {
+        self.rows.push((
+            Some(before.into_iter().collect()),
+            Some(after.into_iter().collect()),
+        ));
+        self
+    }
+
+    /// Builds the event.
+    ///
+    /// Returns an `InvalidInput` error if a row doesn't match the table,
+    /// or if images doesn't match the event type (e.g. a row without
+    /// the before-image is given for an UPDATE event).
+    pub fn build(self) -> io::Result<RowsEventData<'static>> {
+        let invalid = |msg: &str| io::Error::new(io::ErrorKind::InvalidInput, msg.to_owned());
+
+        let tme = self.table_map_event;
+        let num_columns = tme.columns_count() as usize;
+        let mut columns = Vec::with_capacity(num_columns);
+        for i in 0..num_columns {
+            let column_type = match tme.get_column_type(i) {
+                Ok(Some(ty)) => ty,
+                Ok(None) => return Err(invalid("No column type")),
+                Err(e) => return Err(io::Error::new(io::ErrorKind::InvalidInput, e)),
+            };
+            columns.push((column_type, tme.get_column_metadata(i).unwrap_or(&[])));
         }
+
+        let is_update = self.event_type == EventType::UPDATE_ROWS_EVENT;
+        let is_delete = self.event_type == EventType::DELETE_ROWS_EVENT;
+
+        let mut rows_data = Vec::new();
+        for (before, after) in &self.rows {
+            if before.is_some() != (is_update || is_delete) || after.is_some() == is_delete {
+                return Err(invalid("row images doesn't match the event type"));
+            }
+            for image in before.iter().chain(after) {
+                if image.len() != num_columns {
+                    return Err(invalid("number of values doesn't match the table"));
+                }
+                let mut null_bitmap = bitvec![u8, Lsb0; 0; num_columns];
+                for (i, value) in image.iter().enumerate() {
+                    null_bitmap.set(i, *value == Value::NULL);
+                }
+                rows_data.extend_from_slice(null_bitmap.as_raw_slice());
+                for (value, (column_type, column_meta)) in image.iter().zip(&columns) {
+                    if *value != Value::NULL {
+                        serialize_value(value, *column_type, column_meta, &mut rows_data)?;
+                    }
+                }
+            }
+        }
+
+        let columns_image = RawBytes::new(bitvec![u8, Lsb0; 1; num_columns].into_vec());
+        let event = RowsEvent {
+            event_type: self.event_type,
+            table_id: RawInt::new(tme.table_id()),
+            flags: RawFlags::new(self.flags.bits()),
+            extra_data: RawBytes::new(Vec::new()),
+            num_columns: RawInt::new(num_columns as u64),
+            columns_before_image: (is_update || is_delete).then(|| columns_image.clone()),
+            columns_after_image: (!is_delete).then_some(columns_image),
+            rows_data: RawBytes::new(rows_data),
+            uncompressed_rows_data: None,
+        };
+
+        Ok(match self.event_type {
+            EventType::UPDATE_ROWS_EVENT => RowsEventData::UpdateRowsEvent(UpdateRowsEvent(event)),
+            EventType::DELETE_ROWS_EVENT => RowsEventData::DeleteRowsEvent(DeleteRowsEvent(event)),
+            _ => RowsEventData::WriteRowsEvent(WriteRowsEvent(event)),
+        })
     }
 }
 
@@ -178,6 +369,16 @@ pub struct RowsEventCtx<'a> {
     pub binlog_ctx: BinlogCtx<'a>,
 }
 
+impl<'a> RowsEventCtx<'a> {
+    /// Creates a new instance.
+    pub fn new(event_type: EventType, binlog_ctx: BinlogCtx<'a>) -> Self {
+        Self {
+            event_type,
+            binlog_ctx,
+        }
+    }
+}
+
 impl<'de> MyDeserialize<'de> for RowsEvent<'de> {
     const SIZE: Option<usize> = None;
     type Ctx = RowsEventCtx<'de>;
@@ -188,12 +389,14 @@ impl<'de> MyDeserialize<'de> for RowsEvent<'de> {
             .fde
             .get_event_type_header_length(ctx.event_type);
 
-        let is_delete_event = ctx.event_type == EventType::DELETE_ROWS_EVENT
-            || ctx.event_type == EventType::DELETE_ROWS_EVENT_V1;
+        let event_type = uncompressed_event_type(ctx.event_type);
+
+        let is_delete_event = event_type == EventType::DELETE_ROWS_EVENT
+            || event_type == EventType::DELETE_ROWS_EVENT_V1;
 
-        let is_update_event = ctx.event_type == EventType::UPDATE_ROWS_EVENT
-            || ctx.event_type == EventType::UPDATE_ROWS_EVENT_V1
-            || ctx.event_type == EventType::PARTIAL_UPDATE_ROWS_EVENT;
+        let is_update_event = event_type == EventType::UPDATE_ROWS_EVENT
+            || event_type == EventType::UPDATE_ROWS_EVENT_V1
+            || event_type == EventType::PARTIAL_UPDATE_ROWS_EVENT;
 
         let table_id = if post_header_len == 6 {
             // old server
@@ -233,7 +436,12 @@ impl<'de> MyDeserialize<'de> for RowsEvent<'de> {
             columns_after_image = Some(buf.parse(bitmap_len)?);
         }
 
-        let rows_data = buf.parse(())?;
+        let rows_data: RawBytes<EofBytes> = buf.parse(())?;
+        let uncompressed_rows_data = if event_type != ctx.event_type {
+            Some(decompress_event_data(rows_data.as_bytes())?)
+        } else {
+            None
+        };
 
         Ok(Self {
             event_type: ctx.event_type,
@@ -244,6 +452,7 @@ impl<'de> MyDeserialize<'de> for RowsEvent<'de> {
             columns_before_image,
             columns_after_image,
             rows_data,
+            uncompressed_rows_data,
         })
     }
 }
@@ -253,11 +462,7 @@ impl MySerialize for RowsEvent<'_> {
         self.table_id.serialize(&mut *buf);
         self.flags.serialize(&mut *buf);
 
-        if self.event_type == EventType::WRITE_ROWS_EVENT
-            || self.event_type == EventType::UPDATE_ROWS_EVENT
-            || self.event_type == EventType::DELETE_ROWS_EVENT
-            || self.event_type == EventType::PARTIAL_UPDATE_ROWS_EVENT
-        {
+        if self.has_extra_data() {
             let len = min(self.extra_data.len().saturating_add(2), u16::MAX as usize) as u16;
             buf.put_u16_le(len);
             self.extra_data.serialize(&mut *buf);
@@ -277,11 +482,19 @@ impl MySerialize for RowsEvent<'_> {
 }
 
 /// Iterator over rows of a `RowsEvent`.
+///
+/// Column metadata is the same for every row of an event, so all the before-image rows
+/// (and all the after-image rows) yielded by this iterator share a single `Arc<[Column]>`.
 #[derive(Clone, Eq, PartialEq)]
 pub struct RowsEventRows<'a> {
     rows_event: &'a RowsEvent<'a>,
     table_map_event: &'a TableMapEvent<'a>,
     rows_data: ParseBuf<'a>,
+    columns_before: Option<SharedColumns>,
+    columns_after: Option<SharedColumns>,
+    generated: Vec<Option<GeneratedColumn>>,
+    enum_and_set_names: Option<EnumAndSetNames>,
+    projection: Option<&'a Projection>,
 }
 
 impl<'a> RowsEventRows<'a> {
@@ -294,7 +507,77 @@ impl<'a> RowsEventRows<'a> {
             rows_event,
             table_map_event,
             rows_data,
+            columns_before: None,
+            columns_after: None,
+            generated: Vec::new(),
+            enum_and_set_names: None,
+            projection: None,
+        }
+    }
+
+    /// Returns modified `self` that will mark the given columns of decoded rows as generated
+    /// (see [`ColumnAttributes::generated`]).
+    ///
+    /// Columns are given by their index within the table. Binlog doesn't describe
+    /// generated columns, so these are usually taken from the table definition
+    /// (see [`GeneratedColumn::from_extra`]).
+    ///
+    /// [`ColumnAttributes::generated`]: crate::binlog::row::ColumnAttributes::generated
+    pub fn with_generated_columns(
+        mut self,
+        columns: impl IntoIterator<Item = (usize, GeneratedColumn)>,
+    ) -> Self {
+        self.generated.clear();
+        for (index, kind) in columns {
+            if self.generated.len() <= index {
+                self.generated.resize(index + 1, None);
+            }
+            self.generated[index] = Some(kind);
+        }
+        self
+    }
+
+    /// Returns modified `self` that will decode ENUM and SET values to strings.
+    ///
+    /// Variant names are taken from the optional metadata of the table map event
+    /// (it's there if `binlog_row_metadata` is `FULL`). An ENUM value is decoded to the name
+    /// of its variant (or to the empty string for the `0` index), and a SET value is decoded
+    /// to comma-separated names of its variants. Values are left as is (i.e. as ENUM
+    /// indexes or SET bitmaps) if variant names are unknown.
+    ///
+    /// Returns an error if the optional metadata is malformed.
+    pub fn with_enum_and_set_names(mut self) -> io::Result<Self> {
+        let names = EnumAndSetNames::new(self.table_map_event)?;
+        self.enum_and_set_names = Some(names).filter(|x| !x.is_empty());
+        Ok(self)
+    }
+
+    /// Returns modified `self` that will decode only columns of the given projection
+    /// (values of other columns are skipped without decoding).
+    ///
+    /// Columns of a decoded row are ordered as in the projection. A projected column
+    /// is missing from a row if it isn't in the row image (see `binlog_row_image`),
+    /// so use [`ColumnAttributes::index`] to identify columns of such rows.
+    ///
+    /// [`ColumnAttributes::index`]: crate::binlog::row::ColumnAttributes::index
+    pub fn with_projection(mut self, projection: &'a Projection) -> Self {
+        self.projection = Some(projection);
+        self
+    }
+
+    /// Decodes ENUM and SET values (if requested). Columns of the first row are marked
+    /// as generated (if any) and stored in `shared` to be reused by subsequent rows.
+    fn finish_row(&self, mut row: BinlogRow, shared: &mut Option<SharedColumns>) -> BinlogRow {
+        if shared.is_none() {
+            if !self.generated.is_empty() {
+                row.mark_generated(&self.generated);
+            }
+            *shared = Some(row.shared_columns());
+        }
+        if let Some(ref names) = self.enum_and_set_names {
+            row.decode_enum_and_set(names);
         }
+        row
     }
 }
 
@@ -316,8 +599,18 @@ impl<'a> Iterator for RowsEventRows<'a> {
                 false,
                 self.table_map_event,
             );
-            row_before = match self.rows_data.parse(ctx) {
-                Ok(row_before) => Some(row_before),
+            row_before = match BinlogRow::deserialize_projected(
+                ctx,
+                self.projection,
+                self.columns_before.as_ref(),
+                &mut self.rows_data,
+            ) {
+                Ok(row_before) => {
+                    let mut shared = self.columns_before.take();
+                    let row_before = self.finish_row(row_before, &mut shared);
+                    self.columns_before = shared;
+                    Some(row_before)
+                }
                 Err(err) => return Some(Err(err)),
             };
         }
@@ -329,8 +622,18 @@ impl<'a> Iterator for RowsEventRows<'a> {
                 self.rows_event.event_type == EventType::PARTIAL_UPDATE_ROWS_EVENT,
                 self.table_map_event,
             );
-            row_after = match self.rows_data.parse(ctx) {
-                Ok(row_after) => Some(row_after),
+            row_after = match BinlogRow::deserialize_projected(
+                ctx,
+                self.projection,
+                self.columns_after.as_ref(),
+                &mut self.rows_data,
+            ) {
+                Ok(row_after) => {
+                    let mut shared = self.columns_after.take();
+                    let row_after = self.finish_row(row_after, &mut shared);
+                    self.columns_after = shared;
+                    Some(row_after)
+                }
                 Err(err) => return Some(Err(err)),
             };
         }
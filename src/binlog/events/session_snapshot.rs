@@ -0,0 +1,201 @@
+// Copyright (c) 2023 Anatoly Ikorsky
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+use std::io;
+
+use crate::{constants::SqlMode, value::Value};
+
+use super::{QueryEvent, StatusVarVal};
+
+/// Session variables a `QUERY_EVENT` was executed with.
+///
+/// The snapshot is taken from the event status variables (see [`SessionSnapshot::from_query_event`]).
+/// `None` means that the event doesn't define the variable, so the applier's session
+/// value shouldn't be changed. `lc_time_names` and `collation_database` are omitted
+/// by the server if they have default values, so they are never unknown.
+#[derive(Debug, Default, Clone, Eq, PartialEq, Hash)]
+pub struct SessionSnapshot {
+    sql_mode: Option<u64>,
+    charset: Option<(u16, u16, u16)>,
+    time_zone: Option<String>,
+    lc_time_names: u16,
+    collation_database: u16,
+    default_collation_for_utf8mb4: Option<u16>,
+    explicit_defaults_for_timestamp: Option<bool>,
+}
+
+impl SessionSnapshot {
+    /// Creates a snapshot with unknown values of session variables.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Takes a snapshot of session variables defined by status variables of the given event.
+    ///
+    /// Returns an `InvalidData` error if a relevant status variable is malformed.
+    pub fn from_query_event(event: &QueryEvent<'_>) -> io::Result<Self> {
+        let mut snapshot = Self::new();
+        for var in event.status_vars().iter() {
+            let value = var.get_value().map_err(|_| {
+                io::Error::new(io::ErrorKind::InvalidData, "malformed status variable")
+            })?;
+            match value {
+                StatusVarVal::SqlMode(sql_mode) => snapshot.sql_mode = Some(sql_mode.0),
+                StatusVarVal::Charset {
+                    charset_client,
+                    collation_connection,
+                    collation_server,
+                } => {
+                    snapshot.charset =
+                        Some((charset_client, collation_connection, collation_server));
+                }
+                StatusVarVal::TimeZone(time_zone) => {
+                    snapshot.time_zone = Some(time_zone.as_str().into_owned());
+                }
+                StatusVarVal::LcTimeNames(lc_time_names) => snapshot.lc_time_names = lc_time_names,
+                StatusVarVal::CharsetDatabase(collation) => snapshot.collation_database = collation,
+                StatusVarVal::DefaultCollationForUtf8mb4(collation) => {
+                    snapshot.default_collation_for_utf8mb4 = Some(collation);
+                }
+                StatusVarVal::ExplicitDefaultsForTimestamp(value) => {
+                    snapshot.explicit_defaults_for_timestamp = Some(value);
+                }
+                _ => (),
+            }
+        }
+        Ok(snapshot)
+    }
+
+    /// Returns the raw `sql_mode` value.
+    pub fn sql_mode_raw(&self) -> Option<u64> {
+        self.sql_mode
+    }
+
+    /// Returns the `sql_mode` value (unknown bits are truncated).
+    pub fn sql_mode(&self) -> Option<SqlMode> {
+        self.sql_mode.map(SqlMode::from_bits_truncate)
+    }
+
+    /// Returns the `(character_set_client, collation_connection, collation_server)` ids.
+    pub fn charset(&self) -> Option<(u16, u16, u16)> {
+        self.charset
+    }
+
+    /// Returns the `time_zone` value.
+    pub fn time_zone(&self) -> Option<&str> {
+        self.time_zone.as_deref()
+    }
+
+    /// Returns the `lc_time_names` locale id (`0` is `en_US`).
+    pub fn lc_time_names(&self) -> u16 {
+        self.lc_time_names
+    }
+
+    /// Returns the `collation_database` id (`0` means the server default).
+    pub fn collation_database(&self) -> u16 {
+        self.collation_database
+    }
+
+    /// Returns the `default_collation_for_utf8mb4` id.
+    pub fn default_collation_for_utf8mb4(&self) -> Option<u16> {
+        self.default_collation_for_utf8mb4
+    }
+
+    /// Returns the `explicit_defaults_for_timestamp` value.
+    pub fn explicit_defaults_for_timestamp(&self) -> Option<bool> {
+        self.explicit_defaults_for_timestamp
+    }
+
+    /// Returns `SET` statements that turn a session described by `self`
+    /// into a session described by `other`.
+    ///
+    /// Only variables known in `other` and different in `self` are set.
+    /// Statements are formatted the way `mysqlbinlog` formats them.
+    pub fn diff(&self, other: &SessionSnapshot) -> Vec<String> {
+        let mut statements = Vec::new();
+
+        if let Some(sql_mode) = other.sql_mode.filter(|x| self.sql_mode != Some(*x)) {
+            statements.push(format!("SET @@session.sql_mode={}", sql_mode));
+        }
+        if let Some((client, connection, server)) =
+            other.charset.filter(|x| self.charset != Some(*x))
+        {
+            statements.push(format!(
+                "SET @@session.character_set_client={},@@session.collation_connection={},@@session.collation_server={}",
+                client, connection, server
+            ));
+        }
+        if let Some(time_zone) = other
+            .time_zone
+            .as_ref()
+            .filter(|x| self.time_zone.as_ref() != Some(*x))
+        {
+            let sql_mode = other.sql_mode().or_else(|| self.sql_mode());
+            let no_backslash_escapes =
+                matches!(sql_mode, Some(x) if x.contains(SqlMode::MODE_NO_BACKSLASH_ESCAPES));
+            statements.push(format!(
+                "SET @@session.time_zone={}",
+                Value::from(&**time_zone).as_sql(no_backslash_escapes)
+            ));
+        }
+        if other.lc_time_names != self.lc_time_names {
+            statements.push(format!(
+                "SET @@session.lc_time_names={}",
+                other.lc_time_names
+            ));
+        }
+        if other.collation_database != self.collation_database {
+            if other.collation_database == 0 {
+                statements.push("SET @@session.collation_database=DEFAULT".into());
+            } else {
+                statements.push(format!(
+                    "SET @@session.collation_database={}",
+                    other.collation_database
+                ));
+            }
+        }
+        if let Some(collation) = other
+            .default_collation_for_utf8mb4
+            .filter(|x| self.default_collation_for_utf8mb4 != Some(*x))
+        {
+            statements.push(format!(
+                "SET @@session.default_collation_for_utf8mb4={}",
+                collation
+            ));
+        }
+        if let Some(value) = other
+            .explicit_defaults_for_timestamp
+            .filter(|x| self.explicit_defaults_for_timestamp != Some(*x))
+        {
+            statements.push(format!(
+                "SET @@session.explicit_defaults_for_timestamp={}",
+                value as u8
+            ));
+        }
+
+        statements
+    }
+
+    /// Updates `self` with variables known in `other`
+    /// (i.e. applies the result of [`SessionSnapshot::diff`]).
+    pub fn update(&mut self, other: &SessionSnapshot) {
+        self.sql_mode = other.sql_mode.or(self.sql_mode);
+        self.charset = other.charset.or(self.charset);
+        if other.time_zone.is_some() {
+            self.time_zone = other.time_zone.clone();
+        }
+        self.lc_time_names = other.lc_time_names;
+        self.collation_database = other.collation_database;
+        self.default_collation_for_utf8mb4 = other
+            .default_collation_for_utf8mb4
+            .or(self.default_collation_for_utf8mb4);
+        self.explicit_defaults_for_timestamp = other
+            .explicit_defaults_for_timestamp
+            .or(self.explicit_defaults_for_timestamp);
+    }
+}
@@ -0,0 +1,74 @@
+// Copyright (c) 2023 Anatoly Ikorsky
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+use super::{EventData, IntvarEvent, QueryEvent, RandEvent, UserVarEvent};
+
+/// Builder of the context events that precede a statement-based `QUERY_EVENT`.
+///
+/// Events are emitted in the order used by the server:
+///
+/// 1. `INTVAR_EVENT` with `LAST_INSERT_ID_EVENT` subtype;
+/// 2. `INTVAR_EVENT` with `INSERT_ID_EVENT` subtype;
+/// 3. `RAND_EVENT`;
+/// 4. `USER_VAR_EVENT`s (in the order they were added);
+/// 5. the `QUERY_EVENT` itself.
+#[derive(Debug, Default, Clone, Eq, PartialEq, Hash)]
+pub struct StatementContextBuilder<'a> {
+    last_insert_id: Option<u64>,
+    insert_id: Option<u64>,
+    rand: Option<(u64, u64)>,
+    user_vars: Vec<UserVarEvent<'a>>,
+}
+
+impl<'a> StatementContextBuilder<'a> {
+    /// Creates a new builder with an empty context.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the value to use for the `LAST_INSERT_ID()` function.
+    pub fn with_last_insert_id(mut self, value: u64) -> Self {
+        self.last_insert_id = Some(value);
+        self
+    }
+
+    /// Sets the value to use for an `AUTO_INCREMENT` column.
+    pub fn with_insert_id(mut self, value: u64) -> Self {
+        self.insert_id = Some(value);
+        self
+    }
+
+    /// Sets the random seed to use for the `RAND()` function.
+    pub fn with_rand_seed(mut self, seed1: u64, seed2: u64) -> Self {
+        self.rand = Some((seed1, seed2));
+        self
+    }
+
+    /// Adds a user variable used by the statement.
+    pub fn with_user_var(mut self, user_var: UserVarEvent<'a>) -> Self {
+        self.user_vars.push(user_var);
+        self
+    }
+
+    /// Returns the context events followed by the given `query`.
+    pub fn build(self, query: QueryEvent<'a>) -> Vec<EventData<'a>> {
+        let mut events = Vec::with_capacity(4 + self.user_vars.len());
+        if let Some(value) = self.last_insert_id {
+            events.push(EventData::IntvarEvent(IntvarEvent::last_insert_id(value)));
+        }
+        if let Some(value) = self.insert_id {
+            events.push(EventData::IntvarEvent(IntvarEvent::insert_id(value)));
+        }
+        if let Some((seed1, seed2)) = self.rand {
+            events.push(EventData::RandEvent(RandEvent::new(seed1, seed2)));
+        }
+        events.extend(self.user_vars.into_iter().map(EventData::UserVarEvent));
+        events.push(EventData::QueryEvent(query));
+        events
+    }
+}
@@ -0,0 +1,47 @@
+// Copyright (c) 2023 Anatoly Ikorsky
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+use std::io;
+
+use crate::{
+    binlog::{
+        consts::{BinlogVersion, EventType},
+        BinlogCtx, BinlogEvent, BinlogStruct,
+    },
+    io::ParseBuf,
+    proto::{MyDeserialize, MySerialize},
+};
+
+/// Stop event.
+///
+/// Written to the binlog when the server shuts down. It has no payload.
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq, Hash)]
+pub struct StopEvent;
+
+impl<'de> MyDeserialize<'de> for StopEvent {
+    const SIZE: Option<usize> = Some(0);
+    type Ctx = BinlogCtx<'de>;
+
+    fn deserialize(_ctx: Self::Ctx, _buf: &mut ParseBuf<'de>) -> io::Result<Self> {
+        Ok(Self)
+    }
+}
+
+impl MySerialize for StopEvent {
+    fn serialize(&self, _buf: &mut Vec<u8>) {}
+}
+
+impl<'a> BinlogEvent<'a> for StopEvent {
+    const EVENT_TYPE: EventType = EventType::STOP_EVENT;
+}
+
+impl<'a> BinlogStruct<'a> for StopEvent {
+    fn len(&self, _version: BinlogVersion) -> usize {
+        0
+    }
+}
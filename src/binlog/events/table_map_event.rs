@@ -6,29 +6,35 @@
 // option. All files in the project carrying such notice may not be copied,
 // modified, or distributed except according to those terms.
 
-use std::{borrow::Cow, cmp::min, convert::TryFrom, io, iter::Peekable};
+use std::{borrow::Cow, cmp::min, convert::TryFrom, io, iter::Peekable, str::Utf8Error};
 
 use bitvec::prelude::*;
 use byteorder::ReadBytesExt;
+use bytes::BufMut;
 use saturating::Saturating as S;
 
 use crate::{
     binlog::{
         consts::{BinlogVersion, EventType, OptionalMetadataFieldType},
+        misc::check_identifier,
         BinlogCtx, BinlogEvent, BinlogStruct,
     },
-    constants::{ColumnType, GeometryType, UnknownColumnType},
-    io::ParseBuf,
+    constants::{ColumnFlags, ColumnType, GeometryType, UnknownColumnType},
+    io::{BufMutExt, ParseBuf},
     misc::raw::{
         bytes::{BareBytes, EofBytes, LenEnc, U8Bytes},
         int::*,
         Either, RawBytes, RawConst, RawSeq, Skip,
     },
+    packets::column_ddl::{blob_type, mbmaxlen, ColumnDdl},
     proto::{MyDeserialize, MySerialize},
 };
 
 use super::BinlogEventHeader;
 
+/// Collation id of the `binary` character set.
+const BINARY_COLLATION_ID: u16 = 63;
+
 #[derive(Debug, Clone, Copy, Eq, PartialEq, thiserror::Error)]
 pub enum BadColumnType {
     #[error(transparent)]
@@ -83,11 +89,26 @@ pub struct TableMapEvent<'a> {
 }
 
 impl<'a> TableMapEvent<'a> {
+    /// Returns a builder for a table map event of the given table
+    /// (see [`TableMapEventBuilder`]).
+    pub fn builder(
+        database_name: impl Into<Cow<'a, [u8]>>,
+        table_name: impl Into<Cow<'a, [u8]>>,
+    ) -> TableMapEventBuilder<'a> {
+        TableMapEventBuilder::new(database_name, table_name)
+    }
+
     /// Returns the table identifier.
     pub fn table_id(&self) -> u64 {
         self.table_id.0
     }
 
+    /// Defines the table identifier.
+    pub fn with_table_id(mut self, table_id: u64) -> Self {
+        self.table_id = RawInt::new(table_id);
+        self
+    }
+
     /// Returns the number of columns
     pub fn columns_count(&self) -> u64 {
         self.columns_count.0
@@ -121,6 +142,11 @@ impl<'a> TableMapEvent<'a> {
         self.database_name.as_str()
     }
 
+    /// Returns database name as a string (fails if it isn't a valid utf-8 string).
+    pub fn database_name_str(&'a self) -> Result<&'a str, Utf8Error> {
+        std::str::from_utf8(self.database_name.as_bytes())
+    }
+
     /// Returns raw table name value.
     pub fn table_name_raw(&'a self) -> &'a [u8] {
         self.table_name.as_bytes()
@@ -131,6 +157,11 @@ impl<'a> TableMapEvent<'a> {
         self.table_name.as_str()
     }
 
+    /// Returns table name as a string (fails if it isn't a valid utf-8 string).
+    pub fn table_name_str(&'a self) -> Result<&'a str, Utf8Error> {
+        std::str::from_utf8(self.table_name.as_bytes())
+    }
+
     /// Returns raw type of the column as stored in the column_type field of the Table Map Event.
     ///
     /// `None` means that the column index is out of range.
@@ -180,6 +211,27 @@ impl<'a> TableMapEvent<'a> {
         None
     }
 
+    /// Returns `true` if the given numeric column is `UNSIGNED` (uses `SIGNEDNESS` optional
+    /// metadata).
+    ///
+    /// `None` means that the column index is out of range, the column is not numeric
+    /// or there is no signedness data in the optional metadata.
+    pub fn column_is_unsigned(&self, col_idx: usize) -> Option<bool> {
+        if !self.get_column_type(col_idx).ok()??.is_numeric_type() {
+            return None;
+        }
+        let mut numeric_idx = 0;
+        for i in 0..col_idx {
+            if self.get_column_type(i).ok()??.is_numeric_type() {
+                numeric_idx += 1;
+            }
+        }
+        OptionalMetaExtractor::new(self.iter_optional_meta())
+            .ok()?
+            .iter_signedness()
+            .nth(numeric_idx)
+    }
+
     pub fn iter_optional_meta(&'a self) -> OptionalMetadataIter<'a> {
         OptionalMetadataIter {
             columns: &self.columns_type,
@@ -187,6 +239,114 @@ impl<'a> TableMapEvent<'a> {
         }
     }
 
+    /// Returns `CREATE TABLE` definitions of columns (see [`ColumnDdl`]).
+    ///
+    /// Column names, signedness, character sets and ENUM/SET variants are taken
+    /// from the optional metadata (see `binlog_row_metadata=FULL`). Without it columns
+    /// are named `@<index>`, numeric columns are signed and string lengths are in bytes.
+    pub fn columns_ddl(&self) -> io::Result<Vec<ColumnDdl<'static>>> {
+        let extractor = OptionalMetaExtractor::new(self.iter_optional_meta())?;
+        let mut signedness = extractor.iter_signedness();
+        let mut charsets = extractor.iter_charset();
+        let mut enum_and_set_charsets = extractor.iter_enum_and_set_charset();
+        let mut names = extractor.iter_column_name();
+
+        let mut enum_values = extractor.iter_enum_str_value();
+        let mut set_values = extractor.iter_set_str_value();
+
+        let mut columns = Vec::with_capacity(self.columns_count() as usize);
+        for i in 0..self.columns_count() as usize {
+            let column_type = match self.get_column_type(i) {
+                Ok(Some(ty)) => ty,
+                Ok(None) => {
+                    return Err(io::Error::new(io::ErrorKind::InvalidData, "No column type"))
+                }
+                Err(e) => return Err(io::Error::new(io::ErrorKind::InvalidData, e)),
+            };
+            let meta = self.get_column_metadata(i).unwrap_or(&[]);
+            let meta_byte = |n: usize| meta.get(n).copied().unwrap_or_default();
+
+            let mut flags = ColumnFlags::empty();
+            if column_type.is_numeric_type() && signedness.next().unwrap_or_default() {
+                flags |= ColumnFlags::UNSIGNED_FLAG;
+            }
+            if !self
+                .null_bitmask()
+                .get(i)
+                .as_deref()
+                .copied()
+                .unwrap_or(true)
+            {
+                flags |= ColumnFlags::NOT_NULL_FLAG;
+            }
+
+            let charset = if column_type.is_character_type() {
+                charsets.next().transpose()?.unwrap_or_default()
+            } else if column_type.is_enum_or_set_type() {
+                enum_and_set_charsets
+                    .next()
+                    .transpose()?
+                    .unwrap_or_default()
+            } else {
+                BINARY_COLLATION_ID
+            };
+
+            let name = match names.next().transpose()? {
+                Some(name) => name.name().into_owned(),
+                None => format!("@{}", i),
+            };
+
+            let mut column = ColumnDdl::new(name, column_type)
+                .with_flags(flags)
+                .with_character_set(charset);
+            column = match column_type {
+                ColumnType::MYSQL_TYPE_VARCHAR | ColumnType::MYSQL_TYPE_VAR_STRING => {
+                    let max_len = u16::from_le_bytes([meta_byte(0), meta_byte(1)]) as u32;
+                    column.with_length(Some(max_len / mbmaxlen(charset)))
+                }
+                ColumnType::MYSQL_TYPE_STRING => {
+                    let (b0, b1) = (meta_byte(0) as u32, meta_byte(1) as u32);
+                    let max_len = (((b0 & 0x30) ^ 0x30) << 4) | b1;
+                    column.with_length(Some(max_len / mbmaxlen(charset)))
+                }
+                ColumnType::MYSQL_TYPE_NEWDECIMAL => column
+                    .with_length(Some(meta_byte(0) as u32))
+                    .with_decimals(meta_byte(1)),
+                ColumnType::MYSQL_TYPE_BIT => {
+                    column.with_length(Some(meta_byte(1) as u32 * 8 + meta_byte(0) as u32))
+                }
+                ColumnType::MYSQL_TYPE_TIME2
+                | ColumnType::MYSQL_TYPE_DATETIME2
+                | ColumnType::MYSQL_TYPE_TIMESTAMP2 => column.with_decimals(meta_byte(0)),
+                ColumnType::MYSQL_TYPE_BLOB => {
+                    let max_len = match meta_byte(0) {
+                        1 => 0xff,
+                        2 => 0xffff,
+                        3 => 0xffffff,
+                        _ => u32::MAX,
+                    };
+                    ColumnDdl::new(column.name().to_owned(), blob_type(max_len))
+                        .with_flags(flags)
+                        .with_character_set(charset)
+                }
+                ColumnType::MYSQL_TYPE_ENUM => {
+                    let values = enum_values.next().transpose()?;
+                    let values = values.iter().flat_map(|x| x.values());
+                    column.with_values(values.map(|x| x.value().into_owned()))
+                }
+                ColumnType::MYSQL_TYPE_SET => {
+                    let values = set_values.next().transpose()?;
+                    let values = values.iter().flat_map(|x| x.values());
+                    column.with_values(values.map(|x| x.value().into_owned()))
+                }
+                _ => column,
+            };
+            columns.push(column);
+        }
+
+        Ok(columns)
+    }
+
     /// Returns a `'static` version of `self`.
     pub fn into_owned(self) -> TableMapEvent<'static> {
         TableMapEvent {
@@ -319,13 +479,263 @@ impl<'a> BinlogStruct<'a> for TableMapEvent<'a> {
         len += S(crate::misc::lenenc_int_len(self.columns_count() as u64) as usize);
         len += S(self.columns_count() as usize);
         len += S(crate::misc::lenenc_str_len(self.columns_metadata.as_bytes()) as usize);
-        len += S((self.columns_count() as usize + 8) / 7);
+        len += S((self.columns_count() as usize).div_ceil(8));
         len += S(self.optional_metadata.len());
 
         min(len.0, u32::MAX as usize - BinlogEventHeader::LEN)
     }
 }
 
+/// Builder of a [`TableMapEvent`] out of column definitions (see [`ColumnDdl`]).
+///
+/// Column types and metadata, the null bitmask and the optional metadata
+/// (see `binlog_row_metadata=FULL`) are computed from the given columns, so that
+/// [`TableMapEvent::columns_ddl`] gives back the same definitions.
+///
+/// Temporal columns are always mapped to their fractional seconds precision variants
+/// (`MYSQL_TYPE_TIME2` and alike).
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct TableMapEventBuilder<'a> {
+    table_id: u64,
+    database_name: Cow<'a, [u8]>,
+    table_name: Cow<'a, [u8]>,
+    columns: Vec<ColumnDdl<'a>>,
+    optional_metadata: bool,
+}
+
+impl<'a> TableMapEventBuilder<'a> {
+    /// Creates a new builder (see [`TableMapEvent::builder`]).
+    pub fn new(
+        database_name: impl Into<Cow<'a, [u8]>>,
+        table_name: impl Into<Cow<'a, [u8]>>,
+    ) -> Self {
+        Self {
+            table_id: 0,
+            database_name: database_name.into(),
+            table_name: table_name.into(),
+            columns: Vec::new(),
+            optional_metadata: true,
+        }
+    }
+
+    /// Defines the table identifier (defaults to `0`).
+    pub fn with_table_id(mut self, table_id: u64) -> Self {
+        self.table_id = table_id;
+        self
+    }
+
+    /// Adds a column.
+    ///
+    /// Signedness, nullability and the primary key are taken from column flags.
+    pub fn with_column(mut self, column: ColumnDdl<'a>) -> Self {
+        self.columns.push(column);
+        self
+    }
+
+    /// Adds columns.
+    pub fn with_columns(mut self, columns: impl IntoIterator<Item = ColumnDdl<'a>>) -> Self {
+        self.columns.extend(columns);
+        self
+    }
+
+    /// Defines whether to write the optional metadata (defaults to `true`).
+    pub fn with_optional_metadata(mut self, optional_metadata: bool) -> Self {
+        self.optional_metadata = optional_metadata;
+        self
+    }
+
+    /// Builds the event.
+    ///
+    /// Returns an `InvalidInput` error if the database or the table name isn't a valid
+    /// identifier (a utf-8 string of at most [`NAME_CHAR_LEN`] characters),
+    /// or if a column type can't appear in a table (e.g. `MYSQL_TYPE_NULL`),
+    /// or if a column length is out of range for its type.
+    ///
+    /// [`NAME_CHAR_LEN`]: crate::binlog::misc::NAME_CHAR_LEN
+    pub fn build(self) -> io::Result<TableMapEvent<'a>> {
+        use ColumnType::*;
+
+        let invalid = |msg: String| io::Error::new(io::ErrorKind::InvalidInput, msg);
+
+        check_identifier(&self.database_name, "database name")?;
+        check_identifier(&self.table_name, "table name")?;
+
+        let mut columns_type = Vec::with_capacity(self.columns.len());
+        let mut columns_metadata = Vec::new();
+        let mut null_bitmask = bitvec![u8, Lsb0; 0; self.columns.len()];
+
+        let mut signedness = BitVec::<u8, Msb0>::new();
+        let mut charsets = Vec::new();
+        let mut enum_and_set_charsets = Vec::new();
+        let mut names = Vec::new();
+        let mut set_values = Vec::new();
+        let mut enum_values = Vec::new();
+        let mut primary_key = Vec::new();
+
+        for (i, column) in self.columns.iter().enumerate() {
+            let charset = column.character_set();
+            let length = column.length();
+            let max_byte_len = |default: u32| length.unwrap_or(default) * mbmaxlen(charset);
+
+            // (real type, type written into the event, metadata)
+            let (real_type, column_type, meta): (_, _, Vec<u8>) = match column.column_type() {
+                MYSQL_TYPE_TINY | MYSQL_TYPE_SHORT | MYSQL_TYPE_INT24 | MYSQL_TYPE_LONG
+                | MYSQL_TYPE_LONGLONG | MYSQL_TYPE_YEAR => {
+                    (column.column_type(), column.column_type(), vec![])
+                }
+                MYSQL_TYPE_DATE | MYSQL_TYPE_NEWDATE => {
+                    (MYSQL_TYPE_NEWDATE, MYSQL_TYPE_DATE, vec![])
+                }
+                MYSQL_TYPE_FLOAT => (MYSQL_TYPE_FLOAT, MYSQL_TYPE_FLOAT, vec![4]),
+                MYSQL_TYPE_DOUBLE => (MYSQL_TYPE_DOUBLE, MYSQL_TYPE_DOUBLE, vec![8]),
+                MYSQL_TYPE_DECIMAL | MYSQL_TYPE_NEWDECIMAL => {
+                    let precision = length.unwrap_or(10);
+                    if precision > 65 || column.decimals() as u32 > precision {
+                        return Err(invalid(format!("bad DECIMAL column `{}`", column.name())));
+                    }
+                    let meta = vec![precision as u8, column.decimals()];
+                    (MYSQL_TYPE_NEWDECIMAL, MYSQL_TYPE_NEWDECIMAL, meta)
+                }
+                MYSQL_TYPE_TIME | MYSQL_TYPE_TIME2 => {
+                    (MYSQL_TYPE_TIME2, MYSQL_TYPE_TIME2, vec![column.decimals()])
+                }
+                MYSQL_TYPE_DATETIME | MYSQL_TYPE_DATETIME2 => (
+                    MYSQL_TYPE_DATETIME2,
+                    MYSQL_TYPE_DATETIME2,
+                    vec![column.decimals()],
+                ),
+                MYSQL_TYPE_TIMESTAMP | MYSQL_TYPE_TIMESTAMP2 => (
+                    MYSQL_TYPE_TIMESTAMP2,
+                    MYSQL_TYPE_TIMESTAMP2,
+                    vec![column.decimals()],
+                ),
+                MYSQL_TYPE_BIT => {
+                    let width = length.unwrap_or(1);
+                    if width > 64 {
+                        return Err(invalid(format!("bad BIT column `{}`", column.name())));
+                    }
+                    let meta = vec![(width % 8) as u8, (width / 8) as u8];
+                    (MYSQL_TYPE_BIT, MYSQL_TYPE_BIT, meta)
+                }
+                MYSQL_TYPE_VARCHAR | MYSQL_TYPE_VAR_STRING => {
+                    let max_len = u16::try_from(max_byte_len(255)).map_err(|_| {
+                        invalid(format!("VARCHAR column `{}` is too long", column.name()))
+                    })?;
+                    let meta = max_len.to_le_bytes().to_vec();
+                    (MYSQL_TYPE_VARCHAR, MYSQL_TYPE_VARCHAR, meta)
+                }
+                MYSQL_TYPE_STRING => {
+                    let max_len = max_byte_len(1);
+                    if max_len > 0x3ff {
+                        let msg = format!("CHAR column `{}` is too long", column.name());
+                        return Err(invalid(msg));
+                    }
+                    let meta = vec![
+                        (MYSQL_TYPE_STRING as u8) ^ ((max_len & 0x300) >> 4) as u8,
+                        max_len as u8,
+                    ];
+                    (MYSQL_TYPE_STRING, MYSQL_TYPE_STRING, meta)
+                }
+                MYSQL_TYPE_ENUM => {
+                    let pack_len = if column.values().len() > 0xff { 2 } else { 1 };
+                    let meta = vec![MYSQL_TYPE_ENUM as u8, pack_len];
+                    (MYSQL_TYPE_ENUM, MYSQL_TYPE_STRING, meta)
+                }
+                MYSQL_TYPE_SET => {
+                    let pack_len = column.values().len().div_ceil(8);
+                    if pack_len > 8 {
+                        let msg = format!("SET column `{}` has too many values", column.name());
+                        return Err(invalid(msg));
+                    }
+                    let meta = vec![MYSQL_TYPE_SET as u8, pack_len.max(1) as u8];
+                    (MYSQL_TYPE_SET, MYSQL_TYPE_STRING, meta)
+                }
+                ty @ (MYSQL_TYPE_TINY_BLOB
+                | MYSQL_TYPE_BLOB
+                | MYSQL_TYPE_MEDIUM_BLOB
+                | MYSQL_TYPE_LONG_BLOB) => {
+                    let pack_len = match ty {
+                        MYSQL_TYPE_TINY_BLOB => 1,
+                        MYSQL_TYPE_BLOB => 2,
+                        MYSQL_TYPE_MEDIUM_BLOB => 3,
+                        _ => 4,
+                    };
+                    (MYSQL_TYPE_BLOB, MYSQL_TYPE_BLOB, vec![pack_len])
+                }
+                MYSQL_TYPE_JSON => (MYSQL_TYPE_JSON, MYSQL_TYPE_JSON, vec![4]),
+                MYSQL_TYPE_GEOMETRY => (MYSQL_TYPE_GEOMETRY, MYSQL_TYPE_GEOMETRY, vec![4]),
+                ty @ (MYSQL_TYPE_NULL | MYSQL_TYPE_TYPED_ARRAY | MYSQL_TYPE_UNKNOWN) => {
+                    let msg = format!("column `{}` can't be of type {:?}", column.name(), ty);
+                    return Err(invalid(msg));
+                }
+            };
+
+            columns_type.push(column_type as u8);
+            columns_metadata.extend_from_slice(&meta);
+            null_bitmask.set(i, !column.flags().contains(ColumnFlags::NOT_NULL_FLAG));
+
+            if real_type.is_numeric_type() {
+                signedness.push(column.flags().contains(ColumnFlags::UNSIGNED_FLAG));
+            }
+            if real_type.is_character_type() {
+                charsets.put_lenenc_int(charset as u64);
+            } else if real_type.is_enum_or_set_type() {
+                enum_and_set_charsets.put_lenenc_int(charset as u64);
+                let values = if real_type == MYSQL_TYPE_ENUM {
+                    &mut enum_values
+                } else {
+                    &mut set_values
+                };
+                values.put_lenenc_int(column.values().len() as u64);
+                for value in column.values() {
+                    values.put_lenenc_str(value.as_bytes());
+                }
+            }
+            names.put_lenenc_str(column.name().as_bytes());
+            if column.flags().contains(ColumnFlags::PRI_KEY_FLAG) {
+                primary_key.put_lenenc_int(i as u64);
+            }
+        }
+
+        let mut optional_metadata = Vec::new();
+        if self.optional_metadata {
+            let mut put_field = |field_type: OptionalMetadataFieldType, value: &[u8]| {
+                if !value.is_empty() {
+                    optional_metadata.put_u8(field_type as u8);
+                    optional_metadata.put_lenenc_str(value);
+                }
+            };
+            put_field(
+                OptionalMetadataFieldType::SIGNEDNESS,
+                signedness.as_raw_slice(),
+            );
+            put_field(OptionalMetadataFieldType::COLUMN_CHARSET, &charsets);
+            put_field(OptionalMetadataFieldType::COLUMN_NAME, &names);
+            put_field(OptionalMetadataFieldType::SET_STR_VALUE, &set_values);
+            put_field(OptionalMetadataFieldType::ENUM_STR_VALUE, &enum_values);
+            put_field(OptionalMetadataFieldType::SIMPLE_PRIMARY_KEY, &primary_key);
+            put_field(
+                OptionalMetadataFieldType::ENUM_AND_SET_COLUMN_CHARSET,
+                &enum_and_set_charsets,
+            );
+        }
+
+        Ok(TableMapEvent {
+            table_id: RawInt::new(self.table_id),
+            flags: RawInt::new(0),
+            database_name: RawBytes::new(self.database_name),
+            __null_1: Default::default(),
+            table_name: RawBytes::new(self.table_name),
+            __null_2: Default::default(),
+            columns_count: RawInt::new(self.columns.len() as u64),
+            columns_type: RawSeq::new(columns_type),
+            columns_metadata: RawBytes::new(columns_metadata),
+            null_bitmask: RawBytes::new(null_bitmask.into_vec()),
+            optional_metadata: RawBytes::new(optional_metadata),
+        })
+    }
+}
+
 /// Optional metadata field that contains charsets for columns.
 ///
 /// - contains charsets for caracter columns if it's a [`OptionalMetadataField::DefaultCharset`];
@@ -1159,7 +1569,9 @@ impl<'a> OptionalMetadataIter<'a> {
     /// Reads type-length-value value.
     fn read_tlv(&mut self) -> io::Result<(RawConst<u8, OptionalMetadataFieldType>, &'a [u8])> {
         let t = self.data.read_u8()?;
-        let l = self.data.read_u8()? as usize;
+        let mut buf = ParseBuf(self.data);
+        let l = buf.parse::<RawInt<LenEnc>>(())?.0 as usize;
+        self.data = buf.0;
         let v = match self.data.get(..l) {
             Some(v) => v,
             None => {
@@ -1277,10 +1689,14 @@ pub struct OptionalMetaExtractor<'a> {
     default_charset: Option<DefaultCharset<'a>>,
     column_charset: Option<ColumnCharsets<'a>>,
     column_name: Option<ColumnNames<'a>>,
+    set_str_value: Option<SetsStrValues<'a>>,
+    enum_str_value: Option<EnumsStrValues<'a>>,
+    geometry_type: Option<GeometryTypes<'a>>,
     simple_primary_key: Option<SimplePrimaryKey<'a>>,
     primary_key_with_prefix: Option<PrimaryKeysWithPrefix<'a>>,
     enum_and_set_default_charset: Option<DefaultCharset<'a>>,
     enum_and_set_column_charset: Option<ColumnCharsets<'a>>,
+    column_visibility: Option<&'a BitSlice<u8, Msb0>>,
 }
 
 impl<'a> OptionalMetaExtractor<'a> {
@@ -1290,10 +1706,14 @@ impl<'a> OptionalMetaExtractor<'a> {
             default_charset: None,
             column_charset: None,
             column_name: None,
+            set_str_value: None,
+            enum_str_value: None,
+            geometry_type: None,
             simple_primary_key: None,
             primary_key_with_prefix: None,
             enum_and_set_default_charset: None,
             enum_and_set_column_charset: None,
+            column_visibility: None,
         };
 
         for field in iter_optional_meta {
@@ -1308,9 +1728,15 @@ impl<'a> OptionalMetaExtractor<'a> {
                 OptionalMetadataField::ColumnName(x) => {
                     this.column_name = Some(x);
                 }
-                OptionalMetadataField::SetStrValue(_) => (),
-                OptionalMetadataField::EnumStrValue(_) => (),
-                OptionalMetadataField::GeometryType(_) => (),
+                OptionalMetadataField::SetStrValue(x) => {
+                    this.set_str_value = Some(x);
+                }
+                OptionalMetadataField::EnumStrValue(x) => {
+                    this.enum_str_value = Some(x);
+                }
+                OptionalMetadataField::GeometryType(x) => {
+                    this.geometry_type = Some(x);
+                }
                 OptionalMetadataField::SimplePrimaryKey(x) => {
                     this.simple_primary_key = Some(x);
                 }
@@ -1323,7 +1749,9 @@ impl<'a> OptionalMetaExtractor<'a> {
                 OptionalMetadataField::EnumAndSetColumnCharset(x) => {
                     this.enum_and_set_column_charset = Some(x);
                 }
-                OptionalMetadataField::ColumnVisibility(_) => (),
+                OptionalMetadataField::ColumnVisibility(x) => {
+                    this.column_visibility = Some(x);
+                }
             }
         }
 
@@ -1418,6 +1846,52 @@ impl<'a> OptionalMetaExtractor<'a> {
             .into_iter()
             .flatten()
     }
+
+    /// For every SET column (in order) emits its variants.
+    ///
+    /// Emits nothing if there are no SET values data in the optional metadata.
+    pub fn iter_set_str_value(&'a self) -> impl Iterator<Item = io::Result<SetStrValues<'a>>> + 'a {
+        self.set_str_value
+            .as_ref()
+            .map(|x| x.iter_values())
+            .into_iter()
+            .flatten()
+    }
+
+    /// For every ENUM column (in order) emits its variants.
+    ///
+    /// Emits nothing if there are no ENUM values data in the optional metadata.
+    pub fn iter_enum_str_value(
+        &'a self,
+    ) -> impl Iterator<Item = io::Result<EnumStrValues<'a>>> + 'a {
+        self.enum_str_value
+            .as_ref()
+            .map(|x| x.iter_values())
+            .into_iter()
+            .flatten()
+    }
+
+    /// For every GEOMETRY column (in order) emits its geometry type.
+    ///
+    /// Emits nothing if there are no geometry type data in the optional metadata.
+    pub fn iter_geometry_type(&'a self) -> impl Iterator<Item = io::Result<GeometryType>> + 'a {
+        self.geometry_type
+            .as_ref()
+            .map(|x| x.iter_geometry_types())
+            .into_iter()
+            .flatten()
+    }
+
+    /// For every column (in order) emits its visibility (`true` means _visible_).
+    ///
+    /// Emits nothing if there are no visibility data in the optional metadata.
+    pub fn iter_column_visibility(&'a self) -> impl Iterator<Item = bool> + 'a {
+        self.column_visibility
+            .as_ref()
+            .map(|x| x.iter().by_vals())
+            .into_iter()
+            .flatten()
+    }
 }
 
 fn iter_charset_helper<'a>(
@@ -0,0 +1,297 @@
+// Copyright (c) 2023 Anatoly Ikorsky
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+use std::{borrow::Cow, cmp::min, io};
+
+use saturating::Saturating as S;
+
+use crate::{
+    binlog::{
+        consts::{BinlogVersion, EventType},
+        BinlogCtx, BinlogEvent, BinlogStruct,
+    },
+    io::ParseBuf,
+    misc::raw::{
+        bytes::{BareBytes, BareU16Bytes, BareU8Bytes},
+        int::*,
+        RawBytes, RawInt,
+    },
+    packets::GtidSet,
+    proto::{MyDeserialize, MySerialize},
+};
+
+use super::BinlogEventHeader;
+
+/// Transaction context event (added in MySql 5.7).
+///
+/// Written by Group Replication members, it carries the information needed to certify
+/// a transaction: the snapshot version (executed GTID set) the transaction was executed on
+/// and the hashes of rows it wrote and read.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct TransactionContextEvent<'a> {
+    // post-header
+    server_uuid_len: RawInt<u8>,
+    thread_id: RawInt<LeU32>,
+    gtid_specified: RawInt<u8>,
+    snapshot_version_len: RawInt<LeU32>,
+    write_set_len: RawInt<LeU32>,
+    read_set_len: RawInt<LeU32>,
+
+    // payload
+    server_uuid: RawBytes<'a, BareU8Bytes>,
+    /// Encoded GTID set (see [`GtidSet`]).
+    snapshot_version: RawBytes<'a, BareBytes<{ u32::MAX as usize }>>,
+    write_set: Vec<RawBytes<'a, BareU16Bytes>>,
+    read_set: Vec<RawBytes<'a, BareU16Bytes>>,
+}
+
+impl<'a> TransactionContextEvent<'a> {
+    /// Length of the post-header.
+    const POST_HEADER_LEN: usize = 18;
+
+    /// Creates a new instance with an empty snapshot version and empty read and write sets.
+    pub fn new(server_uuid: impl Into<Cow<'a, [u8]>>) -> Self {
+        let server_uuid = RawBytes::new(server_uuid);
+        let mut snapshot_version = Vec::new();
+        GtidSet::default().serialize(&mut snapshot_version);
+        Self {
+            server_uuid_len: RawInt::new(min(server_uuid.len(), u8::MAX as usize) as u8),
+            thread_id: Default::default(),
+            gtid_specified: Default::default(),
+            snapshot_version_len: RawInt::new(snapshot_version.len() as u32),
+            write_set_len: Default::default(),
+            read_set_len: Default::default(),
+            server_uuid,
+            snapshot_version: RawBytes::new(snapshot_version),
+            write_set: Vec::new(),
+            read_set: Vec::new(),
+        }
+    }
+
+    /// Sets the `server_uuid` value (max length is `u8::MAX`).
+    pub fn with_server_uuid(mut self, server_uuid: impl Into<Cow<'a, [u8]>>) -> Self {
+        self.server_uuid = RawBytes::new(server_uuid);
+        self.server_uuid_len = RawInt::new(min(self.server_uuid.len(), u8::MAX as usize) as u8);
+        self
+    }
+
+    /// Sets the `thread_id` value.
+    pub fn with_thread_id(mut self, thread_id: u32) -> Self {
+        self.thread_id = RawInt::new(thread_id);
+        self
+    }
+
+    /// Sets the `gtid_specified` value.
+    pub fn with_gtid_specified(mut self, gtid_specified: bool) -> Self {
+        self.gtid_specified = RawInt::new(gtid_specified as u8);
+        self
+    }
+
+    /// Sets the `snapshot_version` value.
+    pub fn with_snapshot_version(self, snapshot_version: &GtidSet<'_>) -> Self {
+        let mut encoded = Vec::new();
+        snapshot_version.serialize(&mut encoded);
+        self.with_snapshot_version_raw(encoded)
+    }
+
+    /// Sets the raw `snapshot_version` value (an encoded GTID set).
+    pub fn with_snapshot_version_raw(mut self, snapshot_version: impl Into<Cow<'a, [u8]>>) -> Self {
+        self.snapshot_version = RawBytes::new(snapshot_version);
+        self.snapshot_version_len =
+            RawInt::new(min(self.snapshot_version.len(), u32::MAX as usize) as u32);
+        self
+    }
+
+    /// Sets the `write_set` value (max length of an item is `u16::MAX`).
+    pub fn with_write_set<T, U>(mut self, write_set: T) -> Self
+    where
+        T: IntoIterator<Item = U>,
+        U: Into<Cow<'a, [u8]>>,
+    {
+        self.write_set = write_set.into_iter().map(RawBytes::new).collect();
+        self.write_set_len = RawInt::new(self.write_set.len() as u32);
+        self
+    }
+
+    /// Sets the `read_set` value (max length of an item is `u16::MAX`).
+    pub fn with_read_set<T, U>(mut self, read_set: T) -> Self
+    where
+        T: IntoIterator<Item = U>,
+        U: Into<Cow<'a, [u8]>>,
+    {
+        self.read_set = read_set.into_iter().map(RawBytes::new).collect();
+        self.read_set_len = RawInt::new(self.read_set.len() as u32);
+        self
+    }
+
+    /// Returns the raw `server_uuid` value.
+    ///
+    /// It's the UUID of the server that executed the transaction.
+    pub fn server_uuid_raw(&'a self) -> &'a [u8] {
+        self.server_uuid.as_bytes()
+    }
+
+    /// Returns the `server_uuid` value as a string (lossy converted).
+    pub fn server_uuid(&'a self) -> Cow<'a, str> {
+        self.server_uuid.as_str()
+    }
+
+    /// Returns the `thread_id` value.
+    pub fn thread_id(&self) -> u32 {
+        *self.thread_id
+    }
+
+    /// Returns `true` if the GTID of the transaction was specified by the user
+    /// (i.e. `gtid_next` wasn't `AUTOMATIC`).
+    pub fn gtid_specified(&self) -> bool {
+        *self.gtid_specified != 0
+    }
+
+    /// Returns the raw `snapshot_version` value (an encoded GTID set).
+    pub fn snapshot_version_raw(&'a self) -> &'a [u8] {
+        self.snapshot_version.as_bytes()
+    }
+
+    /// Parses the `snapshot_version` value.
+    ///
+    /// It's the set of GTIDs executed on the server when the transaction was executed.
+    pub fn snapshot_version(&self) -> io::Result<GtidSet<'_>> {
+        ParseBuf(self.snapshot_version.as_bytes()).parse(())
+    }
+
+    /// Returns an iterator over hashes of rows written by the transaction.
+    pub fn write_set(&self) -> impl Iterator<Item = &[u8]> + '_ {
+        self.write_set.iter().map(|x| x.as_bytes())
+    }
+
+    /// Returns an iterator over hashes of rows read by the transaction.
+    pub fn read_set(&self) -> impl Iterator<Item = &[u8]> + '_ {
+        self.read_set.iter().map(|x| x.as_bytes())
+    }
+
+    /// Returns a `'static` version of `self`.
+    pub fn into_owned(self) -> TransactionContextEvent<'static> {
+        TransactionContextEvent {
+            server_uuid_len: self.server_uuid_len,
+            thread_id: self.thread_id,
+            gtid_specified: self.gtid_specified,
+            snapshot_version_len: self.snapshot_version_len,
+            write_set_len: self.write_set_len,
+            read_set_len: self.read_set_len,
+            server_uuid: self.server_uuid.into_owned(),
+            snapshot_version: self.snapshot_version.into_owned(),
+            write_set: self
+                .write_set
+                .into_iter()
+                .map(RawBytes::into_owned)
+                .collect(),
+            read_set: self
+                .read_set
+                .into_iter()
+                .map(RawBytes::into_owned)
+                .collect(),
+        }
+    }
+
+    /// Parses `count` items of a row set (every item is prefixed by its `u16` length).
+    fn deserialize_set(
+        buf: &mut ParseBuf<'a>,
+        count: u32,
+    ) -> io::Result<Vec<RawBytes<'a, BareU16Bytes>>> {
+        // every item takes at least two bytes
+        let mut set = Vec::with_capacity(min(count as usize, buf.len() / 2));
+        for _ in 0..count {
+            let len: RawInt<LeU16> = buf.parse(())?;
+            set.push(buf.parse(*len as usize)?);
+        }
+        Ok(set)
+    }
+
+    fn serialize_set(set: &[RawBytes<'a, BareU16Bytes>], buf: &mut Vec<u8>) {
+        for item in set {
+            RawInt::<LeU16>::new(min(item.len(), u16::MAX as usize) as u16).serialize(&mut *buf);
+            item.serialize(&mut *buf);
+        }
+    }
+
+    fn set_len(set: &[RawBytes<'a, BareU16Bytes>]) -> S<usize> {
+        let mut len = S(0);
+        for item in set {
+            len += S(2);
+            len += S(min(item.len(), u16::MAX as usize));
+        }
+        len
+    }
+}
+
+impl<'de> MyDeserialize<'de> for TransactionContextEvent<'de> {
+    const SIZE: Option<usize> = None;
+    type Ctx = BinlogCtx<'de>;
+
+    fn deserialize(_ctx: Self::Ctx, buf: &mut ParseBuf<'de>) -> io::Result<Self> {
+        let mut sbuf: ParseBuf = buf.parse(Self::POST_HEADER_LEN)?;
+        let server_uuid_len: RawInt<u8> = sbuf.parse_unchecked(())?;
+        let thread_id = sbuf.parse_unchecked(())?;
+        let gtid_specified = sbuf.parse_unchecked(())?;
+        let snapshot_version_len: RawInt<LeU32> = sbuf.parse_unchecked(())?;
+        let write_set_len: RawInt<LeU32> = sbuf.parse_unchecked(())?;
+        let read_set_len: RawInt<LeU32> = sbuf.parse_unchecked(())?;
+
+        let server_uuid = buf.parse(*server_uuid_len as usize)?;
+        let snapshot_version = buf.parse(*snapshot_version_len as usize)?;
+        let write_set = Self::deserialize_set(&mut *buf, *write_set_len)?;
+        let read_set = Self::deserialize_set(&mut *buf, *read_set_len)?;
+
+        Ok(Self {
+            server_uuid_len,
+            thread_id,
+            gtid_specified,
+            snapshot_version_len,
+            write_set_len,
+            read_set_len,
+            server_uuid,
+            snapshot_version,
+            write_set,
+            read_set,
+        })
+    }
+}
+
+impl MySerialize for TransactionContextEvent<'_> {
+    fn serialize(&self, buf: &mut Vec<u8>) {
+        self.server_uuid_len.serialize(&mut *buf);
+        self.thread_id.serialize(&mut *buf);
+        self.gtid_specified.serialize(&mut *buf);
+        self.snapshot_version_len.serialize(&mut *buf);
+        self.write_set_len.serialize(&mut *buf);
+        self.read_set_len.serialize(&mut *buf);
+
+        self.server_uuid.serialize(&mut *buf);
+        self.snapshot_version.serialize(&mut *buf);
+        Self::serialize_set(&self.write_set, &mut *buf);
+        Self::serialize_set(&self.read_set, &mut *buf);
+    }
+}
+
+impl<'a> BinlogEvent<'a> for TransactionContextEvent<'a> {
+    const EVENT_TYPE: EventType = EventType::TRANSACTION_CONTEXT_EVENT;
+}
+
+impl<'a> BinlogStruct<'a> for TransactionContextEvent<'a> {
+    fn len(&self, _version: BinlogVersion) -> usize {
+        let mut len = S(0);
+
+        len += S(Self::POST_HEADER_LEN);
+        len += S(min(self.server_uuid.len(), u8::MAX as usize));
+        len += S(min(self.snapshot_version.len(), u32::MAX as usize));
+        len += Self::set_len(&self.write_set);
+        len += Self::set_len(&self.read_set);
+
+        min(len.0, u32::MAX as usize - BinlogEventHeader::LEN)
+    }
+}
@@ -0,0 +1,363 @@
+// Copyright (c) 2023 Anatoly Ikorsky
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+use std::{borrow::Cow, cmp::min, convert::TryFrom, io};
+
+use saturating::Saturating as S;
+
+use crate::{
+    binlog::{
+        consts::{
+            BinlogChecksumAlg, BinlogVersion, EventType, PayloadCompressionType,
+            UnknownPayloadCompressionType,
+        },
+        BinlogCtx, BinlogEvent, BinlogStruct,
+    },
+    io::{BufMutExt, ParseBuf},
+    misc::{
+        lenenc_int_len,
+        raw::{bytes::EofBytes, int::LeU32, RawBytes, RawInt},
+        unexpected_buf_eof,
+    },
+    proto::{MyDeserialize, MySerialize},
+};
+
+use super::{BinlogEventFooter, BinlogEventHeader, Event, FormatDescriptionEvent};
+
+/// Marks the end of the payload event header.
+const OTW_PAYLOAD_HEADER_END_MARK: u64 = 0;
+/// Size of the payload field.
+const OTW_PAYLOAD_SIZE_FIELD: u64 = 1;
+/// Compression type field.
+const OTW_PAYLOAD_COMPRESSION_TYPE_FIELD: u64 = 2;
+/// Uncompressed size field.
+const OTW_PAYLOAD_UNCOMPRESSED_SIZE_FIELD: u64 = 3;
+
+/// Compression codec of a transaction payload.
+///
+/// MySql uses [`PayloadCompressionType::ZSTD`] if `binlog_transaction_compression=ON`
+/// (see [`ZstdCompression`], requires the `zstd` feature). [`NoCompression`] stores
+/// the payload as is.
+pub trait PayloadCodec {
+    /// Compression type this codec implements.
+    fn compression_type(&self) -> PayloadCompressionType;
+
+    /// Compresses `input` appending the result to `output`.
+    fn compress(&self, input: &[u8], output: &mut Vec<u8>) -> io::Result<()>;
+
+    /// Decompresses `input` appending the result to `output`.
+    fn decompress(&self, input: &[u8], output: &mut Vec<u8>) -> io::Result<()>;
+}
+
+impl<T: PayloadCodec + ?Sized> PayloadCodec for &T {
+    fn compression_type(&self) -> PayloadCompressionType {
+        (**self).compression_type()
+    }
+
+    fn compress(&self, input: &[u8], output: &mut Vec<u8>) -> io::Result<()> {
+        (**self).compress(input, output)
+    }
+
+    fn decompress(&self, input: &[u8], output: &mut Vec<u8>) -> io::Result<()> {
+        (**self).decompress(input, output)
+    }
+}
+
+/// Codec that stores the payload as is ([`PayloadCompressionType::NONE`]).
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq, Hash)]
+pub struct NoCompression;
+
+impl PayloadCodec for NoCompression {
+    fn compression_type(&self) -> PayloadCompressionType {
+        PayloadCompressionType::NONE
+    }
+
+    fn compress(&self, input: &[u8], output: &mut Vec<u8>) -> io::Result<()> {
+        output.extend_from_slice(input);
+        Ok(())
+    }
+
+    fn decompress(&self, input: &[u8], output: &mut Vec<u8>) -> io::Result<()> {
+        output.extend_from_slice(input);
+        Ok(())
+    }
+}
+
+/// Codec that compresses the payload using zstd ([`PayloadCompressionType::ZSTD`]).
+#[cfg(feature = "zstd")]
+#[cfg_attr(docsrs, doc(cfg(feature = "zstd")))]
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub struct ZstdCompression {
+    level: i32,
+}
+
+#[cfg(feature = "zstd")]
+impl ZstdCompression {
+    /// Compression level used by MySql by default (`binlog_transaction_compression_level_zstd`).
+    pub const DEFAULT_LEVEL: i32 = 3;
+
+    /// Creates a new codec with the given compression level.
+    pub fn new(level: i32) -> Self {
+        Self { level }
+    }
+
+    /// Returns the compression level.
+    pub fn level(&self) -> i32 {
+        self.level
+    }
+}
+
+#[cfg(feature = "zstd")]
+impl Default for ZstdCompression {
+    fn default() -> Self {
+        Self::new(Self::DEFAULT_LEVEL)
+    }
+}
+
+#[cfg(feature = "zstd")]
+impl PayloadCodec for ZstdCompression {
+    fn compression_type(&self) -> PayloadCompressionType {
+        PayloadCompressionType::ZSTD
+    }
+
+    fn compress(&self, input: &[u8], output: &mut Vec<u8>) -> io::Result<()> {
+        zstd::stream::copy_encode(input, output, self.level)
+    }
+
+    fn decompress(&self, input: &[u8], output: &mut Vec<u8>) -> io::Result<()> {
+        zstd::stream::copy_decode(input, output)
+    }
+}
+
+/// Transaction payload event (added in MySql 8.0.20).
+///
+/// Contains a (compressed) sequence of events of a single transaction.
+/// Events within the payload are stored without checksums.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct TransactionPayloadEvent<'a> {
+    /// Raw compression type (see [`PayloadCompressionType`]).
+    compression_type: u64,
+    /// Size of the payload after decompression.
+    uncompressed_size: u64,
+    /// Compressed events.
+    payload: RawBytes<'a, EofBytes>,
+}
+
+impl<'a> TransactionPayloadEvent<'a> {
+    /// Creates a new instance.
+    pub fn new(
+        compression_type: PayloadCompressionType,
+        uncompressed_size: u64,
+        payload: impl Into<Cow<'a, [u8]>>,
+    ) -> Self {
+        Self {
+            compression_type: compression_type as u64,
+            uncompressed_size,
+            payload: RawBytes::new(payload),
+        }
+    }
+
+    /// Wraps the given events into a payload compressed using the given codec.
+    ///
+    /// Events are stored without checksums and with zero `log_pos`.
+    pub fn pack<'e, 'd: 'e>(
+        events: impl IntoIterator<Item = &'e Event<'d>>,
+        codec: impl PayloadCodec,
+    ) -> io::Result<TransactionPayloadEvent<'static>> {
+        let mut uncompressed = Vec::new();
+        for event in events {
+            let event_size = u32::try_from(BinlogEventHeader::LEN + event.data().len())
+                .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "event is too large"))?;
+            let mut header = event.header();
+            header.event_size = RawInt::<LeU32>::new(event_size);
+            header.log_pos = RawInt::<LeU32>::new(0);
+            header.serialize(&mut uncompressed);
+            uncompressed.extend_from_slice(event.data());
+        }
+
+        let mut payload = Vec::new();
+        codec.compress(&uncompressed, &mut payload)?;
+
+        Ok(TransactionPayloadEvent::new(
+            codec.compression_type(),
+            uncompressed.len() as u64,
+            payload,
+        ))
+    }
+
+    /// Decompresses the payload using the given codec and returns contained events.
+    ///
+    /// `fde` is the format description event of the enclosing binlog.
+    pub fn unpack(
+        &self,
+        fde: &FormatDescriptionEvent<'_>,
+        codec: impl PayloadCodec,
+    ) -> io::Result<Vec<Event<'static>>> {
+        if self.compression_type != codec.compression_type() as u64 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "payload compression type {} doesn't match the codec",
+                    self.compression_type
+                ),
+            ));
+        }
+
+        let mut uncompressed = Vec::with_capacity(min(self.uncompressed_size, 1 << 24) as usize);
+        codec.decompress(self.payload.as_bytes(), &mut uncompressed)?;
+        if uncompressed.len() as u64 != self.uncompressed_size {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "uncompressed payload size mismatch",
+            ));
+        }
+
+        let fde = fde.clone().into_owned().with_footer(BinlogEventFooter::new(
+            BinlogChecksumAlg::BINLOG_CHECKSUM_ALG_OFF,
+        ));
+
+        let mut events = Vec::new();
+        let mut buf = &uncompressed[..];
+        while !buf.is_empty() {
+            let header = ParseBuf(buf)
+                .parse::<BinlogEventHeader>(())
+                .map_err(|_| unexpected_buf_eof())?;
+            let event_size = header.event_size() as usize;
+            if event_size < BinlogEventHeader::LEN || event_size > buf.len() {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "invalid event size within the payload",
+                ));
+            }
+            events.push(Event::read(&fde, &buf[..event_size])?);
+            buf = &buf[event_size..];
+        }
+
+        Ok(events)
+    }
+
+    /// Returns the raw compression type.
+    pub fn compression_type_raw(&self) -> u64 {
+        self.compression_type
+    }
+
+    /// Returns the compression type, or raw value if it's unknown.
+    pub fn compression_type(
+        &self,
+    ) -> Result<PayloadCompressionType, UnknownPayloadCompressionType> {
+        PayloadCompressionType::try_from(self.compression_type)
+    }
+
+    /// Returns the size of the payload after decompression.
+    pub fn uncompressed_size(&self) -> u64 {
+        self.uncompressed_size
+    }
+
+    /// Returns the raw (compressed) payload.
+    pub fn payload(&'a self) -> &'a [u8] {
+        self.payload.as_bytes()
+    }
+
+    pub fn into_owned(self) -> TransactionPayloadEvent<'static> {
+        TransactionPayloadEvent {
+            compression_type: self.compression_type,
+            uncompressed_size: self.uncompressed_size,
+            payload: self.payload.into_owned(),
+        }
+    }
+
+    fn fields(&self) -> [(u64, u64); 3] {
+        [
+            (OTW_PAYLOAD_COMPRESSION_TYPE_FIELD, self.compression_type),
+            (OTW_PAYLOAD_UNCOMPRESSED_SIZE_FIELD, self.uncompressed_size),
+            (OTW_PAYLOAD_SIZE_FIELD, self.payload.len() as u64),
+        ]
+    }
+}
+
+impl<'de> MyDeserialize<'de> for TransactionPayloadEvent<'de> {
+    const SIZE: Option<usize> = None;
+    type Ctx = BinlogCtx<'de>;
+
+    fn deserialize(_ctx: Self::Ctx, buf: &mut ParseBuf<'de>) -> io::Result<Self> {
+        let mut payload_size = None;
+        let mut compression_type = PayloadCompressionType::NONE as u64;
+        let mut uncompressed_size = None;
+
+        loop {
+            let field_type = buf
+                .checked_eat_lenenc_int()
+                .ok_or_else(unexpected_buf_eof)?;
+            if field_type == OTW_PAYLOAD_HEADER_END_MARK {
+                break;
+            }
+            let length = buf
+                .checked_eat_lenenc_int()
+                .ok_or_else(unexpected_buf_eof)?;
+            let mut value = buf
+                .checked_eat_buf(length as usize)
+                .ok_or_else(unexpected_buf_eof)?;
+            let value = value
+                .checked_eat_lenenc_int()
+                .ok_or_else(unexpected_buf_eof);
+            match field_type {
+                OTW_PAYLOAD_SIZE_FIELD => payload_size = Some(value?),
+                OTW_PAYLOAD_COMPRESSION_TYPE_FIELD => compression_type = value?,
+                OTW_PAYLOAD_UNCOMPRESSED_SIZE_FIELD => uncompressed_size = Some(value?),
+                // unknown fields are skipped
+                _ => (),
+            }
+        }
+
+        let payload: RawBytes<'de, EofBytes> = buf.parse(())?;
+        if matches!(payload_size, Some(size) if size != payload.len() as u64) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "transaction payload size mismatch",
+            ));
+        }
+
+        Ok(Self {
+            compression_type,
+            uncompressed_size: uncompressed_size.unwrap_or(payload.len() as u64),
+            payload,
+        })
+    }
+}
+
+impl MySerialize for TransactionPayloadEvent<'_> {
+    fn serialize(&self, buf: &mut Vec<u8>) {
+        for (field_type, value) in self.fields() {
+            buf.put_lenenc_int(field_type);
+            buf.put_lenenc_int(lenenc_int_len(value));
+            buf.put_lenenc_int(value);
+        }
+        buf.put_lenenc_int(OTW_PAYLOAD_HEADER_END_MARK);
+        self.payload.serialize(&mut *buf);
+    }
+}
+
+impl<'a> BinlogEvent<'a> for TransactionPayloadEvent<'a> {
+    const EVENT_TYPE: EventType = EventType::TRANSACTION_PAYLOAD_EVENT;
+}
+
+impl<'a> BinlogStruct<'a> for TransactionPayloadEvent<'a> {
+    fn len(&self, _version: BinlogVersion) -> usize {
+        let mut len = S(0);
+
+        for (field_type, value) in self.fields() {
+            len += S(lenenc_int_len(field_type) as usize);
+            len += S(lenenc_int_len(lenenc_int_len(value)) as usize);
+            len += S(lenenc_int_len(value) as usize);
+        }
+        len += S(lenenc_int_len(OTW_PAYLOAD_HEADER_END_MARK) as usize);
+        len += S(self.payload.len());
+
+        min(len.0, u32::MAX as usize - BinlogEventHeader::LEN)
+    }
+}
@@ -12,7 +12,7 @@ use std::io::{self};
 
 use crate::{
     binlog::{
-        consts::{BinlogVersion, EventType},
+        consts::{BinlogVersion, EventType, RowsEventFlags},
         BinlogCtx, BinlogEvent, BinlogStruct,
     },
     io::ParseBuf,
@@ -24,14 +24,43 @@ use super::{rows_event::RowsEventCtx, RowsEvent, RowsEventRows, TableMapEvent};
 /// Update rows event v1 (mariadb and mysql 5.1.15-5.6.x).
 #[derive(Debug, Clone, Eq, PartialEq, Hash)]
 #[repr(transparent)]
-pub struct UpdateRowsEventV1<'a>(RowsEvent<'a>);
+pub struct UpdateRowsEventV1<'a>(pub(super) RowsEvent<'a>);
 
 impl<'a> UpdateRowsEventV1<'a> {
+    /// Returns the actual event type of this event.
+    ///
+    /// It differs from [`BinlogEvent::EVENT_TYPE`] for a MariaDB compressed rows event.
+    pub fn event_type(&self) -> EventType {
+        self.0.event_type()
+    }
+
+    /// Returns `true` if this is a MariaDB compressed rows event.
+    ///
+    /// Rows data of such an event is decompressed transparently.
+    pub fn is_compressed(&self) -> bool {
+        self.0.is_compressed()
+    }
+
     /// Returns the number that identifies the table (see `TableMapEvent`).
     pub fn table_id(&self) -> u64 {
         self.0.table_id()
     }
 
+    /// Defines the table identifier.
+    pub fn with_table_id(self, table_id: u64) -> Self {
+        Self(self.0.with_table_id(table_id))
+    }
+
+    /// Returns the raw rows event flags.
+    pub fn flags_raw(&self) -> u16 {
+        self.0.flags_raw()
+    }
+
+    /// Returns rows event flags. Unknown bits will be truncated.
+    pub fn flags(&self) -> RowsEventFlags {
+        self.0.flags()
+    }
+
     /// Returns the number of columns in the table.
     pub fn num_columns(&self) -> u64 {
         self.0.num_columns()
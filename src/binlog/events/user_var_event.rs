@@ -9,6 +9,7 @@
 use std::{
     borrow::Cow,
     cmp::min,
+    convert::TryInto,
     io::{self},
 };
 
@@ -18,6 +19,8 @@ use saturating::Saturating as S;
 use crate::{
     binlog::{
         consts::{BinlogVersion, EventType, UserVarFlags},
+        decimal::Decimal,
+        misc::decode_text,
         BinlogCtx, BinlogEvent, BinlogStruct,
     },
     constants::{ItemResult, UnknownItemResultType},
@@ -54,6 +57,44 @@ pub struct UserVarEvent<'a> {
 }
 
 impl<'a> UserVarEvent<'a> {
+    /// Creates a new instance.
+    ///
+    /// `value` is the binary representation of a value of the given type
+    /// (see [`Self::value`]).
+    pub fn new(
+        name: impl Into<Cow<'a, [u8]>>,
+        value_type: ItemResult,
+        charset: u32,
+        value: impl Into<Cow<'a, [u8]>>,
+    ) -> Self {
+        Self {
+            name: RawBytes::new(name),
+            is_null: false,
+            value_type: RawConst::new(value_type as i8),
+            charset: RawInt::new(charset),
+            value: RawBytes::new(value),
+            flags: RawFlags::default(),
+        }
+    }
+
+    /// Creates a new instance with a `NULL` value.
+    pub fn null(name: impl Into<Cow<'a, [u8]>>) -> Self {
+        Self {
+            name: RawBytes::new(name),
+            is_null: true,
+            value_type: RawConst::new(ItemResult::STRING_RESULT as i8),
+            charset: RawInt::new(63),
+            value: RawBytes::default(),
+            flags: RawFlags::default(),
+        }
+    }
+
+    /// Sets the `flags` value.
+    pub fn with_flags(mut self, flags: UserVarFlags) -> Self {
+        self.flags = RawFlags::new(flags.bits());
+        self
+    }
+
     /// Returns the raw name of the variable.
     pub fn name_raw(&'a self) -> &'a [u8] {
         self.name.as_bytes()
@@ -84,6 +125,71 @@ impl<'a> UserVarEvent<'a> {
         self.value.as_bytes()
     }
 
+    /// Returns the string value decoded using the value charset
+    /// (`None` if [`Self::is_null`] is `true`).
+    ///
+    /// Returns an error if the value type isn't `STRING_RESULT`
+    /// or if the value can't be decoded (see [`decode_text`]).
+    pub fn value_str(&'a self) -> io::Result<Option<Cow<'a, str>>> {
+        match self.typed_value(ItemResult::STRING_RESULT)? {
+            Some(value) => decode_text(self.charset.0, value).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Returns the signed integer value (`None` if [`Self::is_null`] is `true`).
+    ///
+    /// Returns an error if the value type isn't `INT_RESULT`
+    /// or if an unsigned value doesn't fit `i64`.
+    pub fn value_int(&self) -> io::Result<Option<i64>> {
+        match self.int_value()? {
+            Some(value) if self.flags().contains(UserVarFlags::UNSIGNED) && value < 0 => Err(
+                io::Error::new(io::ErrorKind::InvalidData, "unsigned value doesn't fit i64"),
+            ),
+            value => Ok(value),
+        }
+    }
+
+    /// Returns the unsigned integer value (`None` if [`Self::is_null`] is `true`).
+    ///
+    /// Returns an error if the value type isn't `INT_RESULT`
+    /// or if a signed value is negative.
+    pub fn value_uint(&self) -> io::Result<Option<u64>> {
+        match self.int_value()? {
+            Some(value) if !self.flags().contains(UserVarFlags::UNSIGNED) && value < 0 => Err(
+                io::Error::new(io::ErrorKind::InvalidData, "negative value doesn't fit u64"),
+            ),
+            value => Ok(value.map(|x| x as u64)),
+        }
+    }
+
+    /// Returns the floating point value (`None` if [`Self::is_null`] is `true`).
+    ///
+    /// Returns an error if the value type isn't `REAL_RESULT`.
+    pub fn value_real(&self) -> io::Result<Option<f64>> {
+        match self.typed_value(ItemResult::REAL_RESULT)? {
+            Some(value) => value
+                .try_into()
+                .map(|x| Some(f64::from_le_bytes(x)))
+                .map_err(|_| invalid_value_len()),
+            None => Ok(None),
+        }
+    }
+
+    /// Returns the decimal value (`None` if [`Self::is_null`] is `true`).
+    ///
+    /// Returns an error if the value type isn't `DECIMAL_RESULT`.
+    pub fn value_decimal(&self) -> io::Result<Option<Decimal>> {
+        match self.typed_value(ItemResult::DECIMAL_RESULT)? {
+            // precision and scale are followed by the binary decimal
+            Some([precision, scale, value @ ..]) => {
+                Decimal::read_bin(value, *precision as usize, *scale as usize, false).map(Some)
+            }
+            Some(_) => Err(invalid_value_len()),
+            None => Ok(None),
+        }
+    }
+
     /// Returns the raw flags.
     pub fn flags_raw(&self) -> u8 {
         self.flags.0
@@ -94,6 +200,31 @@ impl<'a> UserVarEvent<'a> {
         self.flags.get()
     }
 
+    /// Returns the raw value, if the value type is `expected` (`None` if the value is null).
+    fn typed_value(&self, expected: ItemResult) -> io::Result<Option<&[u8]>> {
+        if self.is_null {
+            return Ok(None);
+        }
+        match self.value_type() {
+            Ok(value_type) if value_type == expected => Ok(Some(self.value.as_bytes())),
+            Ok(value_type) => Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("user variable type is {:?}, not {:?}", value_type, expected),
+            )),
+            Err(err) => Err(io::Error::new(io::ErrorKind::InvalidData, err)),
+        }
+    }
+
+    fn int_value(&self) -> io::Result<Option<i64>> {
+        match self.typed_value(ItemResult::INT_RESULT)? {
+            Some(value) => value
+                .try_into()
+                .map(|x| Some(i64::from_le_bytes(x)))
+                .map_err(|_| invalid_value_len()),
+            None => Ok(None),
+        }
+    }
+
     pub fn into_owned(self) -> UserVarEvent<'static> {
         UserVarEvent {
             name: self.name.into_owned(),
@@ -106,6 +237,13 @@ impl<'a> UserVarEvent<'a> {
     }
 }
 
+fn invalid_value_len() -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        "invalid length of a user variable value",
+    )
+}
+
 impl<'de> MyDeserialize<'de> for UserVarEvent<'de> {
     const SIZE: Option<usize> = None;
     type Ctx = BinlogCtx<'de>;
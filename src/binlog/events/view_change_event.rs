@@ -0,0 +1,209 @@
+// Copyright (c) 2023 Anatoly Ikorsky
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+use std::{borrow::Cow, cmp::min, io};
+
+use saturating::Saturating as S;
+
+use crate::{
+    binlog::{
+        consts::{BinlogVersion, EventType},
+        BinlogCtx, BinlogEvent, BinlogStruct,
+    },
+    io::ParseBuf,
+    misc::raw::{
+        bytes::{BareBytes, BareU16Bytes, FixedLengthText},
+        int::*,
+        RawBytes, RawInt,
+    },
+    proto::{MyDeserialize, MySerialize},
+};
+
+use super::BinlogEventHeader;
+
+/// Max length of a view id.
+const VIEW_ID_LEN: usize = 40;
+
+/// View change event (added in MySql 5.7).
+///
+/// Written by Group Replication when the group membership changes, it marks the point
+/// where a new view was installed and carries the certification info needed by joining members.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct ViewChangeEvent<'a> {
+    // post-header
+    view_id: RawBytes<'a, FixedLengthText<{ VIEW_ID_LEN }>>,
+    seq_number: RawInt<LeU64>,
+    cert_info_len: RawInt<LeU32>,
+
+    // payload
+    certification_info: Vec<CertificationInfoEntry<'a>>,
+}
+
+/// Key and value of the certification info map.
+type CertificationInfoEntry<'a> = (
+    RawBytes<'a, BareU16Bytes>,
+    RawBytes<'a, BareBytes<{ u32::MAX as usize }>>,
+);
+
+impl<'a> ViewChangeEvent<'a> {
+    /// Length of the post-header.
+    const POST_HEADER_LEN: usize = VIEW_ID_LEN + 8 + 4;
+
+    /// Creates a new instance with empty certification info.
+    pub fn new(view_id: impl Into<Cow<'a, [u8]>>) -> Self {
+        Self {
+            view_id: RawBytes::new(view_id),
+            seq_number: Default::default(),
+            cert_info_len: Default::default(),
+            certification_info: Vec::new(),
+        }
+    }
+
+    /// Sets the `view_id` value (max length is `40`).
+    pub fn with_view_id(mut self, view_id: impl Into<Cow<'a, [u8]>>) -> Self {
+        self.view_id = RawBytes::new(view_id);
+        self
+    }
+
+    /// Sets the `seq_number` value.
+    pub fn with_seq_number(mut self, seq_number: u64) -> Self {
+        self.seq_number = RawInt::new(seq_number);
+        self
+    }
+
+    /// Sets the `certification_info` value.
+    ///
+    /// Max length of a key is `u16::MAX`, max length of a value is `u32::MAX`.
+    pub fn with_certification_info<T, K, V>(mut self, certification_info: T) -> Self
+    where
+        T: IntoIterator<Item = (K, V)>,
+        K: Into<Cow<'a, [u8]>>,
+        V: Into<Cow<'a, [u8]>>,
+    {
+        self.certification_info = certification_info
+            .into_iter()
+            .map(|(k, v)| (RawBytes::new(k), RawBytes::new(v)))
+            .collect();
+        self.cert_info_len = RawInt::new(self.certification_info.len() as u32);
+        self
+    }
+
+    /// Returns the raw `view_id` value (without the zero padding).
+    ///
+    /// It's the identifier of the installed view, e.g. `15732871249421424:3`.
+    pub fn view_id_raw(&'a self) -> &'a [u8] {
+        self.view_id.as_bytes()
+    }
+
+    /// Returns the `view_id` value as a string (lossy converted).
+    pub fn view_id(&'a self) -> Cow<'a, str> {
+        self.view_id.as_str()
+    }
+
+    /// Returns the `seq_number` value.
+    pub fn seq_number(&self) -> u64 {
+        *self.seq_number
+    }
+
+    /// Returns an iterator over the certification info map (key, value).
+    ///
+    /// Keys are hashes of written rows (or `group_gtid_executed`, etc.)
+    /// and values are encoded GTID sets.
+    pub fn certification_info(&self) -> impl Iterator<Item = (&[u8], &[u8])> + '_ {
+        self.certification_info
+            .iter()
+            .map(|(k, v)| (k.as_bytes(), v.as_bytes()))
+    }
+
+    /// Returns a `'static` version of `self`.
+    pub fn into_owned(self) -> ViewChangeEvent<'static> {
+        ViewChangeEvent {
+            view_id: self.view_id.into_owned(),
+            seq_number: self.seq_number,
+            cert_info_len: self.cert_info_len,
+            certification_info: self
+                .certification_info
+                .into_iter()
+                .map(|(k, v)| (k.into_owned(), v.into_owned()))
+                .collect(),
+        }
+    }
+}
+
+impl<'de> MyDeserialize<'de> for ViewChangeEvent<'de> {
+    const SIZE: Option<usize> = None;
+    type Ctx = BinlogCtx<'de>;
+
+    fn deserialize(_ctx: Self::Ctx, buf: &mut ParseBuf<'de>) -> io::Result<Self> {
+        let mut sbuf: ParseBuf = buf.parse(Self::POST_HEADER_LEN)?;
+        let view_id: ParseBuf = sbuf.parse_unchecked(VIEW_ID_LEN)?;
+        let seq_number = sbuf.parse_unchecked(())?;
+        let cert_info_len: RawInt<LeU32> = sbuf.parse_unchecked(())?;
+
+        // strip the zero padding
+        let view_id_len = view_id
+            .0
+            .iter()
+            .position(|x| *x == 0)
+            .unwrap_or(VIEW_ID_LEN);
+        let view_id = RawBytes::new(&view_id.0[..view_id_len]);
+
+        // every entry takes at least six bytes
+        let mut certification_info =
+            Vec::with_capacity(min(*cert_info_len as usize, buf.len() / 6));
+        for _ in 0..*cert_info_len {
+            let key_len: RawInt<LeU16> = buf.parse(())?;
+            let key = buf.parse(*key_len as usize)?;
+            let value_len: RawInt<LeU32> = buf.parse(())?;
+            let value = buf.parse(*value_len as usize)?;
+            certification_info.push((key, value));
+        }
+
+        Ok(Self {
+            view_id,
+            seq_number,
+            cert_info_len,
+            certification_info,
+        })
+    }
+}
+
+impl MySerialize for ViewChangeEvent<'_> {
+    fn serialize(&self, buf: &mut Vec<u8>) {
+        self.view_id.serialize(&mut *buf);
+        self.seq_number.serialize(&mut *buf);
+        self.cert_info_len.serialize(&mut *buf);
+
+        for (key, value) in &self.certification_info {
+            RawInt::<LeU16>::new(min(key.len(), u16::MAX as usize) as u16).serialize(&mut *buf);
+            key.serialize(&mut *buf);
+            RawInt::<LeU32>::new(min(value.len(), u32::MAX as usize) as u32).serialize(&mut *buf);
+            value.serialize(&mut *buf);
+        }
+    }
+}
+
+impl<'a> BinlogEvent<'a> for ViewChangeEvent<'a> {
+    const EVENT_TYPE: EventType = EventType::VIEW_CHANGE_EVENT;
+}
+
+impl<'a> BinlogStruct<'a> for ViewChangeEvent<'a> {
+    fn len(&self, _version: BinlogVersion) -> usize {
+        let mut len = S(0);
+
+        len += S(Self::POST_HEADER_LEN);
+        for (key, value) in &self.certification_info {
+            len += S(2);
+            len += S(min(key.len(), u16::MAX as usize));
+            len += S(4);
+            len += S(min(value.len(), u32::MAX as usize));
+        }
+
+        min(len.0, u32::MAX as usize - BinlogEventHeader::LEN)
+    }
+}
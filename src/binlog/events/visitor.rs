@@ -0,0 +1,284 @@
+// Copyright (c) 2023 Anatoly Ikorsky
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+use crate::binlog::{
+    consts::EventType,
+    mariadb::{AnnotateRowsEvent, BinlogCheckpointEvent, MariadbGtidEvent, MariadbGtidListEvent},
+};
+
+use super::{
+    AnonymousGtidEvent, BeginLoadQueryEvent, EventData, ExecuteLoadQueryEvent,
+    FormatDescriptionEvent, GtidEvent, HeartbeatEvent, IgnorableEvent, IncidentEvent, IntvarEvent,
+    LoadEvent, NewLoadEvent, PreviousGtidsEvent, QueryEvent, RandEvent, RotateEvent, RowsEventData,
+    RowsQueryEvent, StopEvent, TableMapEvent, TransactionContextEvent, TransactionPayloadEvent,
+    UserVarEvent, ViewChangeEvent, XidEvent,
+};
+
+/// Visitor over parsed event data (see [`EventData::accept`]).
+///
+/// There is a method per kind of event. Every method falls back to
+/// [`EventVisitor::visit_other`] by default, so an implementation overrides only the kinds
+/// it's interested in and keeps compiling when new kinds of events are supported.
+///
+/// Events that are not parsed by this implementation (see [`EventData`]) are visited
+/// by [`EventVisitor::visit_raw_event`].
+pub trait EventVisitor<'a> {
+    /// Result of a visit.
+    type Output;
+
+    /// Visits an event of the given type that isn't handled by a more specific method.
+    fn visit_other(&mut self, event_type: EventType) -> Self::Output;
+
+    /// Visits an event whose data is not parsed (the data is empty for events without one,
+    /// i.e. [`EventData::UnknownEvent`] and [`EventData::SlaveEvent`]).
+    fn visit_raw_event(&mut self, event_type: EventType, data: &[u8]) -> Self::Output {
+        let _ = data;
+        self.visit_other(event_type)
+    }
+
+    /// Visits a query event.
+    fn visit_query_event(&mut self, _event: &QueryEvent<'a>) -> Self::Output {
+        self.visit_other(EventType::QUERY_EVENT)
+    }
+
+    /// Visits a stop event.
+    fn visit_stop_event(&mut self, _event: &StopEvent) -> Self::Output {
+        self.visit_other(EventType::STOP_EVENT)
+    }
+
+    /// Visits a rotate event.
+    fn visit_rotate_event(&mut self, _event: &RotateEvent<'a>) -> Self::Output {
+        self.visit_other(EventType::ROTATE_EVENT)
+    }
+
+    /// Visits an intvar event.
+    fn visit_intvar_event(&mut self, _event: &IntvarEvent) -> Self::Output {
+        self.visit_other(EventType::INTVAR_EVENT)
+    }
+
+    /// Visits a load event.
+    fn visit_load_event(&mut self, _event: &LoadEvent<'a>) -> Self::Output {
+        self.visit_other(EventType::LOAD_EVENT)
+    }
+
+    /// Visits a new load event.
+    fn visit_new_load_event(&mut self, _event: &NewLoadEvent<'a>) -> Self::Output {
+        self.visit_other(EventType::NEW_LOAD_EVENT)
+    }
+
+    /// Visits a rand event.
+    fn visit_rand_event(&mut self, _event: &RandEvent) -> Self::Output {
+        self.visit_other(EventType::RAND_EVENT)
+    }
+
+    /// Visits a user var event.
+    fn visit_user_var_event(&mut self, _event: &UserVarEvent<'a>) -> Self::Output {
+        self.visit_other(EventType::USER_VAR_EVENT)
+    }
+
+    /// Visits a format description event.
+    fn visit_format_description_event(
+        &mut self,
+        _event: &FormatDescriptionEvent<'a>,
+    ) -> Self::Output {
+        self.visit_other(EventType::FORMAT_DESCRIPTION_EVENT)
+    }
+
+    /// Visits an XID event.
+    fn visit_xid_event(&mut self, _event: &XidEvent) -> Self::Output {
+        self.visit_other(EventType::XID_EVENT)
+    }
+
+    /// Visits a begin load query event.
+    fn visit_begin_load_query_event(&mut self, _event: &BeginLoadQueryEvent<'a>) -> Self::Output {
+        self.visit_other(EventType::BEGIN_LOAD_QUERY_EVENT)
+    }
+
+    /// Visits an execute load query event.
+    fn visit_execute_load_query_event(
+        &mut self,
+        _event: &ExecuteLoadQueryEvent<'a>,
+    ) -> Self::Output {
+        self.visit_other(EventType::EXECUTE_LOAD_QUERY_EVENT)
+    }
+
+    /// Visits a table map event.
+    fn visit_table_map_event(&mut self, _event: &TableMapEvent<'a>) -> Self::Output {
+        self.visit_other(EventType::TABLE_MAP_EVENT)
+    }
+
+    /// Visits an incident event.
+    fn visit_incident_event(&mut self, _event: &IncidentEvent<'a>) -> Self::Output {
+        self.visit_other(EventType::INCIDENT_EVENT)
+    }
+
+    /// Visits a heartbeat event.
+    fn visit_heartbeat_event(&mut self, _event: &HeartbeatEvent<'a>) -> Self::Output {
+        self.visit_other(EventType::HEARTBEAT_EVENT)
+    }
+
+    /// Visits an ignorable event.
+    fn visit_ignorable_event(&mut self, _event: &IgnorableEvent<'a>) -> Self::Output {
+        self.visit_other(EventType::IGNORABLE_EVENT)
+    }
+
+    /// Visits a rows query event.
+    fn visit_rows_query_event(&mut self, _event: &RowsQueryEvent<'a>) -> Self::Output {
+        self.visit_other(EventType::ROWS_QUERY_EVENT)
+    }
+
+    /// Visits a GTID event.
+    fn visit_gtid_event(&mut self, _event: &GtidEvent) -> Self::Output {
+        self.visit_other(EventType::GTID_EVENT)
+    }
+
+    /// Visits an anonymous GTID event.
+    fn visit_anonymous_gtid_event(&mut self, _event: &AnonymousGtidEvent) -> Self::Output {
+        self.visit_other(EventType::ANONYMOUS_GTID_EVENT)
+    }
+
+    /// Visits a previous GTIDs event.
+    fn visit_previous_gtids_event(&mut self, _event: &PreviousGtidsEvent<'a>) -> Self::Output {
+        self.visit_other(EventType::PREVIOUS_GTIDS_EVENT)
+    }
+
+    /// Visits a transaction context event.
+    fn visit_transaction_context_event(
+        &mut self,
+        _event: &TransactionContextEvent<'a>,
+    ) -> Self::Output {
+        self.visit_other(EventType::TRANSACTION_CONTEXT_EVENT)
+    }
+
+    /// Visits a view change event.
+    fn visit_view_change_event(&mut self, _event: &ViewChangeEvent<'a>) -> Self::Output {
+        self.visit_other(EventType::VIEW_CHANGE_EVENT)
+    }
+
+    /// Visits a rows event (of any kind, see [`RowsEventData::event_type`]).
+    fn visit_rows_event(&mut self, event: &RowsEventData<'a>) -> Self::Output {
+        self.visit_other(event.event_type())
+    }
+
+    /// Visits a transaction payload event.
+    fn visit_transaction_payload_event(
+        &mut self,
+        _event: &TransactionPayloadEvent<'a>,
+    ) -> Self::Output {
+        self.visit_other(EventType::TRANSACTION_PAYLOAD_EVENT)
+    }
+
+    /// Visits a MariaDB annotate rows event.
+    fn visit_mariadb_annotate_rows_event(
+        &mut self,
+        _event: &AnnotateRowsEvent<'a>,
+    ) -> Self::Output {
+        self.visit_other(EventType::MARIADB_ANNOTATE_ROWS_EVENT)
+    }
+
+    /// Visits a MariaDB binlog checkpoint event.
+    fn visit_mariadb_binlog_checkpoint_event(
+        &mut self,
+        _event: &BinlogCheckpointEvent<'a>,
+    ) -> Self::Output {
+        self.visit_other(EventType::MARIADB_BINLOG_CHECKPOINT_EVENT)
+    }
+
+    /// Visits a MariaDB GTID event.
+    fn visit_mariadb_gtid_event(&mut self, _event: &MariadbGtidEvent<'a>) -> Self::Output {
+        self.visit_other(EventType::MARIADB_GTID_EVENT)
+    }
+
+    /// Visits a MariaDB GTID list event.
+    fn visit_mariadb_gtid_list_event(&mut self, _event: &MariadbGtidListEvent<'a>) -> Self::Output {
+        self.visit_other(EventType::MARIADB_GTID_LIST_EVENT)
+    }
+}
+
+impl<'a> EventData<'a> {
+    /// Calls the method of the given visitor that corresponds to the kind of this event.
+    pub fn accept<V: EventVisitor<'a>>(&self, visitor: &mut V) -> V::Output {
+        match self {
+            EventData::UnknownEvent => visitor.visit_raw_event(EventType::UNKNOWN_EVENT, &[]),
+            EventData::SlaveEvent => visitor.visit_raw_event(EventType::SLAVE_EVENT, &[]),
+            EventData::StartEventV3(data) => {
+                visitor.visit_raw_event(EventType::START_EVENT_V3, data)
+            }
+            EventData::CreateFileEvent(data) => {
+                visitor.visit_raw_event(EventType::CREATE_FILE_EVENT, data)
+            }
+            EventData::AppendBlockEvent(data) => {
+                visitor.visit_raw_event(EventType::APPEND_BLOCK_EVENT, data)
+            }
+            EventData::ExecLoadEvent(data) => {
+                visitor.visit_raw_event(EventType::EXEC_LOAD_EVENT, data)
+            }
+            EventData::DeleteFileEvent(data) => {
+                visitor.visit_raw_event(EventType::DELETE_FILE_EVENT, data)
+            }
+            EventData::PreGaWriteRowsEvent(data) => {
+                visitor.visit_raw_event(EventType::PRE_GA_WRITE_ROWS_EVENT, data)
+            }
+            EventData::PreGaUpdateRowsEvent(data) => {
+                visitor.visit_raw_event(EventType::PRE_GA_UPDATE_ROWS_EVENT, data)
+            }
+            EventData::PreGaDeleteRowsEvent(data) => {
+                visitor.visit_raw_event(EventType::PRE_GA_DELETE_ROWS_EVENT, data)
+            }
+            EventData::XaPrepareLogEvent(data) => {
+                visitor.visit_raw_event(EventType::XA_PREPARE_LOG_EVENT, data)
+            }
+            EventData::MariadbStartEncryptionEvent(data) => {
+                visitor.visit_raw_event(EventType::MARIADB_START_ENCRYPTION_EVENT, data)
+            }
+            EventData::MariadbQueryCompressedEvent(data) => {
+                visitor.visit_raw_event(EventType::MARIADB_QUERY_COMPRESSED_EVENT, data)
+            }
+            EventData::QueryEvent(event) => visitor.visit_query_event(event),
+            EventData::StopEvent(event) => visitor.visit_stop_event(event),
+            EventData::RotateEvent(event) => visitor.visit_rotate_event(event),
+            EventData::IntvarEvent(event) => visitor.visit_intvar_event(event),
+            EventData::LoadEvent(event) => visitor.visit_load_event(event),
+            EventData::NewLoadEvent(event) => visitor.visit_new_load_event(event),
+            EventData::RandEvent(event) => visitor.visit_rand_event(event),
+            EventData::UserVarEvent(event) => visitor.visit_user_var_event(event),
+            EventData::FormatDescriptionEvent(event) => {
+                visitor.visit_format_description_event(event)
+            }
+            EventData::XidEvent(event) => visitor.visit_xid_event(event),
+            EventData::BeginLoadQueryEvent(event) => visitor.visit_begin_load_query_event(event),
+            EventData::ExecuteLoadQueryEvent(event) => {
+                visitor.visit_execute_load_query_event(event)
+            }
+            EventData::TableMapEvent(event) => visitor.visit_table_map_event(event),
+            EventData::IncidentEvent(event) => visitor.visit_incident_event(event),
+            EventData::HeartbeatEvent(event) => visitor.visit_heartbeat_event(event),
+            EventData::IgnorableEvent(event) => visitor.visit_ignorable_event(event),
+            EventData::RowsQueryEvent(event) => visitor.visit_rows_query_event(event),
+            EventData::GtidEvent(event) => visitor.visit_gtid_event(event),
+            EventData::AnonymousGtidEvent(event) => visitor.visit_anonymous_gtid_event(event),
+            EventData::PreviousGtidsEvent(event) => visitor.visit_previous_gtids_event(event),
+            EventData::TransactionContextEvent(event) => {
+                visitor.visit_transaction_context_event(event)
+            }
+            EventData::ViewChangeEvent(event) => visitor.visit_view_change_event(event),
+            EventData::RowsEvent(event) => visitor.visit_rows_event(event),
+            EventData::TransactionPayloadEvent(event) => {
+                visitor.visit_transaction_payload_event(event)
+            }
+            EventData::MariadbAnnotateRowsEvent(event) => {
+                visitor.visit_mariadb_annotate_rows_event(event)
+            }
+            EventData::MariadbBinlogCheckpointEvent(event) => {
+                visitor.visit_mariadb_binlog_checkpoint_event(event)
+            }
+            EventData::MariadbGtidEvent(event) => visitor.visit_mariadb_gtid_event(event),
+            EventData::MariadbGtidListEvent(event) => visitor.visit_mariadb_gtid_list_event(event),
+        }
+    }
+}
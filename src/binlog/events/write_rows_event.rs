@@ -12,28 +12,66 @@ use std::io::{self};
 
 use crate::{
     binlog::{
-        consts::{BinlogVersion, EventType},
+        consts::{BinlogVersion, EventType, RowsEventFlags},
         BinlogCtx, BinlogEvent, BinlogStruct,
     },
     io::ParseBuf,
     proto::{MyDeserialize, MySerialize},
 };
 
-use super::{rows_event::RowsEventCtx, RowsEvent, RowsEventRows, TableMapEvent};
+use super::{
+    rows_event::{RowsEventBuilder, RowsEventCtx},
+    RowsEvent, RowsEventRows, TableMapEvent,
+};
 
 /// Write rows event.
 ///
 /// Used for row-based binary logging. Contains the row data to insert.
 #[derive(Debug, Clone, Eq, PartialEq, Hash)]
 #[repr(transparent)]
-pub struct WriteRowsEvent<'a>(RowsEvent<'a>);
+pub struct WriteRowsEvent<'a>(pub(super) RowsEvent<'a>);
 
 impl<'a> WriteRowsEvent<'a> {
+    /// Returns a builder of this event for the table defined by the given `TableMapEvent`
+    /// (see [`RowsEventBuilder`]).
+    pub fn builder(table_map_event: &'a TableMapEvent<'a>) -> RowsEventBuilder<'a> {
+        RowsEventBuilder::new(Self::EVENT_TYPE, table_map_event)
+    }
+
+    /// Returns the actual event type of this event.
+    ///
+    /// It differs from [`BinlogEvent::EVENT_TYPE`] for a MariaDB compressed rows event.
+    pub fn event_type(&self) -> EventType {
+        self.0.event_type()
+    }
+
+    /// Returns `true` if this is a MariaDB compressed rows event.
+    ///
+    /// Rows data of such an event is decompressed transparently.
+    pub fn is_compressed(&self) -> bool {
+        self.0.is_compressed()
+    }
+
     /// Returns the number that identifies the table (see `TableMapEvent`).
     pub fn table_id(&self) -> u64 {
         self.0.table_id()
     }
 
+    /// Defines the table identifier.
+    pub fn with_table_id(self, table_id: u64) -> Self {
+        Self(self.0.with_table_id(table_id))
+    }
+
+    /// Returns the raw rows event flags.
+    pub fn flags_raw(&self) -> u16 {
+        self.0.flags_raw()
+    }
+
+    /// Returns rows event flags. Unknown bits will be truncated.
+    pub fn flags(&self) -> RowsEventFlags {
+        self.0.flags()
+    }
+
     /// Returns the number of columns in the table.
     pub fn num_columns(&self) -> u64 {
         self.0.num_columns()
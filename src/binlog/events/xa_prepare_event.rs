@@ -0,0 +1,143 @@
+// Copyright (c) 2023 Anatoly Ikorsky
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+use std::{borrow::Cow, io};
+
+use saturating::Saturating as S;
+
+use crate::{
+    binlog::{
+        consts::{BinlogVersion, EventType},
+        xa::{XaStatement, XaTransactionId},
+        BinlogCtx, BinlogEvent, BinlogStruct,
+    },
+    io::ParseBuf,
+    misc::{
+        raw::{int::*, RawInt},
+        unexpected_buf_eof,
+    },
+    proto::{MyDeserialize, MySerialize},
+};
+
+/// XA prepare event (added in MySql 5.7).
+///
+/// Terminates the event group of a prepared XA transaction (`XA PREPARE`),
+/// or of a transaction committed by `XA COMMIT … ONE PHASE`, similarly to [`super::XidEvent`].
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct XaPrepareEvent<'a> {
+    one_phase: RawInt<u8>,
+    xid: XaTransactionId<'a>,
+}
+
+impl<'a> XaPrepareEvent<'a> {
+    /// Creates a new instance.
+    pub fn new(xid: XaTransactionId<'a>, one_phase: bool) -> Self {
+        Self {
+            one_phase: RawInt::new(one_phase as u8),
+            xid,
+        }
+    }
+
+    /// Returns `true` if the transaction is committed in one phase (`XA COMMIT … ONE PHASE`).
+    pub fn one_phase(&self) -> bool {
+        *self.one_phase != 0
+    }
+
+    /// Returns the XID of the transaction.
+    pub fn xid(&self) -> &XaTransactionId<'a> {
+        &self.xid
+    }
+
+    /// Returns the XA statement that produced this event
+    /// (either `XA PREPARE` or `XA COMMIT … ONE PHASE`).
+    pub fn xa_statement(&self) -> XaStatement<'a> {
+        if self.one_phase() {
+            XaStatement::Commit {
+                xid: self.xid.clone(),
+                one_phase: true,
+            }
+        } else {
+            XaStatement::Prepare(self.xid.clone())
+        }
+    }
+
+    /// Returns a `'static` version of `self`.
+    pub fn into_owned(self) -> XaPrepareEvent<'static> {
+        XaPrepareEvent {
+            one_phase: self.one_phase,
+            xid: self.xid.into_owned(),
+        }
+    }
+}
+
+impl<'de> MyDeserialize<'de> for XaPrepareEvent<'de> {
+    const SIZE: Option<usize> = None;
+    type Ctx = BinlogCtx<'de>;
+
+    fn deserialize(ctx: Self::Ctx, buf: &mut ParseBuf<'de>) -> io::Result<Self> {
+        let post_header_len = ctx.fde.get_event_type_header_length(Self::EVENT_TYPE);
+
+        if !buf.checked_skip(post_header_len as usize) {
+            return Err(unexpected_buf_eof());
+        }
+
+        let mut sbuf: ParseBuf = buf.parse(13)?;
+        let one_phase = sbuf.parse_unchecked(())?;
+        let format_id: RawInt<LeU32> = sbuf.parse_unchecked(())?;
+        let gtrid_len: RawInt<LeU32> = sbuf.parse_unchecked(())?;
+        let bqual_len: RawInt<LeU32> = sbuf.parse_unchecked(())?;
+
+        let gtrid_len = *gtrid_len as usize;
+        let bqual_len = *bqual_len as usize;
+        if gtrid_len > XaTransactionId::MAX_PART_LEN || bqual_len > XaTransactionId::MAX_PART_LEN {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "invalid XID length in XA prepare event",
+            ));
+        }
+
+        let data = buf
+            .checked_eat(gtrid_len + bqual_len)
+            .ok_or_else(unexpected_buf_eof)?;
+
+        Ok(Self {
+            one_phase,
+            xid: XaTransactionId::new(
+                *format_id,
+                Cow::Borrowed(&data[..gtrid_len]),
+                Cow::Borrowed(&data[gtrid_len..]),
+            ),
+        })
+    }
+}
+
+impl MySerialize for XaPrepareEvent<'_> {
+    fn serialize(&self, buf: &mut Vec<u8>) {
+        self.one_phase.serialize(&mut *buf);
+        RawInt::<LeU32>::new(self.xid.format_id()).serialize(&mut *buf);
+        RawInt::<LeU32>::new(self.xid.gtrid().len() as u32).serialize(&mut *buf);
+        RawInt::<LeU32>::new(self.xid.bqual().len() as u32).serialize(&mut *buf);
+        buf.extend_from_slice(self.xid.gtrid());
+        buf.extend_from_slice(self.xid.bqual());
+    }
+}
+
+impl<'a> BinlogEvent<'a> for XaPrepareEvent<'a> {
+    const EVENT_TYPE: EventType = EventType::XA_PREPARE_LOG_EVENT;
+}
+
+impl<'a> BinlogStruct<'a> for XaPrepareEvent<'a> {
+    fn len(&self, _version: BinlogVersion) -> usize {
+        let mut len = S(13);
+
+        len += S(self.xid.gtrid().len());
+        len += S(self.xid.bqual().len());
+
+        len.0
+    }
+}
@@ -20,13 +20,45 @@ use crate::{
     proto::{MyDeserialize, MySerialize},
 };
 
+use super::GtidEvent;
+
 /// Xid event.
 ///
 /// Generated for a commit of a transaction that modifies one or more tables of an XA-capable
 /// storage engine.
 #[derive(Debug, Clone, Eq, PartialEq, Hash)]
 pub struct XidEvent {
+    #[doc(hidden)]
     pub xid: u64,
+    /// GTID of the transaction (not a part of the event, see [`XidEvent::with_gtid`]).
+    gtid: Option<([u8; GtidEvent::ENCODED_SID_LENGTH], u64)>,
+}
+
+impl XidEvent {
+    /// Creates a new instance.
+    pub fn new(xid: u64) -> Self {
+        Self { xid, gtid: None }
+    }
+
+    /// Returns the XID of the committed transaction.
+    pub fn xid(&self) -> u64 {
+        self.xid
+    }
+
+    /// Links this event to the GTID (`(sid, gno)`) of the committed transaction.
+    ///
+    /// The GTID isn't stored in the event, it's defined by the preceding GTID event
+    /// (see [`crate::binlog::EventStreamReader::last_gtid`]).
+    pub fn with_gtid(mut self, gtid: Option<([u8; GtidEvent::ENCODED_SID_LENGTH], u64)>) -> Self {
+        self.gtid = gtid;
+        self
+    }
+
+    /// Returns the GTID (`(sid, gno)`) of the committed transaction, if linked
+    /// (see [`XidEvent::with_gtid`]).
+    pub fn gtid(&self) -> Option<([u8; GtidEvent::ENCODED_SID_LENGTH], u64)> {
+        self.gtid
+    }
 }
 
 impl<'de> MyDeserialize<'de> for XidEvent {
@@ -42,7 +74,7 @@ impl<'de> MyDeserialize<'de> for XidEvent {
 
         let xid = buf.checked_eat_u64_le().ok_or_else(unexpected_buf_eof)?;
 
-        Ok(Self { xid })
+        Ok(Self::new(xid))
     }
 }
 
@@ -0,0 +1,412 @@
+// Copyright (c) 2023 Anatoly Ikorsky
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! JSON export of binlog events.
+//!
+//! Every event is exported as a JSON object:
+//!
+//! ```json
+//! {
+//!     "type": "Write_rows",
+//!     "timestamp": "2023-06-19T12:30:00Z",
+//!     "server_id": 1,
+//!     "log_file": "mysql-bin.000001",
+//!     "position": 4,
+//!     "next_position": 120,
+//!     "size": 116,
+//!     "data": { ... }
+//! }
+//! ```
+//!
+//! *   `type` is the event type name as printed by `mysqlbinlog`
+//!     (e.g. `Query`, `Table_map`, `Update_rows`, `Gtid`, `Xid`);
+//! *   `timestamp` is the ISO 8601 UTC timestamp of the event;
+//! *   `log_file` is the current binlog file name (`null` until known, see
+//!     [`JsonExporter::with_log_file`]; it's updated by rotate events);
+//! *   `position` is the offset of the event (`null` if `log_pos` is zero);
+//! *   `next_position` is the `log_pos` header field.
+//!
+//! `data` depends on the event type:
+//!
+//! *   `Query` – `{"thread_id", "exec_time", "error_code", "schema", "query"}`;
+//! *   `Rotate` – `{"name", "position"}`;
+//! *   `Format_desc` – `{"binlog_version", "server_version", "create_timestamp"}`;
+//! *   `Xid` – `{"xid"}`;
+//! *   `Gtid` and `Anonymous_Gtid` – `{"gtid", "last_committed", "sequence_number",
+//!     "immediate_commit_timestamp", "original_commit_timestamp", "immediate_server_version",
+//!     "original_server_version"}` (ISO timestamps with microseconds and versions formatted
+//!     as `8.0.33`, `null` if not present);
+//! *   `Intvar` – `{"subtype", "value"}`, `Rand` – `{"seed1", "seed2"}`;
+//! *   `Rows_query` – `{"query"}`, `Incident` – `{"incident", "message"}`;
+//! *   `Previous_gtids` – `{"gtid_set"}` formatted as `gtid_executed`;
+//! *   `Table_map` – `{"table_id", "schema", "table", "columns"}`;
+//! *   rows events – `{"table_id", "schema", "table", "action", "rows"}`, where
+//!     `action` is one of `insert`, `update` or `delete`, and every row is
+//!     `{"before": {...}, "after": {...}}` with images keyed by column name
+//!     (see [`super::row::BinlogRow::to_json`]; names are `@<i>` unless the table map
+//!     contains column names). Missing images are omitted;
+//! *   other events – `{"raw"}` containing base64-encoded event data.
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde_json::{json, Map, Value as Json};
+use uuid::Uuid;
+
+use std::{collections::HashMap, io, io::Write};
+
+use super::{
+    consts::EventType,
+    events::{Event, EventData, GtidEvent, RowsEventData, ServerVersion, TableMapEvent},
+    misc::civil_from_days,
+};
+
+/// Exports binlog events as JSON (see the [module docs](self) for the schema).
+///
+/// Events must be exported in order, so that rows events could be decoded
+/// using the preceding table map events.
+#[derive(Debug, Clone, Default)]
+pub struct JsonExporter {
+    log_file: Option<String>,
+    table_maps: HashMap<u64, TableMapEvent<'static>>,
+}
+
+impl JsonExporter {
+    /// Creates a new exporter.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns modified `self` with the given name of the current binlog file.
+    pub fn with_log_file(mut self, log_file: Option<String>) -> Self {
+        self.log_file = log_file;
+        self
+    }
+
+    /// Returns the name of the current binlog file (if known).
+    pub fn log_file(&self) -> Option<&str> {
+        self.log_file.as_deref()
+    }
+
+    /// Exports the given event.
+    ///
+    /// Returns an `InvalidData` error if a rows event refers to an unknown table.
+    pub fn export(&mut self, event: &Event<'_>) -> io::Result<Json> {
+        let header = event.header();
+
+        let (type_name, data) = match event.read_data()? {
+            Some(data) => (event_type_name(data.event_type()), self.export_data(data)?),
+            None => ("Unknown", raw_data(event)),
+        };
+
+        if let Ok(EventType::ROTATE_EVENT) = header.event_type() {
+            if let Some(name) = data.get("name").and_then(Json::as_str) {
+                self.log_file = Some(name.into());
+            }
+        }
+
+        let position = (header.log_pos() != 0)
+            .then(|| (header.log_pos() as u64).saturating_sub(header.event_size() as u64));
+
+        Ok(json!({
+            "type": type_name,
+            "timestamp": iso_timestamp(header.timestamp() as u64 * 1_000_000, false),
+            "server_id": header.server_id(),
+            "log_file": self.log_file,
+            "position": position,
+            "next_position": header.log_pos(),
+            "size": header.event_size(),
+            "data": data,
+        }))
+    }
+
+    /// Exports the given event and writes it as a single line of JSON
+    /// (i.e. in the JSON Lines format).
+    pub fn write_event<W: Write>(&mut self, event: &Event<'_>, mut output: W) -> io::Result<()> {
+        let json = self.export(event)?;
+        serde_json::to_writer(&mut output, &json)?;
+        output.write_all(b"\n")
+    }
+
+    fn export_data(&mut self, data: EventData<'_>) -> io::Result<Json> {
+        let json = match data {
+            EventData::QueryEvent(ev) => json!({
+                "thread_id": ev.thread_id(),
+                "exec_time": ev.execution_time(),
+                "error_code": ev.error_code(),
+                "schema": ev.schema(),
+                "query": ev.query(),
+            }),
+            EventData::RotateEvent(ev) => json!({
+                "name": ev.name(),
+                "position": ev.position(),
+            }),
+            EventData::FormatDescriptionEvent(ev) => json!({
+                "binlog_version": ev.binlog_version() as u16,
+                "server_version": ev.server_version(),
+                "create_timestamp": ev.create_timestamp(),
+            }),
+            EventData::XidEvent(ev) => json!({ "xid": ev.xid() }),
+            EventData::GtidEvent(ev) => gtid(&ev),
+            EventData::AnonymousGtidEvent(ev) => gtid(ev.gtid_event()),
+            EventData::IntvarEvent(ev) => json!({
+                "subtype": format!("{:?}", ev.subtype()),
+                "value": ev.value(),
+            }),
+            EventData::RandEvent(ev) => json!({
+                "seed1": ev.seed1.0,
+                "seed2": ev.seed2.0,
+            }),
+            EventData::RowsQueryEvent(ev) => json!({ "query": ev.query() }),
+            EventData::PreviousGtidsEvent(ev) => json!({ "gtid_set": ev.gtid_set().to_string() }),
+            EventData::IncidentEvent(ev) => json!({
+                "incident": ev
+                    .incident_type()
+                    .map(|x| format!("{:?}", x))
+                    .unwrap_or_else(|x| x.0.to_string()),
+                "message": ev.message(),
+            }),
+            EventData::TableMapEvent(ev) => {
+                let columns = (0..ev.columns_count() as usize)
+                    .map(|i| match ev.get_column_type(i) {
+                        Ok(Some(ty)) => format!("{:?}", ty).into(),
+                        _ => Json::Null,
+                    })
+                    .collect::<Vec<Json>>();
+                let json = json!({
+                    "table_id": ev.table_id(),
+                    "schema": ev.database_name(),
+                    "table": ev.table_name(),
+                    "columns": columns,
+                });
+                self.table_maps.insert(ev.table_id(), ev.into_owned());
+                json
+            }
+            EventData::RowsEvent(ev) => self.rows(&ev)?,
+            other => {
+                let mut buf = Vec::new();
+                crate::proto::MySerialize::serialize(&other, &mut buf);
+                json!({ "raw": STANDARD.encode(buf) })
+            }
+        };
+        Ok(json)
+    }
+
+    fn rows(&self, ev: &RowsEventData<'_>) -> io::Result<Json> {
+        let tme = self.table_maps.get(&ev.table_id()).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("no table map for table id {}", ev.table_id()),
+            )
+        })?;
+
+        let action = match ev.event_type() {
+            EventType::WRITE_ROWS_EVENT | EventType::WRITE_ROWS_EVENT_V1 => "insert",
+            EventType::DELETE_ROWS_EVENT | EventType::DELETE_ROWS_EVENT_V1 => "delete",
+            _ => "update",
+        };
+
+        let mut rows = Vec::new();
+        for row in ev.rows(tme) {
+            let (before, after) = row?;
+            let mut object = Map::new();
+            for (key, image) in [("before", before), ("after", after)] {
+                if let Some(image) = image {
+                    let image = image
+                        .to_json()
+                        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+                    object.insert(key.into(), Json::Object(image));
+                }
+            }
+            rows.push(Json::Object(object));
+        }
+
+        Ok(json!({
+            "table_id": ev.table_id(),
+            "schema": tme.database_name(),
+            "table": tme.table_name(),
+            "action": action,
+            "rows": rows,
+        }))
+    }
+}
+
+fn gtid(ev: &GtidEvent) -> Json {
+    let timestamp = |micros: u64| (micros != 0).then(|| iso_timestamp(micros, true));
+    let version = |version: ServerVersion| version.is_defined().then(|| version.to_string());
+    json!({
+        "gtid": format!("{}:{}", Uuid::from_bytes(ev.sid()).hyphenated(), ev.gno()),
+        "last_committed": ev.last_committed(),
+        "sequence_number": ev.sequence_number(),
+        "immediate_commit_timestamp": timestamp(ev.immediate_commit_timestamp()),
+        "original_commit_timestamp": timestamp(ev.original_commit_timestamp()),
+        "immediate_server_version": version(ev.immediate_server_version()),
+        "original_server_version": version(ev.original_server_version()),
+    })
+}
+
+fn raw_data(event: &Event<'_>) -> Json {
+    json!({ "raw": STANDARD.encode(event.data()) })
+}
+
+/// Returns the event type name as printed by `mysqlbinlog`.
+fn event_type_name(event_type: EventType) -> &'static str {
+    use EventType::*;
+
+    match event_type {
+        UNKNOWN_EVENT | ENUM_END_EVENT => "Unknown",
+        START_EVENT_V3 => "Start_v3",
+        QUERY_EVENT => "Query",
+        STOP_EVENT => "Stop",
+        ROTATE_EVENT => "Rotate",
+        INTVAR_EVENT => "Intvar",
+        LOAD_EVENT => "Load",
+        SLAVE_EVENT => "Slave",
+        CREATE_FILE_EVENT => "Create_file",
+        APPEND_BLOCK_EVENT => "Append_block",
+        EXEC_LOAD_EVENT => "Exec_load",
+        DELETE_FILE_EVENT => "Delete_file",
+        NEW_LOAD_EVENT => "New_load",
+        RAND_EVENT => "RAND",
+        USER_VAR_EVENT => "User var",
+        FORMAT_DESCRIPTION_EVENT => "Format_desc",
+        XID_EVENT => "Xid",
+        BEGIN_LOAD_QUERY_EVENT => "Begin_load_query",
+        EXECUTE_LOAD_QUERY_EVENT => "Execute_load_query",
+        TABLE_MAP_EVENT => "Table_map",
+        PRE_GA_WRITE_ROWS_EVENT => "Write_rows_event_old",
+        PRE_GA_UPDATE_ROWS_EVENT => "Update_rows_event_old",
+        PRE_GA_DELETE_ROWS_EVENT => "Delete_rows_event_old",
+        WRITE_ROWS_EVENT_V1 => "Write_rows_v1",
+        UPDATE_ROWS_EVENT_V1 => "Update_rows_v1",
+        DELETE_ROWS_EVENT_V1 => "Delete_rows_v1",
+        INCIDENT_EVENT => "Incident",
+        HEARTBEAT_EVENT => "Heartbeat",
+        IGNORABLE_EVENT => "Ignorable",
+        ROWS_QUERY_EVENT => "Rows_query",
+        WRITE_ROWS_EVENT => "Write_rows",
+        UPDATE_ROWS_EVENT => "Update_rows",
+        DELETE_ROWS_EVENT => "Delete_rows",
+        GTID_EVENT => "Gtid",
+        ANONYMOUS_GTID_EVENT => "Anonymous_Gtid",
+        PREVIOUS_GTIDS_EVENT => "Previous_gtids",
+        TRANSACTION_CONTEXT_EVENT => "Transaction_context",
+        VIEW_CHANGE_EVENT => "View_change",
+        XA_PREPARE_LOG_EVENT => "XA_prepare",
+        PARTIAL_UPDATE_ROWS_EVENT => "Update_rows_partial",
+        TRANSACTION_PAYLOAD_EVENT => "Transaction_payload",
+        MARIADB_ANNOTATE_ROWS_EVENT => "Annotate_rows",
+        MARIADB_BINLOG_CHECKPOINT_EVENT => "Binlog_checkpoint",
+        MARIADB_GTID_EVENT => "Gtid",
+        MARIADB_GTID_LIST_EVENT => "Gtid_list",
+        MARIADB_START_ENCRYPTION_EVENT => "Start_encryption",
+        MARIADB_QUERY_COMPRESSED_EVENT => "Query_compressed",
+        MARIADB_WRITE_ROWS_COMPRESSED_EVENT_V1 => "Write_rows_compressed_v1",
+        MARIADB_UPDATE_ROWS_COMPRESSED_EVENT_V1 => "Update_rows_compressed_v1",
+        MARIADB_DELETE_ROWS_COMPRESSED_EVENT_V1 => "Delete_rows_compressed_v1",
+        MARIADB_WRITE_ROWS_COMPRESSED_EVENT => "Write_rows_compressed",
+        MARIADB_UPDATE_ROWS_COMPRESSED_EVENT => "Update_rows_compressed",
+        MARIADB_DELETE_ROWS_COMPRESSED_EVENT => "Delete_rows_compressed",
+    }
+}
+
+/// Formats microseconds since unix epoch as an ISO 8601 UTC timestamp.
+fn iso_timestamp(micros: u64, with_fraction: bool) -> String {
+    let secs = micros / 1_000_000;
+    let (days, secs_of_day) = (secs / 86_400, secs % 86_400);
+    let (year, month, day) = civil_from_days(days);
+
+    let mut output = format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}",
+        year,
+        month,
+        day,
+        secs_of_day / 3_600,
+        secs_of_day % 3_600 / 60,
+        secs_of_day % 60,
+    );
+    if with_fraction {
+        output.push_str(&format!(".{:06}", micros % 1_000_000));
+    }
+    output.push('Z');
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+
+    use serde_json::Value as Json;
+
+    use super::{iso_timestamp, JsonExporter};
+    use crate::binlog::{consts::BinlogVersion, BinlogFile};
+
+    #[test]
+    fn should_format_iso_timestamps() {
+        assert_eq!(iso_timestamp(0, false), "1970-01-01T00:00:00Z");
+        assert_eq!(
+            iso_timestamp(1_687_177_800_123_456, true),
+            "2023-06-19T12:30:00.123456Z"
+        );
+        assert_eq!(
+            iso_timestamp(951_782_400_000_000, false),
+            "2000-02-29T00:00:00Z"
+        );
+        assert_eq!(
+            iso_timestamp(u32::MAX as u64 * 1_000_000, false),
+            "2106-02-07T06:28:15Z"
+        );
+    }
+
+    #[test]
+    fn should_export_binlog_as_json() -> io::Result<()> {
+        let file_data = std::fs::read("./test-data/binlogs/mysql-enum-string-set.000001")?;
+        let binlog_file = BinlogFile::new(BinlogVersion::Version4, &file_data[..])?;
+
+        let mut exporter = JsonExporter::new().with_log_file(Some("binlog.000001".into()));
+        let mut output = Vec::new();
+        for ev in binlog_file {
+            exporter.write_event(&ev?, &mut output)?;
+        }
+
+        let events = output
+            .split(|x| *x == b'\n')
+            .filter(|line| !line.is_empty())
+            .map(serde_json::from_slice)
+            .collect::<Result<Vec<Json>, _>>()?;
+
+        assert_eq!(events[0]["type"], "Format_desc");
+        assert_eq!(events[0]["position"], 4);
+        assert_eq!(events[0]["log_file"], "binlog.000001");
+        for pair in events.windows(2) {
+            if pair[0]["next_position"] != 0 && pair[1]["next_position"] != 0 {
+                assert_eq!(pair[0]["next_position"], pair[1]["position"]);
+            }
+        }
+
+        let insert = events.iter().find(|ev| ev["type"] == "Write_rows").unwrap();
+        assert_eq!(insert["data"]["action"], "insert");
+        let after = &insert["data"]["rows"][0]["after"];
+        assert_eq!(after["f3"], 1);
+        assert_eq!(after["f5"], "0123456789");
+        assert!(insert["data"]["rows"][0].get("before").is_none());
+
+        let update = events
+            .iter()
+            .find(|ev| ev["type"] == "Update_rows")
+            .unwrap();
+        assert_eq!(update["data"]["action"], "update");
+        assert_eq!(update["data"]["rows"][0]["after"]["f1"], "field1");
+        assert!(update["data"]["rows"][0]["before"].is_object());
+
+        assert!(events.iter().any(|ev| ev["type"] == "Table_map"));
+        assert!(events
+            .iter()
+            .all(|ev| ev["timestamp"].as_str().unwrap().ends_with('Z')));
+
+        Ok(())
+    }
+}
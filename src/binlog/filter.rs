@@ -0,0 +1,324 @@
+// Copyright (c) 2023 Anatoly Ikorsky
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! `mysqlbinlog`-style filtering of binlog events (see [`BinlogFileFilter`]).
+
+use std::{
+    collections::{HashMap, HashSet},
+    io,
+};
+
+use crate::io::ParseBuf;
+
+use super::{
+    consts::EventType,
+    events::{Event, QueryEvent, TableMapEvent},
+};
+
+/// Iterator adapter that skips events of a binlog file (see [`super::BinlogFile`])
+/// or of any other source of events.
+///
+/// Events are skipped by event type, by server id and by database or table.
+/// The database of a query event is its default schema, and the table of a rows event
+/// is defined by the preceding table map event. Only the parts needed for the decision
+/// are parsed, so [`EventData`](super::events::EventData) is never materialized
+/// for skipped events.
+///
+/// Format description and rotate events are never skipped by server id, and
+/// transaction control statements (`BEGIN`, `COMMIT` and `ROLLBACK`) are never skipped
+/// by database, so the structure of the stream is preserved.
+#[derive(Debug, Clone)]
+pub struct BinlogFileFilter<I> {
+    events: I,
+    event_types: HashSet<u8>,
+    server_ids: HashSet<u32>,
+    databases: HashSet<String>,
+    tables: HashSet<(String, String)>,
+    /// Whether rows events of a table id should be skipped.
+    table_map: HashMap<u64, bool>,
+}
+
+impl<I> BinlogFileFilter<I> {
+    /// Creates a new filter that skips nothing.
+    pub fn new(events: I) -> Self {
+        Self {
+            events,
+            event_types: HashSet::new(),
+            server_ids: HashSet::new(),
+            databases: HashSet::new(),
+            tables: HashSet::new(),
+            table_map: HashMap::new(),
+        }
+    }
+
+    /// Returns modified `self` that will skip events of the given types.
+    pub fn with_skipped_event_types(
+        mut self,
+        event_types: impl IntoIterator<Item = EventType>,
+    ) -> Self {
+        self.event_types = event_types.into_iter().map(|x| x as u8).collect();
+        self
+    }
+
+    /// Returns modified `self` that will skip events originating from the given servers.
+    pub fn with_skipped_server_ids(mut self, server_ids: impl IntoIterator<Item = u32>) -> Self {
+        self.server_ids = server_ids.into_iter().collect();
+        self
+    }
+
+    /// Returns modified `self` that will skip query events, table map events and rows events
+    /// of the given databases.
+    pub fn with_skipped_databases<T: Into<String>>(
+        mut self,
+        databases: impl IntoIterator<Item = T>,
+    ) -> Self {
+        self.databases = databases.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Returns modified `self` that will skip table map events and rows events
+    /// of the given tables (given as `(database, table)` pairs).
+    pub fn with_skipped_tables<T: Into<String>, U: Into<String>>(
+        mut self,
+        tables: impl IntoIterator<Item = (T, U)>,
+    ) -> Self {
+        self.tables = tables
+            .into_iter()
+            .map(|(db, table)| (db.into(), table.into()))
+            .collect();
+        self
+    }
+
+    /// Returns a reference to the underlying source of events.
+    pub fn get_ref(&self) -> &I {
+        &self.events
+    }
+
+    /// Returns the underlying source of events.
+    pub fn into_inner(self) -> I {
+        self.events
+    }
+
+    /// Returns `true` if the given event passes the filter.
+    ///
+    /// Events must be given in the stream order, because table map events define
+    /// the tables of the following rows events.
+    pub fn accepts(&mut self, event: &Event<'_>) -> io::Result<bool> {
+        let header = event.header();
+        let event_type = header.event_type_raw();
+
+        if self.event_types.contains(&event_type) {
+            return Ok(false);
+        }
+
+        if self.server_ids.contains(&header.server_id())
+            && event_type != EventType::FORMAT_DESCRIPTION_EVENT as u8
+            && event_type != EventType::ROTATE_EVENT as u8
+        {
+            return Ok(false);
+        }
+
+        if self.databases.is_empty() && self.tables.is_empty() {
+            return Ok(true);
+        }
+
+        match header.event_type() {
+            Ok(EventType::QUERY_EVENT) => {
+                let query = event.read_event::<QueryEvent>()?;
+                let text = query.query();
+                let text = text.trim();
+                let is_tx_control = ["BEGIN", "COMMIT", "ROLLBACK"]
+                    .iter()
+                    .any(|x| text.eq_ignore_ascii_case(x));
+                Ok(is_tx_control || !self.databases.contains(&*query.schema()))
+            }
+            Ok(EventType::TABLE_MAP_EVENT) => {
+                let tme = event.read_event::<TableMapEvent>()?;
+                let skipped = self.is_table_skipped(&tme);
+                self.table_map.insert(tme.table_id(), skipped);
+                Ok(!skipped)
+            }
+            Ok(
+                EventType::WRITE_ROWS_EVENT_V1
+                | EventType::UPDATE_ROWS_EVENT_V1
+                | EventType::DELETE_ROWS_EVENT_V1
+                | EventType::WRITE_ROWS_EVENT
+                | EventType::UPDATE_ROWS_EVENT
+                | EventType::DELETE_ROWS_EVENT
+                | EventType::PARTIAL_UPDATE_ROWS_EVENT
+                | EventType::MARIADB_WRITE_ROWS_COMPRESSED_EVENT_V1
+                | EventType::MARIADB_UPDATE_ROWS_COMPRESSED_EVENT_V1
+                | EventType::MARIADB_DELETE_ROWS_COMPRESSED_EVENT_V1
+                | EventType::MARIADB_WRITE_ROWS_COMPRESSED_EVENT
+                | EventType::MARIADB_UPDATE_ROWS_COMPRESSED_EVENT
+                | EventType::MARIADB_DELETE_ROWS_COMPRESSED_EVENT,
+            ) => {
+                let skipped = rows_event_table_id(event)
+                    .and_then(|table_id| self.table_map.get(&table_id))
+                    .copied()
+                    .unwrap_or(false);
+                Ok(!skipped)
+            }
+            _ => Ok(true),
+        }
+    }
+
+    fn is_table_skipped(&self, tme: &TableMapEvent<'_>) -> bool {
+        let database = tme.database_name();
+        if self.databases.contains(&*database) {
+            return true;
+        }
+        !self.tables.is_empty()
+            && self
+                .tables
+                .contains(&(database.into_owned(), tme.table_name().into_owned()))
+    }
+}
+
+/// Returns the table id of a rows event without parsing the event.
+///
+/// Returns `None` if the event is too short.
+fn rows_event_table_id(event: &Event<'_>) -> Option<u64> {
+    let event_type = event.header().event_type().ok()?;
+    let mut data = ParseBuf(event.data());
+    if event.fde().get_event_type_header_length(event_type) == 6 {
+        // old server
+        data.checked_eat_u32_le().map(u64::from)
+    } else {
+        data.checked_eat_u48_le()
+    }
+}
+
+impl<'a, I: Iterator<Item = io::Result<Event<'a>>>> Iterator for BinlogFileFilter<I> {
+    type Item = io::Result<Event<'a>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let event = match self.events.next()? {
+                Ok(event) => event,
+                Err(err) => return Some(Err(err)),
+            };
+            match self.accepts(&event) {
+                Ok(true) => return Some(Ok(event)),
+                Ok(false) => (),
+                Err(err) => return Some(Err(err)),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+
+    use super::BinlogFileFilter;
+    use crate::binlog::{
+        consts::{BinlogVersion, EventType},
+        events::{Event, EventData},
+        BinlogFile,
+    };
+
+    /// Returns `(database, table)` of every table map and rows event.
+    fn tables(events: &[Event<'static>]) -> io::Result<Vec<(String, String)>> {
+        let mut table_map = std::collections::HashMap::new();
+        let mut tables = Vec::new();
+        for ev in events {
+            match ev.read_data()? {
+                Some(EventData::TableMapEvent(tme)) => {
+                    let table = (
+                        tme.database_name().into_owned(),
+                        tme.table_name().into_owned(),
+                    );
+                    table_map.insert(tme.table_id(), table.clone());
+                    tables.push(table);
+                }
+                Some(EventData::RowsEvent(rows)) => {
+                    tables.push(table_map[&rows.table_id()].clone());
+                }
+                _ => (),
+            }
+        }
+        Ok(tables)
+    }
+
+    #[test]
+    fn should_filter_events() -> io::Result<()> {
+        let file_data = std::fs::read("./test-data/binlogs/write-full-row.binlog")?;
+        let all = BinlogFile::new(BinlogVersion::Version4, &file_data[..])?
+            .collect::<io::Result<Vec<_>>>()?;
+        let filter = || BinlogFileFilter::new(all.iter().cloned().map(Ok));
+        let ndb_apply_status = ("mysql".to_owned(), "ndb_apply_status".to_owned());
+        let ba = ("test".to_owned(), "ba".to_owned());
+        let all_tables = tables(&all)?;
+        assert!(all_tables.contains(&ndb_apply_status));
+        assert!(all_tables.contains(&ba));
+
+        // nothing is skipped by default
+        assert_eq!(filter().collect::<io::Result<Vec<_>>>()?, all);
+
+        // by event type
+        let filtered = filter()
+            .with_skipped_event_types(vec![EventType::TABLE_MAP_EVENT])
+            .collect::<io::Result<Vec<_>>>()?;
+        let table_maps = all
+            .iter()
+            .filter(|ev| ev.header().event_type() == Ok(EventType::TABLE_MAP_EVENT))
+            .count();
+        assert!(table_maps > 0);
+        assert_eq!(filtered.len(), all.len() - table_maps);
+
+        // by server id
+        let server_id = all[1].header().server_id();
+        let filtered = filter()
+            .with_skipped_server_ids(vec![server_id])
+            .collect::<io::Result<Vec<_>>>()?;
+        assert!(filtered.iter().all(|ev| {
+            ev.header().server_id() != server_id
+                || ev.header().event_type() == Ok(EventType::FORMAT_DESCRIPTION_EVENT)
+                || ev.header().event_type() == Ok(EventType::ROTATE_EVENT)
+        }));
+        assert!(filtered.len() < all.len());
+
+        // by table
+        let filtered = filter()
+            .with_skipped_tables(vec![ba.clone()])
+            .collect::<io::Result<Vec<_>>>()?;
+        let expected = all_tables
+            .iter()
+            .filter(|x| **x != ba)
+            .cloned()
+            .collect::<Vec<_>>();
+        assert_eq!(tables(&filtered)?, expected);
+
+        // by database
+        let filtered = filter()
+            .with_skipped_databases(vec!["mysql"])
+            .collect::<io::Result<Vec<_>>>()?;
+        let expected = all_tables
+            .iter()
+            .filter(|x| **x != ndb_apply_status)
+            .cloned()
+            .collect::<Vec<_>>();
+        assert_eq!(tables(&filtered)?, expected);
+        for ev in &filtered {
+            if let Some(EventData::QueryEvent(query)) = ev.read_data()? {
+                let text = query.query();
+                assert!(query.schema() != "mysql" || text.trim() == "BEGIN");
+            }
+        }
+
+        // works with a binlog file directly
+        let binlog_file = BinlogFile::new(BinlogVersion::Version4, &file_data[..])?;
+        let filtered = BinlogFileFilter::new(binlog_file)
+            .with_skipped_tables(vec![ba.clone()])
+            .collect::<io::Result<Vec<_>>>()?;
+        assert!(tables(&filtered)?.iter().all(|x| *x != ba));
+
+        Ok(())
+    }
+}
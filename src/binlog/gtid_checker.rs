@@ -0,0 +1,244 @@
+// Copyright (c) 2023 Anatoly Ikorsky
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! GTID continuity checks for binlog streams.
+
+use uuid::Uuid;
+
+use std::{collections::HashMap, fmt, io};
+
+use crate::packets::Sid;
+
+use super::{
+    consts::EventType,
+    events::{Event, GtidEvent},
+};
+
+/// Length of a SID.
+const SID_LEN: usize = GtidEvent::ENCODED_SID_LENGTH;
+
+/// An issue found by the [`GtidGapChecker`].
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum GtidGapWarning {
+    /// GNOs in `expected..found` are missing for the given SID.
+    Gap {
+        sid: [u8; SID_LEN],
+        expected: u64,
+        found: u64,
+        log_pos: u64,
+    },
+    /// GNO is not greater than the last GNO seen for the given SID
+    /// (`found == last` means that the transaction is duplicated).
+    OutOfOrder {
+        sid: [u8; SID_LEN],
+        last: u64,
+        found: u64,
+        log_pos: u64,
+    },
+}
+
+impl GtidGapWarning {
+    /// Returns the SID of the offending GTID.
+    pub fn sid(&self) -> [u8; SID_LEN] {
+        match *self {
+            Self::Gap { sid, .. } | Self::OutOfOrder { sid, .. } => sid,
+        }
+    }
+
+    /// Returns the GNO of the offending GTID.
+    pub fn found(&self) -> u64 {
+        match *self {
+            Self::Gap { found, .. } | Self::OutOfOrder { found, .. } => found,
+        }
+    }
+
+    /// Returns the `log_pos` of the offending GTID event (i.e. the position of the next event).
+    pub fn log_pos(&self) -> u64 {
+        match *self {
+            Self::Gap { log_pos, .. } | Self::OutOfOrder { log_pos, .. } => log_pos,
+        }
+    }
+}
+
+impl fmt::Display for GtidGapWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Self::Gap {
+                sid,
+                expected,
+                found,
+                log_pos,
+            } => write!(
+                f,
+                "GTID gap for {}: expected {}, found {} (log_pos {})",
+                Uuid::from_bytes(sid).hyphenated(),
+                expected,
+                found,
+                log_pos
+            ),
+            Self::OutOfOrder {
+                sid,
+                last,
+                found,
+                log_pos,
+            } => write!(
+                f,
+                "out-of-order GTID for {}: last {}, found {} (log_pos {})",
+                Uuid::from_bytes(sid).hyphenated(),
+                last,
+                found,
+                log_pos
+            ),
+        }
+    }
+}
+
+/// Checks that GNOs are consecutive for every SID in a binlog stream.
+///
+/// Useful for validating relay pipelines and filtered replication setups.
+/// GNOs of a SID that wasn't seen before are accepted as is, unless the checker
+/// was seeded with an already executed GTID set (see [`GtidGapChecker::with_executed`]).
+#[derive(Debug, Default, Clone, Eq, PartialEq)]
+pub struct GtidGapChecker {
+    last_gno: HashMap<[u8; SID_LEN], u64>,
+}
+
+impl GtidGapChecker {
+    /// Creates a new instance.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns modified `self` seeded with the given executed GTIDs,
+    /// so that the stream is expected to continue right after the last executed GNO.
+    pub fn with_executed(mut self, sid: &Sid<'_>) -> Self {
+        if let Some(end) = sid.intervals().iter().map(|x| x.end()).max() {
+            let last = self.last_gno.entry(sid.uuid()).or_insert(0);
+            *last = (*last).max(end.saturating_sub(1));
+        }
+        self
+    }
+
+    /// Returns the last GNO seen for the given SID.
+    pub fn last_gno(&self, sid: [u8; SID_LEN]) -> Option<u64> {
+        self.last_gno.get(&sid).copied()
+    }
+
+    /// Checks the given GTID event.
+    ///
+    /// `log_pos` is only used to report the position of an issue.
+    pub fn check(&mut self, event: &GtidEvent, log_pos: u64) -> Option<GtidGapWarning> {
+        let sid = event.sid();
+        let found = event.gno();
+
+        let last = match self.last_gno.get_mut(&sid) {
+            Some(last) => last,
+            None => {
+                self.last_gno.insert(sid, found);
+                return None;
+            }
+        };
+
+        if found <= *last {
+            return Some(GtidGapWarning::OutOfOrder {
+                sid,
+                last: *last,
+                found,
+                log_pos,
+            });
+        }
+
+        let expected = *last + 1;
+        *last = found;
+        if found > expected {
+            Some(GtidGapWarning::Gap {
+                sid,
+                expected,
+                found,
+                log_pos,
+            })
+        } else {
+            None
+        }
+    }
+
+    /// Checks the given binlog event. Events other than `GTID_EVENT` are ignored.
+    pub fn check_event(&mut self, event: &Event<'_>) -> io::Result<Option<GtidGapWarning>> {
+        if event.header().event_type_raw() != EventType::GTID_EVENT as u8 {
+            return Ok(None);
+        }
+        let gtid = event.read_event::<GtidEvent>()?;
+        Ok(self.check(&gtid, event.header().log_pos() as u64))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{GtidGapChecker, GtidGapWarning};
+    use crate::{
+        binlog::{consts::BinlogVersion, events::GtidEvent, BinlogFile},
+        packets::{GnoInterval, Sid},
+    };
+
+    const SID: [u8; 16] = [1; 16];
+    const OTHER_SID: [u8; 16] = [2; 16];
+
+    #[test]
+    fn should_detect_gaps() {
+        let mut checker = GtidGapChecker::new();
+        assert_eq!(checker.check(&GtidEvent::new(SID, 5), 100), None);
+        assert_eq!(checker.check(&GtidEvent::new(SID, 6), 200), None);
+        assert_eq!(checker.check(&GtidEvent::new(OTHER_SID, 1), 300), None);
+        assert_eq!(
+            checker.check(&GtidEvent::new(SID, 9), 400),
+            Some(GtidGapWarning::Gap {
+                sid: SID,
+                expected: 7,
+                found: 9,
+                log_pos: 400
+            })
+        );
+        assert_eq!(
+            checker.check(&GtidEvent::new(SID, 8), 500),
+            Some(GtidGapWarning::OutOfOrder {
+                sid: SID,
+                last: 9,
+                found: 8,
+                log_pos: 500
+            })
+        );
+        assert_eq!(checker.last_gno(SID), Some(9));
+        assert_eq!(checker.check(&GtidEvent::new(SID, 10), 600), None);
+
+        let warning = checker.check(&GtidEvent::new(OTHER_SID, 1), 700).unwrap();
+        assert_eq!(
+            warning.to_string(),
+            "out-of-order GTID for 02020202-0202-0202-0202-020202020202: last 1, found 1 (log_pos 700)"
+        );
+
+        let sid = Sid::new(SID).with_interval(GnoInterval::new(1, 11));
+        let mut checker = GtidGapChecker::new().with_executed(&sid);
+        assert_eq!(checker.check(&GtidEvent::new(SID, 11), 100), None);
+        assert!(checker.check(&GtidEvent::new(SID, 13), 200).is_some());
+    }
+
+    #[test]
+    fn should_check_binlog_file() -> std::io::Result<()> {
+        let file_data = std::fs::read("./test-data/binlogs/binlog_transaction_with_GTID.000001")?;
+        let binlog_file = BinlogFile::new(BinlogVersion::Version4, &file_data[..])?;
+
+        let mut checker = GtidGapChecker::new();
+        let mut warnings = Vec::new();
+        for ev in binlog_file {
+            warnings.extend(checker.check_event(&ev?)?);
+        }
+        assert!(warnings.is_empty(), "{:?}", warnings);
+
+        Ok(())
+    }
+}
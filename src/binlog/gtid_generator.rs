@@ -0,0 +1,210 @@
+// Copyright (c) 2023 Anatoly Ikorsky
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! GTID bookkeeping for generated binlog files.
+
+use std::io;
+
+use crate::packets::{GnoInterval, GtidSet, Sid};
+
+use super::events::{GtidEvent, PreviousGtidsEvent};
+
+/// Length of a SID.
+const SID_LEN: usize = GtidEvent::ENCODED_SID_LENGTH;
+
+/// Maximum GNO accepted by the server (`GNO_END - 1`).
+const MAX_GNO: u64 = i64::MAX as u64 - 1;
+
+/// Generates GTIDs of transactions written to binlog files.
+///
+/// The generator keeps the set of executed GTIDs, so that every new binlog file
+/// can start with a correct `PREVIOUS_GTIDS_EVENT` (see [`GtidGenerator::start_file`]),
+/// and every transaction gets a `GTID_EVENT` with the next GNO of the server's SID
+/// (see [`GtidGenerator::next_gtid_event`]).
+///
+/// Logical timestamps of generated events describe a serial execution: the sequence
+/// number starts with `1` in every file and `last_committed` is the previous sequence number.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct GtidGenerator {
+    sid: [u8; SID_LEN],
+    executed: Vec<Sid<'static>>,
+    next_gno: u64,
+    sequence_number: u64,
+}
+
+impl GtidGenerator {
+    /// Creates a new generator for the given server SID.
+    ///
+    /// `executed` is the set of GTIDs executed before the first generated transaction
+    /// (i.e. `gtid_executed`). The first generated GNO follows the greatest GNO
+    /// of the given SID within this set.
+    pub fn new(sid: [u8; SID_LEN], executed: GtidSet<'_>) -> Self {
+        let executed = executed
+            .sids()
+            .iter()
+            .cloned()
+            .map(Sid::into_owned)
+            .collect::<Vec<_>>();
+        let next_gno = executed
+            .iter()
+            .filter(|x| x.uuid() == sid)
+            .flat_map(|x| x.intervals())
+            .map(|x| x.end())
+            .max()
+            .unwrap_or(1);
+        Self {
+            sid,
+            executed,
+            next_gno,
+            sequence_number: 0,
+        }
+    }
+
+    /// Returns modified `self` that will continue with the given GNO.
+    pub fn with_next_gno(mut self, next_gno: u64) -> Self {
+        self.next_gno = next_gno;
+        self
+    }
+
+    /// Returns the server SID.
+    pub fn sid(&self) -> [u8; SID_LEN] {
+        self.sid
+    }
+
+    /// Returns the GNO of the next generated transaction.
+    pub fn next_gno(&self) -> u64 {
+        self.next_gno
+    }
+
+    /// Returns the set of executed GTIDs including generated ones.
+    pub fn gtid_set(&self) -> GtidSet<'static> {
+        GtidSet::new(self.executed.clone())
+    }
+
+    /// Returns the `PREVIOUS_GTIDS_EVENT` for a new binlog file and resets logical timestamps.
+    ///
+    /// It must be written right after the format description event of every file.
+    pub fn start_file(&mut self) -> PreviousGtidsEvent<'static> {
+        self.sequence_number = 0;
+        PreviousGtidsEvent::new(self.gtid_set())
+    }
+
+    /// Returns the `GTID_EVENT` of the next transaction and records its GTID as executed.
+    ///
+    /// Returns an `InvalidData` error if GNOs of the server SID are exhausted.
+    pub fn next_gtid_event(&mut self) -> io::Result<GtidEvent> {
+        let gno = self.next_gno;
+        if gno == 0 || gno > MAX_GNO {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("GNO {} is out of range", gno),
+            ));
+        }
+
+        self.add_gtid(gno);
+        self.next_gno = gno + 1;
+        self.sequence_number += 1;
+
+        Ok(GtidEvent::new(self.sid, gno)
+            .with_last_committed(self.sequence_number - 1)
+            .with_sequence_number(self.sequence_number))
+    }
+
+    /// Adds the given GNO of the server SID to the set of executed GTIDs.
+    fn add_gtid(&mut self, gno: u64) {
+        let pos = match self.executed.iter().position(|x| x.uuid() >= self.sid) {
+            Some(pos) if self.executed[pos].uuid() == self.sid => pos,
+            Some(pos) => {
+                self.executed.insert(pos, Sid::new(self.sid));
+                pos
+            }
+            None => {
+                self.executed.push(Sid::new(self.sid));
+                self.executed.len() - 1
+            }
+        };
+
+        let sid = &mut self.executed[pos];
+        let mut intervals = sid.intervals().to_vec();
+        intervals.push(GnoInterval::new(gno, gno + 1));
+        intervals.sort_by_key(|x| x.start());
+        let mut merged: Vec<GnoInterval> = Vec::with_capacity(intervals.len());
+        for interval in intervals {
+            match merged.last_mut() {
+                Some(last) if last.end() >= interval.start() => {
+                    *last = GnoInterval::new(last.start(), last.end().max(interval.end()));
+                }
+                _ => merged.push(interval),
+            }
+        }
+        *sid = Sid::new(self.sid).with_intervals(merged);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::GtidGenerator;
+    use crate::packets::{GnoInterval, GtidSet, Sid};
+
+    const SID: [u8; 16] = [2; 16];
+    const LOWER_SID: [u8; 16] = [1; 16];
+    const HIGHER_SID: [u8; 16] = [3; 16];
+
+    #[test]
+    fn should_generate_gtids() -> std::io::Result<()> {
+        let executed = GtidSet::new(vec![
+            Sid::new(LOWER_SID).with_interval(GnoInterval::new(1, 10)),
+            Sid::new(HIGHER_SID).with_interval(GnoInterval::new(1, 3)),
+        ]);
+        let mut generator = GtidGenerator::new(SID, executed.clone());
+        assert_eq!(generator.next_gno(), 1);
+        assert_eq!(generator.start_file().gtid_set(), &executed);
+
+        for gno in 1..=3 {
+            let ev = generator.next_gtid_event()?;
+            assert_eq!(ev.sid(), SID);
+            assert_eq!(ev.gno(), gno);
+            assert_eq!(ev.sequence_number(), gno);
+            assert_eq!(ev.last_committed(), gno - 1);
+        }
+
+        // new file starts with the updated set and resets logical timestamps
+        let expected = GtidSet::new(vec![
+            Sid::new(LOWER_SID).with_interval(GnoInterval::new(1, 10)),
+            Sid::new(SID).with_interval(GnoInterval::new(1, 4)),
+            Sid::new(HIGHER_SID).with_interval(GnoInterval::new(1, 3)),
+        ]);
+        assert_eq!(generator.start_file().gtid_set(), &expected);
+        let ev = generator.next_gtid_event()?;
+        assert_eq!(
+            (ev.gno(), ev.sequence_number(), ev.last_committed()),
+            (4, 1, 0)
+        );
+
+        // continues after the greatest executed GNO
+        let generator = GtidGenerator::new(SID, expected);
+        assert_eq!(generator.next_gno(), 4);
+
+        // fills gaps and merges intervals
+        let executed = GtidSet::new(vec![Sid::new(SID)
+            .with_interval(GnoInterval::new(1, 3))
+            .with_interval(GnoInterval::new(4, 6))]);
+        let mut generator = GtidGenerator::new(SID, executed).with_next_gno(3);
+        generator.next_gtid_event()?;
+        assert_eq!(
+            generator.gtid_set(),
+            GtidSet::new(vec![Sid::new(SID).with_interval(GnoInterval::new(1, 6))])
+        );
+
+        // invalid GNO
+        let mut generator = GtidGenerator::new(SID, GtidSet::default()).with_next_gno(0);
+        assert!(generator.next_gtid_event().is_err());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,197 @@
+// Copyright (c) 2023 Anatoly Ikorsky
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Lookup of the binlog file to start streaming from for a given GTID set.
+
+use std::io::{self, Read};
+
+use crate::packets::GtidSet;
+
+use super::{consts::EventType, events::PreviousGtidsEvent, BinlogFile, BinlogFileHeader};
+
+/// Position to start streaming from (see [`GtidLocator::locate`]).
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub struct GtidLocation<'a> {
+    file_name: &'a str,
+    pos: u64,
+}
+
+impl<'a> GtidLocation<'a> {
+    /// Returns the name of the binlog file.
+    pub fn file_name(&self) -> &'a str {
+        self.file_name
+    }
+
+    /// Returns the position within the binlog file.
+    pub fn pos(&self) -> u64 {
+        self.pos
+    }
+}
+
+/// An index of binlog files with their `PREVIOUS_GTIDS_EVENT`s.
+///
+/// Performs the same lookup the server does on `COM_BINLOG_DUMP_GTID`:
+/// the newest file whose previous GTIDs are all executed by the client is the first file
+/// that may contain a transaction the client is missing.
+#[derive(Debug, Default, Clone, Eq, PartialEq)]
+pub struct GtidLocator {
+    files: Vec<(String, GtidSet<'static>)>,
+}
+
+impl GtidLocator {
+    /// Creates an empty index.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns modified `self` with the given file appended to the index.
+    ///
+    /// Files are expected to be added in the order of the binlog index
+    /// (i.e. from the oldest to the newest).
+    pub fn with_file(mut self, file_name: impl Into<String>, previous_gtids: GtidSet<'_>) -> Self {
+        self.add_file(file_name, previous_gtids);
+        self
+    }
+
+    /// Appends the given file to the index (see [`GtidLocator::with_file`]).
+    pub fn add_file(&mut self, file_name: impl Into<String>, previous_gtids: GtidSet<'_>) {
+        self.files
+            .push((file_name.into(), previous_gtids.into_owned()));
+    }
+
+    /// Reads the `PREVIOUS_GTIDS_EVENT` from the given binlog file
+    /// and appends the file to the index (see [`GtidLocator::with_file`]).
+    ///
+    /// Returns an `InvalidData` error if the event is not found at the beginning of the file.
+    pub fn add_binlog_file<T: Read>(
+        &mut self,
+        file_name: impl Into<String>,
+        binlog_file: BinlogFile<T>,
+    ) -> io::Result<()> {
+        for event in binlog_file {
+            let event = event?;
+            match event.header().event_type() {
+                Ok(EventType::PREVIOUS_GTIDS_EVENT) => {
+                    let previous_gtids = event.read_event::<PreviousGtidsEvent>()?;
+                    self.add_file(file_name, previous_gtids.into_gtid_set());
+                    return Ok(());
+                }
+                Ok(EventType::GTID_EVENT)
+                | Ok(EventType::ANONYMOUS_GTID_EVENT)
+                | Ok(EventType::QUERY_EVENT) => break,
+                _ => (),
+            }
+        }
+
+        Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "PREVIOUS_GTIDS_EVENT not found",
+        ))
+    }
+
+    /// Returns indexed files with their previous GTIDs (from the oldest to the newest).
+    pub fn files(&self) -> impl Iterator<Item = (&str, &GtidSet<'static>)> {
+        self.files
+            .iter()
+            .map(|(name, gtids)| (name.as_str(), gtids))
+    }
+
+    /// Returns the position to start streaming from, so that the stream covers
+    /// every transaction missing from the given `executed` GTID set.
+    ///
+    /// Transactions already in `executed` that precede the missing ones within the returned file
+    /// are expected to be skipped by the caller.
+    ///
+    /// Returns `None` if there is no such file, i.e. the index is empty or some of the missing
+    /// transactions were purged.
+    pub fn locate(&self, executed: &GtidSet<'_>) -> Option<GtidLocation<'_>> {
+        self.files
+            .iter()
+            .rev()
+            .find(|(_, previous_gtids)| previous_gtids.is_subset(executed))
+            .map(|(file_name, _)| GtidLocation {
+                file_name,
+                pos: BinlogFileHeader::LEN as u64,
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::GtidLocator;
+    use crate::{
+        binlog::{consts::BinlogVersion, BinlogFile},
+        packets::GtidSet,
+    };
+
+    fn gtid_set(text: &str) -> GtidSet<'static> {
+        text.parse().unwrap()
+    }
+
+    #[test]
+    fn should_locate_start_file() {
+        const UUID: &str = "3e11fa47-71ca-11e1-9e33-c80aa9429562";
+        const OTHER_UUID: &str = "4a1c2b4e-71ca-11e1-9e33-c80aa9429562";
+
+        let locator = GtidLocator::new()
+            .with_file("bin.000002", gtid_set(&format!("{}:1-10", UUID)))
+            .with_file(
+                "bin.000003",
+                gtid_set(&format!("{}:1-20,{}:1-5", UUID, OTHER_UUID)),
+            )
+            .with_file(
+                "bin.000004",
+                gtid_set(&format!("{}:1-30,{}:1-5", UUID, OTHER_UUID)),
+            );
+
+        let locate = |executed: &str| {
+            locator
+                .locate(&gtid_set(executed))
+                .map(|x| (x.file_name(), x.pos()))
+        };
+
+        assert_eq!(locate(&format!("{}:1-10", UUID)), Some(("bin.000002", 4)));
+        assert_eq!(locate(&format!("{}:1-25", UUID)), Some(("bin.000002", 4)));
+        assert_eq!(
+            locate(&format!("{}:1-25,{}:1-5", UUID, OTHER_UUID)),
+            Some(("bin.000003", 4))
+        );
+        assert_eq!(
+            locate(&format!("{}:1-15:16-40,{}:1-7", UUID, OTHER_UUID)),
+            Some(("bin.000004", 4))
+        );
+        // purged
+        assert_eq!(locate(""), None);
+        assert_eq!(locate(&format!("{}:2-40", UUID)), None);
+        assert_eq!(GtidLocator::new().locate(&gtid_set("")), None);
+    }
+
+    #[test]
+    fn should_read_previous_gtids() {
+        let file_data =
+            std::fs::read("./test-data/binlogs/binlog_transaction_with_GTID.000001").unwrap();
+        let binlog_file = BinlogFile::new(BinlogVersion::Version4, &file_data[..]).unwrap();
+
+        let mut locator = GtidLocator::new();
+        locator
+            .add_binlog_file("binlog.000001", binlog_file)
+            .unwrap();
+        assert_eq!(
+            locator.files().collect::<Vec<_>>(),
+            vec![("binlog.000001", &GtidSet::default())]
+        );
+        assert_eq!(
+            locator.locate(&GtidSet::default()).unwrap().file_name(),
+            "binlog.000001"
+        );
+
+        let file_data = std::fs::read("./test-data/binlogs/ver_5_1_23.001").unwrap();
+        let binlog_file = BinlogFile::new(BinlogVersion::Version4, &file_data[..]).unwrap();
+        assert!(locator.add_binlog_file("old.001", binlog_file).is_err());
+    }
+}
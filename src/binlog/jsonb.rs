@@ -16,6 +16,8 @@ use std::{
     str::{from_utf8, Utf8Error},
 };
 
+use bytes::BufMut;
+
 use crate::{
     constants::ColumnType,
     io::ParseBuf,
@@ -197,6 +199,16 @@ pub struct ComplexValue<'a, T, U> {
     __phantom: PhantomData<(T, U)>,
 }
 
+impl<T, U> ComplexValue<'static, T, U> {
+    fn from_data(element_count: usize, data: Vec<u8>) -> Self {
+        Self {
+            element_count: element_count as u32,
+            data: Cow::Owned(data),
+            __phantom: PhantomData,
+        }
+    }
+}
+
 impl<'a, T, U> ComplexValue<'a, T, U> {
     /// Returns a `'static` version of `self`.
     pub fn into_owned(self) -> ComplexValue<'static, T, U> {
@@ -555,6 +567,185 @@ impl<'a> Value<'a> {
             _ => None,
         }
     }
+
+    /// Returns the JSONB type marker of this value.
+    pub fn jsonb_type(&self) -> JsonbType {
+        match self {
+            Value::Null | Value::Bool(_) => JsonbType::JSONB_TYPE_LITERAL,
+            Value::I16(_) => JsonbType::JSONB_TYPE_INT16,
+            Value::U16(_) => JsonbType::JSONB_TYPE_UINT16,
+            Value::I32(_) => JsonbType::JSONB_TYPE_INT32,
+            Value::U32(_) => JsonbType::JSONB_TYPE_UINT32,
+            Value::I64(_) => JsonbType::JSONB_TYPE_INT64,
+            Value::U64(_) => JsonbType::JSONB_TYPE_UINT64,
+            Value::F64(_) => JsonbType::JSONB_TYPE_DOUBLE,
+            Value::String(_) => JsonbType::JSONB_TYPE_STRING,
+            Value::SmallArray(_) => JsonbType::JSONB_TYPE_SMALL_ARRAY,
+            Value::LargeArray(_) => JsonbType::JSONB_TYPE_LARGE_ARRAY,
+            Value::SmallObject(_) => JsonbType::JSONB_TYPE_SMALL_OBJECT,
+            Value::LargeObject(_) => JsonbType::JSONB_TYPE_LARGE_OBJECT,
+            Value::Opaque(_) => JsonbType::JSONB_TYPE_OPAQUE,
+        }
+    }
+
+    /// Serializes the value without the type marker.
+    fn serialize_body(&self, buf: &mut Vec<u8>) {
+        match self {
+            Value::Null => buf.put_u8(LiteralType::JSONB_NULL_LITERAL as u8),
+            Value::Bool(true) => buf.put_u8(LiteralType::JSONB_TRUE_LITERAL as u8),
+            Value::Bool(false) => buf.put_u8(LiteralType::JSONB_FALSE_LITERAL as u8),
+            Value::I16(x) => buf.put_i16_le(*x),
+            Value::U16(x) => buf.put_u16_le(*x),
+            Value::I32(x) => buf.put_i32_le(*x),
+            Value::U32(x) => buf.put_u32_le(*x),
+            Value::I64(x) => buf.put_i64_le(*x),
+            Value::U64(x) => buf.put_u64_le(*x),
+            Value::F64(x) => buf.put_f64_le(*x),
+            Value::String(x) => x.serialize(buf),
+            Value::SmallArray(x) => buf.put_slice(&x.data),
+            Value::LargeArray(x) => buf.put_slice(&x.data),
+            Value::SmallObject(x) => buf.put_slice(&x.data),
+            Value::LargeObject(x) => buf.put_slice(&x.data),
+            Value::Opaque(x) => {
+                x.value_type.serialize(&mut *buf);
+                x.data.serialize(buf);
+            }
+        }
+    }
+}
+
+impl MySerialize for Value<'_> {
+    fn serialize(&self, buf: &mut Vec<u8>) {
+        buf.put_u8(self.jsonb_type() as u8);
+        self.serialize_body(buf);
+    }
+}
+
+/// Converts JSON into its JSONB representation the same way MySql does it, i.e.:
+///
+/// * integers are stored using the smallest suitable type,
+/// * object keys are sorted by length and then by value,
+/// * large storage format is only used if a value doesn't fit the small one.
+///
+/// Returns an `InvalidInput` error if the value is too large to be stored as JSONB.
+impl TryFrom<&serde_json::Value> for Value<'static> {
+    type Error = io::Error;
+
+    fn try_from(value: &serde_json::Value) -> io::Result<Self> {
+        match value {
+            serde_json::Value::Null => Ok(Value::Null),
+            serde_json::Value::Bool(x) => Ok(Value::Bool(*x)),
+            serde_json::Value::Number(x) => Ok(match (x.as_i64(), x.as_u64()) {
+                (Some(x), _) => match (i16::try_from(x), i32::try_from(x)) {
+                    (Ok(x), _) => Value::I16(x),
+                    (_, Ok(x)) => Value::I32(x),
+                    _ => Value::I64(x),
+                },
+                (None, Some(x)) => Value::U64(x),
+                (None, None) => Value::F64(x.as_f64().unwrap_or_default()),
+            }),
+            serde_json::Value::String(x) => {
+                Ok(Value::String(JsonbString::new(x.as_bytes().to_vec())))
+            }
+            serde_json::Value::Array(xs) => {
+                let values = xs
+                    .iter()
+                    .map(Value::try_from)
+                    .collect::<io::Result<Vec<_>>>()?;
+                if let Some(data) = encode_complex::<Small>(&[], &values) {
+                    Ok(Value::SmallArray(ComplexValue::from_data(
+                        values.len(),
+                        data,
+                    )))
+                } else if let Some(data) = encode_complex::<Large>(&[], &values) {
+                    Ok(Value::LargeArray(ComplexValue::from_data(
+                        values.len(),
+                        data,
+                    )))
+                } else {
+                    Err(too_large())
+                }
+            }
+            serde_json::Value::Object(map) => {
+                let mut entries = map.iter().collect::<Vec<_>>();
+                entries.sort_by(|(a, _), (b, _)| a.len().cmp(&b.len()).then(a.cmp(b)));
+                let keys = entries
+                    .iter()
+                    .map(|(k, _)| k.as_bytes())
+                    .collect::<Vec<_>>();
+                let values = entries
+                    .iter()
+                    .map(|(_, v)| Value::try_from(*v))
+                    .collect::<io::Result<Vec<_>>>()?;
+                if let Some(data) = encode_complex::<Small>(&keys, &values) {
+                    Ok(Value::SmallObject(ComplexValue::from_data(
+                        values.len(),
+                        data,
+                    )))
+                } else if let Some(data) = encode_complex::<Large>(&keys, &values) {
+                    Ok(Value::LargeObject(ComplexValue::from_data(
+                        values.len(),
+                        data,
+                    )))
+                } else {
+                    Err(too_large())
+                }
+            }
+        }
+    }
+}
+
+fn too_large() -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidInput,
+        "JSON value is too large to be stored as JSONB",
+    )
+}
+
+/// Encodes an array (if `keys` is empty) or an object using the given storage format.
+///
+/// Returns `None` if the result doesn't fit the storage format.
+fn encode_complex<T: StorageFormat>(keys: &[&[u8]], values: &[Value<'_>]) -> Option<Vec<u8>> {
+    let header_size = if keys.is_empty() {
+        Array::header_size::<T>(values.len() as u32)
+    } else {
+        Object::header_size::<T>(values.len() as u32)
+    };
+
+    let mut header = Vec::with_capacity(header_size);
+    let mut data = vec![0_u8; header_size];
+
+    T::put_offset(&mut header, values.len())?;
+    // total size is not yet known
+    T::put_offset(&mut header, 0)?;
+
+    for key in keys {
+        T::put_offset(&mut header, data.len())?;
+        header.put_u16_le(u16::try_from(key.len()).ok()?);
+        data.put_slice(key);
+    }
+
+    for value in values {
+        let value_type = value.jsonb_type();
+        header.put_u8(value_type as u8);
+        if value_type.is_inlined::<T>() {
+            let mut inlined = Vec::with_capacity(T::OFFSET_SIZE as usize);
+            value.serialize_body(&mut inlined);
+            inlined.resize(T::OFFSET_SIZE as usize, 0);
+            header.put_slice(&inlined);
+        } else {
+            T::put_offset(&mut header, data.len())?;
+            value.serialize_body(&mut data);
+        }
+    }
+
+    let mut total_size = Vec::with_capacity(T::OFFSET_SIZE as usize);
+    T::put_offset(&mut total_size, data.len())?;
+    let offset_size = T::OFFSET_SIZE as usize;
+    header[offset_size..2 * offset_size].copy_from_slice(&total_size);
+    data[..header_size].copy_from_slice(&header);
+
+    Some(data)
 }
 
 impl<'a> TryFrom<Value<'a>> for serde_json::Value {
@@ -668,6 +859,18 @@ pub trait StorageFormat {
     const VALUE_ENTRY_SIZE: u8 = Self::OFFSET_SIZE + 1;
 
     fn eat_offset(buf: &mut ParseBuf<'_>) -> u32;
+
+    /// Writes the given offset or size.
+    ///
+    /// Returns `None` if it doesn't fit the storage format.
+    fn put_offset(buf: &mut Vec<u8>, offset: usize) -> Option<()> {
+        let offset_size = Self::OFFSET_SIZE as usize;
+        if offset as u64 >= 1 << (8 * offset_size) {
+            return None;
+        }
+        buf.put_slice(&(offset as u64).to_le_bytes()[..offset_size]);
+        Some(())
+    }
 }
 
 /// Small array/object storage format. See [`StorageFormat`].
@@ -797,3 +1000,75 @@ impl TryFrom<u8> for LiteralType {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::{convert::TryFrom, io};
+
+    use super::Value;
+    use crate::{
+        binlog::{consts::BinlogVersion, events::EventData, value::BinlogValue, BinlogFile},
+        io::ParseBuf,
+        proto::MySerialize,
+    };
+
+    fn check_roundtrip(value: &Value<'_>) -> io::Result<()> {
+        let mut raw = Vec::new();
+        value.serialize(&mut raw);
+        assert_eq!(&ParseBuf(&raw).parse::<Value>(())?, value);
+
+        // MySql encodes JSON the same way
+        if let Ok(json) = serde_json::Value::try_from(value.clone()) {
+            let encoded = Value::try_from(&json)?;
+            let mut encoded_raw = Vec::new();
+            encoded.serialize(&mut encoded_raw);
+            assert_eq!(encoded_raw, raw, "{}", json);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn jsonb_roundtrip() -> io::Result<()> {
+        let file_data = std::fs::read("./test-data/binlogs/json.binlog.000001")?;
+        let mut binlog_file = BinlogFile::new(BinlogVersion::Version4, &file_data[..])?;
+
+        let mut found = 0;
+        while let Some(ev) = binlog_file.next() {
+            let ev = ev?;
+            if let Some(EventData::RowsEvent(rows_event)) = ev.read_data()? {
+                let tme = binlog_file.reader().get_tme(rows_event.table_id()).unwrap();
+                for row in rows_event.rows(tme) {
+                    let (before, after) = row?;
+                    for row in before.into_iter().chain(after) {
+                        for value in row.unwrap() {
+                            if let BinlogValue::Jsonb(value) = value {
+                                check_roundtrip(&value)?;
+                                found += 1;
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        assert!(found > 0);
+
+        let json: serde_json::Value = serde_json::from_str(
+            r#"{"a": [1, -1, 70000, -3000000000, 18446744073709551615, 1.5, true, null],
+                "bb": {"key": "value"}, "c": ""}"#,
+        )
+        .unwrap();
+        let value = Value::try_from(&json)?;
+        assert!(value.is_object());
+        check_roundtrip(&value)?;
+        assert_eq!(serde_json::Value::try_from(value).unwrap(), json);
+
+        // must switch to the large storage format
+        let json = serde_json::Value::Array(vec!["x".repeat(u16::MAX as usize).into()]);
+        let value = Value::try_from(&json)?;
+        assert!(matches!(value, Value::LargeArray(_)));
+        check_roundtrip(&value)?;
+
+        Ok(())
+    }
+}
@@ -9,12 +9,12 @@
 use std::{borrow::Cow, convert::TryFrom, io};
 
 use crate::{
-    io::ParseBuf,
+    io::{BufMutExt, ParseBuf},
     misc::raw::{bytes::LenEnc, Const, RawBytes, RawInt},
-    proto::MyDeserialize,
+    proto::{MyDeserialize, MySerialize},
 };
 
-use super::jsonb;
+use super::jsonb::{self, JsonbToJsonError};
 
 /// An operation kind of a JsonDiff object.
 #[allow(non_camel_case_types)]
@@ -34,6 +34,12 @@ pub enum JsonDiffOperation {
 #[repr(transparent)]
 pub struct UnknownJsonDiffOperation(pub u8);
 
+impl From<JsonDiffOperation> for u8 {
+    fn from(x: JsonDiffOperation) -> Self {
+        x as u8
+    }
+}
+
 impl From<UnknownJsonDiffOperation> for u8 {
     fn from(x: UnknownJsonDiffOperation) -> Self {
         x.0
@@ -65,6 +71,25 @@ pub struct JsonDiff<'a> {
 }
 
 impl<'a> JsonDiff<'a> {
+    /// Creates a new instance.
+    ///
+    /// `value` is ignored for the [`JsonDiffOperation::REMOVE`] operation.
+    pub fn new(
+        operation: JsonDiffOperation,
+        path: impl Into<Cow<'a, [u8]>>,
+        value: Option<jsonb::Value<'a>>,
+    ) -> Self {
+        let value = match operation {
+            JsonDiffOperation::REMOVE => None,
+            _ => Some(value.unwrap_or(jsonb::Value::Null)),
+        };
+        Self {
+            path: RawBytes::new(path),
+            operation: Const::new(operation),
+            value,
+        }
+    }
+
     /// Returns the raw JsonDiff path.
     pub fn path(&'a self) -> &'a [u8] {
         self.path.as_bytes()
@@ -85,6 +110,79 @@ impl<'a> JsonDiff<'a> {
         self.value.as_ref()
     }
 
+    /// Parses the JsonDiff path.
+    pub fn path_legs(&self) -> Result<Vec<JsonPathLeg>, JsonDiffApplyError> {
+        parse_json_path(self.path.as_bytes())
+    }
+
+    /// Applies this change to the given JSON document.
+    pub fn apply(&self, doc: &mut serde_json::Value) -> Result<(), JsonDiffApplyError> {
+        let legs = self.path_legs()?;
+        let not_found = || JsonDiffApplyError::PathNotFound(self.path_str().into_owned());
+
+        let value = match self.value {
+            Some(ref value) => Some(serde_json::Value::try_from(value.clone())?),
+            None => None,
+        };
+
+        let (last, parent_legs) = match legs.split_last() {
+            Some(x) => x,
+            None => {
+                // the path is `$`
+                return match value {
+                    Some(value) if self.operation() == JsonDiffOperation::REPLACE => {
+                        *doc = value;
+                        Ok(())
+                    }
+                    _ => Err(JsonDiffApplyError::InvalidPath(
+                        self.path_str().into_owned(),
+                    )),
+                };
+            }
+        };
+
+        let mut parent = doc;
+        for leg in parent_legs {
+            parent = leg.get_mut(parent).ok_or_else(not_found)?;
+        }
+
+        match (self.operation(), last, parent) {
+            (JsonDiffOperation::REPLACE, leg, parent) => {
+                *leg.get_mut(parent).ok_or_else(not_found)? = value.unwrap_or_default();
+            }
+            (
+                JsonDiffOperation::INSERT,
+                JsonPathLeg::Member(key),
+                serde_json::Value::Object(map),
+            ) => {
+                map.insert(key.clone(), value.unwrap_or_default());
+            }
+            (JsonDiffOperation::INSERT, JsonPathLeg::Cell(cell), serde_json::Value::Array(arr)) => {
+                // positions past the end of an array mean appending
+                let pos = cell.resolve(arr.len()).unwrap_or(0).min(arr.len());
+                arr.insert(pos, value.unwrap_or_default());
+            }
+            (
+                JsonDiffOperation::REMOVE,
+                JsonPathLeg::Member(key),
+                serde_json::Value::Object(map),
+            ) => {
+                map.remove(key).ok_or_else(not_found)?;
+            }
+            (JsonDiffOperation::REMOVE, JsonPathLeg::Cell(cell), serde_json::Value::Array(arr)) => {
+                match cell.resolve(arr.len()) {
+                    Some(pos) if pos < arr.len() => {
+                        arr.remove(pos);
+                    }
+                    _ => return Err(not_found()),
+                }
+            }
+            _ => return Err(not_found()),
+        }
+
+        Ok(())
+    }
+
     /// Returns a `'static` version of `self`.
     pub fn into_owned(self) -> JsonDiff<'static> {
         JsonDiff {
@@ -95,6 +193,18 @@ impl<'a> JsonDiff<'a> {
     }
 }
 
+impl MySerialize for JsonDiff<'_> {
+    fn serialize(&self, buf: &mut Vec<u8>) {
+        self.operation.serialize(&mut *buf);
+        self.path.serialize(&mut *buf);
+        if let Some(ref value) = self.value {
+            let mut value_buf = Vec::new();
+            value.serialize(&mut value_buf);
+            buf.put_lenenc_str(&value_buf);
+        }
+    }
+}
+
 impl<'de> MyDeserialize<'de> for JsonDiff<'de> {
     const SIZE: Option<usize> = None;
     type Ctx = ();
@@ -117,3 +227,224 @@ impl<'de> MyDeserialize<'de> for JsonDiff<'de> {
         })
     }
 }
+
+/// Applies the given changes to the JSON document (in order).
+pub fn apply_json_diffs<'a>(
+    doc: &mut serde_json::Value,
+    diffs: impl IntoIterator<Item = &'a JsonDiff<'a>>,
+) -> Result<(), JsonDiffApplyError> {
+    for diff in diffs {
+        diff.apply(doc)?;
+    }
+    Ok(())
+}
+
+/// An error of applying a [`JsonDiff`].
+#[derive(Debug, thiserror::Error)]
+pub enum JsonDiffApplyError {
+    #[error("Invalid JsonDiff path: {}", _0)]
+    InvalidPath(String),
+    #[error("JsonDiff path doesn't exist in the document: {}", _0)]
+    PathNotFound(String),
+    #[error("Invalid JsonDiff value: {}", _0)]
+    InvalidValue(#[from] JsonbToJsonError),
+}
+
+/// A position within a JSON array.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum JsonArrayCell {
+    /// `[N]`.
+    Index(usize),
+    /// `[last]` or `[last - N]`.
+    Last(usize),
+}
+
+impl JsonArrayCell {
+    /// Resolves this position within an array of the given length.
+    ///
+    /// Returns `None` if it points before the beginning of an array.
+    pub fn resolve(self, len: usize) -> Option<usize> {
+        match self {
+            JsonArrayCell::Index(x) => Some(x),
+            JsonArrayCell::Last(x) => len.checked_sub(1)?.checked_sub(x),
+        }
+    }
+}
+
+/// A leg of a JSON path (see [`JsonDiff::path_legs`]).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum JsonPathLeg {
+    /// Object member (`.key` or `."key"`).
+    Member(String),
+    /// Array cell (`[N]`, `[last]` or `[last - N]`).
+    Cell(JsonArrayCell),
+}
+
+impl JsonPathLeg {
+    fn get_mut<'a>(&self, value: &'a mut serde_json::Value) -> Option<&'a mut serde_json::Value> {
+        match (self, value) {
+            (JsonPathLeg::Member(key), serde_json::Value::Object(map)) => map.get_mut(key),
+            (JsonPathLeg::Cell(cell), serde_json::Value::Array(arr)) => {
+                let pos = cell.resolve(arr.len())?;
+                arr.get_mut(pos)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Parses a JSON path without wildcards (e.g. `$.a[1]."b c"[last]`).
+fn parse_json_path(path: &[u8]) -> Result<Vec<JsonPathLeg>, JsonDiffApplyError> {
+    let invalid = || JsonDiffApplyError::InvalidPath(String::from_utf8_lossy(path).into_owned());
+    let path = std::str::from_utf8(path).map_err(|_| invalid())?.trim();
+
+    let mut rest = path.strip_prefix('$').ok_or_else(invalid)?.trim_start();
+    let mut legs = Vec::new();
+    while !rest.is_empty() {
+        if let Some(member) = rest.strip_prefix('.') {
+            let member = member.trim_start();
+            if member.starts_with('"') {
+                // find the closing quote
+                let mut escaped = false;
+                let end = member
+                    .char_indices()
+                    .skip(1)
+                    .find(|&(_, c)| {
+                        let found = !escaped && c == '"';
+                        escaped = !escaped && c == '\\';
+                        found
+                    })
+                    .map(|(i, _)| i + 1)
+                    .ok_or_else(invalid)?;
+                let key: String = serde_json::from_str(&member[..end]).map_err(|_| invalid())?;
+                legs.push(JsonPathLeg::Member(key));
+                rest = &member[end..];
+            } else {
+                let end = member
+                    .find(|c: char| c == '.' || c == '[' || c.is_whitespace())
+                    .unwrap_or(member.len());
+                let key = &member[..end];
+                if key.is_empty() || key.contains('*') {
+                    return Err(invalid());
+                }
+                legs.push(JsonPathLeg::Member(key.into()));
+                rest = &member[end..];
+            }
+        } else if let Some(cell) = rest.strip_prefix('[') {
+            let end = cell.find(']').ok_or_else(invalid)?;
+            let spec = cell[..end].trim();
+            let cell_value = match spec.strip_prefix("last") {
+                Some(offset) => match offset.trim_start().strip_prefix('-') {
+                    Some(offset) => {
+                        JsonArrayCell::Last(offset.trim().parse().map_err(|_| invalid())?)
+                    }
+                    None if offset.trim().is_empty() => JsonArrayCell::Last(0),
+                    None => return Err(invalid()),
+                },
+                None => JsonArrayCell::Index(spec.parse().map_err(|_| invalid())?),
+            };
+            legs.push(JsonPathLeg::Cell(cell_value));
+            rest = &cell[end + 1..];
+        } else {
+            return Err(invalid());
+        }
+        rest = rest.trim_start();
+    }
+
+    Ok(legs)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::TryFrom;
+
+    use serde_json::json;
+
+    use super::{
+        apply_json_diffs, JsonArrayCell, JsonDiff, JsonDiffApplyError, JsonDiffOperation,
+        JsonPathLeg,
+    };
+    use crate::{binlog::jsonb, io::ParseBuf, proto::MySerialize};
+
+    fn diff(
+        operation: JsonDiffOperation,
+        path: &'static str,
+        value: serde_json::Value,
+    ) -> JsonDiff<'static> {
+        let value = jsonb::Value::try_from(&value).unwrap();
+        JsonDiff::new(operation, path.as_bytes(), Some(value))
+    }
+
+    #[test]
+    fn json_path_legs() {
+        let diff = diff(
+            JsonDiffOperation::REMOVE,
+            r#"$.a[1]."b \"c\"" [last] [ last - 2 ].d"#,
+            json!(null),
+        );
+        assert_eq!(
+            diff.path_legs().unwrap(),
+            vec![
+                JsonPathLeg::Member("a".into()),
+                JsonPathLeg::Cell(JsonArrayCell::Index(1)),
+                JsonPathLeg::Member("b \"c\"".into()),
+                JsonPathLeg::Cell(JsonArrayCell::Last(0)),
+                JsonPathLeg::Cell(JsonArrayCell::Last(2)),
+                JsonPathLeg::Member("d".into()),
+            ]
+        );
+
+        for invalid in &["", "a", "$.", "$.*", "$[*]", "$[1", "$[last+1]", "$.\"a"] {
+            let diff = JsonDiff::new(JsonDiffOperation::REMOVE, invalid.as_bytes(), None);
+            assert!(
+                matches!(diff.path_legs(), Err(JsonDiffApplyError::InvalidPath(_))),
+                "{}",
+                invalid
+            );
+        }
+    }
+
+    #[test]
+    fn apply_json_diff() {
+        let mut doc = json!({"a": [1, 2, 3], "b": {"c": "d"}});
+        let diffs = vec![
+            diff(JsonDiffOperation::REPLACE, "$.a[0]", json!(10)),
+            diff(JsonDiffOperation::INSERT, "$.a[1]", json!(15)),
+            diff(JsonDiffOperation::INSERT, "$.a[100]", json!(40)),
+            diff(JsonDiffOperation::REMOVE, "$.a[last - 1]", json!(null)),
+            diff(JsonDiffOperation::INSERT, "$.b.e", json!([true])),
+            diff(JsonDiffOperation::REMOVE, r#"$."b".c"#, json!(null)),
+        ];
+
+        // must survive serialization
+        let diffs = diffs
+            .into_iter()
+            .map(|diff| {
+                let mut raw = Vec::new();
+                diff.serialize(&mut raw);
+                ParseBuf(&raw).parse::<JsonDiff>(()).unwrap().into_owned()
+            })
+            .collect::<Vec<_>>();
+
+        apply_json_diffs(&mut doc, &diffs).unwrap();
+        assert_eq!(doc, json!({"a": [10, 15, 2, 40], "b": {"e": [true]}}));
+
+        assert!(matches!(
+            diff(JsonDiffOperation::REPLACE, "$.x", json!(1)).apply(&mut doc),
+            Err(JsonDiffApplyError::PathNotFound(_))
+        ));
+        assert!(matches!(
+            diff(JsonDiffOperation::REMOVE, "$.a[10]", json!(1)).apply(&mut doc),
+            Err(JsonDiffApplyError::PathNotFound(_))
+        ));
+        assert!(matches!(
+            diff(JsonDiffOperation::INSERT, "$.a.b", json!(1)).apply(&mut doc),
+            Err(JsonDiffApplyError::PathNotFound(_))
+        ));
+
+        diff(JsonDiffOperation::REPLACE, "$", json!("x"))
+            .apply(&mut doc)
+            .unwrap();
+        assert_eq!(doc, json!("x"));
+    }
+}
@@ -0,0 +1,331 @@
+// Copyright (c) 2023 Anatoly Ikorsky
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Assembly of `LOAD DATA INFILE` file payloads.
+//!
+//! The server writes the contents of a loaded file into the binlog as a `BEGIN_LOAD_QUERY_EVENT`
+//! followed by zero or more `APPEND_BLOCK_EVENT`s (all sharing the same `file_id`).
+//! The statement itself is then logged as an `EXECUTE_LOAD_QUERY_EVENT`
+//! (see [`super::events::ExecuteLoadQueryEvent::substituted_query`]).
+
+use std::{
+    collections::HashMap,
+    convert::TryInto,
+    fs::{self, File, OpenOptions},
+    io::{self, Cursor, Read, Seek, SeekFrom, Write},
+    path::PathBuf,
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+use super::events::EventData;
+
+/// Counter used to generate unique names of spill files.
+static SPILL_FILE_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// Collects `LOAD DATA INFILE` file payloads by `file_id`.
+///
+/// Payloads are kept in memory until the total amount of buffered data exceeds the memory limit
+/// (see [`LoadFileAssembler::with_memory_limit`]). After that a payload being appended
+/// is spilled into a temporary file (see [`LoadFileAssembler::with_spill_dir`]).
+/// Spill files are removed once the corresponding [`LoadFileReader`] or the assembler is dropped.
+#[derive(Debug)]
+pub struct LoadFileAssembler {
+    memory_limit: Option<usize>,
+    spill_dir: PathBuf,
+    memory_usage: usize,
+    files: HashMap<u32, LoadFileData>,
+}
+
+impl Default for LoadFileAssembler {
+    fn default() -> Self {
+        Self {
+            memory_limit: None,
+            spill_dir: std::env::temp_dir(),
+            memory_usage: 0,
+            files: HashMap::new(),
+        }
+    }
+}
+
+impl LoadFileAssembler {
+    /// Creates a new assembler without memory limit.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns modified `self` with the given limit on the total size of in-memory payloads
+    /// (`None` means unlimited).
+    pub fn with_memory_limit(mut self, memory_limit: Option<usize>) -> Self {
+        self.memory_limit = memory_limit;
+        self
+    }
+
+    /// Returns modified `self` with the given directory for spill files
+    /// (defaults to [`std::env::temp_dir`]).
+    pub fn with_spill_dir(mut self, spill_dir: impl Into<PathBuf>) -> Self {
+        self.spill_dir = spill_dir.into();
+        self
+    }
+
+    /// Returns the memory limit (`None` means unlimited).
+    pub fn memory_limit(&self) -> Option<usize> {
+        self.memory_limit
+    }
+
+    /// Returns the total size of in-memory payloads.
+    pub fn memory_usage(&self) -> usize {
+        self.memory_usage
+    }
+
+    /// Returns the number of files being assembled.
+    pub fn len(&self) -> usize {
+        self.files.len()
+    }
+
+    /// Returns `true` if there are no files being assembled.
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+
+    /// Handles `BEGIN_LOAD_QUERY_EVENT`, `APPEND_BLOCK_EVENT` and `DELETE_FILE_EVENT`.
+    ///
+    /// Returns `false` if the event is of any other type (i.e. it's ignored).
+    pub fn handle_event(&mut self, event: &EventData<'_>) -> io::Result<bool> {
+        match event {
+            EventData::BeginLoadQueryEvent(ev) => {
+                self.begin(ev.file_id(), ev.block_data())?;
+            }
+            EventData::AppendBlockEvent(data) => {
+                let (file_id, block_data) = split_file_id(data)?;
+                self.append(file_id, block_data)?;
+            }
+            EventData::DeleteFileEvent(data) => {
+                let (file_id, _) = split_file_id(data)?;
+                self.discard(file_id);
+            }
+            _ => return Ok(false),
+        }
+        Ok(true)
+    }
+
+    /// Starts a new file with the given first block (replaces a file with the same id).
+    pub fn begin(&mut self, file_id: u32, block_data: &[u8]) -> io::Result<()> {
+        self.discard(file_id);
+        self.files.insert(file_id, LoadFileData::Memory(Vec::new()));
+        self.append(file_id, block_data)
+    }
+
+    /// Appends a block to the file.
+    ///
+    /// Returns an `InvalidData` error if the file wasn't started.
+    pub fn append(&mut self, file_id: u32, block_data: &[u8]) -> io::Result<()> {
+        let exceeds_limit = matches!(
+            self.memory_limit,
+            Some(limit) if self.memory_usage + block_data.len() > limit
+        );
+        let spill_path = self.spill_path(file_id);
+        let file = self.files.get_mut(&file_id).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("append to unknown load file {}", file_id),
+            )
+        })?;
+
+        match file {
+            LoadFileData::Memory(data) if exceeds_limit => {
+                let mut spill = SpillFile::create(spill_path)?;
+                spill.file.write_all(data)?;
+                spill.file.write_all(block_data)?;
+                self.memory_usage -= data.len();
+                *file = LoadFileData::Spilled(spill);
+            }
+            LoadFileData::Memory(data) => {
+                data.extend_from_slice(block_data);
+                self.memory_usage += block_data.len();
+            }
+            LoadFileData::Spilled(spill) => spill.file.write_all(block_data)?,
+        }
+
+        Ok(())
+    }
+
+    /// Removes the file and returns a reader over its contents (if the file exists).
+    pub fn take(&mut self, file_id: u32) -> io::Result<Option<LoadFileReader>> {
+        match self.files.remove(&file_id) {
+            Some(LoadFileData::Memory(data)) => {
+                self.memory_usage -= data.len();
+                Ok(Some(LoadFileReader(ReaderInner::Memory(Cursor::new(data)))))
+            }
+            Some(LoadFileData::Spilled(mut spill)) => {
+                spill.file.flush()?;
+                spill.file.seek(SeekFrom::Start(0))?;
+                Ok(Some(LoadFileReader(ReaderInner::Spilled(spill))))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Removes the file (if it exists).
+    pub fn discard(&mut self, file_id: u32) {
+        if let Some(LoadFileData::Memory(data)) = self.files.remove(&file_id) {
+            self.memory_usage -= data.len();
+        }
+    }
+
+    fn spill_path(&self, file_id: u32) -> PathBuf {
+        self.spill_dir.join(format!(
+            "mysql_common-load-{}-{}-{}.data",
+            std::process::id(),
+            file_id,
+            SPILL_FILE_COUNTER.fetch_add(1, Ordering::Relaxed),
+        ))
+    }
+}
+
+/// Splits `APPEND_BLOCK_EVENT` or `DELETE_FILE_EVENT` data into the file id and the rest.
+fn split_file_id(data: &[u8]) -> io::Result<(u32, &[u8])> {
+    if data.len() < 4 {
+        return Err(io::Error::new(
+            io::ErrorKind::UnexpectedEof,
+            "can't parse load file id",
+        ));
+    }
+    let (file_id, rest) = data.split_at(4);
+    let file_id = u32::from_le_bytes(file_id.try_into().expect("4 bytes"));
+    Ok((file_id, rest))
+}
+
+#[derive(Debug)]
+enum LoadFileData {
+    Memory(Vec<u8>),
+    Spilled(SpillFile),
+}
+
+/// Temporary file that is removed on drop.
+#[derive(Debug)]
+struct SpillFile {
+    path: PathBuf,
+    file: File,
+}
+
+impl SpillFile {
+    fn create(path: PathBuf) -> io::Result<Self> {
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create_new(true)
+            .open(&path)?;
+        Ok(Self { path, file })
+    }
+}
+
+impl Drop for SpillFile {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+#[derive(Debug)]
+enum ReaderInner {
+    Memory(Cursor<Vec<u8>>),
+    Spilled(SpillFile),
+}
+
+/// A reader over an assembled file (see [`LoadFileAssembler::take`]).
+#[derive(Debug)]
+pub struct LoadFileReader(ReaderInner);
+
+impl LoadFileReader {
+    /// Returns `true` if the contents was spilled into a temporary file.
+    pub fn is_spilled(&self) -> bool {
+        matches!(self.0, ReaderInner::Spilled(_))
+    }
+}
+
+impl Read for LoadFileReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self.0 {
+            ReaderInner::Memory(ref mut cursor) => cursor.read(buf),
+            ReaderInner::Spilled(ref mut spill) => spill.file.read(buf),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{borrow::Cow, io::Read};
+
+    use super::LoadFileAssembler;
+    use crate::binlog::events::{BeginLoadQueryEvent, EventData, XidEvent};
+
+    fn append_block(file_id: u32, data: &[u8]) -> EventData<'static> {
+        let mut raw = file_id.to_le_bytes().to_vec();
+        raw.extend_from_slice(data);
+        EventData::AppendBlockEvent(Cow::Owned(raw))
+    }
+
+    fn read_all(assembler: &mut LoadFileAssembler, file_id: u32) -> (Vec<u8>, bool) {
+        let mut reader = assembler.take(file_id).unwrap().unwrap();
+        let mut output = Vec::new();
+        reader.read_to_end(&mut output).unwrap();
+        (output, reader.is_spilled())
+    }
+
+    #[test]
+    fn load_file_assembly() {
+        let dir = std::env::temp_dir().join(format!("mysql_common-load-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let mut assembler = LoadFileAssembler::new()
+            .with_memory_limit(Some(8))
+            .with_spill_dir(&dir);
+
+        let begin =
+            |file_id, data: &'static [u8]| BeginLoadQueryEvent::new(file_id).with_block_data(data);
+        assert!(assembler
+            .handle_event(&EventData::BeginLoadQueryEvent(begin(1, b"abc")))
+            .unwrap());
+        assert!(assembler
+            .handle_event(&EventData::BeginLoadQueryEvent(begin(2, b"12")))
+            .unwrap());
+        assert!(assembler.handle_event(&append_block(1, b"def")).unwrap());
+        assert_eq!(assembler.memory_usage(), 8);
+        assert!(!assembler
+            .handle_event(&EventData::XidEvent(XidEvent::new(1)))
+            .unwrap());
+
+        // exceeds the limit
+        assembler.handle_event(&append_block(2, b"345")).unwrap();
+        assembler.handle_event(&append_block(2, b"6")).unwrap();
+        assert_eq!(assembler.memory_usage(), 6);
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 1);
+
+        assert_eq!(read_all(&mut assembler, 1), (b"abcdef".to_vec(), false));
+        assert_eq!(read_all(&mut assembler, 2), (b"123456".to_vec(), true));
+        assert_eq!(assembler.memory_usage(), 0);
+        assert!(assembler.is_empty());
+        // spill file is removed with the reader
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 0);
+
+        // DELETE_FILE_EVENT
+        assembler.begin(3, b"x").unwrap();
+        assert!(assembler
+            .handle_event(&EventData::DeleteFileEvent(Cow::Borrowed(
+                &3_u32.to_le_bytes()[..]
+            )))
+            .unwrap());
+        assert!(assembler.take(3).unwrap().is_none());
+        assert!(assembler.handle_event(&append_block(3, b"x")).is_err());
+        assert!(assembler
+            .handle_event(&EventData::AppendBlockEvent(Cow::Borrowed(b"x")))
+            .is_err());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
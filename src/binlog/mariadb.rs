@@ -0,0 +1,873 @@
+// Copyright (c) 2023 Anatoly Ikorsky
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! MariaDB-specific binlog events.
+
+use std::{
+    borrow::Cow,
+    cmp::min,
+    fmt,
+    io::{self, Read, Write},
+};
+
+use flate2::{read::ZlibDecoder, write::ZlibEncoder, Compression};
+
+use saturating::Saturating as S;
+
+use crate::{
+    binlog::{
+        consts::{BinlogVersion, EventType, MariadbGtidFlags},
+        events::BinlogEventHeader,
+        xa::XaTransactionId,
+        BinlogCtx, BinlogEvent, BinlogStruct,
+    },
+    io::ParseBuf,
+    misc::{
+        raw::{
+            bytes::{EofBytes, U32Bytes},
+            int::*,
+            RawBytes, RawFlags, Skip,
+        },
+        unexpected_buf_eof,
+    },
+    proto::{MyDeserialize, MySerialize},
+};
+
+/// Query that caused the following rows events (MariaDB).
+///
+/// It is written if the `binlog_annotate_row_events` server variable is set.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct AnnotateRowsEvent<'a> {
+    query: RawBytes<'a, EofBytes>,
+}
+
+impl<'a> AnnotateRowsEvent<'a> {
+    /// Creates a new `AnnotateRowsEvent`.
+    pub fn new(query: impl Into<Cow<'a, [u8]>>) -> Self {
+        Self {
+            query: RawBytes::new(query),
+        }
+    }
+
+    /// Returns the raw query.
+    pub fn query_raw(&'a self) -> &'a [u8] {
+        self.query.as_bytes()
+    }
+
+    /// Returns query as a string (lossy converted).
+    pub fn query(&'a self) -> Cow<'a, str> {
+        self.query.as_str()
+    }
+
+    pub fn into_owned(self) -> AnnotateRowsEvent<'static> {
+        AnnotateRowsEvent {
+            query: self.query.into_owned(),
+        }
+    }
+}
+
+impl<'de> MyDeserialize<'de> for AnnotateRowsEvent<'de> {
+    const SIZE: Option<usize> = None;
+    type Ctx = BinlogCtx<'de>;
+
+    fn deserialize(_ctx: Self::Ctx, buf: &mut ParseBuf<'de>) -> io::Result<Self> {
+        Ok(Self {
+            query: buf.parse(())?,
+        })
+    }
+}
+
+impl MySerialize for AnnotateRowsEvent<'_> {
+    fn serialize(&self, buf: &mut Vec<u8>) {
+        self.query.serialize(&mut *buf);
+    }
+}
+
+impl<'a> BinlogEvent<'a> for AnnotateRowsEvent<'a> {
+    const EVENT_TYPE: EventType = EventType::MARIADB_ANNOTATE_ROWS_EVENT;
+}
+
+impl<'a> BinlogStruct<'a> for AnnotateRowsEvent<'a> {
+    fn len(&self, _version: BinlogVersion) -> usize {
+        min(
+            self.query.0.len(),
+            u32::MAX as usize - BinlogEventHeader::LEN,
+        )
+    }
+}
+
+/// Names the oldest binlog file that is still needed for XA crash recovery (MariaDB).
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct BinlogCheckpointEvent<'a> {
+    file_name: RawBytes<'a, U32Bytes>,
+}
+
+impl<'a> BinlogCheckpointEvent<'a> {
+    /// Creates a new `BinlogCheckpointEvent`.
+    pub fn new(file_name: impl Into<Cow<'a, [u8]>>) -> Self {
+        Self {
+            file_name: RawBytes::new(file_name),
+        }
+    }
+
+    /// Returns the raw binlog file name.
+    pub fn file_name_raw(&'a self) -> &'a [u8] {
+        self.file_name.as_bytes()
+    }
+
+    /// Returns the binlog file name as a string (lossy converted).
+    pub fn file_name(&'a self) -> Cow<'a, str> {
+        self.file_name.as_str()
+    }
+
+    pub fn into_owned(self) -> BinlogCheckpointEvent<'static> {
+        BinlogCheckpointEvent {
+            file_name: self.file_name.into_owned(),
+        }
+    }
+}
+
+impl<'de> MyDeserialize<'de> for BinlogCheckpointEvent<'de> {
+    const SIZE: Option<usize> = None;
+    type Ctx = BinlogCtx<'de>;
+
+    fn deserialize(_ctx: Self::Ctx, buf: &mut ParseBuf<'de>) -> io::Result<Self> {
+        Ok(Self {
+            file_name: buf.parse(())?,
+        })
+    }
+}
+
+impl MySerialize for BinlogCheckpointEvent<'_> {
+    fn serialize(&self, buf: &mut Vec<u8>) {
+        self.file_name.serialize(&mut *buf);
+    }
+}
+
+impl<'a> BinlogEvent<'a> for BinlogCheckpointEvent<'a> {
+    const EVENT_TYPE: EventType = EventType::MARIADB_BINLOG_CHECKPOINT_EVENT;
+}
+
+impl<'a> BinlogStruct<'a> for BinlogCheckpointEvent<'a> {
+    fn len(&self, _version: BinlogVersion) -> usize {
+        let mut len = S(0);
+
+        len += S(4);
+        len += S(min(self.file_name.0.len(), u32::MAX as usize));
+
+        min(len.0, u32::MAX as usize - BinlogEventHeader::LEN)
+    }
+}
+
+/// XID of an XA transaction (as stored in a MariaDB GTID event).
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct MariadbXid<'a> {
+    format_id: RawInt<LeU32>,
+    gtrid: Cow<'a, [u8]>,
+    bqual: Cow<'a, [u8]>,
+}
+
+impl<'a> MariadbXid<'a> {
+    /// Maximum length of the `gtrid` and `bqual` parts.
+    pub const MAX_PART_LEN: usize = 64;
+
+    /// Creates a new XID.
+    ///
+    /// `gtrid` and `bqual` will be truncated to [`MariadbXid::MAX_PART_LEN`] bytes.
+    pub fn new(
+        format_id: u32,
+        gtrid: impl Into<Cow<'a, [u8]>>,
+        bqual: impl Into<Cow<'a, [u8]>>,
+    ) -> Self {
+        let truncate = |mut part: Cow<'a, [u8]>| {
+            if part.len() > Self::MAX_PART_LEN {
+                part.to_mut().truncate(Self::MAX_PART_LEN);
+            }
+            part
+        };
+        Self {
+            format_id: RawInt::new(format_id),
+            gtrid: truncate(gtrid.into()),
+            bqual: truncate(bqual.into()),
+        }
+    }
+
+    /// Returns the format id.
+    pub fn format_id(&self) -> u32 {
+        self.format_id.0
+    }
+
+    /// Returns the global transaction identifier.
+    pub fn gtrid(&self) -> &[u8] {
+        &self.gtrid
+    }
+
+    /// Returns the branch qualifier.
+    pub fn bqual(&self) -> &[u8] {
+        &self.bqual
+    }
+
+    pub fn into_owned(self) -> MariadbXid<'static> {
+        MariadbXid {
+            format_id: self.format_id,
+            gtrid: Cow::Owned(self.gtrid.into_owned()),
+            bqual: Cow::Owned(self.bqual.into_owned()),
+        }
+    }
+
+    fn len(&self) -> usize {
+        4 + 1 + 1 + self.gtrid.len() + self.bqual.len()
+    }
+}
+
+impl<'de> MyDeserialize<'de> for MariadbXid<'de> {
+    const SIZE: Option<usize> = None;
+    type Ctx = ();
+
+    fn deserialize((): Self::Ctx, buf: &mut ParseBuf<'de>) -> io::Result<Self> {
+        let mut sbuf: ParseBuf = buf.parse(6)?;
+        let format_id = sbuf.parse_unchecked(())?;
+        let gtrid_len = sbuf.eat_u8() as usize;
+        let bqual_len = sbuf.eat_u8() as usize;
+
+        if gtrid_len > Self::MAX_PART_LEN || bqual_len > Self::MAX_PART_LEN {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "invalid XID length in MariaDB GTID event",
+            ));
+        }
+
+        let data = buf
+            .checked_eat(gtrid_len + bqual_len)
+            .ok_or_else(unexpected_buf_eof)?;
+
+        Ok(Self {
+            format_id,
+            gtrid: Cow::Borrowed(&data[..gtrid_len]),
+            bqual: Cow::Borrowed(&data[gtrid_len..]),
+        })
+    }
+}
+
+impl MySerialize for MariadbXid<'_> {
+    fn serialize(&self, buf: &mut Vec<u8>) {
+        self.format_id.serialize(&mut *buf);
+        buf.push(self.gtrid.len() as u8);
+        buf.push(self.bqual.len() as u8);
+        buf.extend_from_slice(&self.gtrid);
+        buf.extend_from_slice(&self.bqual);
+    }
+}
+
+impl<'a> From<MariadbXid<'a>> for XaTransactionId<'a> {
+    fn from(xid: MariadbXid<'a>) -> Self {
+        XaTransactionId::new(xid.format_id.0, xid.gtrid, xid.bqual)
+    }
+}
+
+/// Starts an event group (transaction) and defines its GTID (MariaDB).
+///
+/// Server id of the GTID is the server id of the event header.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct MariadbGtidEvent<'a> {
+    seq_no: RawInt<LeU64>,
+    domain_id: RawInt<LeU32>,
+    flags: RawFlags<MariadbGtidFlags, u8>,
+    /// Only present if [`MariadbGtidFlags::GROUP_COMMIT_ID`] is set.
+    commit_id: Option<RawInt<LeU64>>,
+    /// Only present if [`MariadbGtidFlags::PREPARED_XA`] or [`MariadbGtidFlags::COMPLETED_XA`]
+    /// is set.
+    xid: Option<MariadbXid<'a>>,
+    /// Extra data written by newer servers (extra flags, engine count, etc.).
+    extra: RawBytes<'a, EofBytes>,
+}
+
+impl<'a> MariadbGtidEvent<'a> {
+    /// Creates a new `MariadbGtidEvent`.
+    pub fn new(domain_id: u32, seq_no: u64) -> Self {
+        Self {
+            seq_no: RawInt::new(seq_no),
+            domain_id: RawInt::new(domain_id),
+            flags: RawFlags::new(0),
+            commit_id: None,
+            xid: None,
+            extra: Default::default(),
+        }
+    }
+
+    /// Defines the `flags` value.
+    ///
+    /// Note that the [`MariadbGtidFlags::GROUP_COMMIT_ID`] flag is managed
+    /// by [`MariadbGtidEvent::with_commit_id`].
+    pub fn with_flags(mut self, flags: MariadbGtidFlags) -> Self {
+        let group_commit = self.flags.0 & MariadbGtidFlags::GROUP_COMMIT_ID.bits();
+        self.flags = RawFlags::new(
+            (flags.bits() & !MariadbGtidFlags::GROUP_COMMIT_ID.bits()) | group_commit,
+        );
+        self
+    }
+
+    /// Defines the `commit_id` value (sets or clears the [`MariadbGtidFlags::GROUP_COMMIT_ID`]
+    /// flag accordingly).
+    pub fn with_commit_id(mut self, commit_id: Option<u64>) -> Self {
+        self.commit_id = commit_id.map(RawInt::new);
+        if self.commit_id.is_some() {
+            self.flags.0 |= MariadbGtidFlags::GROUP_COMMIT_ID.bits();
+        } else {
+            self.flags.0 &= !MariadbGtidFlags::GROUP_COMMIT_ID.bits();
+        }
+        self
+    }
+
+    /// Defines the XID of an XA transaction.
+    ///
+    /// If `xid` is `Some`, then the [`MariadbGtidFlags::PREPARED_XA`] flag is set
+    /// (unless [`MariadbGtidFlags::COMPLETED_XA`] is already set). Otherwise both flags
+    /// are cleared.
+    pub fn with_xid(mut self, xid: Option<MariadbXid<'a>>) -> Self {
+        let xa_flags = (MariadbGtidFlags::PREPARED_XA | MariadbGtidFlags::COMPLETED_XA).bits();
+        self.xid = xid;
+        if self.xid.is_none() {
+            self.flags.0 &= !xa_flags;
+        } else if self.flags.0 & xa_flags == 0 {
+            self.flags.0 |= MariadbGtidFlags::PREPARED_XA.bits();
+        }
+        self
+    }
+
+    /// Returns the sequence number.
+    pub fn seq_no(&self) -> u64 {
+        self.seq_no.0
+    }
+
+    /// Returns the replication domain id.
+    pub fn domain_id(&self) -> u32 {
+        self.domain_id.0
+    }
+
+    /// Returns the raw `flags` value.
+    pub fn flags_raw(&self) -> u8 {
+        self.flags.0
+    }
+
+    /// Returns the `flags` value.
+    pub fn flags(&self) -> MariadbGtidFlags {
+        self.flags.get()
+    }
+
+    /// Returns the group commit id (if any).
+    pub fn commit_id(&self) -> Option<u64> {
+        self.commit_id.map(|x| x.0)
+    }
+
+    /// Returns the XID of an XA transaction (if any).
+    pub fn xid(&self) -> Option<&MariadbXid<'a>> {
+        self.xid.as_ref()
+    }
+
+    /// Returns extra data that follows known fields (not parsed).
+    pub fn extra(&'a self) -> &'a [u8] {
+        self.extra.as_bytes()
+    }
+
+    /// Returns the GTID of this event group given the `server_id` of the event header.
+    pub fn gtid(&self, server_id: u32) -> MariadbGtid {
+        MariadbGtid::new(self.domain_id(), server_id, self.seq_no())
+    }
+
+    pub fn into_owned(self) -> MariadbGtidEvent<'static> {
+        MariadbGtidEvent {
+            seq_no: self.seq_no,
+            domain_id: self.domain_id,
+            flags: self.flags,
+            commit_id: self.commit_id,
+            xid: self.xid.map(MariadbXid::into_owned),
+            extra: self.extra.into_owned(),
+        }
+    }
+}
+
+impl<'de> MyDeserialize<'de> for MariadbGtidEvent<'de> {
+    const SIZE: Option<usize> = None;
+    type Ctx = BinlogCtx<'de>;
+
+    fn deserialize(_ctx: Self::Ctx, buf: &mut ParseBuf<'de>) -> io::Result<Self> {
+        let mut sbuf: ParseBuf = buf.parse(8 + 4 + 1)?;
+        let seq_no = sbuf.parse_unchecked(())?;
+        let domain_id = sbuf.parse_unchecked(())?;
+        let flags: RawFlags<MariadbGtidFlags, u8> = sbuf.parse_unchecked(())?;
+
+        let commit_id = if flags.0 & MariadbGtidFlags::GROUP_COMMIT_ID.bits() != 0 {
+            Some(buf.parse(())?)
+        } else {
+            buf.parse::<Skip<6>>(())?;
+            None
+        };
+
+        let xa_flags = (MariadbGtidFlags::PREPARED_XA | MariadbGtidFlags::COMPLETED_XA).bits();
+        let xid = if flags.0 & xa_flags != 0 {
+            Some(buf.parse(())?)
+        } else {
+            None
+        };
+
+        Ok(Self {
+            seq_no,
+            domain_id,
+            flags,
+            commit_id,
+            xid,
+            extra: buf.parse(())?,
+        })
+    }
+}
+
+impl MySerialize for MariadbGtidEvent<'_> {
+    fn serialize(&self, buf: &mut Vec<u8>) {
+        self.seq_no.serialize(&mut *buf);
+        self.domain_id.serialize(&mut *buf);
+        self.flags.serialize(&mut *buf);
+        match self.commit_id {
+            Some(commit_id) => commit_id.serialize(&mut *buf),
+            None => Skip::<6>.serialize(&mut *buf),
+        }
+        if let Some(ref xid) = self.xid {
+            xid.serialize(&mut *buf);
+        }
+        self.extra.serialize(&mut *buf);
+    }
+}
+
+impl<'a> BinlogEvent<'a> for MariadbGtidEvent<'a> {
+    const EVENT_TYPE: EventType = EventType::MARIADB_GTID_EVENT;
+}
+
+impl<'a> BinlogStruct<'a> for MariadbGtidEvent<'a> {
+    fn len(&self, _version: BinlogVersion) -> usize {
+        let mut len = S(0);
+
+        len += S(8 + 4 + 1);
+        len += S(if self.commit_id.is_some() { 8 } else { 6 });
+        len += S(self.xid.as_ref().map(MariadbXid::len).unwrap_or(0));
+        len += S(self.extra.0.len());
+
+        min(len.0, u32::MAX as usize - BinlogEventHeader::LEN)
+    }
+}
+
+/// MariaDB GTID (`domain_id-server_id-seq_no`).
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct MariadbGtid {
+    domain_id: u32,
+    server_id: u32,
+    seq_no: u64,
+}
+
+impl MariadbGtid {
+    /// Creates a new GTID.
+    pub fn new(domain_id: u32, server_id: u32, seq_no: u64) -> Self {
+        Self {
+            domain_id,
+            server_id,
+            seq_no,
+        }
+    }
+
+    /// Returns the replication domain id.
+    pub fn domain_id(&self) -> u32 {
+        self.domain_id
+    }
+
+    /// Returns the server id.
+    pub fn server_id(&self) -> u32 {
+        self.server_id
+    }
+
+    /// Returns the sequence number.
+    pub fn seq_no(&self) -> u64 {
+        self.seq_no
+    }
+}
+
+impl fmt::Display for MariadbGtid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}-{}-{}", self.domain_id, self.server_id, self.seq_no)
+    }
+}
+
+impl<'de> MyDeserialize<'de> for MariadbGtid {
+    const SIZE: Option<usize> = Some(4 + 4 + 8);
+    type Ctx = ();
+
+    fn deserialize((): Self::Ctx, buf: &mut ParseBuf<'de>) -> io::Result<Self> {
+        Ok(Self {
+            domain_id: buf.eat_u32_le(),
+            server_id: buf.eat_u32_le(),
+            seq_no: buf.eat_u64_le(),
+        })
+    }
+}
+
+impl MySerialize for MariadbGtid {
+    fn serialize(&self, buf: &mut Vec<u8>) {
+        buf.extend_from_slice(&self.domain_id.to_le_bytes());
+        buf.extend_from_slice(&self.server_id.to_le_bytes());
+        buf.extend_from_slice(&self.seq_no.to_le_bytes());
+    }
+}
+
+/// Last GTID of each replication domain logged in previous binlog files (MariaDB).
+///
+/// It is written at the start of each binlog file.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct MariadbGtidListEvent<'a> {
+    /// Upper 4 bits of the count field.
+    flags: u8,
+    list: Vec<MariadbGtid>,
+    /// Trailing bytes (the server may pad an empty list).
+    extra: RawBytes<'a, EofBytes>,
+}
+
+impl<'a> MariadbGtidListEvent<'a> {
+    /// Event is sent to a slave that reached its `START SLAVE UNTIL` position.
+    pub const FLAG_UNTIL_REACHED: u8 = 0x01;
+    /// GTIDs of the list should be ignored by a slave.
+    pub const FLAG_IGN_GTIDS: u8 = 0x02;
+    /// Maximum number of GTIDs in the list.
+    pub const MAX_COUNT: usize = (1 << 28) - 1;
+
+    /// Creates a new `MariadbGtidListEvent`.
+    ///
+    /// The list will be truncated to [`MariadbGtidListEvent::MAX_COUNT`] elements.
+    pub fn new(list: impl IntoIterator<Item = MariadbGtid>) -> Self {
+        Self {
+            flags: 0,
+            list: list.into_iter().take(Self::MAX_COUNT).collect(),
+            extra: Default::default(),
+        }
+    }
+
+    /// Defines the `flags` value (only lower 4 bits are used).
+    pub fn with_flags(mut self, flags: u8) -> Self {
+        self.flags = flags & 0x0f;
+        self
+    }
+
+    /// Returns the `flags` value.
+    pub fn flags(&self) -> u8 {
+        self.flags
+    }
+
+    /// Returns the list of GTIDs.
+    pub fn list(&self) -> &[MariadbGtid] {
+        &self.list
+    }
+
+    /// Returns the list of GTIDs.
+    pub fn into_list(self) -> Vec<MariadbGtid> {
+        self.list
+    }
+
+    /// Returns trailing bytes that follow the list (not parsed).
+    pub fn extra(&'a self) -> &'a [u8] {
+        self.extra.as_bytes()
+    }
+
+    pub fn into_owned(self) -> MariadbGtidListEvent<'static> {
+        MariadbGtidListEvent {
+            flags: self.flags,
+            list: self.list,
+            extra: self.extra.into_owned(),
+        }
+    }
+}
+
+impl<'de> MyDeserialize<'de> for MariadbGtidListEvent<'de> {
+    const SIZE: Option<usize> = None;
+    type Ctx = BinlogCtx<'de>;
+
+    fn deserialize(_ctx: Self::Ctx, buf: &mut ParseBuf<'de>) -> io::Result<Self> {
+        let count: RawInt<LeU32> = buf.parse(())?;
+        let flags = (count.0 >> 28) as u8;
+        let count = (count.0 & Self::MAX_COUNT as u32) as usize;
+
+        let mut sbuf: ParseBuf = buf.parse(count * MariadbGtid::SIZE.unwrap())?;
+        let mut list = Vec::with_capacity(count);
+        for _ in 0..count {
+            list.push(sbuf.parse_unchecked(())?);
+        }
+
+        Ok(Self {
+            flags,
+            list,
+            extra: buf.parse(())?,
+        })
+    }
+}
+
+impl MySerialize for MariadbGtidListEvent<'_> {
+    fn serialize(&self, buf: &mut Vec<u8>) {
+        let count = ((self.flags as u32) << 28) | self.list.len() as u32;
+        RawInt::<LeU32>::new(count).serialize(&mut *buf);
+        for gtid in &self.list {
+            gtid.serialize(&mut *buf);
+        }
+        self.extra.serialize(&mut *buf);
+    }
+}
+
+impl<'a> BinlogEvent<'a> for MariadbGtidListEvent<'a> {
+    const EVENT_TYPE: EventType = EventType::MARIADB_GTID_LIST_EVENT;
+}
+
+impl<'a> BinlogStruct<'a> for MariadbGtidListEvent<'a> {
+    fn len(&self, _version: BinlogVersion) -> usize {
+        let mut len = S(0);
+
+        len += S(4);
+        len += S(self.list.len()) * S(MariadbGtid::SIZE.unwrap());
+        len += S(self.extra.0.len());
+
+        min(len.0, u32::MAX as usize - BinlogEventHeader::LEN)
+    }
+}
+
+/// Flag of the first byte of a buffer of a compressed event.
+const COMPRESSED_FLAG: u8 = 0x80;
+
+/// Compression algorithm of a compressed event (MariaDB only implements zlib).
+const COMPRESSION_ALG_ZLIB: u8 = 0;
+
+/// Decompresses a buffer of a MariaDB compressed event (the query of
+/// [`EventType::MARIADB_QUERY_COMPRESSED_EVENT`] or the rows data of compressed rows events).
+///
+/// The buffer starts with a header byte (`0x80 | algorithm << 4 | len_len`) followed by
+/// the uncompressed length (`len_len` bytes, big-endian) and the compressed data.
+///
+/// Returns an `InvalidData` error if the buffer is malformed or the algorithm isn't zlib.
+pub fn decompress_event_data(data: &[u8]) -> io::Result<Vec<u8>> {
+    let header = *data.first().ok_or_else(unexpected_buf_eof)?;
+    if header & COMPRESSED_FLAG == 0 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "event data isn't compressed",
+        ));
+    }
+
+    let alg = (header & 0x70) >> 4;
+    if alg != COMPRESSION_ALG_ZLIB {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("unknown event compression algorithm {}", alg),
+        ));
+    }
+
+    let len_len = (header & 0x07) as usize;
+    if !(1..=4).contains(&len_len) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("invalid length of the uncompressed length {}", len_len),
+        ));
+    }
+    let len = data
+        .get(1..1 + len_len)
+        .ok_or_else(unexpected_buf_eof)?
+        .iter()
+        .fold(0_u64, |len, x| len << 8 | *x as u64);
+
+    // don't trust the length to preallocate
+    let mut output = Vec::with_capacity(min(len as usize, data.len().saturating_mul(16)));
+    ZlibDecoder::new(&data[1 + len_len..])
+        .take(len + 1)
+        .read_to_end(&mut output)?;
+    if output.len() as u64 != len {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "uncompressed length of event data doesn't match the header",
+        ));
+    }
+
+    Ok(output)
+}
+
+/// Compresses the given data as MariaDB does for compressed events
+/// (see [`decompress_event_data`]).
+pub fn compress_event_data(data: &[u8]) -> io::Result<Vec<u8>> {
+    let len = data.len() as u32;
+    let len_len = 4 - min(len.leading_zeros() as usize / 8, 3);
+
+    let mut output = Vec::with_capacity(1 + len_len + data.len() / 2);
+    output.push(COMPRESSED_FLAG | COMPRESSION_ALG_ZLIB << 4 | len_len as u8);
+    output.extend_from_slice(&len.to_be_bytes()[4 - len_len..]);
+
+    let mut encoder = ZlibEncoder::new(output, Compression::default());
+    encoder.write_all(data)?;
+    encoder.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+
+    use super::{
+        compress_event_data, decompress_event_data, MariadbGtid, MariadbGtidEvent,
+        MariadbGtidListEvent, MariadbXid,
+    };
+    use crate::{
+        binlog::{
+            consts::{BinlogVersion, EventFlags, EventType, MariadbGtidFlags},
+            events::{BinlogEventHeader, Event, EventData, FormatDescriptionEvent},
+            BinlogCtx, BinlogFile, BinlogStruct,
+        },
+        io::ParseBuf,
+        proto::MySerialize,
+    };
+
+    #[test]
+    fn should_compress_event_data() -> io::Result<()> {
+        for len in [0, 1, 255, 256, 70_000] {
+            let data = (0..len).map(|x| (x % 7) as u8).collect::<Vec<_>>();
+            let compressed = compress_event_data(&data)?;
+            let len_len = match len {
+                0..=255 => 1,
+                256..=65535 => 2,
+                _ => 3,
+            };
+            assert_eq!(compressed[0], 0x80 | len_len);
+            assert_eq!(decompress_event_data(&compressed)?, data);
+        }
+
+        let compressed = compress_event_data(b"foo")?;
+        // not compressed
+        assert!(decompress_event_data(&compressed[1..]).is_err());
+        // unknown algorithm
+        assert!(decompress_event_data(&[&[0x91][..], &compressed[1..]].concat()).is_err());
+        // length mismatch
+        assert!(
+            decompress_event_data(&[&compressed[..1], &[4], &compressed[2..]].concat()).is_err()
+        );
+        assert!(
+            decompress_event_data(&[&compressed[..1], &[2], &compressed[2..]].concat()).is_err()
+        );
+        // truncated
+        assert!(decompress_event_data(&compressed[..compressed.len() - 2]).is_err());
+        assert!(decompress_event_data(&[]).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn should_read_compressed_rows_events() -> io::Result<()> {
+        let data = std::fs::read("./test-data/binlogs/mariadb-bin.000001")?;
+        let mut binlog_file = BinlogFile::new(BinlogVersion::Version4, &data[..])?;
+        let fde = FormatDescriptionEvent::new(BinlogVersion::Version4);
+
+        let mut count = 0;
+        while let Some(event) = binlog_file.next() {
+            let event = event?;
+            let rows_event = match event.read_data()? {
+                Some(EventData::RowsEvent(rows_event)) => rows_event,
+                _ => continue,
+            };
+            let tme = binlog_file.reader().get_tme(rows_event.table_id()).unwrap();
+            let compressed_type = match rows_event.event_type() {
+                EventType::WRITE_ROWS_EVENT_V1 => EventType::MARIADB_WRITE_ROWS_COMPRESSED_EVENT_V1,
+                EventType::UPDATE_ROWS_EVENT_V1 => {
+                    EventType::MARIADB_UPDATE_ROWS_COMPRESSED_EVENT_V1
+                }
+                EventType::DELETE_ROWS_EVENT_V1 => {
+                    EventType::MARIADB_DELETE_ROWS_COMPRESSED_EVENT_V1
+                }
+                other => panic!("unexpected {:?}", other),
+            };
+
+            // compress rows data as MariaDB does
+            let rows_data = rows_event.rows_data();
+            let mut payload = event.data()[..event.data().len() - rows_data.len()].to_vec();
+            payload.extend(compress_event_data(rows_data)?);
+            let mut raw = Vec::new();
+            BinlogEventHeader::new(
+                0,
+                compressed_type,
+                1,
+                (BinlogEventHeader::LEN + payload.len()) as u32,
+                0,
+                EventFlags::empty(),
+            )
+            .serialize(&mut raw);
+            raw.extend_from_slice(&payload);
+
+            let compressed = Event::read(&fde, &raw[..])?;
+            let compressed_rows = match compressed.read_data()? {
+                Some(EventData::RowsEvent(rows_event)) => rows_event,
+                other => panic!("unexpected {:?}", other),
+            };
+            assert_eq!(compressed_rows.event_type(), compressed_type);
+            assert_eq!(compressed_rows.rows_data(), rows_data);
+            assert_eq!(
+                compressed_rows.rows(tme).collect::<io::Result<Vec<_>>>()?,
+                rows_event.rows(tme).collect::<io::Result<Vec<_>>>()?
+            );
+
+            // compressed data is kept as is
+            let mut output = Vec::new();
+            compressed_rows.serialize(&mut output);
+            assert_eq!(output, payload);
+            assert_eq!(
+                compressed_rows.serialized_len(BinlogVersion::Version4),
+                payload.len()
+            );
+            count += 1;
+        }
+        assert!(count > 0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn mariadb_events_roundtrip() {
+        let fde = FormatDescriptionEvent::new(BinlogVersion::Version4);
+
+        let gtid = MariadbGtidEvent::new(3, 42)
+            .with_flags(MariadbGtidFlags::TRANSACTIONAL)
+            .with_commit_id(Some(7))
+            .with_xid(Some(MariadbXid::new(1, &b"gtrid"[..], &b"bq"[..])));
+        assert_eq!(
+            gtid.flags(),
+            MariadbGtidFlags::TRANSACTIONAL
+                | MariadbGtidFlags::GROUP_COMMIT_ID
+                | MariadbGtidFlags::PREPARED_XA
+        );
+
+        let mut output = Vec::new();
+        gtid.serialize(&mut output);
+        assert_eq!(output.len(), gtid.len(BinlogVersion::Version4));
+
+        let parsed: MariadbGtidEvent = ParseBuf(&output)
+            .parse(BinlogCtx::new(output.len(), &fde))
+            .unwrap();
+        assert_eq!(parsed, gtid);
+        assert_eq!(parsed.commit_id(), Some(7));
+        assert_eq!(parsed.xid().unwrap().gtrid(), b"gtrid");
+        assert_eq!(parsed.gtid(1).to_string(), "3-1-42");
+
+        let list =
+            MariadbGtidListEvent::new(vec![MariadbGtid::new(0, 1, 100), MariadbGtid::new(1, 2, 5)])
+                .with_flags(MariadbGtidListEvent::FLAG_IGN_GTIDS);
+
+        let mut output = Vec::new();
+        list.serialize(&mut output);
+        assert_eq!(output.len(), list.len(BinlogVersion::Version4));
+        assert_eq!(output[3] >> 4, MariadbGtidListEvent::FLAG_IGN_GTIDS);
+
+        let parsed: MariadbGtidListEvent = ParseBuf(&output)
+            .parse(BinlogCtx::new(output.len(), &fde))
+            .unwrap();
+        assert_eq!(parsed, list);
+    }
+}
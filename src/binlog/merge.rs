@@ -0,0 +1,449 @@
+// Copyright (c) 2023 Anatoly Ikorsky
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Merging of binlog event streams from multiple sources.
+
+use std::io;
+
+use super::{
+    consts::MariadbGtidFlags,
+    events::{Event, EventData, GtidEvent},
+    mariadb::MariadbGtid,
+};
+
+/// GTID of an event group.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum MergeGtid {
+    /// MySql GTID (`sid:gno`).
+    Mysql([u8; GtidEvent::ENCODED_SID_LENGTH], u64),
+    /// MariaDB GTID (`domain_id-server_id-seq_no`).
+    Mariadb(MariadbGtid),
+}
+
+/// Key that defines the order of merged event groups.
+///
+/// Groups are ordered by timestamp, then by server id, then by GTID
+/// (groups without GTID go first).
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct MergeKey {
+    timestamp: u32,
+    server_id: u32,
+    gtid: Option<MergeGtid>,
+}
+
+impl MergeKey {
+    /// Returns the timestamp of the first event of the group.
+    pub fn timestamp(&self) -> u32 {
+        self.timestamp
+    }
+
+    /// Returns the server id of the first event of the group.
+    pub fn server_id(&self) -> u32 {
+        self.server_id
+    }
+
+    /// Returns the GTID of the group (if any).
+    pub fn gtid(&self) -> Option<MergeGtid> {
+        self.gtid
+    }
+}
+
+/// Group of events that must not be interleaved with other events, i.e. a transaction
+/// or a single event outside of a transaction.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EventGroup {
+    source: usize,
+    key: MergeKey,
+    events: Vec<Event<'static>>,
+}
+
+impl EventGroup {
+    /// Returns the index of the source this group came from.
+    pub fn source(&self) -> usize {
+        self.source
+    }
+
+    /// Returns the merge key of this group.
+    pub fn key(&self) -> MergeKey {
+        self.key
+    }
+
+    /// Returns events of this group.
+    pub fn events(&self) -> &[Event<'static>] {
+        &self.events
+    }
+
+    /// Returns events of this group.
+    pub fn into_events(self) -> Vec<Event<'static>> {
+        self.events
+    }
+}
+
+/// Resolution of a conflict between two groups with the same GTID (see [`ConflictPolicy`]).
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum ConflictResolution {
+    /// Keep the group that is merged first and drop the other one (the default).
+    #[default]
+    KeepFirst,
+    /// Keep the other group and drop the group that is merged first.
+    KeepSecond,
+    /// Keep both groups.
+    KeepBoth,
+}
+
+/// Decides what to do if groups of different sources have the same GTID
+/// (e.g. a transaction that reached several sources via replication).
+pub trait ConflictPolicy {
+    /// Resolves a conflict between two groups with the same GTID.
+    ///
+    /// `first` is the group that is merged first. Returning an error stops the merge.
+    fn resolve(
+        &mut self,
+        first: &EventGroup,
+        second: &EventGroup,
+    ) -> io::Result<ConflictResolution>;
+}
+
+/// Resolves every conflict the same way.
+impl ConflictPolicy for ConflictResolution {
+    fn resolve(&mut self, _: &EventGroup, _: &EventGroup) -> io::Result<ConflictResolution> {
+        Ok(*self)
+    }
+}
+
+impl<F> ConflictPolicy for F
+where
+    F: FnMut(&EventGroup, &EventGroup) -> io::Result<ConflictResolution>,
+{
+    fn resolve(
+        &mut self,
+        first: &EventGroup,
+        second: &EventGroup,
+    ) -> io::Result<ConflictResolution> {
+        self(first, second)
+    }
+}
+
+/// Classifies an event for grouping into transactions.
+///
+/// Returns `Some(gtid)` if the event starts a group (with the given GTID, if any),
+/// and whether it ends the current group. `in_group` is whether a group is started,
+/// and `began` tracks whether the current group is an explicit transaction
+/// (so that a DDL query doesn't end it).
+pub(crate) fn classify_event(
+    event: &Event<'_>,
+    in_group: bool,
+    began: &mut bool,
+) -> io::Result<(Option<Option<MergeGtid>>, bool)> {
+    let mut starts = None;
+    let mut ends = false;
+    match event.read_data()? {
+        Some(EventData::GtidEvent(ev)) => {
+            starts = Some(Some(MergeGtid::Mysql(ev.sid(), ev.gno())));
+        }
+        Some(EventData::AnonymousGtidEvent(_)) => starts = Some(None),
+        Some(EventData::MariadbGtidEvent(ev)) => {
+            let gtid = ev.gtid(event.header().server_id());
+            starts = Some(Some(MergeGtid::Mariadb(gtid)));
+            // MariaDB doesn't write `BEGIN` after the GTID event
+            *began = !ev.flags().contains(MariadbGtidFlags::STANDALONE);
+        }
+        Some(EventData::QueryEvent(query)) => {
+            let text = query.query();
+            let text = text.trim();
+            if text.eq_ignore_ascii_case("BEGIN") {
+                if !in_group {
+                    starts = Some(None);
+                }
+                *began = true;
+            } else if text.eq_ignore_ascii_case("COMMIT") || text.eq_ignore_ascii_case("ROLLBACK") {
+                ends = true;
+            } else {
+                // DDL is a transaction on its own
+                ends = !*began;
+            }
+        }
+        Some(EventData::XidEvent(_))
+        | Some(EventData::XaPrepareLogEvent(_))
+        | Some(EventData::TransactionPayloadEvent(_)) => ends = true,
+        _ => (),
+    }
+    Ok((starts, ends))
+}
+
+/// Single source of a merge.
+#[derive(Debug)]
+struct MergeSource<I> {
+    events: I,
+    head: Option<EventGroup>,
+    exhausted: bool,
+}
+
+impl<I: Iterator<Item = io::Result<Event<'static>>>> MergeSource<I> {
+    /// Reads the next group of this source into `self.head` (if empty).
+    fn fill(&mut self, source: usize) -> io::Result<()> {
+        if self.head.is_some() || self.exhausted {
+            return Ok(());
+        }
+
+        let mut group: Option<EventGroup> = None;
+        // Whether the group is an explicit transaction (not a single DDL statement).
+        let mut began = false;
+        loop {
+            let event = match self.events.next() {
+                Some(event) => event?,
+                None => {
+                    self.exhausted = true;
+                    return match group {
+                        Some(_) => Err(io::Error::new(
+                            io::ErrorKind::UnexpectedEof,
+                            "incomplete transaction at the end of the merge source",
+                        )),
+                        None => Ok(()),
+                    };
+                }
+            };
+
+            let (starts, ends) = classify_event(&event, group.is_some(), &mut began)?;
+
+            match (group.as_mut(), starts) {
+                (Some(group), _) => group.events.push(event),
+                (None, Some(gtid)) => {
+                    group = Some(EventGroup {
+                        source,
+                        key: MergeKey {
+                            timestamp: event.header().timestamp(),
+                            server_id: event.header().server_id(),
+                            gtid,
+                        },
+                        events: vec![event],
+                    });
+                }
+                (None, None) => {
+                    // an event outside of a transaction
+                    self.head = Some(EventGroup {
+                        source,
+                        key: MergeKey {
+                            timestamp: event.header().timestamp(),
+                            server_id: event.header().server_id(),
+                            gtid: None,
+                        },
+                        events: vec![event],
+                    });
+                    return Ok(());
+                }
+            }
+
+            if ends {
+                self.head = group;
+                return Ok(());
+            }
+        }
+    }
+}
+
+/// Interleaves events of multiple sources (e.g. binlogs of different servers)
+/// into a single stream ordered by [`MergeKey`].
+///
+/// It's an iterator over [`EventGroup`]s, so transactions are never interleaved.
+/// A transaction starts with a GTID event (or a `BEGIN` query if there is no GTID)
+/// and ends with an XID event, a `COMMIT`/`ROLLBACK` query, or a DDL query
+/// that follows the GTID event. Other events form groups of their own.
+///
+/// Each source is expected to be ordered. Groups with equal keys are merged in the order
+/// of sources. Groups of different sources with the same GTID are resolved by the
+/// [`ConflictPolicy`] (duplicates are dropped by default).
+#[derive(Debug)]
+pub struct EventMerger<I, P = ConflictResolution> {
+    sources: Vec<MergeSource<I>>,
+    policy: P,
+    failed: bool,
+}
+
+impl<I> EventMerger<I> {
+    /// Creates a merger without sources that drops duplicate groups.
+    pub fn new() -> Self {
+        Self {
+            sources: Vec::new(),
+            policy: ConflictResolution::default(),
+            failed: false,
+        }
+    }
+}
+
+impl<I> Default for EventMerger<I> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<I, P> EventMerger<I, P>
+where
+    I: Iterator<Item = io::Result<Event<'static>>>,
+    P: ConflictPolicy,
+{
+    /// Returns modified `self` with the given conflict policy.
+    pub fn with_conflict_policy<Q: ConflictPolicy>(self, policy: Q) -> EventMerger<I, Q> {
+        EventMerger {
+            sources: self.sources,
+            policy,
+            failed: self.failed,
+        }
+    }
+
+    /// Returns modified `self` with the given source added.
+    pub fn with_source(mut self, events: I) -> Self {
+        self.add_source(events);
+        self
+    }
+
+    /// Adds the given source and returns its index.
+    pub fn add_source(&mut self, events: I) -> usize {
+        self.sources.push(MergeSource {
+            events,
+            head: None,
+            exhausted: false,
+        });
+        self.sources.len() - 1
+    }
+
+    /// Returns the number of sources.
+    pub fn sources_count(&self) -> usize {
+        self.sources.len()
+    }
+
+    fn next_group(&mut self) -> io::Result<Option<EventGroup>> {
+        for (i, source) in self.sources.iter_mut().enumerate() {
+            source.fill(i)?;
+        }
+
+        let next = self
+            .sources
+            .iter()
+            .enumerate()
+            .filter_map(|(i, source)| source.head.as_ref().map(|head| (head.key, i)))
+            .min();
+        let mut group = match next {
+            Some((_, i)) => self.sources[i].head.take().expect("should be here"),
+            None => return Ok(None),
+        };
+
+        if let Some(gtid) = group.key.gtid {
+            for i in 0..self.sources.len() {
+                let other = match self.sources[i].head.as_ref() {
+                    Some(other) if other.key.gtid == Some(gtid) => other,
+                    _ => continue,
+                };
+                match self.policy.resolve(&group, other)? {
+                    ConflictResolution::KeepFirst => {
+                        self.sources[i].head = None;
+                    }
+                    ConflictResolution::KeepSecond => {
+                        group = self.sources[i].head.take().expect("should be here");
+                    }
+                    ConflictResolution::KeepBoth => (),
+                }
+            }
+        }
+
+        Ok(Some(group))
+    }
+}
+
+impl<I, P> Iterator for EventMerger<I, P>
+where
+    I: Iterator<Item = io::Result<Event<'static>>>,
+    P: ConflictPolicy,
+{
+    type Item = io::Result<EventGroup>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+
+        match self.next_group() {
+            Ok(group) => group.map(Ok),
+            Err(err) => {
+                self.failed = true;
+                Some(Err(err))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+
+    use super::{ConflictResolution, EventGroup, EventMerger};
+    use crate::binlog::{consts::BinlogVersion, events::Event, BinlogFile};
+
+    fn read_events(name: &str) -> io::Result<Vec<Event<'static>>> {
+        let data = std::fs::read(format!("./test-data/binlogs/{}", name))?;
+        BinlogFile::new(BinlogVersion::Version4, &data[..])?.collect()
+    }
+
+    #[test]
+    fn should_merge_sources() -> io::Result<()> {
+        let mysql = read_events("binlog_transaction_with_GTID.000001")?;
+        let mariadb = read_events("mariadb-bin.000001")?;
+
+        let single = EventMerger::new()
+            .with_source(mysql.clone().into_iter().map(Ok))
+            .collect::<io::Result<Vec<_>>>()?;
+        assert_eq!(
+            single.iter().map(|x| x.events().len()).sum::<usize>(),
+            mysql.len()
+        );
+        assert!(single.iter().any(|x| x.key().gtid().is_some()));
+
+        let merged = EventMerger::new()
+            .with_source(mysql.clone().into_iter().map(Ok))
+            .with_source(mariadb.clone().into_iter().map(Ok))
+            .collect::<io::Result<Vec<_>>>()?;
+        assert_eq!(
+            merged.iter().map(|x| x.events().len()).sum::<usize>(),
+            mysql.len() + mariadb.len()
+        );
+        assert!(merged.windows(2).all(|x| x[0].key() <= x[1].key()));
+        let mariadb_groups = merged.iter().filter(|x| x.source() == 1).count();
+        // fde, gtid list, checkpoint and two transactions
+        assert_eq!(mariadb_groups, 5);
+
+        // the same stream twice
+        let merged = EventMerger::new()
+            .with_source(mysql.clone().into_iter().map(Ok))
+            .with_source(mysql.clone().into_iter().map(Ok))
+            .collect::<io::Result<Vec<_>>>()?;
+        let transactions = single.iter().filter(|x| x.key().gtid().is_some()).count();
+        assert_eq!(merged.len(), single.len() * 2 - transactions);
+        assert!(merged
+            .iter()
+            .filter(|x| x.key().gtid().is_some())
+            .all(|x| x.source() == 0));
+
+        let merged = EventMerger::new()
+            .with_source(mysql.clone().into_iter().map(Ok))
+            .with_source(mysql.clone().into_iter().map(Ok))
+            .with_conflict_policy(ConflictResolution::KeepBoth)
+            .collect::<io::Result<Vec<_>>>()?;
+        assert_eq!(merged.len(), single.len() * 2);
+
+        let mut merger = EventMerger::new()
+            .with_source(mysql.clone().into_iter().map(Ok))
+            .with_source(mysql.into_iter().map(Ok))
+            .with_conflict_policy(|_: &EventGroup, _: &EventGroup| {
+                Err(io::Error::new(io::ErrorKind::InvalidData, "conflict"))
+            });
+        assert!(merger.by_ref().any(|x| x.is_err()));
+        assert!(merger.next().is_none());
+
+        Ok(())
+    }
+}
@@ -6,15 +6,15 @@
 // option. All files in the project carrying such notice may not be copied,
 // modified, or distributed except according to those terms.
 
-use byteorder::{BigEndian as BE, ReadBytesExt};
+use byteorder::{BigEndian as BE, ReadBytesExt, WriteBytesExt};
 use saturating::Saturating as S;
 
 use std::{
     cmp::min,
-    io::{self, Write},
+    io::{self, Read, Write},
 };
 
-use crate::value::Value;
+use crate::{io::BufMutExt, value::Value};
 
 pub const TIMEF_INT_OFS: i64 = 0x800000;
 pub const TIMEF_OFS: i64 = 0x800000000000;
@@ -32,12 +32,12 @@ pub fn my_time_packed_from_binary<T: io::Read>(mut input: T, dec: u32) -> io::Re
     match dec {
         1 | 2 => {
             let mut intpart = input.read_u24::<BE>()? as i64 - TIMEF_INT_OFS;
-            let mut frac = input.read_u8()? as u32;
+            let mut frac = input.read_u8()? as i64;
             if intpart < 0 && frac > 0 {
                 intpart += 1;
                 frac -= 0x100;
             }
-            Ok(my_packed_time_make(intpart, (frac as i64) * 10_000))
+            Ok(my_packed_time_make(intpart, frac * 10_000))
         }
         3 | 4 => {
             let mut intpart = input.read_u24::<BE>()? as i64 - TIMEF_INT_OFS;
@@ -76,7 +76,8 @@ pub fn time_from_packed(mut tmp: i64) -> Value {
     let m = ((hms >> 6) as u32) % (1 << 6);
     let s = ((hms) as u32) % (1 << 6);
     let u = my_packed_time_get_frac_part(tmp);
-    Value::Time(neg, 0, h as u8, m as u8, s as u8, u as u32)
+    // hours may exceed 24 (up to 838)
+    Value::Time(neg, h / 24, (h % 24) as u8, m as u8, s as u8, u as u32)
 }
 
 pub fn my_datetime_packed_from_binary<T: io::Read>(mut input: T, dec: u32) -> io::Result<i64> {
@@ -131,23 +132,319 @@ pub fn my_timestamp_from_binary<T: io::Read>(mut input: T, dec: u8) -> io::Resul
     Ok((sec, usec))
 }
 
+/// Decodes a `MYSQL_TYPE_TIME2` value with the given fractional seconds precision
+/// (column metadata).
+pub fn time2_from_binary<T: io::Read>(input: T, dec: u8) -> io::Result<Value> {
+    my_time_packed_from_binary(input, dec as u32).map(time_from_packed)
+}
+
+/// Decodes a `MYSQL_TYPE_DATETIME2` value with the given fractional seconds precision
+/// (column metadata).
+pub fn datetime2_from_binary<T: io::Read>(input: T, dec: u8) -> io::Result<Value> {
+    my_datetime_packed_from_binary(input, dec as u32).map(datetime_from_packed)
+}
+
+/// Decodes a `MYSQL_TYPE_TIMESTAMP2` value with the given fractional seconds precision
+/// (column metadata) as an UTC date and time.
+///
+/// Zero timestamp is decoded as the zero date (`0000-00-00 00:00:00`).
+pub fn timestamp2_from_binary<T: io::Read>(input: T, dec: u8) -> io::Result<Value> {
+    let (sec, usec) = my_timestamp_from_binary(input, dec)?;
+    let (sec, usec) = (sec as u32 as u64, usec as u32);
+    if sec == 0 && usec == 0 {
+        return Ok(Value::Date(0, 0, 0, 0, 0, 0, 0));
+    }
+
+    let (days, secs_of_day) = (sec / 86_400, sec % 86_400);
+    let (year, month, day) = civil_from_days(days);
+    Ok(Value::Date(
+        year as u16,
+        month,
+        day,
+        (secs_of_day / 3_600) as u8,
+        (secs_of_day % 3_600 / 60) as u8,
+        (secs_of_day % 60) as u8,
+        usec,
+    ))
+}
+
+/// Writes the fractional part of a temporal value with the given precision.
+fn write_frac(buf: &mut Vec<u8>, frac: i64, dec: u8) -> io::Result<()> {
+    match dec {
+        1 | 2 => buf.write_i8((frac / 10_000) as i8),
+        3 | 4 => buf.write_i16::<BE>((frac / 100) as i16),
+        5 | 6 => {
+            buf.put_u24_be(frac as u32);
+            Ok(())
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Encodes a `MYSQL_TYPE_TIME2` value with the given fractional seconds precision
+/// (column metadata).
+///
+/// Expects `Value::Time`.
+pub fn time2_to_binary(buf: &mut Vec<u8>, value: &Value, dec: u8) -> io::Result<()> {
+    let (neg, days, h, m, s, u) = match *value {
+        Value::Time(neg, days, h, m, s, u) => (neg, days, h, m, s, u),
+        _ => return Err(temporal_mismatch("TIME")),
+    };
+    let hours = days as i64 * 24 + h as i64;
+    if hours > TIME_MAX_HOURS || m > 59 || s > 59 || u > MAX_MICROS {
+        return Err(temporal_mismatch("TIME"));
+    }
+    let hms = (hours << 12) | ((m as i64) << 6) | s as i64;
+    let mut packed = my_packed_time_make(hms, u as i64);
+    if neg {
+        packed = -packed;
+    }
+    match dec {
+        1..=4 => {
+            buf.put_u24_be((my_packed_time_get_int_part(packed) + TIMEF_INT_OFS) as u32);
+            write_frac(buf, my_packed_time_get_frac_part(packed), dec)
+        }
+        5 | 6 => {
+            buf.put_u48_be((packed + TIMEF_OFS) as u64);
+            Ok(())
+        }
+        _ => {
+            buf.put_u24_be((my_packed_time_get_int_part(packed) + TIMEF_INT_OFS) as u32);
+            Ok(())
+        }
+    }
+}
+
+/// Encodes a `MYSQL_TYPE_DATETIME2` value with the given fractional seconds precision
+/// (column metadata).
+///
+/// Expects `Value::Date`.
+pub fn datetime2_to_binary(buf: &mut Vec<u8>, value: &Value, dec: u8) -> io::Result<()> {
+    let (y, mo, d, h, mi, s, u) = match *value {
+        Value::Date(y, mo, d, h, mi, s, u) => (y, mo, d, h, mi, s, u),
+        _ => return Err(temporal_mismatch("DATETIME")),
+    };
+    // zero dates and zero date parts are allowed (see `NO_ZERO_IN_DATE` sql mode)
+    if y > 9999 || mo > 12 || d > 31 || !is_valid_time(h, mi, s, u) {
+        return Err(temporal_mismatch("DATETIME"));
+    }
+    let ymd = ((y as i64 * 13 + mo as i64) << 5) | d as i64;
+    let hms = ((h as i64) << 12) | ((mi as i64) << 6) | s as i64;
+    let intpart = (ymd << 17) | hms;
+    buf.put_u40_be((intpart + DATETIMEF_INT_OFS) as u64);
+    write_frac(buf, u as i64, dec)
+}
+
+/// Encodes a `MYSQL_TYPE_TIMESTAMP2` value with the given fractional seconds precision
+/// (column metadata).
+///
+/// Expects `Value::Date` (in UTC) or `Value::Int` (seconds since unix epoch).
+pub fn timestamp2_to_binary(buf: &mut Vec<u8>, value: &Value, dec: u8) -> io::Result<()> {
+    let (sec, usec) = match *value {
+        Value::Date(0, 0, 0, 0, 0, 0, 0) => (0, 0),
+        Value::Date(y, mo, d, h, mi, s, u) => {
+            if !is_valid_date(y, mo, d) || !is_valid_time(h, mi, s, u) {
+                return Err(temporal_mismatch("TIMESTAMP"));
+            }
+            let days =
+                days_from_civil(y as u64, mo, d).ok_or_else(|| temporal_mismatch("TIMESTAMP"))?;
+            let secs = days * 86_400 + h as u64 * 3_600 + mi as u64 * 60 + s as u64;
+            if secs == 0 {
+                // `1970-01-01 00:00:00` is reserved for the zero timestamp
+                return Err(temporal_mismatch("TIMESTAMP"));
+            }
+            (secs, u)
+        }
+        Value::Int(x) if x >= 0 => (x as u64, 0),
+        Value::UInt(x) => (x, 0),
+        _ => return Err(temporal_mismatch("TIMESTAMP")),
+    };
+    if sec > TIMESTAMP_MAX_SECS {
+        return Err(temporal_mismatch("TIMESTAMP"));
+    }
+    buf.write_u32::<BE>(sec as u32)?;
+    write_frac(buf, usec as i64, dec)
+}
+
+fn temporal_mismatch(ty: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidInput,
+        format!("value is not a valid {} value", ty),
+    )
+}
+
+/// Maximum number of hours in a `TIME` value (`838:59:59`).
+const TIME_MAX_HOURS: i64 = 838;
+
+/// Maximum value of a `TIMESTAMP` (`2038-01-19 03:14:07` UTC).
+const TIMESTAMP_MAX_SECS: u64 = i32::MAX as u64;
+
+/// Maximum value of the fractional part of a temporal value.
+const MAX_MICROS: u32 = 999_999;
+
+/// Returns `true` if the given date exists (zero dates are not valid).
+fn is_valid_date(year: u16, month: u8, day: u8) -> bool {
+    let is_leap = year.is_multiple_of(4) && (!year.is_multiple_of(100) || year.is_multiple_of(400));
+    let days_in_month = match month {
+        1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+        4 | 6 | 9 | 11 => 30,
+        2 if is_leap => 29,
+        2 => 28,
+        _ => return false,
+    };
+    year > 0 && (1..=days_in_month).contains(&day)
+}
+
+/// Returns `true` if the given time of day is valid.
+fn is_valid_time(hour: u8, minute: u8, second: u8, micros: u32) -> bool {
+    hour <= 23 && minute <= 59 && second <= 59 && micros <= MAX_MICROS
+}
+
+/// Converts a valid `(year, month, day)` triple to the number of days since unix epoch.
+///
+/// Returns `None` for dates before unix epoch.
+pub(crate) fn days_from_civil(year: u64, month: u8, day: u8) -> Option<u64> {
+    // see http://howardhinnant.github.io/date_algorithms.html#days_from_civil
+    debug_assert!((1..=12).contains(&month) && day >= 1);
+    let year = year.checked_sub((month <= 2) as u64)?;
+    let era = year / 400;
+    let yoe = year - era * 400;
+    let mp = (month as u64 + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + day as u64 - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    (era * 146_097 + doe).checked_sub(719_468)
+}
+
+/// Converts the number of days since unix epoch to a `(year, month, day)` triple.
+pub(crate) fn civil_from_days(days: u64) -> (u64, u8, u8) {
+    // see http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+    let z = days + 719_468;
+    let era = z / 146_097;
+    let doe = z % 146_097;
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + (month <= 2) as u64;
+    (year, month as u8, day as u8)
+}
+
+/// Maximum length of a database or table name (in characters).
+pub const NAME_CHAR_LEN: usize = 64;
+
+/// Checks that the given database or table name is a valid utf-8 string that fits
+/// both [`NAME_CHAR_LEN`] and a one-byte length prefix.
+///
+/// `what` is used in the error message (e.g. `"table name"`).
+pub(crate) fn check_identifier(name: &[u8], what: &str) -> io::Result<()> {
+    let invalid = |msg: String| io::Error::new(io::ErrorKind::InvalidInput, msg);
+    let name = std::str::from_utf8(name)
+        .map_err(|err| invalid(format!("{} is not a valid utf-8 string: {}", what, err)))?;
+    if name.chars().count() > NAME_CHAR_LEN || name.len() > u8::MAX as usize {
+        return Err(invalid(format!(
+            "{} is longer than {} characters",
+            what, NAME_CHAR_LEN
+        )));
+    }
+    Ok(())
+}
+
+/// `binary` collation id.
+pub const BINARY_COLLATION_ID: u32 = 63;
+
+pub use crate::misc::charset::decode_text;
+
+/// Defines how binlog write routines handle data that exceeds its maximum serialized length.
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum TruncationPolicy {
+    /// Silently truncate the data (the default).
+    ///
+    /// Output won't round-trip if the data was truncated.
+    #[default]
+    Truncate,
+    /// Emit an `InvalidInput` error if the data would be truncated.
+    Error,
+}
+
+/// Reader that reads no more than the given number of bytes from the underlying reader.
+///
+/// Unlike [`io::Take`] it reports premature end of the underlying reader
+/// as an `UnexpectedEof` error.
+#[derive(Debug)]
+pub struct LimitRead<T> {
+    read: T,
+    limit: u64,
+}
+
+impl<T> LimitRead<T> {
+    /// Creates a new instance that reads at most `limit` bytes.
+    pub fn new(read: T, limit: u64) -> Self {
+        Self { read, limit }
+    }
+
+    /// Returns the number of bytes that remain to be read.
+    pub fn limit(&self) -> u64 {
+        self.limit
+    }
+
+    /// Returns the underlying reader.
+    pub fn into_inner(self) -> T {
+        self.read
+    }
+}
+
+impl<T: Read> Read for LimitRead<T> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.limit == 0 || buf.is_empty() {
+            return Ok(0);
+        }
+        let len = min(buf.len() as u64, self.limit) as usize;
+        let count = self.read.read(&mut buf[..len])?;
+        if count == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "underlying reader ended before the limit",
+            ));
+        }
+        self.limit -= count as u64;
+        Ok(count)
+    }
+}
+
 pub(crate) struct LimitedWrite<T> {
     limit: S<usize>,
+    policy: TruncationPolicy,
     write: T,
 }
 
 impl<T> LimitedWrite<T> {
-    pub fn new(write: T, limit: S<usize>) -> Self {
-        Self { limit, write }
+    pub fn new(write: T, limit: S<usize>, policy: TruncationPolicy) -> Self {
+        Self {
+            limit,
+            policy,
+            write,
+        }
     }
 }
 
 impl<T: Write> Write for LimitedWrite<T> {
     fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
         let limit = min(buf.len(), self.limit.0);
+        if limit < buf.len() && self.policy == TruncationPolicy::Error {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "data exceeds maximum serialized length",
+            ));
+        }
         let count = self.write.write(&buf[..limit])?;
         self.limit -= S(count);
-        Ok(count)
+        if count == limit {
+            // everything beyond the limit is discarded
+            Ok(buf.len())
+        } else {
+            Ok(count)
+        }
     }
 
     fn flush(&mut self) -> io::Result<()> {
@@ -156,9 +453,190 @@ impl<T: Write> Write for LimitedWrite<T> {
 }
 
 pub(crate) trait LimitWrite: Write + Sized {
-    fn limit(&mut self, limit: S<usize>) -> LimitedWrite<&mut Self> {
-        LimitedWrite::new(self, limit)
+    fn limit(&mut self, limit: S<usize>, policy: TruncationPolicy) -> LimitedWrite<&mut Self> {
+        LimitedWrite::new(self, limit, policy)
     }
 }
 
 impl<T: Write> LimitWrite for T {}
+
+#[cfg(test)]
+mod tests {
+    use saturating::Saturating as S;
+
+    use std::io::{ErrorKind, Write};
+
+    use super::{
+        datetime2_from_binary, datetime2_to_binary, time2_from_binary, time2_to_binary,
+        timestamp2_from_binary, timestamp2_to_binary, LimitWrite, TruncationPolicy,
+    };
+    use crate::value::Value::{self, Date, Int, Time};
+
+    #[test]
+    fn temporal2_from_binary() {
+        // 2023-06-19 12:30:45
+        assert_eq!(
+            datetime2_from_binary(&b"\x99\xb0\x66\xc7\xad"[..], 0).unwrap(),
+            Date(2023, 6, 19, 12, 30, 45, 0)
+        );
+        // 2023-06-19 12:30:45.123
+        assert_eq!(
+            datetime2_from_binary(&b"\x99\xb0\x66\xc7\xad\x04\xce"[..], 3).unwrap(),
+            Date(2023, 6, 19, 12, 30, 45, 123_000)
+        );
+
+        // 838:59:59
+        assert_eq!(
+            time2_from_binary(&b"\xb4\x6e\xfb"[..], 0).unwrap(),
+            Time(false, 34, 22, 59, 59, 0)
+        );
+        // -00:00:01.50
+        assert_eq!(
+            time2_from_binary(&b"\x7f\xff\xfe\xce"[..], 2).unwrap(),
+            Time(true, 0, 0, 0, 1, 500_000)
+        );
+        // 00:00:00.000001
+        assert_eq!(
+            time2_from_binary(&b"\x80\x00\x00\x00\x00\x01"[..], 6).unwrap(),
+            Time(false, 0, 0, 0, 0, 1)
+        );
+
+        // 2023-06-19 12:30:45.123456 UTC
+        assert_eq!(
+            timestamp2_from_binary(&b"\x64\x90\x4a\x75\x01\xe2\x40"[..], 6).unwrap(),
+            Date(2023, 6, 19, 12, 30, 45, 123_456)
+        );
+        assert_eq!(
+            timestamp2_from_binary(&b"\x00\x00\x00\x00"[..], 0).unwrap(),
+            Date(0, 0, 0, 0, 0, 0, 0)
+        );
+        assert_eq!(
+            timestamp2_from_binary(&b"\x00\x00\x00\x01"[..], 0).unwrap(),
+            Date(1970, 1, 1, 0, 0, 1, 0)
+        );
+
+        assert!(datetime2_from_binary(&b"\x99\xb0\x66\xc7\xad"[..], 3).is_err());
+    }
+
+    #[test]
+    fn temporal2_to_binary() {
+        type Encode = fn(&mut Vec<u8>, &Value, u8) -> std::io::Result<()>;
+        type Decode = fn(&[u8], u8) -> std::io::Result<Value>;
+        let round_trip = |encode: Encode, decode: Decode, value: Value, dec: u8| {
+            let mut buf = Vec::new();
+            encode(&mut buf, &value, dec).unwrap();
+            assert_eq!(decode(&buf, dec).unwrap(), value);
+        };
+        let invalid = |encode: Encode, value: Value| {
+            let err = encode(&mut Vec::new(), &value, 6).unwrap_err();
+            assert_eq!(err.kind(), ErrorKind::InvalidInput, "{:?}", value);
+        };
+
+        for dec in [0, 2, 4, 6] {
+            let micros = if dec == 0 { 0 } else { 990_000 };
+            for value in [
+                Time(false, 34, 22, 59, 59, micros),
+                Time(true, 34, 22, 59, 59, micros),
+                Time(false, 0, 0, 0, 0, 0),
+            ] {
+                round_trip(
+                    time2_to_binary,
+                    |x, dec| time2_from_binary(x, dec),
+                    value,
+                    dec,
+                );
+            }
+            for value in [
+                Date(9999, 12, 31, 23, 59, 59, micros),
+                Date(1000, 1, 1, 0, 0, 0, 0),
+                Date(2023, 0, 0, 0, 0, 0, 0),
+                Date(0, 0, 0, 0, 0, 0, 0),
+            ] {
+                round_trip(
+                    datetime2_to_binary,
+                    |x, dec| datetime2_from_binary(x, dec),
+                    value,
+                    dec,
+                );
+            }
+            for value in [
+                Date(2038, 1, 19, 3, 14, 7, micros),
+                Date(1970, 1, 1, 0, 0, 1, 0),
+                Date(2024, 2, 29, 12, 0, 0, 0),
+                Date(0, 0, 0, 0, 0, 0, 0),
+            ] {
+                round_trip(
+                    timestamp2_to_binary,
+                    |x, dec| timestamp2_from_binary(x, dec),
+                    value,
+                    dec,
+                );
+            }
+        }
+
+        for value in [
+            Time(false, 34, 23, 0, 0, 0),
+            Time(false, 1000, 0, 0, 0, 0),
+            Time(false, 0, 0, 60, 0, 0),
+            Time(false, 0, 0, 0, 60, 0),
+            Time(false, 0, 0, 0, 0, 1_000_000),
+            Date(2023, 1, 1, 0, 0, 0, 0),
+        ] {
+            invalid(time2_to_binary, value);
+        }
+        for value in [
+            Date(10000, 1, 1, 0, 0, 0, 0),
+            Date(2023, 13, 1, 0, 0, 0, 0),
+            Date(2023, 1, 32, 0, 0, 0, 0),
+            Date(2023, 1, 1, 24, 0, 0, 0),
+            Date(2023, 1, 1, 0, 60, 0, 0),
+            Date(2023, 1, 1, 0, 0, 60, 0),
+            Date(2023, 1, 1, 0, 0, 0, 1_000_000),
+        ] {
+            invalid(datetime2_to_binary, value);
+        }
+        for value in [
+            // year 0
+            Date(0, 1, 1, 0, 0, 0, 0),
+            Date(0, 0, 0, 0, 0, 1, 0),
+            // pre-epoch
+            Date(1969, 12, 31, 23, 59, 59, 0),
+            Date(1000, 1, 1, 0, 0, 0, 0),
+            Date(1970, 1, 1, 0, 0, 0, 0),
+            // after 2038-01-19 03:14:07
+            Date(2038, 1, 19, 3, 14, 8, 0),
+            Date(9999, 12, 31, 0, 0, 0, 0),
+            // out-of-range components
+            Date(2023, 13, 45, 0, 0, 0, 0),
+            Date(2023, 0, 1, 0, 0, 0, 0),
+            Date(2023, 2, 29, 0, 0, 0, 0),
+            Date(2023, 4, 31, 0, 0, 0, 0),
+            Date(2023, 1, 1, 24, 0, 0, 0),
+            Date(2023, 1, 1, 0, 60, 0, 0),
+            Date(2023, 1, 1, 0, 0, 60, 0),
+            Date(2023, 1, 1, 0, 0, 0, 1_000_000),
+            Int(-1),
+            Int(i32::MAX as i64 + 1),
+        ] {
+            invalid(timestamp2_to_binary, value);
+        }
+    }
+
+    #[test]
+    fn limited_write_policy() {
+        let mut output = Vec::new();
+        output
+            .limit(S(4), TruncationPolicy::Truncate)
+            .write_all(b"foobar")
+            .unwrap();
+        assert_eq!(output, b"foob");
+
+        let mut output = Vec::new();
+        let mut limited = output.limit(S(4), TruncationPolicy::Error);
+        limited.write_all(b"foo").unwrap();
+        let err = limited.write_all(b"bar").unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidInput);
+        limited.write_all(b"b").unwrap();
+        assert_eq!(output, b"foob");
+    }
+}
@@ -15,43 +15,88 @@
 // #![cfg(features = "binlog")]
 
 use std::{
-    collections::HashMap,
+    collections::{HashMap, HashSet},
     convert::TryFrom,
     hash::Hash,
     io::{
         self, Error,
-        ErrorKind::{InvalidData, UnexpectedEof},
-        Read, Write,
+        ErrorKind::{InvalidData, InvalidInput, UnexpectedEof},
+        Read, Seek, SeekFrom, Write,
     },
 };
 
 use crate::{
     constants::ColumnType,
+    io::ParseBuf,
     proto::{MyDeserialize, MySerialize},
 };
 
 use self::{
-    consts::{BinlogVersion, EventType},
-    events::{Event, FormatDescriptionEvent, TableMapEvent},
+    consts::{BinlogVersion, EventFlags, EventType, RowsEventFlags},
+    events::{
+        BinlogEventHeader, Event, FdeFooterMode, FormatDescriptionEvent, GtidEvent, RotateEvent,
+        TableMapEvent,
+    },
+    pool::{BufferPool, EventBufferPool},
 };
 
+#[cfg(feature = "futures")]
+#[cfg_attr(docsrs, doc(cfg(feature = "futures")))]
+pub mod async_reader;
+#[cfg(feature = "futures")]
+#[cfg_attr(docsrs, doc(cfg(feature = "futures")))]
+pub mod channel;
+pub mod clock;
 pub mod consts;
 pub mod decimal;
 pub mod events;
+pub mod export;
+pub mod filter;
+pub mod gtid_checker;
+pub mod gtid_generator;
+pub mod gtid_locator;
 pub mod jsonb;
 pub mod jsondiff;
+pub mod load_file;
+pub mod mariadb;
+pub mod merge;
 pub mod misc;
+pub mod network;
+pub mod pool;
+pub mod projection;
 pub mod row;
+pub mod semi_sync;
+pub mod split;
+pub mod storage;
+pub mod streaming;
+pub mod tailer;
+pub mod transform;
+pub mod tx_analyzer;
 pub mod value;
+pub mod verify;
+pub mod xa;
 
 pub struct BinlogCtx<'a> {
     pub event_size: usize,
     pub fde: &'a FormatDescriptionEvent<'a>,
+    /// Maximum length of a query text retained by parsed query events
+    /// (`None` means unlimited, see [`events::QueryEvent::query_len`]).
+    pub max_query_len: Option<usize>,
 }
 
 impl<'a> BinlogCtx<'a> {
     pub fn new(event_size: usize, fde: &'a FormatDescriptionEvent<'a>) -> Self {
-        Self { event_size, fde }
+        Self {
+            event_size,
+            fde,
+            max_query_len: None,
+        }
+    }
+
+    /// Returns modified `self` with the given maximum length of a retained query text.
+    pub fn with_max_query_len(mut self, max_query_len: Option<usize>) -> Self {
+        self.max_query_len = max_query_len;
+        self
     }
 }
 
@@ -98,14 +143,67 @@ impl BinlogFileHeader {
     }
 }
 
+/// Defines how [`EventStreamReader`] handles MariaDB service events
+/// ([`EventType::MARIADB_BINLOG_CHECKPOINT_EVENT`] and
+/// [`EventType::MARIADB_START_ENCRYPTION_EVENT`]).
+///
+/// These events carry no data changes, so a consumer may safely ignore them:
+///
+/// *   binlog checkpoint only names the oldest binlog file needed for crash recovery;
+/// *   start encryption only describes encryption of the binlog file. Replication
+///     streams carry decrypted events, but events that follow it in an encrypted binlog
+///     file can't be decoded by this crate.
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum MariadbEventPolicy {
+    /// Return such events to the caller (the default).
+    #[default]
+    Pass,
+    /// Skip such events (the stream position is still tracked).
+    Skip,
+}
+
+/// Defines how [`EventStreamReader`] handles flags that a server sets on events it didn't
+/// originate ([`EventFlags::LOG_EVENT_ARTIFICIAL_F`] and [`EventFlags::LOG_EVENT_RELAY_LOG_F`]).
+///
+/// A relay or a proxy that re-emits events as if they came from the source may want
+/// to clear them, whereas a consumer of a relay log may need them to make forwarding decisions
+/// (see [`Event::is_artificial`] and [`Event::is_relay_log`]).
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum ArtificialFlagsPolicy {
+    /// Return events with flags as they were read (the default).
+    #[default]
+    Preserve,
+    /// Clear both flags (the checksum is recalculated). Other flags are left as is,
+    /// except for unknown bits, that are dropped from the affected events.
+    Clear,
+}
+
 /// Reader for binlog events.
 ///
-/// It'll maintain actual fde and table map, and can be used
+/// It'll maintain actual fde, table map and binlog position, and can be used
 /// to read binlog files and binlog event streams from server.
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct EventStreamReader {
     fde: FormatDescriptionEvent<'static>,
     table_map: HashMap<u64, TableMapEvent<'static>>,
+    /// Whether the table map cache should be cleared before the next event.
+    table_map_expired: bool,
+    log_file: Option<String>,
+    log_pos: u64,
+    /// GTID (`(sid, gno)`) of the last GTID event.
+    last_gtid: Option<([u8; GtidEvent::ENCODED_SID_LENGTH], u64)>,
+    fde_footer: FdeFooterMode,
+    ignored_server_ids: HashSet<u32>,
+    rewrite_server_id: Option<u32>,
+    mariadb_event_policy: MariadbEventPolicy,
+    artificial_flags_policy: ArtificialFlagsPolicy,
+    max_query_len: Option<usize>,
+    max_event_size: Option<usize>,
+    /// Event types returned by this reader (`None` means all event types).
+    event_mask: Option<HashSet<u8>>,
+    /// Whether table map events must be parsed even if they aren't returned.
+    table_maps_needed: bool,
+    buffer_pool: BufferPool,
 }
 
 impl EventStreamReader {
@@ -114,7 +212,254 @@ impl EventStreamReader {
         Self {
             fde: FormatDescriptionEvent::new(version),
             table_map: Default::default(),
+            table_map_expired: false,
+            log_file: None,
+            log_pos: 0,
+            last_gtid: None,
+            fde_footer: FdeFooterMode::Auto,
+            ignored_server_ids: HashSet::new(),
+            rewrite_server_id: None,
+            mariadb_event_policy: MariadbEventPolicy::Pass,
+            artificial_flags_policy: ArtificialFlagsPolicy::Preserve,
+            max_query_len: None,
+            max_event_size: None,
+            event_mask: None,
+            table_maps_needed: true,
+            buffer_pool: BufferPool::default(),
+        }
+    }
+
+    /// Returns modified `self` with the given mode to determine the footer
+    /// of format description events (defaults to [`FdeFooterMode::Auto`]).
+    ///
+    /// Use it to override the detection for binlogs known to contain
+    /// (or to lack) the checksum algorithm description.
+    pub fn with_fde_footer(mut self, fde_footer: FdeFooterMode) -> Self {
+        self.fde_footer = fde_footer;
+        self
+    }
+
+    /// Returns the mode used to determine the footer of format description events.
+    pub fn fde_footer(&self) -> FdeFooterMode {
+        self.fde_footer
+    }
+
+    /// Returns modified `self` that will drop events originating from the given servers
+    /// (e.g. to prevent loops in circular replication).
+    ///
+    /// Format description and rotate events are never dropped because they define
+    /// the state of the stream.
+    pub fn with_ignored_server_ids(mut self, server_ids: impl IntoIterator<Item = u32>) -> Self {
+        self.ignored_server_ids = server_ids.into_iter().collect();
+        self
+    }
+
+    /// Returns ids of servers whose events are dropped.
+    pub fn ignored_server_ids(&self) -> &HashSet<u32> {
+        &self.ignored_server_ids
+    }
+
+    /// Returns modified `self` that will rewrite the server id in the header
+    /// of every returned event (see [`Event::with_server_id`]).
+    pub fn with_rewrite_server_id(mut self, server_id: Option<u32>) -> Self {
+        self.rewrite_server_id = server_id;
+        self
+    }
+
+    /// Returns the server id written into the header of every returned event (if any).
+    pub fn rewrite_server_id(&self) -> Option<u32> {
+        self.rewrite_server_id
+    }
+
+    /// Returns modified `self` with the given policy for MariaDB service events
+    /// (defaults to [`MariadbEventPolicy::Pass`]).
+    pub fn with_mariadb_event_policy(mut self, policy: MariadbEventPolicy) -> Self {
+        self.mariadb_event_policy = policy;
+        self
+    }
+
+    /// Returns the policy for MariaDB service events.
+    pub fn mariadb_event_policy(&self) -> MariadbEventPolicy {
+        self.mariadb_event_policy
+    }
+
+    /// Returns modified `self` with the given policy for artificial and relay-log flags
+    /// of returned events (defaults to [`ArtificialFlagsPolicy::Preserve`]).
+    pub fn with_artificial_flags_policy(mut self, policy: ArtificialFlagsPolicy) -> Self {
+        self.artificial_flags_policy = policy;
+        self
+    }
+
+    /// Returns the policy for artificial and relay-log flags.
+    pub fn artificial_flags_policy(&self) -> ArtificialFlagsPolicy {
+        self.artificial_flags_policy
+    }
+
+    /// Returns modified `self` that will limit the query text retained by query events
+    /// parsed from returned events (see [`Event::with_max_query_len`]).
+    ///
+    /// Defaults to `None` (the full query is retained).
+    pub fn with_max_query_len(mut self, max_query_len: Option<usize>) -> Self {
+        self.max_query_len = max_query_len;
+        self
+    }
+
+    /// Returns the maximum length of a query text retained by query events.
+    pub fn max_query_len(&self) -> Option<usize> {
+        self.max_query_len
+    }
+
+    /// Returns modified `self` that will refuse events larger than `max_event_size` bytes
+    /// (with an `InvalidData` error) before allocating a buffer for them.
+    ///
+    /// Defaults to `None` (no limit). Use it to read untrusted input.
+    pub fn with_max_event_size(mut self, max_event_size: Option<usize>) -> Self {
+        self.max_event_size = max_event_size;
+        self
+    }
+
+    /// Returns the maximum size of an event.
+    pub fn max_event_size(&self) -> Option<usize> {
+        self.max_event_size
+    }
+
+    /// Checks the size of an event with the given header
+    /// (see [`EventStreamReader::with_max_event_size`]).
+    pub(crate) fn check_event_size(&self, header: &BinlogEventHeader) -> io::Result<()> {
+        let event_size = header.event_size() as usize;
+        if event_size < BinlogEventHeader::LEN {
+            return Err(Error::new(
+                InvalidData,
+                "event size is less than the header size",
+            ));
+        }
+        match self.max_event_size {
+            Some(max_event_size) if event_size > max_event_size => Err(Error::new(
+                InvalidData,
+                format!(
+                    "event size {} exceeds the limit of {} bytes",
+                    event_size, max_event_size
+                ),
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// Returns modified `self` that reads event data into buffers acquired from the given pool
+    /// (defaults to [`pool::GlobalBufferPool`]).
+    ///
+    /// Buffers are given back by [`EventStreamReader::recycle`].
+    pub fn with_buffer_pool(mut self, buffer_pool: BufferPool) -> Self {
+        self.buffer_pool = buffer_pool;
+        self
+    }
+
+    /// Returns the pool of event data buffers.
+    pub fn buffer_pool(&self) -> &BufferPool {
+        &self.buffer_pool
+    }
+
+    /// Gives the data buffer of the given event (that is no longer needed)
+    /// back to the buffer pool.
+    pub fn recycle(&self, event: Event<'_>) {
+        self.buffer_pool.release(event.into_raw_data());
+    }
+
+    /// Defines event types returned by this reader (an empty slice means all event types).
+    ///
+    /// Bodies of other events are consumed without being parsed (or even read into memory
+    /// by the blocking reader), only their headers are used to track the binlog position.
+    /// Format description and rotate events are always parsed, because they define the state
+    /// of the stream, and so are table map events if rows events are returned.
+    pub fn set_event_mask(&mut self, event_types: &[EventType]) {
+        if event_types.is_empty() {
+            self.event_mask = None;
+            self.table_maps_needed = true;
+            return;
+        }
+        let mask = event_types.iter().map(|x| *x as u8).collect::<HashSet<_>>();
+        self.table_maps_needed = event_types.iter().any(|x| {
+            matches!(
+                x,
+                EventType::WRITE_ROWS_EVENT_V1
+                    | EventType::UPDATE_ROWS_EVENT_V1
+                    | EventType::DELETE_ROWS_EVENT_V1
+                    | EventType::WRITE_ROWS_EVENT
+                    | EventType::UPDATE_ROWS_EVENT
+                    | EventType::DELETE_ROWS_EVENT
+                    | EventType::PARTIAL_UPDATE_ROWS_EVENT
+                    | EventType::MARIADB_WRITE_ROWS_COMPRESSED_EVENT_V1
+                    | EventType::MARIADB_UPDATE_ROWS_COMPRESSED_EVENT_V1
+                    | EventType::MARIADB_DELETE_ROWS_COMPRESSED_EVENT_V1
+                    | EventType::MARIADB_WRITE_ROWS_COMPRESSED_EVENT
+                    | EventType::MARIADB_UPDATE_ROWS_COMPRESSED_EVENT
+                    | EventType::MARIADB_DELETE_ROWS_COMPRESSED_EVENT
+            )
+        });
+        self.event_mask = Some(mask);
+    }
+
+    /// Returns event types returned by this reader (`None` means all event types).
+    pub fn event_mask(&self) -> Option<&HashSet<u8>> {
+        self.event_mask.as_ref()
+    }
+
+    /// Returns `true` if events of the given type are returned by this reader.
+    fn is_wanted(&self, event_type: u8) -> bool {
+        match self.event_mask {
+            Some(ref mask) => mask.contains(&event_type),
+            None => true,
+        }
+    }
+
+    /// Returns `true` if the body of an event with the given header must be skipped
+    /// (see [`EventStreamReader::set_event_mask`]).
+    ///
+    /// The binlog position is updated for skipped events.
+    pub(crate) fn skip_body(&mut self, header: &BinlogEventHeader) -> bool {
+        let event_type = header.event_type_raw();
+        let needs_body = self.is_wanted(event_type)
+            || event_type == EventType::FORMAT_DESCRIPTION_EVENT as u8
+            || event_type == EventType::ROTATE_EVENT as u8
+            || (event_type == EventType::TABLE_MAP_EVENT as u8 && self.table_maps_needed);
+        if needs_body {
+            return false;
+        }
+        if header.log_pos() != 0 {
+            self.log_pos = header.log_pos() as u64;
         }
+        true
+    }
+
+    /// Returns the name of the current binlog file.
+    ///
+    /// It is known only after a rotate event (either an artificial one at the beginning
+    /// of a binlog stream or the one at the end of a binlog file).
+    pub fn log_file(&self) -> Option<&str> {
+        self.log_file.as_deref()
+    }
+
+    /// Returns the position of the next event in the current binlog file
+    /// (`0` if unknown).
+    pub fn log_pos(&self) -> u64 {
+        self.log_pos
+    }
+
+    /// Returns the GTID (`(sid, gno)`) of the last read GTID event, i.e. the GTID
+    /// of the current transaction (`None` after an anonymous GTID event or if there was
+    /// no GTID event yet).
+    ///
+    /// Use it to link XID events to their transactions (see [`events::XidEvent::with_gtid`]).
+    pub fn last_gtid(&self) -> Option<([u8; GtidEvent::ENCODED_SID_LENGTH], u64)> {
+        self.last_gtid
+    }
+
+    /// Applies the given rotate event to the tracked binlog position.
+    pub fn apply_rotate(&mut self, rotate: &RotateEvent<'_>) -> io::Result<()> {
+        let (log_file, log_pos) = rotate.next_file()?;
+        self.log_file = Some(log_file);
+        self.log_pos = log_pos;
+        Ok(())
     }
 
     /// Returns the format description event.
@@ -131,17 +476,122 @@ impl EventStreamReader {
         self.table_map.get(&table_id)
     }
 
+    /// Returns the latest table map event for the given table id.
+    ///
+    /// Table map events are cached until the end of a statement, i.e. the cache is cleared
+    /// before the event that follows a rows event with the `STMT_END` flag, and on rotate.
+    /// So the table map event of the last read rows event is always available.
+    pub fn table_map(&self, table_id: u64) -> Option<&TableMapEvent<'static>> {
+        self.get_tme(table_id)
+    }
+
     /// Will read next event from the given stream.
-    pub fn read<T: Read>(&mut self, input: T) -> io::Result<Event> {
-        let event = Event::read(&self.fde, input)?;
+    ///
+    /// Events from ignored servers are skipped (see [`EventStreamReader::with_ignored_server_ids`]),
+    /// as well as MariaDB service events if requested
+    /// (see [`EventStreamReader::with_mariadb_event_policy`]) and events excluded
+    /// by the event mask (see [`EventStreamReader::set_event_mask`]).
+    pub fn read<T: Read>(&mut self, mut input: T) -> io::Result<Event<'static>> {
+        loop {
+            let header = self.read_header(&mut input)?;
+            let event = self.read_body(header, &mut input)?;
+            if let Some(event) = self.accept(event) {
+                return Ok(event);
+            }
+        }
+    }
+
+    /// Reads the header of the next event whose body isn't skipped
+    /// (see [`EventStreamReader::set_event_mask`]).
+    fn read_header<T: Read>(&mut self, mut input: T) -> io::Result<BinlogEventHeader> {
+        loop {
+            let mut header_buf = [0_u8; BinlogEventHeader::LEN];
+            input.read_exact(&mut header_buf)?;
+            let header = BinlogEventHeader::deserialize((), &mut ParseBuf(&header_buf))?;
+            self.check_event_size(&header)?;
+
+            if self.skip_body(&header) {
+                let body_len =
+                    (header.event_size() as u64).saturating_sub(BinlogEventHeader::LEN as u64);
+                let skipped = io::copy(&mut (&mut input).take(body_len), &mut io::sink())?;
+                if skipped < body_len {
+                    return Err(Error::new(UnexpectedEof, "truncated event body"));
+                }
+                continue;
+            }
+
+            return Ok(header);
+        }
+    }
+
+    /// Applies skipping and rewriting rules of this reader to the given event.
+    ///
+    /// Returns `None` if the event should be skipped.
+    fn accept(&self, event: Event<'static>) -> Option<Event<'static>> {
         let event_type = event.header().event_type_raw();
 
+        if !self.is_wanted(event_type) {
+            return None;
+        }
+
+        if self.mariadb_event_policy == MariadbEventPolicy::Skip
+            && (event_type == EventType::MARIADB_BINLOG_CHECKPOINT_EVENT as u8
+                || event_type == EventType::MARIADB_START_ENCRYPTION_EVENT as u8)
+        {
+            return None;
+        }
+
+        if self
+            .ignored_server_ids
+            .contains(&event.header().server_id())
+            && event_type != EventType::FORMAT_DESCRIPTION_EVENT as u8
+            && event_type != EventType::ROTATE_EVENT as u8
+        {
+            return None;
+        }
+
+        let mut event = event.with_max_query_len(self.max_query_len);
+        if let Some(server_id) = self.rewrite_server_id {
+            event = event.with_server_id(server_id);
+        }
+
+        let artificial_flags =
+            EventFlags::LOG_EVENT_ARTIFICIAL_F | EventFlags::LOG_EVENT_RELAY_LOG_F;
+        if self.artificial_flags_policy == ArtificialFlagsPolicy::Clear
+            && event.header().flags().intersects(artificial_flags)
+        {
+            let flags = event.header().flags() - artificial_flags;
+            event = event.with_flags(flags);
+        }
+
+        Some(event)
+    }
+
+    /// Reads the body of an event with the given header and updates the state of this reader.
+    pub(crate) fn read_body<T: Read>(
+        &mut self,
+        header: BinlogEventHeader,
+        input: T,
+    ) -> io::Result<Event<'static>> {
+        let event = Event::read_body(&self.fde, header, input, self.fde_footer, &self.buffer_pool)?;
+        let event_type = event.header().event_type_raw();
+
+        if self.table_map_expired {
+            self.table_map.clear();
+            self.table_map_expired = false;
+        }
+
         if event_type == EventType::FORMAT_DESCRIPTION_EVENT as u8 {
             // we'll redefine fde with an actual one
             self.fde = match event.read_event::<FormatDescriptionEvent>() {
                 Ok(fde) => fde.into_owned().with_footer(event.footer()),
                 Err(err) => return Err(err),
             };
+        } else if event_type == EventType::GTID_EVENT as u8 {
+            let gtid = event.read_event::<GtidEvent>()?;
+            self.last_gtid = Some((gtid.sid(), gtid.gno()));
+        } else if event_type == EventType::ANONYMOUS_GTID_EVENT as u8 {
+            self.last_gtid = None;
         } else if event_type == EventType::TABLE_MAP_EVENT as u8 {
             // we'll maintain known table maps
             match event.read_event::<TableMapEvent>() {
@@ -150,10 +600,56 @@ impl EventStreamReader {
                 }
                 Err(err) => return Err(err),
             }
+        } else if let Ok(
+            EventType::WRITE_ROWS_EVENT_V1
+            | EventType::UPDATE_ROWS_EVENT_V1
+            | EventType::DELETE_ROWS_EVENT_V1
+            | EventType::WRITE_ROWS_EVENT
+            | EventType::UPDATE_ROWS_EVENT
+            | EventType::DELETE_ROWS_EVENT
+            | EventType::PARTIAL_UPDATE_ROWS_EVENT
+            | EventType::MARIADB_WRITE_ROWS_COMPRESSED_EVENT_V1
+            | EventType::MARIADB_UPDATE_ROWS_COMPRESSED_EVENT_V1
+            | EventType::MARIADB_DELETE_ROWS_COMPRESSED_EVENT_V1
+            | EventType::MARIADB_WRITE_ROWS_COMPRESSED_EVENT
+            | EventType::MARIADB_UPDATE_ROWS_COMPRESSED_EVENT
+            | EventType::MARIADB_DELETE_ROWS_COMPRESSED_EVENT,
+        ) = event.header().event_type()
+        {
+            // table maps are valid until the end of the statement
+            self.table_map_expired = self
+                .rows_event_flags(&event)?
+                .contains(RowsEventFlags::STMT_END);
+        }
+
+        if event_type == EventType::ROTATE_EVENT as u8 {
+            self.table_map.clear();
+            // both artificial and real rotate events define the position to continue from
+            self.apply_rotate(&event.read_event::<RotateEvent>()?)?;
+        } else if event.header().log_pos() != 0 {
+            self.log_pos = event.header().log_pos() as u64;
         }
 
         Ok(event)
     }
+
+    /// Reads flags of a rows event from its post-header (the rest of the event isn't parsed).
+    fn rows_event_flags(&self, event: &Event<'_>) -> io::Result<RowsEventFlags> {
+        let post_header_len = event
+            .header()
+            .event_type()
+            .map(|event_type| self.fde.get_event_type_header_length(event_type))
+            .unwrap_or_default();
+        // table id is 4 bytes long for old servers
+        let offset = if post_header_len == 6 { 4 } else { 6 };
+        let flags = event
+            .data()
+            .get(offset..offset + 2)
+            .ok_or_else(|| Error::new(UnexpectedEof, "rows event post-header is truncated"))?;
+        Ok(RowsEventFlags::from_bits_truncate(u16::from_le_bytes([
+            flags[0], flags[1],
+        ])))
+    }
 }
 
 /// Binlog file.
@@ -163,29 +659,159 @@ impl EventStreamReader {
 pub struct BinlogFile<T> {
     reader: EventStreamReader,
     read: T,
+    pos: u64,
 }
 
 impl<T: Read> BinlogFile<T> {
     /// Creates a new instance.
     ///
     /// It'll try to read binlog file header.
-    pub fn new(version: BinlogVersion, mut read: T) -> io::Result<Self> {
-        let reader = EventStreamReader::new(version);
+    pub fn new(version: BinlogVersion, read: T) -> io::Result<Self> {
+        Self::with_reader(EventStreamReader::new(version), read)
+    }
+
+    /// Creates a new instance using the given binlog stream reader.
+    ///
+    /// It'll try to read binlog file header.
+    pub fn with_reader(reader: EventStreamReader, mut read: T) -> io::Result<Self> {
         BinlogFileHeader::read(&mut read)?;
-        Ok(Self { reader, read })
+        Ok(Self {
+            reader,
+            read,
+            pos: BinlogFileHeader::LEN as u64,
+        })
+    }
+
+    /// Returns modified `self` with the given mode to determine the footer
+    /// of format description events (see [`EventStreamReader::with_fde_footer`]).
+    pub fn with_fde_footer(mut self, fde_footer: FdeFooterMode) -> Self {
+        self.reader = self.reader.with_fde_footer(fde_footer);
+        self
     }
 
     /// Returns a reference to the binlog stream reader.
     pub fn reader(&self) -> &EventStreamReader {
         &self.reader
     }
+
+    /// Returns the offset of the next event within this file.
+    ///
+    /// It's the number of bytes consumed so far (the binlog file header included),
+    /// so it's only meaningful if the underlying reader started at the beginning of the file.
+    pub fn position(&self) -> u64 {
+        self.pos
+    }
+
+    /// Reads the next event keeping track of the position.
+    fn read_event(&mut self) -> io::Result<Event<'static>> {
+        let read = TrackPos {
+            read: &mut self.read,
+            pos: &mut self.pos,
+        };
+        self.reader.read(read)
+    }
+}
+
+impl<T: Read + Seek> BinlogFile<T> {
+    /// Creates a new instance that starts at the given position
+    /// (like `mysqlbinlog --start-position`).
+    ///
+    /// It'll try to read binlog file header. Format description event is read first
+    /// (see [`EventStreamReader::get_fde`]), even if the position is past it, and then
+    /// the reader seeks to the given position. Positions within the binlog file header
+    /// are treated as the position of the first event.
+    ///
+    /// Returns an `InvalidInput` error if the position is within the format description event.
+    pub fn with_start_pos(version: BinlogVersion, read: T, pos: u64) -> io::Result<Self> {
+        let mut this = Self::new(version, read)?;
+        if pos > this.pos {
+            this.read_event()?;
+            if this.pos > pos {
+                return Err(Error::new(
+                    InvalidInput,
+                    format!("position {} is within the format description event", pos),
+                ));
+            }
+            this.read.seek(SeekFrom::Current((pos - this.pos) as i64))?;
+            this.pos = pos;
+        }
+        Ok(this)
+    }
+
+    /// Returns an iterator over the remaining events of this file that only reads event headers.
+    ///
+    /// It yields `(header, body_offset, body_len)`, where `body_offset` is the offset
+    /// of the event body (i.e. data following the header, checksum included) within
+    /// the underlying reader, and `body_len` is its length. Bodies are skipped using
+    /// [`Seek`], so they are never read nor copied. For memory-mapped files
+    /// use [`io::Cursor`] over the mapped bytes.
+    ///
+    /// It's meant for tools that only index positions and timestamps, so the state
+    /// of the [`EventStreamReader`] isn't updated, and a truncated last event
+    /// isn't detected.
+    pub fn raw_events(&mut self) -> RawEvents<'_, T> {
+        RawEvents {
+            read: &mut self.read,
+            pos: &mut self.pos,
+        }
+    }
 }
 
 impl<T: Read> Iterator for BinlogFile<T> {
-    type Item = io::Result<Event>;
+    type Item = io::Result<Event<'static>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.read_event() {
+            Ok(event) => Some(Ok(event)),
+            Err(err) if err.kind() == UnexpectedEof => None,
+            Err(err) => Some(Err(err)),
+        }
+    }
+}
+
+/// Reader that counts consumed bytes (see [`BinlogFile::position`]).
+struct TrackPos<'a, T> {
+    read: &'a mut T,
+    pos: &'a mut u64,
+}
+
+impl<T: Read> Read for TrackPos<'_, T> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let len = self.read.read(buf)?;
+        *self.pos += len as u64;
+        Ok(len)
+    }
+}
+
+/// Iterator over event headers of a binlog file (see [`BinlogFile::raw_events`]).
+#[derive(Debug)]
+pub struct RawEvents<'a, T> {
+    read: &'a mut T,
+    pos: &'a mut u64,
+}
+
+impl<T: Read + Seek> RawEvents<'_, T> {
+    fn read_next(&mut self) -> io::Result<(BinlogEventHeader, u64, usize)> {
+        let mut header_buf = [0_u8; BinlogEventHeader::LEN];
+        self.read.read_exact(&mut header_buf)?;
+        let header = BinlogEventHeader::deserialize((), &mut ParseBuf(&header_buf))?;
+
+        let body_len = (header.event_size() as usize)
+            .checked_sub(BinlogEventHeader::LEN)
+            .ok_or_else(|| Error::new(InvalidData, "event size is less than the header size"))?;
+        let body_offset = self.read.stream_position()?;
+        self.read.seek(SeekFrom::Current(body_len as i64))?;
+        *self.pos += header.event_size() as u64;
+
+        Ok((header, body_offset, body_len))
+    }
+}
+
+impl<T: Read + Seek> Iterator for RawEvents<'_, T> {
+    type Item = io::Result<(BinlogEventHeader, u64, usize)>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        match self.reader.read(&mut self.read) {
+        match self.read_next() {
             Ok(event) => Some(Ok(event)),
             Err(err) if err.kind() == UnexpectedEof => None,
             Err(err) => Some(Err(err)),
@@ -234,21 +860,46 @@ impl ColumnType {
 
 #[cfg(test)]
 mod tests {
+    use byteorder::{ByteOrder, LittleEndian};
+
     use std::{
+        cmp::min,
         collections::HashMap,
+        convert::TryFrom,
         io,
         iter::{once, repeat},
     };
 
     use super::{
-        consts::{EventFlags, EventType},
-        events::{BinlogEventHeader, EventData, GtidEvent},
-        BinlogFile, BinlogFileHeader, BinlogVersion,
+        consts::{
+            BinlogChecksumAlg, EventFlags, EventType, LoadDuplicateHandling, LoadOptFlags,
+            MariadbGtidFlags, RowsEventFlags,
+        },
+        events::{
+            BinlogEventFooter, BinlogEventHeader, Event, EventData, EventVisitor,
+            ExecuteLoadQueryEvent, FdeFooterMode, FormatDescriptionEvent, GtidEvent,
+            HeartbeatEvent, IgnorableEvent, IntvarEvent, LazyEvent, LoadEvent, NewLoadEvent,
+            OptionalMetaExtractor, PreviousGtidsEvent, QueryEvent, RandEvent, RotateEvent,
+            ServerVersion, SessionSnapshot, SqlExchange, StatementContextBuilder, StatusVars,
+            StopEvent, TableMapEvent, TransactionContextEvent, UserVarEvent, ViewChangeEvent,
+            XaPrepareEvent, XidEvent,
+        },
+        mariadb::MariadbXid,
+        projection::Projection,
+        xa::{XaStatement, XaTransactionId},
+        ArtificialFlagsPolicy, BinlogCtx, BinlogFile, BinlogFileHeader, BinlogStruct,
+        BinlogVersion, EventStreamReader, MariadbEventPolicy,
     };
 
     use crate::{
-        binlog::{events::RowsEventData, value::BinlogValue},
-        constants::ColumnFlags,
+        binlog::{
+            events::{DeleteRowsEvent, RowsEventData, UpdateRowsEvent, WriteRowsEvent},
+            row::GeneratedColumn,
+            value::BinlogValue,
+        },
+        constants::{ColumnFlags, ColumnType, ItemResult, SqlMode},
+        io::ParseBuf,
+        packets::{column_ddl::ColumnDdl, GtidSet},
         proto::MySerialize,
         value::Value,
     };
@@ -631,94 +1282,2234 @@ mod tests {
     }
 
     #[test]
-    fn binlog_event_roundtrip() -> io::Result<()> {
-        const PATH: &str = "./test-data/binlogs";
+    fn binlog_file_rotate() -> io::Result<()> {
+        let mut binlog_file = BinlogFile::new(BinlogVersion::Version4, BINLOG_FILE)?;
+        assert_eq!(binlog_file.reader().log_file(), None);
 
-        let binlogs = std::fs::read_dir(PATH)?
-            .filter_map(|path| path.ok())
-            .map(|entry| entry.path())
-            .filter(|path| path.file_name().is_some());
+        let mut last = None;
+        while let Some(ev) = binlog_file.next() {
+            let ev = ev?;
+            if ev.header().event_type_raw() != EventType::ROTATE_EVENT as u8 {
+                assert_eq!(binlog_file.reader().log_pos(), ev.header().log_pos() as u64);
+            }
+            last = Some(ev);
+        }
+        assert!(!last.unwrap().is_artificial_rotate());
+        assert_eq!(binlog_file.reader().log_file(), Some("master-bin.000002"));
+        assert_eq!(binlog_file.reader().log_pos(), 4);
+
+        // relay log starts with an artificial rotate event
+        let file_data = std::fs::read("./test-data/binlogs/bug33029-slave-relay-bin.000001")?;
+        let mut binlog_file = BinlogFile::new(BinlogVersion::Version4, &file_data[..])?;
+        let fde = binlog_file.next().unwrap()?;
+        assert!(!fde.is_artificial_rotate());
+        let rotate = binlog_file.next().unwrap()?;
+        assert!(rotate.is_artificial_rotate());
+        assert_eq!(
+            rotate.read_event::<RotateEvent>()?.next_file()?,
+            ("master-bin.000001".into(), 4)
+        );
+        assert_eq!(binlog_file.reader().log_file(), Some("master-bin.000001"));
+
+        assert!(RotateEvent::new(4, &b""[..]).next_file().is_err());
+        assert!(RotateEvent::new(4, &b"../foo"[..]).next_file().is_err());
+        assert_eq!(
+            RotateEvent::new(0, &b"foo.000001"[..]).next_file()?,
+            ("foo.000001".into(), 4)
+        );
 
-        'outer: for file_path in binlogs {
-            let file_data = std::fs::read(dbg!(&file_path))?;
-            let mut binlog_file = BinlogFile::new(BinlogVersion::Version4, &file_data[..])?;
+        Ok(())
+    }
 
-            let mut ev_pos = 4;
-            let mut table_map_events = HashMap::new();
+    #[test]
+    fn fde_footer_detection() -> io::Result<()> {
+        // 5.6.4 binlog with `binlog_checksum=NONE`
+        let file_data = std::fs::read("./test-data/binlogs/ver_trunk_row_v2.001")?;
+
+        // same binlog, but the fde lacks the checksum algorithm description and the checksum
+        let fde_size = LittleEndian::read_u32(&file_data[13..17]) as usize;
+        let mut stripped = file_data[..4 + fde_size - 5].to_vec();
+        stripped[13..17].copy_from_slice(&(fde_size as u32 - 5).to_le_bytes());
+        stripped.extend_from_slice(&file_data[4 + fde_size..]);
+
+        let original = BinlogFile::new(BinlogVersion::Version4, &file_data[..])?;
+        let binlog_file = BinlogFile::new(BinlogVersion::Version4, &stripped[..])?;
+        let mut events = 0;
+        for (expected, ev) in original.zip(binlog_file) {
+            let (expected, ev) = (expected?, ev?);
+            if events == 0 {
+                assert_eq!(
+                    expected.footer().get_checksum_alg(),
+                    Ok(Some(BinlogChecksumAlg::BINLOG_CHECKSUM_ALG_OFF))
+                );
+                assert_eq!(ev.footer().get_checksum_alg(), Ok(None));
+            }
+            assert_eq!(ev.data(), expected.data());
+            ev.read_data()?;
+            events += 1;
+        }
+        assert!(events > 1);
 
-            while let Some(ev) = binlog_file.next() {
-                let ev = ev?;
-                let _ = dbg!(ev.header().event_type());
-                let ev_end = ev_pos + ev.header().event_size() as usize;
-                let binlog_version = binlog_file.reader.fde.binlog_version();
+        // explicit override
+        let mut binlog_file = BinlogFile::new(BinlogVersion::Version4, &file_data[..])?
+            .with_fde_footer(FdeFooterMode::Absent);
+        let fde = binlog_file.next().unwrap()?;
+        assert_eq!(fde.footer().get_checksum_alg(), Ok(None));
+        assert_eq!(fde.data().len(), fde_size - BinlogEventHeader::LEN);
 
-                let mut output = Vec::new();
-                ev.write(binlog_version, &mut output)?;
+        let mut binlog_file = BinlogFile::new(BinlogVersion::Version4, &stripped[..])?
+            .with_fde_footer(FdeFooterMode::Present);
+        let fde = binlog_file.next().unwrap()?;
+        assert!(fde.footer().get_checksum_alg().unwrap().is_some());
+        assert_eq!(fde.data().len(), fde_size - 5 - BinlogEventHeader::LEN - 5);
 
-                let event = match ev.read_data() {
-                    Ok(event) => {
-                        let event = match event {
-                            Some(e) => e,
-                            None => {
-                                if file_path.file_name().unwrap() == "mariadb-bin.000001" {
-                                    continue;
-                                } else {
-                                    dbg!(&ev);
-                                    panic!();
-                                }
-                            }
-                        };
-                        match event {
-                            EventData::TableMapEvent(ref ev) => {
-                                // store table maps for later use
-                                table_map_events.insert(ev.table_id(), ev.clone().into_owned());
+        Ok(())
+    }
 
-                                event
-                            }
-                            EventData::RowsEvent(ref rows_event) => {
-                                // iterate rows in a rows event
-                                let table_map_event =
-                                    binlog_file.reader().get_tme(rows_event.table_id()).unwrap();
-                                for row in rows_event.rows(table_map_event) {
-                                    let _row = row.unwrap();
-                                    if file_path.file_name().unwrap() == "mariadb-bin.000001" {
-                                        // should parse metadata for `binlog_row_metadata=FULL`
-                                        let after = _row.1.as_ref().unwrap();
-                                        let columns = after.columns_ref();
+    #[test]
+    fn server_id_filter_and_rewrite() -> io::Result<()> {
+        let file_data = std::fs::read("./test-data/binlogs/rpl_unfiltered_hidden_gcol.000001")?;
 
-                                        for col in columns.iter() {
-                                            assert_eq!(col.schema_ref(), b"toddy_test");
-                                            assert_eq!(col.table_ref(), b"outbox");
-                                            assert_eq!(col.org_table_ref(), b"outbox");
-                                        }
+        let reader = EventStreamReader::new(BinlogVersion::Version4).with_ignored_server_ids([1]);
+        let binlog_file = BinlogFile::with_reader(reader, &file_data[..])?;
+        let mut events = 0;
+        for ev in binlog_file {
+            let ev = ev?;
+            if ev.header().server_id() == 1 {
+                assert!(matches!(
+                    ev.header().event_type(),
+                    Ok(EventType::FORMAT_DESCRIPTION_EVENT) | Ok(EventType::ROTATE_EVENT)
+                ));
+            } else {
+                events += 1;
+            }
+        }
+        assert!(events > 0);
 
-                                        for (col, col_name) in columns.iter().zip([
-                                            "id",
-                                            "topic",
-                                            "event_type",
-                                            "event",
-                                            "created",
-                                        ]) {
-                                            assert_eq!(col.name_ref(), col_name.as_bytes());
-                                        }
+        let reader =
+            EventStreamReader::new(BinlogVersion::Version4).with_rewrite_server_id(Some(42));
+        let binlog_file = BinlogFile::with_reader(reader, &file_data[..])?;
+        for ev in binlog_file {
+            let ev = ev?;
+            assert_eq!(ev.header().server_id(), 42);
+
+            // checksum must be recalculated
+            let mut raw = Vec::new();
+            ev.write_raw(&mut raw)?;
+            let mut output = Vec::new();
+            ev.checked_write(BinlogVersion::Version4, &mut output)?;
+            assert_eq!(raw, output);
+        }
 
-                                        for (col, f) in columns.iter().zip(
-                                            once(ColumnFlags::PRI_KEY_FLAG)
-                                                .chain(repeat(ColumnFlags::empty())),
-                                        ) {
-                                            assert_eq!(col.flags(), f);
-                                        }
+        Ok(())
+    }
 
-                                        for (col, charset) in columns.iter().zip([0, 45, 45, 63, 0])
-                                        {
-                                            assert_eq!(col.character_set(), charset);
-                                        }
-                                    }
-                                }
+    #[test]
+    fn mariadb_events() -> io::Result<()> {
+        let file_data = std::fs::read("./test-data/binlogs/mariadb-bin.000001")?;
+
+        let binlog_file = BinlogFile::new(BinlogVersion::Version4, &file_data[..])?;
+        let mut gtids = Vec::new();
+        let mut annotations = 0;
+        let mut gtid_lists = 0;
+        for ev in binlog_file {
+            let ev = ev?;
+            let data = ev.read_data()?.expect("known event type");
 
-                                event
-                            }
-                            _ => event,
+            let mut serialized = Vec::new();
+            data.serialize(&mut serialized);
+            assert_eq!(serialized, ev.data());
+
+            match data {
+                EventData::MariadbGtidListEvent(ev) => {
+                    assert!(ev.list().is_empty());
+                    gtid_lists += 1;
+                }
+                EventData::MariadbGtidEvent(gtid) => {
+                    assert_eq!(gtid.domain_id(), 0);
+                    assert_eq!(gtid.commit_id(), None);
+                    assert_eq!(
+                        gtid.flags(),
+                        MariadbGtidFlags::TRANSACTIONAL | MariadbGtidFlags::ALLOW_PARALLEL
+                    );
+                    gtids.push(gtid.gtid(ev.header().server_id()).to_string());
+                }
+                EventData::MariadbAnnotateRowsEvent(ev) => {
+                    assert!(ev.query().starts_with("insert into outbox"));
+                    annotations += 1;
+                }
+                _ => (),
+            }
+        }
+        assert_eq!(gtid_lists, 1);
+        assert_eq!(gtids, ["0-1-1", "0-1-2"]);
+        assert_eq!(annotations, 2);
+
+        Ok(())
+    }
+
+    #[test]
+    fn rows_event_v1() -> io::Result<()> {
+        let file_data = std::fs::read("./test-data/binlogs/mariadb-bin.000001")?;
+
+        let binlog_file = BinlogFile::new(BinlogVersion::Version4, &file_data[..])?;
+        let mut tme = None;
+        let mut rows = Vec::new();
+        for ev in binlog_file {
+            let ev = ev?;
+            match ev.read_data()? {
+                Some(EventData::TableMapEvent(ev)) => tme = Some(ev.into_owned()),
+                Some(EventData::RowsEvent(RowsEventData::WriteRowsEventV1(ev))) => {
+                    let tme = tme.as_ref().expect("table map event");
+                    assert_eq!(ev.table_id(), tme.table_id());
+                    for row in ev.rows(tme) {
+                        let (before, after) = row?;
+                        assert!(before.is_none());
+                        rows.push(after.expect("after-image").unwrap());
+                    }
+                }
+                Some(EventData::RowsEvent(ev)) => panic!("unexpected rows event {:?}", ev),
+                _ => (),
+            }
+        }
+        assert_eq!(rows.len(), 2);
+        for (row, id) in rows.iter().zip([62, 63]) {
+            assert_eq!(row[0], BinlogValue::Value(Value::Int(id)));
+            assert_eq!(row[1], BinlogValue::Value(Value::Bytes(b"foo".to_vec())));
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn mariadb_event_policy() -> io::Result<()> {
+        let file_data = std::fs::read("./test-data/binlogs/mariadb-bin.000001")?;
+
+        let binlog_file = BinlogFile::new(BinlogVersion::Version4, &file_data[..])?;
+        let mut checkpoints = 0;
+        for ev in binlog_file {
+            let ev = ev?;
+            if let Some(EventData::MariadbBinlogCheckpointEvent(ev)) = ev.read_data()? {
+                assert_eq!(ev.file_name(), "mariadb-bin.000001");
+                checkpoints += 1;
+            }
+        }
+        assert!(checkpoints > 0);
+
+        let reader = EventStreamReader::new(BinlogVersion::Version4)
+            .with_mariadb_event_policy(MariadbEventPolicy::Skip);
+        let binlog_file = BinlogFile::with_reader(reader, &file_data[..])?;
+        let mut events = 0;
+        for ev in binlog_file {
+            let ev = ev?;
+            assert_ne!(
+                ev.header().event_type(),
+                Ok(EventType::MARIADB_BINLOG_CHECKPOINT_EVENT)
+            );
+            events += 1;
+        }
+        assert!(events > 0);
+
+        assert_eq!(
+            EventType::try_from(0xa4),
+            Ok(EventType::MARIADB_START_ENCRYPTION_EVENT)
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn artificial_flags_policy() -> io::Result<()> {
+        let fde = FormatDescriptionEvent::new(BinlogVersion::Version4);
+        let flags = EventFlags::LOG_EVENT_ARTIFICIAL_F
+            | EventFlags::LOG_EVENT_RELAY_LOG_F
+            | EventFlags::LOG_EVENT_SUPPRESS_USE_F;
+        let header = BinlogEventHeader::new(0, EventType::XID_EVENT, 1, 0, 0, flags);
+        let event =
+            Event::try_from_data(header, &EventData::XidEvent(XidEvent::new(1)), &fde, None)?;
+        assert!(event.is_artificial());
+        assert!(event.is_relay_log());
+        let mut input = Vec::new();
+        event.write_raw(&mut input)?;
+
+        let mut reader = EventStreamReader::new(BinlogVersion::Version4);
+        let ev = reader.read(&input[..])?;
+        assert_eq!(ev.header().flags(), flags);
+
+        let mut reader = EventStreamReader::new(BinlogVersion::Version4)
+            .with_artificial_flags_policy(ArtificialFlagsPolicy::Clear);
+        let ev = reader.read(&input[..])?;
+        assert!(!ev.is_artificial());
+        assert!(!ev.is_relay_log());
+        assert_eq!(ev.header().flags(), EventFlags::LOG_EVENT_SUPPRESS_USE_F);
+        assert_eq!(ev.data(), event.data());
+
+        // checksum is recalculated
+        let alg = Some(BinlogChecksumAlg::BINLOG_CHECKSUM_ALG_CRC32);
+        let data = EventData::XidEvent(XidEvent::new(1));
+        let event = Event::try_from_data(header, &data, &fde, alg)?;
+        let expected =
+            Event::try_from_data(header.with_flags(EventFlags::empty()), &data, &fde, alg)?;
+        assert_eq!(event.with_flags(EventFlags::empty()), expected);
+
+        Ok(())
+    }
+
+    #[test]
+    fn user_var_event_values() -> io::Result<()> {
+        let mut found = 0;
+        for entry in std::fs::read_dir("./test-data/binlogs")? {
+            let file_data = std::fs::read(entry?.path())?;
+            let binlog_file = match BinlogFile::new(BinlogVersion::Version4, &file_data[..]) {
+                Ok(binlog_file) => binlog_file,
+                Err(_) => continue,
+            };
+            for ev in binlog_file {
+                let ev = match ev {
+                    Ok(ev) => ev,
+                    Err(_) => break,
+                };
+                if ev.header().event_type_raw() != EventType::USER_VAR_EVENT as u8 {
+                    continue;
+                }
+                let ev = ev.read_event::<UserVarEvent>()?;
+                match ev.value_type() {
+                    Ok(ItemResult::STRING_RESULT) => {
+                        ev.value_str()?;
+                        assert_eq!(ev.value_int().is_err(), !ev.is_null());
+                    }
+                    Ok(ItemResult::REAL_RESULT) => {
+                        ev.value_real()?;
+                    }
+                    Ok(ItemResult::INT_RESULT) => {
+                        ev.value_int()?;
+                        assert_eq!(ev.value_str().is_err(), !ev.is_null());
+                    }
+                    Ok(ItemResult::DECIMAL_RESULT) => {
+                        ev.value_decimal()?;
+                    }
+                    _ => (),
+                }
+                found += 1;
+            }
+        }
+        assert!(found > 0);
+
+        fn user_var(value_type: ItemResult, charset: u32, value: &[u8], flags: u8) -> Vec<u8> {
+            let mut data = Vec::new();
+            data.extend_from_slice(&1_u32.to_le_bytes());
+            data.push(b'a');
+            data.push(0);
+            data.push(value_type as i8 as u8);
+            data.extend_from_slice(&charset.to_le_bytes());
+            data.extend_from_slice(&(value.len() as u32).to_le_bytes());
+            data.extend_from_slice(value);
+            data.push(flags);
+            data
+        }
+
+        fn parse(data: Vec<u8>) -> io::Result<UserVarEvent<'static>> {
+            let fde = FormatDescriptionEvent::new(BinlogVersion::Version4);
+            ParseBuf(&data)
+                .parse::<UserVarEvent>(BinlogCtx::new(data.len(), &fde))
+                .map(UserVarEvent::into_owned)
+        }
+
+        // latin1
+        let ev = parse(user_var(ItemResult::STRING_RESULT, 8, b"caf\xe9 \x80", 0))?;
+        assert_eq!(ev.value_str()?.as_deref(), Some("caf\u{e9} \u{20ac}"));
+        // utf8mb4
+        let ev = parse(user_var(
+            ItemResult::STRING_RESULT,
+            255,
+            "café".as_bytes(),
+            0,
+        ))?;
+        assert_eq!(ev.value_str()?.as_deref(), Some("café"));
+        let ev = parse(user_var(ItemResult::STRING_RESULT, 45, b"\xff", 0))?;
+        assert_eq!(
+            ev.value_str().unwrap_err().kind(),
+            io::ErrorKind::InvalidData
+        );
+        // ujis isn't supported
+        let ev = parse(user_var(ItemResult::STRING_RESULT, 12, b"a", 0))?;
+        assert_eq!(
+            ev.value_str().unwrap_err().kind(),
+            io::ErrorKind::Unsupported
+        );
+
+        let ev = parse(user_var(
+            ItemResult::INT_RESULT,
+            63,
+            &(-5_i64).to_le_bytes(),
+            0,
+        ))?;
+        assert_eq!(ev.value_int()?, Some(-5));
+        assert!(ev.value_uint().is_err());
+        assert!(ev.value_real().is_err());
+        let ev = parse(user_var(
+            ItemResult::INT_RESULT,
+            63,
+            &u64::MAX.to_le_bytes(),
+            1,
+        ))?;
+        assert_eq!(ev.value_uint()?, Some(u64::MAX));
+        assert!(ev.value_int().is_err());
+
+        let ev = parse(user_var(
+            ItemResult::REAL_RESULT,
+            63,
+            &1.5_f64.to_le_bytes(),
+            0,
+        ))?;
+        assert_eq!(ev.value_real()?, Some(1.5));
+        let ev = parse(user_var(ItemResult::REAL_RESULT, 63, b"123", 0))?;
+        assert_eq!(
+            ev.value_real().unwrap_err().kind(),
+            io::ErrorKind::InvalidData
+        );
+
+        let mut value = vec![5, 2];
+        "-123.45"
+            .parse::<super::decimal::Decimal>()
+            .unwrap()
+            .write_bin_with_precision(&mut value, 5, 2)?;
+        let ev = parse(user_var(ItemResult::DECIMAL_RESULT, 63, &value, 0))?;
+        assert_eq!(ev.value_decimal()?.unwrap().to_string(), "-123.45");
+
+        // null values
+        let mut data = Vec::new();
+        data.extend_from_slice(&1_u32.to_le_bytes());
+        data.extend_from_slice(b"a\x01");
+        let ev = parse(data)?;
+        assert_eq!(ev.value_str()?, None);
+        assert_eq!(ev.value_int()?, None);
+        assert_eq!(ev.value_decimal()?, None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn statement_context_builder() -> io::Result<()> {
+        let query = QueryEvent::new(&[][..], &b"test"[..])
+            .with_query(&b"INSERT INTO t VALUES (NULL, RAND(), @a, LAST_INSERT_ID())"[..]);
+        let events = StatementContextBuilder::new()
+            .with_user_var(UserVarEvent::new(
+                &b"a"[..],
+                ItemResult::INT_RESULT,
+                63,
+                42_i64.to_le_bytes().to_vec(),
+            ))
+            .with_user_var(UserVarEvent::null(&b"b"[..]))
+            .with_rand_seed(1, 2)
+            .with_insert_id(10)
+            .with_last_insert_id(5)
+            .build(query.clone());
+
+        assert_eq!(
+            events.iter().map(EventData::event_type).collect::<Vec<_>>(),
+            vec![
+                EventType::INTVAR_EVENT,
+                EventType::INTVAR_EVENT,
+                EventType::RAND_EVENT,
+                EventType::USER_VAR_EVENT,
+                EventType::USER_VAR_EVENT,
+                EventType::QUERY_EVENT,
+            ]
+        );
+        assert_eq!(
+            events[0],
+            EventData::IntvarEvent(IntvarEvent::last_insert_id(5))
+        );
+        assert_eq!(
+            events[1],
+            EventData::IntvarEvent(IntvarEvent::insert_id(10))
+        );
+        assert_eq!(events[2], EventData::RandEvent(RandEvent::new(1, 2)));
+        assert_eq!(events[5], EventData::QueryEvent(query.clone()));
+
+        // events must survive the round-trip
+        let fde = FormatDescriptionEvent::new(BinlogVersion::Version4);
+        for data in &events {
+            let header = BinlogEventHeader::new(0, data.event_type(), 1, 0, 0, EventFlags::empty());
+            let event = Event::try_from_data(
+                header,
+                data,
+                &fde,
+                Some(BinlogChecksumAlg::BINLOG_CHECKSUM_ALG_CRC32),
+            )?;
+            assert_eq!(
+                event.read_data()?.map(EventData::into_owned),
+                Some(data.clone().into_owned())
+            );
+        }
+
+        let events = StatementContextBuilder::new().build(query.clone());
+        assert_eq!(events, vec![EventData::QueryEvent(query)]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn session_snapshot() -> io::Result<()> {
+        let status_vars = StatusVars::builder()
+            .with_sql_mode(SqlMode::MODE_NO_BACKSLASH_ESCAPES)
+            .with_charset(33, 33, 8)
+            .with_time_zone(&b"Europe/O'Land"[..])
+            .with_lc_time_names(5)
+            .with_charset_database(45)
+            .with_default_collation_for_utf8mb4(255)
+            .with_explicit_defaults_for_timestamp(true)
+            .build()?;
+        let query = QueryEvent::new(status_vars, &b"test"[..]).with_query(&b"SELECT 1"[..]);
+        let snapshot = SessionSnapshot::from_query_event(&query)?;
+        assert_eq!(
+            snapshot.sql_mode(),
+            Some(SqlMode::MODE_NO_BACKSLASH_ESCAPES)
+        );
+        assert_eq!(snapshot.charset(), Some((33, 33, 8)));
+        assert_eq!(snapshot.time_zone(), Some("Europe/O'Land"));
+        assert_eq!(snapshot.lc_time_names(), 5);
+        assert_eq!(snapshot.collation_database(), 45);
+        assert_eq!(snapshot.default_collation_for_utf8mb4(), Some(255));
+        assert_eq!(snapshot.explicit_defaults_for_timestamp(), Some(true));
+
+        let mut session = SessionSnapshot::new();
+        assert_eq!(
+            session.diff(&snapshot),
+            vec![
+                format!(
+                    "SET @@session.sql_mode={}",
+                    SqlMode::MODE_NO_BACKSLASH_ESCAPES.bits()
+                ),
+                "SET @@session.character_set_client=33,@@session.collation_connection=33,@@session.collation_server=8".to_owned(),
+                "SET @@session.time_zone='Europe/O''Land'".to_owned(),
+                "SET @@session.lc_time_names=5".to_owned(),
+                "SET @@session.collation_database=45".to_owned(),
+                "SET @@session.default_collation_for_utf8mb4=255".to_owned(),
+                "SET @@session.explicit_defaults_for_timestamp=1".to_owned(),
+            ]
+        );
+        session.update(&snapshot);
+        assert_eq!(session, snapshot);
+        assert!(session.diff(&snapshot).is_empty());
+
+        // variables absent from the event are kept, defaults are restored
+        let status_vars = StatusVars::builder().with_charset(45, 45, 8).build()?;
+        let query = QueryEvent::new(status_vars, &b"test"[..]).with_query(&b"SELECT 1"[..]);
+        let next = SessionSnapshot::from_query_event(&query)?;
+        assert_eq!(
+            session.diff(&next),
+            vec![
+                "SET @@session.character_set_client=45,@@session.collation_connection=45,@@session.collation_server=8",
+                "SET @@session.lc_time_names=0",
+                "SET @@session.collation_database=DEFAULT",
+            ]
+        );
+        session.update(&next);
+        assert_eq!(session.time_zone(), Some("Europe/O'Land"));
+        assert!(session.diff(&next).is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn decode_query_using_charset() -> io::Result<()> {
+        // `SELECT 'café'` issued by a latin1 client
+        let status_vars = StatusVars::builder().with_charset(8, 8, 45).build()?;
+        let query = QueryEvent::new(status_vars, &b"test"[..]).with_query(&b"SELECT 'caf\xe9'"[..]);
+        assert_eq!(query.charset_client(), Some(8));
+        assert_eq!(query.query_decoded()?, "SELECT 'café'");
+        assert_eq!(query.query(), "SELECT 'caf\u{fffd}'");
+
+        // utf8mb4 is assumed without the charset status variable
+        let query = QueryEvent::new(StatusVars::builder().build()?, &b"test"[..])
+            .with_query("SELECT 'café'".as_bytes());
+        assert_eq!(query.charset_client(), None);
+        assert_eq!(query.query_decoded()?, "SELECT 'café'");
+        let query = query.with_query(&b"SELECT 'caf\xe9'"[..]);
+        assert_eq!(
+            query.query_decoded().unwrap_err().kind(),
+            io::ErrorKind::InvalidData
+        );
+
+        let status_vars = StatusVars::builder().with_charset(28, 28, 45).build()?;
+        let query = QueryEvent::new(status_vars, &b"test"[..]).with_query(&b"SELECT 1"[..]);
+        assert_eq!(
+            query.query_decoded().unwrap_err().kind(),
+            io::ErrorKind::Unsupported
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn event_builders() -> io::Result<()> {
+        use ColumnType::*;
+
+        let fde = FormatDescriptionEvent::new(BinlogVersion::Version4);
+        let roundtrip = |data: EventData<'_>| -> io::Result<EventData<'static>> {
+            let header = BinlogEventHeader::new(0, data.event_type(), 1, 0, 0, EventFlags::empty());
+            let event = Event::try_from_data(header, &data, &fde, None)?;
+            let read = event.read_data()?.map(EventData::into_owned);
+            assert_eq!(read, Some(data.clone().into_owned()));
+            Ok(read.unwrap())
+        };
+
+        let query = QueryEvent::builder()
+            .with_schema(&b"test"[..])
+            .with_query(&b"CREATE TABLE t (...)"[..])
+            .with_thread_id(7)
+            .build()?;
+        assert_eq!(query.schema(), "test");
+        assert_eq!(query.thread_id(), 7);
+        roundtrip(EventData::QueryEvent(query))?;
+        assert!(QueryEvent::builder()
+            .with_schema(vec![b'a'; 256])
+            .build()
+            .is_err());
+
+        let utf8mb4 = 45;
+        let columns = vec![
+            ColumnDdl::new("id", MYSQL_TYPE_LONGLONG).with_flags(
+                ColumnFlags::UNSIGNED_FLAG | ColumnFlags::NOT_NULL_FLAG | ColumnFlags::PRI_KEY_FLAG,
+            ),
+            ColumnDdl::new("tiny", MYSQL_TYPE_TINY),
+            ColumnDdl::new("name", MYSQL_TYPE_VARCHAR)
+                .with_length(Some(100))
+                .with_character_set(utf8mb4),
+            ColumnDdl::new("code", MYSQL_TYPE_STRING)
+                .with_length(Some(200))
+                .with_character_set(utf8mb4),
+            ColumnDdl::new("price", MYSQL_TYPE_NEWDECIMAL)
+                .with_length(Some(10))
+                .with_decimals(2),
+            ColumnDdl::new("born", MYSQL_TYPE_NEWDATE),
+            ColumnDdl::new("created", MYSQL_TYPE_DATETIME2).with_decimals(3),
+            ColumnDdl::new("updated", MYSQL_TYPE_TIMESTAMP2).with_decimals(6),
+            ColumnDdl::new("span", MYSQL_TYPE_TIME2).with_decimals(2),
+            ColumnDdl::new("flags", MYSQL_TYPE_BIT).with_length(Some(12)),
+            ColumnDdl::new("color", MYSQL_TYPE_ENUM)
+                .with_character_set(utf8mb4)
+                .with_values(["red", "green"]),
+            ColumnDdl::new("tags", MYSQL_TYPE_SET)
+                .with_character_set(utf8mb4)
+                .with_values(["a", "b", "c"]),
+            ColumnDdl::new("body", MYSQL_TYPE_BLOB).with_character_set(utf8mb4),
+            ColumnDdl::new("doc", MYSQL_TYPE_JSON),
+            ColumnDdl::new("ratio", MYSQL_TYPE_DOUBLE),
+        ];
+        let tme = TableMapEvent::builder(&b"test"[..], &b"t"[..])
+            .with_table_id(42)
+            .with_columns(columns.clone())
+            .build()?;
+        // primary key isn't a part of the column definition in the optional metadata
+        let mut expected = columns.clone();
+        expected[0] = expected[0]
+            .clone()
+            .with_flags(ColumnFlags::UNSIGNED_FLAG | ColumnFlags::NOT_NULL_FLAG);
+        assert_eq!(tme.columns_ddl()?, expected);
+        assert_eq!(tme.column_is_unsigned(0), Some(true));
+        assert_eq!(tme.column_is_unsigned(1), Some(false));
+        let tme = match roundtrip(EventData::TableMapEvent(tme))? {
+            EventData::TableMapEvent(tme) => tme,
+            _ => unreachable!(),
+        };
+        assert!(TableMapEvent::builder(&b"test"[..], &b"t"[..])
+            .with_column(ColumnDdl::new("x", MYSQL_TYPE_NULL))
+            .build()
+            .is_err());
+
+        let row = vec![
+            Value::UInt(u64::MAX),
+            Value::Int(-5),
+            Value::Bytes("привет".into()),
+            Value::Bytes(b"abc".to_vec()),
+            Value::Bytes(b"-12.50".to_vec()),
+            Value::Date(2023, 5, 17, 0, 0, 0, 0),
+            Value::Date(2023, 5, 17, 13, 45, 1, 123000),
+            Value::Date(2001, 9, 9, 1, 46, 40, 5),
+            Value::Time(true, 1, 2, 3, 4, 560000),
+            Value::Bytes(vec![0x0a, 0xbc]),
+            Value::Int(2),
+            Value::Bytes(vec![0b101]),
+            Value::Bytes(b"blob".to_vec()),
+            Value::Bytes(br#"{"a":[1,"x"]}"#.to_vec()),
+            Value::Double(0.5),
+        ];
+        let mut other = row.clone();
+        other[1] = Value::NULL;
+        other[2] = Value::Bytes(b"bar".to_vec());
+
+        let decode = |data: &EventData<'static>| -> io::Result<Vec<Vec<Option<Vec<Value>>>>> {
+            let ev = match data {
+                EventData::RowsEvent(ev) => ev,
+                _ => unreachable!(),
+            };
+            assert_eq!(ev.table_id(), 42);
+            ev.rows(&tme)
+                .map(|row| {
+                    let (before, after) = row?;
+                    Ok(vec![before, after]
+                        .into_iter()
+                        .map(|image| {
+                            image.map(|image| {
+                                image
+                                    .unwrap()
+                                    .into_iter()
+                                    .map(|x| Value::try_from(x).unwrap())
+                                    .collect::<Vec<_>>()
+                            })
+                        })
+                        .collect())
+                })
+                .collect()
+        };
+
+        let write = WriteRowsEvent::builder(&tme)
+            .with_row(row.clone())
+            .with_row(other.clone())
+            .build()?;
+        let write = roundtrip(EventData::RowsEvent(write))?;
+        let rows = decode(&write)?;
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[1], vec![None, Some(other.clone())]);
+        // JSON is decoded into its text representation
+        let mut expected = row.clone();
+        expected[13] = Value::Bytes(br#"{"a":[1,"x"]}"#.to_vec());
+        assert_eq!(rows[0], vec![None, Some(expected)]);
+
+        // values of other columns are skipped
+        for first in 0..2 {
+            let indexes = (first..row.len()).step_by(2).collect::<Vec<_>>();
+            let projection = Projection::from_indexes(indexes.iter().rev().copied());
+            let ev = match write {
+                EventData::RowsEvent(ref ev) => ev,
+                _ => unreachable!(),
+            };
+            for (projected, full) in ev.rows_projected(&tme, &projection).zip(&rows) {
+                let projected = projected?.1.unwrap().unwrap();
+                let full = full[1].as_ref().unwrap();
+                let expected = indexes.iter().rev().map(|&i| full[i].clone());
+                let projected = projected.into_iter().map(|x| Value::try_from(x).unwrap());
+                assert!(projected.eq(expected));
+            }
+        }
+
+        let update = UpdateRowsEvent::builder(&tme)
+            .with_update(row.clone(), other.clone())
+            .build()?;
+        let rows = decode(&roundtrip(EventData::RowsEvent(update))?)?;
+        assert_eq!(rows, vec![vec![Some(row.clone()), Some(other.clone())]]);
+
+        let delete = DeleteRowsEvent::builder(&tme)
+            .with_row(other.clone())
+            .build()?;
+        let rows = decode(&roundtrip(EventData::RowsEvent(delete))?)?;
+        assert_eq!(rows, vec![vec![Some(other.clone()), None]]);
+
+        assert!(UpdateRowsEvent::builder(&tme)
+            .with_row(row.clone())
+            .build()
+            .is_err());
+        assert!(WriteRowsEvent::builder(&tme)
+            .with_row(row[1..].to_vec())
+            .build()
+            .is_err());
+        let mut bad = row;
+        bad[1] = Value::Int(1000);
+        assert!(WriteRowsEvent::builder(&tme).with_row(bad).build().is_err());
+
+        for (year, encoded) in [(0, 0), (1901, 1), (2155, 255)] {
+            let mut buf = Vec::new();
+            BinlogValue::Value(Value::Int(year)).serialize_bin(MYSQL_TYPE_YEAR, &[], &mut buf)?;
+            assert_eq!(buf, [encoded]);
+        }
+        for year in [1, 1000, 1900, 2156] {
+            let err = BinlogValue::Value(Value::Int(year))
+                .serialize_bin(MYSQL_TYPE_YEAR, &[], &mut Vec::new())
+                .unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::InvalidInput, "{}", year);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn geometry_rows() -> io::Result<()> {
+        use crate::value::{geometry::Geometry, point::Point};
+        use ColumnType::*;
+
+        let tme = TableMapEvent::builder(&b"test"[..], &b"t"[..])
+            .with_table_id(1)
+            .with_column(ColumnDdl::new("id", MYSQL_TYPE_LONG))
+            .with_column(ColumnDdl::new("g", MYSQL_TYPE_GEOMETRY))
+            .build()?;
+        let point = Geometry::from(Point::with_srid(1.5, -2.0, 4326));
+        let write = WriteRowsEvent::builder(&tme)
+            .with_row(vec![Value::Int(1), point.clone().into()])
+            .with_row(vec![Value::Int(2), Value::NULL])
+            .build()?;
+
+        let rows = write
+            .rows(&tme)
+            .map(|row| row.map(|(_, after)| after.unwrap()))
+            .collect::<io::Result<Vec<_>>>()?;
+        assert_eq!(rows[0].geometry(1), Some(Ok(point)));
+        assert_eq!(rows[0].geometry(0), None);
+        assert_eq!(rows[1].geometry(1), None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn bit_rows() -> io::Result<()> {
+        use ColumnType::*;
+
+        let tme = TableMapEvent::builder(&b"test"[..], &b"t"[..])
+            .with_table_id(1)
+            .with_column(ColumnDdl::new("b1", MYSQL_TYPE_BIT).with_length(Some(1)))
+            .with_column(ColumnDdl::new("b9", MYSQL_TYPE_BIT).with_length(Some(9)))
+            .with_column(ColumnDdl::new("d", MYSQL_TYPE_DOUBLE))
+            .with_column(ColumnDdl::new("b64", MYSQL_TYPE_BIT).with_length(Some(64)))
+            .with_column(ColumnDdl::new("id", MYSQL_TYPE_LONG))
+            .build()?;
+        // BIT metadata is (bits % 8, bits / 8)
+        assert_eq!(tme.get_column_metadata(0), Some(&[1, 0][..]));
+        assert_eq!(tme.get_column_metadata(1), Some(&[1, 1][..]));
+        assert_eq!(tme.get_column_metadata(2), Some(&[8][..]));
+        assert_eq!(tme.get_column_metadata(3), Some(&[0, 8][..]));
+        assert_eq!(tme.get_column_metadata(4), Some(&[][..]));
+
+        let write = WriteRowsEvent::builder(&tme)
+            .with_row(vec![
+                Value::UInt(1),
+                Value::Bytes(vec![0x01, 0x02]),
+                Value::Double(0.5),
+                Value::UInt(u64::MAX),
+                Value::Int(7),
+            ])
+            .build()?;
+        let rows = write
+            .rows(&tme)
+            .map(|row| row.map(|(_, after)| after.unwrap()))
+            .collect::<io::Result<Vec<_>>>()?;
+        let row = &rows[0];
+        assert_eq!(
+            row.as_ref(1),
+            Some(&BinlogValue::Value(Value::Bytes(vec![0x01, 0x02])))
+        );
+        assert_eq!(row.bit(0), Some(1));
+        assert_eq!(row.bit(1), Some(0x0102));
+        assert_eq!(row.bit(3), Some(u64::MAX));
+        assert_eq!(row.bit(2), None);
+        assert_eq!(row.as_ref(4), Some(&BinlogValue::Value(Value::Int(7))));
+
+        Ok(())
+    }
+
+    #[test]
+    fn malformed_rows_event_body() -> io::Result<()> {
+        let mut file_data = std::fs::read("./test-data/binlogs/write-full-row.binlog")?;
+        let events = BinlogFile::new(BinlogVersion::Version4, &file_data[..])?
+            .collect::<io::Result<Vec<_>>>()?;
+
+        let mut offset = BinlogFileHeader::LEN;
+        for ev in events.iter() {
+            if ev.header().event_type() == Ok(EventType::WRITE_ROWS_EVENT_V1) {
+                break;
+            }
+            offset += ev.header().event_size() as usize;
+        }
+        // corrupt the number of columns that follows the post-header
+        let data = &mut file_data[offset + BinlogEventHeader::LEN..];
+        assert_eq!(data[8], 5);
+        data[8..12].copy_from_slice(&[0xfd, 0xff, 0xff, 0xff]);
+
+        // only the post-header of a rows event is parsed by the reader
+        let mut binlog_file = BinlogFile::new(BinlogVersion::Version4, &file_data[..])?;
+        let read = binlog_file.by_ref().collect::<io::Result<Vec<_>>>()?;
+        assert_eq!(read.len(), events.len());
+        let corrupted = read
+            .iter()
+            .find(|ev| ev.header().event_type() == Ok(EventType::WRITE_ROWS_EVENT_V1))
+            .unwrap();
+        assert!(corrupted.read_data().is_err());
+        assert!(binlog_file.reader().table_map.is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn event_mask() -> io::Result<()> {
+        use EventType::*;
+
+        let file_data = std::fs::read("./test-data/binlogs/write-full-row.binlog")?;
+        let mut binlog_file = BinlogFile::new(BinlogVersion::Version4, &file_data[..])?;
+        let all = binlog_file.by_ref().collect::<io::Result<Vec<_>>>()?;
+        let log_pos = binlog_file.reader().log_pos();
+
+        let rows_types = [
+            WRITE_ROWS_EVENT_V1,
+            UPDATE_ROWS_EVENT_V1,
+            DELETE_ROWS_EVENT_V1,
+            WRITE_ROWS_EVENT,
+            UPDATE_ROWS_EVENT,
+            DELETE_ROWS_EVENT,
+        ];
+        let expected = all
+            .iter()
+            .filter(|ev| matches!(ev.header().event_type(), Ok(x) if rows_types.contains(&x)))
+            .cloned()
+            .collect::<Vec<_>>();
+        assert!(!expected.is_empty());
+
+        let mut reader = EventStreamReader::new(BinlogVersion::Version4);
+        reader.set_event_mask(&rows_types);
+        let mut binlog_file = BinlogFile::with_reader(reader, &file_data[..])?;
+        let mut masked = Vec::new();
+        while let Some(ev) = binlog_file.next() {
+            let ev = ev?;
+            // table maps are maintained for returned rows events
+            if let Some(EventData::RowsEvent(rows)) = ev.read_data()? {
+                assert!(binlog_file.reader().table_map(rows.table_id()).is_some());
+            }
+            masked.push(ev);
+        }
+        assert_eq!(masked, expected);
+        assert_eq!(binlog_file.reader().log_pos(), log_pos);
+
+        // bodies of table maps are skipped if rows events aren't returned
+        let mut reader = EventStreamReader::new(BinlogVersion::Version4);
+        reader.set_event_mask(&[QUERY_EVENT]);
+        let mut binlog_file = BinlogFile::with_reader(reader, &file_data[..])?;
+        let queries = binlog_file.by_ref().collect::<io::Result<Vec<_>>>()?;
+        assert!(!queries.is_empty());
+        assert!(queries
+            .iter()
+            .all(|ev| ev.header().event_type() == Ok(QUERY_EVENT)));
+        assert_eq!(binlog_file.reader().log_pos(), log_pos);
+        assert!(binlog_file.reader().table_map.is_empty());
+
+        // an empty mask returns all events
+        let mut reader = EventStreamReader::new(BinlogVersion::Version4);
+        reader.set_event_mask(&[XID_EVENT]);
+        reader.set_event_mask(&[]);
+        assert!(reader.event_mask().is_none());
+        let binlog_file = BinlogFile::with_reader(reader, &file_data[..])?;
+        assert_eq!(binlog_file.collect::<io::Result<Vec<_>>>()?, all);
+
+        Ok(())
+    }
+
+    #[test]
+    fn event_consistency() -> io::Result<()> {
+        let file_data = std::fs::read("./test-data/binlogs/write-full-row.binlog")?;
+        let binlog_file = BinlogFile::new(BinlogVersion::Version4, &file_data[..])?;
+        let mut fde = FormatDescriptionEvent::new(BinlogVersion::Version4);
+        for ev in binlog_file {
+            let ev = ev?;
+            ev.validate_consistency()?;
+            if let Ok(EventType::FORMAT_DESCRIPTION_EVENT) = ev.header().event_type() {
+                fde = ev
+                    .read_event::<FormatDescriptionEvent>()?
+                    .into_owned()
+                    .with_footer(ev.footer());
+            }
+        }
+
+        // log_pos that points inside the event
+        let ev = BinlogFile::new(BinlogVersion::Version4, &file_data[..])?
+            .nth(1)
+            .unwrap()?;
+        let mut bytes = Vec::new();
+        ev.write_raw(&mut bytes)?;
+        LittleEndian::write_u32(&mut bytes[13..17], 1);
+        let ev = Event::parse(&fde, &bytes)?;
+        let err = ev.validate_consistency().unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+        Ok(())
+    }
+
+    #[test]
+    fn strict_identifiers() -> io::Result<()> {
+        let name = "т".repeat(64);
+        let tme = TableMapEvent::builder(name.as_bytes(), &b"t1"[..]).build()?;
+        assert_eq!(tme.database_name_str(), Ok(&*name));
+        assert_eq!(tme.table_name_str(), Ok("t1"));
+        let query = QueryEvent::builder().with_schema(name.as_bytes()).build()?;
+        assert_eq!(query.schema_str(), Ok(&*name));
+
+        let too_long = "a".repeat(65);
+        assert!(TableMapEvent::builder(&b"db"[..], too_long.as_bytes())
+            .build()
+            .is_err());
+        assert!(QueryEvent::builder()
+            .with_schema(too_long.as_bytes())
+            .build()
+            .is_err());
+        assert!(TableMapEvent::builder(&b"db\xff"[..], &b"t1"[..])
+            .build()
+            .is_err());
+        assert!(QueryEvent::builder()
+            .with_schema(&b"db\xff"[..])
+            .build()
+            .is_err());
+
+        // events that aren't built using builders aren't validated
+        let query = QueryEvent::new(&[][..], &b"db\xff"[..]);
+        assert!(query.schema_str().is_err());
+        assert_eq!(query.schema(), "db\u{FFFD}");
+
+        Ok(())
+    }
+
+    #[test]
+    fn status_vars_builder() -> io::Result<()> {
+        use super::{
+            consts::StatusVarKey,
+            events::{StatusVarVal, StatusVars},
+        };
+        use crate::constants::{Flags2, SqlMode};
+
+        let vars = StatusVars::builder()
+            .with_flags2(Flags2::OPTION_NOT_AUTOCOMMIT)
+            .with_sql_mode(SqlMode::MODE_NO_ENGINE_SUBSTITUTION | SqlMode::MODE_STRICT_TRANS_TABLES)
+            .with_auto_increment(2, 1)
+            .with_charset(45, 45, 255)
+            .with_time_zone(&b"SYSTEM"[..])
+            .with_catalog(&b"std"[..])
+            .with_lc_time_names(0)
+            .with_charset_database(255)
+            .with_table_map_for_update(3)
+            .with_invoker(&b"root"[..], &b"localhost"[..])
+            .with_updated_db_names(vec![&b"db1"[..], &b"db2"[..]])
+            .with_microseconds(999_999)
+            .with_explicit_defaults_for_timestamp(true)
+            .with_ddl_logged_with_xid(42)
+            .with_default_collation_for_utf8mb4(255)
+            .with_sql_require_primary_key(1)
+            .with_default_table_encryption(0)
+            .build()?;
+
+        let vars_list = vars.iter().collect::<Vec<_>>();
+        let values = vars_list
+            .iter()
+            .map(|var| var.get_value().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(values.len(), 17);
+        assert_eq!(
+            values[1],
+            StatusVarVal::SqlMode(crate::misc::raw::RawFlags::new(
+                (SqlMode::MODE_NO_ENGINE_SUBSTITUTION | SqlMode::MODE_STRICT_TRANS_TABLES).bits()
+            ))
+        );
+        assert_eq!(
+            values[3],
+            StatusVarVal::Charset {
+                charset_client: 45,
+                collation_connection: 45,
+                collation_server: 255,
+            }
+        );
+        match &values[9] {
+            StatusVarVal::Invoker { username, hostname } => {
+                assert_eq!(username.as_bytes(), b"root");
+                assert_eq!(hostname.as_bytes(), b"localhost");
+            }
+            x => panic!("unexpected value {:?}", x),
+        }
+        match &values[10] {
+            StatusVarVal::UpdatedDbNames(names) => {
+                let names = names.iter().map(|x| x.as_bytes()).collect::<Vec<_>>();
+                assert_eq!(names, vec![&b"db1"[..], &b"db2"[..]]);
+            }
+            x => panic!("unexpected value {:?}", x),
+        }
+        assert_eq!(values[11], StatusVarVal::Microseconds(999_999));
+        assert_eq!(values[16], StatusVarVal::DefaultTableEncryption(0));
+
+        let event = QueryEvent::builder()
+            .with_status_vars(vars.clone())
+            .with_schema(&b"db1"[..])
+            .with_query(&b"UPDATE db1.t, db2.t SET ..."[..])
+            .build()?;
+        assert_eq!(event.status_vars(), &vars);
+        let fde = FormatDescriptionEvent::new(BinlogVersion::Version4);
+        let header =
+            BinlogEventHeader::new(0, EventType::QUERY_EVENT, 1, 0, 0, EventFlags::empty());
+        let event = Event::try_from_data(header, &EventData::QueryEvent(event), &fde, None)?;
+        match event.read_data()? {
+            Some(EventData::QueryEvent(ev)) => assert_eq!(ev.status_vars(), &vars),
+            x => panic!("unexpected event data {:?}", x),
+        }
+
+        let vars = StatusVars::builder()
+            .with_updated_db_names((0..17).map(|i| format!("db{}", i).into_bytes()))
+            .with_charset(33, 33, 8)
+            .build()?;
+        assert_eq!(
+            vars.get_status_var(StatusVarKey::UpdatedDbNames)
+                .unwrap()
+                .get_value(),
+            Ok(StatusVarVal::UpdatedDbNames(vec![]))
+        );
+        assert!(vars.get_status_var(StatusVarKey::Charset).is_some());
+
+        assert!(StatusVars::builder()
+            .with_time_zone(vec![b'a'; 256])
+            .build()
+            .is_err());
+        assert!(StatusVars::builder()
+            .with_microseconds(1_000_000)
+            .build()
+            .is_err());
+        assert!(StatusVars::builder()
+            .with_updated_db_names(vec![&[b'a', 0, b'b'][..]])
+            .build()
+            .is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn execute_load_query_substitution() -> io::Result<()> {
+        let mut found = 0;
+        for file in &[
+            "ver_5_1_23.001",
+            "ver_5_1-wl2325_s.001",
+            "ver_trunk_row_v2.001",
+        ] {
+            let file_data = std::fs::read(format!("./test-data/binlogs/{}", file))?;
+            let binlog_file = BinlogFile::new(BinlogVersion::Version4, &file_data[..])?;
+            for ev in binlog_file {
+                let ev = ev?;
+                if ev.header().event_type_raw() != EventType::EXECUTE_LOAD_QUERY_EVENT as u8 {
+                    continue;
+                }
+                let ev = ev.read_event::<ExecuteLoadQueryEvent>()?;
+                // original file name must give the original query
+                assert_eq!(ev.substituted_query("big_file.dat", false)?, ev.query_raw());
+                let query = ev.substituted_query("/tmp/it's.data", true)?;
+                assert!(query.starts_with(b"LOAD DATA LOCAL INFILE '/tmp/it\\'s.data' INTO TABLE"));
+                found += 1;
+            }
+        }
+        assert!(found > 0);
+
+        let ev = ExecuteLoadQueryEvent::new(
+            1,
+            LoadDuplicateHandling::LOAD_DUP_REPLACE,
+            &[][..],
+            &b"test"[..],
+        )
+        .with_query(&b"LOAD DATA INFILE 'x' IGNORE INTO TABLE t"[..])
+        .with_start_pos(9)
+        .with_end_pos(32);
+        assert_eq!(
+            ev.substituted_query("y", false)?,
+            b"LOAD DATA INFILE 'y' REPLACE INTO TABLE t"
+        );
+        let ev = ev.with_end_pos(100);
+        assert_eq!(
+            ev.substituted_query("y", false).unwrap_err().kind(),
+            io::ErrorKind::InvalidData
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn load_events() -> io::Result<()> {
+        let fde = FormatDescriptionEvent::new(BinlogVersion::Version4);
+        let roundtrip = |data: EventData<'_>| -> io::Result<Vec<u8>> {
+            let header = BinlogEventHeader::new(0, data.event_type(), 1, 0, 0, EventFlags::empty());
+            let event = Event::try_from_data(header, &data, &fde, None)?;
+            let read = event.read_data()?.map(EventData::into_owned);
+            assert_eq!(read, Some(data.into_owned()));
+            Ok(event.data().to_vec())
+        };
+
+        let sql_ex = SqlExchange::new()
+            .with_field_term(&b","[..])
+            .with_enclosed(&b"\""[..])
+            .with_line_term(&b"\r\n"[..])
+            .with_escaped(&b"\\"[..])
+            .with_opt_flags(LoadOptFlags::OPT_ENCLOSED_FLAG | LoadOptFlags::REPLACE_FLAG);
+
+        let ev = NewLoadEvent::new(&b"t"[..], &b"test"[..], &b"/tmp/data.csv"[..])
+            .with_thread_id(3)
+            .with_skip_lines(1)
+            .with_sql_ex(sql_ex.clone())
+            .with_field_names([&b"id"[..], &b"name"[..]]);
+        assert_eq!(ev.num_fields(), 2);
+        assert_eq!(ev.field_names().collect::<Vec<_>>(), ["id", "name"]);
+        assert_eq!(ev.sql_ex().line_term(), "\r\n");
+        let data = roundtrip(EventData::NewLoadEvent(ev.clone()))?;
+        assert_eq!(ev.len(BinlogVersion::Version4), data.len());
+        assert!(data.ends_with(b"\0t\0test\0/tmp/data.csv"));
+
+        // terminators are truncated to a single byte in the old format
+        let ev = LoadEvent::new(&b"t"[..], &b"test"[..], &b"/tmp/data.csv"[..])
+            .with_sql_ex(sql_ex.with_line_term(&b"\n"[..]))
+            .with_field_names([&b"id"[..]]);
+        let data = roundtrip(EventData::LoadEvent(ev.clone()))?;
+        assert_eq!(ev.len(BinlogVersion::Version4), data.len());
+        let sql_ex = ev.sql_ex();
+        assert_eq!(sql_ex.field_term_raw(), b",");
+        assert_eq!(sql_ex.line_start_raw(), b"");
+        assert!(sql_ex.opt_flags().contains(LoadOptFlags::REPLACE_FLAG));
+        assert_eq!(ev.table_name(), "t");
+        assert_eq!(ev.schema(), "test");
+        assert_eq!(ev.file_name(), "/tmp/data.csv");
+
+        Ok(())
+    }
+
+    #[test]
+    fn group_replication_events() -> io::Result<()> {
+        let fde = FormatDescriptionEvent::new(BinlogVersion::Version4);
+        let roundtrip = |data: EventData<'_>| -> io::Result<Vec<u8>> {
+            let header = BinlogEventHeader::new(0, data.event_type(), 1, 0, 0, EventFlags::empty());
+            let event = Event::try_from_data(header, &data, &fde, None)?;
+            let read = event.read_data()?.map(EventData::into_owned);
+            assert_eq!(read, Some(data.into_owned()));
+            Ok(event.data().to_vec())
+        };
+
+        let uuid = "3e11fa47-71ca-11e1-9e33-c80aa9429562";
+        let snapshot_version: GtidSet = format!("{}:1-5", uuid).parse().unwrap();
+        let ev = TransactionContextEvent::new(uuid.as_bytes())
+            .with_thread_id(7)
+            .with_gtid_specified(true)
+            .with_snapshot_version(&snapshot_version)
+            .with_write_set(vec![&b"12345"[..], &b"67890"[..]])
+            .with_read_set(vec![&b"abc"[..]]);
+        let data = roundtrip(EventData::TransactionContextEvent(ev.clone()))?;
+        assert_eq!(ev.len(BinlogVersion::Version4), data.len());
+        assert_eq!(data[..6], [36, 7, 0, 0, 0, 1]);
+        assert_eq!(ev.server_uuid(), uuid);
+        assert_eq!(ev.thread_id(), 7);
+        assert!(ev.gtid_specified());
+        assert_eq!(ev.snapshot_version()?.into_owned(), snapshot_version);
+        assert_eq!(ev.write_set().collect::<Vec<_>>(), [b"12345", b"67890"]);
+        assert_eq!(ev.read_set().collect::<Vec<_>>(), [b"abc"]);
+        assert!(data.ends_with(b"\x05\x0012345\x05\x0067890\x03\x00abc"));
+
+        let ev = ViewChangeEvent::new(&b"15732871249421424:3"[..])
+            .with_seq_number(42)
+            .with_certification_info(vec![(
+                &b"group_gtid_executed"[..],
+                &b"3e11fa47-71ca-11e1-9e33-c80aa9429562:1-5"[..],
+            )]);
+        let data = roundtrip(EventData::ViewChangeEvent(ev.clone()))?;
+        assert_eq!(ev.len(BinlogVersion::Version4), data.len());
+        assert_eq!(data.len(), 52 + 2 + 19 + 4 + 40);
+        assert_eq!(ev.view_id(), "15732871249421424:3");
+        assert_eq!(ev.seq_number(), 42);
+        assert_eq!(
+            ev.certification_info().collect::<Vec<_>>(),
+            [(
+                &b"group_gtid_executed"[..],
+                &b"3e11fa47-71ca-11e1-9e33-c80aa9429562:1-5"[..]
+            )]
+        );
+
+        // truncated certification info
+        let truncated = &data[..data.len() - 10];
+        assert!(ParseBuf(truncated)
+            .parse::<ViewChangeEvent>(BinlogCtx::new(truncated.len(), &fde))
+            .is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn gtid_event_server_versions() -> io::Result<()> {
+        let version = ServerVersion::new(8, 0, 33);
+        assert_eq!(version.raw(), 80033);
+        assert_eq!(version.split(), Some((8, 0, 33)));
+        assert_eq!(version.to_string(), "8.0.33");
+        assert!(version.supports_transaction_compression());
+        assert!(!ServerVersion::new(8, 0, 19).supports_transaction_compression());
+        assert!(!ServerVersion::UNDEFINED.is_defined());
+        assert!(!ServerVersion::UNDEFINED.supports_transaction_compression());
+        assert_eq!(ServerVersion::UNDEFINED.split(), None);
+        assert_eq!(ServerVersion::UNDEFINED.to_string(), "undefined");
+
+        let fde = FormatDescriptionEvent::new(BinlogVersion::Version4);
+        let ev = GtidEvent::new([1; 16], 5)
+            .with_lc_typecode()
+            .with_original_server_version((5, 7, 44))
+            .with_immediate_server_version(80033);
+        let header = BinlogEventHeader::new(0, EventType::GTID_EVENT, 1, 0, 0, EventFlags::empty());
+        let event = Event::try_from_data(header, &EventData::GtidEvent(ev), &fde, None)?;
+        let ev = event.read_event::<GtidEvent>()?;
+        assert_eq!(ev.original_server_version(), ServerVersion::new(5, 7, 44));
+        assert_eq!(ev.original_server_version_raw(), 50744);
+        assert_eq!(ev.immediate_server_version(), version);
+
+        let mut count = 0;
+        for file in std::fs::read_dir("./test-data/binlogs")? {
+            let data = std::fs::read(file?.path())?;
+            let binlog_file = BinlogFile::new(BinlogVersion::Version4, &data[..])?;
+            for event in binlog_file {
+                let event = event?;
+                // some files contain broken events on purpose
+                if let Ok(Some(EventData::GtidEvent(ev))) = event.read_data() {
+                    let fde_version = event.fde().split_version();
+                    for version in [ev.original_server_version(), ev.immediate_server_version()] {
+                        match version.split() {
+                            Some(version) => assert!(version >= (8, 0, 14)),
+                            None => assert!(fde_version < (8, 0, 14)),
+                        }
+                    }
+                    count += 1;
+                }
+            }
+        }
+        assert!(count > 0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn xa_prepare_event_and_xid_gtid() -> io::Result<()> {
+        let fde = FormatDescriptionEvent::new(BinlogVersion::Version4);
+        let xid = XaTransactionId::new(1, &b"ab"[..], &b"c"[..]);
+        for one_phase in [false, true] {
+            let mut data = Vec::new();
+            XaPrepareEvent::new(xid.clone(), one_phase).serialize(&mut data);
+            let header = BinlogEventHeader::new(
+                0,
+                EventType::XA_PREPARE_LOG_EVENT,
+                1,
+                0,
+                0,
+                EventFlags::empty(),
+            );
+            let event = Event::try_from_data(
+                header,
+                &EventData::XaPrepareLogEvent(data.into()),
+                &fde,
+                None,
+            )?;
+            let ev = event.read_event::<XaPrepareEvent>()?;
+            assert_eq!(ev.xid(), &xid);
+            assert_eq!(ev.one_phase(), one_phase);
+            assert_eq!(ev.xa_statement().xid(), &xid);
+        }
+
+        let query =
+            QueryEvent::new(&b""[..], &b""[..]).with_query(&b"XA COMMIT X'6162',X'63',1"[..]);
+        assert_eq!(
+            query.xa_statement(),
+            Some(XaStatement::Commit {
+                xid: xid.clone(),
+                one_phase: false,
+            })
+        );
+        assert_eq!(
+            XaTransactionId::from(MariadbXid::new(1, &b"ab"[..], &b"c"[..])),
+            xid
+        );
+
+        let data = std::fs::read("./test-data/binlogs/mysql-enum-string-set.000001")?;
+        let mut binlog_file = BinlogFile::new(BinlogVersion::Version4, &data[..])?;
+        let mut last_gtid = None;
+        let mut count = 0;
+        while let Some(event) = binlog_file.next() {
+            match event?.read_data()? {
+                Some(EventData::GtidEvent(ev)) => last_gtid = Some((ev.sid(), ev.gno())),
+                Some(EventData::XidEvent(ev)) => {
+                    let ev = ev.with_gtid(binlog_file.reader().last_gtid());
+                    assert!(ev.gtid().is_some());
+                    assert_eq!(ev.gtid(), last_gtid);
+                    count += 1;
+                }
+                _ => (),
+            }
+        }
+        assert!(count > 0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn event_visitor() -> io::Result<()> {
+        /// Counts rows events by table and other events by type.
+        #[derive(Default)]
+        struct Counter {
+            rows_events: HashMap<u64, usize>,
+            others: HashMap<EventType, usize>,
+        }
+
+        impl<'a> EventVisitor<'a> for Counter {
+            type Output = bool;
+
+            fn visit_other(&mut self, event_type: EventType) -> bool {
+                *self.others.entry(event_type).or_default() += 1;
+                false
+            }
+
+            fn visit_rows_event(&mut self, event: &RowsEventData<'a>) -> bool {
+                *self.rows_events.entry(event.table_id()).or_default() += 1;
+                true
+            }
+        }
+
+        let mut counter = Counter::default();
+        let mut expected = HashMap::new();
+        let mut rows_events = 0;
+        for file in std::fs::read_dir("./test-data/binlogs")? {
+            let data = std::fs::read(file?.path())?;
+            let binlog_file = BinlogFile::new(BinlogVersion::Version4, &data[..])?;
+            for event in binlog_file {
+                // some files contain broken events on purpose
+                let data = match event?.read_data() {
+                    Ok(Some(data)) => data.into_owned(),
+                    _ => continue,
+                };
+                let is_rows_event = matches!(data, EventData::RowsEvent(_));
+                assert_eq!(data.accept(&mut counter), is_rows_event);
+                if is_rows_event {
+                    rows_events += 1;
+                } else {
+                    *expected.entry(data.event_type()).or_insert(0) += 1;
+                }
+            }
+        }
+        assert!(rows_events > 0);
+        assert_eq!(counter.rows_events.values().sum::<usize>(), rows_events);
+        assert_eq!(counter.others, expected);
+
+        assert!(!EventData::SlaveEvent.accept(&mut counter));
+        assert_eq!(counter.others[&EventType::SLAVE_EVENT], 1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn optional_meta_extractor() -> io::Result<()> {
+        let read_tme = |file_name: &str| -> io::Result<TableMapEvent<'static>> {
+            let file_data = std::fs::read(format!("./test-data/binlogs/{}", file_name))?;
+            let binlog_file = BinlogFile::new(BinlogVersion::Version4, &file_data[..])?;
+            for ev in binlog_file {
+                if let Some(EventData::TableMapEvent(tme)) = ev?.read_data()? {
+                    return Ok(tme.into_owned());
+                }
+            }
+            panic!("no table map event in {}", file_name);
+        };
+
+        let tme = read_tme("binlog-invisible-columns.000001")?;
+        let extractor = OptionalMetaExtractor::new(tme.iter_optional_meta())?;
+        let names = extractor
+            .iter_column_name()
+            .map(|x| x.map(|x| x.name().into_owned()))
+            .collect::<io::Result<Vec<_>>>()?;
+        let visibility = extractor.iter_column_visibility().collect::<Vec<_>>();
+        assert_eq!(names, ["f1", "f2", "f3", "f4", "f5", "f6"]);
+        assert_eq!(visibility, [false, false, true, true, true, false]);
+        assert_eq!(
+            extractor.iter_signedness().collect::<Vec<_>>(),
+            [true, true, false, true]
+        );
+        assert_eq!(
+            (0..7)
+                .map(|i| tme.column_is_unsigned(i))
+                .collect::<Vec<_>>(),
+            [
+                Some(true),
+                Some(true),
+                Some(false),
+                None,
+                None,
+                Some(true),
+                None
+            ]
+        );
+
+        let tme = read_tme("mysql-enum-string-set.000001")?;
+        let extractor = OptionalMetaExtractor::new(tme.iter_optional_meta())?;
+        let enums = extractor
+            .iter_enum_str_value()
+            .map(|x| {
+                x.map(|x| {
+                    x.values()
+                        .iter()
+                        .map(|x| x.value().into_owned())
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect::<io::Result<Vec<_>>>()?;
+        let sets = extractor
+            .iter_set_str_value()
+            .map(|x| {
+                x.map(|x| {
+                    x.values()
+                        .iter()
+                        .map(|x| x.value().into_owned())
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect::<io::Result<Vec<_>>>()?;
+        assert_eq!(enums, [["var1", "variant2", "foo"]]);
+        assert_eq!(sets, [["one", "two", "three", "four"]]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn table_map_columns_ddl() -> io::Result<()> {
+        let columns_ddl = |file_name: &str| -> io::Result<Vec<String>> {
+            let file_data = std::fs::read(format!("./test-data/binlogs/{}", file_name))?;
+            let binlog_file = BinlogFile::new(BinlogVersion::Version4, &file_data[..])?;
+            for ev in binlog_file {
+                if let Some(EventData::TableMapEvent(tme)) = ev?.read_data()? {
+                    return Ok(tme
+                        .columns_ddl()?
+                        .iter()
+                        .filter_map(|x| x.to_sql())
+                        .collect());
+                }
+            }
+            panic!("no table map event in {}", file_name);
+        };
+
+        assert_eq!(
+            columns_ddl("mysql-enum-string-set.000001")?,
+            vec![
+                "`f1` CHAR(128)",
+                "`f2` VARCHAR(300)",
+                "`f3` ENUM('var1','variant2','foo')",
+                "`f4` SET('one','two','three','four')",
+                "`f5` TEXT",
+            ]
+        );
+        // without optional metadata
+        assert_eq!(
+            columns_ddl("json.binlog.000001")?,
+            vec![
+                "`@0` INT NOT NULL",
+                "`@1` JSON",
+                "`@2` VARCHAR(100)",
+                "`@3` INT",
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn previous_gtids_event() -> io::Result<()> {
+        let file_data = std::fs::read("./test-data/binlogs/binlog_transaction_with_GTID.000001")?;
+        let binlog_file = BinlogFile::new(BinlogVersion::Version4, &file_data[..])?;
+
+        let mut events = 0;
+        for ev in binlog_file {
+            let ev = ev?;
+            if ev.header().event_type_raw() != EventType::PREVIOUS_GTIDS_EVENT as u8 {
+                continue;
+            }
+
+            let prev = ev.read_event::<PreviousGtidsEvent>()?;
+            assert!(prev.gtid_set().is_empty());
+            assert_eq!(prev.gtid_set().to_string(), "");
+
+            let gtid_set: GtidSet = "3e11fa47-71ca-11e1-9e33-c80aa9429562:1-5:10,\n\
+                4a1c2b4e-71ca-11e1-9e33-c80aa9429562:7"
+                .parse()?;
+            let data = EventData::PreviousGtidsEvent(PreviousGtidsEvent::new(gtid_set.clone()));
+            let rebuilt = Event::try_from_data(
+                ev.header(),
+                &data,
+                ev.fde(),
+                ev.footer().get_checksum_alg().unwrap(),
+            )?;
+            assert_eq!(rebuilt.data().len() as u64, gtid_set.encoded_len());
+            assert_eq!(rebuilt.read_data()?.map(EventData::into_owned), Some(data));
+
+            let prev = rebuilt.read_event::<PreviousGtidsEvent>()?.into_owned();
+            assert_eq!(prev.gtid_set(), &gtid_set);
+            assert_eq!(
+                prev.gtid_set().to_string(),
+                "3e11fa47-71ca-11e1-9e33-c80aa9429562:1-5:10,\n\
+                4a1c2b4e-71ca-11e1-9e33-c80aa9429562:7"
+            );
+            events += 1;
+        }
+        assert_eq!(events, 1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn max_query_len() -> io::Result<()> {
+        let file_data = std::fs::read("./test-data/binlogs/binlog_transaction.000001")?;
+
+        let reader = EventStreamReader::new(BinlogVersion::Version4).with_max_query_len(Some(10));
+        let binlog_file = BinlogFile::with_reader(reader, &file_data[..])?;
+        let mut truncated = 0;
+        for ev in binlog_file {
+            let ev = ev?;
+            assert_eq!(ev.max_query_len(), Some(10));
+            if ev.header().event_type_raw() != EventType::QUERY_EVENT as u8 {
+                continue;
+            }
+
+            let query = ev.read_event::<QueryEvent>()?.into_owned();
+            let full = ev.clone().with_max_query_len(None);
+            let full = full.read_event::<QueryEvent>()?;
+            assert!(!full.is_query_truncated());
+            assert_eq!(query.query_len(), full.query_raw().len());
+            assert_eq!(
+                query.query_raw(),
+                &full.query_raw()[..min(10, full.query_len())]
+            );
+            assert_eq!(query.is_query_truncated(), full.query_len() > 10);
+            if query.is_query_truncated() {
+                truncated += 1;
+            }
+        }
+        assert!(truncated > 0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn max_event_size() -> io::Result<()> {
+        let file_data = std::fs::read("./test-data/binlogs/binlog_transaction.000001")?;
+        let fde = FormatDescriptionEvent::new(BinlogVersion::Version4);
+
+        // malformed event size
+        let mut header = Vec::new();
+        BinlogEventHeader::new(0, EventType::QUERY_EVENT, 1, 10, 0, EventFlags::empty())
+            .serialize(&mut header);
+        let err = Event::read(&fde, &header[..]).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+        // truncated event body
+        let err = Event::read(&fde, &file_data[BinlogFileHeader::LEN..][..50]).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+
+        let sizes = BinlogFile::new(BinlogVersion::Version4, &file_data[..])?
+            .map(|ev| ev.map(|ev| ev.header().event_size() as usize))
+            .collect::<io::Result<Vec<_>>>()?;
+        let largest = sizes.iter().copied().max().unwrap();
+
+        let reader =
+            EventStreamReader::new(BinlogVersion::Version4).with_max_event_size(Some(largest - 1));
+        assert_eq!(reader.max_event_size(), Some(largest - 1));
+        let mut binlog_file = BinlogFile::with_reader(reader, &file_data[..])?;
+        for size in sizes.iter().take_while(|size| **size < largest) {
+            assert_eq!(
+                binlog_file.next().unwrap()?.header().event_size() as usize,
+                *size
+            );
+        }
+        let err = binlog_file.next().unwrap().unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+        Ok(())
+    }
+
+    #[test]
+    fn truncated_event_under_checksummed_fde() -> io::Result<()> {
+        // the last event of this file is a 23 byte stop event under a CRC32 format description
+        let file_data = std::fs::read("./test-data/binlogs/bug27213339-bin.000001")?;
+        let (events, stop_event) = file_data.split_at(file_data.len() - 23);
+        assert_eq!(stop_event[4], EventType::STOP_EVENT as u8);
+
+        for event_size in BinlogEventHeader::LEN..stop_event.len() {
+            let mut data = events.to_vec();
+            data.extend_from_slice(&stop_event[..event_size]);
+            LittleEndian::write_u32(&mut data[events.len() + 9..], event_size as u32);
+
+            let mut binlog_file = BinlogFile::new(BinlogVersion::Version4, &data[..])?;
+            let err = loop {
+                match binlog_file.next() {
+                    Some(Ok(_)) => (),
+                    Some(Err(err)) => break err,
+                    None => panic!("truncated event of {} bytes was accepted", event_size),
+                }
+            };
+            assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        }
+
+        Ok(())
+    }
+
+    #[cfg(feature = "zstd")]
+    #[test]
+    fn zstd_transaction_payload() -> io::Result<()> {
+        use super::{
+            consts::PayloadCompressionType,
+            events::{NoCompression, TransactionPayloadEvent, ZstdCompression},
+        };
+
+        let file_data = std::fs::read("./test-data/binlogs/binlog_transaction_with_GTID.000001")?;
+        let events = BinlogFile::new(BinlogVersion::Version4, &file_data[..])?
+            .collect::<io::Result<Vec<_>>>()?;
+        // `BEGIN`, `INSERT` and `COMMIT` query events
+        let transaction = &events[5..8];
+        let fde = transaction[0].fde();
+        let expected = TransactionPayloadEvent::pack(transaction, NoCompression)?;
+
+        // the same events compressed by the `zstd` command line utility
+        let compressed =
+            std::fs::read("./test-data/zstd/binlog_transaction_with_GTID.payload.zst")?;
+        let payload = TransactionPayloadEvent::new(
+            PayloadCompressionType::ZSTD,
+            expected.uncompressed_size(),
+            &compressed[..],
+        );
+        let unpacked = payload.unpack(fde, ZstdCompression::default())?;
+        assert_eq!(unpacked, expected.unpack(fde, NoCompression)?);
+        for (ev, orig) in unpacked.iter().zip(transaction) {
+            assert_eq!(ev.header().event_type_raw(), orig.header().event_type_raw());
+            assert_eq!(ev.data(), orig.data());
+        }
+
+        let packed = TransactionPayloadEvent::pack(transaction, ZstdCompression::default())?;
+        assert_eq!(packed.compression_type(), Ok(PayloadCompressionType::ZSTD));
+        assert_eq!(packed.uncompressed_size(), expected.uncompressed_size());
+        assert_eq!(packed.unpack(fde, ZstdCompression::new(19))?, unpacked);
+
+        let truncated = TransactionPayloadEvent::new(
+            PayloadCompressionType::ZSTD,
+            expected.uncompressed_size(),
+            &compressed[..compressed.len() - 1],
+        );
+        assert!(truncated.unpack(fde, ZstdCompression::default()).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn post_header_lengths() -> io::Result<()> {
+        let v4 = FormatDescriptionEvent::default_post_header_lengths(BinlogVersion::Version4);
+        assert_eq!(v4.len(), EventType::ENUM_END_EVENT as usize - 1);
+        assert_eq!(v4[EventType::QUERY_EVENT as usize - 1], 13);
+        assert_eq!(v4[EventType::ROTATE_EVENT as usize - 1], 8);
+        assert_eq!(v4[EventType::WRITE_ROWS_EVENT as usize - 1], 10);
+
+        let v1 = FormatDescriptionEvent::default_post_header_lengths(BinlogVersion::Version1);
+        assert_eq!(v1.len(), EventType::FORMAT_DESCRIPTION_EVENT as usize - 1);
+        assert_eq!(v1[EventType::QUERY_EVENT as usize - 1], 11);
+        assert_eq!(v1[EventType::ROTATE_EVENT as usize - 1], 0);
+        let v3 = FormatDescriptionEvent::default_post_header_lengths(BinlogVersion::Version3);
+        assert_eq!(v3[EventType::ROTATE_EVENT as usize - 1], 8);
+
+        let fde = FormatDescriptionEvent::new(BinlogVersion::Version4);
+        let custom = fde
+            .clone()
+            .with_event_type_header_length(EventType::XID_EVENT, 16);
+        assert_eq!(
+            custom.event_type_header_lengths().len(),
+            EventType::XID_EVENT as usize
+        );
+        assert_eq!(
+            custom.get_event_type_header_length(EventType::XID_EVENT),
+            16
+        );
+        assert_eq!(
+            custom.get_event_type_header_length(EventType::QUERY_EVENT),
+            13
+        );
+        for &event_type in &[EventType::QUERY_EVENT, EventType::TABLE_MAP_EVENT] {
+            assert_eq!(
+                fde.get_event_type_header_length(event_type),
+                v4[event_type as usize - 1]
+            );
+        }
+
+        // mismatched fde is detected while reading
+        let header = BinlogEventHeader::new(0, EventType::XID_EVENT, 1, 0, 0, EventFlags::empty());
+        let event =
+            Event::try_from_data(header, &EventData::XidEvent(XidEvent::new(1)), &fde, None)?;
+        let mut output = Vec::new();
+        event.write(BinlogVersion::Version4, &mut output)?;
+        assert_eq!(Event::read(&fde, &output[..])?.data(), event.data());
+        let err = Event::read(&custom, &output[..]).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        let err = Event::parse(&custom, &output[..]).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+        Ok(())
+    }
+
+    #[test]
+    fn raw_events() -> io::Result<()> {
+        let file_data = std::fs::read("./test-data/binlogs/binlog_transaction.000001")?;
+
+        let expected = BinlogFile::new(BinlogVersion::Version4, &file_data[..])?
+            .map(|ev| ev.map(|ev| ev.header()))
+            .collect::<io::Result<Vec<_>>>()?;
+
+        let mut binlog_file =
+            BinlogFile::new(BinlogVersion::Version4, io::Cursor::new(&file_data[..]))?;
+        let mut offset = BinlogFileHeader::LEN as u64;
+        let mut headers = Vec::new();
+        for ev in binlog_file.raw_events() {
+            let (header, body_offset, body_len) = ev?;
+            assert_eq!(body_offset, offset + BinlogEventHeader::LEN as u64);
+            assert_eq!(
+                body_len + BinlogEventHeader::LEN,
+                header.event_size() as usize
+            );
+            if header.log_pos() != 0 {
+                assert_eq!(header.log_pos() as u64, body_offset + body_len as u64);
+            }
+            offset = body_offset + body_len as u64;
+            headers.push(header);
+        }
+        assert_eq!(offset, file_data.len() as u64);
+        assert_eq!(headers, expected);
+
+        // the iterator is exhausted
+        assert!(binlog_file.raw_events().next().is_none());
+
+        // invalid event size
+        let mut invalid = file_data[..BinlogFileHeader::LEN + BinlogEventHeader::LEN].to_vec();
+        invalid[BinlogFileHeader::LEN + 9..][..4].copy_from_slice(&18_u32.to_le_bytes());
+        let mut binlog_file = BinlogFile::new(BinlogVersion::Version4, io::Cursor::new(invalid))?;
+        assert!(binlog_file.raw_events().next().unwrap().is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn start_pos() -> io::Result<()> {
+        let file_data = std::fs::read("./test-data/binlogs/binlog_transaction.000001")?;
+
+        let mut binlog_file = BinlogFile::new(BinlogVersion::Version4, &file_data[..])?;
+        let mut positions = vec![binlog_file.position()];
+        let mut events = Vec::new();
+        while let Some(ev) = binlog_file.next() {
+            events.push(ev?);
+            positions.push(binlog_file.position());
+        }
+        assert_eq!(positions[0], BinlogFileHeader::LEN as u64);
+        assert_eq!(binlog_file.position(), file_data.len() as u64);
+
+        for (i, &pos) in positions.iter().enumerate().skip(2) {
+            let binlog_file = BinlogFile::with_start_pos(
+                BinlogVersion::Version4,
+                io::Cursor::new(&file_data),
+                pos,
+            )?;
+            assert_eq!(binlog_file.position(), pos);
+            assert_eq!(
+                binlog_file.reader().get_fde(),
+                &events[0]
+                    .read_event::<FormatDescriptionEvent>()?
+                    .into_owned()
+                    .with_footer(events[0].footer())
+            );
+            let rest = binlog_file.collect::<io::Result<Vec<_>>>()?;
+            assert_eq!(rest, &events[i..]);
+        }
+
+        // positions up to the first event start from the beginning
+        for pos in 0..=BinlogFileHeader::LEN as u64 {
+            let binlog_file = BinlogFile::with_start_pos(
+                BinlogVersion::Version4,
+                io::Cursor::new(&file_data),
+                pos,
+            )?;
+            assert_eq!(binlog_file.position(), BinlogFileHeader::LEN as u64);
+            assert_eq!(binlog_file.count(), events.len());
+        }
+
+        // position within the format description event
+        let err = BinlogFile::with_start_pos(
+            BinlogVersion::Version4,
+            io::Cursor::new(&file_data),
+            positions[1] - 1,
+        )
+        .unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+
+        Ok(())
+    }
+
+    #[test]
+    fn column_attributes() -> io::Result<()> {
+        let file_data = std::fs::read("./test-data/binlogs/binlog-invisible-columns.000001")?;
+        let mut binlog_file = BinlogFile::new(BinlogVersion::Version4, &file_data[..])?;
+
+        let mut rows = 0;
+        while let Some(ev) = binlog_file.next() {
+            let ev = ev?;
+            if let Some(EventData::RowsEvent(data)) = ev.read_data()? {
+                let tme = binlog_file.reader().get_tme(data.table_id()).unwrap();
+                let generated = [(1, GeneratedColumn::Stored), (4, GeneratedColumn::Virtual)];
+                for row in data
+                    .rows(tme)
+                    .with_generated_columns(generated.iter().copied())
+                {
+                    let (before, after) = row?;
+                    for row in before.iter().chain(after.iter()) {
+                        let attrs = row.column_attributes();
+                        assert_eq!(attrs.len(), row.columns_ref().len());
+                        for attrs in attrs {
+                            assert_eq!(
+                                attrs.is_invisible(),
+                                [true, true, false, false, false, true][attrs.index()]
+                            );
+                            assert_eq!(
+                                attrs.generated(),
+                                generated.iter().find(|x| x.0 == attrs.index()).map(|x| x.1)
+                            );
+                        }
+                        rows += 1;
+                    }
+                }
+            }
+        }
+        assert!(rows > 0);
+
+        assert_eq!(
+            GeneratedColumn::from_extra("STORED GENERATED"),
+            Some(GeneratedColumn::Stored)
+        );
+        assert_eq!(
+            GeneratedColumn::from_extra("VIRTUAL GENERATED INVISIBLE"),
+            Some(GeneratedColumn::Virtual)
+        );
+        assert_eq!(GeneratedColumn::from_extra("auto_increment"), None);
+        assert_eq!(GeneratedColumn::from_extra(""), None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn enum_and_set_names() -> io::Result<()> {
+        let file_data = std::fs::read("./test-data/binlogs/mysql-enum-string-set.000001")?;
+        let mut binlog_file = BinlogFile::new(BinlogVersion::Version4, &file_data[..])?;
+
+        let mut decoded = Vec::new();
+        while let Some(ev) = binlog_file.next() {
+            let ev = ev?;
+            if let Some(EventData::RowsEvent(data)) = ev.read_data()? {
+                let tme = binlog_file.reader().get_tme(data.table_id()).unwrap();
+                for row in data.rows(tme).with_enum_and_set_names()? {
+                    let (before, after) = row?;
+                    for mut row in before.into_iter().chain(after) {
+                        let enum_value = row.take(2).unwrap().into_owned();
+                        let set_value = row.take(3).unwrap().into_owned();
+                        decoded.push((enum_value, set_value));
+                    }
+                }
+            }
+        }
+
+        let expected = |enum_value: &str, set_value: &str| {
+            (
+                BinlogValue::Value(enum_value.into()),
+                BinlogValue::Value(set_value.into()),
+            )
+        };
+        assert_eq!(
+            decoded,
+            vec![
+                // write
+                expected("var1", "one,three"),
+                // update
+                expected("var1", "one,three"),
+                expected("variant2", "two,four"),
+                // delete
+                expected("variant2", "two,four"),
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn table_map_cache() -> io::Result<()> {
+        let file_data = std::fs::read("./test-data/binlogs/write-full-row.binlog")?;
+        let mut binlog_file = BinlogFile::new(BinlogVersion::Version4, &file_data[..])?;
+
+        let mut stmt_end = None;
+        let mut rows_events = 0;
+        while let Some(ev) = binlog_file.next() {
+            let ev = ev?;
+            if let Some(table_id) = stmt_end.take() {
+                assert!(binlog_file.reader().table_map(table_id).is_none());
+            }
+            match ev.read_data()? {
+                Some(EventData::TableMapEvent(tme)) => {
+                    let cached = binlog_file.reader().table_map(tme.table_id());
+                    assert_eq!(cached, Some(&tme.into_owned()));
+                }
+                Some(EventData::RowsEvent(rows)) => {
+                    let tme = binlog_file.reader().table_map(rows.table_id()).unwrap();
+                    for row in rows.rows(tme) {
+                        row?;
+                    }
+                    if rows.flags().contains(RowsEventFlags::STMT_END) {
+                        stmt_end = Some(rows.table_id());
+                    }
+                    rows_events += 1;
+                }
+                Some(EventData::RotateEvent(_)) => {
+                    assert!(binlog_file.reader().table_map.is_empty());
+                }
+                _ => (),
+            }
+        }
+        assert!(rows_events > 0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn lazy_event() -> io::Result<()> {
+        let file_data = std::fs::read("./test-data/binlogs/binlog_transaction.000001")?;
+        let binlog_file = BinlogFile::new(BinlogVersion::Version4, &file_data[..])?;
+
+        let mut events = 0;
+        for ev in binlog_file {
+            let ev = ev?;
+            let expected = ev.read_data()?.map(EventData::into_owned);
+
+            let lazy = LazyEvent::from(ev.clone());
+            assert!(!lazy.is_parsed());
+            assert_eq!(lazy.header(), ev.header());
+            assert_eq!(lazy.clone().into_data()?, expected);
+            assert!(!lazy.is_parsed());
+
+            let data = lazy.data()?;
+            assert_eq!(data, expected.as_ref());
+            assert!(lazy.is_parsed());
+            // parsed data is cached
+            assert!(matches!(
+                (data, lazy.data()?),
+                (Some(x), Some(y)) if std::ptr::eq(x, y)
+            ));
+            assert_eq!(lazy.into_event(), ev);
+            events += 1;
+        }
+        assert!(events > 0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn parse_borrowed_events() -> io::Result<()> {
+        let file_data = std::fs::read("./test-data/binlogs/binlog_transaction.000001")?;
+        let expected = BinlogFile::new(BinlogVersion::Version4, &file_data[..])?
+            .collect::<io::Result<Vec<_>>>()?;
+
+        let mut fde = FormatDescriptionEvent::new(BinlogVersion::Version4);
+        let mut buf = &file_data[BinlogFileHeader::LEN..];
+        let mut events = Vec::new();
+        while !buf.is_empty() {
+            let ev = Event::parse(&fde, buf)?;
+            // event data is borrowed from the buffer
+            assert!(std::ptr::eq(
+                ev.raw_data().as_ptr(),
+                buf[BinlogEventHeader::LEN..].as_ptr()
+            ));
+            assert_eq!(ev.read_data()?, expected[events.len()].read_data()?);
+            buf = &buf[ev.header().event_size() as usize..];
+
+            let next_fde =
+                if ev.header().event_type_raw() == EventType::FORMAT_DESCRIPTION_EVENT as u8 {
+                    Some(
+                        ev.read_event::<FormatDescriptionEvent>()?
+                            .into_owned()
+                            .with_footer(ev.footer()),
+                    )
+                } else {
+                    None
+                };
+            events.push(ev.into_owned());
+            if let Some(next_fde) = next_fde {
+                fde = next_fde;
+            }
+        }
+        assert_eq!(events, expected);
+
+        // truncated buffer
+        let fde = FormatDescriptionEvent::new(BinlogVersion::Version4);
+        let truncated = &file_data[BinlogFileHeader::LEN..][..BinlogEventHeader::LEN + 1];
+        let err = Event::parse(&fde, truncated).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+
+        Ok(())
+    }
+
+    #[test]
+    fn parse_short_events() -> io::Result<()> {
+        let fde = FormatDescriptionEvent::new(BinlogVersion::Version4).with_footer(
+            BinlogEventFooter::new(BinlogChecksumAlg::BINLOG_CHECKSUM_ALG_CRC32),
+        );
+
+        let mut buf = Vec::new();
+        BinlogEventHeader::new(0, EventType::STOP_EVENT, 1, 0, 0, EventFlags::empty())
+            .serialize(&mut buf);
+        buf.extend_from_slice(&[0; BinlogEventFooter::BINLOG_CHECKSUM_LEN]);
+
+        // event size is less than the header size or doesn't fit the checksum
+        for event_size in (0..BinlogEventHeader::LEN).chain(BinlogEventHeader::LEN..buf.len()) {
+            LittleEndian::write_u32(&mut buf[9..], event_size as u32);
+            let err = Event::parse(&fde, &buf).unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::InvalidData, "{}", event_size);
+        }
+
+        let event_size = buf.len();
+        LittleEndian::write_u32(&mut buf[9..], event_size as u32);
+        let ev = Event::parse(&fde, &buf)?;
+        assert_eq!(ev.header().event_size() as usize, event_size);
+        assert_eq!(ev.read_data()?, Some(EventData::StopEvent(StopEvent)));
+
+        Ok(())
+    }
+
+    #[test]
+    fn zero_payload_events() -> io::Result<()> {
+        let file_data = std::fs::read("./test-data/binlogs/bug16266.000001")?;
+        let binlog_file = BinlogFile::new(BinlogVersion::Version4, &file_data[..])?;
+
+        let mut stop_events = 0;
+        for ev in binlog_file {
+            let ev = ev?;
+            if ev.header().event_type_raw() == EventType::STOP_EVENT as u8 {
+                assert_eq!(ev.read_event::<StopEvent>()?, StopEvent);
+                assert_eq!(ev.read_data()?, Some(EventData::StopEvent(StopEvent)));
+                let err = Event::try_from_data(
+                    ev.header(),
+                    &EventData::XidEvent(XidEvent::new(1)),
+                    ev.fde(),
+                    ev.footer().get_checksum_alg().unwrap(),
+                )
+                .unwrap_err();
+                assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+                stop_events += 1;
+            }
+        }
+        assert!(stop_events > 0);
+
+        let heartbeat = HeartbeatEvent::new(&b"master-bin.000001"[..]);
+        assert_eq!(heartbeat.log_ident(), "master-bin.000001");
+        assert_eq!(heartbeat.len(BinlogVersion::Version4), 17);
+        let mut output = Vec::new();
+        heartbeat.serialize(&mut output);
+        assert_eq!(output, b"master-bin.000001");
+
+        let ignorable = IgnorableEvent::new(&b"\x01\x02"[..]);
+        assert_eq!(ignorable.data(), b"\x01\x02");
+        assert_eq!(ignorable.len(BinlogVersion::Version4), 2);
+
+        Ok(())
+    }
+
+    #[test]
+    fn binlog_event_roundtrip() -> io::Result<()> {
+        const PATH: &str = "./test-data/binlogs";
+
+        let binlogs = std::fs::read_dir(PATH)?
+            .filter_map(|path| path.ok())
+            .map(|entry| entry.path())
+            .filter(|path| path.file_name().is_some());
+
+        'outer: for file_path in binlogs {
+            let file_data = std::fs::read(dbg!(&file_path))?;
+            let mut binlog_file = BinlogFile::new(BinlogVersion::Version4, &file_data[..])?;
+
+            let mut ev_pos = 4;
+            let mut table_map_events = HashMap::new();
+
+            while let Some(ev) = binlog_file.next() {
+                let ev = ev?;
+                let _ = dbg!(ev.header().event_type());
+                let ev_end = ev_pos + ev.header().event_size() as usize;
+                let binlog_version = binlog_file.reader.fde.binlog_version();
+
+                let mut output = Vec::new();
+                ev.checked_write(binlog_version, &mut output)?;
+
+                // raw event must be forwarded byte-identically
+                let mut raw = Vec::new();
+                ev.write_raw(&mut raw)?;
+                assert_eq!(raw, &file_data[ev_pos..ev_end]);
+                assert!(ev.raw_data().starts_with(ev.data()));
+
+                let event = match ev.read_data() {
+                    Ok(event) => {
+                        let event = match event {
+                            Some(e) => e,
+                            None => {
+                                if file_path.file_name().unwrap() == "mariadb-bin.000001" {
+                                    ev_pos = ev_end;
+                                    continue;
+                                } else {
+                                    dbg!(&ev);
+                                    panic!();
+                                }
+                            }
+                        };
+                        match event {
+                            EventData::TableMapEvent(ref ev) => {
+                                // store table maps for later use
+                                table_map_events.insert(ev.table_id(), ev.clone().into_owned());
+
+                                event
+                            }
+                            EventData::RowsEvent(ref rows_event) => {
+                                // iterate rows in a rows event
+                                let table_map_event =
+                                    binlog_file.reader().get_tme(rows_event.table_id()).unwrap();
+                                let mut prev_after: Option<crate::binlog::row::BinlogRow> = None;
+                                for row in rows_event.rows(table_map_event) {
+                                    let _row = row.unwrap();
+                                    if let (Some(prev), Some(after)) = (&prev_after, &_row.1) {
+                                        // column metadata must be shared between rows
+                                        assert!(prev.shares_columns_with(after));
+                                    }
+                                    prev_after = _row.1.clone();
+                                    if file_path.file_name().unwrap() == "mariadb-bin.000001" {
+                                        // should parse metadata for `binlog_row_metadata=FULL`
+                                        let after = _row.1.as_ref().unwrap();
+                                        let columns = after.columns_ref();
+
+                                        for col in columns.iter() {
+                                            assert_eq!(col.schema_ref(), b"toddy_test");
+                                            assert_eq!(col.table_ref(), b"outbox");
+                                            assert_eq!(col.org_table_ref(), b"outbox");
+                                        }
+
+                                        for (col, col_name) in columns.iter().zip([
+                                            "id",
+                                            "topic",
+                                            "event_type",
+                                            "event",
+                                            "created",
+                                        ]) {
+                                            assert_eq!(col.name_ref(), col_name.as_bytes());
+                                        }
+
+                                        for (col, f) in columns.iter().zip(
+                                            once(ColumnFlags::PRI_KEY_FLAG)
+                                                .chain(repeat(ColumnFlags::empty())),
+                                        ) {
+                                            assert_eq!(col.flags(), f);
+                                        }
+
+                                        for (col, charset) in columns.iter().zip([0, 45, 45, 63, 0])
+                                        {
+                                            assert_eq!(col.character_set(), charset);
+                                        }
+                                    }
+                                }
+
+                                event
+                            }
+                            _ => event,
                         }
                     }
                     Err(err)
@@ -861,9 +3652,12 @@ mod tests {
                 if file_path.file_name().unwrap() != "mariadb-bin.000001" {
                     assert_eq!(output, &file_data[ev_pos..ev_end]);
                 }
+                let checksum_alg = ev.footer().get_checksum_alg().unwrap();
+                assert_eq!(ev.serialized_len(checksum_alg), output.len());
 
                 output = Vec::new();
                 event.serialize(&mut output);
+                assert_eq!(event.serialized_len(binlog_version), output.len());
 
                 if matches!(event, EventData::UserVarEvent(_)) {
                     // Server may or may not write the flags field, but we will always write it.
@@ -883,6 +3677,15 @@ mod tests {
                     assert_eq!(&output[..GtidEvent::POST_HEADER_LENGTH], ev.data());
                 } else {
                     assert_eq!(output, ev.data());
+
+                    if file_path.file_name().unwrap() != "mariadb-bin.000001" {
+                        // parsed data must convert back into the same raw event
+                        let rebuilt =
+                            Event::try_from_data(ev.header(), &event, ev.fde(), checksum_alg)?;
+                        let mut raw = Vec::new();
+                        rebuilt.write_raw(&mut raw)?;
+                        assert_eq!(raw, &file_data[ev_pos..ev_end]);
+                    }
                 }
 
                 ev_pos = ev_end;
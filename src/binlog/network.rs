@@ -0,0 +1,423 @@
+// Copyright (c) 2023 Anatoly Ikorsky
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Framing of a binlog network stream (packets sent by the server after `COM_BINLOG_DUMP`).
+
+use std::{
+    borrow::Cow,
+    io::{self, ErrorKind::InvalidData},
+};
+
+use bytes::BufMut;
+
+use crate::{
+    constants::{CapabilityFlags, StatusFlags},
+    io::ParseBuf,
+    packets::{
+        ErrPacket, NetworkStreamTerminator, OkPacket, OkPacketDeserializer, OkPacketKind,
+        ServerError,
+    },
+    proto::{MyDeserialize, MySerialize},
+};
+
+use super::{
+    events::{BinlogEventHeader, Event},
+    semi_sync::SemiSyncPrefix,
+    EventStreamReader,
+};
+
+/// Header of a packet that carries a binlog event.
+const EVENT_PACKET_HEADER: u8 = 0x00;
+
+/// Header of an error packet.
+const ERR_PACKET_HEADER: u8 = 0xFF;
+
+/// EOF packet is shorter than this (a binlog event never is).
+const MAX_EOF_PACKET_LEN: usize = 9;
+
+/// Packet of a binlog network stream, i.e. a packet returned by the server
+/// after `COM_BINLOG_DUMP` or `COM_BINLOG_DUMP_GTID`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum BinlogNetworkEvent {
+    /// Binlog event.
+    Event {
+        /// The event, or `None` if it was skipped by the reader
+        /// (see [`EventStreamReader::read`]).
+        event: Option<Event<'static>>,
+        /// Semi-sync prefix of the event (if semi-sync replication is enabled).
+        semi_sync: Option<SemiSyncPrefix>,
+    },
+    /// End of the stream (sent if `BINLOG_DUMP_NON_BLOCK` flag was given).
+    Eof(OkPacket<'static>),
+    /// Error reported by the server. It terminates the stream.
+    Error(ServerError<'static>),
+}
+
+impl BinlogNetworkEvent {
+    /// Parses a packet of a binlog network stream dispatching a binlog event
+    /// into the given reader.
+    ///
+    /// `semi_sync` must be `true` if the replica enabled semi-sync replication
+    /// (see [`SemiSyncPrefix`]).
+    pub fn read(
+        reader: &mut EventStreamReader,
+        packet: &[u8],
+        semi_sync: bool,
+    ) -> io::Result<Self> {
+        match NetworkBinlogEventPacket::parse(packet, semi_sync)? {
+            NetworkBinlogEventPacket::Event { semi_sync, data } => {
+                Self::read_event(reader, &data, semi_sync)
+            }
+            NetworkBinlogEventPacket::Eof { .. } => {
+                let ok = OkPacketDeserializer::<NetworkStreamTerminator>::deserialize(
+                    CapabilityFlags::empty(),
+                    &mut ParseBuf(packet),
+                )?;
+                Ok(Self::Eof(ok.into_inner().into_owned()))
+            }
+            NetworkBinlogEventPacket::Error(err) => Ok(Self::Error(err.into_owned())),
+        }
+    }
+
+    /// Reads an event (its size is already checked).
+    fn read_event(
+        reader: &mut EventStreamReader,
+        data: &[u8],
+        semi_sync: Option<SemiSyncPrefix>,
+    ) -> io::Result<Self> {
+        let header = BinlogEventHeader::deserialize((), &mut ParseBuf(data))?;
+        reader.check_event_size(&header)?;
+        let event = if reader.skip_body(&header) {
+            None
+        } else {
+            let event = reader.read_body(header, &data[BinlogEventHeader::LEN..])?;
+            reader.accept(event)
+        };
+
+        Ok(Self::Event { event, semi_sync })
+    }
+
+    /// Returns the event (if any).
+    pub fn event(&self) -> Option<&Event<'static>> {
+        match self {
+            Self::Event { event, .. } => event.as_ref(),
+            _ => None,
+        }
+    }
+
+    /// Returns the event (if any).
+    pub fn into_event(self) -> Option<Event<'static>> {
+        match self {
+            Self::Event { event, .. } => event,
+            _ => None,
+        }
+    }
+
+    /// Returns `true` if the server waits for a semi-sync ACK packet for this event
+    /// (see [`super::semi_sync::ack_packet`]).
+    ///
+    /// Note that skipped events must be acknowledged as well.
+    pub fn needs_ack(&self) -> bool {
+        matches!(self, Self::Event { semi_sync: Some(prefix), .. } if prefix.needs_ack())
+    }
+}
+
+/// Framing of a packet of a binlog network stream (event bytes aren't interpreted).
+///
+/// It's shared by both sides of a binlog stream (e.g. by a relay): a replica parses packets
+/// using [`NetworkBinlogEventPacket::parse`], and a source writes them using [`MySerialize`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum NetworkBinlogEventPacket<'a> {
+    /// Binlog event following the OK byte.
+    Event {
+        /// Semi-sync prefix of the event (if semi-sync replication is enabled).
+        semi_sync: Option<SemiSyncPrefix>,
+        /// Serialized event (its header and footer included).
+        data: Cow<'a, [u8]>,
+    },
+    /// End of the stream (sent if `BINLOG_DUMP_NON_BLOCK` flag was given).
+    Eof {
+        /// Number of warnings.
+        warnings: u16,
+        /// Server status flags.
+        status_flags: StatusFlags,
+    },
+    /// Error reported by the server. It terminates the stream.
+    Error(ServerError<'a>),
+}
+
+impl<'a> NetworkBinlogEventPacket<'a> {
+    /// Creates an event packet for the given event.
+    pub fn from_event(event: &Event<'_>, semi_sync: Option<SemiSyncPrefix>) -> io::Result<Self> {
+        let mut data = Vec::with_capacity(event.header().event_size() as usize);
+        event.write_raw(&mut data)?;
+        Ok(Self::Event {
+            semi_sync,
+            data: Cow::Owned(data),
+        })
+    }
+
+    /// Parses a packet of a binlog network stream.
+    ///
+    /// `semi_sync` must be `true` if the replica enabled semi-sync replication
+    /// (see [`SemiSyncPrefix`]). Returns an `InvalidData` error if the size of an event
+    /// doesn't match the packet length.
+    pub fn parse(packet: &'a [u8], semi_sync: bool) -> io::Result<Self> {
+        match packet.first() {
+            Some(&EVENT_PACKET_HEADER) => {
+                let (semi_sync, data) = if semi_sync {
+                    let (prefix, data) = SemiSyncPrefix::split(&packet[1..])?;
+                    (Some(prefix), data)
+                } else {
+                    (None, &packet[1..])
+                };
+
+                let header = BinlogEventHeader::deserialize((), &mut ParseBuf(data))?;
+                if header.event_size() as usize != data.len() {
+                    return Err(io::Error::new(
+                        InvalidData,
+                        "event size doesn't match the packet length",
+                    ));
+                }
+
+                Ok(Self::Event {
+                    semi_sync,
+                    data: Cow::Borrowed(data),
+                })
+            }
+            Some(&ERR_PACKET_HEADER) => {
+                let err = ErrPacket::deserialize(CapabilityFlags::empty(), &mut ParseBuf(packet))?;
+                match err {
+                    ErrPacket::Error(err) => Ok(Self::Error(err)),
+                    ErrPacket::Progress(_) => unreachable!("progress reports are disabled"),
+                }
+            }
+            Some(&NetworkStreamTerminator::HEADER) if packet.len() < MAX_EOF_PACKET_LEN => {
+                let ok = OkPacketDeserializer::<NetworkStreamTerminator>::deserialize(
+                    CapabilityFlags::empty(),
+                    &mut ParseBuf(packet),
+                )?
+                .into_inner();
+                Ok(Self::Eof {
+                    warnings: ok.warnings(),
+                    status_flags: ok.status_flags(),
+                })
+            }
+            Some(header) => Err(io::Error::new(
+                InvalidData,
+                format!("unexpected binlog network packet header {:#04x}", header),
+            )),
+            None => Err(io::Error::new(InvalidData, "empty binlog network packet")),
+        }
+    }
+
+    /// Returns a `'static` version of `self`.
+    pub fn into_owned(self) -> NetworkBinlogEventPacket<'static> {
+        match self {
+            Self::Event { semi_sync, data } => NetworkBinlogEventPacket::Event {
+                semi_sync,
+                data: Cow::Owned(data.into_owned()),
+            },
+            Self::Eof {
+                warnings,
+                status_flags,
+            } => NetworkBinlogEventPacket::Eof {
+                warnings,
+                status_flags,
+            },
+            Self::Error(err) => NetworkBinlogEventPacket::Error(err.into_owned()),
+        }
+    }
+}
+
+impl MySerialize for NetworkBinlogEventPacket<'_> {
+    fn serialize(&self, buf: &mut Vec<u8>) {
+        match self {
+            Self::Event { semi_sync, data } => {
+                buf.put_u8(EVENT_PACKET_HEADER);
+                if let Some(prefix) = semi_sync {
+                    prefix.serialize(&mut *buf);
+                }
+                buf.put_slice(data);
+            }
+            Self::Eof {
+                warnings,
+                status_flags,
+            } => {
+                buf.put_u8(NetworkStreamTerminator::HEADER);
+                buf.put_u16_le(*warnings);
+                buf.put_u16_le(status_flags.bits());
+            }
+            Self::Error(err) => ErrPacket::Error(err.clone()).serialize(buf),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+
+    use super::{BinlogNetworkEvent, NetworkBinlogEventPacket};
+    use crate::{
+        binlog::{
+            consts::{BinlogVersion, EventType, SemiSyncFlags},
+            semi_sync::SemiSyncPrefix,
+            BinlogFile, BinlogFileHeader, EventStreamReader,
+        },
+        constants::StatusFlags,
+        packets::{ErrPacket, ServerError},
+        proto::MySerialize,
+    };
+
+    #[test]
+    fn should_read_binlog_network_packets() -> io::Result<()> {
+        let data = std::fs::read("./test-data/binlogs/write-full-row.binlog")?;
+        let expected =
+            BinlogFile::new(BinlogVersion::Version4, &data[..])?.collect::<io::Result<Vec<_>>>()?;
+
+        let mut packets = Vec::new();
+        let mut raw = &data[BinlogFileHeader::LEN..];
+        for event in &expected {
+            let (bytes, rest) = raw.split_at(event.header().event_size() as usize);
+            packets.push(bytes);
+            raw = rest;
+        }
+
+        let mut reader = EventStreamReader::new(BinlogVersion::Version4);
+        let mut semi_sync_reader = EventStreamReader::new(BinlogVersion::Version4);
+        for (i, (packet, event)) in packets.iter().zip(&expected).enumerate() {
+            let mut plain = vec![0x00];
+            plain.extend_from_slice(packet);
+            let read = BinlogNetworkEvent::read(&mut reader, &plain, false)?;
+            assert_eq!(read.event(), Some(event));
+            assert!(!read.needs_ack());
+
+            let flags = if i % 2 == 0 {
+                SemiSyncFlags::SEMI_SYNC_ACK_REQ
+            } else {
+                SemiSyncFlags::empty()
+            };
+            let mut prefixed = vec![0x00];
+            SemiSyncPrefix::new(flags).serialize(&mut prefixed);
+            prefixed.extend_from_slice(packet);
+            let read = BinlogNetworkEvent::read(&mut semi_sync_reader, &prefixed, true)?;
+            assert_eq!(read.needs_ack(), i % 2 == 0);
+            assert_eq!(read.into_event().as_ref(), Some(event));
+
+            // missing prefix
+            assert!(BinlogNetworkEvent::read(&mut reader, &plain, true).is_err());
+            // truncated event
+            assert!(
+                BinlogNetworkEvent::read(&mut reader, &plain[..plain.len() - 1], false).is_err()
+            );
+        }
+
+        // skipped events are still reported (they may need an ACK)
+        let mut reader = EventStreamReader::new(BinlogVersion::Version4);
+        reader.set_event_mask(&[EventType::FORMAT_DESCRIPTION_EVENT]);
+        let mut prefixed = vec![0x00];
+        SemiSyncPrefix::new(SemiSyncFlags::SEMI_SYNC_ACK_REQ).serialize(&mut prefixed);
+        prefixed.extend_from_slice(packets[1]);
+        let read = BinlogNetworkEvent::read(&mut reader, &prefixed, true)?;
+        assert!(read.needs_ack());
+        assert_eq!(read.event(), None);
+        assert_eq!(reader.log_pos(), expected[1].header().log_pos() as u64);
+
+        // EOF packet in non-blocking mode
+        let read = BinlogNetworkEvent::read(&mut reader, b"\xfe\x00\x00\x02\x00", false)?;
+        match read {
+            BinlogNetworkEvent::Eof(ok) => assert_eq!(ok.warnings(), 0),
+            other => panic!("unexpected {:?}", other),
+        }
+
+        let mut err = Vec::new();
+        ErrPacket::Error(ServerError::new(
+            1236,
+            *b"HY000",
+            &b"could not find binlog"[..],
+        ))
+        .serialize(&mut err);
+        match BinlogNetworkEvent::read(&mut reader, &err, false)? {
+            BinlogNetworkEvent::Error(err) => {
+                assert_eq!(err.error_code(), 1236);
+                assert_eq!(err.message_str(), "could not find binlog");
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+
+        assert!(BinlogNetworkEvent::read(&mut reader, b"", false).is_err());
+        assert!(BinlogNetworkEvent::read(&mut reader, b"\x01abc", false).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn should_frame_binlog_network_packets() -> io::Result<()> {
+        let data = std::fs::read("./test-data/binlogs/write-full-row.binlog")?;
+        let events =
+            BinlogFile::new(BinlogVersion::Version4, &data[..])?.collect::<io::Result<Vec<_>>>()?;
+
+        let mut reader = EventStreamReader::new(BinlogVersion::Version4);
+        let mut pos = BinlogFileHeader::LEN;
+        for (i, event) in events.iter().enumerate() {
+            let semi_sync = if i % 2 == 0 {
+                Some(SemiSyncPrefix::new(SemiSyncFlags::SEMI_SYNC_ACK_REQ))
+            } else {
+                None
+            };
+            let packet = NetworkBinlogEventPacket::from_event(event, semi_sync)?;
+            let mut bytes = Vec::new();
+            packet.serialize(&mut bytes);
+
+            let size = event.header().event_size() as usize;
+            assert_eq!(bytes[0], 0x00);
+            assert!(bytes.ends_with(&data[pos..pos + size]));
+            pos += size;
+
+            let parsed = NetworkBinlogEventPacket::parse(&bytes, semi_sync.is_some())?;
+            assert_eq!(parsed, packet);
+            let read = BinlogNetworkEvent::read(&mut reader, &bytes, semi_sync.is_some())?;
+            assert_eq!(read.event(), Some(event));
+        }
+
+        let eof = NetworkBinlogEventPacket::Eof {
+            warnings: 1,
+            status_flags: StatusFlags::SERVER_STATUS_AUTOCOMMIT,
+        };
+        let mut bytes = Vec::new();
+        eof.serialize(&mut bytes);
+        assert_eq!(bytes, b"\xfe\x01\x00\x02\x00");
+        assert_eq!(NetworkBinlogEventPacket::parse(&bytes, true)?, eof);
+        match BinlogNetworkEvent::read(&mut reader, &bytes, false)? {
+            BinlogNetworkEvent::Eof(ok) => assert_eq!(ok.warnings(), 1),
+            other => panic!("unexpected {:?}", other),
+        }
+
+        let err = NetworkBinlogEventPacket::Error(ServerError::new(
+            1236,
+            *b"HY000",
+            &b"could not find binlog"[..],
+        ));
+        let mut bytes = Vec::new();
+        err.serialize(&mut bytes);
+        assert_eq!(bytes[0], 0xff);
+        let parsed = NetworkBinlogEventPacket::parse(&bytes, false)?;
+        assert_eq!(parsed.into_owned(), err);
+
+        // event size doesn't match the packet length
+        let mut bytes = Vec::new();
+        NetworkBinlogEventPacket::Event {
+            semi_sync: None,
+            data: data[BinlogFileHeader::LEN..BinlogFileHeader::LEN + 30].into(),
+        }
+        .serialize(&mut bytes);
+        assert!(NetworkBinlogEventPacket::parse(&bytes, false).is_err());
+
+        Ok(())
+    }
+}
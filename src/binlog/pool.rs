@@ -0,0 +1,236 @@
+// Copyright (c) 2023 Anatoly Ikorsky
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Reusable buffers for event data.
+
+use std::{
+    fmt,
+    sync::{Arc, Mutex},
+};
+
+/// Source of buffers for event data (see [`super::EventStreamReader::with_buffer_pool`]).
+///
+/// Buffers are acquired to read an event and may be given back once the event
+/// (or a row decoded from it) is no longer needed, so that a high-throughput consumer
+/// doesn't hit the allocator for every event.
+pub trait EventBufferPool: Send + Sync {
+    /// Returns an empty buffer with at least the given capacity.
+    fn acquire(&self, capacity: usize) -> Vec<u8>;
+
+    /// Gives the given buffer back to the pool.
+    fn release(&self, buffer: Vec<u8>);
+}
+
+/// Pool that uses the global allocator for every buffer (the default).
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq, Hash)]
+pub struct GlobalBufferPool;
+
+impl EventBufferPool for GlobalBufferPool {
+    fn acquire(&self, capacity: usize) -> Vec<u8> {
+        Vec::with_capacity(capacity)
+    }
+
+    fn release(&self, _: Vec<u8>) {}
+}
+
+/// Pool that keeps released buffers for reuse.
+///
+/// At most `max_buffers` buffers are kept, and buffers with capacity greater than
+/// `max_capacity` are dropped on release, so that a single huge event doesn't pin memory.
+/// A buffer is acquired from the pool if its capacity is sufficient, otherwise
+/// the global allocator is used.
+#[derive(Debug, Default)]
+pub struct PooledBuffers {
+    max_buffers: usize,
+    max_capacity: usize,
+    buffers: Mutex<Vec<Vec<u8>>>,
+}
+
+impl PooledBuffers {
+    /// Creates an empty pool with the given limits.
+    pub fn new(max_buffers: usize, max_capacity: usize) -> Self {
+        Self {
+            max_buffers,
+            max_capacity,
+            buffers: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Returns the maximum number of buffers kept in the pool.
+    pub fn max_buffers(&self) -> usize {
+        self.max_buffers
+    }
+
+    /// Returns the maximum capacity of a buffer kept in the pool.
+    pub fn max_capacity(&self) -> usize {
+        self.max_capacity
+    }
+
+    /// Returns the number of buffers kept in the pool.
+    pub fn len(&self) -> usize {
+        self.buffers.lock().expect("poisoned").len()
+    }
+
+    /// Returns `true` if there are no buffers in the pool.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl EventBufferPool for PooledBuffers {
+    fn acquire(&self, capacity: usize) -> Vec<u8> {
+        let mut buffers = self.buffers.lock().expect("poisoned");
+        match buffers.iter().position(|x| x.capacity() >= capacity) {
+            Some(i) => buffers.swap_remove(i),
+            None => Vec::with_capacity(capacity),
+        }
+    }
+
+    fn release(&self, mut buffer: Vec<u8>) {
+        if buffer.capacity() == 0 || buffer.capacity() > self.max_capacity {
+            return;
+        }
+        let mut buffers = self.buffers.lock().expect("poisoned");
+        if buffers.len() < self.max_buffers {
+            buffer.clear();
+            buffers.push(buffer);
+        }
+    }
+}
+
+/// Shared handle to an [`EventBufferPool`].
+///
+/// Handles are equal if they refer to the same pool.
+#[derive(Clone)]
+pub struct BufferPool(Arc<dyn EventBufferPool>);
+
+impl BufferPool {
+    /// Creates a handle to the given pool.
+    pub fn new<P: EventBufferPool + 'static>(pool: P) -> Self {
+        Self(Arc::new(pool))
+    }
+
+    /// Creates a handle to the given shared pool.
+    pub fn from_arc(pool: Arc<dyn EventBufferPool>) -> Self {
+        Self(pool)
+    }
+}
+
+impl EventBufferPool for BufferPool {
+    fn acquire(&self, capacity: usize) -> Vec<u8> {
+        self.0.acquire(capacity)
+    }
+
+    fn release(&self, buffer: Vec<u8>) {
+        self.0.release(buffer)
+    }
+}
+
+impl Default for BufferPool {
+    fn default() -> Self {
+        Self::new(GlobalBufferPool)
+    }
+}
+
+impl PartialEq for BufferPool {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for BufferPool {}
+
+impl fmt::Debug for BufferPool {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("BufferPool").finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{io, sync::Arc};
+
+    use super::{BufferPool, EventBufferPool, GlobalBufferPool, PooledBuffers};
+    use crate::binlog::{
+        consts::BinlogVersion,
+        events::{Event, EventData},
+        BinlogFile, EventStreamReader,
+    };
+
+    #[test]
+    fn should_reuse_buffers() {
+        let pool = PooledBuffers::new(2, 1024);
+        let buf = pool.acquire(100);
+        assert!(buf.is_empty() && buf.capacity() >= 100);
+        let ptr = buf.as_ptr();
+        pool.release(buf);
+        assert_eq!(pool.len(), 1);
+
+        let buf = pool.acquire(50);
+        assert_eq!(buf.as_ptr(), ptr);
+        assert!(pool.is_empty());
+        pool.release(buf);
+
+        // too large
+        pool.release(Vec::with_capacity(2048));
+        pool.release(Vec::new());
+        assert_eq!(pool.len(), 1);
+        // too many
+        pool.release(Vec::with_capacity(10));
+        pool.release(Vec::with_capacity(10));
+        assert_eq!(pool.len(), 2);
+        // not enough capacity
+        assert!(pool.acquire(512).capacity() >= 512);
+        assert_eq!(pool.len(), 2);
+
+        assert!(GlobalBufferPool.acquire(10).capacity() >= 10);
+
+        let handle = BufferPool::default();
+        assert_eq!(handle, handle.clone());
+        assert_ne!(handle, BufferPool::default());
+    }
+
+    #[test]
+    fn should_read_events_into_pooled_buffers() -> io::Result<()> {
+        let data = std::fs::read("./test-data/binlogs/write-full-row.binlog")?;
+        let expected =
+            BinlogFile::new(BinlogVersion::Version4, &data[..])?.collect::<io::Result<Vec<_>>>()?;
+
+        let pooled = Arc::new(PooledBuffers::new(4, 1024 * 1024));
+        let reader = EventStreamReader::new(BinlogVersion::Version4)
+            .with_buffer_pool(BufferPool::from_arc(pooled.clone()));
+        let mut binlog_file = BinlogFile::with_reader(reader, &data[..])?;
+
+        let mut count = 0;
+        while let Some(event) = binlog_file.next() {
+            let event = event?;
+            assert_eq!(event, expected[count]);
+            count += 1;
+
+            if let Some(EventData::RowsEvent(rows)) = event.read_data()? {
+                let tme = binlog_file.reader().get_tme(rows.table_id()).unwrap();
+                for row in rows.rows(tme) {
+                    let (before, after) = row?;
+                    for row in before.into_iter().chain(after) {
+                        row.recycle(&*pooled);
+                    }
+                }
+            }
+
+            binlog_file.reader().recycle(event);
+            assert!(!pooled.is_empty());
+        }
+        assert_eq!(count, expected.len());
+
+        let pool = binlog_file.reader().buffer_pool();
+        let event = Event::read_with_buffer_pool(expected[0].fde(), &data[4..], pool)?;
+        assert_eq!(event.raw_data(), expected[0].raw_data());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,330 @@
+// Copyright (c) 2023 Anatoly Ikorsky
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Projection of decoded rows onto a subset of table columns.
+
+use std::io;
+
+use super::events::{OptionalMetaExtractor, TableMapEvent};
+
+/// Source of column names of tables (e.g. `information_schema.COLUMNS`).
+///
+/// It's needed if column names are not in the optional metadata of table map events
+/// (i.e. if `binlog_row_metadata` is `MINIMAL`).
+pub trait SchemaProvider {
+    /// Returns names of columns of the given table in the order of the table definition,
+    /// or `None` if the table is unknown.
+    fn column_names(&self, schema: &[u8], table: &[u8]) -> Option<Vec<Vec<u8>>>;
+}
+
+/// Set of table columns to decode (see [`super::events::RowsEventRows::with_projection`]).
+///
+/// Column names are resolved to column indexes once, so that rows are projected
+/// without name lookups. Names are compared case-insensitively (as MySQL does).
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct Projection {
+    /// Indexes of projected columns within the table (in the requested order).
+    indexes: Vec<usize>,
+    /// Position within the projection of every column of the table.
+    positions: Vec<Option<usize>>,
+}
+
+impl Projection {
+    /// Creates a projection of the given columns of a table given by their index.
+    ///
+    /// Duplicates are ignored.
+    pub fn from_indexes(indexes: impl IntoIterator<Item = usize>) -> Self {
+        let mut projection = Self {
+            indexes: Vec::new(),
+            positions: Vec::new(),
+        };
+        for index in indexes {
+            if projection.position(index).is_some() {
+                continue;
+            }
+            if projection.positions.len() <= index {
+                projection.positions.resize(index + 1, None);
+            }
+            projection.positions[index] = Some(projection.indexes.len());
+            projection.indexes.push(index);
+        }
+        projection
+    }
+
+    /// Creates a projection of the requested columns given names of all columns of a table
+    /// (in the order of the table definition).
+    ///
+    /// Returns an `InvalidInput` error if a requested column is not in the table.
+    pub fn from_column_names<T, U>(
+        table_columns: impl IntoIterator<Item = T>,
+        requested: impl IntoIterator<Item = U>,
+    ) -> io::Result<Self>
+    where
+        T: AsRef<[u8]>,
+        U: AsRef<[u8]>,
+    {
+        let table_columns = table_columns.into_iter().collect::<Vec<_>>();
+        let mut indexes = Vec::new();
+        for name in requested {
+            let name = name.as_ref();
+            let index = table_columns
+                .iter()
+                .position(|x| x.as_ref().eq_ignore_ascii_case(name))
+                .ok_or_else(|| {
+                    io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        format!("unknown column `{}`", String::from_utf8_lossy(name)),
+                    )
+                })?;
+            indexes.push(index);
+        }
+        Ok(Self::from_indexes(indexes))
+    }
+
+    /// Creates a projection of the requested columns of the table described by the given
+    /// table map event using column names of its optional metadata.
+    ///
+    /// Returns an `InvalidInput` error if a requested column is not in the table,
+    /// or if the event has no column names (see [`Projection::from_schema_provider`]).
+    pub fn from_table_map<U: AsRef<[u8]>>(
+        tme: &TableMapEvent<'_>,
+        requested: impl IntoIterator<Item = U>,
+    ) -> io::Result<Self> {
+        let extractor = OptionalMetaExtractor::new(tme.iter_optional_meta())?;
+        let names = extractor
+            .iter_column_name()
+            .map(|x| x.map(|x| x.name_raw().to_vec()))
+            .collect::<io::Result<Vec<_>>>()?;
+        if names.is_empty() && tme.columns_count() > 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "table map event has no column names (see `binlog_row_metadata`)",
+            ));
+        }
+        Self::from_column_names(names, requested)
+    }
+
+    /// Creates a projection of the requested columns of the table described by the given
+    /// table map event using column names given by the schema provider.
+    ///
+    /// Returns a `NotFound` error if the table is unknown to the provider,
+    /// an `InvalidData` error if the provider doesn't agree with the event on the number
+    /// of columns, and an `InvalidInput` error if a requested column is not in the table.
+    pub fn from_schema_provider<U: AsRef<[u8]>>(
+        provider: &dyn SchemaProvider,
+        tme: &TableMapEvent<'_>,
+        requested: impl IntoIterator<Item = U>,
+    ) -> io::Result<Self> {
+        let names = provider
+            .column_names(tme.database_name_raw(), tme.table_name_raw())
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::NotFound,
+                    format!(
+                        "unknown table `{}`.`{}`",
+                        tme.database_name(),
+                        tme.table_name()
+                    ),
+                )
+            })?;
+        if names.len() as u64 != tme.columns_count() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "number of columns doesn't match the table map event",
+            ));
+        }
+        Self::from_column_names(names, requested)
+    }
+
+    /// Returns indexes of projected columns within the table (in the requested order).
+    pub fn indexes(&self) -> &[usize] {
+        &self.indexes
+    }
+
+    /// Returns the number of projected columns.
+    pub fn len(&self) -> usize {
+        self.indexes.len()
+    }
+
+    /// Returns `true` if no columns are projected.
+    pub fn is_empty(&self) -> bool {
+        self.indexes.is_empty()
+    }
+
+    /// Returns the position within the projection of a table column with the given index,
+    /// or `None` if the column isn't projected.
+    pub fn position(&self, index: usize) -> Option<usize> {
+        self.positions.get(index).copied().flatten()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashMap, io};
+
+    use super::{Projection, SchemaProvider};
+    use crate::{
+        binlog::{
+            consts::BinlogVersion,
+            events::{EventData, RowsEventData},
+            value::BinlogValue,
+            BinlogFile,
+        },
+        value::Value,
+    };
+
+    /// Column names by `schema.table`.
+    struct Tables(HashMap<Vec<u8>, Vec<Vec<u8>>>);
+
+    impl SchemaProvider for Tables {
+        fn column_names(&self, schema: &[u8], table: &[u8]) -> Option<Vec<Vec<u8>>> {
+            self.0.get(&[schema, b".", table].concat()).cloned()
+        }
+    }
+
+    #[test]
+    fn should_build_projection() -> io::Result<()> {
+        let projection = Projection::from_indexes([3, 1, 3]);
+        assert_eq!(projection.indexes(), [3, 1]);
+        assert_eq!(projection.position(1), Some(1));
+        assert_eq!(projection.position(3), Some(0));
+        assert_eq!(projection.position(2), None);
+        assert_eq!(projection.position(100), None);
+
+        let projection = Projection::from_column_names(["id", "name", "age"], ["AGE", "id"])?;
+        assert_eq!(projection.indexes(), [2, 0]);
+        assert_eq!(
+            Projection::from_column_names(["id"], ["x"])
+                .unwrap_err()
+                .kind(),
+            io::ErrorKind::InvalidInput
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn should_decode_projected_rows() -> io::Result<()> {
+        let data = std::fs::read("./test-data/binlogs/mariadb-bin.000001")?;
+        let mut binlog_file = BinlogFile::new(BinlogVersion::Version4, &data[..])?;
+
+        let mut count = 0;
+        while let Some(event) = binlog_file.next() {
+            let event = event?;
+            let rows_event = match event.read_data()? {
+                Some(EventData::RowsEvent(rows_event)) => rows_event,
+                _ => continue,
+            };
+            let tme = binlog_file.reader().get_tme(rows_event.table_id()).unwrap();
+
+            let projection = Projection::from_table_map(tme, ["event_type", "id"])?;
+            let mut tables = HashMap::new();
+            tables.insert(
+                b"toddy_test.outbox".to_vec(),
+                ["id", "topic", "event_type", "event", "created"]
+                    .iter()
+                    .map(|x| x.as_bytes().to_vec())
+                    .collect(),
+            );
+            let provider = Tables(tables);
+            assert_eq!(
+                Projection::from_schema_provider(&provider, tme, ["event_type", "id"])?,
+                projection
+            );
+            assert_eq!(
+                Projection::from_schema_provider(&Tables(HashMap::new()), tme, ["id"])
+                    .unwrap_err()
+                    .kind(),
+                io::ErrorKind::NotFound
+            );
+
+            let full = rows_event.rows(tme).collect::<io::Result<Vec<_>>>()?;
+            let projected = rows_event
+                .rows_projected(tme, &projection)
+                .collect::<io::Result<Vec<_>>>()?;
+            assert_eq!(full.len(), projected.len());
+
+            for (full, projected) in full.into_iter().zip(projected) {
+                let images = vec![(full.0, projected.0), (full.1, projected.1)];
+                for (full, projected) in images {
+                    let (full, projected) = match (full, projected) {
+                        (Some(full), Some(projected)) => (full, projected),
+                        (None, None) => continue,
+                        _ => panic!("image mismatch"),
+                    };
+                    assert_eq!(projected.len(), 2);
+                    assert_eq!(projected.columns_ref()[0].name_ref(), b"event_type");
+                    assert_eq!(projected.columns_ref()[1].name_ref(), b"id");
+                    assert_eq!(projected.as_ref(0), full.as_ref(2));
+                    assert_eq!(projected.as_ref(1), full.as_ref(0));
+                    assert_eq!(projected.column_attributes()[0].index(), 2);
+                    assert!(matches!(
+                        projected.as_ref(1),
+                        Some(BinlogValue::Value(Value::Int(_) | Value::UInt(_)))
+                    ));
+                    count += 1;
+                }
+            }
+
+            if let RowsEventData::WriteRowsEvent(_) = rows_event {
+                let empty = Projection::from_indexes([]);
+                for row in rows_event.rows_projected(tme, &empty) {
+                    assert_eq!(row?.1.unwrap().len(), 0);
+                }
+            }
+        }
+        assert!(count > 0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn should_skip_values_of_other_columns() -> io::Result<()> {
+        let mut count = 0;
+        for entry in std::fs::read_dir("./test-data/binlogs")? {
+            let data = std::fs::read(entry?.path())?;
+            let mut binlog_file = BinlogFile::new(BinlogVersion::Version4, &data[..])?;
+            while let Some(event) = binlog_file.next() {
+                let event = event?;
+                // some files contain broken events on purpose
+                let rows_event = match event.read_data() {
+                    Ok(Some(EventData::RowsEvent(rows_event))) => rows_event,
+                    _ => continue,
+                };
+                let tme = binlog_file.reader().get_tme(rows_event.table_id()).unwrap();
+                // every other column in the reverse order
+                let projection =
+                    Projection::from_indexes((0..tme.columns_count() as usize).rev().step_by(2));
+
+                let full = rows_event.rows(tme);
+                let projected = rows_event.rows_projected(tme, &projection);
+                for (full, projected) in full.zip(projected) {
+                    let (full, projected) = (full?, projected?);
+                    let images = vec![(full.0, projected.0), (full.1, projected.1)];
+                    for (full, projected) in images.into_iter().filter_map(|(a, b)| a.zip(b)) {
+                        let expected = full
+                            .column_attributes()
+                            .iter()
+                            .enumerate()
+                            .filter_map(|(i, x)| Some((projection.position(x.index())?, i)))
+                            .collect::<std::collections::BTreeMap<_, _>>();
+                        assert_eq!(projected.len(), expected.len());
+                        for (j, i) in expected.into_values().enumerate() {
+                            assert_eq!(projected.as_ref(j), full.as_ref(i));
+                            assert_eq!(projected.columns_ref()[j], full.columns_ref()[i]);
+                        }
+                        count += 1;
+                    }
+                }
+            }
+        }
+        assert!(count > 0);
+
+        Ok(())
+    }
+}
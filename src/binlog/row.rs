@@ -13,7 +13,9 @@ use std::{
     sync::Arc,
 };
 
+use base64::{engine::general_purpose::STANDARD, Engine};
 use bitvec::{prelude::BitVec, slice::BitSlice};
+use serde_json::{Map, Value as Json};
 
 use crate::{
     constants::{ColumnFlags, ColumnType},
@@ -22,14 +24,24 @@ use crate::{
     packets::Column,
     proto::MyDeserialize,
     row::{new_row_raw, Row},
-    value::Value,
+    value::{
+        geometry::{Geometry, GeometryError},
+        json::value_to_json,
+        Value,
+    },
 };
 
 use super::{
     events::{OptionalMetaExtractor, TableMapEvent},
-    value::{BinlogValue, BinlogValueToValueError},
+    jsonb::JsonbToJsonError,
+    pool::EventBufferPool,
+    projection::Projection,
+    value::{skip_value, BinlogValue, BinlogValueToValueError},
 };
 
+/// Id of the `binary` character set.
+const BINARY_CHARSET: u16 = 63;
+
 /// Binlog rows event row value options.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 #[allow(non_camel_case_types)]
@@ -61,16 +73,126 @@ impl TryFrom<u64> for BinlogRowValueOptions {
     }
 }
 
+/// Kind of a generated column.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GeneratedColumn {
+    /// Values are computed when read (`VIRTUAL`).
+    Virtual,
+    /// Values are computed when written and stored (`STORED`).
+    Stored,
+}
+
+impl GeneratedColumn {
+    /// Parses the `EXTRA` column of `information_schema.COLUMNS`
+    /// (e.g. `VIRTUAL GENERATED` or `STORED GENERATED INVISIBLE`).
+    ///
+    /// Returns `None` if the column isn't generated.
+    pub fn from_extra(extra: &str) -> Option<Self> {
+        let mut words = extra.split_ascii_whitespace();
+        if !words.clone().any(|x| x.eq_ignore_ascii_case("GENERATED")) {
+            return None;
+        }
+        if words.any(|x| x.eq_ignore_ascii_case("STORED") || x.eq_ignore_ascii_case("PERSISTENT")) {
+            Some(Self::Stored)
+        } else {
+            Some(Self::Virtual)
+        }
+    }
+}
+
+/// Attributes of a column of a binlog row that aren't covered by [`Column`].
+///
+/// Visibility comes from the optional metadata of the table map event
+/// (see [`OptionalMetaExtractor::iter_column_visibility`]). Binlog doesn't describe
+/// generated columns, so this attribute must be given by the caller
+/// (see [`super::events::RowsEventRows::with_generated_columns`]).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ColumnAttributes {
+    index: usize,
+    invisible: bool,
+    generated: Option<GeneratedColumn>,
+}
+
+impl ColumnAttributes {
+    /// Creates attributes of a visible non-generated column with the given index.
+    pub fn new(index: usize) -> Self {
+        Self {
+            index,
+            invisible: false,
+            generated: None,
+        }
+    }
+
+    /// Returns modified `self` with the given visibility.
+    pub fn with_invisible(mut self, invisible: bool) -> Self {
+        self.invisible = invisible;
+        self
+    }
+
+    /// Returns modified `self` with the given kind of a generated column.
+    pub fn with_generated(mut self, generated: Option<GeneratedColumn>) -> Self {
+        self.generated = generated;
+        self
+    }
+
+    /// Returns the index of the column within the table.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Returns `true` if the column is invisible (`false` if unknown).
+    pub fn is_invisible(&self) -> bool {
+        self.invisible
+    }
+
+    /// Returns the kind of a generated column (`None` if the column isn't generated
+    /// or if it's unknown).
+    pub fn generated(&self) -> Option<GeneratedColumn> {
+        self.generated
+    }
+
+    /// Returns `true` if the column is known to be generated.
+    ///
+    /// Values of such columns must be omitted from `INSERT` and `UPDATE` statements.
+    pub fn is_generated(&self) -> bool {
+        self.generated.is_some()
+    }
+}
+
+/// Columns and column attributes shared by rows of a rows event.
+pub(crate) type SharedColumns = (Arc<[Column]>, Arc<[ColumnAttributes]>);
+
 /// Representation of a binlog row.
 #[derive(Clone, PartialEq)]
 pub struct BinlogRow {
     values: Vec<Option<BinlogValue<'static>>>,
     columns: Arc<[Column]>,
+    attributes: Arc<[ColumnAttributes]>,
 }
 
 impl BinlogRow {
+    /// Creates a new row.
+    ///
+    /// Columns are considered visible and non-generated, and their table indexes
+    /// are their indexes within the row (see [`BinlogRow::with_column_attributes`]).
     pub fn new(values: Vec<Option<BinlogValue<'static>>>, columns: Arc<[Column]>) -> Self {
-        Self { values, columns }
+        let attributes = (0..columns.len()).map(ColumnAttributes::new).collect();
+        Self {
+            values,
+            columns,
+            attributes,
+        }
+    }
+
+    /// Returns modified `self` with the given column attributes (one per column).
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of attributes doesn't match the number of columns.
+    pub fn with_column_attributes(mut self, attributes: Arc<[ColumnAttributes]>) -> Self {
+        assert_eq!(attributes.len(), self.columns.len());
+        self.attributes = attributes;
+        self
     }
 
     /// Returns length of a row.
@@ -93,6 +215,22 @@ impl BinlogRow {
         self.columns.clone()
     }
 
+    /// Returns attributes of columns of this row (see [`ColumnAttributes`]).
+    pub fn column_attributes(&self) -> &[ColumnAttributes] {
+        &self.attributes
+    }
+
+    /// Marks the given columns of the table as generated.
+    ///
+    /// `generated` is indexed by the column index within the table.
+    pub(crate) fn mark_generated(&mut self, generated: &[Option<GeneratedColumn>]) {
+        let mut attributes = self.attributes.to_vec();
+        for attrs in attributes.iter_mut() {
+            attrs.generated = generated.get(attrs.index).copied().flatten();
+        }
+        self.attributes = attributes.into();
+    }
+
     /// Returns reference to the value of a column with index `index` if it exists and wasn't taken
     /// by `Row::take` method.
     ///
@@ -101,6 +239,49 @@ impl BinlogRow {
         self.values.get(index).and_then(|x| x.as_ref())
     }
 
+    /// Parses the value of a `GEOMETRY` column with index `index` (see [`Geometry`]).
+    ///
+    /// Returns `None` if the column is not a `GEOMETRY` column, or if its value is `NULL`
+    /// or was taken by `Row::take` method.
+    pub fn geometry(&self, index: usize) -> Option<Result<Geometry, GeometryError>> {
+        if self.columns.get(index)?.column_type() != ColumnType::MYSQL_TYPE_GEOMETRY {
+            return None;
+        }
+        match self.as_ref(index)? {
+            BinlogValue::Value(Value::Bytes(bytes)) => Some(Geometry::from_geometry_bytes(bytes)),
+            _ => None,
+        }
+    }
+
+    /// Returns the value of a `BIT` column with index `index` as an integer
+    /// (the column value itself is the big-endian bytes representation).
+    ///
+    /// Returns `None` if the column is not a `BIT` column, or if its value is `NULL`
+    /// or was taken by `Row::take` method.
+    pub fn bit(&self, index: usize) -> Option<u64> {
+        if self.columns.get(index)?.column_type() != ColumnType::MYSQL_TYPE_BIT {
+            return None;
+        }
+        match self.as_ref(index)? {
+            BinlogValue::Value(Value::Bytes(bytes)) if bytes.len() <= 8 => Some(
+                bytes
+                    .iter()
+                    .fold(0_u64, |acc, byte| (acc << 8) | u64::from(*byte)),
+            ),
+            _ => None,
+        }
+    }
+
+    /// Gives buffers of byte values of this row back to the given pool
+    /// (see [`EventBufferPool`]), e.g. once the row is applied.
+    pub fn recycle(self, pool: &dyn EventBufferPool) {
+        for value in self.values.into_iter().flatten() {
+            if let BinlogValue::Value(Value::Bytes(bytes)) = value {
+                pool.release(bytes);
+            }
+        }
+    }
+
     /// Will take value of a column with index `index` if it exists and wasn't taken earlier then
     /// will converts it to `T`.
     pub fn take(&mut self, index: usize) -> Option<BinlogValue> {
@@ -119,10 +300,156 @@ impl BinlogRow {
             .collect()
     }
 
+    /// Returns `true` if this row shares column metadata with the `other` row
+    /// (i.e. both hold the same `Arc<[Column]>`).
+    pub fn shares_columns_with(&self, other: &BinlogRow) -> bool {
+        Arc::ptr_eq(&self.columns, &other.columns)
+    }
+
+    /// Returns columns and column attributes of this row to be shared with subsequent rows
+    /// (see [`BinlogRow::deserialize_projected`]).
+    pub(crate) fn shared_columns(&self) -> SharedColumns {
+        (self.columns.clone(), self.attributes.clone())
+    }
+
+    /// Converts this row into a JSON object keyed by column name (see [`Row::to_json`]).
+    ///
+    /// Values of binary columns (`binary` charset, `BIT` and `GEOMETRY`) are always encoded
+    /// as base64 strings. Partial JSON updates are represented as arrays
+    /// of `{"op", "path", "value"}` objects.
+    pub fn to_json(&self) -> Result<Map<String, Json>, JsonbToJsonError> {
+        let mut object = Map::new();
+        for (value, column) in self.values.iter().zip(self.columns.iter()) {
+            let value = match value {
+                Some(value) => value,
+                None => continue,
+            };
+            let json = match value {
+                BinlogValue::Value(Value::Bytes(bytes)) if is_binary_column(column) => {
+                    Json::String(STANDARD.encode(bytes))
+                }
+                BinlogValue::Value(value) => value_to_json(value),
+                BinlogValue::Jsonb(value) => Json::try_from(value.clone())?,
+                BinlogValue::JsonDiff(diffs) => {
+                    let mut ops = Vec::with_capacity(diffs.len());
+                    for diff in diffs {
+                        let mut op = Map::new();
+                        op.insert("op".into(), format!("{:?}", diff.operation()).into());
+                        op.insert("path".into(), diff.path_str().into_owned().into());
+                        let value = match diff.value() {
+                            Some(value) => Json::try_from(value.clone())?,
+                            None => Json::Null,
+                        };
+                        op.insert("value".into(), value);
+                        ops.push(Json::Object(op));
+                    }
+                    Json::Array(ops)
+                }
+            };
+            object.insert(column.name_str().into_owned(), json);
+        }
+        Ok(object)
+    }
+
     #[doc(hidden)]
     pub fn place(&mut self, index: usize, value: BinlogValue<'static>) {
         self.values[index] = Some(value);
     }
+
+    /// Replaces ENUM and SET values of this row with their string representations.
+    pub(crate) fn decode_enum_and_set(&mut self, names: &EnumAndSetNames) {
+        for (i, value) in self.values.iter_mut().enumerate() {
+            let column_type = self.columns[i].column_type();
+            let variants = match names.get(self.attributes[i].index) {
+                Some(variants) => variants,
+                None => continue,
+            };
+            let decoded = match (column_type, value.as_ref()) {
+                (ColumnType::MYSQL_TYPE_ENUM, Some(BinlogValue::Value(Value::Int(index)))) => {
+                    match *index {
+                        // the empty string is stored for invalid values
+                        0 => Some(Vec::new()),
+                        index => usize::try_from(index - 1)
+                            .ok()
+                            .and_then(|x| variants.get(x))
+                            .cloned(),
+                    }
+                }
+                (ColumnType::MYSQL_TYPE_SET, Some(BinlogValue::Value(Value::Bytes(bitmap)))) => {
+                    let bits = BitSlice::<u8>::from_slice(bitmap);
+                    if bits.iter_ones().all(|x| x < variants.len()) {
+                        let mut decoded = Vec::new();
+                        for x in bits.iter_ones() {
+                            if !decoded.is_empty() {
+                                decoded.push(b',');
+                            }
+                            decoded.extend_from_slice(&variants[x]);
+                        }
+                        Some(decoded)
+                    } else {
+                        None
+                    }
+                }
+                _ => None,
+            };
+            if let Some(decoded) = decoded {
+                *value = Some(BinlogValue::Value(Value::Bytes(decoded)));
+            }
+        }
+    }
+}
+
+/// Variant names of ENUM and SET columns of a table
+/// (taken from the optional metadata of a table map event).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct EnumAndSetNames(Vec<Option<Vec<Vec<u8>>>>);
+
+impl EnumAndSetNames {
+    /// Collects variant names of ENUM and SET columns of the given table.
+    ///
+    /// Columns are left without names if the optional metadata doesn't have them
+    /// (i.e. if `binlog_row_metadata` isn't `FULL`).
+    pub(crate) fn new(tme: &TableMapEvent<'_>) -> io::Result<Self> {
+        let extractor = OptionalMetaExtractor::new(tme.iter_optional_meta())?;
+        let mut enum_values = extractor.iter_enum_str_value();
+        let mut set_values = extractor.iter_set_str_value();
+
+        let mut names = Vec::with_capacity(tme.columns_count() as usize);
+        for i in 0..tme.columns_count() as usize {
+            let variants = match tme.get_column_type(i) {
+                Ok(Some(ColumnType::MYSQL_TYPE_ENUM)) => enum_values
+                    .next()
+                    .transpose()?
+                    .map(|x| x.values().iter().map(|x| x.value_raw().to_vec()).collect()),
+                Ok(Some(ColumnType::MYSQL_TYPE_SET)) => set_values
+                    .next()
+                    .transpose()?
+                    .map(|x| x.values().iter().map(|x| x.value_raw().to_vec()).collect()),
+                _ => None,
+            };
+            names.push(variants);
+        }
+
+        Ok(Self(names))
+    }
+
+    /// Returns variant names of the given column of the table.
+    fn get(&self, index: usize) -> Option<&[Vec<u8>]> {
+        self.0.get(index)?.as_deref()
+    }
+
+    /// Returns `true` if there are no names for any column.
+    pub(crate) fn is_empty(&self) -> bool {
+        self.0.iter().all(Option::is_none)
+    }
+}
+
+/// Returns `true` if values of the given column are not textual.
+fn is_binary_column(column: &Column) -> bool {
+    match column.column_type() {
+        ColumnType::MYSQL_TYPE_BIT | ColumnType::MYSQL_TYPE_GEOMETRY => true,
+        ty => ty.is_character_type() && column.character_set() == BINARY_CHARSET,
+    }
 }
 
 impl<'de> MyDeserialize<'de> for BinlogRow {
@@ -136,12 +463,29 @@ impl<'de> MyDeserialize<'de> for BinlogRow {
     /// * corresponding table map event
     type Ctx = (u64, &'de BitSlice<u8>, bool, &'de TableMapEvent<'de>);
 
-    fn deserialize(
-        (num_columns, cols, have_shared_image, table_info): Self::Ctx,
+    fn deserialize(ctx: Self::Ctx, buf: &mut ParseBuf<'de>) -> io::Result<Self> {
+        Self::deserialize_projected(ctx, None, None, buf)
+    }
+}
+
+impl BinlogRow {
+    /// Deserializes a row image keeping only columns of the given projection (if any).
+    ///
+    /// Values of other columns are skipped without decoding. Projected columns are ordered
+    /// as in the projection.
+    ///
+    /// Columns are taken from `shared` (if given) instead of being built from the table map,
+    /// so it must come from a previous row of the same image of the same rows event.
+    pub(crate) fn deserialize_projected<'de>(
+        (num_columns, cols, have_shared_image, table_info): <Self as MyDeserialize<'de>>::Ctx,
+        projection: Option<&Projection>,
+        shared: Option<&SharedColumns>,
         buf: &mut ParseBuf<'de>,
     ) -> io::Result<Self> {
+        let mut positions = vec![];
         let mut values: Vec<Option<BinlogValue<'static>>> = vec![];
         let mut columns = vec![];
+        let mut attributes = vec![];
 
         // read a shared image if needed (see WL#2955)
         let mut partial_cols = if have_shared_image {
@@ -174,47 +518,80 @@ impl<'de> MyDeserialize<'de> for BinlogRow {
         let mut enum_and_set_charset_iter = opt_meta_extractor.iter_enum_and_set_charset();
         let mut primary_key_iter = opt_meta_extractor.iter_primary_key();
         let mut column_name_iter = opt_meta_extractor.iter_column_name();
+        let mut visibility_iter = opt_meta_extractor.iter_column_visibility();
 
         for i in 0..(num_columns as usize) {
+            // Optional metadata and partial JSON bits are given for every column of the table,
+            // so the corresponding iterators are advanced even if column isn't in the row.
+            let column_type = table_info.get_column_type(i);
+
+            // TableMapEvent must define column type for the current column.
+            let column_type = match column_type {
+                Ok(Some(ty)) => ty,
+                Ok(None) => {
+                    return Err(io::Error::new(io::ErrorKind::InvalidData, "No column type"))
+                }
+                Err(e) => return Err(io::Error::new(io::ErrorKind::InvalidData, e)),
+            };
+
+            let is_partial = column_type == ColumnType::MYSQL_TYPE_JSON
+                && partial_cols
+                    .as_mut()
+                    .and_then(|bits| bits.next().as_deref().copied())
+                    .unwrap_or(false);
+
+            let is_unsigned = column_type
+                .is_numeric_type()
+                .then(|| signedness_iterator.next())
+                .flatten()
+                .unwrap_or_default();
+
+            let charset = if column_type.is_character_type() {
+                charset_iter.next().transpose()?.unwrap_or_default()
+            } else if column_type.is_enum_or_set_type() {
+                enum_and_set_charset_iter
+                    .next()
+                    .transpose()?
+                    .unwrap_or_default()
+            } else {
+                Default::default()
+            };
+
+            let column_name_raw = column_name_iter.next().transpose()?;
+
+            let is_primary_key = primary_key_iter
+                .next_if(|next| next.is_err() || next.as_ref().ok() == Some(&(i as u64)))
+                .transpose()?
+                .is_some();
+
+            let is_invisible = visibility_iter.next().map(|x| !x).unwrap_or(false);
+
             // check if column is in columns list
-            if cols.get(i).as_deref().copied().unwrap_or(false) {
-                let column_type = table_info.get_column_type(i);
-
-                // TableMapEvent must define column type for the current column.
-                let column_type = match column_type {
-                    Ok(Some(ty)) => ty,
-                    Ok(None) => {
-                        return Err(io::Error::new(io::ErrorKind::InvalidData, "No column type"))
+            if !cols.get(i).as_deref().copied().unwrap_or(false) {
+                continue;
+            }
+
+            let column_meta = table_info.get_column_metadata(i).unwrap_or(&[]);
+            let is_null = null_bitmap
+                .get(image_idx)
+                .as_deref()
+                .copied()
+                .unwrap_or(true);
+            image_idx += 1;
+
+            if let Some(projection) = projection {
+                match projection.position(i) {
+                    Some(position) => positions.push(position),
+                    None => {
+                        if !is_null {
+                            skip_value(column_type, column_meta, buf)?;
+                        }
+                        continue;
                     }
-                    Err(e) => return Err(io::Error::new(io::ErrorKind::InvalidData, e)),
-                };
-
-                let column_meta = table_info.get_column_metadata(i).unwrap_or(&[]);
-
-                let is_partial = column_type == ColumnType::MYSQL_TYPE_JSON
-                    && partial_cols
-                        .as_mut()
-                        .and_then(|bits| bits.next().as_deref().copied())
-                        .unwrap_or(false);
-
-                let is_unsigned = column_type
-                    .is_numeric_type()
-                    .then(|| signedness_iterator.next())
-                    .flatten()
-                    .unwrap_or_default();
-
-                let charset = if column_type.is_character_type() {
-                    charset_iter.next().transpose()?.unwrap_or_default()
-                } else if column_type.is_enum_or_set_type() {
-                    enum_and_set_charset_iter
-                        .next()
-                        .transpose()?
-                        .unwrap_or_default()
-                } else {
-                    Default::default()
-                };
-
-                let column_name_raw = column_name_iter.next().transpose()?;
+                }
+            }
+
+            if shared.is_none() {
                 let column_name = column_name_raw
                     .as_ref()
                     .map(|x| Cow::Borrowed(x.name_raw()))
@@ -229,10 +606,7 @@ impl<'de> MyDeserialize<'de> for BinlogRow {
                     column_flags |= ColumnFlags::UNSIGNED_FLAG;
                 }
 
-                if let Some(_) = primary_key_iter
-                    .next_if(|next| next.is_err() || next.as_ref().ok() == Some(&(i as u64)))
-                    .transpose()?
-                {
+                if is_primary_key {
                     column_flags |= ColumnFlags::PRI_KEY_FLAG;
                 }
 
@@ -247,26 +621,51 @@ impl<'de> MyDeserialize<'de> for BinlogRow {
                     .with_character_set(charset);
 
                 columns.push(column);
+                attributes.push(ColumnAttributes::new(i).with_invisible(is_invisible));
+            }
 
-                // check if column is null
-                if null_bitmap
-                    .get(image_idx)
-                    .as_deref()
-                    .copied()
-                    .unwrap_or(true)
-                {
-                    values.push(Some(BinlogValue::Value(Value::NULL)));
-                } else {
-                    let ctx = (column_type, column_meta, is_unsigned, is_partial);
-                    values.push(Some(buf.parse::<BinlogValue>(ctx)?.into_owned()));
-                }
-
-                image_idx += 1;
+            if is_null {
+                values.push(Some(BinlogValue::Value(Value::NULL)));
+            } else {
+                let ctx = (column_type, column_meta, is_unsigned, is_partial);
+                values.push(Some(buf.parse::<BinlogValue>(ctx)?.into_owned()));
             }
         }
 
-        Ok(BinlogRow::new(values, columns.into_boxed_slice().into()))
+        if projection.is_some() {
+            // columns are decoded in the table order
+            values = sort_by_positions(&positions, values);
+            columns = sort_by_positions(&positions, columns);
+            attributes = sort_by_positions(&positions, attributes);
+        }
+
+        let (columns, attributes) = match shared {
+            Some((columns, attributes)) => (columns.clone(), attributes.clone()),
+            None => (columns.into(), attributes.into()),
+        };
+        if columns.len() != values.len() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "shared columns don't match the row image",
+            ));
+        }
+
+        Ok(BinlogRow {
+            values,
+            columns,
+            attributes,
+        })
+    }
+}
+
+/// Reorders `items` decoded in the table order by their positions in a projection.
+fn sort_by_positions<T>(positions: &[usize], items: Vec<T>) -> Vec<T> {
+    if items.is_empty() {
+        return items;
     }
+    let mut items = positions.iter().copied().zip(items).collect::<Vec<_>>();
+    items.sort_by_key(|(position, _)| *position);
+    items.into_iter().map(|(_, item)| item).collect()
 }
 
 impl fmt::Debug for BinlogRow {
@@ -0,0 +1,179 @@
+// Copyright (c) 2023 Anatoly Ikorsky
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Semi-synchronous replication framing of a binlog network stream.
+
+use std::io;
+
+use crate::{
+    io::ParseBuf,
+    misc::raw::{int::ConstU8, RawFlags},
+    packets::SemiSyncAckPacket,
+    proto::{MyDeserialize, MySerialize},
+};
+
+use super::{consts::SemiSyncFlags, EventStreamReader};
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, thiserror::Error)]
+#[error("Invalid semi-sync indicator")]
+pub struct InvalidSemiSyncIndicator;
+
+/// Magic byte of a semi-sync prefix.
+pub type SemiSyncIndicator = ConstU8<InvalidSemiSyncIndicator, 0xEF>;
+
+/// Prefix of a binlog event sent to a semi-sync replica (`0xEF <flags>`).
+///
+/// If `rpl_semi_sync_slave_enabled` is set (and the replica announced it
+/// via `@rpl_semi_sync_slave`), every event of a binlog network stream is prefixed
+/// with this structure (after the leading OK byte). It can't be detected reliably,
+/// so it must be parsed only if semi-sync replication is enabled.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub struct SemiSyncPrefix {
+    indicator: SemiSyncIndicator,
+    flags: RawFlags<SemiSyncFlags, u8>,
+}
+
+impl SemiSyncPrefix {
+    /// Length of the prefix.
+    pub const LEN: usize = 2;
+
+    /// Creates a new prefix.
+    pub fn new(flags: SemiSyncFlags) -> Self {
+        Self {
+            indicator: SemiSyncIndicator::new(),
+            flags: RawFlags::new(flags.bits()),
+        }
+    }
+
+    /// Splits the given packet payload (the leading OK byte excluded)
+    /// into the prefix and the event bytes.
+    ///
+    /// Returns an `InvalidData` error if the payload doesn't start with a semi-sync prefix.
+    pub fn split(payload: &[u8]) -> io::Result<(Self, &[u8])> {
+        let mut buf = ParseBuf(payload);
+        let prefix = buf.parse::<Self>(())?;
+        Ok((prefix, buf.0))
+    }
+
+    /// Returns the flags (unknown bits are truncated).
+    pub fn flags(&self) -> SemiSyncFlags {
+        self.flags.get()
+    }
+
+    /// Returns the raw flags value.
+    pub fn flags_raw(&self) -> u8 {
+        self.flags.bits()
+    }
+
+    /// Returns `true` if the source waits for an ACK packet for this event
+    /// (see [`ack_packet`]).
+    pub fn needs_ack(&self) -> bool {
+        self.flags().contains(SemiSyncFlags::SEMI_SYNC_ACK_REQ)
+    }
+}
+
+impl<'de> MyDeserialize<'de> for SemiSyncPrefix {
+    const SIZE: Option<usize> = Some(Self::LEN);
+    type Ctx = ();
+
+    fn deserialize((): Self::Ctx, buf: &mut ParseBuf<'de>) -> io::Result<Self> {
+        Ok(Self {
+            indicator: buf.parse_unchecked(())?,
+            flags: buf.parse_unchecked(())?,
+        })
+    }
+}
+
+impl MySerialize for SemiSyncPrefix {
+    fn serialize(&self, buf: &mut Vec<u8>) {
+        self.indicator.serialize(&mut *buf);
+        self.flags.serialize(&mut *buf);
+    }
+}
+
+/// Returns the ACK packet for the last event read by the given reader,
+/// i.e. the packet that acknowledges the current binlog position.
+///
+/// Returns `None` if the binlog file name isn't known yet (see [`EventStreamReader::log_file`]).
+pub fn ack_packet(reader: &EventStreamReader) -> Option<SemiSyncAckPacket<'_>> {
+    let log_file = reader.log_file()?;
+    Some(SemiSyncAckPacket::new(
+        reader.log_pos(),
+        log_file.as_bytes(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+
+    use super::{ack_packet, SemiSyncPrefix};
+    use crate::{
+        binlog::{
+            consts::{BinlogVersion, SemiSyncFlags},
+            events::RotateEvent,
+            EventStreamReader,
+        },
+        io::ParseBuf,
+        packets::SemiSyncAckPacket,
+        proto::{MyDeserialize, MySerialize},
+    };
+
+    #[test]
+    fn semi_sync_prefix() -> io::Result<()> {
+        let (prefix, event) = SemiSyncPrefix::split(&[0xEF, 0x01, 0xAA, 0xBB])?;
+        assert!(prefix.needs_ack());
+        assert_eq!(
+            prefix,
+            SemiSyncPrefix::new(SemiSyncFlags::SEMI_SYNC_ACK_REQ)
+        );
+        assert_eq!(event, &[0xAA, 0xBB]);
+
+        let (prefix, event) = SemiSyncPrefix::split(&[0xEF, 0x00])?;
+        assert!(!prefix.needs_ack());
+        assert!(event.is_empty());
+
+        let mut buf = Vec::new();
+        SemiSyncPrefix::new(SemiSyncFlags::empty()).serialize(&mut buf);
+        assert_eq!(buf, [0xEF, 0x00]);
+
+        assert_eq!(
+            SemiSyncPrefix::split(&[0x00, 0x01]).unwrap_err().kind(),
+            io::ErrorKind::InvalidData
+        );
+        assert_eq!(
+            SemiSyncPrefix::split(&[0xEF]).unwrap_err().kind(),
+            io::ErrorKind::UnexpectedEof
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn semi_sync_ack_packet() -> io::Result<()> {
+        let mut reader = EventStreamReader::new(BinlogVersion::Version4);
+        assert!(ack_packet(&reader).is_none());
+
+        reader.apply_rotate(&RotateEvent::new(4, &b"binlog.000002"[..]))?;
+
+        let ack = ack_packet(&reader).unwrap();
+        assert_eq!(ack, SemiSyncAckPacket::new(4, &b"binlog.000002"[..]));
+
+        let mut buf = Vec::new();
+        ack.serialize(&mut buf);
+        assert_eq!(buf[0], 0xEF);
+        assert_eq!(&buf[1..9], &4_u64.to_le_bytes());
+        assert_eq!(&buf[9..], b"binlog.000002");
+        assert_eq!(
+            SemiSyncAckPacket::deserialize((), &mut ParseBuf(&buf))?,
+            ack
+        );
+
+        Ok(())
+    }
+}
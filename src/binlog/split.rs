@@ -0,0 +1,311 @@
+// Copyright (c) 2023 Anatoly Ikorsky
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Splitting of a binlog file into standalone binlog files.
+
+use std::{
+    convert::TryFrom,
+    io::{self, Write},
+    ops::Range,
+    thread,
+};
+
+use super::{
+    consts::{BinlogVersion, EventType},
+    merge::classify_event,
+    BinlogFile, BinlogFileHeader, EventStreamReader,
+};
+
+/// Splits a binlog file into standalone binlog files at transaction boundaries.
+///
+/// Every part starts with the binlog file header and a copy of the format description event
+/// of the source file (the first part is the beginning of the file itself), so it can be read
+/// on its own. `log_pos` of every event is recalculated (see [`super::events::Event::with_log_pos`]).
+///
+/// Parts are balanced by size, but never split a transaction (see [`super::merge::EventMerger`]
+/// for the definition of a transaction), so there may be fewer parts than requested.
+/// An incomplete transaction at the end of the file goes into the last part,
+/// whereas a truncated event is dropped.
+///
+/// ```
+/// # use mysql_common::binlog::split::BinlogSplitter;
+/// # fn main() -> std::io::Result<()> {
+/// let data = std::fs::read("./test-data/binlogs/binlog_transaction_with_GTID.000001")?;
+/// let parts = BinlogSplitter::new(2).with_parallel(true).split(&data)?;
+/// assert_eq!(parts.len(), 2);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub struct BinlogSplitter {
+    parts: usize,
+    parallel: bool,
+}
+
+impl BinlogSplitter {
+    /// Creates a splitter that splits a file into (at most) the given number of parts
+    /// sequentially.
+    pub fn new(parts: usize) -> Self {
+        Self {
+            parts,
+            parallel: false,
+        }
+    }
+
+    /// Returns modified `self` that writes parts in parallel (a thread per part).
+    pub fn with_parallel(mut self, parallel: bool) -> Self {
+        self.parallel = parallel;
+        self
+    }
+
+    /// Returns the requested number of parts.
+    pub fn parts(&self) -> usize {
+        self.parts
+    }
+
+    /// Returns `true` if parts are written in parallel.
+    pub fn parallel(&self) -> bool {
+        self.parallel
+    }
+
+    /// Returns byte ranges of the given binlog file covered by each part.
+    ///
+    /// The first range starts right after the binlog file header (the format description
+    /// event included), other ranges start at a transaction boundary.
+    ///
+    /// Returns an `InvalidInput` error if the requested number of parts is zero,
+    /// and an `InvalidData` error if the file doesn't start with a format description event.
+    pub fn split_points(&self, data: &[u8]) -> io::Result<Vec<Range<usize>>> {
+        self.plan(data).map(|(_, ranges)| ranges)
+    }
+
+    /// Returns the end offset of the format description event and ranges of parts.
+    fn plan(&self, data: &[u8]) -> io::Result<(usize, Vec<Range<usize>>)> {
+        if self.parts == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "number of parts must be positive",
+            ));
+        }
+
+        let (fde_end, boundaries, end) = scan(data)?;
+        let total = end - fde_end;
+
+        let mut starts = vec![BinlogFileHeader::LEN];
+        let mut boundaries = boundaries.into_iter().peekable();
+        for i in 1..self.parts {
+            let target = fde_end + total * i / self.parts;
+            let last = *starts.last().expect("should be here");
+            while let Some(&pos) = boundaries.peek() {
+                if pos >= target && pos > last && pos < end {
+                    break;
+                }
+                boundaries.next();
+            }
+            match boundaries.next() {
+                Some(pos) if pos < end => starts.push(pos),
+                _ => break,
+            }
+        }
+
+        let mut ends = starts.iter().skip(1).copied().collect::<Vec<_>>();
+        ends.push(end);
+        let ranges = starts.into_iter().zip(ends).map(|(s, e)| s..e).collect();
+        Ok((fde_end, ranges))
+    }
+
+    /// Splits the given binlog file into in-memory binlog files.
+    pub fn split(&self, data: &[u8]) -> io::Result<Vec<Vec<u8>>> {
+        self.split_into(data, |_| Ok(Vec::new()))
+    }
+
+    /// Splits the given binlog file writing each part into the output
+    /// created by `make_output` for the part index.
+    ///
+    /// Returns outputs in the order of parts.
+    pub fn split_into<W, F>(&self, data: &[u8], make_output: F) -> io::Result<Vec<W>>
+    where
+        W: Write + Send,
+        F: Fn(usize) -> io::Result<W> + Sync,
+    {
+        let (fde_end, ranges) = self.plan(data)?;
+        let fde = &data[BinlogFileHeader::LEN..fde_end];
+
+        let write_part = |(i, range): (usize, Range<usize>)| -> io::Result<W> {
+            let mut output = make_output(i)?;
+            write_part(&mut output, fde, &data[range], i == 0)?;
+            Ok(output)
+        };
+
+        if self.parallel {
+            thread::scope(|scope| {
+                let handles = ranges
+                    .into_iter()
+                    .enumerate()
+                    .map(|part| scope.spawn(move || write_part(part)))
+                    .collect::<Vec<_>>();
+                handles
+                    .into_iter()
+                    .map(|handle| handle.join().expect("split thread panicked"))
+                    .collect()
+            })
+        } else {
+            ranges.into_iter().enumerate().map(write_part).collect()
+        }
+    }
+}
+
+/// Returns the end offset of the format description event, offsets of transaction boundaries
+/// that follow it and the end offset of the last complete event.
+fn scan(data: &[u8]) -> io::Result<(usize, Vec<usize>, usize)> {
+    let mut binlog_file = BinlogFile::new(BinlogVersion::Version4, data)?;
+    match binlog_file.next().transpose()? {
+        Some(event)
+            if event.header().event_type_raw() == EventType::FORMAT_DESCRIPTION_EVENT as u8 => {}
+        _ => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "binlog file doesn't start with a format description event",
+            ))
+        }
+    }
+    let fde_end = binlog_file.position() as usize;
+
+    let mut boundaries = Vec::new();
+    let mut in_group = false;
+    let mut began = false;
+    let mut end = fde_end;
+    while let Some(event) = binlog_file.next().transpose()? {
+        end = binlog_file.position() as usize;
+        let (starts, ends) = classify_event(&event, in_group, &mut began)?;
+        in_group = in_group || starts.is_some();
+        if !in_group || ends {
+            in_group = false;
+            began = false;
+            boundaries.push(end);
+        }
+    }
+
+    Ok((fde_end, boundaries, end))
+}
+
+/// Writes a single part. `events` are events of the part (the format description event
+/// included if `is_first`).
+fn write_part<W: Write>(
+    output: &mut W,
+    fde: &[u8],
+    events: &[u8],
+    is_first: bool,
+) -> io::Result<()> {
+    let mut reader = EventStreamReader::new(BinlogVersion::Version4);
+    BinlogFileHeader.write(BinlogVersion::Version4, &mut *output)?;
+    let mut log_pos = BinlogFileHeader::LEN as u64;
+
+    let mut fde = fde;
+    let fde = reader.read(&mut fde)?;
+    if !is_first {
+        fde.write_raw(&mut *output)?;
+        log_pos += fde.header().event_size() as u64;
+    }
+
+    let mut events = events;
+    while !events.is_empty() {
+        let event = reader.read(&mut events)?;
+        log_pos += event.header().event_size() as u64;
+        let event = if event.header().log_pos() != 0 {
+            let pos = u32::try_from(log_pos)
+                .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "log position overflow"))?;
+            event.with_log_pos(pos)
+        } else {
+            event
+        };
+        event.write_raw(&mut *output)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+
+    use super::BinlogSplitter;
+    use crate::binlog::{
+        consts::{BinlogVersion, EventType},
+        events::Event,
+        BinlogFile, BinlogFileHeader,
+    };
+
+    fn read_events(data: &[u8]) -> io::Result<Vec<Event<'static>>> {
+        BinlogFile::new(BinlogVersion::Version4, data)?.collect()
+    }
+
+    #[test]
+    fn should_split_binlog_file() -> io::Result<()> {
+        let data = std::fs::read("./test-data/binlogs/binlog_transaction.000001")?;
+        let events = read_events(&data)?;
+
+        let splitter = BinlogSplitter::new(4);
+        let ranges = splitter.split_points(&data)?;
+        assert_eq!(ranges.len(), 4);
+        assert_eq!(ranges[0].start, BinlogFileHeader::LEN);
+        assert_eq!(ranges[3].end, data.len());
+        assert!(ranges.windows(2).all(|x| x[0].end == x[1].start));
+
+        let parts = splitter.split(&data)?;
+        assert_eq!(parts, splitter.with_parallel(true).split(&data)?);
+        assert_eq!(parts.len(), 4);
+
+        let mut split_events = Vec::new();
+        for (i, part) in parts.iter().enumerate() {
+            let part_events = read_events(part)?;
+            let mut log_pos = BinlogFileHeader::LEN as u32;
+            for event in &part_events {
+                log_pos += event.header().event_size();
+                assert_eq!(event.header().log_pos(), log_pos);
+                event.validate_consistency()?;
+            }
+            assert_eq!(log_pos as usize, part.len());
+
+            assert_eq!(
+                part_events[0].header().event_type(),
+                Ok(EventType::FORMAT_DESCRIPTION_EVENT)
+            );
+            let skip = if i == 0 { 0 } else { 1 };
+            split_events.extend(part_events.into_iter().skip(skip));
+        }
+
+        assert_eq!(split_events.len(), events.len());
+        for (split, original) in split_events.iter().zip(&events) {
+            assert_eq!(
+                split.header().event_type_raw(),
+                original.header().event_type_raw()
+            );
+            assert_eq!(split.data(), original.data());
+        }
+
+        // not enough transactions
+        let parts = BinlogSplitter::new(10_000).split(&data)?;
+        assert!(parts.len() < 10_000);
+        assert_eq!(
+            parts
+                .iter()
+                .map(|x| read_events(x).map(|x| x.len()))
+                .sum::<io::Result<usize>>()?,
+            events.len() + parts.len() - 1
+        );
+
+        assert_eq!(BinlogSplitter::new(1).split(&data)?, vec![data.clone()]);
+        assert_eq!(
+            BinlogSplitter::new(0).split(&data).unwrap_err().kind(),
+            io::ErrorKind::InvalidInput
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,717 @@
+// Copyright (c) 2023 Anatoly Ikorsky
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Pluggable sources of binlog files.
+//!
+//! [`BinlogStorage`] abstracts a set of binlog files, so that the same reading logic
+//! (see [`StorageEvents`] and [`GtidLocator::from_storage`]) works for the local filesystem
+//! (see [`FsStorage`]) as well as for user-defined backends (e.g. object stores).
+//!
+//! [`BinlogDirectory`] reads events of all binlog files of a local directory in order
+//! of its index file (see [`BinlogIndexFile`]).
+
+use std::{
+    fs::File,
+    io::{self, BufReader, Read, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+    vec,
+};
+
+use super::{
+    consts::BinlogVersion, events::Event, gtid_locator::GtidLocator, BinlogFile, BinlogFileHeader,
+    EventStreamReader,
+};
+
+/// A binlog file opened from a [`BinlogStorage`].
+pub trait StorageFile {
+    /// Returns the current size of the file.
+    fn size(&self) -> io::Result<u64>;
+
+    /// Reads bytes starting at the given offset.
+    ///
+    /// Returns the number of bytes read (`0` at the end of the file).
+    fn read_at(&self, offset: u64, buf: &mut [u8]) -> io::Result<usize>;
+}
+
+impl StorageFile for File {
+    fn size(&self) -> io::Result<u64> {
+        Ok(self.metadata()?.len())
+    }
+
+    #[cfg(unix)]
+    fn read_at(&self, offset: u64, buf: &mut [u8]) -> io::Result<usize> {
+        std::os::unix::fs::FileExt::read_at(self, buf, offset)
+    }
+
+    #[cfg(windows)]
+    fn read_at(&self, offset: u64, buf: &mut [u8]) -> io::Result<usize> {
+        std::os::windows::fs::FileExt::seek_read(self, buf, offset)
+    }
+}
+
+impl StorageFile for Vec<u8> {
+    fn size(&self) -> io::Result<u64> {
+        Ok(self.len() as u64)
+    }
+
+    fn read_at(&self, offset: u64, buf: &mut [u8]) -> io::Result<usize> {
+        let data = self.get(offset as usize..).unwrap_or_default();
+        let len = data.len().min(buf.len());
+        buf[..len].copy_from_slice(&data[..len]);
+        Ok(len)
+    }
+}
+
+/// A set of binlog files.
+pub trait BinlogStorage {
+    type File: StorageFile;
+
+    /// Returns names of available binlog files (from the oldest to the newest).
+    fn list_files(&self) -> io::Result<Vec<String>>;
+
+    /// Opens the binlog file with the given name.
+    fn open(&self, file_name: &str) -> io::Result<Self::File>;
+
+    /// Returns an iterator over events of all files starting at the given file and position
+    /// (see [`StorageEvents`]).
+    fn events(&self, file_name: &str, pos: u64) -> io::Result<StorageEvents<'_, Self>>
+    where
+        Self: Sized,
+    {
+        StorageEvents::new(self, file_name, pos)
+    }
+}
+
+/// Binlog files in a local directory.
+///
+/// Files are listed using the binlog index file (see [`FsStorage::with_index_file`]).
+/// Without an index file every file of the directory with a numeric extension
+/// (e.g. `binlog.000001`) is listed in the order of names.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct FsStorage {
+    dir: PathBuf,
+    index_file: Option<PathBuf>,
+}
+
+impl FsStorage {
+    /// Creates a new instance for the given directory.
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            index_file: None,
+        }
+    }
+
+    /// Returns modified `self` with the given binlog index file
+    /// (the `.index` file that lists binlog files in order).
+    pub fn with_index_file(mut self, index_file: Option<PathBuf>) -> Self {
+        self.index_file = index_file;
+        self
+    }
+
+    /// Returns the directory of binlog files.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+}
+
+impl BinlogStorage for FsStorage {
+    type File = File;
+
+    fn list_files(&self) -> io::Result<Vec<String>> {
+        if let Some(ref index_file) = self.index_file {
+            let index = BinlogIndexFile::open(index_file)?;
+            return Ok(index.file_names().map(ToOwned::to_owned).collect());
+        }
+
+        let mut names = Vec::new();
+        for entry in std::fs::read_dir(&self.dir)? {
+            let entry = entry?;
+            if !entry.file_type()?.is_file() {
+                continue;
+            }
+            let name = match entry.file_name().into_string() {
+                Ok(name) => name,
+                Err(_) => continue,
+            };
+            let is_binlog = matches!(
+                name.rsplit_once('.'),
+                Some((base, ext)) if !base.is_empty()
+                    && !ext.is_empty()
+                    && ext.bytes().all(|x| x.is_ascii_digit())
+            );
+            if is_binlog {
+                names.push(name);
+            }
+        }
+        names.sort_unstable();
+        Ok(names)
+    }
+
+    fn open(&self, file_name: &str) -> io::Result<File> {
+        File::open(self.dir.join(file_name))
+    }
+}
+
+/// Contents of a binlog index file (e.g. `binlog.index`).
+///
+/// The index file lists paths of binlog files (from the oldest to the newest), one per line.
+/// Paths are usually relative to the data directory (e.g. `./binlog.000001`).
+#[derive(Debug, Clone, Default, Eq, PartialEq, Hash)]
+pub struct BinlogIndexFile {
+    entries: Vec<String>,
+}
+
+impl BinlogIndexFile {
+    /// Creates an empty index.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parses the contents of an index file.
+    ///
+    /// Surrounding whitespace is trimmed and empty lines are skipped.
+    pub fn parse(index: &str) -> Self {
+        Self {
+            entries: index
+                .lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty())
+                .map(ToOwned::to_owned)
+                .collect(),
+        }
+    }
+
+    /// Reads and parses an index file.
+    ///
+    /// Returns an `InvalidData` error if the contents isn't a valid utf-8 string.
+    pub fn read<R: Read>(mut read: R) -> io::Result<Self> {
+        let mut index = String::new();
+        read.read_to_string(&mut index)?;
+        Ok(Self::parse(&index))
+    }
+
+    /// Reads and parses the index file at the given path.
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        Self::read(File::open(path)?)
+    }
+
+    /// Returns modified `self` with the given path appended.
+    pub fn with_entry(mut self, path: impl Into<String>) -> Self {
+        self.entries.push(path.into());
+        self
+    }
+
+    /// Returns listed paths (as is).
+    pub fn entries(&self) -> &[String] {
+        &self.entries
+    }
+
+    /// Returns an iterator over names of listed files (i.e. the last component of every path).
+    pub fn file_names(&self) -> impl Iterator<Item = &str> + '_ {
+        self.entries
+            .iter()
+            .filter_map(|entry| Path::new(entry).file_name()?.to_str())
+    }
+
+    /// Writes the index in the format of the server (every path is followed by `\n`).
+    pub fn write<W: Write>(&self, mut write: W) -> io::Result<()> {
+        for entry in &self.entries {
+            write.write_all(entry.as_bytes())?;
+            write.write_all(b"\n")?;
+        }
+        Ok(())
+    }
+}
+
+/// Iterator over events of all binlog files of a local directory
+/// (in the order of the index file, see [`BinlogDirectory::open`]).
+///
+/// Every file is read using its own [`BinlogFile`]. A partially written last event
+/// of a file is skipped, as well as for [`BinlogFile`].
+#[derive(Debug)]
+pub struct BinlogDirectory {
+    storage: FsStorage,
+    file_names: vec::IntoIter<String>,
+    current: Option<(String, BinlogFile<BufReader<File>>)>,
+}
+
+impl BinlogDirectory {
+    /// Opens binlog files of the given directory.
+    ///
+    /// Files are listed using the `.index` file of the directory. If there is no index file,
+    /// files are listed as by [`FsStorage`]. Returns an `InvalidInput` error if the directory
+    /// contains several index files (see [`BinlogDirectory::from_storage`]).
+    pub fn open(dir: impl Into<PathBuf>) -> io::Result<Self> {
+        let dir = dir.into();
+        let mut index_file = None;
+        for entry in std::fs::read_dir(&dir)? {
+            let path = entry?.path();
+            if path.extension() != Some("index".as_ref()) || !path.is_file() {
+                continue;
+            }
+            if index_file.is_some() {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("{} contains several index files", dir.display()),
+                ));
+            }
+            index_file = Some(path);
+        }
+        Self::from_storage(FsStorage::new(dir).with_index_file(index_file))
+    }
+
+    /// Opens binlog files listed by the given storage.
+    pub fn from_storage(storage: FsStorage) -> io::Result<Self> {
+        let file_names = storage.list_files()?.into_iter();
+        Ok(Self {
+            storage,
+            file_names,
+            current: None,
+        })
+    }
+
+    /// Returns the name of the current file (`None` before the first event or at the end).
+    pub fn file_name(&self) -> Option<&str> {
+        self.current.as_ref().map(|(name, _)| name.as_str())
+    }
+
+    /// Returns the current file (`None` before the first event or at the end).
+    pub fn file(&self) -> Option<&BinlogFile<BufReader<File>>> {
+        self.current.as_ref().map(|(_, file)| file)
+    }
+
+    /// Opens the next listed file.
+    ///
+    /// Returns `false` if there are no more files.
+    fn advance(&mut self) -> io::Result<bool> {
+        self.current = None;
+        let file_name = match self.file_names.next() {
+            Some(file_name) => file_name,
+            None => return Ok(false),
+        };
+        let read = BufReader::new(self.storage.open(&file_name)?);
+        let binlog_file = BinlogFile::new(BinlogVersion::Version4, read)?;
+        self.current = Some((file_name, binlog_file));
+        Ok(true)
+    }
+}
+
+impl Iterator for BinlogDirectory {
+    type Item = io::Result<Event<'static>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some((_, ref mut binlog_file)) = self.current {
+                if let Some(event) = binlog_file.next() {
+                    return Some(event);
+                }
+            }
+            match self.advance() {
+                Ok(true) => (),
+                Ok(false) => return None,
+                Err(err) => return Some(Err(err)),
+            }
+        }
+    }
+}
+
+/// [`Read`] and [`Seek`] implementation over a [`StorageFile`].
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct StorageReader<F> {
+    file: F,
+    pos: u64,
+}
+
+impl<F: StorageFile> StorageReader<F> {
+    /// Creates a new reader positioned at the beginning of the file.
+    pub fn new(file: F) -> Self {
+        Self { file, pos: 0 }
+    }
+
+    /// Returns a reference to the underlying file.
+    pub fn get_ref(&self) -> &F {
+        &self.file
+    }
+
+    /// Returns the underlying file.
+    pub fn into_inner(self) -> F {
+        self.file
+    }
+}
+
+impl<F: StorageFile> Read for StorageReader<F> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let len = self.file.read_at(self.pos, buf)?;
+        self.pos += len as u64;
+        Ok(len)
+    }
+}
+
+impl<F: StorageFile> Seek for StorageReader<F> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let pos = match pos {
+            SeekFrom::Start(pos) => Some(pos),
+            SeekFrom::End(offset) => checked_add(self.file.size()?, offset),
+            SeekFrom::Current(offset) => checked_add(self.pos, offset),
+        };
+        self.pos = pos.ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "invalid seek to a negative or overflowing position",
+            )
+        })?;
+        Ok(self.pos)
+    }
+}
+
+fn checked_add(base: u64, offset: i64) -> Option<u64> {
+    if offset >= 0 {
+        base.checked_add(offset as u64)
+    } else {
+        base.checked_sub(offset.unsigned_abs())
+    }
+}
+
+/// Iterator over events of consecutive binlog files of a [`BinlogStorage`]
+/// (see [`BinlogStorage::events`]).
+///
+/// It continues with the next listed file once the current one is exhausted.
+/// A partially written last event of a file is skipped, as well as for [`BinlogFile`].
+#[derive(Debug)]
+pub struct StorageEvents<'a, S: BinlogStorage> {
+    storage: &'a S,
+    file_names: Vec<String>,
+    current: usize,
+    read: StorageReader<S::File>,
+    reader: EventStreamReader,
+}
+
+impl<'a, S: BinlogStorage> StorageEvents<'a, S> {
+    /// Creates a new instance that starts at the given position of the given file.
+    ///
+    /// Format description event of the file is read even if the position is past it.
+    pub fn new(storage: &'a S, file_name: &str, pos: u64) -> io::Result<Self> {
+        let file_names = storage.list_files()?;
+        let current = file_names
+            .iter()
+            .position(|name| name == file_name)
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::NotFound,
+                    format!("{} is not listed in the storage", file_name),
+                )
+            })?;
+
+        let mut read = StorageReader::new(storage.open(file_name)?);
+        BinlogFileHeader::read(&mut read)?;
+        let mut reader = EventStreamReader::new(BinlogVersion::Version4);
+        if pos > BinlogFileHeader::LEN as u64 {
+            reader.read(&mut read)?;
+            if read.pos > pos {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("position {} is within the format description event", pos),
+                ));
+            }
+            read.seek(SeekFrom::Start(pos))?;
+        }
+
+        Ok(Self {
+            storage,
+            file_names,
+            current,
+            read,
+            reader,
+        })
+    }
+
+    /// Returns the name of the current file.
+    pub fn file_name(&self) -> &str {
+        &self.file_names[self.current]
+    }
+
+    /// Returns the offset of the next event within the current file.
+    pub fn offset(&self) -> u64 {
+        self.read.pos
+    }
+
+    /// Returns a reference to the binlog stream reader.
+    pub fn reader(&self) -> &EventStreamReader {
+        &self.reader
+    }
+
+    /// Switches to the next listed file.
+    ///
+    /// Returns `false` if the current file is the last one.
+    fn advance(&mut self) -> io::Result<bool> {
+        let next = self.current + 1;
+        if next == self.file_names.len() {
+            return Ok(false);
+        }
+        let mut read = StorageReader::new(self.storage.open(&self.file_names[next])?);
+        BinlogFileHeader::read(&mut read)?;
+        self.read = read;
+        self.current = next;
+        Ok(true)
+    }
+}
+
+impl<S: BinlogStorage> Iterator for StorageEvents<'_, S> {
+    type Item = io::Result<Event<'static>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let offset = self.read.pos;
+            match self.reader.read(&mut self.read) {
+                Ok(event) => return Some(Ok(event)),
+                Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => {
+                    self.read.pos = offset;
+                }
+                Err(err) => return Some(Err(err)),
+            }
+            match self.advance() {
+                Ok(true) => (),
+                Ok(false) => return None,
+                Err(err) => return Some(Err(err)),
+            }
+        }
+    }
+}
+
+impl GtidLocator {
+    /// Creates an index of all files of the given storage (see [`GtidLocator::add_binlog_file`]).
+    pub fn from_storage<S: BinlogStorage>(storage: &S) -> io::Result<Self> {
+        let mut locator = Self::new();
+        for file_name in storage.list_files()? {
+            let read = StorageReader::new(storage.open(&file_name)?);
+            let binlog_file = BinlogFile::new(BinlogVersion::Version4, read)?;
+            locator.add_binlog_file(file_name, binlog_file)?;
+        }
+        Ok(locator)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::BTreeMap,
+        fs,
+        io::{self, Read, Seek, SeekFrom},
+    };
+
+    use super::{BinlogDirectory, BinlogIndexFile, BinlogStorage, FsStorage, StorageReader};
+    use crate::{
+        binlog::{
+            consts::{BinlogVersion, EventType},
+            gtid_locator::GtidLocator,
+            BinlogFile,
+        },
+        packets::GtidSet,
+    };
+
+    /// In-memory storage.
+    struct MemStorage(BTreeMap<String, Vec<u8>>);
+
+    impl BinlogStorage for MemStorage {
+        type File = Vec<u8>;
+
+        fn list_files(&self) -> io::Result<Vec<String>> {
+            Ok(self.0.keys().cloned().collect())
+        }
+
+        fn open(&self, file_name: &str) -> io::Result<Vec<u8>> {
+            self.0
+                .get(file_name)
+                .cloned()
+                .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, file_name.to_owned()))
+        }
+    }
+
+    #[test]
+    fn storage_reader() -> io::Result<()> {
+        let mut read = StorageReader::new(b"0123456789".to_vec());
+        let mut buf = [0; 4];
+        read.read_exact(&mut buf)?;
+        assert_eq!(&buf, b"0123");
+        assert_eq!(read.seek(SeekFrom::Current(2))?, 6);
+        assert_eq!(read.seek(SeekFrom::End(-3))?, 7);
+        let mut rest = Vec::new();
+        read.read_to_end(&mut rest)?;
+        assert_eq!(rest, b"789");
+        assert!(read.seek(SeekFrom::Current(-11)).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn storage_events() -> io::Result<()> {
+        // ends with a rotate event
+        let first = fs::read("./test-data/binlogs/binlog_transaction_with_GTID.000001")?;
+        let second = fs::read("./test-data/binlogs/write-full-row.binlog")?;
+        let storage = MemStorage(
+            vec![
+                ("binlog.000001".to_owned(), first.clone()),
+                ("binlog.000002".to_owned(), second),
+            ]
+            .into_iter()
+            .collect(),
+        );
+
+        let all = storage
+            .events("binlog.000001", 4)?
+            .collect::<io::Result<Vec<_>>>()?;
+        let first_len = first.len() as u64;
+        let split = all
+            .iter()
+            .position(|ev| ev.header().log_pos() as u64 == first_len)
+            .unwrap();
+        assert!(split + 2 < all.len());
+        assert_eq!(
+            all[split + 1].header().event_type(),
+            Ok(EventType::FORMAT_DESCRIPTION_EVENT)
+        );
+
+        // start in the middle of the first file
+        let pos = all[1].header().log_pos() as u64;
+        let mut events = storage.events("binlog.000001", pos)?;
+        assert_eq!(events.offset(), pos);
+        assert_eq!(events.next().unwrap()?, all[2]);
+        assert_eq!(events.count(), all.len() - 3);
+
+        let mut events = storage.events("binlog.000002", 4)?;
+        let event = events.next().unwrap()?;
+        assert_eq!(event.header(), all[split + 1].header());
+        assert_eq!(event.data(), all[split + 1].data());
+        assert_eq!(events.file_name(), "binlog.000002");
+
+        assert!(storage.events("binlog.000003", 4).is_err());
+        assert!(storage.events("binlog.000001", 10).is_err());
+
+        let locator = GtidLocator::from_storage(&storage);
+        assert!(locator.is_err());
+        let storage = MemStorage(
+            vec![("binlog.000001".to_owned(), first)]
+                .into_iter()
+                .collect(),
+        );
+        let locator = GtidLocator::from_storage(&storage)?;
+        let location = locator.locate(&GtidSet::default()).unwrap();
+        assert_eq!(
+            storage
+                .events(location.file_name(), location.pos())?
+                .count(),
+            split + 1
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn fs_storage() -> io::Result<()> {
+        let dir = std::env::temp_dir().join(format!("mysql_common-storage-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir)?;
+        let data = fs::read("./test-data/binlogs/binlog_transaction_with_GTID.000001")?;
+        fs::write(dir.join("binlog.000002"), &data)?;
+        fs::write(dir.join("binlog.000001"), &data)?;
+        fs::write(dir.join("binlog.index"), "./binlog.000002\n")?;
+        fs::write(dir.join("binlog.tmp"), "")?;
+
+        let storage = FsStorage::new(&dir);
+        assert_eq!(
+            storage.list_files()?,
+            vec!["binlog.000001", "binlog.000002"]
+        );
+        let count = storage.events("binlog.000001", 4)?.count();
+
+        let storage = storage.with_index_file(Some(dir.join("binlog.index")));
+        assert_eq!(storage.list_files()?, vec!["binlog.000002"]);
+        assert_eq!(storage.events("binlog.000002", 4)?.count() * 2, count);
+
+        fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn binlog_index_file() -> io::Result<()> {
+        let index = BinlogIndexFile::parse("./binlog.000001\r\n\n  /var/lib/mysql/binlog.000002\n");
+        assert_eq!(
+            index.entries(),
+            ["./binlog.000001", "/var/lib/mysql/binlog.000002"]
+        );
+        assert_eq!(
+            index.file_names().collect::<Vec<_>>(),
+            ["binlog.000001", "binlog.000002"]
+        );
+
+        let index = index.with_entry("./binlog.000003");
+        let mut output = Vec::new();
+        index.write(&mut output)?;
+        assert_eq!(
+            output,
+            b"./binlog.000001\n/var/lib/mysql/binlog.000002\n./binlog.000003\n"
+        );
+        assert_eq!(BinlogIndexFile::read(&output[..])?, index);
+        assert_eq!(BinlogIndexFile::parse(""), BinlogIndexFile::new());
+        assert_eq!(
+            BinlogIndexFile::read(&b"\xff"[..]).unwrap_err().kind(),
+            io::ErrorKind::InvalidData
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn binlog_directory() -> io::Result<()> {
+        let dir =
+            std::env::temp_dir().join(format!("mysql_common-directory-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir)?;
+        let first = fs::read("./test-data/binlogs/binlog_transaction_with_GTID.000001")?;
+        let second = fs::read("./test-data/binlogs/write-full-row.binlog")?;
+        fs::write(dir.join("binlog.000001"), &first)?;
+        fs::write(dir.join("binlog.000002"), &second)?;
+        fs::write(dir.join("binlog.000003"), &first)?;
+
+        let count = |data: &[u8]| {
+            BinlogFile::new(BinlogVersion::Version4, data)
+                .unwrap()
+                .count()
+        };
+        let (first_count, second_count) = (count(&first), count(&second));
+
+        // without an index file
+        let mut directory = BinlogDirectory::open(&dir)?;
+        assert_eq!(directory.file_name(), None);
+        directory.next().unwrap()?;
+        assert_eq!(directory.file_name(), Some("binlog.000001"));
+        assert_eq!(directory.count(), 2 * first_count + second_count - 1);
+
+        let index = BinlogIndexFile::new()
+            .with_entry("./binlog.000002")
+            .with_entry("./binlog.000001");
+        index.write(fs::File::create(dir.join("binlog.index"))?)?;
+        let events = BinlogDirectory::open(&dir)?.collect::<io::Result<Vec<_>>>()?;
+        assert_eq!(events.len(), first_count + second_count);
+        assert_eq!(
+            events[second_count].header().event_type(),
+            Ok(EventType::FORMAT_DESCRIPTION_EVENT)
+        );
+
+        fs::write(dir.join("relay.index"), "./binlog.000003\n")?;
+        assert_eq!(
+            BinlogDirectory::open(&dir).unwrap_err().kind(),
+            io::ErrorKind::InvalidInput
+        );
+        let storage = FsStorage::new(&dir).with_index_file(Some(dir.join("relay.index")));
+        assert_eq!(BinlogDirectory::from_storage(storage)?.count(), first_count);
+
+        fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+}
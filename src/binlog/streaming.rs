@@ -0,0 +1,305 @@
+// Copyright (c) 2023 Anatoly Ikorsky
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Streaming of rows of large rows events.
+//!
+//! [`EventStreamReader::read`] buffers the whole event, which may be expensive for rows
+//! events of bulk statements. [`EventStreamReader::read_streaming`] reads only the fixed part
+//! of a rows event and exposes its rows as a reader bounded by the event size.
+
+use std::{
+    convert::TryFrom,
+    io::{self, Read},
+};
+
+use crate::io::ParseBuf;
+
+use super::{
+    consts::{BinlogChecksumAlg, EventFlags, EventType, RowsEventFlags},
+    events::{BinlogEventFooter, BinlogEventHeader, Event, RowsEvent, RowsEventCtx},
+    misc::LimitRead,
+    ArtificialFlagsPolicy, BinlogCtx, EventStreamReader,
+};
+
+/// Event read by [`EventStreamReader::read_streaming`].
+#[derive(Debug)]
+pub enum StreamingEvent<'a, T> {
+    /// Buffered event.
+    Event(Event<'static>),
+    /// Rows event with streamed rows.
+    Rows(StreamingRowsEvent<'a, T>),
+}
+
+/// Rows event whose rows are read directly from the underlying stream.
+///
+/// [`StreamingRowsEvent::finish`] must be called (after the rows are consumed or not)
+/// to keep the stream at the event boundary.
+#[derive(Debug)]
+pub struct StreamingRowsEvent<'a, T> {
+    header: BinlogEventHeader,
+    /// Rows event without the rows data.
+    rows_event: RowsEvent<'static>,
+    rows: LimitRead<&'a mut T>,
+    checksum_len: u64,
+}
+
+impl<'a, T: Read> StreamingRowsEvent<'a, T> {
+    /// Returns the event header.
+    pub fn header(&self) -> BinlogEventHeader {
+        self.header
+    }
+
+    /// Returns the rows event with empty rows data
+    /// (table id, flags, number of columns and column bitmaps are available).
+    pub fn rows_event(&self) -> &RowsEvent<'static> {
+        &self.rows_event
+    }
+
+    /// Returns the reader of the rows data (see [`RowsEvent::rows_data`]).
+    pub fn rows(&mut self) -> &mut LimitRead<&'a mut T> {
+        &mut self.rows
+    }
+
+    /// Skips the unread rows data and the checksum.
+    ///
+    /// Note that the checksum of a streamed event isn't verified.
+    pub fn finish(mut self) -> io::Result<()> {
+        io::copy(&mut self.rows, &mut io::sink())?;
+        let mut checksum = LimitRead::new(self.rows.into_inner(), self.checksum_len);
+        io::copy(&mut checksum, &mut io::sink())?;
+        Ok(())
+    }
+}
+
+impl EventStreamReader {
+    /// Will read next event from the given stream streaming rows of rows events.
+    ///
+    /// Uncompressed rows events are returned as [`StreamingEvent::Rows`] without buffering
+    /// their rows data, other events are read as by [`EventStreamReader::read`].
+    pub fn read_streaming<'a, T: Read>(
+        &mut self,
+        input: &'a mut T,
+    ) -> io::Result<StreamingEvent<'a, T>> {
+        loop {
+            let header = self.read_header(&mut *input)?;
+
+            if self.is_streamed(&header) {
+                return self.read_streaming_rows(header, input);
+            }
+
+            let event = self.read_body(header, &mut *input)?;
+            if let Some(event) = self.accept(event) {
+                return Ok(StreamingEvent::Event(event));
+            }
+        }
+    }
+
+    /// Returns `true` if rows of an event with the given header should be streamed.
+    fn is_streamed(&self, header: &BinlogEventHeader) -> bool {
+        matches!(
+            header.event_type(),
+            Ok(EventType::WRITE_ROWS_EVENT_V1
+                | EventType::UPDATE_ROWS_EVENT_V1
+                | EventType::DELETE_ROWS_EVENT_V1
+                | EventType::WRITE_ROWS_EVENT
+                | EventType::UPDATE_ROWS_EVENT
+                | EventType::DELETE_ROWS_EVENT
+                | EventType::PARTIAL_UPDATE_ROWS_EVENT)
+        ) && !self.ignored_server_ids.contains(&header.server_id())
+    }
+
+    fn read_streaming_rows<'a, T: Read>(
+        &mut self,
+        header: BinlogEventHeader,
+        input: &'a mut T,
+    ) -> io::Result<StreamingEvent<'a, T>> {
+        let event_type = header
+            .event_type()
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "unknown event type"))?;
+        let checksum_len = match self.fde.footer().get_checksum_alg() {
+            Ok(None) | Ok(Some(BinlogChecksumAlg::BINLOG_CHECKSUM_ALG_OFF)) => 0,
+            _ => BinlogEventFooter::BINLOG_CHECKSUM_LEN,
+        };
+        let body_len = (header.event_size() as usize)
+            .checked_sub(BinlogEventHeader::LEN + checksum_len)
+            .ok_or_else(too_short)?;
+
+        let prefix = self.read_rows_prefix(event_type, body_len, &mut *input)?;
+        let rows_event = ParseBuf(&prefix)
+            .parse::<RowsEvent>(RowsEventCtx::new(
+                event_type,
+                BinlogCtx::new(header.event_size() as usize, &self.fde),
+            ))?
+            .into_owned();
+
+        if self.table_map_expired {
+            self.table_map.clear();
+        }
+        // table maps are valid until the end of the statement
+        self.table_map_expired = rows_event.flags().contains(RowsEventFlags::STMT_END);
+        if header.log_pos() != 0 {
+            self.log_pos = header.log_pos() as u64;
+        }
+
+        let mut header = header;
+        if let Some(server_id) = self.rewrite_server_id {
+            header = header.with_server_id(server_id);
+        }
+        let artificial_flags =
+            EventFlags::LOG_EVENT_ARTIFICIAL_F | EventFlags::LOG_EVENT_RELAY_LOG_F;
+        if self.artificial_flags_policy == ArtificialFlagsPolicy::Clear
+            && header.flags().intersects(artificial_flags)
+        {
+            header = header.with_flags(header.flags() - artificial_flags);
+        }
+
+        Ok(StreamingEvent::Rows(StreamingRowsEvent {
+            header,
+            rows_event,
+            rows: LimitRead::new(input, (body_len - prefix.len()) as u64),
+            checksum_len: checksum_len as u64,
+        }))
+    }
+
+    /// Reads the part of a rows event that precedes the rows data
+    /// (see [`RowsEvent`] deserialization).
+    fn read_rows_prefix<T: Read>(
+        &self,
+        event_type: EventType,
+        body_len: usize,
+        mut input: T,
+    ) -> io::Result<Vec<u8>> {
+        let mut prefix = Vec::new();
+        let mut read = |prefix: &mut Vec<u8>, len: usize| {
+            if len > body_len - prefix.len() {
+                return Err(too_short());
+            }
+            let offset = prefix.len();
+            prefix.resize(offset + len, 0);
+            input.read_exact(&mut prefix[offset..])
+        };
+
+        let post_header_len = self.fde.get_event_type_header_length(event_type);
+        // table id and flags
+        read(&mut prefix, if post_header_len == 6 { 6 } else { 8 })?;
+        if post_header_len
+            == self
+                .fde
+                .get_event_type_header_length(EventType::WRITE_ROWS_EVENT)
+        {
+            // variable-length post header containing extra data
+            let offset = prefix.len();
+            read(&mut prefix, 2)?;
+            let extra_data_len = u16::from_le_bytes([prefix[offset], prefix[offset + 1]]) as usize;
+            read(&mut prefix, extra_data_len.saturating_sub(2))?;
+        }
+
+        let offset = prefix.len();
+        read(&mut prefix, 1)?;
+        let num_columns = match prefix[offset] {
+            x @ 0..=0xfa => x as u64,
+            0xfb | 0xff => 0,
+            x => {
+                let len = match x {
+                    0xfc => 2,
+                    0xfd => 3,
+                    _ => 8,
+                };
+                read(&mut prefix, len)?;
+                let mut num_columns = [0_u8; 8];
+                num_columns[..len].copy_from_slice(&prefix[offset + 1..]);
+                u64::from_le_bytes(num_columns)
+            }
+        };
+
+        let bitmap_len =
+            usize::try_from(num_columns.saturating_add(7) / 8).map_err(|_| too_short())?;
+        let bitmaps = if event_type == EventType::UPDATE_ROWS_EVENT
+            || event_type == EventType::UPDATE_ROWS_EVENT_V1
+            || event_type == EventType::PARTIAL_UPDATE_ROWS_EVENT
+        {
+            2
+        } else {
+            1
+        };
+        for _ in 0..bitmaps {
+            read(&mut prefix, bitmap_len)?;
+        }
+
+        Ok(prefix)
+    }
+}
+
+fn too_short() -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        "rows event is shorter than its fixed part",
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{self, Read};
+
+    use super::StreamingEvent;
+    use crate::binlog::{
+        consts::BinlogVersion, events::EventData, BinlogFileHeader, EventStreamReader,
+    };
+
+    #[test]
+    fn should_stream_rows() -> io::Result<()> {
+        let mut count = 0;
+        for entry in std::fs::read_dir("./test-data/binlogs")? {
+            let data = std::fs::read(entry?.path())?;
+            let mut buffered = &data[..];
+            let mut streamed = &data[..];
+            BinlogFileHeader::read(&mut buffered)?;
+            BinlogFileHeader::read(&mut streamed)?;
+
+            let mut buffered_reader = EventStreamReader::new(BinlogVersion::Version4);
+            let mut streamed_reader = EventStreamReader::new(BinlogVersion::Version4);
+            while !buffered.is_empty() {
+                // some files contain broken events on purpose
+                let event = match buffered_reader.read(&mut buffered) {
+                    Ok(event) => event,
+                    Err(_) => break,
+                };
+                let rows_event = match event.read_data() {
+                    Ok(Some(EventData::RowsEvent(rows_event))) => Some(rows_event),
+                    Ok(_) => None,
+                    Err(_) => break,
+                };
+
+                match streamed_reader.read_streaming(&mut streamed)? {
+                    StreamingEvent::Event(streamed) => assert_eq!(streamed, event),
+                    StreamingEvent::Rows(mut streamed) => {
+                        let rows_event = rows_event.unwrap();
+                        assert_eq!(streamed.header(), event.header());
+                        assert_eq!(streamed.rows_event().table_id(), rows_event.table_id());
+                        assert_eq!(streamed.rows_event().flags(), rows_event.flags());
+                        assert_eq!(streamed.rows_event().rows_data(), b"");
+
+                        // unread rows are skipped by `finish`
+                        if count % 2 == 0 {
+                            let mut rows = Vec::new();
+                            streamed.rows().read_to_end(&mut rows)?;
+                            assert_eq!(rows, rows_event.rows_data());
+                        }
+                        streamed.finish()?;
+                        count += 1;
+                    }
+                }
+                assert_eq!(streamed.len(), buffered.len());
+                assert_eq!(streamed_reader.log_pos(), buffered_reader.log_pos());
+            }
+        }
+        assert!(count > 0);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,422 @@
+// Copyright (c) 2023 Anatoly Ikorsky
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Following growing binlog files.
+
+use std::{
+    fs::File,
+    io::{self, Read, Seek, SeekFrom},
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use crate::{io::ParseBuf, proto::MyDeserialize};
+
+use super::{
+    consts::{BinlogVersion, EventType},
+    events::{BinlogEventHeader, Event},
+    BinlogFileHeader, EventStreamReader,
+};
+
+/// Default interval between checks for new data.
+const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Waits for new data to appear in a tailed binlog (see [`BinlogTailer`]).
+pub trait TailNotifier {
+    /// Blocks until new data may be available.
+    ///
+    /// Returning `Ok(false)` stops the tailing.
+    fn wait(&mut self) -> io::Result<bool>;
+}
+
+/// Polls with the given interval.
+impl TailNotifier for Duration {
+    fn wait(&mut self) -> io::Result<bool> {
+        std::thread::sleep(*self);
+        Ok(true)
+    }
+}
+
+impl<F: FnMut() -> io::Result<bool>> TailNotifier for F {
+    fn wait(&mut self) -> io::Result<bool> {
+        self()
+    }
+}
+
+/// Follows a growing binlog file, like `tail -F`.
+///
+/// It's an iterator over events that blocks at the end of the file waiting for new events
+/// (see [`TailNotifier`]). Partially written events are never returned.
+///
+/// *   it continues with the next file after a rotate event;
+/// *   if an index file is given (see [`BinlogTailer::with_index_file`]), it also continues
+///     with the next listed file once the current one is exhausted (e.g. after a crash
+///     that left the file without a rotate event);
+/// *   if the file is truncated, it starts over from the beginning of the file.
+#[derive(Debug)]
+pub struct BinlogTailer<N = Duration> {
+    dir: PathBuf,
+    file_name: String,
+    file: Option<File>,
+    offset: u64,
+    reader: EventStreamReader,
+    index_file: Option<PathBuf>,
+    notifier: N,
+}
+
+impl BinlogTailer {
+    /// Creates a new instance that starts at the beginning of the given file
+    /// and polls for new data every second.
+    ///
+    /// The file doesn't have to exist yet.
+    pub fn new(path: impl AsRef<Path>) -> io::Result<Self> {
+        let path = path.as_ref();
+        let file_name = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "invalid binlog path"))?;
+        Ok(Self {
+            dir: path.parent().map(Path::to_path_buf).unwrap_or_default(),
+            file_name: file_name.to_owned(),
+            file: None,
+            offset: 0,
+            reader: EventStreamReader::new(BinlogVersion::Version4),
+            index_file: None,
+            notifier: DEFAULT_POLL_INTERVAL,
+        })
+    }
+}
+
+impl<N: TailNotifier> BinlogTailer<N> {
+    /// Returns modified `self` with the given notifier (e.g. a poll interval).
+    pub fn with_notifier<M: TailNotifier>(self, notifier: M) -> BinlogTailer<M> {
+        BinlogTailer {
+            dir: self.dir,
+            file_name: self.file_name,
+            file: self.file,
+            offset: self.offset,
+            reader: self.reader,
+            index_file: self.index_file,
+            notifier,
+        }
+    }
+
+    /// Returns modified `self` with the given binlog index file
+    /// (the `.index` file that lists binlog files in order).
+    pub fn with_index_file(mut self, index_file: Option<PathBuf>) -> Self {
+        self.index_file = index_file;
+        self
+    }
+
+    /// Returns the name of the file being tailed.
+    pub fn file_name(&self) -> &str {
+        &self.file_name
+    }
+
+    /// Returns the offset of the next event in the file being tailed.
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    /// Returns a reference to the binlog stream reader.
+    pub fn reader(&self) -> &EventStreamReader {
+        &self.reader
+    }
+
+    /// Switches to the given file.
+    fn switch_to(&mut self, file_name: String) {
+        self.file_name = file_name;
+        self.file = None;
+        self.offset = 0;
+    }
+
+    /// Reads the next complete event, if it's available.
+    fn try_read(&mut self) -> io::Result<Option<Event<'static>>> {
+        if self.file.is_none() {
+            match File::open(self.dir.join(&self.file_name)) {
+                Ok(file) => self.file = Some(file),
+                Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+                Err(err) => return Err(err),
+            }
+        }
+        let file = self.file.as_mut().expect("should be opened");
+
+        let len = file.metadata()?.len();
+        if len < self.offset {
+            // the file was truncated
+            self.offset = 0;
+            self.reader = EventStreamReader::new(BinlogVersion::Version4);
+        }
+
+        if self.offset == 0 {
+            if len < BinlogFileHeader::LEN as u64 {
+                return Ok(None);
+            }
+            file.seek(SeekFrom::Start(0))?;
+            BinlogFileHeader::read(&mut *file)?;
+            self.offset = BinlogFileHeader::LEN as u64;
+        }
+
+        if len - self.offset < BinlogEventHeader::LEN as u64 {
+            return Ok(None);
+        }
+        let mut header_buf = [0_u8; BinlogEventHeader::LEN];
+        file.seek(SeekFrom::Start(self.offset))?;
+        file.read_exact(&mut header_buf)?;
+        let header = BinlogEventHeader::deserialize((), &mut ParseBuf(&header_buf))?;
+        let event_size = header.event_size() as u64;
+        if event_size < BinlogEventHeader::LEN as u64 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("invalid event size {} at {}", event_size, self.offset),
+            ));
+        }
+        if len - self.offset < event_size {
+            return Ok(None);
+        }
+
+        let mut buf = vec![0_u8; event_size as usize];
+        buf[..BinlogEventHeader::LEN].copy_from_slice(&header_buf);
+        file.read_exact(&mut buf[BinlogEventHeader::LEN..])?;
+        let event = self.reader.read(&buf[..])?;
+        self.offset += event_size;
+
+        if event.header().event_type_raw() == EventType::ROTATE_EVENT as u8
+            && !event.is_artificial_rotate()
+        {
+            if let Some(next) = self.reader.log_file() {
+                let next = next.to_owned();
+                self.switch_to(next);
+            }
+        }
+
+        Ok(Some(event))
+    }
+
+    /// Switches to the next file listed in the index file (if any).
+    ///
+    /// Returns `true` if switched.
+    fn advance_by_index(&mut self) -> io::Result<bool> {
+        let index_file = match self.index_file {
+            Some(ref index_file) => index_file,
+            None => return Ok(false),
+        };
+        let index = std::fs::read_to_string(index_file)?;
+        let mut names = index
+            .lines()
+            .filter_map(|line| Path::new(line.trim()).file_name()?.to_str());
+
+        if !names.any(|name| name == self.file_name) {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("{} is not listed in the index file", self.file_name),
+            ));
+        }
+        match names.next() {
+            Some(next) => {
+                let next = next.to_owned();
+                self.switch_to(next);
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+}
+
+impl<N: TailNotifier> Iterator for BinlogTailer<N> {
+    type Item = io::Result<Event<'static>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            match self.try_read() {
+                Ok(Some(event)) => return Some(Ok(event)),
+                Ok(None) => (),
+                Err(err) => return Some(Err(err)),
+            }
+            match self.advance_by_index() {
+                Ok(true) => continue,
+                Ok(false) => (),
+                Err(err) => return Some(Err(err)),
+            }
+            match self.notifier.wait() {
+                Ok(true) => (),
+                Ok(false) => return None,
+                Err(err) => return Some(Err(err)),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        fs::{self, OpenOptions},
+        io::{self, Write},
+        path::PathBuf,
+    };
+
+    use super::BinlogTailer;
+    use crate::binlog::consts::EventType;
+
+    /// Returns offsets of events in the given binlog file.
+    fn event_offsets(data: &[u8]) -> Vec<usize> {
+        let mut offsets = Vec::new();
+        let mut offset = 4;
+        while offset < data.len() {
+            offsets.push(offset);
+            let size = &data[offset + 9..offset + 13];
+            offset += u32::from_le_bytes([size[0], size[1], size[2], size[3]]) as usize;
+        }
+        offsets
+    }
+
+    fn test_dir(name: &str) -> io::Result<PathBuf> {
+        let dir = std::env::temp_dir().join(format!(
+            "mysql_common-tailer-{}-{}",
+            name,
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir)?;
+        Ok(dir)
+    }
+
+    fn append(path: &PathBuf, data: &[u8]) -> io::Result<()> {
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)?
+            .write_all(data)
+    }
+
+    #[test]
+    fn should_follow_growing_binlog() -> io::Result<()> {
+        // ends with a rotate event to mysqld-bin.000002
+        let data = fs::read("./test-data/binlogs/write-full-row.binlog")?;
+        let offsets = event_offsets(&data);
+        let last = *offsets.last().unwrap();
+        let dir = test_dir("follow")?;
+        let first = dir.join("mysqld-bin.000001");
+        let second = dir.join("mysqld-bin.000002");
+
+        // file is written in small chunks, so events are split across writes
+        let mut chunks = data.chunks(7).map(<[u8]>::to_vec).collect::<Vec<_>>();
+        // the second file is the same but without the rotate event
+        chunks.push(data[..last].to_vec());
+        let mut chunks = chunks.into_iter();
+        let (first_path, second_path) = (first.clone(), second.clone());
+        let mut written = 0;
+        let notifier = move || {
+            match chunks.next() {
+                Some(chunk) if written < data.len() => {
+                    written += chunk.len();
+                    append(&first_path, &chunk)?;
+                }
+                Some(chunk) => append(&second_path, &chunk)?,
+                None => return Ok(false),
+            }
+            Ok(true)
+        };
+
+        let mut tailer = BinlogTailer::new(&first)?.with_notifier(notifier);
+        let mut events = Vec::new();
+        let mut file_names = Vec::new();
+        while let Some(event) = tailer.next() {
+            events.push(event?);
+            file_names.push(tailer.file_name().to_owned());
+        }
+
+        assert_eq!(events.len(), offsets.len() * 2 - 1);
+        assert_eq!(
+            events[offsets.len() - 1].header().event_type(),
+            Ok(EventType::ROTATE_EVENT)
+        );
+        assert_eq!(file_names[offsets.len() - 1], "mysqld-bin.000002");
+        assert_eq!(tailer.offset(), last as u64);
+
+        fs::remove_dir_all(&dir)
+    }
+
+    #[test]
+    fn should_use_index_file_and_handle_truncation() -> io::Result<()> {
+        let data = fs::read("./test-data/binlogs/write-full-row.binlog")?;
+        let offsets = event_offsets(&data);
+        let last = *offsets.last().unwrap();
+        let dir = test_dir("index")?;
+        let first = dir.join("binlog.000001");
+        let second = dir.join("binlog.000002");
+        let index = dir.join("binlog.index");
+
+        // the first file lacks the rotate event
+        fs::write(&first, &data[..last])?;
+        fs::write(&index, "./binlog.000001\n")?;
+
+        let mut steps = vec![
+            // no new data
+            (None, None),
+            // the next file appears in the index
+            (
+                Some("./binlog.000001\n./binlog.000002\n"),
+                Some(data[..last].to_vec()),
+            ),
+        ]
+        .into_iter();
+        let (index_path, second_path) = (index.clone(), second.clone());
+        let notifier = move || match steps.next() {
+            Some((index_data, second_data)) => {
+                if let Some(index_data) = index_data {
+                    fs::write(&index_path, index_data)?;
+                }
+                if let Some(second_data) = second_data {
+                    fs::write(&second_path, second_data)?;
+                }
+                Ok(true)
+            }
+            None => Ok(false),
+        };
+
+        let mut tailer = BinlogTailer::new(&first)?
+            .with_notifier(notifier)
+            .with_index_file(Some(index.clone()));
+        let mut count = 0;
+        for event in &mut tailer {
+            event?;
+            count += 1;
+        }
+        assert_eq!(count, (offsets.len() - 1) * 2);
+        assert_eq!(tailer.file_name(), "binlog.000002");
+
+        // truncated and rewritten file is read again from the beginning
+        fs::write(&second, &data[..offsets[2]])?;
+        let mut rewritten = false;
+        let second_path = second.clone();
+        let mut tailer = tailer.with_notifier(move || {
+            if rewritten {
+                return Ok(false);
+            }
+            rewritten = true;
+            fs::write(&second_path, &data[..last])?;
+            Ok(true)
+        });
+        let mut count = 0;
+        for event in &mut tailer {
+            event?;
+            count += 1;
+        }
+        assert_eq!(count, offsets.len() - 1);
+
+        // the current file is purged from the index
+        fs::write(&index, "./binlog.000003\n")?;
+        assert_eq!(
+            tailer.next().unwrap().unwrap_err().kind(),
+            io::ErrorKind::NotFound
+        );
+
+        fs::remove_dir_all(&dir)
+    }
+}
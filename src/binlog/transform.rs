@@ -0,0 +1,1043 @@
+// Copyright (c) 2023 Anatoly Ikorsky
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Transformations applied to binlog events when replaying or merging binlog streams.
+
+use bitvec::prelude::*;
+use sha2::{Digest, Sha256};
+
+use std::{borrow::Cow, collections::HashMap, convert::TryFrom, io, ops::Range, time::Duration};
+
+use crate::{
+    constants::ColumnType,
+    io::ParseBuf,
+    misc::raw::{int::LenEnc, RawInt},
+};
+
+use super::{
+    consts::{EventFlags, EventType, RowsEventFlags, StatusVarKey},
+    events::{
+        BinlogEventHeader, Event, EventData, OptionalMetaExtractor, PayloadCodec, QueryEvent,
+        RowsEventData, StatusVarVal, TableMapEvent, TransactionPayloadEvent,
+    },
+    row::BinlogRowValueOptions,
+    value::BinlogValue,
+    BinlogFileHeader,
+};
+
+/// Table id of a dummy rows event that only carries the end of statement flag.
+const DUMMY_TABLE_ID: u64 = 0x00ff_ffff;
+
+/// Maximum table id (it's 6 bytes long).
+const MAX_TABLE_ID: u64 = (1 << 48) - 1;
+
+/// Rewrites table identifiers of table map events and subsequent rows events.
+///
+/// The mapping is applied to every `TableMapEvent`, and rows events are rewritten
+/// to the id their table map was mapped to, so ids stay consistent within each
+/// statement group (the group ends with a rows event having the `STMT_END` flag).
+/// Needed when merging binlogs from multiple sources into one stream.
+///
+/// Rows events that refer to a table that wasn't mapped within the current statement group
+/// are reported as `InvalidData` errors.
+#[derive(Debug, Clone)]
+pub struct TableIdRemapper<F> {
+    mapping: F,
+    group: HashMap<u64, u64>,
+}
+
+impl<F: FnMut(u64) -> u64> TableIdRemapper<F> {
+    /// Creates a new remapper using the given `mapping` (original id -> new id).
+    pub fn new(mapping: F) -> Self {
+        Self {
+            mapping,
+            group: HashMap::new(),
+        }
+    }
+
+    /// Returns the new table id for the given original id within the current statement group.
+    pub fn mapped(&self, table_id: u64) -> Option<u64> {
+        self.group.get(&table_id).copied()
+    }
+
+    /// Rewrites table id of the given event data (other events are returned as is).
+    pub fn remap<'a>(&mut self, data: EventData<'a>) -> io::Result<EventData<'a>> {
+        match data {
+            EventData::TableMapEvent(ev) => {
+                let table_id = (self.mapping)(ev.table_id());
+                if table_id > MAX_TABLE_ID {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        format!("table id {} is out of range", table_id),
+                    ));
+                }
+                self.group.insert(ev.table_id(), table_id);
+                Ok(EventData::TableMapEvent(ev.with_table_id(table_id)))
+            }
+            EventData::RowsEvent(ev) => {
+                let stmt_end = ev.flags().contains(RowsEventFlags::STMT_END);
+                let ev = if ev.table_id() == DUMMY_TABLE_ID {
+                    ev
+                } else {
+                    match self.group.get(&ev.table_id()) {
+                        Some(table_id) => ev.with_table_id(*table_id),
+                        None => {
+                            return Err(io::Error::new(
+                                io::ErrorKind::InvalidData,
+                                format!("rows event refers to unknown table id {}", ev.table_id()),
+                            ))
+                        }
+                    }
+                };
+                if stmt_end {
+                    self.group.clear();
+                }
+                Ok(EventData::RowsEvent(ev))
+            }
+            other => Ok(other),
+        }
+    }
+}
+
+/// Defines how to redact a value.
+#[derive(Debug, Default, Clone, Eq, PartialEq, Hash)]
+pub enum Redaction {
+    /// Keep the value as is.
+    #[default]
+    Keep,
+    /// Replace the value with its (salted) SHA-256 hash in hex.
+    ///
+    /// Equal values are replaced with equal hashes, so joins and grouping are preserved.
+    Hash,
+    /// Replace the value with the given token.
+    Token(Cow<'static, [u8]>),
+}
+
+impl Redaction {
+    /// Overwrites `value` according to this redaction.
+    ///
+    /// Length of the value is preserved, so the hash (or the token) is repeated
+    /// or truncated as needed.
+    fn apply(&self, salt: &[u8], value: &mut [u8]) {
+        let filler: Cow<'_, [u8]> = match self {
+            Redaction::Keep => return,
+            Redaction::Hash => {
+                let mut hasher = Sha256::new();
+                hasher.update(salt);
+                hasher.update(&*value);
+                let hash = hasher.finalize();
+                let hex = hash
+                    .iter()
+                    .map(|x| format!("{:02x}", x))
+                    .collect::<String>();
+                Cow::Owned(hex.into_bytes())
+            }
+            Redaction::Token(token) if token.is_empty() => Cow::Borrowed(&b"x"[..]),
+            Redaction::Token(token) => Cow::Borrowed(&**token),
+        };
+        for (dst, src) in value.iter_mut().zip(filler.iter().cycle()) {
+            *dst = *src;
+        }
+    }
+}
+
+/// Per table and per column redaction rules (see [`Anonymizer`]).
+///
+/// Columns are identified by name. Names are only available if the binlog was written
+/// with `binlog_row_metadata=FULL`, otherwise a column is named `@<i>`,
+/// where `i` is its zero-based offset in the table (same as in [`super::row::BinlogRow`]).
+#[derive(Debug, Default, Clone, Eq, PartialEq)]
+pub struct RedactionRules {
+    default: Redaction,
+    tables: HashMap<(Vec<u8>, Vec<u8>), TableRules>,
+}
+
+/// Redaction rules of a single table.
+#[derive(Debug, Default, Clone, Eq, PartialEq)]
+struct TableRules {
+    default: Option<Redaction>,
+    columns: HashMap<Vec<u8>, Redaction>,
+}
+
+impl RedactionRules {
+    /// Creates new rules with the given default redaction.
+    pub fn new(default: Redaction) -> Self {
+        Self {
+            default,
+            ..Default::default()
+        }
+    }
+
+    /// Returns modified `self` with the given redaction for every column of a table.
+    pub fn with_table(
+        mut self,
+        schema: impl Into<Vec<u8>>,
+        table: impl Into<Vec<u8>>,
+        redaction: Redaction,
+    ) -> Self {
+        self.tables
+            .entry((schema.into(), table.into()))
+            .or_default()
+            .default = Some(redaction);
+        self
+    }
+
+    /// Returns modified `self` with the given redaction for a column.
+    pub fn with_column(
+        mut self,
+        schema: impl Into<Vec<u8>>,
+        table: impl Into<Vec<u8>>,
+        column: impl Into<Vec<u8>>,
+        redaction: Redaction,
+    ) -> Self {
+        self.tables
+            .entry((schema.into(), table.into()))
+            .or_default()
+            .columns
+            .insert(column.into(), redaction);
+        self
+    }
+
+    /// Returns redaction for the given column (column rule, then table rule, then the default).
+    pub fn get(&self, schema: &[u8], table: &[u8], column: &[u8]) -> &Redaction {
+        match self.tables.get(&(schema.to_vec(), table.to_vec())) {
+            Some(rules) => rules
+                .columns
+                .get(column)
+                .or(rules.default.as_ref())
+                .unwrap_or(&self.default),
+            None => &self.default,
+        }
+    }
+}
+
+/// Redacts string and blob data of a binlog stream for compliance.
+///
+/// *   string and blob cells of rows events are redacted according to [`RedactionRules`];
+/// *   string literals of `QueryEvent` and `RowsQueryEvent` queries are redacted
+///     (using the default redaction unless [`Anonymizer::with_query_redaction`] is used).
+///
+/// Redacted values keep their length, so event sizes and positions don't change
+/// and the anonymized binlog stays valid. Checksums are recalculated.
+#[derive(Debug, Clone)]
+pub struct Anonymizer {
+    rules: RedactionRules,
+    query_redaction: Redaction,
+    salt: Vec<u8>,
+    tables: HashMap<u64, (TableMapEvent<'static>, Vec<Redaction>)>,
+}
+
+impl Anonymizer {
+    /// Creates a new anonymizer.
+    pub fn new(rules: RedactionRules) -> Self {
+        Self {
+            query_redaction: rules.default.clone(),
+            rules,
+            salt: Vec::new(),
+            tables: HashMap::new(),
+        }
+    }
+
+    /// Returns modified `self` with the given redaction of query literals.
+    pub fn with_query_redaction(mut self, redaction: Redaction) -> Self {
+        self.query_redaction = redaction;
+        self
+    }
+
+    /// Returns modified `self` with the given salt for [`Redaction::Hash`].
+    pub fn with_salt(mut self, salt: impl Into<Vec<u8>>) -> Self {
+        self.salt = salt.into();
+        self
+    }
+
+    /// Anonymizes the given event.
+    ///
+    /// Events must be given in the stream order, so that table maps are known
+    /// for rows events.
+    pub fn anonymize(&mut self, mut event: Event<'static>) -> io::Result<Event<'static>> {
+        let mut patches: Vec<(Range<usize>, Redaction)> = Vec::new();
+
+        match event.read_data()? {
+            Some(EventData::TableMapEvent(tme)) => {
+                let plan = self.plan(&tme)?;
+                self.tables.insert(tme.table_id(), (tme.into_owned(), plan));
+            }
+            Some(EventData::RowsEvent(rows_event)) => {
+                if let Some((tme, plan)) = self.tables.get(&rows_event.table_id()) {
+                    let rows_data = rows_event.rows_data();
+                    let offset = event.data().len() - rows_data.len();
+                    for (range, redaction) in rows_patches(&rows_event, tme, plan)? {
+                        patches.push((offset + range.start..offset + range.end, redaction));
+                    }
+                }
+            }
+            Some(EventData::QueryEvent(ev)) => {
+                let offset = event.data().len() - ev.query_raw().len();
+                for range in string_literals(ev.query_raw()) {
+                    patches.push((
+                        offset + range.start..offset + range.end,
+                        self.query_redaction.clone(),
+                    ));
+                }
+            }
+            Some(EventData::RowsQueryEvent(ev)) => {
+                let offset = event.data().len() - ev.query_raw().len();
+                for range in string_literals(ev.query_raw()) {
+                    patches.push((
+                        offset + range.start..offset + range.end,
+                        self.query_redaction.clone(),
+                    ));
+                }
+            }
+            _ => (),
+        }
+
+        if !patches.is_empty() {
+            let is_query = matches!(
+                event.header().event_type(),
+                Ok(EventType::QUERY_EVENT) | Ok(EventType::ROWS_QUERY_EVENT)
+            );
+            let salt = &self.salt;
+            event.patch_data(|data| {
+                for (range, redaction) in patches {
+                    let value = &mut data[range];
+                    redaction.apply(salt, value);
+                    if is_query {
+                        // redacted literal must not terminate the literal
+                        for x in value.iter_mut() {
+                            if matches!(*x, b'\'' | b'"' | b'\\') {
+                                *x = b'x';
+                            }
+                        }
+                    }
+                }
+            });
+        }
+
+        Ok(event)
+    }
+
+    /// Computes redaction of every column of the given table.
+    fn plan(&self, tme: &TableMapEvent<'_>) -> io::Result<Vec<Redaction>> {
+        let extractor = OptionalMetaExtractor::new(tme.iter_optional_meta())?;
+        let mut names = extractor.iter_column_name();
+        let mut plan = Vec::with_capacity(tme.columns_count() as usize);
+        for i in 0..tme.columns_count() as usize {
+            let name = match names.next().transpose()? {
+                Some(name) => name.name_raw().to_vec(),
+                None => format!("@{}", i).into_bytes(),
+            };
+            let redaction = match redactable_type(tme, i) {
+                Some(_) => self
+                    .rules
+                    .get(tme.database_name_raw(), tme.table_name_raw(), &name)
+                    .clone(),
+                None => Redaction::Keep,
+            };
+            plan.push(redaction);
+        }
+        Ok(plan)
+    }
+}
+
+/// Returns the real type of a column if it contains strings or blobs.
+fn redactable_type(tme: &TableMapEvent<'_>, column: usize) -> Option<ColumnType> {
+    let mut column_type = tme.get_column_type(column).ok()??;
+    let meta = tme.get_column_metadata(column).unwrap_or(&[]);
+    if column_type == ColumnType::MYSQL_TYPE_STRING && !meta.is_empty() {
+        // the real type is stored in the metadata (see `BinlogValue` deserialization)
+        column_type = ColumnType::try_from(meta[0]).unwrap_or(column_type);
+    }
+    match column_type {
+        ColumnType::MYSQL_TYPE_VARCHAR
+        | ColumnType::MYSQL_TYPE_VAR_STRING
+        | ColumnType::MYSQL_TYPE_STRING
+        | ColumnType::MYSQL_TYPE_TINY_BLOB
+        | ColumnType::MYSQL_TYPE_MEDIUM_BLOB
+        | ColumnType::MYSQL_TYPE_LONG_BLOB
+        | ColumnType::MYSQL_TYPE_BLOB => Some(column_type),
+        _ => None,
+    }
+}
+
+/// Returns ranges of redacted cells within rows data along with their redaction.
+fn rows_patches<'a>(
+    rows_event: &'a RowsEventData<'_>,
+    tme: &'a TableMapEvent<'_>,
+    plan: &[Redaction],
+) -> io::Result<Vec<(Range<usize>, Redaction)>> {
+    let rows_data = rows_event.rows_data();
+    let is_partial = matches!(rows_event, RowsEventData::PartialUpdateRowsEvent(_));
+    let mut buf = ParseBuf(rows_data);
+    let mut patches = Vec::new();
+
+    while !buf.is_empty() {
+        if let Some(cols) = rows_event.columns_before_image() {
+            image_patches(
+                &mut buf,
+                rows_data.len(),
+                cols,
+                false,
+                tme,
+                plan,
+                &mut patches,
+            )?;
+        }
+        if let Some(cols) = rows_event.columns_after_image() {
+            image_patches(
+                &mut buf,
+                rows_data.len(),
+                cols,
+                is_partial,
+                tme,
+                plan,
+                &mut patches,
+            )?;
+        }
+    }
+
+    Ok(patches)
+}
+
+/// Walks a single row image (see `BinlogRow` deserialization) collecting redacted cells.
+fn image_patches<'a>(
+    buf: &mut ParseBuf<'a>,
+    total_len: usize,
+    cols: &BitSlice<u8>,
+    have_shared_image: bool,
+    tme: &'a TableMapEvent<'_>,
+    plan: &[Redaction],
+    patches: &mut Vec<(Range<usize>, Redaction)>,
+) -> io::Result<()> {
+    let mut partial_cols = if have_shared_image {
+        let value_options = *buf.parse::<RawInt<LenEnc>>(())?;
+        if value_options & BinlogRowValueOptions::PARTIAL_JSON_UPDATES as u64 > 0 {
+            let json_columns_count = tme.json_column_count();
+            let partial_columns: &[u8] = buf.parse(json_columns_count.div_ceil(8))?;
+            let partial_columns = BitSlice::<u8>::from_slice(partial_columns);
+            Some(partial_columns.into_iter().take(json_columns_count))
+        } else {
+            None
+        }
+    } else {
+        None
+    };
+
+    let num_bits = cols.count_ones();
+    let null_bitmap: &[u8] = buf.parse(num_bits.div_ceil(8))?;
+    let null_bitmap = BitSlice::<u8>::from_slice(null_bitmap);
+
+    let present_cols = cols.iter().enumerate().filter(|(_, x)| **x);
+    for (image_idx, (i, _)) in present_cols.enumerate() {
+        let column_type = match tme.get_column_type(i) {
+            Ok(Some(ty)) => ty,
+            Ok(None) => return Err(io::Error::new(io::ErrorKind::InvalidData, "No column type")),
+            Err(e) => return Err(io::Error::new(io::ErrorKind::InvalidData, e)),
+        };
+        let column_meta = tme.get_column_metadata(i).unwrap_or(&[]);
+        let is_partial = column_type == ColumnType::MYSQL_TYPE_JSON
+            && partial_cols
+                .as_mut()
+                .and_then(|bits| bits.next().as_deref().copied())
+                .unwrap_or(false);
+
+        let is_null = null_bitmap
+            .get(image_idx)
+            .as_deref()
+            .copied()
+            .unwrap_or(true);
+        if is_null {
+            continue;
+        }
+
+        let ctx = (column_type, column_meta, false, is_partial);
+        let value = buf.parse::<BinlogValue>(ctx)?;
+        let end = total_len - buf.len();
+        match (plan.get(i), value) {
+            (Some(Redaction::Keep), _) | (None, _) => (),
+            (Some(redaction), BinlogValue::Value(crate::Value::Bytes(bytes))) => {
+                patches.push((end - bytes.len()..end, redaction.clone()));
+            }
+            _ => (),
+        }
+    }
+
+    Ok(())
+}
+
+/// Returns ranges of string literal contents (quotes excluded) in the given query.
+///
+/// Identifiers and comments are skipped.
+fn string_literals(query: &[u8]) -> Vec<Range<usize>> {
+    let mut literals = Vec::new();
+    let mut i = 0;
+    while i < query.len() {
+        match query[i] {
+            quote @ (b'\'' | b'"') => {
+                let start = i + 1;
+                i = start;
+                while i < query.len() {
+                    if query[i] == b'\\' {
+                        i += 2;
+                    } else if query[i] == quote {
+                        if query.get(i + 1) == Some(&quote) {
+                            // doubled quote
+                            i += 2;
+                        } else {
+                            break;
+                        }
+                    } else {
+                        i += 1;
+                    }
+                }
+                let end = i.min(query.len());
+                literals.push(start..end);
+                i = end + 1;
+            }
+            b'`' => {
+                i += 1;
+                while i < query.len() && query[i] != b'`' {
+                    i += 1;
+                }
+                i += 1;
+            }
+            b'#' => {
+                while i < query.len() && query[i] != b'\n' {
+                    i += 1;
+                }
+            }
+            b'-' if query[i..].starts_with(b"-- ") => {
+                while i < query.len() && query[i] != b'\n' {
+                    i += 1;
+                }
+            }
+            b'/' if query[i..].starts_with(b"/*") => {
+                i += 2;
+                while i < query.len() && !query[i..].starts_with(b"*/") {
+                    i += 1;
+                }
+                i += 2;
+            }
+            _ => i += 1,
+        }
+    }
+    literals
+}
+
+/// Shifts event timestamps by a fixed offset.
+///
+/// Header timestamps and the `Microseconds` status variable of `QueryEvent`s are shifted
+/// (timestamp of an event without this variable is taken as a whole second),
+/// so relative ordering of events is preserved. Events with zero timestamp (e.g. artificial
+/// events sent by the server) are left as is. Checksums are recalculated.
+///
+/// Useful for replaying production binlogs into a test environment at a different time.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub struct TimestampShifter {
+    offset_micros: i64,
+}
+
+impl TimestampShifter {
+    /// Creates a new shifter that moves timestamps to the future.
+    pub fn forward(offset: Duration) -> Self {
+        Self {
+            offset_micros: offset.as_micros() as i64,
+        }
+    }
+
+    /// Creates a new shifter that moves timestamps to the past.
+    pub fn backward(offset: Duration) -> Self {
+        Self {
+            offset_micros: -(offset.as_micros() as i64),
+        }
+    }
+
+    /// Returns the offset in microseconds (negative if timestamps are moved to the past).
+    pub fn offset_micros(&self) -> i64 {
+        self.offset_micros
+    }
+
+    /// Shifts timestamps of the given event.
+    ///
+    /// Returns an error if the shifted timestamp doesn't fit into the header.
+    pub fn shift(&self, event: Event<'static>) -> io::Result<Event<'static>> {
+        let timestamp = event.header().timestamp();
+        if timestamp == 0 {
+            return Ok(event);
+        }
+
+        let microseconds = match event.header().event_type() {
+            Ok(EventType::QUERY_EVENT) => {
+                let ev = event.read_event::<QueryEvent>()?;
+                let status_vars = ev.status_vars();
+                status_vars
+                    .status_var_range(StatusVarKey::Microseconds)
+                    .and_then(|range| {
+                        let value = status_vars.get_status_var(StatusVarKey::Microseconds)?;
+                        match value.get_value() {
+                            Ok(StatusVarVal::Microseconds(x)) => {
+                                // status vars are followed by the schema, `0x00` and the query
+                                let offset = event.data().len()
+                                    - ev.query_raw().len()
+                                    - 1
+                                    - ev.schema_raw().len()
+                                    - ev.status_vars_raw().len();
+                                Some((offset + range.start..offset + range.end, x))
+                            }
+                            _ => None,
+                        }
+                    })
+            }
+            _ => None,
+        };
+
+        let micros = microseconds.as_ref().map(|(_, x)| *x).unwrap_or(0);
+        let shifted = (timestamp as i64 * 1_000_000 + micros as i64)
+            .checked_add(self.offset_micros)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "timestamp overflow"))?;
+        let new_timestamp = u32::try_from(shifted.div_euclid(1_000_000))
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "timestamp out of range"))?;
+
+        let mut event = event.with_timestamp(new_timestamp);
+        if let Some((range, _)) = microseconds {
+            let new_micros = shifted.rem_euclid(1_000_000) as u32;
+            event.patch_data(|data| {
+                data[range].copy_from_slice(&new_micros.to_le_bytes()[..3]);
+            });
+        }
+
+        Ok(event)
+    }
+}
+
+/// Re-wraps transactions into transaction payload events for writing compact archival binlogs.
+///
+/// Events of a transaction started by `BEGIN` and finished by `XID_EVENT` or `COMMIT` are replaced
+/// with a single `TRANSACTION_PAYLOAD_EVENT` (see [`TransactionPayloadEvent::pack`]), as MySql
+/// 8.0.20+ does if `binlog_transaction_compression` is enabled. GTID events and events outside
+/// of transactions are kept as is, and so are transactions finished by `ROLLBACK`.
+///
+/// `log_pos` of every returned event is recalculated, so the output can be written
+/// sequentially (using [`Event::write_raw`]) into a new binlog file.
+#[derive(Debug, Clone)]
+pub struct PayloadCompressor<C> {
+    codec: C,
+    log_pos: u64,
+    transaction: Vec<Event<'static>>,
+}
+
+impl<C: PayloadCodec> PayloadCompressor<C> {
+    /// Creates a new compressor using the given codec.
+    ///
+    /// Output is assumed to start right after the binlog file header.
+    pub fn new(codec: C) -> Self {
+        Self {
+            codec,
+            log_pos: BinlogFileHeader::LEN as u64,
+            transaction: Vec::new(),
+        }
+    }
+
+    /// Returns modified `self` with the given position of the next output event.
+    pub fn with_log_pos(mut self, log_pos: u64) -> Self {
+        self.log_pos = log_pos;
+        self
+    }
+
+    /// Returns the position of the next output event.
+    pub fn log_pos(&self) -> u64 {
+        self.log_pos
+    }
+
+    /// Feeds the next event and returns events that are ready to be written.
+    pub fn feed(&mut self, event: Event<'static>) -> io::Result<Vec<Event<'static>>> {
+        let query = match event.header().event_type() {
+            Ok(EventType::QUERY_EVENT) => {
+                Some(event.read_event::<QueryEvent>()?.query_raw().to_vec())
+            }
+            _ => None,
+        };
+
+        if self.transaction.is_empty() {
+            if query.as_deref() == Some(b"BEGIN") {
+                self.transaction.push(event);
+                return Ok(Vec::new());
+            }
+            return Ok(vec![self.reposition(event)?]);
+        }
+
+        let is_commit = match event.header().event_type() {
+            Ok(EventType::XID_EVENT) => true,
+            Ok(EventType::QUERY_EVENT) => query.as_deref() == Some(b"COMMIT"),
+            _ => false,
+        };
+        let is_rollback = query.as_deref() == Some(b"ROLLBACK");
+
+        self.transaction.push(event);
+        if is_commit {
+            let payload = self.wrap()?;
+            Ok(vec![self.reposition(payload)?])
+        } else if is_rollback {
+            self.finish()
+        } else {
+            Ok(Vec::new())
+        }
+    }
+
+    /// Returns pending events of an unfinished transaction (as is).
+    pub fn finish(&mut self) -> io::Result<Vec<Event<'static>>> {
+        std::mem::take(&mut self.transaction)
+            .into_iter()
+            .map(|event| self.reposition(event))
+            .collect()
+    }
+
+    /// Wraps the buffered transaction into a payload event.
+    fn wrap(&mut self) -> io::Result<Event<'static>> {
+        let events = std::mem::take(&mut self.transaction);
+        let first = &events[0];
+        let payload = TransactionPayloadEvent::pack(&events, &self.codec)?;
+
+        let header = BinlogEventHeader::new(
+            first.header().timestamp(),
+            EventType::TRANSACTION_PAYLOAD_EVENT,
+            first.header().server_id(),
+            0,
+            0,
+            EventFlags::empty(),
+        );
+        Event::try_from_data(
+            header,
+            &EventData::TransactionPayloadEvent(payload),
+            first.fde(),
+            first.footer().get_checksum_alg().ok().flatten(),
+        )
+    }
+
+    /// Updates `log_pos` of the given event.
+    fn reposition(&mut self, event: Event<'static>) -> io::Result<Event<'static>> {
+        self.log_pos += event.header().event_size() as u64;
+        let log_pos = u32::try_from(self.log_pos)
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "log position overflow"))?;
+        Ok(event.with_log_pos(log_pos))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+
+    use std::time::Duration;
+
+    use super::{
+        string_literals, Anonymizer, PayloadCompressor, Redaction, RedactionRules, TableIdRemapper,
+        TimestampShifter,
+    };
+    use crate::{
+        binlog::{
+            consts::{BinlogVersion, EventType, PayloadCompressionType, StatusVarKey},
+            events::{
+                EventData, NoCompression, PayloadCodec, StatusVarVal, TransactionPayloadEvent,
+            },
+            value::BinlogValue,
+            BinlogFile, BinlogFileHeader,
+        },
+        value::Value,
+    };
+
+    #[test]
+    fn should_remap_table_ids() -> io::Result<()> {
+        let file_data = std::fs::read("./test-data/binlogs/ver_trunk_row_v2.001")?;
+        let binlog_file = BinlogFile::new(BinlogVersion::Version4, &file_data[..])?;
+
+        let mut remapper = TableIdRemapper::new(|id| id + 1000);
+        let mut last_table_map = None;
+        let mut rows_events = 0;
+        for ev in binlog_file {
+            let ev = ev?;
+            let data = match ev.read_data()? {
+                Some(data) => data,
+                None => continue,
+            };
+            let original_id = match data {
+                EventData::TableMapEvent(ref ev) => Some(ev.table_id()),
+                EventData::RowsEvent(ref ev) => Some(ev.table_id()),
+                _ => None,
+            };
+            match remapper.remap(data)? {
+                EventData::TableMapEvent(ev) => {
+                    assert_eq!(Some(ev.table_id()), original_id.map(|id| id + 1000));
+                    last_table_map = Some(ev.table_id());
+                }
+                EventData::RowsEvent(ev) => {
+                    assert_eq!(Some(ev.table_id()), original_id.map(|id| id + 1000));
+                    assert_eq!(Some(ev.table_id()), last_table_map);
+                    rows_events += 1;
+                }
+                _ => (),
+            }
+        }
+        assert!(rows_events > 0);
+
+        // rows event without a table map
+        let binlog_file = BinlogFile::new(BinlogVersion::Version4, &file_data[..])?;
+        let mut remapper = TableIdRemapper::new(|id| id);
+        let err = binlog_file
+            .filter_map(|ev| match ev.unwrap().read_data().unwrap() {
+                Some(EventData::RowsEvent(ev)) => Some(ev.into_owned()),
+                _ => None,
+            })
+            .map(|ev| remapper.remap(EventData::RowsEvent(ev)))
+            .find_map(Result::err)
+            .unwrap();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+        Ok(())
+    }
+
+    #[test]
+    fn should_find_string_literals() {
+        let query = br#"INSERT INTO `a'b` VALUES ('x\'y', "it""s", 'z') -- 'no'
+/* 'no' */ # 'no'
+"#;
+        let literals = string_literals(query)
+            .into_iter()
+            .map(|range| &query[range])
+            .collect::<Vec<_>>();
+        assert_eq!(literals, vec![&br"x\'y"[..], br#"it""s"#, b"z"]);
+    }
+
+    #[test]
+    fn should_anonymize_binlog() -> io::Result<()> {
+        let file_data = std::fs::read("./test-data/binlogs/mysql-enum-string-set.000001")?;
+        let binlog_file = BinlogFile::new(BinlogVersion::Version4, &file_data[..])?;
+
+        let rules = RedactionRules::new(Redaction::Hash);
+        let mut anonymizer = Anonymizer::new(rules).with_salt("salt");
+
+        let mut output = file_data[..4].to_vec();
+        let mut original = BinlogFile::new(BinlogVersion::Version4, &file_data[..])?;
+        let mut redacted_cells = 0;
+        let mut redacted_queries = 0;
+        for ev in binlog_file {
+            let ev = ev?;
+            let orig = original.next().unwrap()?;
+            let ev = anonymizer.anonymize(ev)?;
+            assert_eq!(ev.data().len(), orig.data().len());
+
+            let mut checked = Vec::new();
+            ev.checked_write(BinlogVersion::Version4, &mut checked)?;
+            let mut raw = Vec::new();
+            ev.write_raw(&mut raw)?;
+            assert_eq!(raw, checked);
+            output.extend_from_slice(&raw);
+
+            match (ev.read_data()?, orig.read_data()?) {
+                (Some(EventData::RowsEvent(ev)), Some(EventData::RowsEvent(orig_ev))) => {
+                    let tme = original.reader().get_tme(orig_ev.table_id()).unwrap();
+                    for (row, orig_row) in ev.rows(tme).zip(orig_ev.rows(tme)) {
+                        let (row, orig_row) = (row?, orig_row?);
+                        let images = vec![(row.0, orig_row.0), (row.1, orig_row.1)];
+                        for (row, orig_row) in images {
+                            let (row, orig_row) = match (row, orig_row) {
+                                (Some(row), Some(orig_row)) => (row, orig_row),
+                                (None, None) => continue,
+                                _ => panic!("image mismatch"),
+                            };
+                            for i in 0..row.len() {
+                                let column_type = row.columns_ref()[i].column_type();
+                                match (row.as_ref(i), orig_row.as_ref(i)) {
+                                    (
+                                        Some(BinlogValue::Value(Value::Bytes(a))),
+                                        Some(BinlogValue::Value(Value::Bytes(b))),
+                                    ) if column_type.is_character_type() => {
+                                        assert_eq!(a.len(), b.len());
+                                        if !a.is_empty() {
+                                            assert_ne!(a, b);
+                                            redacted_cells += 1;
+                                        }
+                                    }
+                                    (a, b) => assert_eq!(a, b),
+                                }
+                            }
+                        }
+                    }
+                }
+                (Some(EventData::QueryEvent(ev)), Some(EventData::QueryEvent(orig_ev))) => {
+                    if ev.query_raw() != orig_ev.query_raw() {
+                        redacted_queries += 1;
+                    }
+                    assert_eq!(
+                        string_literals(ev.query_raw()),
+                        string_literals(orig_ev.query_raw())
+                    );
+                }
+                _ => (),
+            }
+        }
+        assert!(redacted_cells > 0);
+        assert!(redacted_queries > 0);
+
+        // anonymized binlog is still valid
+        let binlog_file = BinlogFile::new(BinlogVersion::Version4, &output[..])?;
+        for ev in binlog_file {
+            ev?.read_data()?;
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn should_shift_timestamps() -> io::Result<()> {
+        fn micros(data: &Option<EventData<'_>>) -> Option<u32> {
+            match data {
+                Some(EventData::QueryEvent(ev)) => ev
+                    .status_vars()
+                    .get_status_var(StatusVarKey::Microseconds)
+                    .and_then(|var| match var.get_value() {
+                        Ok(StatusVarVal::Microseconds(x)) => Some(x),
+                        _ => None,
+                    }),
+                _ => None,
+            }
+        }
+
+        let forward = TimestampShifter::forward(Duration::from_micros(86_400_999_999));
+        let backward = TimestampShifter::backward(Duration::from_micros(86_400_999_999));
+        assert_eq!(backward.offset_micros(), -86_400_999_999);
+
+        let mut with_micros = 0;
+        for file in std::fs::read_dir("./test-data/binlogs")? {
+            let file_data = std::fs::read(file?.path())?;
+            let binlog_file = match BinlogFile::new(BinlogVersion::Version4, &file_data[..]) {
+                Ok(file) => file,
+                Err(_) => continue,
+            };
+            let mut prev = (0, 0);
+            for ev in binlog_file {
+                let ev = match ev {
+                    Ok(ev) => ev,
+                    Err(_) => break,
+                };
+                let timestamp = ev.header().timestamp();
+                let orig_micros = match ev.read_data() {
+                    Ok(data) => micros(&data),
+                    Err(_) => continue,
+                };
+
+                let shifted = forward.shift(ev.clone())?;
+                let new_micros = micros(&shifted.read_data()?);
+                let mut checked = Vec::new();
+                shifted.checked_write(BinlogVersion::Version4, &mut checked)?;
+                let mut raw = Vec::new();
+                shifted.write_raw(&mut raw)?;
+                assert_eq!(raw, checked);
+
+                if timestamp == 0 {
+                    assert_eq!(shifted.header().timestamp(), 0);
+                    continue;
+                }
+
+                let before = timestamp as u64 * 1_000_000 + orig_micros.unwrap_or(0) as u64;
+                let after = shifted.header().timestamp() as u64 * 1_000_000
+                    + new_micros.unwrap_or(0) as u64;
+                if orig_micros.is_some() {
+                    with_micros += 1;
+                    assert_eq!(after, before + 86_400_999_999);
+                } else {
+                    assert_eq!(shifted.header().timestamp(), timestamp + 86_400);
+                }
+                if before >= prev.0 {
+                    assert!(after >= prev.1);
+                }
+                prev = (before, after);
+
+                if orig_micros.is_some() {
+                    // sub-second part of the offset is lost for events without microseconds
+                    let restored = backward.shift(shifted)?;
+                    assert_eq!(restored.header().timestamp(), timestamp);
+                    assert_eq!(restored.raw_data(), ev.raw_data());
+                }
+            }
+        }
+        assert!(with_micros > 0);
+
+        Ok(())
+    }
+
+    fn wrap_transactions_into_payloads(codec: impl PayloadCodec + Copy) -> io::Result<()> {
+        let file_data = std::fs::read("./test-data/binlogs/binlog_transaction_with_GTID.000001")?;
+        let original = BinlogFile::new(BinlogVersion::Version4, &file_data[..])?
+            .collect::<io::Result<Vec<_>>>()?;
+
+        let mut compressor = PayloadCompressor::new(codec);
+        let mut output = BinlogFileHeader::VALUE.to_vec();
+        for ev in original.iter().cloned() {
+            for ev in compressor.feed(ev)? {
+                ev.write_raw(&mut output)?;
+            }
+        }
+        for ev in compressor.finish()? {
+            ev.write_raw(&mut output)?;
+        }
+        assert_eq!(compressor.log_pos(), output.len() as u64);
+
+        let mut unpacked = Vec::new();
+        let mut payloads = 0;
+        let mut offset = BinlogFileHeader::LEN;
+        for ev in BinlogFile::new(BinlogVersion::Version4, &output[..])? {
+            let ev = ev?;
+            offset += ev.header().event_size() as usize;
+            assert_eq!(ev.header().log_pos() as usize, offset);
+            match ev.read_data()? {
+                Some(EventData::TransactionPayloadEvent(payload)) => {
+                    assert_eq!(payload.compression_type(), Ok(codec.compression_type()));
+                    payloads += 1;
+                    unpacked.extend(payload.unpack(ev.fde(), codec)?);
+                }
+                _ => unpacked.push(ev),
+            }
+        }
+        assert!(payloads > 0);
+
+        assert_eq!(unpacked.len(), original.len());
+        for (ev, orig) in unpacked.iter().zip(&original) {
+            assert_eq!(ev.header().event_type_raw(), orig.header().event_type_raw());
+            assert_eq!(ev.header().timestamp(), orig.header().timestamp());
+            assert_eq!(ev.data(), orig.data());
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn should_wrap_transactions_into_payloads() -> io::Result<()> {
+        wrap_transactions_into_payloads(NoCompression)?;
+
+        // codec must match the compression type
+        let file_data = std::fs::read("./test-data/binlogs/binlog_transaction_with_GTID.000001")?;
+        let fde_event = BinlogFile::new(BinlogVersion::Version4, &file_data[..])?
+            .next()
+            .unwrap()?;
+        assert_eq!(
+            fde_event.header().event_type(),
+            Ok(EventType::FORMAT_DESCRIPTION_EVENT)
+        );
+        let payload = TransactionPayloadEvent::new(PayloadCompressionType::ZSTD, 0, &[][..]);
+        let err = payload.unpack(fde_event.fde(), NoCompression).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+
+        Ok(())
+    }
+
+    #[cfg(feature = "zstd")]
+    #[test]
+    fn should_wrap_transactions_into_zstd_payloads() -> io::Result<()> {
+        wrap_transactions_into_payloads(crate::binlog::events::ZstdCompression::default())
+    }
+}
@@ -0,0 +1,388 @@
+// Copyright (c) 2023 Anatoly Ikorsky
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Event size statistics and large transaction detection for binlog streams.
+
+use std::{collections::HashMap, io};
+
+use super::events::{Event, EventData, GtidEvent, TableMapEvent};
+
+/// Histogram of event sizes with power-of-two buckets.
+#[derive(Debug, Default, Clone, Eq, PartialEq, Hash)]
+pub struct EventSizeHistogram {
+    /// Bucket `i` counts sizes in `[2^(i-1), 2^i)` (bucket `0` counts empty events).
+    counts: Vec<u64>,
+    total_events: u64,
+    total_bytes: u64,
+    max: u64,
+}
+
+impl EventSizeHistogram {
+    /// Creates an empty histogram.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the given event size to the histogram.
+    pub fn add(&mut self, size: u64) {
+        let bucket = (u64::BITS - size.leading_zeros()) as usize;
+        if self.counts.len() <= bucket {
+            self.counts.resize(bucket + 1, 0);
+        }
+        self.counts[bucket] += 1;
+        self.total_events += 1;
+        self.total_bytes += size;
+        self.max = self.max.max(size);
+    }
+
+    /// Returns non-empty buckets as `(upper bound (exclusive), number of events)` pairs
+    /// in ascending order.
+    pub fn buckets(&self) -> impl Iterator<Item = (u64, u64)> + '_ {
+        self.counts
+            .iter()
+            .enumerate()
+            .filter(|(_, count)| **count > 0)
+            .map(|(i, count)| (1_u64.checked_shl(i as u32).unwrap_or(u64::MAX), *count))
+    }
+
+    /// Returns the number of events.
+    pub fn total_events(&self) -> u64 {
+        self.total_events
+    }
+
+    /// Returns the total size of events in bytes.
+    pub fn total_bytes(&self) -> u64 {
+        self.total_bytes
+    }
+
+    /// Returns the size of the largest event.
+    pub fn max(&self) -> u64 {
+        self.max
+    }
+}
+
+/// Statistics of a single table within a transaction.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct TableStats {
+    schema: String,
+    table: String,
+    rows: u64,
+    bytes: u64,
+}
+
+impl TableStats {
+    /// Returns the schema name.
+    pub fn schema(&self) -> &str {
+        &self.schema
+    }
+
+    /// Returns the table name.
+    pub fn table(&self) -> &str {
+        &self.table
+    }
+
+    /// Returns the number of changed rows.
+    pub fn rows(&self) -> u64 {
+        self.rows
+    }
+
+    /// Returns the total size of table map and rows events of this table.
+    pub fn bytes(&self) -> u64 {
+        self.bytes
+    }
+}
+
+/// A transaction that exceeds thresholds of the [`LargeTransactionDetector`].
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct LargeTransaction {
+    gtid: Option<([u8; GtidEvent::ENCODED_SID_LENGTH], u64)>,
+    start_pos: u64,
+    end_pos: u64,
+    bytes: u64,
+    rows: u64,
+    tables: Vec<TableStats>,
+}
+
+impl LargeTransaction {
+    /// Returns the SID and the GNO of the transaction (if it has a GTID).
+    pub fn gtid(&self) -> Option<([u8; GtidEvent::ENCODED_SID_LENGTH], u64)> {
+        self.gtid
+    }
+
+    /// Returns the position of the first event of the transaction (`0` if unknown).
+    pub fn start_pos(&self) -> u64 {
+        self.start_pos
+    }
+
+    /// Returns the position right after the last event of the transaction (`0` if unknown).
+    pub fn end_pos(&self) -> u64 {
+        self.end_pos
+    }
+
+    /// Returns the total size of transaction events in bytes.
+    pub fn bytes(&self) -> u64 {
+        self.bytes
+    }
+
+    /// Returns the number of changed rows.
+    pub fn rows(&self) -> u64 {
+        self.rows
+    }
+
+    /// Returns statistics of tables changed by the transaction, in the order of appearance.
+    pub fn tables(&self) -> &[TableStats] {
+        &self.tables
+    }
+}
+
+/// Flags transactions that exceed the configured size or row count.
+///
+/// A transaction starts with a GTID event (or a `BEGIN` query if there is no GTID)
+/// and ends with an XID event, a `COMMIT`/`ROLLBACK` query, or a DDL query
+/// that follows the GTID event. Events outside of transactions are only
+/// accounted in the [`EventSizeHistogram`].
+///
+/// Useful to find transactions that cause replication lag.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct LargeTransactionDetector {
+    max_bytes: Option<u64>,
+    max_rows: Option<u64>,
+    histogram: EventSizeHistogram,
+    current: Option<TransactionState>,
+}
+
+/// State of a transaction being accumulated.
+#[derive(Debug, Clone, PartialEq)]
+struct TransactionState {
+    tx: LargeTransaction,
+    /// Whether `BEGIN` was seen.
+    began: bool,
+    /// Table maps of the transaction (value is an index in `tx.tables`).
+    tables: HashMap<u64, (TableMapEvent<'static>, usize)>,
+}
+
+impl LargeTransactionDetector {
+    /// Creates a new detector without thresholds (no transaction is flagged).
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns modified `self` with the given maximum transaction size in bytes.
+    pub fn with_max_bytes(mut self, max_bytes: Option<u64>) -> Self {
+        self.max_bytes = max_bytes;
+        self
+    }
+
+    /// Returns modified `self` with the given maximum number of changed rows.
+    pub fn with_max_rows(mut self, max_rows: Option<u64>) -> Self {
+        self.max_rows = max_rows;
+        self
+    }
+
+    /// Returns the maximum transaction size in bytes.
+    pub fn max_bytes(&self) -> Option<u64> {
+        self.max_bytes
+    }
+
+    /// Returns the maximum number of changed rows.
+    pub fn max_rows(&self) -> Option<u64> {
+        self.max_rows
+    }
+
+    /// Returns the histogram of sizes of all the events seen so far.
+    pub fn histogram(&self) -> &EventSizeHistogram {
+        &self.histogram
+    }
+
+    /// Accounts the given event.
+    ///
+    /// Returns the transaction if the event ends a transaction that exceeds
+    /// one of the thresholds.
+    pub fn feed(&mut self, event: &Event<'_>) -> io::Result<Option<LargeTransaction>> {
+        let event_size = event.header().event_size() as u64;
+        let log_pos = event.header().log_pos() as u64;
+        self.histogram.add(event_size);
+
+        let mut ends = false;
+        match event.read_data()? {
+            Some(EventData::GtidEvent(gtid)) => {
+                self.start(Some((gtid.sid(), gtid.gno())), event);
+            }
+            Some(EventData::AnonymousGtidEvent(_)) => {
+                self.start(None, event);
+            }
+            Some(EventData::QueryEvent(query)) => {
+                let text = query.query();
+                let text = text.trim();
+                if text.eq_ignore_ascii_case("BEGIN") {
+                    if self.current.is_none() {
+                        self.start(None, event);
+                    }
+                    if let Some(state) = self.current.as_mut() {
+                        state.began = true;
+                    }
+                } else if text.eq_ignore_ascii_case("COMMIT")
+                    || text.eq_ignore_ascii_case("ROLLBACK")
+                {
+                    ends = true;
+                } else if let Some(state) = self.current.as_ref() {
+                    // DDL is a transaction on its own
+                    ends = !state.began;
+                }
+            }
+            Some(EventData::XidEvent(_)) => ends = true,
+            Some(EventData::TableMapEvent(tme)) => {
+                if let Some(state) = self.current.as_mut() {
+                    let schema = tme.database_name().into_owned();
+                    let table = tme.table_name().into_owned();
+                    let idx = match state
+                        .tx
+                        .tables
+                        .iter()
+                        .position(|x| x.schema == schema && x.table == table)
+                    {
+                        Some(idx) => idx,
+                        None => {
+                            state.tx.tables.push(TableStats {
+                                schema,
+                                table,
+                                rows: 0,
+                                bytes: 0,
+                            });
+                            state.tx.tables.len() - 1
+                        }
+                    };
+                    state.tx.tables[idx].bytes += event_size;
+                    state.tables.insert(tme.table_id(), (tme.into_owned(), idx));
+                }
+            }
+            Some(EventData::RowsEvent(rows_event)) => {
+                if let Some(state) = self.current.as_mut() {
+                    if let Some((tme, idx)) = state.tables.get(&rows_event.table_id()) {
+                        let mut rows = 0;
+                        for row in rows_event.rows(tme) {
+                            row?;
+                            rows += 1;
+                        }
+                        let stats = &mut state.tx.tables[*idx];
+                        stats.rows += rows;
+                        stats.bytes += event_size;
+                        state.tx.rows += rows;
+                    }
+                }
+            }
+            _ => (),
+        }
+
+        let state = match self.current.as_mut() {
+            Some(state) => state,
+            None => return Ok(None),
+        };
+        state.tx.bytes += event_size;
+        state.tx.end_pos = log_pos;
+
+        if !ends {
+            return Ok(None);
+        }
+
+        let tx = self.current.take().map(|state| state.tx);
+        Ok(tx.filter(|tx| {
+            self.max_bytes.map(|max| tx.bytes > max).unwrap_or(false)
+                || self.max_rows.map(|max| tx.rows > max).unwrap_or(false)
+        }))
+    }
+
+    /// Starts a new transaction with the given event (an unfinished transaction is dropped).
+    fn start(
+        &mut self,
+        gtid: Option<([u8; GtidEvent::ENCODED_SID_LENGTH], u64)>,
+        event: &Event<'_>,
+    ) {
+        let header = event.header();
+        let start_pos = match header.log_pos() {
+            0 => 0,
+            log_pos => log_pos.saturating_sub(header.event_size()) as u64,
+        };
+        self.current = Some(TransactionState {
+            tx: LargeTransaction {
+                gtid,
+                start_pos,
+                end_pos: 0,
+                bytes: 0,
+                rows: 0,
+                tables: Vec::new(),
+            },
+            began: false,
+            tables: HashMap::new(),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+
+    use super::{EventSizeHistogram, LargeTransactionDetector};
+    use crate::binlog::{consts::BinlogVersion, BinlogFile};
+
+    #[test]
+    fn should_build_histogram() {
+        let mut histogram = EventSizeHistogram::new();
+        for size in [0, 19, 31, 32, 100, 5000] {
+            histogram.add(size);
+        }
+        assert_eq!(
+            histogram.buckets().collect::<Vec<_>>(),
+            vec![(1, 1), (32, 2), (64, 1), (128, 1), (8192, 1)]
+        );
+        assert_eq!(histogram.total_events(), 6);
+        assert_eq!(histogram.total_bytes(), 5182);
+        assert_eq!(histogram.max(), 5000);
+    }
+
+    #[test]
+    fn should_detect_large_transactions() -> io::Result<()> {
+        let file_data = std::fs::read("./test-data/binlogs/mysql-enum-string-set.000001")?;
+
+        let binlog_file = BinlogFile::new(BinlogVersion::Version4, &file_data[..])?;
+        let mut detector = LargeTransactionDetector::new().with_max_bytes(Some(800));
+        let mut found = Vec::new();
+        for ev in binlog_file {
+            found.extend(detector.feed(&ev?)?);
+        }
+        assert_eq!(found.len(), 1);
+        let tx = &found[0];
+        assert_eq!(tx.gtid().map(|(_, gno)| gno), Some(4));
+        assert_eq!((tx.start_pos(), tx.end_pos()), (1560, 2659));
+        assert_eq!(tx.bytes(), tx.end_pos() - tx.start_pos());
+        assert_eq!(tx.rows(), 1);
+        assert_eq!(tx.tables().len(), 1);
+        assert_eq!(
+            (tx.tables()[0].schema(), tx.tables()[0].table()),
+            ("mysql", "t")
+        );
+        assert_eq!(
+            detector.histogram().total_bytes(),
+            file_data.len() as u64 - 4
+        );
+
+        // by rows, with BEGIN/COMMIT transactions without GTIDs
+        let file_data = std::fs::read("./test-data/binlogs/ver_trunk_row_v2.001")?;
+        let binlog_file = BinlogFile::new(BinlogVersion::Version4, &file_data[..])?;
+        let mut detector = LargeTransactionDetector::new().with_max_rows(Some(1));
+        let mut found = Vec::new();
+        for ev in binlog_file {
+            found.extend(detector.feed(&ev?)?);
+        }
+        assert_eq!(found.len(), 2);
+        assert!(found.iter().all(|tx| tx.rows() == 2 && tx.gtid().is_none()));
+        assert_eq!(found[0].tables()[0].rows(), 2);
+
+        Ok(())
+    }
+}
@@ -6,20 +6,25 @@
 // option. All files in the project carrying such notice may not be copied,
 // modified, or distributed except according to those terms.
 
-use std::{convert::TryFrom, io};
+use std::{convert::TryFrom, io, str::FromStr};
+
+use bytes::BufMut;
 
 use crate::{
     binlog::{decimal, jsonb, jsondiff::JsonDiff, misc::*},
     constants::{ColumnFlags, ColumnType},
-    io::ParseBuf,
+    io::{BufMutExt, ParseBuf},
     misc::raw::int::*,
-    proto::MyDeserialize,
+    proto::{MyDeserialize, MySerialize},
     value::Value::{self, *},
 };
 
 use super::jsonb::JsonbToJsonError;
 
 /// Value of a binlog event.
+///
+/// Note that `TIMESTAMP` values are decoded as an UTC `Value::Date`
+/// (see [`timestamp2_from_binary`]), rather than seconds since unix epoch.
 #[derive(Debug, Clone, PartialEq)]
 pub enum BinlogValue<'a> {
     /// MySql value.
@@ -43,39 +48,398 @@ impl<'a> BinlogValue<'a> {
     }
 }
 
+impl BinlogValue<'_> {
+    /// Writes the binary representation of this value as stored in a rows event,
+    /// i.e. the reverse of the deserialization.
+    ///
+    /// `col_type` is the real column type (see `TableMapEvent::get_column_type`)
+    /// and `col_meta` is the column metadata. Returns an `InvalidInput` error if the value
+    /// doesn't fit the column. `NULL` values are not written into a rows event,
+    /// so it is an error to serialize them.
+    pub fn serialize_bin(
+        &self,
+        col_type: ColumnType,
+        col_meta: &[u8],
+        buf: &mut Vec<u8>,
+    ) -> io::Result<()> {
+        match self {
+            BinlogValue::Value(value) => serialize_value(value, col_type, col_meta, buf),
+            BinlogValue::Jsonb(value) if col_type == ColumnType::MYSQL_TYPE_JSON => {
+                put_json(buf, value)
+            }
+            BinlogValue::Jsonb(_) | BinlogValue::JsonDiff(_) => Err(invalid_value(col_type)),
+        }
+    }
+}
+
+/// Writes a binary representation of a non-JSONB value (see [`BinlogValue::serialize_bin`]).
+pub(crate) fn serialize_value(
+    value: &Value,
+    col_type: ColumnType,
+    col_meta: &[u8],
+    buf: &mut Vec<u8>,
+) -> io::Result<()> {
+    use ColumnType::*;
+
+    let meta_byte = |n: usize| col_meta.get(n).copied().unwrap_or_default();
+
+    match (col_type, value) {
+        (_, NULL) => Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "NULL values must be marked in the null bitmap",
+        )),
+        (MYSQL_TYPE_TINY, _) => {
+            buf.put_u8(int_value(value, i8::MIN as i128, u8::MAX as i128, col_type)? as u8);
+            Ok(())
+        }
+        (MYSQL_TYPE_SHORT, _) => {
+            let x = int_value(value, i16::MIN as i128, u16::MAX as i128, col_type)?;
+            buf.put_u16_le(x as u16);
+            Ok(())
+        }
+        (MYSQL_TYPE_INT24, _) => {
+            let x = int_value(value, -(1 << 23), (1 << 24) - 1, col_type)?;
+            buf.put_u24_le(x as u32);
+            Ok(())
+        }
+        (MYSQL_TYPE_LONG, _) => {
+            let x = int_value(value, i32::MIN as i128, u32::MAX as i128, col_type)?;
+            buf.put_u32_le(x as u32);
+            Ok(())
+        }
+        (MYSQL_TYPE_LONGLONG, _) => {
+            let x = int_value(value, i64::MIN as i128, u64::MAX as i128, col_type)?;
+            buf.put_u64_le(x as u64);
+            Ok(())
+        }
+        (MYSQL_TYPE_FLOAT, Float(x)) => {
+            buf.put_f32_le(*x);
+            Ok(())
+        }
+        (MYSQL_TYPE_FLOAT, Double(x)) => {
+            buf.put_f32_le(*x as f32);
+            Ok(())
+        }
+        (MYSQL_TYPE_DOUBLE, Double(x)) => {
+            buf.put_f64_le(*x);
+            Ok(())
+        }
+        (MYSQL_TYPE_DOUBLE, Float(x)) => {
+            buf.put_f64_le(*x as f64);
+            Ok(())
+        }
+        (MYSQL_TYPE_TIMESTAMP, _) => {
+            let x = int_value(value, 0, u32::MAX as i128, col_type)?;
+            buf.put_u32_le(x as u32);
+            Ok(())
+        }
+        (MYSQL_TYPE_TIME, Time(false, 0, h, m, s, 0)) => {
+            buf.put_u24_le(*h as u32 * 10000 + *m as u32 * 100 + *s as u32);
+            Ok(())
+        }
+        (MYSQL_TYPE_DATETIME, Date(y, mo, d, h, mi, s, 0)) => {
+            let date = *y as u64 * 10000 + *mo as u64 * 100 + *d as u64;
+            let time = *h as u64 * 10000 + *mi as u64 * 100 + *s as u64;
+            buf.put_u64_le(date * 1_000_000 + time);
+            Ok(())
+        }
+        (MYSQL_TYPE_YEAR, _) => {
+            // zero year or 1901..=2155
+            let year = int_value(value, 0, 2155, col_type)?;
+            if year != 0 && year < 1901 {
+                return Err(invalid_value(col_type));
+            }
+            buf.put_u8(if year == 0 { 0 } else { (year - 1900) as u8 });
+            Ok(())
+        }
+        (MYSQL_TYPE_NEWDATE, Date(y, m, d, 0, 0, 0, 0)) => {
+            buf.put_u24_le(((*y as u32) << 9) | ((*m as u32) << 5) | *d as u32);
+            Ok(())
+        }
+        (MYSQL_TYPE_TIMESTAMP2, _) => timestamp2_to_binary(buf, value, meta_byte(0)),
+        (MYSQL_TYPE_DATETIME2, _) => datetime2_to_binary(buf, value, meta_byte(0)),
+        (MYSQL_TYPE_TIME2, _) => time2_to_binary(buf, value, meta_byte(0)),
+        (MYSQL_TYPE_BIT, _) => {
+            let nbits = meta_byte(1) as usize * 8 + meta_byte(0) as usize;
+            let nbytes = nbits.div_ceil(8);
+            let bytes = match value {
+                Bytes(bytes) => bytes.clone(),
+                _ => (int_value(value, 0, u64::MAX as i128, col_type)? as u64)
+                    .to_be_bytes()
+                    .to_vec(),
+            };
+            put_padded(buf, &bytes, nbytes, false, col_type)
+        }
+        (MYSQL_TYPE_NEWDECIMAL, _) => {
+            let text = match value {
+                Bytes(bytes) => String::from_utf8_lossy(bytes).into_owned(),
+                Int(x) => x.to_string(),
+                UInt(x) => x.to_string(),
+                _ => return Err(invalid_value(col_type)),
+            };
+            let dec = decimal::Decimal::from_str(&text).map_err(|_| invalid_value(col_type))?;
+            dec.write_bin_with_precision(buf, meta_byte(0) as usize, meta_byte(1) as usize)
+        }
+        (MYSQL_TYPE_ENUM, _) => match meta_byte(1) {
+            1 => {
+                buf.put_u8(int_value(value, 0, u8::MAX as i128, col_type)? as u8);
+                Ok(())
+            }
+            2 => {
+                buf.put_u16_le(int_value(value, 0, u16::MAX as i128, col_type)? as u16);
+                Ok(())
+            }
+            _ => Err(io::Error::new(io::ErrorKind::InvalidInput, "Unknown ENUM")),
+        },
+        (MYSQL_TYPE_SET, _) => {
+            let nbytes = meta_byte(1) as usize;
+            let bytes = match value {
+                Bytes(bytes) => bytes.clone(),
+                _ => (int_value(value, 0, u64::MAX as i128, col_type)? as u64)
+                    .to_le_bytes()
+                    .to_vec(),
+            };
+            put_padded(buf, &bytes, nbytes, true, col_type)
+        }
+        (MYSQL_TYPE_JSON, Bytes(bytes)) => {
+            let json: serde_json::Value =
+                serde_json::from_slice(bytes).map_err(|_| invalid_value(col_type))?;
+            put_json(buf, &jsonb::Value::try_from(&json)?)
+        }
+        (
+            MYSQL_TYPE_TINY_BLOB
+            | MYSQL_TYPE_MEDIUM_BLOB
+            | MYSQL_TYPE_LONG_BLOB
+            | MYSQL_TYPE_BLOB
+            | MYSQL_TYPE_GEOMETRY,
+            Bytes(bytes),
+        ) => {
+            let pack_len = meta_byte(0) as usize;
+            if !(1..=4).contains(&pack_len) {
+                return Err(io::Error::new(io::ErrorKind::InvalidInput, "Unknown BLOB"));
+            }
+            if (bytes.len() as u64) >> (pack_len * 8) > 0 {
+                return Err(too_long(col_type));
+            }
+            buf.put_uint_le(bytes.len() as u64, pack_len);
+            buf.put_slice(bytes);
+            Ok(())
+        }
+        (MYSQL_TYPE_VARCHAR | MYSQL_TYPE_VAR_STRING, Bytes(bytes)) => {
+            let max_len = u16::from_le_bytes([meta_byte(0), meta_byte(1)]) as usize;
+            put_string(buf, bytes, max_len, col_type)
+        }
+        (MYSQL_TYPE_STRING, Bytes(bytes)) => {
+            let (byte0, byte1) = (meta_byte(0) as usize, meta_byte(1) as usize);
+            let max_len = byte1 | (((byte0 & 0x30) ^ 0x30) << 4);
+            put_string(buf, bytes, max_len, col_type)
+        }
+        _ => Err(invalid_value(col_type)),
+    }
+}
+
+fn invalid_value(col_type: ColumnType) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidInput,
+        format!("value is not suitable for a {:?} column", col_type),
+    )
+}
+
+fn too_long(col_type: ColumnType) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidInput,
+        format!("value is too long for a {:?} column", col_type),
+    )
+}
+
+/// Returns an integer value (or a text representation of an integer) within the given range.
+fn int_value(value: &Value, min: i128, max: i128, col_type: ColumnType) -> io::Result<i128> {
+    let x = match value {
+        Int(x) => *x as i128,
+        UInt(x) => *x as i128,
+        Bytes(bytes) => std::str::from_utf8(bytes)
+            .ok()
+            .and_then(|x| x.parse().ok())
+            .ok_or_else(|| invalid_value(col_type))?,
+        _ => return Err(invalid_value(col_type)),
+    };
+    if x < min || x > max {
+        return Err(too_long(col_type));
+    }
+    Ok(x)
+}
+
+/// Writes exactly `len` bytes of a BIT (big-endian) or SET (little-endian) value.
+fn put_padded(
+    buf: &mut Vec<u8>,
+    bytes: &[u8],
+    len: usize,
+    little_endian: bool,
+    col_type: ColumnType,
+) -> io::Result<()> {
+    // leading (or trailing for little-endian) zeros are insignificant
+    let significant = if little_endian {
+        let end = bytes.iter().rposition(|x| *x != 0).map_or(0, |x| x + 1);
+        &bytes[..end]
+    } else {
+        let start = bytes.iter().position(|x| *x != 0).unwrap_or(bytes.len());
+        &bytes[start..]
+    };
+    if significant.len() > len {
+        return Err(too_long(col_type));
+    }
+    let zeros = len - significant.len();
+    if little_endian {
+        buf.put_slice(significant);
+        buf.put_bytes(0, zeros);
+    } else {
+        buf.put_bytes(0, zeros);
+        buf.put_slice(significant);
+    }
+    Ok(())
+}
+
+/// Writes a string value prefixed with its length (1 byte if `max_len < 256`, 2 bytes otherwise).
+fn put_string(
+    buf: &mut Vec<u8>,
+    bytes: &[u8],
+    max_len: usize,
+    col_type: ColumnType,
+) -> io::Result<()> {
+    if bytes.len() > max_len {
+        return Err(too_long(col_type));
+    }
+    if max_len < 256 {
+        buf.put_u8(bytes.len() as u8);
+    } else {
+        buf.put_u16_le(bytes.len() as u16);
+    }
+    buf.put_slice(bytes);
+    Ok(())
+}
+
+/// Writes a JSONB value prefixed with its length.
+fn put_json(buf: &mut Vec<u8>, value: &jsonb::Value<'_>) -> io::Result<()> {
+    let mut data = Vec::new();
+    value.serialize(&mut data);
+    let len = u32::try_from(data.len()).map_err(|_| too_long(ColumnType::MYSQL_TYPE_JSON))?;
+    buf.put_u32_le(len);
+    buf.put_slice(&data);
+    Ok(())
+}
+
+/// Returns the type of a column stored in a rows event (i.e. the type of a typed array element)
+/// and the max length of a `MYSQL_TYPE_STRING` column.
+fn real_type_and_length(mut col_type: ColumnType, col_meta: &[u8]) -> (ColumnType, usize) {
+    use ColumnType::*;
+
+    let mut length = 0_usize;
+
+    if col_type == MYSQL_TYPE_TYPED_ARRAY {
+        let type_byte = col_meta[0];
+        col_type = ColumnType::try_from(type_byte).unwrap_or(col_type);
+    }
+
+    if col_type == MYSQL_TYPE_STRING {
+        if col_meta[0] >= 1 {
+            let byte0 = col_meta[0] as usize;
+            let byte1 = col_meta[1] as usize;
+
+            if (byte0 & 0x30) != 0x30 {
+                // a long CHAR() field: see #37426
+                length = byte1 | (((byte0 & 0x30) ^ 0x30) << 4);
+            } else {
+                length = byte1;
+            }
+        } else {
+            length = (ParseBuf(col_meta)).eat_u16_le() as usize;
+        }
+    }
+
+    (col_type, length)
+}
+
+/// Skips a non-null value stored in a rows event without decoding it.
+///
+/// `col_type` and `col_meta` are the same as for the `BinlogValue` deserialization.
+pub(crate) fn skip_value(
+    col_type: ColumnType,
+    col_meta: &[u8],
+    buf: &mut ParseBuf<'_>,
+) -> io::Result<()> {
+    use ColumnType::*;
+
+    let (col_type, length) = real_type_and_length(col_type, col_meta);
+    // fractional seconds part of temporal types
+    let frac_len = |dec: u8| match dec {
+        1..=6 => (dec as usize).div_ceil(2),
+        _ => 0,
+    };
+    let length_prefix = |len: usize, buf: &mut ParseBuf<'_>| -> io::Result<usize> {
+        Ok(match len {
+            1 => *buf.parse::<RawInt<u8>>(())? as usize,
+            2 => *buf.parse::<RawInt<LeU16>>(())? as usize,
+            3 => *buf.parse::<RawInt<LeU24>>(())? as usize,
+            4 => *buf.parse::<RawInt<LeU32>>(())? as usize,
+            _ => return Err(io::Error::new(io::ErrorKind::InvalidData, "Unknown BLOB")),
+        })
+    };
+
+    let nbytes = match col_type {
+        MYSQL_TYPE_TINY | MYSQL_TYPE_YEAR => 1,
+        MYSQL_TYPE_SHORT => 2,
+        MYSQL_TYPE_INT24 | MYSQL_TYPE_TIME | MYSQL_TYPE_NEWDATE => 3,
+        MYSQL_TYPE_LONG | MYSQL_TYPE_FLOAT | MYSQL_TYPE_TIMESTAMP => 4,
+        MYSQL_TYPE_LONGLONG | MYSQL_TYPE_DOUBLE | MYSQL_TYPE_DATETIME => 8,
+        MYSQL_TYPE_BIT => (col_meta[1] as usize * 8 + col_meta[0] as usize).div_ceil(8),
+        MYSQL_TYPE_TIMESTAMP2 => 4 + frac_len(col_meta[0]),
+        MYSQL_TYPE_DATETIME2 => 5 + frac_len(col_meta[0]),
+        MYSQL_TYPE_TIME2 => 3 + frac_len(col_meta[0]),
+        MYSQL_TYPE_JSON => length_prefix(4, buf)?,
+        MYSQL_TYPE_NEWDECIMAL => {
+            let (precision, scale) = (col_meta[0] as usize, col_meta[1] as usize);
+            decimal::check_precision(precision, scale, io::ErrorKind::InvalidData)?;
+            decimal::decimal_bin_size(precision, scale)
+        }
+        MYSQL_TYPE_ENUM => match col_meta[1] {
+            1 | 2 => col_meta[1] as usize,
+            _ => return Err(io::Error::new(io::ErrorKind::InvalidData, "Unknown ENUM")),
+        },
+        MYSQL_TYPE_SET => col_meta[1] as usize,
+        MYSQL_TYPE_TINY_BLOB
+        | MYSQL_TYPE_MEDIUM_BLOB
+        | MYSQL_TYPE_LONG_BLOB
+        | MYSQL_TYPE_BLOB
+        | MYSQL_TYPE_GEOMETRY => length_prefix(col_meta[0] as usize, buf)?,
+        MYSQL_TYPE_VARCHAR | MYSQL_TYPE_VAR_STRING => {
+            let type_len = (col_meta[0] as u16 | ((col_meta[1] as u16) << 8)) as usize;
+            length_prefix(if type_len < 256 { 1 } else { 2 }, buf)?
+        }
+        MYSQL_TYPE_STRING => length_prefix(if length < 256 { 1 } else { 2 }, buf)?,
+        _ => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "Don't know how to handle column",
+            ))
+        }
+    };
+
+    buf.parse::<&[u8]>(nbytes)?;
+    Ok(())
+}
+
 impl<'de> MyDeserialize<'de> for BinlogValue<'de> {
     const SIZE: Option<usize> = None;
     /// <col_type, col_meta, is_unsigned, is_partial>
     type Ctx = (ColumnType, &'de [u8], bool, bool);
 
     fn deserialize(
-        (mut col_type, col_meta, is_unsigned, is_partial): Self::Ctx,
+        (col_type, col_meta, is_unsigned, is_partial): Self::Ctx,
         buf: &mut ParseBuf<'de>,
     ) -> io::Result<Self> {
         use ColumnType::*;
 
-        let mut length = 0_usize;
-
-        if col_type == MYSQL_TYPE_TYPED_ARRAY {
-            let type_byte = col_meta[0];
-            col_type = ColumnType::try_from(type_byte).unwrap_or(col_type);
-        }
-
-        if col_type == MYSQL_TYPE_STRING {
-            if col_meta[0] >= 1 {
-                let byte0 = col_meta[0] as usize;
-                let byte1 = col_meta[1] as usize;
-
-                if (byte0 & 0x30) != 0x30 {
-                    // a long CHAR() field: see #37426
-                    length = byte1 | (((byte0 & 0x30) ^ 0x30) << 4);
-                } else {
-                    length = byte1;
-                }
-            } else {
-                length = (ParseBuf(col_meta)).eat_u16_le() as usize;
-            }
-        }
+        let (col_type, mut length) = real_type_and_length(col_type, col_meta);
 
         match col_type {
             MYSQL_TYPE_TINY | MYSQL_TYPE_SHORT | MYSQL_TYPE_LONG | MYSQL_TYPE_LONGLONG
@@ -138,33 +502,17 @@ impl<'de> MyDeserialize<'de> for BinlogValue<'de> {
             }
             MYSQL_TYPE_BIT => {
                 let nbits = col_meta[1] as usize * 8 + (col_meta[0] as usize);
-                let nbytes = (nbits + 7) / 8;
+                let nbytes = nbits.div_ceil(8);
                 let bytes: &[u8] = buf.parse(nbytes)?;
                 Ok(BinlogValue::Value(Bytes(bytes.into())))
             }
             MYSQL_TYPE_TIMESTAMP2 => {
-                let dec = col_meta[0];
-                let (sec, usec) = my_timestamp_from_binary(&mut *buf, dec)?;
-                if usec == 0 {
-                    Ok(BinlogValue::Value(Bytes(sec.to_string().into_bytes())))
-                } else {
-                    Ok(BinlogValue::Value(Bytes(
-                        format!("{}.{:06}", sec, usec).into_bytes(),
-                    )))
-                }
+                timestamp2_from_binary(&mut *buf, col_meta[0]).map(BinlogValue::Value)
             }
             MYSQL_TYPE_DATETIME2 => {
-                let dec = col_meta[0];
-                my_datetime_packed_from_binary(&mut *buf, dec as u32)
-                    .map(datetime_from_packed)
-                    .map(BinlogValue::Value)
-            }
-            MYSQL_TYPE_TIME2 => {
-                let dec = col_meta[0];
-                my_time_packed_from_binary(&mut *buf, dec as u32)
-                    .map(time_from_packed)
-                    .map(BinlogValue::Value)
+                datetime2_from_binary(&mut *buf, col_meta[0]).map(BinlogValue::Value)
             }
+            MYSQL_TYPE_TIME2 => time2_from_binary(&mut *buf, col_meta[0]).map(BinlogValue::Value),
             MYSQL_TYPE_JSON => {
                 length = *buf.parse::<RawInt<LeU32>>(())? as usize;
                 let mut json_value_buf: ParseBuf = buf.parse(length)?;
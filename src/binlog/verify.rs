@@ -0,0 +1,425 @@
+// Copyright (c) 2023 Anatoly Ikorsky
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Integrity checks for binlog files.
+
+use std::{fmt, io, path::Path};
+
+use crate::{io::ParseBuf, proto::MyDeserialize};
+
+use super::{
+    consts::{BinlogChecksumAlg, BinlogVersion, EventType},
+    events::{BinlogEventHeader, RotateEvent},
+    BinlogFileHeader, EventStreamReader,
+};
+
+/// An issue found by [`verify`].
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub enum Issue {
+    /// File doesn't start with the binlog file header.
+    InvalidMagic,
+    /// The first event isn't a format description event.
+    MissingFde,
+    /// Event at the given offset is truncated (the file ends in the middle of the event).
+    Truncated { offset: u64 },
+    /// Event at the given offset can't be read.
+    Unreadable { offset: u64, message: String },
+    /// Checksum of the event at the given offset doesn't match its data.
+    ChecksumMismatch {
+        offset: u64,
+        expected: u32,
+        found: u32,
+    },
+    /// `log_pos` of the event at the given offset doesn't point to the next event
+    /// (or, for events of a relay log, is less than the expected position in the source's binlog).
+    LogPosMismatch {
+        offset: u64,
+        expected: u64,
+        found: u64,
+    },
+    /// The file doesn't end with a rotate event (it's either truncated or still in use).
+    MissingRotate,
+    /// The final rotate event doesn't point to the next file.
+    RotateMismatch { expected: String, found: String },
+}
+
+impl Issue {
+    /// Returns the offset of the offending event (if any).
+    pub fn offset(&self) -> Option<u64> {
+        match *self {
+            Self::Truncated { offset }
+            | Self::Unreadable { offset, .. }
+            | Self::ChecksumMismatch { offset, .. }
+            | Self::LogPosMismatch { offset, .. } => Some(offset),
+            Self::InvalidMagic => Some(0),
+            Self::MissingFde | Self::MissingRotate | Self::RotateMismatch { .. } => None,
+        }
+    }
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidMagic => write!(f, "invalid binlog file header"),
+            Self::MissingFde => write!(f, "format description event is missing"),
+            Self::Truncated { offset } => write!(f, "event at {} is truncated", offset),
+            Self::Unreadable { offset, message } => {
+                write!(f, "event at {} can't be read: {}", offset, message)
+            }
+            Self::ChecksumMismatch {
+                offset,
+                expected,
+                found,
+            } => write!(
+                f,
+                "checksum mismatch at {}: expected {:#010x}, found {:#010x}",
+                offset, expected, found
+            ),
+            Self::LogPosMismatch {
+                offset,
+                expected,
+                found,
+            } => write!(
+                f,
+                "log_pos mismatch at {}: expected {}, found {}",
+                offset, expected, found
+            ),
+            Self::MissingRotate => write!(f, "rotate event is missing at the end of the file"),
+            Self::RotateMismatch { expected, found } => {
+                write!(f, "rotate event points to {}, expected {}", found, expected)
+            }
+        }
+    }
+}
+
+/// Result of [`verify`].
+#[derive(Debug, Default, Clone, Eq, PartialEq)]
+pub struct Report {
+    events: usize,
+    valid_len: u64,
+    rotate: Option<(String, u64)>,
+    issues: Vec<Issue>,
+}
+
+impl Report {
+    /// Returns the number of events read.
+    pub fn events(&self) -> usize {
+        self.events
+    }
+
+    /// Returns the length of the readable part of the file
+    /// (issues may still exist within this part).
+    pub fn valid_len(&self) -> u64 {
+        self.valid_len
+    }
+
+    /// Returns the next binlog file and position defined by the final rotate event (if any).
+    pub fn rotate(&self) -> Option<(&str, u64)> {
+        self.rotate
+            .as_ref()
+            .map(|(name, pos)| (name.as_str(), *pos))
+    }
+
+    /// Returns issues in the order they were found.
+    pub fn issues(&self) -> &[Issue] {
+        &self.issues
+    }
+
+    /// Returns the offset of the first corrupt event (if any).
+    pub fn first_corrupt_offset(&self) -> Option<u64> {
+        self.issues.iter().filter_map(Issue::offset).min()
+    }
+
+    /// Returns `true` if no issues were found.
+    pub fn is_ok(&self) -> bool {
+        self.issues.is_empty()
+    }
+}
+
+/// Verifies the integrity of the given binlog file.
+///
+/// Checks the binlog file header, presence of the format description event, per-event
+/// checksums, continuity of the `log_pos` chain and the final rotate event, which is
+/// expected to point to the next file of the sequence (e.g. `binlog.000002` for `binlog.000001`).
+///
+/// Returns an error only if the file can't be read.
+pub fn verify(path: impl AsRef<Path>) -> io::Result<Report> {
+    let path = path.as_ref();
+    let data = std::fs::read(path)?;
+    let mut report = verify_bytes(&data);
+
+    let expected = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .and_then(next_file_name);
+    if let (Some(expected), Some((found, _))) = (expected, report.rotate.as_ref()) {
+        if *found != expected {
+            let found = found.clone();
+            report
+                .issues
+                .push(Issue::RotateMismatch { expected, found });
+        }
+    }
+
+    Ok(report)
+}
+
+/// Verifies the integrity of the given binlog file contents (see [`verify`]).
+///
+/// The name of the next file isn't checked since it's unknown.
+pub fn verify_bytes(data: &[u8]) -> Report {
+    let mut report = Report::default();
+
+    if data.len() < BinlogFileHeader::LEN
+        || data[..BinlogFileHeader::LEN] != BinlogFileHeader::VALUE
+    {
+        report.issues.push(Issue::InvalidMagic);
+        return report;
+    }
+
+    let mut reader = EventStreamReader::new(BinlogVersion::Version4);
+    let mut offset = BinlogFileHeader::LEN;
+    let mut last_event_type = None;
+    let mut local_server_id = None;
+    // the last position in the source's binlog (for relay logs)
+    let mut source_pos = 0;
+    report.valid_len = offset as u64;
+
+    while offset < data.len() {
+        let rest = &data[offset..];
+        let header = match rest.get(..BinlogEventHeader::LEN) {
+            Some(header) => BinlogEventHeader::deserialize((), &mut ParseBuf(header)),
+            None => {
+                report.issues.push(Issue::Truncated {
+                    offset: offset as u64,
+                });
+                break;
+            }
+        };
+        let event_size = match header {
+            Ok(ref header) if (header.event_size() as usize) < BinlogEventHeader::LEN => {
+                report.issues.push(Issue::Unreadable {
+                    offset: offset as u64,
+                    message: format!("invalid event size {}", header.event_size()),
+                });
+                break;
+            }
+            Ok(ref header) if header.event_size() as usize > rest.len() => {
+                report.issues.push(Issue::Truncated {
+                    offset: offset as u64,
+                });
+                break;
+            }
+            Ok(header) => header.event_size() as usize,
+            Err(err) => {
+                report.issues.push(Issue::Unreadable {
+                    offset: offset as u64,
+                    message: err.to_string(),
+                });
+                break;
+            }
+        };
+
+        let event = match reader.read(&rest[..event_size]) {
+            Ok(event) => event,
+            Err(err) => {
+                report.issues.push(Issue::Unreadable {
+                    offset: offset as u64,
+                    message: err.to_string(),
+                });
+                break;
+            }
+        };
+
+        let event_type = event.header().event_type_raw();
+        if report.events == 0 && event_type != EventType::FORMAT_DESCRIPTION_EVENT as u8 {
+            report.issues.push(Issue::MissingFde);
+        }
+
+        if let (Some(found), Ok(Some(alg))) = (event.checksum(), event.footer().get_checksum_alg())
+        {
+            if alg == BinlogChecksumAlg::BINLOG_CHECKSUM_ALG_CRC32 {
+                let expected = event.calc_checksum(alg);
+                let found = u32::from_le_bytes(found);
+                if expected != found {
+                    report.issues.push(Issue::ChecksumMismatch {
+                        offset: offset as u64,
+                        expected,
+                        found,
+                    });
+                }
+            }
+        }
+
+        let next_offset = offset + event_size;
+        let log_pos = event.header().log_pos() as u64;
+        if log_pos != 0 {
+            let server_id = event.header().server_id();
+            let is_fde = event_type == EventType::FORMAT_DESCRIPTION_EVENT as u8;
+            if report.events == 0 {
+                local_server_id = Some(server_id);
+            }
+
+            if Some(server_id) == local_server_id || log_pos == next_offset as u64 {
+                if log_pos != next_offset as u64 {
+                    report.issues.push(Issue::LogPosMismatch {
+                        offset: offset as u64,
+                        expected: next_offset as u64,
+                        found: log_pos,
+                    });
+                }
+            } else {
+                // Relay log events carry positions in the source's binlog. Some source events
+                // may be missing from a relay log, so positions are only required to grow.
+                let expected = source_pos + event_size as u64;
+                if log_pos < expected && !is_fde {
+                    report.issues.push(Issue::LogPosMismatch {
+                        offset: offset as u64,
+                        expected,
+                        found: log_pos,
+                    });
+                }
+                source_pos = log_pos;
+            }
+        }
+
+        report.rotate = if event_type == EventType::ROTATE_EVENT as u8 {
+            event
+                .read_event::<RotateEvent>()
+                .and_then(|rotate| rotate.next_file())
+                .ok()
+        } else {
+            None
+        };
+
+        last_event_type = Some(event_type);
+        report.events += 1;
+        offset = next_offset;
+        report.valid_len = offset as u64;
+    }
+
+    if report.events == 0 {
+        report.issues.push(Issue::MissingFde);
+    }
+
+    if last_event_type != Some(EventType::ROTATE_EVENT as u8) {
+        report.issues.push(Issue::MissingRotate);
+    }
+
+    report
+}
+
+/// Returns the name of the file that follows the given one
+/// (`None` if the name doesn't have a numeric extension).
+fn next_file_name(name: &str) -> Option<String> {
+    let (base, index) = name.rsplit_once('.')?;
+    if index.is_empty() || !index.bytes().all(|x| x.is_ascii_digit()) {
+        return None;
+    }
+    let next = index.parse::<u64>().ok()?.checked_add(1)?;
+    Some(format!("{}.{:0width$}", base, next, width = index.len()))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+
+    use super::{next_file_name, verify, verify_bytes, Issue};
+
+    #[test]
+    fn should_compute_next_file_name() {
+        assert_eq!(
+            next_file_name("binlog.000001").as_deref(),
+            Some("binlog.000002")
+        );
+        assert_eq!(next_file_name("binlog.999").as_deref(), Some("binlog.1000"));
+        assert_eq!(next_file_name("binlog"), None);
+        assert_eq!(next_file_name("update-full-row.binlog"), None);
+    }
+
+    #[test]
+    fn should_verify_binlog_file() -> io::Result<()> {
+        let report = verify("./test-data/binlogs/write-full-row.binlog")?;
+        assert!(report.is_ok(), "{:?}", report.issues());
+        assert_eq!(report.events(), 9);
+        assert_eq!(report.rotate(), Some(("mysqld-bin.000002", 4)));
+
+        // relay log
+        let report = verify("./test-data/binlogs/bug33029-slave-relay-bin.000001")?;
+        assert_eq!(report.issues(), &[Issue::MissingRotate]);
+        assert_eq!(report.first_corrupt_offset(), None);
+
+        let report = verify("./test-data/binlogs/bug40482-bin.000001")?;
+        assert_eq!(
+            report.issues(),
+            &[Issue::RotateMismatch {
+                expected: "bug40482-bin.000002".into(),
+                found: "master-bin.000003".into(),
+            }]
+        );
+
+        let report = verify("./test-data/binlogs/binlog_truncated_event.000001")?;
+        assert_eq!(
+            report.issues(),
+            &[
+                Issue::Truncated { offset: 4 },
+                Issue::MissingFde,
+                Issue::MissingRotate
+            ]
+        );
+        assert_eq!(report.first_corrupt_offset(), Some(4));
+
+        let report = verify_bytes(b"\xfebiX");
+        assert_eq!(report.issues(), &[Issue::InvalidMagic]);
+        assert_eq!(report.first_corrupt_offset(), Some(0));
+
+        Ok(())
+    }
+
+    #[test]
+    fn should_detect_corrupt_events() -> io::Result<()> {
+        // CRC32 checksums
+        let mut file_data = std::fs::read("./test-data/binlogs/rpl_unfiltered_hidden_gcol.000001")?;
+        let report = verify_bytes(&file_data);
+        assert_eq!(report.issues(), &[Issue::MissingRotate]);
+        assert_eq!(report.valid_len(), file_data.len() as u64);
+
+        // corrupt the payload of the second event
+        let second =
+            4 + u32::from_le_bytes([file_data[13], file_data[14], file_data[15], file_data[16]]);
+        file_data[second as usize + 20] ^= 0xff;
+        let report = verify_bytes(&file_data);
+        assert!(matches!(
+            report.issues()[0],
+            Issue::ChecksumMismatch { offset, .. } if offset == second as u64
+        ));
+        assert_eq!(report.first_corrupt_offset(), Some(second as u64));
+        assert_eq!(report.valid_len(), file_data.len() as u64);
+
+        // corrupt the log_pos of the second event
+        file_data[second as usize + 20] ^= 0xff;
+        file_data[second as usize + 13] ^= 0x01;
+        let report = verify_bytes(&file_data);
+        assert!(matches!(
+            report.issues()[0],
+            Issue::ChecksumMismatch { offset, .. } if offset == second as u64
+        ));
+        assert!(matches!(
+            report.issues()[1],
+            Issue::LogPosMismatch { offset, .. } if offset == second as u64
+        ));
+
+        // truncate the file in the middle of an event
+        file_data[second as usize + 13] ^= 0x01;
+        let report = verify_bytes(&file_data[..file_data.len() - 1]);
+        assert!(matches!(report.issues()[0], Issue::Truncated { .. }));
+        assert_eq!(report.valid_len(), report.first_corrupt_offset().unwrap());
+
+        Ok(())
+    }
+}
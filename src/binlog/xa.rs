@@ -0,0 +1,304 @@
+// Copyright (c) 2023 Anatoly Ikorsky
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! XA transaction identifiers.
+//!
+//! An XA transaction is written to the binlog in several event groups: `XA START` … `XA END`
+//! followed by an XA prepare event (see [`super::events::XaPrepareEvent`]) and later
+//! by an `XA COMMIT` or `XA ROLLBACK` query event (MariaDB writes `XA PREPARE` as a query event
+//! and stores the XID in its GTID event, see [`super::mariadb::MariadbXid`]).
+//! [`XaTransactionId`] is the common representation of the XID, so that these events
+//! can be correlated.
+
+use std::{borrow::Cow, fmt};
+
+/// XID of an XA transaction.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct XaTransactionId<'a> {
+    format_id: u32,
+    gtrid: Cow<'a, [u8]>,
+    bqual: Cow<'a, [u8]>,
+}
+
+impl<'a> XaTransactionId<'a> {
+    /// Maximum length of the `gtrid` and `bqual` parts.
+    pub const MAX_PART_LEN: usize = 64;
+
+    /// Format id used by the server if it's not given in an XA statement.
+    pub const DEFAULT_FORMAT_ID: u32 = 1;
+
+    /// Creates a new XID.
+    ///
+    /// `gtrid` and `bqual` will be truncated to [`XaTransactionId::MAX_PART_LEN`] bytes.
+    pub fn new(
+        format_id: u32,
+        gtrid: impl Into<Cow<'a, [u8]>>,
+        bqual: impl Into<Cow<'a, [u8]>>,
+    ) -> Self {
+        let truncate = |mut part: Cow<'a, [u8]>| {
+            if part.len() > Self::MAX_PART_LEN {
+                part.to_mut().truncate(Self::MAX_PART_LEN);
+            }
+            part
+        };
+        Self {
+            format_id,
+            gtrid: truncate(gtrid.into()),
+            bqual: truncate(bqual.into()),
+        }
+    }
+
+    /// Parses an XID as written in XA statements (e.g. `X'6162',X'',1` or `'ab'`).
+    ///
+    /// Returns `None` if the XID is malformed.
+    pub fn parse(xid: &str) -> Option<XaTransactionId<'static>> {
+        match parse_xid(xid) {
+            Some((xid, rest)) if rest.trim().is_empty() => Some(xid),
+            _ => None,
+        }
+    }
+
+    /// Returns the format id.
+    pub fn format_id(&self) -> u32 {
+        self.format_id
+    }
+
+    /// Returns the global transaction identifier.
+    pub fn gtrid(&self) -> &[u8] {
+        &self.gtrid
+    }
+
+    /// Returns the branch qualifier.
+    pub fn bqual(&self) -> &[u8] {
+        &self.bqual
+    }
+
+    /// Returns a `'static` version of `self`.
+    pub fn into_owned(self) -> XaTransactionId<'static> {
+        XaTransactionId {
+            format_id: self.format_id,
+            gtrid: Cow::Owned(self.gtrid.into_owned()),
+            bqual: Cow::Owned(self.bqual.into_owned()),
+        }
+    }
+}
+
+/// Formats the XID as the server writes it to the binlog (`X'gtrid',X'bqual',format_id`).
+impl fmt::Display for XaTransactionId<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let hex = |f: &mut fmt::Formatter<'_>, part: &[u8]| {
+            f.write_str("X'")?;
+            for byte in part {
+                write!(f, "{:02x}", byte)?;
+            }
+            f.write_str("'")
+        };
+        hex(f, &self.gtrid)?;
+        f.write_str(",")?;
+        hex(f, &self.bqual)?;
+        write!(f, ",{}", self.format_id)
+    }
+}
+
+/// XA statement of a query event (see [`super::events::QueryEvent::xa_statement`]).
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub enum XaStatement<'a> {
+    /// `XA START` (or `XA BEGIN`).
+    Start(XaTransactionId<'a>),
+    /// `XA END`.
+    End(XaTransactionId<'a>),
+    /// `XA PREPARE`.
+    Prepare(XaTransactionId<'a>),
+    /// `XA COMMIT` (`one_phase` is `true` for `XA COMMIT … ONE PHASE`).
+    Commit {
+        xid: XaTransactionId<'a>,
+        one_phase: bool,
+    },
+    /// `XA ROLLBACK`.
+    Rollback(XaTransactionId<'a>),
+}
+
+impl<'a> XaStatement<'a> {
+    /// Parses an XA statement.
+    ///
+    /// Returns `None` if the query isn't a well-formed XA statement
+    /// (`XA RECOVER` is not recognized since it has no XID).
+    pub fn parse(query: &str) -> Option<XaStatement<'static>> {
+        let (xa, rest) = split_word(query);
+        if !xa.eq_ignore_ascii_case("XA") {
+            return None;
+        }
+        let (keyword, rest) = split_word(rest);
+        let (xid, rest) = parse_xid(rest)?;
+        let suffix = rest
+            .split_whitespace()
+            .map(str::to_ascii_uppercase)
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        match (keyword.to_ascii_uppercase().as_str(), suffix.as_str()) {
+            ("START" | "BEGIN", "" | "JOIN" | "RESUME") => Some(XaStatement::Start(xid)),
+            ("END", "" | "SUSPEND" | "SUSPEND FOR MIGRATE") => Some(XaStatement::End(xid)),
+            ("PREPARE", "") => Some(XaStatement::Prepare(xid)),
+            ("COMMIT", "") => Some(XaStatement::Commit {
+                xid,
+                one_phase: false,
+            }),
+            ("COMMIT", "ONE PHASE") => Some(XaStatement::Commit {
+                xid,
+                one_phase: true,
+            }),
+            ("ROLLBACK", "") => Some(XaStatement::Rollback(xid)),
+            _ => None,
+        }
+    }
+
+    /// Returns the XID of the statement.
+    pub fn xid(&self) -> &XaTransactionId<'a> {
+        match self {
+            XaStatement::Start(xid)
+            | XaStatement::End(xid)
+            | XaStatement::Prepare(xid)
+            | XaStatement::Commit { xid, .. }
+            | XaStatement::Rollback(xid) => xid,
+        }
+    }
+
+    /// Returns a `'static` version of `self`.
+    pub fn into_owned(self) -> XaStatement<'static> {
+        match self {
+            XaStatement::Start(xid) => XaStatement::Start(xid.into_owned()),
+            XaStatement::End(xid) => XaStatement::End(xid.into_owned()),
+            XaStatement::Prepare(xid) => XaStatement::Prepare(xid.into_owned()),
+            XaStatement::Commit { xid, one_phase } => XaStatement::Commit {
+                xid: xid.into_owned(),
+                one_phase,
+            },
+            XaStatement::Rollback(xid) => XaStatement::Rollback(xid.into_owned()),
+        }
+    }
+}
+
+/// Splits the leading alphabetic word of `s` (after whitespace).
+fn split_word(s: &str) -> (&str, &str) {
+    let s = s.trim_start();
+    let end = s
+        .find(|c: char| !c.is_ascii_alphabetic())
+        .unwrap_or(s.len());
+    s.split_at(end)
+}
+
+/// Parses `gtrid [, bqual [, format_id]]` at the beginning of `s`.
+fn parse_xid(s: &str) -> Option<(XaTransactionId<'static>, &str)> {
+    let (gtrid, mut rest) = parse_part(s)?;
+    let mut bqual = Vec::new();
+    let mut format_id = XaTransactionId::DEFAULT_FORMAT_ID;
+
+    if let Some(after_comma) = rest.trim_start().strip_prefix(',') {
+        let (part, after_part) = parse_part(after_comma)?;
+        bqual = part;
+        rest = after_part;
+
+        if let Some(after_comma) = rest.trim_start().strip_prefix(',') {
+            let after_comma = after_comma.trim_start();
+            let end = after_comma
+                .find(|c: char| !c.is_ascii_digit())
+                .unwrap_or(after_comma.len());
+            format_id = after_comma[..end].parse().ok()?;
+            rest = &after_comma[end..];
+        }
+    }
+
+    if gtrid.is_empty()
+        || gtrid.len() > XaTransactionId::MAX_PART_LEN
+        || bqual.len() > XaTransactionId::MAX_PART_LEN
+    {
+        return None;
+    }
+
+    Some((XaTransactionId::new(format_id, gtrid, bqual), rest))
+}
+
+/// Parses a string literal (either `X'hex'` or `'text'`) at the beginning of `s`.
+fn parse_part(s: &str) -> Option<(Vec<u8>, &str)> {
+    let s = s.trim_start();
+    if let Some(hex) = s.strip_prefix("X'").or_else(|| s.strip_prefix("x'")) {
+        let end = hex.find('\'')?;
+        let digits = &hex.as_bytes()[..end];
+        if digits.len() % 2 != 0 {
+            return None;
+        }
+        let part = digits
+            .chunks(2)
+            .map(|pair| u8::from_str_radix(std::str::from_utf8(pair).ok()?, 16).ok())
+            .collect::<Option<Vec<_>>>()?;
+        Some((part, &hex[end + 1..]))
+    } else {
+        let text = s.strip_prefix('\'')?;
+        let end = text.find('\'')?;
+        Some((text.as_bytes()[..end].to_vec(), &text[end + 1..]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{XaStatement, XaTransactionId};
+
+    #[test]
+    fn should_parse_xa_statements() {
+        let xid = XaTransactionId::new(1, &b"ab"[..], &b""[..]);
+        assert_eq!(xid.to_string(), "X'6162',X'',1");
+        assert_eq!(XaTransactionId::parse(&xid.to_string()), Some(xid.clone()));
+        assert_eq!(XaTransactionId::parse("'ab'"), Some(xid.clone()));
+        assert_eq!(
+            XaTransactionId::parse("x'01FF' , 'q', 42"),
+            Some(XaTransactionId::new(42, vec![1, 255], &b"q"[..]))
+        );
+
+        let cases = [
+            (
+                "XA START X'6162',X'',1",
+                Some(XaStatement::Start(xid.clone())),
+            ),
+            ("xa begin 'ab' join", Some(XaStatement::Start(xid.clone()))),
+            ("XA END X'6162',X'',1", Some(XaStatement::End(xid.clone()))),
+            (
+                "XA END 'ab' SUSPEND FOR MIGRATE",
+                Some(XaStatement::End(xid.clone())),
+            ),
+            ("XA PREPARE 'ab'", Some(XaStatement::Prepare(xid.clone()))),
+            (
+                "XA COMMIT X'6162',X'',1",
+                Some(XaStatement::Commit {
+                    xid: xid.clone(),
+                    one_phase: false,
+                }),
+            ),
+            (
+                "XA COMMIT 'ab' ONE PHASE",
+                Some(XaStatement::Commit {
+                    xid: xid.clone(),
+                    one_phase: true,
+                }),
+            ),
+            (
+                "XA ROLLBACK X'6162',X'',1",
+                Some(XaStatement::Rollback(xid)),
+            ),
+            ("XA RECOVER", None),
+            ("XA COMMIT 'ab' TWO PHASE", None),
+            ("XA COMMIT X'616',X'',1", None),
+            ("XA COMMIT '', '', 1", None),
+            ("XAX START 'ab'", None),
+            ("COMMIT", None),
+        ];
+        for (query, expected) in cases {
+            assert_eq!(XaStatement::parse(query), expected, "{}", query);
+        }
+    }
+}
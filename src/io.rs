@@ -36,8 +36,15 @@ pub trait BufMutExt: BufMut {
     }
 
     /// Writes a 3-bytes unsigned integer.
+    #[inline]
     fn put_u24_le(&mut self, x: u32) {
-        self.put_uint_le(x as u64, 3);
+        self.put_slice(&x.to_le_bytes()[..3]);
+    }
+
+    /// Writes a 3-bytes unsigned integer (big-endian).
+    #[inline]
+    fn put_u24_be(&mut self, x: u32) {
+        self.put_slice(&x.to_be_bytes()[1..]);
     }
 
     /// Writes a 3-bytes signed integer.
@@ -45,14 +52,28 @@ pub trait BufMutExt: BufMut {
         self.put_int_le(x as i64, 3);
     }
 
+    /// Writes a 5-bytes unsigned integer (big-endian).
+    #[inline]
+    fn put_u40_be(&mut self, x: u64) {
+        self.put_slice(&x.to_be_bytes()[3..]);
+    }
+
     /// Writes a 6-bytes unsigned integer.
+    #[inline]
     fn put_u48_le(&mut self, x: u64) {
-        self.put_uint_le(x, 6);
+        self.put_slice(&x.to_le_bytes()[..6]);
+    }
+
+    /// Writes a 6-bytes unsigned integer (big-endian).
+    #[inline]
+    fn put_u48_be(&mut self, x: u64) {
+        self.put_slice(&x.to_be_bytes()[2..]);
     }
 
     /// Writes a 7-bytes unsigned integer.
+    #[inline]
     fn put_u56_le(&mut self, x: u64) {
-        self.put_uint_le(x, 7);
+        self.put_slice(&x.to_le_bytes()[..7]);
     }
 
     /// Writes a 7-bytes signed integer.
@@ -92,6 +113,7 @@ impl io::Read for ParseBuf<'_> {
 macro_rules! eat_num {
     ($name:ident, $checked:ident, $t:ident::$fn:ident) => {
         #[doc = "Consumes a number from the head of the buffer."]
+        #[inline]
         pub fn $name(&mut self) -> $t {
             const SIZE: usize = std::mem::size_of::<$t>();
             let bytes = self.eat(SIZE);
@@ -107,19 +129,22 @@ macro_rules! eat_num {
             }
         }
     };
-    ($name:ident, $checked:ident, $size:literal, $offset:literal, $t:ident::$fn:ident) => {
+    ($name:ident, $checked:ident, $size:literal, $t:ident::$fn:ident) => {
         #[doc = "Consumes a number from the head of the buffer."]
+        #[doc = ""]
+        #[doc = "Signed numbers are sign-extended."]
+        #[inline]
         pub fn $name(&mut self) -> $t {
             const SIZE: usize = $size;
-            let mut x: $t = 0;
+            const FULL: usize = std::mem::size_of::<$t>();
             let bytes = self.eat(SIZE);
-            for (i, b) in bytes.iter().enumerate() {
-                x |= (*b as $t) << ((8 * i) + (8 * $offset));
-            }
-            $t::$fn(x)
+            // the number is read into the high bytes and shifted down (which sign-extends it)
+            let x = eat_num!(@high $fn, bytes, SIZE, FULL, $t);
+            x >> (8 * (FULL - SIZE))
         }
 
         #[doc = "Consumes a number from the head of the buffer. Returns `None` if buffer is too small."]
+        #[inline]
         pub fn $checked(&mut self) -> Option<$t> {
             if self.len() >= $size {
                 Some(self.$name())
@@ -128,6 +153,16 @@ macro_rules! eat_num {
             }
         }
     };
+    (@high from_le_bytes, $bytes:ident, $size:ident, $full:ident, $t:ident) => {{
+        let mut buf = [0_u8; $full];
+        buf[$full - $size..].copy_from_slice($bytes);
+        $t::from_le_bytes(buf)
+    }};
+    (@high from_be_bytes, $bytes:ident, $size:ident, $full:ident, $t:ident) => {{
+        let mut buf = [0_u8; $full];
+        buf[..$size].copy_from_slice($bytes);
+        $t::from_be_bytes(buf)
+    }};
 }
 
 impl<'a> ParseBuf<'a> {
@@ -224,26 +259,26 @@ impl<'a> ParseBuf<'a> {
     eat_num!(eat_i16_le, checked_eat_i16_le, i16::from_le_bytes);
     eat_num!(eat_u16_be, checked_eat_u16_be, u16::from_be_bytes);
     eat_num!(eat_i16_be, checked_eat_i16_be, i16::from_be_bytes);
-    eat_num!(eat_u24_le, checked_eat_u24_le, 3, 0, u32::from_le);
-    eat_num!(eat_i24_le, checked_eat_i24_le, 3, 0, i32::from_le);
-    eat_num!(eat_u24_be, checked_eat_u24_be, 3, 1, u32::from_be);
-    eat_num!(eat_i24_be, checked_eat_i24_be, 3, 1, i32::from_be);
+    eat_num!(eat_u24_le, checked_eat_u24_le, 3, u32::from_le_bytes);
+    eat_num!(eat_i24_le, checked_eat_i24_le, 3, i32::from_le_bytes);
+    eat_num!(eat_u24_be, checked_eat_u24_be, 3, u32::from_be_bytes);
+    eat_num!(eat_i24_be, checked_eat_i24_be, 3, i32::from_be_bytes);
     eat_num!(eat_u32_le, checked_eat_u32_le, u32::from_le_bytes);
     eat_num!(eat_i32_le, checked_eat_i32_le, i32::from_le_bytes);
     eat_num!(eat_u32_be, checked_eat_u32_be, u32::from_be_bytes);
     eat_num!(eat_i32_be, checked_eat_i32_be, i32::from_be_bytes);
-    eat_num!(eat_u40_le, checked_eat_u40_le, 5, 0, u64::from_le);
-    eat_num!(eat_i40_le, checked_eat_i40_le, 5, 0, i64::from_le);
-    eat_num!(eat_u40_be, checked_eat_u40_be, 5, 3, u64::from_be);
-    eat_num!(eat_i40_be, checked_eat_i40_be, 5, 3, i64::from_be);
-    eat_num!(eat_u48_le, checked_eat_u48_le, 6, 0, u64::from_le);
-    eat_num!(eat_i48_le, checked_eat_i48_le, 6, 0, i64::from_le);
-    eat_num!(eat_u48_be, checked_eat_u48_be, 6, 2, u64::from_be);
-    eat_num!(eat_i48_be, checked_eat_i48_be, 6, 2, i64::from_be);
-    eat_num!(eat_u56_le, checked_eat_u56_le, 7, 0, u64::from_le);
-    eat_num!(eat_i56_le, checked_eat_i56_le, 7, 0, i64::from_le);
-    eat_num!(eat_u56_be, checked_eat_u56_be, 7, 1, u64::from_be);
-    eat_num!(eat_i56_be, checked_eat_i56_be, 7, 1, i64::from_be);
+    eat_num!(eat_u40_le, checked_eat_u40_le, 5, u64::from_le_bytes);
+    eat_num!(eat_i40_le, checked_eat_i40_le, 5, i64::from_le_bytes);
+    eat_num!(eat_u40_be, checked_eat_u40_be, 5, u64::from_be_bytes);
+    eat_num!(eat_i40_be, checked_eat_i40_be, 5, i64::from_be_bytes);
+    eat_num!(eat_u48_le, checked_eat_u48_le, 6, u64::from_le_bytes);
+    eat_num!(eat_i48_le, checked_eat_i48_le, 6, i64::from_le_bytes);
+    eat_num!(eat_u48_be, checked_eat_u48_be, 6, u64::from_be_bytes);
+    eat_num!(eat_i48_be, checked_eat_i48_be, 6, i64::from_be_bytes);
+    eat_num!(eat_u56_le, checked_eat_u56_le, 7, u64::from_le_bytes);
+    eat_num!(eat_i56_le, checked_eat_i56_le, 7, i64::from_le_bytes);
+    eat_num!(eat_u56_be, checked_eat_u56_be, 7, u64::from_be_bytes);
+    eat_num!(eat_i56_be, checked_eat_i56_be, 7, i64::from_be_bytes);
     eat_num!(eat_u64_le, checked_eat_u64_le, u64::from_le_bytes);
     eat_num!(eat_i64_le, checked_eat_i64_le, i64::from_le_bytes);
     eat_num!(eat_u64_be, checked_eat_u64_be, u64::from_be_bytes);
@@ -418,4 +453,38 @@ mod tests {
         assert_eq!(buf.clone().eat_u56_le(), 0x0006050403020100);
         assert_eq!(buf.clone().eat_u56_be(), 0x0000010203040506);
     }
+
+    #[test]
+    fn odd_width_signed() {
+        let buf = ParseBuf(&[0xff, 0xff, 0xff]);
+        assert_eq!(buf.clone().eat_i24_le(), -1);
+        assert_eq!(buf.clone().eat_i24_be(), -1);
+        assert_eq!(buf.clone().eat_u24_le(), 0x00ff_ffff);
+        let buf = ParseBuf(&[0xfe, 0xff, 0x7f]);
+        assert_eq!(buf.clone().eat_i24_le(), 0x007f_fffe);
+        assert_eq!(buf.clone().eat_i24_be(), -0x0001_0081);
+        let buf = ParseBuf(&[0, 0, 0, 0, 0, 0, 0x80]);
+        assert_eq!(buf.clone().eat_i56_le(), -(1 << 55));
+        assert_eq!(buf.clone().eat_u56_le(), 1 << 55);
+        assert_eq!(ParseBuf(&[1, 2]).checked_eat_u24_le(), None);
+    }
+
+    #[test]
+    fn odd_width_roundtrip() {
+        let mut buf = Vec::new();
+        buf.put_u24_le(0x010203);
+        buf.put_u24_be(0x010203);
+        buf.put_u40_be(0x0102030405);
+        buf.put_u48_le(0x010203040506);
+        buf.put_u48_be(0x010203040506);
+        buf.put_u56_le(0x01020304050607);
+        let mut buf = ParseBuf(&buf);
+        assert_eq!(buf.eat_u24_le(), 0x010203);
+        assert_eq!(buf.eat_u24_be(), 0x010203);
+        assert_eq!(buf.eat_u40_be(), 0x0102030405);
+        assert_eq!(buf.eat_u48_le(), 0x010203040506);
+        assert_eq!(buf.eat_u48_be(), 0x010203040506);
+        assert_eq!(buf.eat_u56_le(), 0x01020304050607);
+        assert!(buf.is_empty());
+    }
 }
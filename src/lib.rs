@@ -29,13 +29,18 @@
 //!
 //! | Type                                 | Notes                                                     |
 //! | ------------------------------------ | -------------------------------------------------------   |
-//! | `{i,u}8..{i,u}128`, `{i,u}size`      | MySql int/uint will be converted, bytes will be parsed.<br>⚠️ Note that range of `{i,u}128` is greater than supported by MySql integer types but it'll be serialized anyway (as decimal bytes string). |
+//! | `{i,u}8..{i,u}128`, `{i,u}size`      | MySql int/uint will be converted, bytes will be parsed.<br>⚠️ Note that range of `{i,u}128` is greater than supported by MySql integer types but it'll be serialized anyway (as decimal bytes string).<br>`{i,u}128` will also parse `DECIMAL` strings with a zero fractional part (e.g. `"42.00"`). |
 //! | `f32`                                | MySql float will be converted to `f32`, bytes will be parsed as `f32`.<br>⚠️ MySql double won't be converted to `f32` to avoid precision loss (see #17) |
 //! | `f64`                                | MySql float and double will be converted to `f64`, bytes will be parsed as `f64`. |
 //! | `bool`                               | MySql int {`0`, `1`} or bytes {`"0x30"`, `"0x31"`}        |
 //! | `Vec<u8>`                            | MySql bytes                                               |
+//! | `[u8; N]`                            | MySql bytes of length `N`                                 |
+//! | [`bytes::Bytes`]                     | MySql bytes (without copying)                             |
 //! | `String`                             | MySql bytes parsed as utf8                                |
 //! | `Duration` (`std` and `time`)        | MySql time or bytes parsed as MySql time string           |
+//! | `mysql_common::value::mysql_time::MysqlTime` | MySql time or bytes parsed as MySql time string.<br>Covers the whole MySql `TIME` range; out-of-range values are errors. |
+//! | `mysql_common::value::point::Point` | MySql bytes parsed as a `POINT` in MySql internal geometry format (SRID + WKB) |
+//! | `mysql_common::value::geometry::Geometry` | MySql bytes parsed as a `GEOMETRY` of any type in MySql internal geometry format (SRID + WKB) |
 //! | [`time::PrimitiveDateTime`] (v0.2.x) | MySql date time or bytes parsed as MySql date time string (⚠️ lossy! microseconds are ignored)           |
 //! | [`time::Date`] (v0.2.x)              | MySql date or bytes parsed as MySql date string (⚠️ lossy! microseconds are ignored)           |
 //! | [`time::Time`] (v0.2.x)              | MySql time or bytes parsed as MySql time string (⚠️ lossy! microseconds are ignored)           |
@@ -47,6 +52,7 @@
 //! | [`chrono::NaiveTime`]                | MySql date or bytes parsed as MySql date string           |
 //! | [`chrono::NaiveDate`]                | MySql date or bytes parsed as MySql date string           |
 //! | [`chrono::NaiveDateTime`]            | MySql date or bytes parsed as MySql date string           |
+//! | [`geo_types::Geometry<f64>`]          | MySql bytes parsed as a `GEOMETRY` in MySql internal geometry format (SRID is ignored) |
 //! | [`uuid::Uuid`]                       | MySql bytes parsed using `Uuid::from_slice`               |
 //! | [`serde_json::Value`]                | MySql bytes parsed using `serde_json::from_str`           |
 //! | `mysql_common::Deserialized<T : DeserializeOwned>` | MySql bytes parsed using `serde_json::from_str` |
@@ -62,21 +68,31 @@
 //! | ----------------------------------------------- | ------------------------------------------------- |
 //! | `Row`                                           | Trivial conversion for `Row` itself.              |
 //! | `T: FromValue`                                  | For rows with a single column.                    |
-//! | `(T1: FromValue [, ..., T12: FromValue])`       | Row to a tuple of arity 1-12.                     |
+//! | `(T1: FromValue [, ..., T24: FromValue])`       | Row to a tuple of arity 1-24.                     |
 //! | [`frunk::Hlist!`] types                         | Usefull to overcome tuple arity limitation        |
 //!
 //! ## Crate features
 //!
-//! | Feature        | Description                                          | Default |
-//! | -------------- | ---------------------------------------------------- | ------- |
-//! | `bigdecimal02` | Enables `bigdecimal` v0.2.x types support            | 🔴      |
-//! | `bigdecimal`   | Enables `bigdecimal` v0.3.x types support            | 🟢      |
-//! | `chrono`       | Enables `chrono` types support                       | 🔴      |
-//! | `rust_decimal` | Enables `rust_decimal` types support                 | 🟢      |
-//! | `time02`       | Enables `time` v0.2.x types support                  | 🔴      |
-//! | `time`         | Enables `time` v0.3.x types support                  | 🟢      |
-//! | `frunk`        | Enables `FromRow` for `frunk::Hlist!` types          | 🟢      |
-//! | `derive`       | Enables [`FromValue` and `FromRow` derive macros][2] | 🟢      |
+//! | Feature           | Description                                             | Default |
+//! | ----------------- | ------------------------------------------------------- | ------- |
+//! | `bigdecimal02`    | Enables `bigdecimal` v0.2.x types support               | 🔴      |
+//! | `bigdecimal`      | Enables `bigdecimal` v0.3.x types support               | 🔴      |
+//! | `chrono`          | Enables `chrono` types support                          | 🔴      |
+//! | `geo-types`       | Enables `geo_types` geometries support                  | 🔴      |
+//! | `rust_decimal`    | Enables `rust_decimal` types support                    | 🔴      |
+//! | `serde_json`      | Enables `serde_json` types support and JSON conversions | 🔴      |
+//! | `time02`          | Enables `time` v0.2.x types support                     | 🔴      |
+//! | `time`            | Enables `time` v0.3.x types support                     | 🔴      |
+//! | `frunk`           | Enables `FromRow` for `frunk::Hlist!` types             | 🔴      |
+//! | `derive`          | Enables [`FromValue` and `FromRow` derive macros][2]    | 🟢      |
+//! | `binlog`          | Enables the `binlog` module                             | 🔴      |
+//! | `client-protocol` | Enables authentication, `crypto` and the packet codec   | 🔴      |
+//! | `zlib`            | Uses `flate2` zlib backend instead of `miniz_oxide`     | 🔴      |
+//! | `futures`         | Enables async binlog reading (`binlog::async_reader`)   | 🔴      |
+//! | `test-support`    | Enables the binlog fixture corpus (`test_support`)      | 🔴      |
+//! | `zstd`            | Enables zstd codec of binlog transaction payloads       | 🔴      |
+//!
+//! Only value and row conversions, protocol packets and derive macros are compiled by default.
 //!
 //! # Derive Macros
 //!
@@ -349,6 +365,9 @@ pub use chrono;
 #[cfg(feature = "frunk")]
 pub use frunk;
 
+#[cfg(feature = "geo-types")]
+pub use geo_types;
+
 #[cfg(feature = "rust_decimal")]
 pub use rust_decimal;
 
@@ -367,9 +386,10 @@ extern crate mysql_common_derive;
 
 pub use num_bigint;
 pub use serde;
+#[cfg(feature = "serde_json")]
 pub use serde_json;
 
-pub use value::convert::FromValueError;
+pub use value::convert::{FromValueError, Nullable};
 pub use value::Value;
 
 pub use row::convert::FromRowError;
@@ -377,6 +397,7 @@ pub use row::Row;
 
 pub use value::json::{Deserialized, Serialized};
 
+/// Most used traits and types (use `mysql_common::prelude::*` to import them all).
 pub mod prelude {
     #[cfg(feature = "derive")]
     #[cfg_attr(docsrs, doc(cfg(feature = "derive")))]
@@ -388,8 +409,16 @@ pub mod prelude {
     #[doc(inline)]
     pub use mysql_common_derive::FromRow;
 
-    pub use crate::row::{convert::FromRow, ColumnIndex};
+    pub use crate::io::{ParseBuf, ReadMysqlExt, WriteMysqlExt};
+    pub use crate::params::Params;
+    pub use crate::proto::{MyDeserialize, MySerialize};
+    pub use crate::row::{convert::FromRow, ColumnIndex, Row};
     pub use crate::value::convert::{FromValue, ToValue};
+    pub use crate::value::Value;
+
+    #[cfg(feature = "binlog")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "binlog")))]
+    pub use crate::binlog::{BinlogEvent, BinlogStruct};
 }
 
 /// This macro is a convenient way to pass named parameters to a statement.
@@ -461,6 +490,8 @@ macro_rules! params {
 }
 
 pub mod constants;
+#[cfg(feature = "client-protocol")]
+#[cfg_attr(docsrs, doc(cfg(feature = "client-protocol")))]
 pub mod crypto;
 pub mod io;
 pub mod misc;
@@ -470,11 +501,19 @@ pub mod packets;
 pub mod params;
 pub mod proto;
 pub mod row;
+#[cfg(feature = "client-protocol")]
+#[cfg_attr(docsrs, doc(cfg(feature = "client-protocol")))]
 pub mod scramble;
 pub mod value;
 
+#[cfg(feature = "binlog")]
+#[cfg_attr(docsrs, doc(cfg(feature = "binlog")))]
 pub mod binlog;
 
+#[cfg(feature = "test-support")]
+#[cfg_attr(docsrs, doc(cfg(feature = "test-support")))]
+pub mod test_support;
+
 #[cfg(test)]
 #[test]
 fn params_macro_test() {
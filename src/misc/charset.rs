@@ -0,0 +1,90 @@
+// Copyright (c) 2023 Anatoly Ikorsky
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Decoding of text stored using MySql character sets.
+
+use std::{borrow::Cow, io};
+
+/// Code points of MySql `latin1` (i.e. cp1252) for bytes in range `0x80..0xA0`.
+const LATIN1_HIGH: [char; 32] = [
+    '\u{20AC}', '\u{0081}', '\u{201A}', '\u{0192}', '\u{201E}', '\u{2026}', '\u{2020}', '\u{2021}',
+    '\u{02C6}', '\u{2030}', '\u{0160}', '\u{2039}', '\u{0152}', '\u{008D}', '\u{017D}', '\u{008F}',
+    '\u{0090}', '\u{2018}', '\u{2019}', '\u{201C}', '\u{201D}', '\u{2022}', '\u{2013}', '\u{2014}',
+    '\u{02DC}', '\u{2122}', '\u{0161}', '\u{203A}', '\u{0153}', '\u{009D}', '\u{017E}', '\u{0178}',
+];
+
+/// Decodes a string stored using the given collation.
+///
+/// Supported character sets are `utf8mb3`, `utf8mb4`, `ascii`, `latin1` and `binary`
+/// (the latter is expected to hold utf8). Returns an `Unsupported` error for other
+/// character sets and an `InvalidData` error if `bytes` isn't valid for the character set.
+pub fn decode_text(collation_id: u32, bytes: &[u8]) -> io::Result<Cow<'_, str>> {
+    match collation_id {
+        // utf8mb3, utf8mb4 and binary
+        33 | 45 | 46 | 63 | 76 | 83 | 192..=215 | 223..=247 | 255..=323 => {
+            match std::str::from_utf8(bytes) {
+                Ok(text) => Ok(Cow::Borrowed(text)),
+                Err(e) => Err(io::Error::new(io::ErrorKind::InvalidData, e)),
+            }
+        }
+        // ascii
+        11 | 65 => {
+            if bytes.is_ascii() {
+                // ascii is valid utf8
+                Ok(Cow::Borrowed(std::str::from_utf8(bytes).expect("ascii")))
+            } else {
+                Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "invalid ascii string",
+                ))
+            }
+        }
+        // latin1
+        5 | 8 | 15 | 31 | 47 | 48 | 49 | 94 => {
+            if bytes.is_ascii() {
+                return Ok(Cow::Borrowed(std::str::from_utf8(bytes).expect("ascii")));
+            }
+            Ok(Cow::Owned(
+                bytes
+                    .iter()
+                    .map(|&x| match x {
+                        0x80..=0x9f => LATIN1_HIGH[(x - 0x80) as usize],
+                        _ => x as char,
+                    })
+                    .collect(),
+            ))
+        }
+        _ => Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            format!("unsupported collation {}", collation_id),
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::ErrorKind;
+
+    use super::decode_text;
+
+    #[test]
+    fn decode_text_charsets() {
+        assert_eq!(decode_text(33, "ü".as_bytes()).unwrap(), "ü");
+        assert_eq!(decode_text(63, b"abc").unwrap(), "abc");
+        assert_eq!(decode_text(11, b"abc").unwrap(), "abc");
+        assert_eq!(
+            decode_text(11, b"\xfc").unwrap_err().kind(),
+            ErrorKind::InvalidData
+        );
+        assert_eq!(decode_text(8, b"\xfc\x9f").unwrap(), "\u{fc}\u{178}");
+        assert_eq!(
+            decode_text(28, b"abc").unwrap_err().kind(),
+            ErrorKind::Unsupported
+        );
+    }
+}
@@ -8,6 +8,7 @@
 
 use std::io::{self};
 
+pub mod charset;
 pub mod raw;
 
 /// Returns length of length-encoded-integer representation of `x`.
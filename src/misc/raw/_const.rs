@@ -26,13 +26,18 @@ use super::{int::IntRepr, RawInt};
 /// if `T::try_from(_: U::Primitive)` fails.
 #[derive(Debug, Default, Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash)]
 #[repr(transparent)]
-pub struct Const<T, U>(pub T, PhantomData<U>);
+pub struct Const<T, U>(#[doc(hidden)] pub T, PhantomData<U>);
 
 impl<T, U> Const<T, U> {
     /// Creates a new `Const`.
     pub fn new(t: T) -> Self {
         Self(t, PhantomData)
     }
+
+    /// Unwraps the value.
+    pub fn into_inner(self) -> T {
+        self.0
+    }
 }
 
 impl<T, U> Deref for Const<T, U> {
@@ -83,13 +88,18 @@ where
 /// * `U` – specifies the parsed value.
 #[derive(Clone, Default, Copy, Eq, PartialEq, Ord, PartialOrd, Hash)]
 #[repr(transparent)]
-pub struct RawConst<T: IntRepr, U>(pub T::Primitive, PhantomData<U>);
+pub struct RawConst<T: IntRepr, U>(#[doc(hidden)] pub T::Primitive, PhantomData<U>);
 
 impl<T: IntRepr, U> RawConst<T, U> {
     /// Creates a new wrapper.
     pub fn new(t: T::Primitive) -> Self {
         Self(t, PhantomData)
     }
+
+    /// Returns the raw value.
+    pub fn raw(&self) -> T::Primitive {
+        self.0
+    }
 }
 
 impl<T: IntRepr, U> Deref for RawConst<T, U> {
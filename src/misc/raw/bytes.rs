@@ -14,7 +14,7 @@ use bytes::BufMut;
 
 use crate::{
     io::{BufMutExt, ParseBuf},
-    misc::unexpected_buf_eof,
+    misc::{charset::decode_text, unexpected_buf_eof},
     proto::{MyDeserialize, MySerialize},
 };
 
@@ -25,7 +25,7 @@ use super::{int::VarLen, RawInt};
 /// `T` encodes the serialized representation.
 #[derive(Clone, Default, Eq, PartialEq, Ord, PartialOrd, Hash)]
 #[repr(transparent)]
-pub struct RawBytes<'a, T: BytesRepr>(pub Cow<'a, [u8]>, PhantomData<T>);
+pub struct RawBytes<'a, T: BytesRepr>(#[doc(hidden)] pub Cow<'a, [u8]>, PhantomData<T>);
 
 impl<'a, T: BytesRepr> RawBytes<'a, T> {
     /// Wraps the given value.
@@ -57,6 +57,18 @@ impl<'a, T: BytesRepr> RawBytes<'a, T> {
     pub fn as_str(&'a self) -> Cow<'a, str> {
         String::from_utf8_lossy(self.as_bytes())
     }
+
+    /// Decodes the value stored using the given collation (see [`decode_text`]).
+    ///
+    /// Unlike [`RawBytes::as_str`] it won't silently replace bytes of a non-utf8 string.
+    pub fn decode(&self, collation_id: u32) -> io::Result<Cow<'_, str>> {
+        decode_text(collation_id, self.as_bytes())
+    }
+
+    /// Unwraps the value (it isn't truncated to `T::MAX_LEN`).
+    pub fn into_inner(self) -> Cow<'a, [u8]> {
+        self.0
+    }
 }
 
 impl<'a, T: Into<Cow<'a, [u8]>>, U: BytesRepr> From<T> for RawBytes<'a, U> {
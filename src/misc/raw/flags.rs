@@ -23,7 +23,7 @@ use super::{int::IntRepr, RawInt};
 /// Deserialization of this type won't lead to an error if value contains unknown flags.
 #[derive(Clone, Default, Copy, Eq, PartialEq, Ord, PartialOrd, Hash)]
 #[repr(transparent)]
-pub struct RawFlags<T: Flags, U>(pub T::Bits, PhantomData<U>);
+pub struct RawFlags<T: Flags, U>(#[doc(hidden)] pub T::Bits, PhantomData<U>);
 
 impl<T: Flags, U> RawFlags<T, U> {
     /// Create new flags.
@@ -35,6 +35,11 @@ impl<T: Flags, U> RawFlags<T, U> {
     pub fn get(&self) -> T {
         T::from_bits_truncate(self.0)
     }
+
+    /// Returns the raw value (including unknown bits).
+    pub fn bits(&self) -> T::Bits {
+        self.0
+    }
 }
 
 impl<T: fmt::Debug, U> fmt::Debug for RawFlags<T, U>
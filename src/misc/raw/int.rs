@@ -24,7 +24,7 @@ use crate::{
 /// Wrapper for an integer, that defines serialization and deserialization.
 #[derive(Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 #[repr(transparent)]
-pub struct RawInt<T: IntRepr>(pub T::Primitive, PhantomData<T>);
+pub struct RawInt<T: IntRepr>(#[doc(hidden)] pub T::Primitive, PhantomData<T>);
 
 impl<T: IntRepr> fmt::Debug for RawInt<T>
 where
@@ -39,6 +39,11 @@ impl<T: IntRepr> RawInt<T> {
     pub fn new(x: T::Primitive) -> Self {
         Self(x, PhantomData)
     }
+
+    /// Returns the wrapped value.
+    pub fn get(&self) -> T::Primitive {
+        self.0
+    }
 }
 
 impl<T: IntRepr> Deref for RawInt<T> {
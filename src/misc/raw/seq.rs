@@ -23,7 +23,7 @@ use super::{
 /// Sequence of serialized values (length serialized as `U`).
 #[derive(Debug, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
 #[repr(transparent)]
-pub struct Seq<'a, T: Clone, U>(pub Cow<'a, [T]>, PhantomData<U>);
+pub struct Seq<'a, T: Clone, U>(#[doc(hidden)] pub Cow<'a, [T]>, PhantomData<U>);
 
 impl<'a, T: Clone, U> Deref for Seq<'a, T, U> {
     type Target = [T];
@@ -38,6 +38,11 @@ impl<'a, T: Clone, U> Seq<'a, T, U> {
         Self(s.into(), PhantomData)
     }
 
+    /// Unwraps the values.
+    pub fn into_inner(self) -> Cow<'a, [T]> {
+        self.0
+    }
+
     /// Returns true if this sequence is empty.
     pub fn is_empty(&self) -> bool {
         self.0.is_empty()
@@ -135,7 +140,8 @@ macro_rules! impl_seq_repr {
                 T: MyDeserialize<'de, Ctx = ()>,
             {
                 let len = *buf.parse::<RawInt<$name>>(())? as usize;
-                let mut seq = Vec::with_capacity(len);
+                // `len` is untrusted, so preallocation is limited by the buffer size
+                let mut seq = Vec::with_capacity(std::cmp::min(len, buf.len()));
                 match T::SIZE {
                     Some(count) => {
                         let mut buf: ParseBuf = buf.parse(count * len)?;
@@ -161,7 +167,7 @@ impl_seq_repr!(u32, LeU32);
 /// Same as `RawCons` but for a sequence of values.
 #[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
 #[repr(transparent)]
-pub struct RawSeq<'a, T: IntRepr, U>(pub Cow<'a, [T::Primitive]>, PhantomData<U>);
+pub struct RawSeq<'a, T: IntRepr, U>(#[doc(hidden)] pub Cow<'a, [T::Primitive]>, PhantomData<U>);
 
 impl<'a, T: IntRepr, U> RawSeq<'a, T, U> {
     /// Creates a new wrapper.
@@ -174,6 +180,16 @@ impl<'a, T: IntRepr, U> RawSeq<'a, T, U> {
         self.0.len()
     }
 
+    /// Returns raw values of this sequence.
+    pub fn as_slice(&self) -> &[T::Primitive] {
+        &self.0
+    }
+
+    /// Unwraps raw values of this sequence.
+    pub fn into_inner(self) -> Cow<'a, [T::Primitive]> {
+        self.0
+    }
+
     /// Returns a `'static` version of `self`.
     pub fn into_owned(self) -> RawSeq<'static, T, U> {
         RawSeq(Cow::Owned(self.0.into_owned()), self.1)
@@ -30,6 +30,13 @@ enum ParserState {
 
 use self::ParserState::*;
 
+/// Returns offsets of positional parameters (`?`) in `query`.
+///
+/// Question marks within string literals and comments are ignored.
+pub fn positional_params(query: &[u8]) -> Vec<usize> {
+    scan_params(query).1
+}
+
 /// Returns pair of:
 ///
 /// * names of named parameters (if any) in order of appearance in `query`. Same name may
@@ -38,8 +45,33 @@ use self::ParserState::*;
 pub fn parse_named_params(
     query: &[u8],
 ) -> Result<(Option<Vec<Vec<u8>>>, Cow<'_, [u8]>), MixedParamsError> {
+    let (params, positional) = scan_params(query);
+    if !params.is_empty() {
+        if !positional.is_empty() {
+            return Err(MixedParamsError);
+        }
+        let mut real_query = Vec::with_capacity(query.len());
+        let mut last = 0;
+        let mut out_params = Vec::with_capacity(params.len());
+        for (start, end, name) in params.into_iter() {
+            real_query.extend(&query[last..start]);
+            real_query.push(b'?');
+            last = end;
+            out_params.push(name);
+        }
+        real_query.extend(&query[last..]);
+        Ok((Some(out_params), real_query.into()))
+    } else {
+        Ok((None, query.into()))
+    }
+}
+
+/// Returns named parameters as `(start_offset, end_offset, name)`
+/// and offsets of positional parameters.
+#[allow(clippy::type_complexity)]
+fn scan_params(query: &[u8]) -> (Vec<(usize, usize, Vec<u8>)>, Vec<usize>) {
     let mut state = TopLevel;
-    let mut have_positional = false;
+    let mut positional = Vec::new();
     let mut cur_param = 0;
     // Vec<(start_offset, end_offset, name)>
     let mut params = Vec::new();
@@ -53,7 +85,7 @@ pub fn parse_named_params(
                 b'#' => state = InSharpComment,
                 b'\'' => state = InStringLiteral(b'\'', b'\''),
                 b'"' => state = InStringLiteral(b'"', b'"'),
-                b'?' => have_positional = true,
+                b'?' => positional.push(i),
                 _ => (),
             },
             InStringLiteral(separator, prev_char) => match c {
@@ -117,6 +149,10 @@ pub fn parse_named_params(
                 b':' => state = MaybeInNamedParam,
                 b'\'' => state = InStringLiteral(b'\'', b'\''),
                 b'"' => state = InStringLiteral(b'"', b'"'),
+                b'?' => {
+                    positional.push(i);
+                    state = TopLevel;
+                }
                 _ => state = TopLevel,
             }
         }
@@ -124,24 +160,7 @@ pub fn parse_named_params(
     if let InNamedParam = state {
         params[cur_param].1 = query.len();
     }
-    if !params.is_empty() {
-        if have_positional {
-            return Err(MixedParamsError);
-        }
-        let mut real_query = Vec::with_capacity(query.len());
-        let mut last = 0;
-        let mut out_params = Vec::with_capacity(params.len());
-        for (start, end, name) in params.into_iter() {
-            real_query.extend(&query[last..start]);
-            real_query.push(b'?');
-            last = end;
-            out_params.push(name);
-        }
-        real_query.extend(&query[last..]);
-        Ok((Some(out_params), real_query.into()))
-    } else {
-        Ok((None, query.into()))
-    }
+    (params, positional)
 }
 
 #[cfg(test)]
@@ -6,12 +6,24 @@
 // option. All files in the project carrying such notice may not be copied,
 // modified, or distributed except according to those terms.
 
-use std::borrow::Cow;
+use std::{borrow::Cow, convert::TryFrom};
 
 use crate::misc::raw::Either;
 
 use super::{BinlogDumpFlags, ComBinlogDump, ComBinlogDumpGtid, Sid};
 
+/// Lossy conversion of a [`BinlogRequest`] into `COM_BINLOG_DUMP`
+/// (see [`BinlogRequest::try_as_cmd`]).
+#[derive(Debug, Clone, PartialEq, Eq, Hash, thiserror::Error)]
+pub enum BinlogRequestError {
+    #[error("Binlog position {} doesn't fit COM_BINLOG_DUMP (use GTID mode)", _0)]
+    PosOverflow(u64),
+    #[error("Flags {:?} are not supported by COM_BINLOG_DUMP (use GTID mode)", _0)]
+    UnsupportedFlags(BinlogDumpFlags),
+    #[error("SID blocks are ignored by COM_BINLOG_DUMP (use GTID mode)")]
+    SidsWithoutGtid,
+}
+
 /// Binlog request representation. Please consult MySql documentation.
 ///
 /// This struct is a helper builder for [`ComBinlogDump`] and [`ComBinlogDumpGtid`].
@@ -46,6 +58,53 @@ impl<'a> BinlogRequest<'a> {
         }
     }
 
+    /// Creates a `COM_BINLOG_DUMP` request that starts the stream at the given position
+    /// of the given binlog file.
+    pub fn from_position(
+        server_id: u32,
+        filename: impl Into<Cow<'a, [u8]>>,
+        pos: impl Into<u64>,
+    ) -> Self {
+        Self::new(server_id).with_filename(filename).with_pos(pos)
+    }
+
+    /// Creates a `COM_BINLOG_DUMP_GTID` request that starts the stream with the first
+    /// transaction that is not in the given GTID set, regardless of binlog files
+    /// (i.e. `SOURCE_AUTO_POSITION=1` mode).
+    ///
+    /// The filename is empty and the position is `4`, so the server looks up the file
+    /// by itself (see [`BinlogRequest::is_auto_position`]).
+    pub fn from_gtid_set<T>(server_id: u32, sids: T) -> Self
+    where
+        T: IntoIterator<Item = Sid<'a>>,
+    {
+        // the filename is empty and the position is 4 by default
+        Self::new(server_id).with_use_gtid(true).with_sids(sids)
+    }
+
+    /// Creates a `COM_BINLOG_DUMP_GTID` request that starts the stream at the given position
+    /// of the given binlog file and skips transactions that are in the given GTID set.
+    pub fn from_position_and_gtid_set<T>(
+        server_id: u32,
+        filename: impl Into<Cow<'a, [u8]>>,
+        pos: impl Into<u64>,
+        sids: T,
+    ) -> Self
+    where
+        T: IntoIterator<Item = Sid<'a>>,
+    {
+        Self::from_position(server_id, filename, pos)
+            .with_use_gtid(true)
+            .with_sids(sids)
+    }
+
+    /// Returns `true` if the server will look up the binlog file by the GTID set,
+    /// i.e. if `use_gtid` is `true` and the filename is empty
+    /// (see [`BinlogRequest::from_gtid_set`]).
+    pub fn is_auto_position(&self) -> bool {
+        self.use_gtid && self.filename.is_empty()
+    }
+
     /// Server id of a slave.
     pub fn server_id(&self) -> u32 {
         self.server_id
@@ -124,6 +183,39 @@ impl<'a> BinlogRequest<'a> {
         self
     }
 
+    /// Returns an error if the request can't be represented by `COM_BINLOG_DUMP` without loss,
+    /// i.e. if `use_gtid` is `false` and `pos` exceeds `u32::MAX`, flags other than
+    /// `BINLOG_DUMP_NON_BLOCK` are given or `sids` are not empty.
+    pub fn validate(&self) -> Result<(), BinlogRequestError> {
+        if self.use_gtid {
+            return Ok(());
+        }
+        if u32::try_from(self.pos).is_err() {
+            return Err(BinlogRequestError::PosOverflow(self.pos));
+        }
+        let unsupported = self.flags - BinlogDumpFlags::BINLOG_DUMP_NON_BLOCK;
+        if !unsupported.is_empty() {
+            return Err(BinlogRequestError::UnsupportedFlags(unsupported));
+        }
+        if !self.sids.is_empty() {
+            return Err(BinlogRequestError::SidsWithoutGtid);
+        }
+        Ok(())
+    }
+
+    /// Same as [`BinlogRequest::as_cmd`] but returns an error instead of a lossy conversion
+    /// (see [`BinlogRequest::validate`]).
+    pub fn try_as_cmd(
+        &self,
+    ) -> Result<Either<ComBinlogDump<'_>, ComBinlogDumpGtid<'_>>, BinlogRequestError> {
+        self.validate()?;
+        Ok(self.as_cmd())
+    }
+
+    /// Returns the corresponding command.
+    ///
+    /// If `use_gtid` is `false`, then `pos` is truncated to `u32`, while `sids` and all flags
+    /// except `BINLOG_DUMP_NON_BLOCK` are ignored (see [`BinlogRequest::try_as_cmd`]).
     pub fn as_cmd(&self) -> Either<ComBinlogDump<'_>, ComBinlogDumpGtid<'_>> {
         if self.use_gtid() {
             let cmd = ComBinlogDumpGtid::new(self.server_id)
@@ -141,3 +233,81 @@ impl<'a> BinlogRequest<'a> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{BinlogRequest, BinlogRequestError};
+    use crate::{
+        misc::raw::Either,
+        packets::{BinlogDumpFlags, GnoInterval, Sid},
+    };
+
+    #[test]
+    fn should_create_binlog_request() {
+        let sid = Sid::new([1; 16]).with_interval(GnoInterval::new(1, 6));
+
+        let request = BinlogRequest::from_gtid_set(42, vec![sid.clone()]);
+        assert!(request.use_gtid());
+        assert!(request.is_auto_position());
+        assert_eq!(request.filename_raw(), b"");
+        assert_eq!(request.pos(), 4);
+        match request.as_cmd() {
+            Either::Right(cmd) => {
+                assert_eq!(cmd.filename_raw(), b"");
+                assert_eq!(cmd.pos(), 4);
+                assert_eq!(cmd.sids(), std::slice::from_ref(&sid));
+                assert!(cmd.flags().contains(BinlogDumpFlags::BINLOG_THROUGH_GTID));
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+
+        let request =
+            BinlogRequest::from_position_and_gtid_set(42, &b"bin.000002"[..], 1234_u32, vec![sid]);
+        assert!(request.use_gtid());
+        assert!(!request.is_auto_position());
+        match request.as_cmd() {
+            Either::Right(cmd) => {
+                assert_eq!(cmd.filename_raw(), b"bin.000002");
+                assert_eq!(cmd.pos(), 1234);
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+
+        let request = BinlogRequest::from_position(42, &b"bin.000002"[..], 1234_u32);
+        assert!(!request.use_gtid());
+        assert!(!request.is_auto_position());
+        assert!(matches!(request.try_as_cmd(), Ok(Either::Left(_))));
+    }
+
+    #[test]
+    fn should_validate_binlog_request() {
+        let request = BinlogRequest::new(42)
+            .with_pos(u32::MAX)
+            .with_flags(BinlogDumpFlags::BINLOG_DUMP_NON_BLOCK);
+        match request.try_as_cmd() {
+            Ok(Either::Left(cmd)) => assert_eq!(cmd.pos(), u32::MAX),
+            other => panic!("unexpected {:?}", other),
+        }
+
+        let request = request.with_pos(u32::MAX as u64 + 1);
+        assert_eq!(
+            request.try_as_cmd().unwrap_err(),
+            BinlogRequestError::PosOverflow(u32::MAX as u64 + 1)
+        );
+        let request = request.with_use_gtid(true);
+        assert!(matches!(request.try_as_cmd(), Ok(Either::Right(_))));
+
+        let request = BinlogRequest::new(42).with_flags(BinlogDumpFlags::all());
+        assert_eq!(
+            request.validate(),
+            Err(BinlogRequestError::UnsupportedFlags(
+                BinlogDumpFlags::BINLOG_THROUGH_POSITION | BinlogDumpFlags::BINLOG_THROUGH_GTID
+            ))
+        );
+        assert!(request.with_use_gtid(true).validate().is_ok());
+
+        let request = BinlogRequest::new(42).with_sids(vec![Sid::new([1; 16])]);
+        assert_eq!(request.validate(), Err(BinlogRequestError::SidsWithoutGtid));
+        assert!(request.with_use_gtid(true).validate().is_ok());
+    }
+}
@@ -0,0 +1,233 @@
+// Copyright (c) 2023 Anatoly Ikorsky
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Connection character set negotiation (see [`CharsetNegotiation`]).
+
+use std::fmt;
+
+use crate::constants::CapabilityFlags;
+
+/// MySql collation (its id, character set name and collation name).
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub struct Collation {
+    id: u16,
+    charset: &'static str,
+    name: &'static str,
+}
+
+impl Collation {
+    pub const LATIN1_SWEDISH_CI: Collation = Collation::new(8, "latin1", "latin1_swedish_ci");
+    pub const UTF8_GENERAL_CI: Collation = Collation::new(33, "utf8", "utf8_general_ci");
+    pub const UTF8MB4_GENERAL_CI: Collation = Collation::new(45, "utf8mb4", "utf8mb4_general_ci");
+    pub const UTF8MB4_BIN: Collation = Collation::new(46, "utf8mb4", "utf8mb4_bin");
+    pub const BINARY: Collation = Collation::new(63, "binary", "binary");
+    pub const UTF8_BIN: Collation = Collation::new(83, "utf8", "utf8_bin");
+    pub const UTF8_UNICODE_CI: Collation = Collation::new(192, "utf8", "utf8_unicode_ci");
+    pub const UTF8MB4_UNICODE_CI: Collation = Collation::new(224, "utf8mb4", "utf8mb4_unicode_ci");
+    pub const UTF8MB4_UNICODE_520_CI: Collation =
+        Collation::new(246, "utf8mb4", "utf8mb4_unicode_520_ci");
+    pub const UTF8MB4_0900_AI_CI: Collation = Collation::new(255, "utf8mb4", "utf8mb4_0900_ai_ci");
+
+    const KNOWN: &'static [Collation] = &[
+        Self::LATIN1_SWEDISH_CI,
+        Self::UTF8_GENERAL_CI,
+        Self::UTF8MB4_GENERAL_CI,
+        Self::UTF8MB4_BIN,
+        Self::BINARY,
+        Self::UTF8_BIN,
+        Self::UTF8_UNICODE_CI,
+        Self::UTF8MB4_UNICODE_CI,
+        Self::UTF8MB4_UNICODE_520_CI,
+        Self::UTF8MB4_0900_AI_CI,
+    ];
+
+    /// Creates a collation (use it for collations not defined as constants).
+    pub const fn new(id: u16, charset: &'static str, name: &'static str) -> Self {
+        Self { id, charset, name }
+    }
+
+    /// Returns one of the collations defined as constants.
+    pub fn from_id(id: u16) -> Option<Self> {
+        Self::KNOWN.iter().find(|x| x.id == id).copied()
+    }
+
+    /// Returns the collation id.
+    pub fn id(&self) -> u16 {
+        self.id
+    }
+
+    /// Returns the character set name.
+    pub fn charset(&self) -> &'static str {
+        self.charset
+    }
+
+    /// Returns the collation name.
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// Returns `true` if this collation belongs to `utf8mb4` character set.
+    pub fn is_utf8mb4(&self) -> bool {
+        self.charset == "utf8mb4"
+    }
+}
+
+impl fmt::Display for Collation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name)
+    }
+}
+
+/// Computes connection collation given the server and the client preferences.
+///
+/// The result is a collation id to put into the `HandshakeResponse` and, if the desired
+/// collation couldn't be set this way, a `SET NAMES` statement to issue after connecting.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub struct CharsetNegotiation {
+    server_version: (u16, u16, u16),
+    server_collation: u16,
+    capabilities: CapabilityFlags,
+    preferred: Option<Collation>,
+}
+
+impl CharsetNegotiation {
+    /// Creates a new negotiation.
+    ///
+    /// * `server_version` and `server_collation` are taken from the initial handshake packet,
+    /// * `capabilities` are the negotiated capability flags.
+    pub fn new(
+        server_version: (u16, u16, u16),
+        server_collation: u16,
+        capabilities: CapabilityFlags,
+    ) -> Self {
+        Self {
+            server_version,
+            server_collation,
+            capabilities,
+            preferred: None,
+        }
+    }
+
+    /// Defines a collation preferred by the client (defaults to `None`).
+    ///
+    /// `None` means that the server default collation will be used if it belongs to `utf8mb4`
+    /// (or `utf8` for servers without `utf8mb4` support), otherwise the general collation
+    /// of this character set will be used.
+    pub fn with_preferred(mut self, preferred: Option<Collation>) -> Self {
+        self.preferred = preferred;
+        self
+    }
+
+    /// Returns `true` if the server supports `utf8mb4` character set.
+    fn supports_utf8mb4(&self) -> bool {
+        self.server_version >= (5, 5, 3)
+    }
+
+    /// Returns the general collation of the best Unicode character set supported by the server.
+    fn default_collation(&self) -> Collation {
+        if self.supports_utf8mb4() {
+            Collation::UTF8MB4_GENERAL_CI
+        } else {
+            Collation::UTF8_GENERAL_CI
+        }
+    }
+
+    /// Returns the collation the connection will use.
+    pub fn collation(&self) -> Collation {
+        match self.preferred {
+            Some(collation) if collation.is_utf8mb4() && !self.supports_utf8mb4() => {
+                Collation::UTF8_GENERAL_CI
+            }
+            Some(collation) => collation,
+            None => Collation::from_id(self.server_collation)
+                .filter(|x| x.charset() == self.default_collation().charset())
+                .unwrap_or_else(|| self.default_collation()),
+        }
+    }
+
+    /// Returns `true` if the collation could be set using the `HandshakeResponse`.
+    fn fits_handshake(&self) -> bool {
+        let collation = self.collation();
+        if !self
+            .capabilities
+            .contains(CapabilityFlags::CLIENT_PROTOCOL_41)
+        {
+            // pre-4.1 handshake response has no collation field
+            return collation.id() == self.server_collation;
+        }
+        collation.id() <= u8::MAX as u16
+    }
+
+    /// Returns the collation id to put into the `HandshakeResponse`.
+    pub fn handshake_collation(&self) -> u8 {
+        if self.fits_handshake() {
+            self.collation().id() as u8
+        } else {
+            self.default_collation().id() as u8
+        }
+    }
+
+    /// Returns a `SET NAMES` statement to issue after connecting (if needed).
+    pub fn set_names(&self) -> Option<String> {
+        if self.fits_handshake() {
+            return None;
+        }
+        let collation = self.collation();
+        Some(format!(
+            "SET NAMES {} COLLATE {}",
+            collation.charset(),
+            collation.name()
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CharsetNegotiation, Collation};
+    use crate::constants::CapabilityFlags;
+
+    #[test]
+    fn should_negotiate_charset() {
+        let caps = CapabilityFlags::CLIENT_PROTOCOL_41;
+
+        let negotiation = CharsetNegotiation::new((8, 0, 33), 255, caps);
+        assert_eq!(negotiation.collation(), Collation::UTF8MB4_0900_AI_CI);
+        assert_eq!(negotiation.handshake_collation(), 255);
+        assert_eq!(negotiation.set_names(), None);
+
+        let negotiation = CharsetNegotiation::new((5, 7, 40), 8, caps);
+        assert_eq!(negotiation.collation(), Collation::UTF8MB4_GENERAL_CI);
+        assert_eq!(negotiation.handshake_collation(), 45);
+        assert_eq!(negotiation.set_names(), None);
+
+        let negotiation = CharsetNegotiation::new((5, 1, 73), 8, caps)
+            .with_preferred(Some(Collation::UTF8MB4_UNICODE_CI));
+        assert_eq!(negotiation.collation(), Collation::UTF8_GENERAL_CI);
+        assert_eq!(negotiation.handshake_collation(), 33);
+        assert_eq!(negotiation.set_names(), None);
+
+        let uca1400 = Collation::new(2304, "utf8mb4", "utf8mb4_uca1400_ai_ci");
+        let negotiation =
+            CharsetNegotiation::new((11, 4, 2), 45, caps).with_preferred(Some(uca1400));
+        assert_eq!(negotiation.collation(), uca1400);
+        assert_eq!(negotiation.handshake_collation(), 45);
+        assert_eq!(
+            negotiation.set_names().as_deref(),
+            Some("SET NAMES utf8mb4 COLLATE utf8mb4_uca1400_ai_ci")
+        );
+
+        let negotiation = CharsetNegotiation::new((5, 7, 40), 45, CapabilityFlags::empty())
+            .with_preferred(Some(Collation::UTF8MB4_BIN));
+        assert_eq!(
+            negotiation.set_names().as_deref(),
+            Some("SET NAMES utf8mb4 COLLATE utf8mb4_bin")
+        );
+        let negotiation = negotiation.with_preferred(None);
+        assert_eq!(negotiation.set_names(), None);
+    }
+}
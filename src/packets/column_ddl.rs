@@ -0,0 +1,397 @@
+// Copyright (c) 2023 Anatoly Ikorsky
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+use std::borrow::Cow;
+
+use crate::{
+    constants::{ColumnFlags, ColumnType},
+    value::Value,
+};
+
+use super::Column;
+
+/// Collation id of the `binary` character set.
+const BINARY_COLLATION_ID: u16 = 63;
+
+/// Column definition as it appears in a `CREATE TABLE` statement, e.g.
+/// `` `id` INT UNSIGNED NOT NULL AUTO_INCREMENT``.
+///
+/// It can be created from a column definition packet (see [`Column`])
+/// or from a table map event (see [`crate::binlog::events::TableMapEvent::columns_ddl`]).
+///
+/// Only the type and the following attributes are rendered: `UNSIGNED`, `ZEROFILL`,
+/// `NOT NULL` and `AUTO_INCREMENT`. Character sets, defaults and keys are not rendered.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct ColumnDdl<'a> {
+    name: Cow<'a, str>,
+    column_type: ColumnType,
+    flags: ColumnFlags,
+    length: Option<u32>,
+    decimals: u8,
+    character_set: u16,
+    values: Vec<Cow<'a, str>>,
+}
+
+impl<'a> ColumnDdl<'a> {
+    /// Creates a new instance.
+    ///
+    /// `column_type` is the real type of the column, i.e. `MYSQL_TYPE_ENUM`
+    /// and `MYSQL_TYPE_SET` are expected for ENUM and SET columns.
+    pub fn new(name: impl Into<Cow<'a, str>>, column_type: ColumnType) -> Self {
+        Self {
+            name: name.into(),
+            column_type,
+            flags: ColumnFlags::empty(),
+            length: None,
+            decimals: 0,
+            character_set: BINARY_COLLATION_ID,
+            values: Vec::new(),
+        }
+    }
+
+    /// Returns modified `self` with the given column flags
+    /// (only `UNSIGNED`, `ZEROFILL`, `NOT NULL` and `AUTO_INCREMENT` are rendered).
+    pub fn with_flags(mut self, flags: ColumnFlags) -> Self {
+        self.flags = flags;
+        self
+    }
+
+    /// Returns modified `self` with the given length.
+    ///
+    /// It's the length in characters for `CHAR`, `VARCHAR`, `BINARY` and `VARBINARY` columns,
+    /// the precision for `DECIMAL` columns and the width for `BIT` columns.
+    pub fn with_length(mut self, length: Option<u32>) -> Self {
+        self.length = length;
+        self
+    }
+
+    /// Returns modified `self` with the given number of decimals
+    /// (the scale for `DECIMAL` columns and the fractional seconds precision
+    /// for temporal columns).
+    pub fn with_decimals(mut self, decimals: u8) -> Self {
+        self.decimals = decimals;
+        self
+    }
+
+    /// Returns modified `self` with the given collation id
+    /// (defines whether a string column is binary, defaults to `binary`).
+    pub fn with_character_set(mut self, character_set: u16) -> Self {
+        self.character_set = character_set;
+        self
+    }
+
+    /// Returns modified `self` with the given variants of an ENUM or SET column.
+    pub fn with_values<T>(mut self, values: impl IntoIterator<Item = T>) -> Self
+    where
+        T: Into<Cow<'a, str>>,
+    {
+        self.values = values.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Returns the column name.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the real column type.
+    pub fn column_type(&self) -> ColumnType {
+        self.column_type
+    }
+
+    /// Returns column flags.
+    pub fn flags(&self) -> ColumnFlags {
+        self.flags
+    }
+
+    /// Returns the length (see [`ColumnDdl::with_length`]).
+    pub fn length(&self) -> Option<u32> {
+        self.length
+    }
+
+    /// Returns the number of decimals (see [`ColumnDdl::with_decimals`]).
+    pub fn decimals(&self) -> u8 {
+        self.decimals
+    }
+
+    /// Returns the collation id.
+    pub fn character_set(&self) -> u16 {
+        self.character_set
+    }
+
+    /// Returns variants of an ENUM or SET column.
+    pub fn values(&self) -> &[Cow<'a, str>] {
+        &self.values
+    }
+
+    /// Returns a `'static` version of `self`.
+    pub fn into_owned(self) -> ColumnDdl<'static> {
+        ColumnDdl {
+            name: Cow::Owned(self.name.into_owned()),
+            column_type: self.column_type,
+            flags: self.flags,
+            length: self.length,
+            decimals: self.decimals,
+            character_set: self.character_set,
+            values: self
+                .values
+                .into_iter()
+                .map(|x| Cow::Owned(x.into_owned()))
+                .collect(),
+        }
+    }
+
+    /// Renders the column definition.
+    ///
+    /// Returns `None` for types that can't be declared
+    /// (`MYSQL_TYPE_NULL`, `MYSQL_TYPE_TYPED_ARRAY` and `MYSQL_TYPE_UNKNOWN`).
+    pub fn to_sql(&self) -> Option<String> {
+        use ColumnType::*;
+
+        let is_binary = self.character_set == BINARY_COLLATION_ID;
+        let fsp = if (1..=6).contains(&self.decimals) {
+            format!("({})", self.decimals)
+        } else {
+            String::new()
+        };
+        let length = self.length.map(|x| format!("({})", x)).unwrap_or_default();
+        let pick = |binary: &'static str, text: &'static str| if is_binary { binary } else { text };
+
+        let sql_type = match self.column_type {
+            MYSQL_TYPE_TINY => "TINYINT".into(),
+            MYSQL_TYPE_SHORT => "SMALLINT".into(),
+            MYSQL_TYPE_INT24 => "MEDIUMINT".into(),
+            MYSQL_TYPE_LONG => "INT".into(),
+            MYSQL_TYPE_LONGLONG => "BIGINT".into(),
+            MYSQL_TYPE_FLOAT => "FLOAT".into(),
+            MYSQL_TYPE_DOUBLE => "DOUBLE".into(),
+            MYSQL_TYPE_DECIMAL | MYSQL_TYPE_NEWDECIMAL => match self.length {
+                Some(precision) => format!("DECIMAL({},{})", precision, self.decimals),
+                None => "DECIMAL".into(),
+            },
+            MYSQL_TYPE_YEAR => "YEAR".into(),
+            MYSQL_TYPE_DATE | MYSQL_TYPE_NEWDATE => "DATE".into(),
+            MYSQL_TYPE_TIME | MYSQL_TYPE_TIME2 => format!("TIME{}", fsp),
+            MYSQL_TYPE_DATETIME | MYSQL_TYPE_DATETIME2 => format!("DATETIME{}", fsp),
+            MYSQL_TYPE_TIMESTAMP | MYSQL_TYPE_TIMESTAMP2 => format!("TIMESTAMP{}", fsp),
+            MYSQL_TYPE_BIT => format!("BIT{}", length),
+            MYSQL_TYPE_VARCHAR | MYSQL_TYPE_VAR_STRING => {
+                format!("{}{}", pick("VARBINARY", "VARCHAR"), length)
+            }
+            MYSQL_TYPE_STRING => format!("{}{}", pick("BINARY", "CHAR"), length),
+            MYSQL_TYPE_TINY_BLOB => pick("TINYBLOB", "TINYTEXT").into(),
+            MYSQL_TYPE_BLOB => pick("BLOB", "TEXT").into(),
+            MYSQL_TYPE_MEDIUM_BLOB => pick("MEDIUMBLOB", "MEDIUMTEXT").into(),
+            MYSQL_TYPE_LONG_BLOB => pick("LONGBLOB", "LONGTEXT").into(),
+            MYSQL_TYPE_JSON => "JSON".into(),
+            MYSQL_TYPE_GEOMETRY => "GEOMETRY".into(),
+            MYSQL_TYPE_ENUM | MYSQL_TYPE_SET => {
+                let values = self
+                    .values
+                    .iter()
+                    .map(|x| Value::from(x.as_ref()).as_sql(true))
+                    .collect::<Vec<_>>()
+                    .join(",");
+                let name = if self.column_type == MYSQL_TYPE_ENUM {
+                    "ENUM"
+                } else {
+                    "SET"
+                };
+                format!("{}({})", name, values)
+            }
+            MYSQL_TYPE_NULL | MYSQL_TYPE_TYPED_ARRAY | MYSQL_TYPE_UNKNOWN => return None,
+        };
+
+        let mut sql = format!("`{}` {}", self.name.replace('`', "``"), sql_type);
+        if self.column_type.is_numeric_type() && self.column_type != MYSQL_TYPE_YEAR {
+            if self.flags.contains(ColumnFlags::UNSIGNED_FLAG) {
+                sql.push_str(" UNSIGNED");
+            }
+            if self.flags.contains(ColumnFlags::ZEROFILL_FLAG) {
+                sql.push_str(" ZEROFILL");
+            }
+        }
+        if self.flags.contains(ColumnFlags::NOT_NULL_FLAG) {
+            sql.push_str(" NOT NULL");
+        }
+        if self.flags.contains(ColumnFlags::AUTO_INCREMENT_FLAG) {
+            sql.push_str(" AUTO_INCREMENT");
+        }
+
+        Some(sql)
+    }
+}
+
+impl<'a> From<&'a Column> for ColumnDdl<'a> {
+    /// Variants of ENUM and SET columns are not available in a column definition packet
+    /// (see [`ColumnDdl::with_values`]).
+    fn from(column: &'a Column) -> Self {
+        use ColumnType::*;
+
+        let flags = column.flags();
+        let column_length = column.column_length();
+        let charset = column.character_set();
+        let decimals = column.decimals();
+
+        let column_type = match column.column_type() {
+            MYSQL_TYPE_STRING if flags.contains(ColumnFlags::ENUM_FLAG) => MYSQL_TYPE_ENUM,
+            MYSQL_TYPE_STRING if flags.contains(ColumnFlags::SET_FLAG) => MYSQL_TYPE_SET,
+            MYSQL_TYPE_TINY_BLOB
+            | MYSQL_TYPE_BLOB
+            | MYSQL_TYPE_MEDIUM_BLOB
+            | MYSQL_TYPE_LONG_BLOB => blob_type(column_length / mbmaxlen(charset)),
+            column_type => column_type,
+        };
+
+        let length = match column_type {
+            MYSQL_TYPE_STRING | MYSQL_TYPE_VARCHAR | MYSQL_TYPE_VAR_STRING => {
+                Some(column_length / mbmaxlen(charset))
+            }
+            MYSQL_TYPE_DECIMAL | MYSQL_TYPE_NEWDECIMAL => {
+                let mut precision = column_length;
+                if decimals > 0 {
+                    precision = precision.saturating_sub(1);
+                }
+                if !flags.contains(ColumnFlags::UNSIGNED_FLAG) {
+                    precision = precision.saturating_sub(1);
+                }
+                Some(precision)
+            }
+            MYSQL_TYPE_BIT => Some(column_length),
+            _ => None,
+        };
+
+        let name = match column.org_name_ref() {
+            [] => column.name_str(),
+            _ => column.org_name_str(),
+        };
+
+        ColumnDdl::new(name, column_type)
+            .with_flags(flags)
+            .with_length(length)
+            .with_decimals(decimals)
+            .with_character_set(charset)
+    }
+}
+
+/// Returns a BLOB type that fits values of the given maximum length.
+pub(crate) fn blob_type(max_len: u32) -> ColumnType {
+    match max_len {
+        0..=0xff => ColumnType::MYSQL_TYPE_TINY_BLOB,
+        0x100..=0xffff => ColumnType::MYSQL_TYPE_BLOB,
+        0x10000..=0xffffff => ColumnType::MYSQL_TYPE_MEDIUM_BLOB,
+        _ => ColumnType::MYSQL_TYPE_LONG_BLOB,
+    }
+}
+
+/// Returns the maximum length of a character (in bytes) for the given collation id.
+///
+/// Unknown collations are assumed to be single-byte.
+pub(crate) fn mbmaxlen(collation_id: u16) -> u32 {
+    match collation_id {
+        // utf8mb4, utf16, utf16le, utf32, gb18030
+        45
+        | 46
+        | 224..=247
+        | 255..=323
+        | 54
+        | 55
+        | 56
+        | 62
+        | 101..=124
+        | 60
+        | 61
+        | 160..=183
+        | 248..=250 => 4,
+        // utf8mb3, ujis, eucjpms
+        33 | 76 | 83 | 192..=215 | 223 | 12 | 91 | 97 | 98 => 3,
+        // ucs2, big5, sjis, euckr, gb2312, gbk, cp932
+        35 | 90 | 128..=151 | 159 | 1 | 84 | 13 | 88 | 19 | 85 | 24 | 86 | 28 | 87 | 95 | 96 => 2,
+        _ => 1,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ColumnDdl;
+    use crate::{
+        constants::{ColumnFlags, ColumnType},
+        packets::Column,
+    };
+
+    #[test]
+    fn column_packet_ddl() {
+        let sql = |column: &Column| ColumnDdl::from(column).to_sql().unwrap();
+
+        let column = Column::new(ColumnType::MYSQL_TYPE_LONG)
+            .with_name(b"alias")
+            .with_org_name(b"id")
+            .with_column_length(10)
+            .with_character_set(63)
+            .with_flags(
+                ColumnFlags::UNSIGNED_FLAG
+                    | ColumnFlags::NOT_NULL_FLAG
+                    | ColumnFlags::AUTO_INCREMENT_FLAG
+                    | ColumnFlags::PRI_KEY_FLAG,
+            );
+        assert_eq!(sql(&column), "`id` INT UNSIGNED NOT NULL AUTO_INCREMENT");
+
+        let column = Column::new(ColumnType::MYSQL_TYPE_VAR_STRING)
+            .with_name(b"na`me")
+            .with_column_length(80)
+            .with_character_set(255)
+            .with_decimals(0x1f);
+        assert_eq!(sql(&column), "`na``me` VARCHAR(20)");
+
+        let column = Column::new(ColumnType::MYSQL_TYPE_STRING)
+            .with_name(b"hash")
+            .with_column_length(16)
+            .with_character_set(63)
+            .with_flags(ColumnFlags::BINARY_FLAG | ColumnFlags::NOT_NULL_FLAG);
+        assert_eq!(sql(&column), "`hash` BINARY(16) NOT NULL");
+
+        let column = Column::new(ColumnType::MYSQL_TYPE_NEWDECIMAL)
+            .with_name(b"price")
+            .with_column_length(12)
+            .with_decimals(2)
+            .with_character_set(63);
+        assert_eq!(sql(&column), "`price` DECIMAL(10,2)");
+
+        let column = Column::new(ColumnType::MYSQL_TYPE_BLOB)
+            .with_name(b"body")
+            .with_column_length(262140)
+            .with_character_set(255)
+            .with_flags(ColumnFlags::BLOB_FLAG);
+        assert_eq!(sql(&column), "`body` TEXT");
+
+        let column = Column::new(ColumnType::MYSQL_TYPE_DATETIME)
+            .with_name(b"at")
+            .with_column_length(23)
+            .with_decimals(3)
+            .with_character_set(63);
+        assert_eq!(sql(&column), "`at` DATETIME(3)");
+
+        let column = Column::new(ColumnType::MYSQL_TYPE_STRING)
+            .with_name(b"size")
+            .with_column_length(24)
+            .with_character_set(255)
+            .with_flags(ColumnFlags::ENUM_FLAG);
+        assert_eq!(
+            ColumnDdl::from(&column)
+                .with_values(vec!["small", "it's big"])
+                .to_sql()
+                .unwrap(),
+            "`size` ENUM('small','it''s big')"
+        );
+
+        assert_eq!(
+            ColumnDdl::new("x", ColumnType::MYSQL_TYPE_NULL).to_sql(),
+            None
+        );
+    }
+}
@@ -14,7 +14,12 @@ use uuid::Uuid;
 
 use std::str::FromStr;
 use std::{
-    borrow::Cow, cmp::max, collections::HashMap, convert::TryFrom, fmt, io, marker::PhantomData,
+    borrow::Cow,
+    cmp::{max, min},
+    collections::{BTreeMap, HashMap},
+    convert::TryFrom,
+    fmt, io,
+    marker::PhantomData,
 };
 
 use crate::{
@@ -92,6 +97,9 @@ macro_rules! define_const_bytes {
 }
 
 pub mod binlog_request;
+pub mod charset_negotiation;
+pub mod column_ddl;
+pub mod replication_setup;
 pub mod session_state_change;
 
 define_const_bytes!(
@@ -779,8 +787,8 @@ impl<'a> fmt::Display for ProgressReport<'a> {
             "Stage: {} of {} '{}'  {:.2}% of stage done",
             self.stage(),
             self.max_stage(),
-            self.progress(),
-            self.stage_info_str()
+            self.stage_info_str(),
+            f64::from(self.progress()) / 1000.0
         )
     }
 }
@@ -871,6 +879,46 @@ impl<'a> fmt::Display for ErrPacket<'a> {
     }
 }
 
+/// Kind of a server response packet (see [`ResponsePacketKind::classify`]).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ResponsePacketKind {
+    /// OK packet.
+    Ok,
+    /// EOF packet (or an OK packet that terminates a result set).
+    Eof,
+    /// Error packet holding a [`ServerError`].
+    Err,
+    /// Error packet holding a [`ProgressReport`] (MariaDB).
+    ///
+    /// Server will send another response afterwards, so the client should keep reading.
+    Progress,
+    /// `LOCAL INFILE` request.
+    LocalInfile,
+    /// Anything else (e.g. a result set header or a row).
+    Other,
+}
+
+impl ResponsePacketKind {
+    /// Classifies a response to a command by its header.
+    ///
+    /// Progress reports are only recognized if `capabilities` contains
+    /// `CLIENT_PROGRESS_OBSOLETE` (same as [`ErrPacket`] deserialization).
+    pub fn classify(packet: &[u8], capabilities: CapabilityFlags) -> Self {
+        match packet {
+            [0x00, ..] => Self::Ok,
+            [0xFE, ..] if packet.len() < 0xFF_FF_FF => Self::Eof,
+            [0xFF, 0xFF, 0xFF, ..]
+                if capabilities.contains(CapabilityFlags::CLIENT_PROGRESS_OBSOLETE) =>
+            {
+                Self::Progress
+            }
+            [0xFF, ..] => Self::Err,
+            [0xFB, ..] => Self::LocalInfile,
+            _ => Self::Other,
+        }
+    }
+}
+
 /// MySql error packet.
 ///
 /// May hold an error or a progress report.
@@ -1165,6 +1213,8 @@ impl<'a> AuthPlugin<'a> {
     /// It'll generate `None` if password is `None` or empty.
     ///
     /// Note, that you should trim terminating null character from the `nonce`.
+    #[cfg(feature = "client-protocol")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "client-protocol")))]
     pub fn gen_data<'b>(&self, pass: Option<&'b str>, nonce: &[u8]) -> Option<AuthPluginData<'b>> {
         use super::scramble::{scramble_323, scramble_native, scramble_sha256};
 
@@ -1943,6 +1993,13 @@ impl<'a> HandshakeResponse<'a> {
         self.collation.0
     }
 
+    /// Overrides the collation chosen by [`HandshakeResponse::new`]
+    /// (see [`charset_negotiation::CharsetNegotiation::handshake_collation`]).
+    pub fn with_collation(mut self, collation: u8) -> Self {
+        self.collation = RawInt::new(collation);
+        self
+    }
+
     pub fn scramble_buf(&self) -> &[u8] {
         match &self.scramble_buf {
             Either::Left(x) => x.as_bytes(),
@@ -2903,6 +2960,16 @@ impl GnoInterval {
         }
         Ok(Self::new(start, end))
     }
+
+    /// Returns the first GNO of this interval (inclusive).
+    pub fn start(&self) -> u64 {
+        self.start.0
+    }
+
+    /// Returns the end of this interval (exclusive).
+    pub fn end(&self) -> u64 {
+        self.end.0
+    }
 }
 
 impl MySerialize for GnoInterval {
@@ -2968,11 +3035,19 @@ impl<'a> Sid<'a> {
         self
     }
 
+    /// Returns a `'static` version of `self`.
+    pub fn into_owned(self) -> Sid<'static> {
+        Sid {
+            uuid: self.uuid,
+            intervals: self.intervals.into_owned(),
+        }
+    }
+
     fn len(&self) -> u64 {
         use saturating::Saturating as S;
         let mut len = S(UUID_LEN as u64); // SID
         len += S(8); // n_intervals
-        len += S((self.intervals.len() * 16) as u64);
+        len += S((self.intervals.len() as u64).saturating_mul(16));
         len.0
     }
 }
@@ -3047,6 +3122,230 @@ impl<'a> FromStr for Sid<'a> {
     }
 }
 
+impl fmt::Display for Sid<'_> {
+    /// Formats this SID using the MySql GTID set syntax (e.g. `uuid:1-5:10`).
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", Uuid::from_bytes(self.uuid).hyphenated())?;
+        for interval in self.intervals() {
+            if interval.end() == interval.start() + 1 {
+                write!(f, ":{}", interval.start())?;
+            } else {
+                write!(f, ":{}-{}", interval.start(), interval.end() - 1)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// GTID set in the binary format.
+///
+/// It's used by the `PREVIOUS_GTIDS_EVENT` binlog event and is the same
+/// as the SID block of the `COM_BINLOG_DUMP_GTID` command.
+#[derive(Debug, Default, Clone, Eq, PartialEq, Hash)]
+pub struct GtidSet<'a> {
+    sids: Seq<'a, Sid<'a>, LeU64>,
+}
+
+impl<'a> GtidSet<'a> {
+    /// Creates a new instance.
+    pub fn new(sids: impl Into<Cow<'a, [Sid<'a>]>>) -> Self {
+        Self {
+            sids: Seq::new(sids),
+        }
+    }
+
+    /// Returns the sequence of sids in this set.
+    pub fn sids(&self) -> &[Sid<'a>] {
+        &self.sids
+    }
+
+    /// Returns `true` if this set contains no sids.
+    pub fn is_empty(&self) -> bool {
+        self.sids.is_empty()
+    }
+
+    /// Appends a SID to this set.
+    pub fn with_sid(mut self, sid: Sid<'a>) -> Self {
+        self.sids.push(sid);
+        self
+    }
+
+    /// Returns the encoded length of this set (saturated at `u64::MAX`).
+    pub fn encoded_len(&self) -> u64 {
+        use saturating::Saturating as S;
+        let mut len = S(8); // n_sids
+        for sid in self.sids.iter() {
+            len += S(sid.len());
+        }
+        len.0
+    }
+
+    /// Returns `true` if every GTID of this set is also contained in `other`.
+    pub fn is_subset(&self, other: &GtidSet<'_>) -> bool {
+        let other = normalize_sids(other.sids());
+
+        self.sids().iter().all(|sid| {
+            let covering = other
+                .iter()
+                .find(|x| x.uuid() == sid.uuid())
+                .map(|x| x.intervals())
+                .unwrap_or_default();
+            sid.intervals()
+                .iter()
+                .filter(|x| x.start() < x.end())
+                .all(|x| {
+                    covering
+                        .iter()
+                        .any(|y| y.start() <= x.start() && x.end() <= y.end())
+                })
+        })
+    }
+
+    /// Returns a `'static` version of `self`.
+    pub fn into_owned(self) -> GtidSet<'static> {
+        GtidSet::new(
+            self.sids
+                .0
+                .into_owned()
+                .into_iter()
+                .map(Sid::into_owned)
+                .collect::<Vec<_>>(),
+        )
+    }
+}
+
+impl MySerialize for GtidSet<'_> {
+    fn serialize(&self, buf: &mut Vec<u8>) {
+        self.sids.serialize(buf);
+    }
+}
+
+impl<'de> MyDeserialize<'de> for GtidSet<'de> {
+    const SIZE: Option<usize> = None;
+    type Ctx = ();
+
+    fn deserialize((): Self::Ctx, buf: &mut ParseBuf<'de>) -> io::Result<Self> {
+        buf.parse(()).map(|sids| Self { sids })
+    }
+}
+
+impl FromStr for GtidSet<'static> {
+    type Err = io::Error;
+
+    /// Parses a GTID set (see [`parse_gtid_set`]).
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_gtid_set(s).map(GtidSet::new)
+    }
+}
+
+impl fmt::Display for GtidSet<'_> {
+    /// Formats this set as the server formats `gtid_executed` (see [`format_gtid_set`]).
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&format_gtid_set(self.sids()))
+    }
+}
+
+/// Maximum length of the SID block of a `COM_BINLOG_DUMP_GTID` packet
+/// (it's prefixed with a 4-byte data-size field).
+pub const MAX_SID_BLOCK_LEN: u64 = u32::MAX as u64;
+
+/// SID block of a `COM_BINLOG_DUMP_GTID` packet doesn't fit the 4-byte data-size field.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, thiserror::Error)]
+#[error(
+    "SID block is too large: {} bytes (maximum is {} bytes)",
+    _0,
+    MAX_SID_BLOCK_LEN
+)]
+pub struct SidBlockTooLarge(pub u64);
+
+/// Parses a GTID set in the textual format of `gtid_executed` and `gtid_purged` variables
+/// (also used in the `Executed_Gtid_Set` column of `SHOW MASTER STATUS`).
+///
+/// Whitespace (including line breaks) around SIDs, UUIDs and interval bounds is ignored,
+/// as well as empty SIDs (e.g. a trailing comma). The result is normalized the same way the server does it: SIDs are sorted by UUID,
+/// SIDs with the same UUID are merged, and intervals are sorted and coalesced.
+///
+/// ```
+/// # use mysql_common::packets::parse_gtid_set;
+/// let sids = parse_gtid_set(
+///     "3e11fa47-71ca-11e1-9e33-c80aa9429562:1-5,\n3E11FA47-71CA-11E1-9E33-C80AA9429562:6-7",
+/// )
+/// .unwrap();
+/// assert_eq!(sids.len(), 1);
+/// assert_eq!(sids[0].to_string(), "3e11fa47-71ca-11e1-9e33-c80aa9429562:1-7");
+/// ```
+pub fn parse_gtid_set(text: &str) -> io::Result<Vec<Sid<'static>>> {
+    let mut gtid_set: BTreeMap<[u8; UUID_LEN], Vec<GnoInterval>> = BTreeMap::new();
+
+    for sid in text.split(',').filter(|sid| !sid.trim().is_empty()) {
+        let mut parts = sid.split(':').map(str::trim);
+        let uuid = parts.next().unwrap_or_default();
+        let uuid = Uuid::parse_str(uuid)
+            .map_err(|e| Sid::wrap_err(format!("invalid uuid format: {}, error: {}", sid, e)))?;
+        let intervals = gtid_set.entry(*uuid.as_bytes()).or_default();
+        for interval in parts {
+            let (start, end) = match interval.split_once('-') {
+                Some((start, end)) => (start.trim_end(), end.trim_start()),
+                None => (interval, interval),
+            };
+            let start = Sid::parse_interval_num(start, sid)?;
+            let end = Sid::parse_interval_num(end, sid)?;
+            intervals.push(GnoInterval::check_and_new(start, end.saturating_add(1))?);
+        }
+    }
+
+    Ok(normalize_gtid_set(gtid_set))
+}
+
+/// Formats the given SIDs as a GTID set exactly as the server prints
+/// `gtid_executed` and `gtid_purged` variables.
+///
+/// SIDs are normalized (see [`parse_gtid_set`]) and separated by `",\n"`.
+/// Empty set is formatted as an empty string.
+pub fn format_gtid_set<'a>(sids: impl IntoIterator<Item = &'a Sid<'a>>) -> String {
+    normalize_sids(sids)
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(",\n")
+}
+
+/// Normalizes the given SIDs (see [`parse_gtid_set`]).
+fn normalize_sids<'a>(sids: impl IntoIterator<Item = &'a Sid<'a>>) -> Vec<Sid<'static>> {
+    let mut gtid_set: BTreeMap<[u8; UUID_LEN], Vec<GnoInterval>> = BTreeMap::new();
+    for sid in sids {
+        gtid_set
+            .entry(sid.uuid())
+            .or_default()
+            .extend_from_slice(sid.intervals());
+    }
+    normalize_gtid_set(gtid_set)
+}
+
+/// Sorts and coalesces intervals, dropping SIDs without intervals.
+fn normalize_gtid_set(gtid_set: BTreeMap<[u8; UUID_LEN], Vec<GnoInterval>>) -> Vec<Sid<'static>> {
+    gtid_set
+        .into_iter()
+        .filter_map(|(uuid, mut intervals)| {
+            intervals.sort_unstable_by_key(|x| (x.start(), x.end()));
+            let mut merged: Vec<GnoInterval> = Vec::with_capacity(intervals.len());
+            for interval in intervals {
+                match merged.last_mut() {
+                    Some(last) if interval.start() <= last.end() => {
+                        *last = GnoInterval::new(last.start(), max(last.end(), interval.end()));
+                    }
+                    _ => merged.push(interval),
+                }
+            }
+            if merged.is_empty() {
+                None
+            } else {
+                Some(Sid::new(uuid).with_intervals(merged))
+            }
+        })
+        .collect()
+}
+
 define_header!(
     ComBinlogDumpGtidHeader,
     COM_BINLOG_DUMP_GTID,
@@ -3073,6 +3372,11 @@ pub struct ComBinlogDumpGtid<'a> {
     /// Position in the binlog-file to start the stream with (`0` by default).
     pos: RawInt<LeU64>,
     /// SID block.
+    ///
+    /// # Note
+    ///
+    /// Serialization will produce a malformed packet if the encoded length is greater
+    /// than [`MAX_SID_BLOCK_LEN`] (see [`ComBinlogDumpGtid::checked_sid_block_len`]).
     sid_block: Seq<'a, Sid<'a>, LeU64>,
 }
 
@@ -3172,14 +3476,25 @@ impl<'a> ComBinlogDumpGtid<'a> {
         self
     }
 
-    fn sid_block_len(&self) -> u32 {
+    /// Returns the encoded length of the SID block (saturated at `u64::MAX`).
+    pub fn sid_block_len(&self) -> u64 {
         use saturating::Saturating as S;
         let mut len = S(8); // n_sids
         for sid in self.sid_block.iter() {
-            len += S(sid.len() as u32);
+            len += S(sid.len());
         }
         len.0
     }
+
+    /// Returns the encoded length of the SID block, or an error
+    /// if it exceeds [`MAX_SID_BLOCK_LEN`].
+    ///
+    /// GTID sets of such size can't be sent to the server, so this should be checked
+    /// before sending a packet built from a user-provided GTID set.
+    pub fn checked_sid_block_len(&self) -> Result<u32, SidBlockTooLarge> {
+        let len = self.sid_block_len();
+        u32::try_from(len).map_err(|_| SidBlockTooLarge(len))
+    }
 }
 
 impl MySerialize for ComBinlogDumpGtid<'_> {
@@ -3189,7 +3504,7 @@ impl MySerialize for ComBinlogDumpGtid<'_> {
         self.server_id.serialize(&mut *buf);
         self.filename.serialize(&mut *buf);
         self.pos.serialize(&mut *buf);
-        buf.put_u32_le(self.sid_block_len());
+        buf.put_u32_le(min(self.sid_block_len(), MAX_SID_BLOCK_LEN) as u32);
         self.sid_block.serialize(&mut *buf);
     }
 }
@@ -3211,6 +3526,12 @@ impl<'de> MyDeserialize<'de> for ComBinlogDumpGtid<'de> {
         let sid_data_len: RawInt<LeU32> = buf.parse(())?;
         let mut buf: ParseBuf = buf.parse(sid_data_len.0 as usize)?;
         let sid_block = buf.parse(())?;
+        if !buf.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "SID block length doesn't match the data-size field",
+            ));
+        }
 
         Ok(Self {
             header,
@@ -3231,6 +3552,7 @@ define_header!(
 
 /// Each Semi Sync Binlog Event with the `SEMI_SYNC_ACK_REQ` flag set the slave has to acknowledge
 /// with Semi-Sync ACK packet.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
 pub struct SemiSyncAckPacket<'a> {
     header: SemiSyncAckPacketPacketHeader,
     position: RawInt<LeU64>,
@@ -3272,6 +3594,14 @@ impl<'a> SemiSyncAckPacket<'a> {
     pub fn filename(&self) -> Cow<'_, str> {
         self.filename.as_str()
     }
+
+    pub fn into_owned(self) -> SemiSyncAckPacket<'static> {
+        SemiSyncAckPacket {
+            header: self.header,
+            position: self.position,
+            filename: self.filename.into_owned(),
+        }
+    }
 }
 
 impl MySerialize for SemiSyncAckPacket<'_> {
@@ -3333,6 +3663,16 @@ mod test {
             assert_eq!(cmd, ComBinlogDump::deserialize((), &mut ParseBuf(&output[..]))?);
         }
 
+        #[test]
+        fn semi_sync_ack_packet_roundtrip(position: u64, filename: Vec<u8>) {
+            let packet = SemiSyncAckPacket::new(position, filename);
+
+            let mut output = Vec::new();
+            packet.serialize(&mut output);
+
+            assert_eq!(packet, SemiSyncAckPacket::deserialize((), &mut ParseBuf(&output[..]))?);
+        }
+
         #[test]
         fn com_register_slave_roundtrip(
             server_id: u32,
@@ -3393,6 +3733,70 @@ mod test {
         }
     }
 
+    #[test]
+    fn com_binlog_dump_gtid_large_sid_blocks() {
+        let sids = (0..5000_u64)
+            .map(|i| {
+                Sid::new(*Uuid::from_u128(i as u128).as_bytes()).with_intervals(vec![
+                    GnoInterval::new(1, i + 2),
+                    GnoInterval::new(i + 3, i + 4),
+                ])
+            })
+            .chain(Some(
+                Sid::new([0xff; 16]).with_intervals(
+                    (0..10_000)
+                        .map(|i| GnoInterval::new(i * 2, i * 2 + 1))
+                        .collect(),
+                ),
+            ))
+            .collect::<Vec<_>>();
+        let cmd = ComBinlogDumpGtid::new(1).with_sids(sids);
+
+        let expected_len = 8 + 5000 * (16 + 8 + 2 * 16) + (16 + 8 + 10_000 * 16);
+        assert_eq!(cmd.sid_block_len(), expected_len);
+        assert_eq!(cmd.checked_sid_block_len(), Ok(expected_len as u32));
+
+        let mut output = Vec::new();
+        cmd.serialize(&mut output);
+        let data_size_pos = output.len() - expected_len as usize - 4;
+        assert_eq!(
+            &output[data_size_pos..][..4],
+            &(expected_len as u32).to_le_bytes()[..]
+        );
+        assert_eq!(
+            cmd,
+            ComBinlogDumpGtid::deserialize((), &mut ParseBuf(&output[..])).unwrap()
+        );
+
+        // data-size field must match the encoded SID block
+        let mut invalid = output.clone();
+        invalid[data_size_pos..][..4].copy_from_slice(&(expected_len as u32 - 16).to_le_bytes());
+        assert!(ComBinlogDumpGtid::deserialize((), &mut ParseBuf(&invalid[..])).is_err());
+        let mut invalid = output;
+        invalid[data_size_pos..][..4].copy_from_slice(&(expected_len as u32 + 1).to_le_bytes());
+        invalid.push(0);
+        assert!(ComBinlogDumpGtid::deserialize((), &mut ParseBuf(&invalid[..])).is_err());
+
+        // 4096 SIDs sharing 65536 intervals give a SID block of more than 4GB
+        let intervals = (0..65536)
+            .map(|i| GnoInterval::new(i, i + 1))
+            .collect::<Vec<_>>();
+        let sids = (0..4096_u64)
+            .map(|i| Sid {
+                uuid: *Uuid::from_u128(i as u128).as_bytes(),
+                intervals: Seq::new(&intervals[..]),
+            })
+            .collect::<Vec<_>>();
+        let cmd = ComBinlogDumpGtid::new(1).with_sids(sids);
+        let expected_len = 8 + 4096 * (16 + 8 + 65536 * 16);
+        assert_eq!(cmd.sid_block_len(), expected_len);
+        assert_eq!(
+            cmd.checked_sid_block_len(),
+            Err(SidBlockTooLarge(expected_len))
+        );
+        assert!(expected_len > MAX_SID_BLOCK_LEN);
+    }
+
     #[test]
     fn should_parse_local_infile_packet() {
         const LIP: &[u8] = b"\xfbfile_name";
@@ -3543,6 +3947,42 @@ mod test {
         assert_eq!(progress_report.max_stage(), 10);
         assert_eq!(progress_report.progress(), 23500);
         assert_eq!(progress_report.stage_info_str(), "stage name");
+        assert_eq!(
+            progress_report.to_string(),
+            "Stage: 1 of 10 'stage name'  23.50% of stage done"
+        );
+
+        assert_eq!(
+            ResponsePacketKind::classify(
+                PROGRESS_PACKET,
+                CapabilityFlags::CLIENT_PROGRESS_OBSOLETE
+            ),
+            ResponsePacketKind::Progress
+        );
+        assert_eq!(
+            ResponsePacketKind::classify(PROGRESS_PACKET, CapabilityFlags::empty()),
+            ResponsePacketKind::Err
+        );
+        assert_eq!(
+            ResponsePacketKind::classify(ERR_PACKET, CapabilityFlags::CLIENT_PROGRESS_OBSOLETE),
+            ResponsePacketKind::Err
+        );
+        assert_eq!(
+            ResponsePacketKind::classify(b"\x00\x00\x00\x02\x00\x00\x00", CapabilityFlags::empty()),
+            ResponsePacketKind::Ok
+        );
+        assert_eq!(
+            ResponsePacketKind::classify(b"\xfe\x00\x00\x02\x00", CapabilityFlags::empty()),
+            ResponsePacketKind::Eof
+        );
+        assert_eq!(
+            ResponsePacketKind::classify(b"\xfbfile", CapabilityFlags::empty()),
+            ResponsePacketKind::LocalInfile
+        );
+        assert_eq!(
+            ResponsePacketKind::classify(b"\x01", CapabilityFlags::empty()),
+            ResponsePacketKind::Other
+        );
     }
 
     #[test]
@@ -3883,4 +4323,95 @@ mod test {
             "start(4) >= end(4) in GnoInterval".to_string()
         );
     }
+
+    #[test]
+    fn sid_to_string() {
+        let input = "3e11fa47-71ca-11e1-9e33-c80aa9429562:1-5:10:12-15";
+        let sid = input.parse::<Sid>().unwrap();
+        assert_eq!(sid.to_string(), input);
+        assert_eq!(sid.to_string().parse::<Sid>().unwrap(), sid);
+    }
+
+    #[test]
+    fn session_track_gtids_to_sids() {
+        use self::session_state_change::Gtids;
+
+        let gtids = Gtids::new(
+            &b"3e11fa47-71ca-11e1-9e33-c80aa9429562:1-5,\n4a1c2b4e-71ca-11e1-9e33-c80aa9429562:7"[..],
+        );
+        let sids = gtids.sids().unwrap();
+        assert_eq!(sids.len(), 2);
+        assert_eq!(sids[0].intervals(), &[GnoInterval::new(1, 6)]);
+        assert_eq!(sids[1].intervals(), &[GnoInterval::new(7, 8)]);
+        assert_eq!(
+            Gtids::from_sids(&sids).as_str(),
+            "3e11fa47-71ca-11e1-9e33-c80aa9429562:1-5,4a1c2b4e-71ca-11e1-9e33-c80aa9429562:7"
+        );
+
+        assert!(Gtids::new(&b""[..]).sids().unwrap().is_empty());
+        assert!(Gtids::new(&b"foo"[..]).sids().is_err());
+
+        // delegates to `parse_gtid_set`
+        let gtids = Gtids::new(
+            &b"4a1c2b4e-71ca-11e1-9e33-c80aa9429562:7,,\n3e11fa47-71ca-11e1-9e33-c80aa9429562:3-5:1-2,"[..],
+        );
+        assert_eq!(
+            gtids.sids().unwrap(),
+            parse_gtid_set(&gtids.as_str()).unwrap()
+        );
+        assert_eq!(
+            Gtids::from_sids(&gtids.sids().unwrap()).as_str(),
+            "3e11fa47-71ca-11e1-9e33-c80aa9429562:1-5,4a1c2b4e-71ca-11e1-9e33-c80aa9429562:7"
+        );
+    }
+
+    #[test]
+    fn gtid_set_text_roundtrip() {
+        const EXECUTED: &str = "3e11fa47-71ca-11e1-9e33-c80aa9429562:1-5:10:12-15,\n\
+            4a1c2b4e-71ca-11e1-9e33-c80aa9429562:7";
+
+        let sids = parse_gtid_set(EXECUTED).unwrap();
+        assert_eq!(sids.len(), 2);
+        assert_eq!(
+            sids[0].intervals(),
+            &[
+                GnoInterval::new(1, 6),
+                GnoInterval::new(10, 11),
+                GnoInterval::new(12, 16)
+            ]
+        );
+        assert_eq!(format_gtid_set(&sids), EXECUTED);
+
+        // whitespace, case, ordering and overlapping intervals
+        let sids = parse_gtid_set(
+            " \n4A1C2B4E-71CA-11E1-9E33-C80AA9429562 : 7 ,\r\n\
+            3e11fa47-71ca-11e1-9e33-c80aa9429562:12 - 15:1-3\t,\n\
+            3e11fa47-71ca-11e1-9e33-c80aa9429562:4-5:10:13\n",
+        )
+        .unwrap();
+        assert_eq!(format_gtid_set(&sids), EXECUTED);
+
+        assert!(parse_gtid_set("").unwrap().is_empty());
+        assert!(parse_gtid_set(" \n ").unwrap().is_empty());
+        assert_eq!(format_gtid_set(&[]), "");
+        assert!(parse_gtid_set("3e11fa47-71ca-11e1-9e33-c80aa9429562")
+            .unwrap()
+            .is_empty());
+
+        assert!(parse_gtid_set("foo:1").is_err());
+        assert!(parse_gtid_set("3e11fa47-71ca-11e1-9e33-c80aa9429562:0").is_err());
+        assert!(parse_gtid_set("3e11fa47-71ca-11e1-9e33-c80aa9429562:5-1").is_err());
+        assert!(parse_gtid_set("3e11fa47-71ca-11e1-9e33-c80aa9429562:1 2").is_err());
+        assert!(parse_gtid_set("3e11fa47-71ca-11e1-9e33-c80aa9429562:1:").is_err());
+
+        // empty SIDs are skipped
+        assert_eq!(
+            parse_gtid_set(",3e11fa47-71ca-11e1-9e33-c80aa9429562:1,, ,").unwrap(),
+            parse_gtid_set("3e11fa47-71ca-11e1-9e33-c80aa9429562:1").unwrap()
+        );
+        assert_eq!(
+            EXECUTED.parse::<GtidSet>().unwrap().sids(),
+            &parse_gtid_set(EXECUTED).unwrap()[..]
+        );
+    }
 }
@@ -0,0 +1,229 @@
+// Copyright (c) 2023 Anatoly Ikorsky
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+use std::{borrow::Cow, time::Duration};
+
+use bytes::BufMut;
+use uuid::Uuid;
+
+use crate::{constants::Command, misc::raw::Either, proto::MySerialize};
+
+use super::{binlog_request::BinlogRequest, ComBinlogDump, ComBinlogDumpGtid, ComRegisterSlave};
+
+/// A single step of the replication handshake (see [`ReplicationSetup`]).
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub enum ReplicationStep<'a> {
+    /// A text query to be sent using `COM_QUERY`.
+    Query(Cow<'a, str>),
+    /// `COM_REGISTER_SLAVE` command.
+    RegisterSlave(ComRegisterSlave<'a>),
+    /// `COM_BINLOG_DUMP` command.
+    BinlogDump(ComBinlogDump<'a>),
+    /// `COM_BINLOG_DUMP_GTID` command.
+    BinlogDumpGtid(ComBinlogDumpGtid<'a>),
+}
+
+impl ReplicationStep<'_> {
+    /// Returns `true` if this is the last step, i.e. the server will respond with
+    /// a binlog stream rather than an OK packet.
+    pub fn is_binlog_dump(&self) -> bool {
+        matches!(self, Self::BinlogDump(_) | Self::BinlogDumpGtid(_))
+    }
+}
+
+impl MySerialize for ReplicationStep<'_> {
+    /// Serializes the command packet payload for this step.
+    fn serialize(&self, buf: &mut Vec<u8>) {
+        match self {
+            Self::Query(query) => {
+                buf.put_u8(Command::COM_QUERY as u8);
+                buf.put_slice(query.as_bytes());
+            }
+            Self::RegisterSlave(cmd) => cmd.serialize(buf),
+            Self::BinlogDump(cmd) => cmd.serialize(buf),
+            Self::BinlogDumpGtid(cmd) => cmd.serialize(buf),
+        }
+    }
+}
+
+/// Replication handshake helper.
+///
+/// Emits, in order, the statements and commands a replica must send to start
+/// receiving a binlog stream:
+///
+/// 1. `SET @master_binlog_checksum = @@global.binlog_checksum` (unless disabled);
+/// 2. `SET @master_heartbeat_period = <ns>` (if heartbeat period is set);
+/// 3. `SET @slave_uuid = '<uuid>'` (if replica UUID is set);
+/// 4. `COM_REGISTER_SLAVE` (unless disabled);
+/// 5. `COM_BINLOG_DUMP` or `COM_BINLOG_DUMP_GTID` (see [`BinlogRequest`]).
+///
+/// Every step except the last one is expected to be answered with an OK packet.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct ReplicationSetup<'a> {
+    request: BinlogRequest<'a>,
+    checksum: bool,
+    heartbeat_period: Option<Duration>,
+    slave_uuid: Option<Uuid>,
+    register_slave: Option<ComRegisterSlave<'a>>,
+}
+
+impl<'a> ReplicationSetup<'a> {
+    /// Creates a new setup for the given binlog request.
+    ///
+    /// Checksum negotiation and slave registration are enabled by default.
+    pub fn new(request: BinlogRequest<'a>) -> Self {
+        Self {
+            register_slave: Some(ComRegisterSlave::new(request.server_id())),
+            request,
+            checksum: true,
+            heartbeat_period: None,
+            slave_uuid: None,
+        }
+    }
+
+    /// Returns the binlog request.
+    pub fn request(&self) -> &BinlogRequest<'a> {
+        &self.request
+    }
+
+    /// Whether the replica announces that it understands binlog checksums
+    /// (defaults to `true`).
+    pub fn checksum(&self) -> bool {
+        self.checksum
+    }
+
+    /// Heartbeat period requested from the master (defaults to `None`).
+    pub fn heartbeat_period(&self) -> Option<Duration> {
+        self.heartbeat_period
+    }
+
+    /// UUID of the replica reported to the master (defaults to `None`).
+    pub fn slave_uuid(&self) -> Option<Uuid> {
+        self.slave_uuid
+    }
+
+    /// `COM_REGISTER_SLAVE` command to send (defaults to the one with the request's server id).
+    pub fn register_slave(&self) -> Option<&ComRegisterSlave<'a>> {
+        self.register_slave.as_ref()
+    }
+
+    /// Returns modified `self` with the given value of the `checksum` field.
+    pub fn with_checksum(mut self, checksum: bool) -> Self {
+        self.checksum = checksum;
+        self
+    }
+
+    /// Returns modified `self` with the given value of the `heartbeat_period` field.
+    pub fn with_heartbeat_period(mut self, heartbeat_period: Option<Duration>) -> Self {
+        self.heartbeat_period = heartbeat_period;
+        self
+    }
+
+    /// Returns modified `self` with the given value of the `slave_uuid` field.
+    pub fn with_slave_uuid(mut self, slave_uuid: Option<Uuid>) -> Self {
+        self.slave_uuid = slave_uuid;
+        self
+    }
+
+    /// Returns modified `self` with the given value of the `register_slave` field.
+    ///
+    /// `None` means that the replica won't be registered.
+    pub fn with_register_slave(mut self, register_slave: Option<ComRegisterSlave<'a>>) -> Self {
+        self.register_slave = register_slave;
+        self
+    }
+
+    /// Returns handshake steps in the order they must be sent.
+    pub fn steps(&self) -> Vec<ReplicationStep<'_>> {
+        let mut steps = Vec::with_capacity(5);
+
+        if self.checksum {
+            steps.push(ReplicationStep::Query(Cow::Borrowed(
+                "SET @master_binlog_checksum = @@global.binlog_checksum",
+            )));
+        }
+
+        if let Some(period) = self.heartbeat_period {
+            steps.push(ReplicationStep::Query(Cow::Owned(format!(
+                "SET @master_heartbeat_period = {}",
+                period.as_nanos()
+            ))));
+        }
+
+        if let Some(uuid) = self.slave_uuid {
+            steps.push(ReplicationStep::Query(Cow::Owned(format!(
+                "SET @slave_uuid = '{}'",
+                uuid.hyphenated()
+            ))));
+        }
+
+        if let Some(ref cmd) = self.register_slave {
+            steps.push(ReplicationStep::RegisterSlave(cmd.clone()));
+        }
+
+        steps.push(match self.request.as_cmd() {
+            Either::Left(cmd) => ReplicationStep::BinlogDump(cmd),
+            Either::Right(cmd) => ReplicationStep::BinlogDumpGtid(cmd),
+        });
+
+        steps
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use uuid::Uuid;
+
+    use super::{ReplicationSetup, ReplicationStep};
+    use crate::{packets::binlog_request::BinlogRequest, proto::MySerialize};
+
+    #[test]
+    fn should_emit_steps_in_order() {
+        let uuid = Uuid::parse_str("3e11fa47-71ca-11e1-9e33-c80aa9429562").unwrap();
+        let setup = ReplicationSetup::new(BinlogRequest::new(42).with_use_gtid(true))
+            .with_heartbeat_period(Some(Duration::from_secs(30)))
+            .with_slave_uuid(Some(uuid));
+
+        let steps = setup.steps();
+        assert_eq!(steps.len(), 5);
+        assert_eq!(
+            steps[0],
+            ReplicationStep::Query("SET @master_binlog_checksum = @@global.binlog_checksum".into())
+        );
+        assert_eq!(
+            steps[1],
+            ReplicationStep::Query("SET @master_heartbeat_period = 30000000000".into())
+        );
+        assert_eq!(
+            steps[2],
+            ReplicationStep::Query(
+                "SET @slave_uuid = '3e11fa47-71ca-11e1-9e33-c80aa9429562'".into()
+            )
+        );
+        match steps[3] {
+            ReplicationStep::RegisterSlave(ref cmd) => assert_eq!(cmd.server_id(), 42),
+            ref other => panic!("unexpected step {:?}", other),
+        }
+        assert!(matches!(steps[4], ReplicationStep::BinlogDumpGtid(_)));
+        assert!(steps[4].is_binlog_dump());
+        assert!(!steps[0].is_binlog_dump());
+
+        let mut buf = Vec::new();
+        steps[1].serialize(&mut buf);
+        assert_eq!(buf, b"\x03SET @master_heartbeat_period = 30000000000");
+
+        let setup = ReplicationSetup::new(BinlogRequest::new(42))
+            .with_checksum(false)
+            .with_register_slave(None);
+        let steps = setup.steps();
+        assert_eq!(steps.len(), 1);
+        assert!(matches!(steps[0], ReplicationStep::BinlogDump(_)));
+    }
+}
@@ -4,6 +4,7 @@ use crate::{
     constants::SessionStateType,
     io::ParseBuf,
     misc::raw::{bytes::EofBytes, int::LenEnc, RawBytes},
+    packets::{parse_gtid_set, Sid},
     proto::{MyDeserialize, MySerialize},
 };
 
@@ -130,6 +131,25 @@ impl<'a> Gtids<'a> {
         self.0.as_str()
     }
 
+    /// Parses the GTID string into a normalized list of SIDs (see [`parse_gtid_set`]),
+    /// suitable for
+    /// [`BinlogRequest::with_sids`](crate::packets::binlog_request::BinlogRequest::with_sids).
+    pub fn sids(&self) -> io::Result<Vec<Sid<'static>>> {
+        let gtid_set = std::str::from_utf8(self.as_bytes())
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        parse_gtid_set(gtid_set)
+    }
+
+    /// Formats the given SIDs as a GTID string.
+    pub fn from_sids<'b>(sids: impl IntoIterator<Item = &'b Sid<'b>>) -> Gtids<'static> {
+        let gtid_set = sids
+            .into_iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join(",");
+        Gtids::new(gtid_set.into_bytes())
+    }
+
     /// Returns a `'static` version of self.
     pub fn into_owned(self) -> Gtids<'static> {
         Gtids(self.0.into_owned())
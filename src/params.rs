@@ -7,6 +7,7 @@
 // modified, or distributed except according to those terms.
 
 use std::{
+    borrow::Cow,
     collections::{
         hash_map::{Entry, Entry::Occupied},
         HashMap,
@@ -15,7 +16,15 @@ use std::{
     fmt,
 };
 
-use crate::value::{convert::ToValue, Value};
+#[cfg(feature = "serde_json")]
+use serde_json::{Map, Value as Json};
+
+#[cfg(feature = "serde_json")]
+use crate::value::json::json_to_value;
+use crate::{
+    named_params::{parse_named_params, positional_params, MixedParamsError},
+    value::{convert::ToValue, Value},
+};
 
 /// `FromValue` conversion error.
 #[derive(Debug, Eq, PartialEq, Clone)]
@@ -92,6 +101,142 @@ impl Params {
             params => Ok(params),
         }
     }
+
+    /// Creates named parameters from a JSON object keyed by parameter name.
+    ///
+    /// JSON scalars are converted to the corresponding `Value` variants (strings are passed
+    /// as is), while nested arrays and objects are passed as serialized JSON text.
+    #[cfg(feature = "serde_json")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "serde_json")))]
+    pub fn from_json(map: Map<String, Json>) -> Params {
+        if map.is_empty() {
+            return Params::Empty;
+        }
+
+        Params::Named(
+            map.into_iter()
+                .map(|(name, value)| (name.into_bytes(), json_to_value(value)))
+                .collect(),
+        )
+    }
+}
+
+/// Error returned while rendering a [`QueryWithParams`].
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum QueryWithParamsError {
+    #[error("Named and positional parameters mixed in one statement")]
+    MixedParams,
+    #[error(
+        "Missing named parameter `{}` for statement",
+        String::from_utf8_lossy(_0)
+    )]
+    MissingNamedParameter(Vec<u8>),
+    #[error("Named parameters given for a statement with positional parameters")]
+    NamedParamsForPositionalQuery,
+    #[error("Statement takes {expected} parameters but {actual} were given")]
+    WrongParamsCount { expected: usize, actual: usize },
+}
+
+impl From<MixedParamsError> for QueryWithParamsError {
+    fn from(_: MixedParamsError) -> Self {
+        Self::MixedParams
+    }
+}
+
+impl From<MissingNamedParameterError> for QueryWithParamsError {
+    fn from(err: MissingNamedParameterError) -> Self {
+        Self::MissingNamedParameter(err.0)
+    }
+}
+
+/// A statement together with its parameters.
+///
+/// It could be rendered either as a statement to prepare plus positional parameters
+/// (see [`QueryWithParams::to_prepared`]), or as a text query with parameters interpolated
+/// (see [`QueryWithParams::to_sql`]) for contexts where prepared statements are unavailable.
+/// Interpolated values are escaped using [`Value::as_sql`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct QueryWithParams<'a> {
+    query: Cow<'a, [u8]>,
+    params: Params,
+}
+
+impl<'a> QueryWithParams<'a> {
+    /// Creates new statement with the given parameters.
+    ///
+    /// Statement may either use named (`:name`) or positional (`?`) parameters.
+    pub fn new(query: impl Into<Cow<'a, [u8]>>, params: impl Into<Params>) -> Self {
+        Self {
+            query: query.into(),
+            params: params.into(),
+        }
+    }
+
+    /// Returns the statement as given.
+    pub fn query(&self) -> &[u8] {
+        &self.query
+    }
+
+    /// Returns parameters.
+    pub fn params(&self) -> &Params {
+        &self.params
+    }
+
+    /// Returns the statement to prepare (named parameters replaced with `?`)
+    /// and parameters in positional form.
+    pub fn to_prepared(&self) -> Result<(Cow<'_, [u8]>, Params), QueryWithParamsError> {
+        let (names, query) = parse_named_params(&self.query)?;
+        let params = match names {
+            Some(names) => match self.params {
+                Params::Named(_) => self.params.clone().into_positional(&names)?,
+                _ => {
+                    return Err(QueryWithParamsError::MissingNamedParameter(
+                        names.into_iter().next().unwrap_or_default(),
+                    ))
+                }
+            },
+            None => {
+                let expected = positional_params(&query).len();
+                let actual = match self.params {
+                    Params::Named(_) if expected > 0 => {
+                        return Err(QueryWithParamsError::NamedParamsForPositionalQuery)
+                    }
+                    Params::Positional(ref params) => params.len(),
+                    Params::Named(_) | Params::Empty => 0,
+                };
+                if expected != actual {
+                    return Err(QueryWithParamsError::WrongParamsCount { expected, actual });
+                }
+                if expected == 0 {
+                    Params::Empty
+                } else {
+                    self.params.clone()
+                }
+            }
+        };
+        Ok((query, params))
+    }
+
+    /// Returns the text query with all the parameters interpolated.
+    ///
+    /// `no_backslash_escape` should reflect the `NO_BACKSLASH_ESCAPES` SQL mode of the server.
+    pub fn to_sql(&self, no_backslash_escape: bool) -> Result<Vec<u8>, QueryWithParamsError> {
+        let (query, params) = self.to_prepared()?;
+        let values = match params {
+            Params::Positional(values) => values,
+            Params::Named(_) | Params::Empty => return Ok(query.into_owned()),
+        };
+
+        let mut output = Vec::with_capacity(query.len());
+        let mut last = 0;
+        for (offset, value) in positional_params(&query).into_iter().zip(values) {
+            output.extend_from_slice(&query[last..offset]);
+            output.extend_from_slice(value.as_sql(no_backslash_escape).as_bytes());
+            last = offset + 1;
+        }
+        output.extend_from_slice(&query[last..]);
+        Ok(output)
+    }
 }
 
 impl<'a, T: Into<Params> + Clone> From<&'a T> for Params {
@@ -240,3 +385,71 @@ into_params_impl!(
     [K, k],
     [L, l]
 );
+
+#[cfg(test)]
+mod tests {
+    use super::{Params, QueryWithParams, QueryWithParamsError};
+    use crate::value::Value;
+
+    #[test]
+    fn should_render_query_with_params() {
+        let query = QueryWithParams::new(
+            &b"SELECT :a, '?:b', :b, :a -- ?\n"[..],
+            params! { "a" => "it's", "b" => 42 },
+        );
+        let (stmt, params) = query.to_prepared().unwrap();
+        assert_eq!(&*stmt, &b"SELECT ?, '?:b', ?, ? -- ?\n"[..]);
+        assert_eq!(
+            params,
+            Params::Positional(vec!["it's".into(), 42.into(), "it's".into()])
+        );
+        assert_eq!(
+            query.to_sql(false).unwrap(),
+            b"SELECT 'it\\'s', '?:b', 42, 'it\\'s' -- ?\n"
+        );
+        assert_eq!(
+            query.to_sql(true).unwrap(),
+            b"SELECT 'it''s', '?:b', 42, 'it''s' -- ?\n"
+        );
+
+        let query = QueryWithParams::new(&b"SELECT ?, \"?\", ?"[..], (Value::NULL, "x\n"));
+        assert_eq!(query.to_sql(false).unwrap(), b"SELECT NULL, \"?\", 'x\\n'");
+        assert_eq!(query.to_prepared().unwrap().0, &b"SELECT ?, \"?\", ?"[..]);
+
+        let query = QueryWithParams::new(&b"SELECT 1"[..], ());
+        assert_eq!(query.to_sql(false).unwrap(), b"SELECT 1");
+        assert_eq!(query.to_prepared().unwrap().1, Params::Empty);
+    }
+
+    #[test]
+    fn should_reject_wrong_params() {
+        let err = |query: &'static [u8], params: Params| {
+            QueryWithParams::new(query, params)
+                .to_sql(false)
+                .unwrap_err()
+        };
+        assert_eq!(
+            err(b"SELECT :a, ?", params! { "a" => 1 }),
+            QueryWithParamsError::MixedParams
+        );
+        assert_eq!(
+            err(b"SELECT :a, :b", params! { "a" => 1 }),
+            QueryWithParamsError::MissingNamedParameter(b"b".to_vec())
+        );
+        assert_eq!(
+            err(b"SELECT :a", (1,).into()),
+            QueryWithParamsError::MissingNamedParameter(b"a".to_vec())
+        );
+        assert_eq!(
+            err(b"SELECT ?", params! { "a" => 1 }),
+            QueryWithParamsError::NamedParamsForPositionalQuery
+        );
+        assert_eq!(
+            err(b"SELECT ?, ?", (1,).into()),
+            QueryWithParamsError::WrongParamsCount {
+                expected: 2,
+                actual: 1
+            }
+        );
+    }
+}
@@ -10,7 +10,6 @@
 
 pub use flate2::Compression;
 
-use byteorder::{ByteOrder, LittleEndian};
 use bytes::{Buf, BufMut, BytesMut};
 use flate2::read::{ZlibDecoder, ZlibEncoder};
 
@@ -23,7 +22,10 @@ use std::{
 };
 
 use self::error::PacketCodecError;
-use crate::constants::{DEFAULT_MAX_ALLOWED_PACKET, MAX_PAYLOAD_LEN, MIN_COMPRESS_LENGTH};
+use crate::{
+    constants::{DEFAULT_MAX_ALLOWED_PACKET, MAX_PAYLOAD_LEN, MIN_COMPRESS_LENGTH},
+    io::{BufMutExt, ParseBuf},
+};
 
 pub mod error;
 
@@ -90,15 +92,15 @@ pub fn compress(
                     }
                 }
 
-                dst.put_uint_le(read as u64, 3);
+                dst.put_u24_le(read as u32);
                 dst.put_u8(seq_id);
-                dst.put_uint_le(chunk.len() as u64, 3);
+                dst.put_u24_le(chunk.len() as u32);
                 dst.advance_mut(read);
             }
         } else {
-            dst.put_uint_le(chunk.len() as u64, 3);
+            dst.put_u24_le(chunk.len() as u32);
             dst.put_u8(seq_id);
-            dst.put_uint_le(0, 3);
+            dst.put_u24_le(0);
             dst.put_slice(chunk);
         }
 
@@ -171,7 +173,7 @@ impl ChunkDecoder {
                     // We need at least 4 bytes to read chunk length and sequence id.
                     Ok(None)
                 } else {
-                    let raw_chunk_len = LittleEndian::read_u24(&*src) as usize;
+                    let raw_chunk_len = ParseBuf(&src[..]).eat_u24_le() as usize;
                     let seq_id = src[3];
 
                     match NonZeroUsize::new(raw_chunk_len) {
@@ -300,9 +302,9 @@ impl CompDecoder {
                     // We need at least 7 bytes to read compressed packet header.
                     Ok(None)
                 } else {
-                    let compressed_len = LittleEndian::read_u24(&*src) as usize;
+                    let compressed_len = ParseBuf(&src[..]).eat_u24_le() as usize;
                     let seq_id = src[3];
-                    let uncompressed_len = LittleEndian::read_u24(&src[4..]) as usize;
+                    let uncompressed_len = ParseBuf(&src[4..]).eat_u24_le() as usize;
 
                     match CompData::new(compressed_len, uncompressed_len, max_allowed_packet)? {
                         Some(needed) => {
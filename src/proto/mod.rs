@@ -10,7 +10,11 @@ use std::io;
 
 use crate::io::ParseBuf;
 
+#[cfg(feature = "client-protocol")]
+#[cfg_attr(docsrs, doc(cfg(feature = "client-protocol")))]
 pub mod codec;
+#[cfg(feature = "client-protocol")]
+#[cfg_attr(docsrs, doc(cfg(feature = "client-protocol")))]
 pub mod sync_framed;
 
 /// Text protocol marker.
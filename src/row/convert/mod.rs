@@ -48,7 +48,7 @@ pub fn from_row_opt<T: FromRow>(row: Row) -> Result<T, FromRowError> {
     FromRow::from_row_opt(row)
 }
 
-/// Trait to convert `Row` into a tuple of `FromValue` implementors up to arity 12.
+/// Trait to convert `Row` into a tuple of `FromValue` implementors up to arity 24.
 ///
 /// This trait is convenient way to convert mysql row to a tuple or rust types and relies on
 /// `FromValue` trait, i.e. calling `from_row::<(T, U)>(row)` is similar to calling
@@ -165,679 +165,141 @@ where
     }
 }
 
-impl<T1, T2> FromRow for (T1, T2)
-where
-    T1: FromValue,
-    T2: FromValue,
-    T1::Intermediate: Into<Value>,
-{
-    fn from_row_opt(mut row: Row) -> Result<(T1, T2), FromRowError> {
-        if row.len() != 2 {
-            return Err(FromRowError(row));
-        }
-        let ir1 = take_or_place!(row, 0, T1);
-        let ir2 = take_or_place!(row, 1, T2, [0, ir1]);
-        Ok((Into::<T1>::into(ir1), Into::<T2>::into(ir2)))
-    }
-}
-
-impl<T1, T2, T3> FromRow for (T1, T2, T3)
-where
-    T1: FromValue,
-    T2: FromValue,
-    T3: FromValue,
-    T1::Intermediate: Into<Value>,
-    T2::Intermediate: Into<Value>,
-{
-    fn from_row_opt(mut row: Row) -> Result<(T1, T2, T3), FromRowError> {
-        if row.len() != 3 {
-            return Err(FromRowError(row));
-        }
-        let ir1 = take_or_place!(row, 0, T1);
-        let ir2 = take_or_place!(row, 1, T2, [0, ir1]);
-        let ir3 = take_or_place!(row, 2, T3, [0, ir1], [1, ir2]);
-        Ok((Into::<T1>::into(ir1), Into::<T2>::into(ir2), ir3.into()))
-    }
-}
-
-impl<T1, T2, T3, T4> FromRow for (T1, T2, T3, T4)
-where
-    T1: FromValue,
-    T2: FromValue,
-    T3: FromValue,
-    T4: FromValue,
-    T1::Intermediate: Into<Value>,
-    T2::Intermediate: Into<Value>,
-    T3::Intermediate: Into<Value>,
-{
-    fn from_row_opt(mut row: Row) -> Result<(T1, T2, T3, T4), FromRowError> {
-        if row.len() != 4 {
-            return Err(FromRowError(row));
-        }
-        let ir1 = take_or_place!(row, 0, T1);
-        let ir2 = take_or_place!(row, 1, T2, [0, ir1]);
-        let ir3 = take_or_place!(row, 2, T3, [0, ir1], [1, ir2]);
-        let ir4 = take_or_place!(row, 3, T4, [0, ir1], [1, ir2], [2, ir3]);
-        Ok((
-            Into::<T1>::into(ir1),
-            Into::<T2>::into(ir2),
-            Into::<T3>::into(ir3),
-            ir4.into(),
-        ))
-    }
-}
-
-impl<T1, T2, T3, T4, T5> FromRow for (T1, T2, T3, T4, T5)
-where
-    T1: FromValue,
-    T2: FromValue,
-    T3: FromValue,
-    T4: FromValue,
-    T5: FromValue,
-    T1::Intermediate: Into<Value>,
-    T2::Intermediate: Into<Value>,
-    T3::Intermediate: Into<Value>,
-    T4::Intermediate: Into<Value>,
-{
-    fn from_row_opt(mut row: Row) -> Result<(T1, T2, T3, T4, T5), FromRowError> {
-        if row.len() != 5 {
-            return Err(FromRowError(row));
-        }
-        let ir1 = take_or_place!(row, 0, T1);
-        let ir2 = take_or_place!(row, 1, T2, [0, ir1]);
-        let ir3 = take_or_place!(row, 2, T3, [0, ir1], [1, ir2]);
-        let ir4 = take_or_place!(row, 3, T4, [0, ir1], [1, ir2], [2, ir3]);
-        let ir5 = take_or_place!(row, 4, T5, [0, ir1], [1, ir2], [2, ir3], [3, ir4]);
-        Ok((
-            Into::<T1>::into(ir1),
-            Into::<T2>::into(ir2),
-            Into::<T3>::into(ir3),
-            Into::<T4>::into(ir4),
-            ir5.into(),
-        ))
-    }
-}
-
-impl<T1, T2, T3, T4, T5, T6> FromRow for (T1, T2, T3, T4, T5, T6)
-where
-    T1: FromValue,
-    T2: FromValue,
-    T3: FromValue,
-    T4: FromValue,
-    T5: FromValue,
-    T6: FromValue,
-    T1::Intermediate: Into<Value>,
-    T2::Intermediate: Into<Value>,
-    T3::Intermediate: Into<Value>,
-    T4::Intermediate: Into<Value>,
-    T5::Intermediate: Into<Value>,
-{
-    fn from_row_opt(mut row: Row) -> Result<(T1, T2, T3, T4, T5, T6), FromRowError> {
-        if row.len() != 6 {
-            return Err(FromRowError(row));
-        }
-        let ir1 = take_or_place!(row, 0, T1);
-        let ir2 = take_or_place!(row, 1, T2, [0, ir1]);
-        let ir3 = take_or_place!(row, 2, T3, [0, ir1], [1, ir2]);
-        let ir4 = take_or_place!(row, 3, T4, [0, ir1], [1, ir2], [2, ir3]);
-        let ir5 = take_or_place!(row, 4, T5, [0, ir1], [1, ir2], [2, ir3], [3, ir4]);
-        let ir6 = take_or_place!(row, 5, T6, [0, ir1], [1, ir2], [2, ir3], [3, ir4], [4, ir5]);
-        Ok((
-            Into::<T1>::into(ir1),
-            Into::<T2>::into(ir2),
-            Into::<T3>::into(ir3),
-            Into::<T4>::into(ir4),
-            Into::<T5>::into(ir5),
-            ir6.into(),
-        ))
-    }
+/// Takes intermediates of tuple elements one by one rolling back already taken ones
+/// if some element could not be converted.
+macro_rules! take_all_or_place {
+    ($row:ident; [$($done_idx:tt $done_ir:ident),*]; $idx:tt $t:ident $ir:ident, $($rest:tt)*) => (
+        let $ir = take_or_place!($row, $idx, $t $(, [$done_idx, $done_ir])*);
+        take_all_or_place!($row; [$($done_idx $done_ir,)* $idx $ir]; $($rest)*);
+    );
+    ($row:ident; [$($done_idx:tt $done_ir:ident),*];) => ();
 }
 
-impl<T1, T2, T3, T4, T5, T6, T7> FromRow for (T1, T2, T3, T4, T5, T6, T7)
-where
-    T1: FromValue,
-    T2: FromValue,
-    T3: FromValue,
-    T4: FromValue,
-    T5: FromValue,
-    T6: FromValue,
-    T7: FromValue,
-    T1::Intermediate: Into<Value>,
-    T2::Intermediate: Into<Value>,
-    T3::Intermediate: Into<Value>,
-    T4::Intermediate: Into<Value>,
-    T5::Intermediate: Into<Value>,
-    T6::Intermediate: Into<Value>,
-{
-    fn from_row_opt(mut row: Row) -> Result<(T1, T2, T3, T4, T5, T6, T7), FromRowError> {
-        if row.len() != 7 {
-            return Err(FromRowError(row));
+/// Implements `FromRow` for a tuple. Intermediates of all but the last element
+/// must be convertible back to `Value` to be able to roll back a failed conversion.
+macro_rules! from_row_tuple_impl {
+    ($len:expr; $([$idx:tt, $t:ident, $ir:ident]),+; [$last_idx:tt, $last_t:ident, $last_ir:ident]) => (
+        impl<$($t,)+ $last_t> FromRow for ($($t,)+ $last_t)
+        where
+            $($t: FromValue,)+
+            $last_t: FromValue,
+            $($t::Intermediate: Into<Value>,)+
+        {
+            fn from_row_opt(mut row: Row) -> Result<($($t,)+ $last_t), FromRowError> {
+                if row.len() != $len {
+                    return Err(FromRowError(row));
+                }
+                take_all_or_place!(row; []; $($idx $t $ir,)+ $last_idx $last_t $last_ir,);
+                Ok(($(Into::<$t>::into($ir),)+ Into::<$last_t>::into($last_ir)))
+            }
         }
-        let ir1 = take_or_place!(row, 0, T1);
-        let ir2 = take_or_place!(row, 1, T2, [0, ir1]);
-        let ir3 = take_or_place!(row, 2, T3, [0, ir1], [1, ir2]);
-        let ir4 = take_or_place!(row, 3, T4, [0, ir1], [1, ir2], [2, ir3]);
-        let ir5 = take_or_place!(row, 4, T5, [0, ir1], [1, ir2], [2, ir3], [3, ir4]);
-        let ir6 = take_or_place!(row, 5, T6, [0, ir1], [1, ir2], [2, ir3], [3, ir4], [4, ir5]);
-        let ir7 = take_or_place!(
-            row,
-            6,
-            T7,
-            [0, ir1],
-            [1, ir2],
-            [2, ir3],
-            [3, ir4],
-            [4, ir5],
-            [5, ir6]
-        );
-        Ok((
-            Into::<T1>::into(ir1),
-            Into::<T2>::into(ir2),
-            Into::<T3>::into(ir3),
-            Into::<T4>::into(ir4),
-            Into::<T5>::into(ir5),
-            Into::<T6>::into(ir6),
-            ir7.into(),
-        ))
-    }
+    );
 }
 
-impl<T1, T2, T3, T4, T5, T6, T7, T8> FromRow for (T1, T2, T3, T4, T5, T6, T7, T8)
-where
-    T1: FromValue,
-    T2: FromValue,
-    T3: FromValue,
-    T4: FromValue,
-    T5: FromValue,
-    T6: FromValue,
-    T7: FromValue,
-    T8: FromValue,
-    T1::Intermediate: Into<Value>,
-    T2::Intermediate: Into<Value>,
-    T3::Intermediate: Into<Value>,
-    T4::Intermediate: Into<Value>,
-    T5::Intermediate: Into<Value>,
-    T6::Intermediate: Into<Value>,
-    T7::Intermediate: Into<Value>,
-{
-    fn from_row_opt(mut row: Row) -> Result<(T1, T2, T3, T4, T5, T6, T7, T8), FromRowError> {
-        if row.len() != 8 {
-            return Err(FromRowError(row));
-        }
-        let ir1 = take_or_place!(row, 0, T1);
-        let ir2 = take_or_place!(row, 1, T2, [0, ir1]);
-        let ir3 = take_or_place!(row, 2, T3, [0, ir1], [1, ir2]);
-        let ir4 = take_or_place!(row, 3, T4, [0, ir1], [1, ir2], [2, ir3]);
-        let ir5 = take_or_place!(row, 4, T5, [0, ir1], [1, ir2], [2, ir3], [3, ir4]);
-        let ir6 = take_or_place!(row, 5, T6, [0, ir1], [1, ir2], [2, ir3], [3, ir4], [4, ir5]);
-        let ir7 = take_or_place!(
-            row,
-            6,
-            T7,
-            [0, ir1],
-            [1, ir2],
-            [2, ir3],
-            [3, ir4],
-            [4, ir5],
-            [5, ir6]
-        );
-        let ir8 = take_or_place!(
-            row,
-            7,
-            T8,
-            [0, ir1],
-            [1, ir2],
-            [2, ir3],
-            [3, ir4],
-            [4, ir5],
-            [5, ir6],
-            [6, ir7]
-        );
-        Ok((
-            Into::<T1>::into(ir1),
-            Into::<T2>::into(ir2),
-            Into::<T3>::into(ir3),
-            Into::<T4>::into(ir4),
-            Into::<T5>::into(ir5),
-            Into::<T6>::into(ir6),
-            Into::<T7>::into(ir7),
-            ir8.into(),
-        ))
-    }
-}
+from_row_tuple_impl!(2; [0, T1, ir1]; [1, T2, ir2]);
+from_row_tuple_impl!(3; [0, T1, ir1], [1, T2, ir2]; [2, T3, ir3]);
+from_row_tuple_impl!(4; [0, T1, ir1], [1, T2, ir2], [2, T3, ir3]; [3, T4, ir4]);
+from_row_tuple_impl!(5; [0, T1, ir1], [1, T2, ir2], [2, T3, ir3], [3, T4, ir4]; [4, T5, ir5]);
+from_row_tuple_impl!(6; [0, T1, ir1], [1, T2, ir2], [2, T3, ir3], [3, T4, ir4], [4, T5, ir5]; [5, T6, ir6]);
+from_row_tuple_impl!(7; [0, T1, ir1], [1, T2, ir2], [2, T3, ir3], [3, T4, ir4], [4, T5, ir5], [5, T6, ir6]; [6, T7, ir7]);
+from_row_tuple_impl!(8; [0, T1, ir1], [1, T2, ir2], [2, T3, ir3], [3, T4, ir4], [4, T5, ir5], [5, T6, ir6], [6, T7, ir7]; [7, T8, ir8]);
+from_row_tuple_impl!(9; [0, T1, ir1], [1, T2, ir2], [2, T3, ir3], [3, T4, ir4], [4, T5, ir5], [5, T6, ir6], [6, T7, ir7], [7, T8, ir8]; [8, T9, ir9]);
+from_row_tuple_impl!(10; [0, T1, ir1], [1, T2, ir2], [2, T3, ir3], [3, T4, ir4], [4, T5, ir5], [5, T6, ir6], [6, T7, ir7], [7, T8, ir8], [8, T9, ir9]; [9, T10, ir10]);
+from_row_tuple_impl!(11; [0, T1, ir1], [1, T2, ir2], [2, T3, ir3], [3, T4, ir4], [4, T5, ir5], [5, T6, ir6], [6, T7, ir7], [7, T8, ir8], [8, T9, ir9], [9, T10, ir10]; [10, T11, ir11]);
+from_row_tuple_impl!(12; [0, T1, ir1], [1, T2, ir2], [2, T3, ir3], [3, T4, ir4], [4, T5, ir5], [5, T6, ir6], [6, T7, ir7], [7, T8, ir8], [8, T9, ir9], [9, T10, ir10], [10, T11, ir11]; [11, T12, ir12]);
+from_row_tuple_impl!(13; [0, T1, ir1], [1, T2, ir2], [2, T3, ir3], [3, T4, ir4], [4, T5, ir5], [5, T6, ir6], [6, T7, ir7], [7, T8, ir8], [8, T9, ir9], [9, T10, ir10], [10, T11, ir11], [11, T12, ir12]; [12, T13, ir13]);
+from_row_tuple_impl!(14; [0, T1, ir1], [1, T2, ir2], [2, T3, ir3], [3, T4, ir4], [4, T5, ir5], [5, T6, ir6], [6, T7, ir7], [7, T8, ir8], [8, T9, ir9], [9, T10, ir10], [10, T11, ir11], [11, T12, ir12], [12, T13, ir13]; [13, T14, ir14]);
+from_row_tuple_impl!(15; [0, T1, ir1], [1, T2, ir2], [2, T3, ir3], [3, T4, ir4], [4, T5, ir5], [5, T6, ir6], [6, T7, ir7], [7, T8, ir8], [8, T9, ir9], [9, T10, ir10], [10, T11, ir11], [11, T12, ir12], [12, T13, ir13], [13, T14, ir14]; [14, T15, ir15]);
+from_row_tuple_impl!(16; [0, T1, ir1], [1, T2, ir2], [2, T3, ir3], [3, T4, ir4], [4, T5, ir5], [5, T6, ir6], [6, T7, ir7], [7, T8, ir8], [8, T9, ir9], [9, T10, ir10], [10, T11, ir11], [11, T12, ir12], [12, T13, ir13], [13, T14, ir14], [14, T15, ir15]; [15, T16, ir16]);
+from_row_tuple_impl!(17; [0, T1, ir1], [1, T2, ir2], [2, T3, ir3], [3, T4, ir4], [4, T5, ir5], [5, T6, ir6], [6, T7, ir7], [7, T8, ir8], [8, T9, ir9], [9, T10, ir10], [10, T11, ir11], [11, T12, ir12], [12, T13, ir13], [13, T14, ir14], [14, T15, ir15], [15, T16, ir16]; [16, T17, ir17]);
+from_row_tuple_impl!(18; [0, T1, ir1], [1, T2, ir2], [2, T3, ir3], [3, T4, ir4], [4, T5, ir5], [5, T6, ir6], [6, T7, ir7], [7, T8, ir8], [8, T9, ir9], [9, T10, ir10], [10, T11, ir11], [11, T12, ir12], [12, T13, ir13], [13, T14, ir14], [14, T15, ir15], [15, T16, ir16], [16, T17, ir17]; [17, T18, ir18]);
+from_row_tuple_impl!(19; [0, T1, ir1], [1, T2, ir2], [2, T3, ir3], [3, T4, ir4], [4, T5, ir5], [5, T6, ir6], [6, T7, ir7], [7, T8, ir8], [8, T9, ir9], [9, T10, ir10], [10, T11, ir11], [11, T12, ir12], [12, T13, ir13], [13, T14, ir14], [14, T15, ir15], [15, T16, ir16], [16, T17, ir17], [17, T18, ir18]; [18, T19, ir19]);
+from_row_tuple_impl!(20; [0, T1, ir1], [1, T2, ir2], [2, T3, ir3], [3, T4, ir4], [4, T5, ir5], [5, T6, ir6], [6, T7, ir7], [7, T8, ir8], [8, T9, ir9], [9, T10, ir10], [10, T11, ir11], [11, T12, ir12], [12, T13, ir13], [13, T14, ir14], [14, T15, ir15], [15, T16, ir16], [16, T17, ir17], [17, T18, ir18], [18, T19, ir19]; [19, T20, ir20]);
+from_row_tuple_impl!(21; [0, T1, ir1], [1, T2, ir2], [2, T3, ir3], [3, T4, ir4], [4, T5, ir5], [5, T6, ir6], [6, T7, ir7], [7, T8, ir8], [8, T9, ir9], [9, T10, ir10], [10, T11, ir11], [11, T12, ir12], [12, T13, ir13], [13, T14, ir14], [14, T15, ir15], [15, T16, ir16], [16, T17, ir17], [17, T18, ir18], [18, T19, ir19], [19, T20, ir20]; [20, T21, ir21]);
+from_row_tuple_impl!(22; [0, T1, ir1], [1, T2, ir2], [2, T3, ir3], [3, T4, ir4], [4, T5, ir5], [5, T6, ir6], [6, T7, ir7], [7, T8, ir8], [8, T9, ir9], [9, T10, ir10], [10, T11, ir11], [11, T12, ir12], [12, T13, ir13], [13, T14, ir14], [14, T15, ir15], [15, T16, ir16], [16, T17, ir17], [17, T18, ir18], [18, T19, ir19], [19, T20, ir20], [20, T21, ir21]; [21, T22, ir22]);
+from_row_tuple_impl!(23; [0, T1, ir1], [1, T2, ir2], [2, T3, ir3], [3, T4, ir4], [4, T5, ir5], [5, T6, ir6], [6, T7, ir7], [7, T8, ir8], [8, T9, ir9], [9, T10, ir10], [10, T11, ir11], [11, T12, ir12], [12, T13, ir13], [13, T14, ir14], [14, T15, ir15], [15, T16, ir16], [16, T17, ir17], [17, T18, ir18], [18, T19, ir19], [19, T20, ir20], [20, T21, ir21], [21, T22, ir22]; [22, T23, ir23]);
+from_row_tuple_impl!(24; [0, T1, ir1], [1, T2, ir2], [2, T3, ir3], [3, T4, ir4], [4, T5, ir5], [5, T6, ir6], [6, T7, ir7], [7, T8, ir8], [8, T9, ir9], [9, T10, ir10], [10, T11, ir11], [11, T12, ir12], [12, T13, ir13], [13, T14, ir14], [14, T15, ir15], [15, T16, ir16], [16, T17, ir17], [17, T18, ir18], [18, T19, ir19], [19, T20, ir20], [20, T21, ir21], [21, T22, ir22], [22, T23, ir23]; [23, T24, ir24]);
 
-impl<T1, T2, T3, T4, T5, T6, T7, T8, T9> FromRow for (T1, T2, T3, T4, T5, T6, T7, T8, T9)
-where
-    T1: FromValue,
-    T2: FromValue,
-    T3: FromValue,
-    T4: FromValue,
-    T5: FromValue,
-    T6: FromValue,
-    T7: FromValue,
-    T8: FromValue,
-    T9: FromValue,
-    T1::Intermediate: Into<Value>,
-    T2::Intermediate: Into<Value>,
-    T3::Intermediate: Into<Value>,
-    T4::Intermediate: Into<Value>,
-    T5::Intermediate: Into<Value>,
-    T6::Intermediate: Into<Value>,
-    T7::Intermediate: Into<Value>,
-    T8::Intermediate: Into<Value>,
-{
-    fn from_row_opt(mut row: Row) -> Result<(T1, T2, T3, T4, T5, T6, T7, T8, T9), FromRowError> {
-        if row.len() != 9 {
-            return Err(FromRowError(row));
-        }
-        let ir1 = take_or_place!(row, 0, T1);
-        let ir2 = take_or_place!(row, 1, T2, [0, ir1]);
-        let ir3 = take_or_place!(row, 2, T3, [0, ir1], [1, ir2]);
-        let ir4 = take_or_place!(row, 3, T4, [0, ir1], [1, ir2], [2, ir3]);
-        let ir5 = take_or_place!(row, 4, T5, [0, ir1], [1, ir2], [2, ir3], [3, ir4]);
-        let ir6 = take_or_place!(row, 5, T6, [0, ir1], [1, ir2], [2, ir3], [3, ir4], [4, ir5]);
-        let ir7 = take_or_place!(
-            row,
-            6,
-            T7,
-            [0, ir1],
-            [1, ir2],
-            [2, ir3],
-            [3, ir4],
-            [4, ir5],
-            [5, ir6]
-        );
-        let ir8 = take_or_place!(
-            row,
-            7,
-            T8,
-            [0, ir1],
-            [1, ir2],
-            [2, ir3],
-            [3, ir4],
-            [4, ir5],
-            [5, ir6],
-            [6, ir7]
-        );
-        let ir9 = take_or_place!(
-            row,
-            8,
-            T9,
-            [0, ir1],
-            [1, ir2],
-            [2, ir3],
-            [3, ir4],
-            [4, ir5],
-            [5, ir6],
-            [6, ir7],
-            [7, ir8]
-        );
-        Ok((
-            Into::<T1>::into(ir1),
-            Into::<T2>::into(ir2),
-            Into::<T3>::into(ir3),
-            Into::<T4>::into(ir4),
-            Into::<T5>::into(ir5),
-            Into::<T6>::into(ir6),
-            Into::<T7>::into(ir7),
-            Into::<T8>::into(ir8),
-            ir9.into(),
-        ))
-    }
-}
+#[test]
+fn tuple_from_row() {
+    use crate::{
+        constants::ColumnType::MYSQL_TYPE_LONG,
+        packets::Column,
+        row::{convert::from_row_opt, new_row},
+        value::Value::*,
+    };
 
-impl<T1, T2, T3, T4, T5, T6, T7, T8, T9, T10> FromRow for (T1, T2, T3, T4, T5, T6, T7, T8, T9, T10)
-where
-    T1: FromValue,
-    T2: FromValue,
-    T3: FromValue,
-    T4: FromValue,
-    T5: FromValue,
-    T6: FromValue,
-    T7: FromValue,
-    T8: FromValue,
-    T9: FromValue,
-    T10: FromValue,
-    T1::Intermediate: Into<Value>,
-    T2::Intermediate: Into<Value>,
-    T3::Intermediate: Into<Value>,
-    T4::Intermediate: Into<Value>,
-    T5::Intermediate: Into<Value>,
-    T6::Intermediate: Into<Value>,
-    T7::Intermediate: Into<Value>,
-    T8::Intermediate: Into<Value>,
-    T9::Intermediate: Into<Value>,
-{
-    fn from_row_opt(
-        mut row: Row,
-    ) -> Result<(T1, T2, T3, T4, T5, T6, T7, T8, T9, T10), FromRowError> {
-        if row.len() != 10 {
-            return Err(FromRowError(row));
-        }
-        let ir1 = take_or_place!(row, 0, T1);
-        let ir2 = take_or_place!(row, 1, T2, [0, ir1]);
-        let ir3 = take_or_place!(row, 2, T3, [0, ir1], [1, ir2]);
-        let ir4 = take_or_place!(row, 3, T4, [0, ir1], [1, ir2], [2, ir3]);
-        let ir5 = take_or_place!(row, 4, T5, [0, ir1], [1, ir2], [2, ir3], [3, ir4]);
-        let ir6 = take_or_place!(row, 5, T6, [0, ir1], [1, ir2], [2, ir3], [3, ir4], [4, ir5]);
-        let ir7 = take_or_place!(
-            row,
-            6,
-            T7,
-            [0, ir1],
-            [1, ir2],
-            [2, ir3],
-            [3, ir4],
-            [4, ir5],
-            [5, ir6]
-        );
-        let ir8 = take_or_place!(
-            row,
-            7,
-            T8,
-            [0, ir1],
-            [1, ir2],
-            [2, ir3],
-            [3, ir4],
-            [4, ir5],
-            [5, ir6],
-            [6, ir7]
-        );
-        let ir9 = take_or_place!(
-            row,
-            8,
-            T9,
-            [0, ir1],
-            [1, ir2],
-            [2, ir3],
-            [3, ir4],
-            [4, ir5],
-            [5, ir6],
-            [6, ir7],
-            [7, ir8]
-        );
-        let ir10 = take_or_place!(
-            row,
-            9,
-            T10,
-            [0, ir1],
-            [1, ir2],
-            [2, ir3],
-            [3, ir4],
-            [4, ir5],
-            [5, ir6],
-            [6, ir7],
-            [7, ir8],
-            [8, ir9]
-        );
-        Ok((
-            Into::<T1>::into(ir1),
-            Into::<T2>::into(ir2),
-            Into::<T3>::into(ir3),
-            Into::<T4>::into(ir4),
-            Into::<T5>::into(ir5),
-            Into::<T6>::into(ir6),
-            Into::<T7>::into(ir7),
-            Into::<T8>::into(ir8),
-            Into::<T9>::into(ir9),
-            ir10.into(),
-        ))
-    }
-}
+    let row = new_row(
+        (0..24).map(Int).collect(),
+        vec![Column::new(MYSQL_TYPE_LONG); 24].into(),
+    );
 
-impl<T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11> FromRow
-    for (T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11)
-where
-    T1: FromValue,
-    T2: FromValue,
-    T3: FromValue,
-    T4: FromValue,
-    T5: FromValue,
-    T6: FromValue,
-    T7: FromValue,
-    T8: FromValue,
-    T9: FromValue,
-    T10: FromValue,
-    T11: FromValue,
-    T1::Intermediate: Into<Value>,
-    T2::Intermediate: Into<Value>,
-    T3::Intermediate: Into<Value>,
-    T4::Intermediate: Into<Value>,
-    T5::Intermediate: Into<Value>,
-    T6::Intermediate: Into<Value>,
-    T7::Intermediate: Into<Value>,
-    T8::Intermediate: Into<Value>,
-    T9::Intermediate: Into<Value>,
-    T10::Intermediate: Into<Value>,
-{
-    fn from_row_opt(
-        mut row: Row,
-    ) -> Result<(T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11), FromRowError> {
-        if row.len() != 11 {
-            return Err(FromRowError(row));
-        }
-        let ir1 = take_or_place!(row, 0, T1);
-        let ir2 = take_or_place!(row, 1, T2, [0, ir1]);
-        let ir3 = take_or_place!(row, 2, T3, [0, ir1], [1, ir2]);
-        let ir4 = take_or_place!(row, 3, T4, [0, ir1], [1, ir2], [2, ir3]);
-        let ir5 = take_or_place!(row, 4, T5, [0, ir1], [1, ir2], [2, ir3], [3, ir4]);
-        let ir6 = take_or_place!(row, 5, T6, [0, ir1], [1, ir2], [2, ir3], [3, ir4], [4, ir5]);
-        let ir7 = take_or_place!(
-            row,
-            6,
-            T7,
-            [0, ir1],
-            [1, ir2],
-            [2, ir3],
-            [3, ir4],
-            [4, ir5],
-            [5, ir6]
-        );
-        let ir8 = take_or_place!(
-            row,
-            7,
-            T8,
-            [0, ir1],
-            [1, ir2],
-            [2, ir3],
-            [3, ir4],
-            [4, ir5],
-            [5, ir6],
-            [6, ir7]
-        );
-        let ir9 = take_or_place!(
-            row,
-            8,
-            T9,
-            [0, ir1],
-            [1, ir2],
-            [2, ir3],
-            [3, ir4],
-            [4, ir5],
-            [5, ir6],
-            [6, ir7],
-            [7, ir8]
-        );
-        let ir10 = take_or_place!(
-            row,
-            9,
-            T10,
-            [0, ir1],
-            [1, ir2],
-            [2, ir3],
-            [3, ir4],
-            [4, ir5],
-            [5, ir6],
-            [6, ir7],
-            [7, ir8],
-            [8, ir9]
-        );
-        let ir11 = take_or_place!(
-            row,
-            10,
-            T11,
-            [0, ir1],
-            [1, ir2],
-            [2, ir3],
-            [3, ir4],
-            [4, ir5],
-            [5, ir6],
-            [6, ir7],
-            [7, ir8],
-            [8, ir9],
-            [9, ir10]
-        );
-        Ok((
-            Into::<T1>::into(ir1),
-            Into::<T2>::into(ir2),
-            Into::<T3>::into(ir3),
-            Into::<T4>::into(ir4),
-            Into::<T5>::into(ir5),
-            Into::<T6>::into(ir6),
-            Into::<T7>::into(ir7),
-            Into::<T8>::into(ir8),
-            Into::<T9>::into(ir9),
-            Into::<T10>::into(ir10),
-            ir11.into(),
-        ))
-    }
-}
+    #[allow(clippy::type_complexity)]
+    let tuple: (
+        u8,
+        u8,
+        u8,
+        u8,
+        u8,
+        u8,
+        u8,
+        u8,
+        u8,
+        u8,
+        u8,
+        u8,
+        u8,
+        u8,
+        u8,
+        u8,
+        u8,
+        u8,
+        u8,
+        u8,
+        u8,
+        u8,
+        u8,
+        i64,
+    ) = from_row_opt(row.clone()).unwrap();
+    assert_eq!(tuple.0, 0);
+    assert_eq!(tuple.12, 12);
+    assert_eq!(tuple.23, 23);
 
-impl<T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11, T12> FromRow
-    for (T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11, T12)
-where
-    T1: FromValue,
-    T2: FromValue,
-    T3: FromValue,
-    T4: FromValue,
-    T5: FromValue,
-    T6: FromValue,
-    T7: FromValue,
-    T8: FromValue,
-    T9: FromValue,
-    T10: FromValue,
-    T11: FromValue,
-    T12: FromValue,
-    T1::Intermediate: Into<Value>,
-    T2::Intermediate: Into<Value>,
-    T3::Intermediate: Into<Value>,
-    T4::Intermediate: Into<Value>,
-    T5::Intermediate: Into<Value>,
-    T6::Intermediate: Into<Value>,
-    T7::Intermediate: Into<Value>,
-    T8::Intermediate: Into<Value>,
-    T9::Intermediate: Into<Value>,
-    T10::Intermediate: Into<Value>,
-    T11::Intermediate: Into<Value>,
-{
-    fn from_row_opt(
-        mut row: Row,
-    ) -> Result<(T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11, T12), FromRowError> {
-        if row.len() != 12 {
-            return Err(FromRowError(row));
-        }
-        let ir1 = take_or_place!(row, 0, T1);
-        let ir2 = take_or_place!(row, 1, T2, [0, ir1]);
-        let ir3 = take_or_place!(row, 2, T3, [0, ir1], [1, ir2]);
-        let ir4 = take_or_place!(row, 3, T4, [0, ir1], [1, ir2], [2, ir3]);
-        let ir5 = take_or_place!(row, 4, T5, [0, ir1], [1, ir2], [2, ir3], [3, ir4]);
-        let ir6 = take_or_place!(row, 5, T6, [0, ir1], [1, ir2], [2, ir3], [3, ir4], [4, ir5]);
-        let ir7 = take_or_place!(
-            row,
-            6,
-            T7,
-            [0, ir1],
-            [1, ir2],
-            [2, ir3],
-            [3, ir4],
-            [4, ir5],
-            [5, ir6]
-        );
-        let ir8 = take_or_place!(
-            row,
-            7,
-            T8,
-            [0, ir1],
-            [1, ir2],
-            [2, ir3],
-            [3, ir4],
-            [4, ir5],
-            [5, ir6],
-            [6, ir7]
-        );
-        let ir9 = take_or_place!(
-            row,
-            8,
-            T9,
-            [0, ir1],
-            [1, ir2],
-            [2, ir3],
-            [3, ir4],
-            [4, ir5],
-            [5, ir6],
-            [6, ir7],
-            [7, ir8]
-        );
-        let ir10 = take_or_place!(
-            row,
-            9,
-            T10,
-            [0, ir1],
-            [1, ir2],
-            [2, ir3],
-            [3, ir4],
-            [4, ir5],
-            [5, ir6],
-            [6, ir7],
-            [7, ir8],
-            [8, ir9]
-        );
-        let ir11 = take_or_place!(
-            row,
-            10,
-            T11,
-            [0, ir1],
-            [1, ir2],
-            [2, ir3],
-            [3, ir4],
-            [4, ir5],
-            [5, ir6],
-            [6, ir7],
-            [7, ir8],
-            [8, ir9],
-            [9, ir10]
-        );
-        let ir12 = take_or_place!(
-            row,
-            11,
-            T12,
-            [0, ir1],
-            [1, ir2],
-            [2, ir3],
-            [3, ir4],
-            [4, ir5],
-            [5, ir6],
-            [6, ir7],
-            [7, ir8],
-            [8, ir9],
-            [9, ir10],
-            [10, ir11]
-        );
-        Ok((
-            Into::<T1>::into(ir1),
-            Into::<T2>::into(ir2),
-            Into::<T3>::into(ir3),
-            Into::<T4>::into(ir4),
-            Into::<T5>::into(ir5),
-            Into::<T6>::into(ir6),
-            Into::<T7>::into(ir7),
-            Into::<T8>::into(ir8),
-            Into::<T9>::into(ir9),
-            Into::<T10>::into(ir10),
-            Into::<T11>::into(ir11),
-            ir12.into(),
-        ))
-    }
+    // conversion of the last column fails, so the row must be restored
+    let mut invalid = row.clone().unwrap();
+    invalid[23] = Bytes(b"foo".to_vec());
+    let invalid = new_row(invalid, row.columns());
+    #[allow(clippy::type_complexity)]
+    let err = from_row_opt::<(
+        u8,
+        u8,
+        u8,
+        u8,
+        u8,
+        u8,
+        u8,
+        u8,
+        u8,
+        u8,
+        u8,
+        u8,
+        u8,
+        u8,
+        u8,
+        u8,
+        u8,
+        u8,
+        u8,
+        u8,
+        u8,
+        u8,
+        u8,
+        i64,
+    )>(invalid)
+    .err()
+    .unwrap();
+    assert!((0..24).all(|i| err.0.as_ref(i).is_some()));
+    assert_eq!(err.0[23], Bytes(b"foo".to_vec()));
 }
 
 #[cfg(feature = "nightly")]
@@ -874,6 +336,7 @@ fn bench_from_row(bencher: &mut test::Bencher) {
             ]
             .into_boxed_slice(),
         ),
+        name_index: None,
     };
 
     bencher.iter(|| from_row::<(String, u8, u16, u32)>(row.clone()));
@@ -6,6 +6,11 @@
 // option. All files in the project carrying such notice may not be copied,
 // modified, or distributed except according to those terms.
 
+#[cfg(feature = "serde_json")]
+use serde_json::{Map, Value as Json};
+
+#[cfg(feature = "serde_json")]
+use crate::value::json::value_to_json;
 use crate::{
     io::ParseBuf,
     misc::unexpected_buf_eof,
@@ -16,7 +21,7 @@ use crate::{
         BinValue, SerializationSide, TextValue, Value, ValueDeserializer,
     },
 };
-use std::{borrow::Cow, fmt, io, marker::PhantomData, ops::Index, sync::Arc};
+use std::{borrow::Cow, collections::HashMap, fmt, io, marker::PhantomData, ops::Index, sync::Arc};
 
 pub mod convert;
 
@@ -25,10 +30,17 @@ pub mod convert;
 /// It allows you to move column values out of a row with `Row::take` method but note that it
 /// makes row incomplete. Calls to `from_row_opt` on incomplete row will return
 /// `Error::FromRowError` and also numerical indexing on taken columns will panic.
-#[derive(Clone, PartialEq)]
+#[derive(Clone)]
 pub struct Row {
     values: Vec<Option<Value>>,
     columns: Arc<[Column]>,
+    name_index: Option<Arc<ColumnNameIndex>>,
+}
+
+impl PartialEq for Row {
+    fn eq(&self, other: &Self) -> bool {
+        self.values == other.values && self.columns == other.columns
+    }
 }
 
 impl fmt::Debug for Row {
@@ -54,6 +66,7 @@ pub fn new_row(values: Vec<Value>, columns: Arc<[Column]>) -> Row {
     Row {
         values: values.into_iter().map(Some).collect::<Vec<_>>(),
         columns,
+        name_index: None,
     }
 }
 
@@ -61,7 +74,11 @@ pub fn new_row(values: Vec<Value>, columns: Arc<[Column]>) -> Row {
 #[doc(hidden)]
 pub fn new_row_raw(values: Vec<Option<Value>>, columns: Arc<[Column]>) -> Row {
     assert!(values.len() == columns.len());
-    Row { values, columns }
+    Row {
+        values,
+        columns,
+        name_index: None,
+    }
 }
 
 impl Row {
@@ -81,10 +98,67 @@ impl Row {
     }
 
     /// Returns columns of this row.
+    ///
+    /// Rows of a result set are expected to share the same column metadata,
+    /// so this is a cheap reference counted clone.
     pub fn columns(&self) -> Arc<[Column]> {
         self.columns.clone()
     }
 
+    /// Returns `true` if this row shares column metadata with the `other` row
+    /// (i.e. both hold the same `Arc<[Column]>`).
+    pub fn shares_columns_with(&self, other: &Row) -> bool {
+        Arc::ptr_eq(&self.columns, &other.columns)
+    }
+
+    /// Attaches a precomputed column name index to this row.
+    ///
+    /// The index is meant to be computed once per result set and shared between its rows,
+    /// so that [`Row::column_index`] lookups are `O(1)`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the index was built for columns with different names.
+    pub fn with_name_index(mut self, name_index: Arc<ColumnNameIndex>) -> Self {
+        assert!(
+            name_index.matches(&self.columns),
+            "column name index doesn't match columns of the row"
+        );
+        self.name_index = Some(name_index);
+        self
+    }
+
+    /// Returns the column name index attached to this row (if any).
+    pub fn name_index(&self) -> Option<&Arc<ColumnNameIndex>> {
+        self.name_index.as_ref()
+    }
+
+    /// Returns index of the first column named exactly `name`.
+    ///
+    /// Uses the attached [`ColumnNameIndex`] if any, otherwise falls back to a linear scan.
+    pub fn column_index(&self, name: &str) -> Option<usize> {
+        match self.name_index {
+            Some(ref name_index) => name_index.get(name),
+            None => name.idx(&self.columns),
+        }
+    }
+
+    /// Returns index of the first column named `name` ignoring case,
+    /// the way MySql compares column names and aliases.
+    ///
+    /// Exact match takes precedence over a case-insensitive one.
+    pub fn column_index_ignore_case(&self, name: &str) -> Option<usize> {
+        match self.name_index {
+            Some(ref name_index) => name_index.get_ignore_case(name),
+            None => name.idx(&self.columns).or_else(|| {
+                let name = name.to_lowercase();
+                self.columns
+                    .iter()
+                    .position(|c| c.name_str().to_lowercase() == name)
+            }),
+        }
+    }
+
     /// Returns reference to the value of a column with index `index` if it exists and wasn't taken
     /// by `Row::take` method.
     ///
@@ -100,7 +174,7 @@ impl Row {
         T: FromValue,
         I: ColumnIndex,
     {
-        index.idx(&*self.columns).and_then(|idx| {
+        index.row_idx(self).and_then(|idx| {
             self.values
                 .get(idx)
                 .and_then(|x| x.as_ref())
@@ -117,7 +191,7 @@ impl Row {
         I: ColumnIndex,
     {
         index
-            .idx(&*self.columns)
+            .row_idx(self)
             .and_then(|idx| self.values.get(idx))
             .and_then(|x| x.as_ref())
             .map(|x| from_value_opt::<T>(x.clone()))
@@ -130,7 +204,7 @@ impl Row {
         T: FromValue,
         I: ColumnIndex,
     {
-        index.idx(&*self.columns).and_then(|idx| {
+        index.row_idx(self).and_then(|idx| {
             self.values
                 .get_mut(idx)
                 .and_then(|x| x.take())
@@ -147,7 +221,7 @@ impl Row {
         I: ColumnIndex,
     {
         index
-            .idx(&*self.columns)
+            .row_idx(self)
             .and_then(|idx| self.values.get_mut(idx))
             .and_then(|x| x.take())
             .map(from_value_opt::<T>)
@@ -165,6 +239,24 @@ impl Row {
             .collect()
     }
 
+    /// Converts this row into a JSON object keyed by column name.
+    ///
+    /// Columns taken by `Row::take` are omitted. Non-UTF-8 bytes are encoded as base64 strings,
+    /// dates and times are formatted as MySql literals.
+    #[cfg(feature = "serde_json")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "serde_json")))]
+    pub fn to_json(&self) -> Map<String, Json> {
+        self.values
+            .iter()
+            .zip(self.columns.iter())
+            .filter_map(|(value, column)| {
+                value
+                    .as_ref()
+                    .map(|value| (column.name_str().into_owned(), value_to_json(value)))
+            })
+            .collect()
+    }
+
     /// Unwraps values as is (taken cells will be `None`).
     #[doc(hidden)]
     pub fn unwrap_raw(self) -> Vec<Option<Value>> {
@@ -189,18 +281,84 @@ impl<'a> Index<&'a str> for Row {
     type Output = Value;
 
     fn index<'r>(&'r self, index: &'a str) -> &'r Value {
-        for (i, column) in self.columns.iter().enumerate() {
-            if column.name_ref() == index.as_bytes() {
-                return self.values[i].as_ref().unwrap();
-            }
+        match self.column_index(index) {
+            Some(i) => self.values[i].as_ref().unwrap(),
+            None => panic!("No such column: `{}` in row {:?}", index, self),
+        }
+    }
+}
+
+/// Precomputed mapping of column names to column indexes.
+///
+/// Should be computed once per result set and shared between its rows
+/// (see [`Row::with_name_index`]). If several columns share a name, the first one wins.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ColumnNameIndex {
+    names: Box<[Box<[u8]>]>,
+    exact: HashMap<Box<[u8]>, usize>,
+    folded: HashMap<String, usize>,
+}
+
+impl ColumnNameIndex {
+    /// Builds an index for the given columns.
+    pub fn new(columns: &[Column]) -> Self {
+        let mut exact = HashMap::with_capacity(columns.len());
+        let mut folded = HashMap::with_capacity(columns.len());
+        for (i, column) in columns.iter().enumerate() {
+            exact.entry(column.name_ref().into()).or_insert(i);
+            folded.entry(column.name_str().to_lowercase()).or_insert(i);
+        }
+        Self {
+            names: columns.iter().map(|c| c.name_ref().into()).collect(),
+            exact,
+            folded,
         }
-        panic!("No such column: `{}` in row {:?}", index, self);
+    }
+
+    /// Returns the number of indexed columns.
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    /// Returns `true` if there are no indexed columns.
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+
+    /// Returns `true` if this index was built for columns with the same names.
+    pub fn matches(&self, columns: &[Column]) -> bool {
+        self.names.len() == columns.len()
+            && self
+                .names
+                .iter()
+                .zip(columns)
+                .all(|(name, column)| **name == *column.name_ref())
+    }
+
+    /// Returns index of the first column named exactly `name`.
+    pub fn get(&self, name: &str) -> Option<usize> {
+        self.exact.get(name.as_bytes()).copied()
+    }
+
+    /// Returns index of the first column named `name` ignoring case.
+    ///
+    /// Exact match takes precedence over a case-insensitive one.
+    pub fn get_ignore_case(&self, name: &str) -> Option<usize> {
+        self.get(name)
+            .or_else(|| self.folded.get(&name.to_lowercase()).copied())
     }
 }
 
 /// Things that may be used as an index of a row column.
 pub trait ColumnIndex {
     fn idx(&self, columns: &[Column]) -> Option<usize>;
+
+    /// Returns the index of a column of the given row.
+    ///
+    /// Name lookups use the [`ColumnNameIndex`] attached to the row (if any).
+    fn row_idx(&self, row: &Row) -> Option<usize> {
+        self.idx(row.columns_ref())
+    }
 }
 
 impl ColumnIndex for usize {
@@ -222,6 +380,10 @@ impl<'a> ColumnIndex for &'a str {
         }
         None
     }
+
+    fn row_idx(&self, row: &Row) -> Option<usize> {
+        row.column_index(self)
+    }
 }
 
 /// Row deserializer.
@@ -243,6 +405,48 @@ impl<S, P> From<RowDeserializer<S, P>> for Row {
     }
 }
 
+/// Row deserializer that attaches a shared column name index to every row
+/// (see [`Row::with_name_index`]).
+///
+/// The index should be built once per result set, e.g. when its column definitions are read.
+///
+/// `S` – serialization side (see [`SerializationSide`]);
+/// `P` – protocol.
+#[derive(Debug, Clone, PartialEq)]
+pub struct IndexedRowDeserializer<S, P>(Row, PhantomData<(S, P)>);
+
+impl<S, P> IndexedRowDeserializer<S, P> {
+    pub fn into_inner(self) -> Row {
+        self.0
+    }
+}
+
+impl<S, P> From<IndexedRowDeserializer<S, P>> for Row {
+    fn from(x: IndexedRowDeserializer<S, P>) -> Self {
+        x.0
+    }
+}
+
+impl<'de, S, P> MyDeserialize<'de> for IndexedRowDeserializer<S, P>
+where
+    RowDeserializer<S, P>: MyDeserialize<'de, Ctx = Arc<[Column]>>,
+{
+    const SIZE: Option<usize> = None;
+    type Ctx = (Arc<[Column]>, Arc<ColumnNameIndex>);
+
+    fn deserialize((columns, name_index): Self::Ctx, buf: &mut ParseBuf<'de>) -> io::Result<Self> {
+        if !name_index.matches(&columns) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "column name index doesn't match columns of the result set",
+            ));
+        }
+        let mut row = RowDeserializer::<S, P>::deserialize(columns, buf)?.into_inner();
+        row.name_index = Some(name_index);
+        Ok(Self(row, PhantomData))
+    }
+}
+
 impl<'de, T> MyDeserialize<'de> for RowDeserializer<T, Text> {
     const SIZE: Option<usize> = None;
     type Ctx = Arc<[Column]>;
@@ -256,7 +460,14 @@ impl<'de, T> MyDeserialize<'de> for RowDeserializer<T, Text> {
             ))
         }
 
-        Ok(Self(Row { values, columns }, PhantomData))
+        Ok(Self(
+            Row {
+                values,
+                columns,
+                name_index: None,
+            },
+            PhantomData,
+        ))
     }
 }
 
@@ -286,6 +497,112 @@ impl<'de, S: SerializationSide> MyDeserialize<'de> for RowDeserializer<S, Binary
             }
         }
 
-        Ok(Self(Row { values, columns }, PhantomData))
+        Ok(Self(
+            Row {
+                values,
+                columns,
+                name_index: None,
+            },
+            PhantomData,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::{new_row, ColumnNameIndex, IndexedRowDeserializer, RowDeserializer};
+    use crate::{
+        constants::ColumnType,
+        io::ParseBuf,
+        packets::Column,
+        proto::{MyDeserialize, Text},
+        value::Value,
+    };
+
+    #[test]
+    fn should_lookup_column_index() {
+        let columns: Arc<[Column]> = vec![
+            Column::new(ColumnType::MYSQL_TYPE_LONG).with_name(b"id"),
+            Column::new(ColumnType::MYSQL_TYPE_VARCHAR).with_name(b"Name"),
+            Column::new(ColumnType::MYSQL_TYPE_VARCHAR).with_name(b"name"),
+        ]
+        .into();
+        let row = new_row(
+            vec![Value::Int(1), Value::from("foo"), Value::from("bar")],
+            columns.clone(),
+        );
+        let indexed = row
+            .clone()
+            .with_name_index(Arc::new(ColumnNameIndex::new(&columns)));
+        assert_eq!(row, indexed);
+
+        for row in &[row, indexed] {
+            assert_eq!(row.column_index("id"), Some(0));
+            assert_eq!(row.column_index("ID"), None);
+            assert_eq!(row.column_index("name"), Some(2));
+            assert_eq!(row.column_index_ignore_case("ID"), Some(0));
+            assert_eq!(row.column_index_ignore_case("name"), Some(2));
+            assert_eq!(row.column_index_ignore_case("NAME"), Some(1));
+            assert_eq!(row.column_index_ignore_case("foo"), None);
+            assert_eq!(row["Name"], Value::from("foo"));
+            assert_eq!(row.get::<String, _>("name").unwrap(), "bar");
+        }
+
+        let other: Arc<[Column]> = vec![
+            Column::new(ColumnType::MYSQL_TYPE_LONG).with_name(b"id"),
+            Column::new(ColumnType::MYSQL_TYPE_VARCHAR).with_name(b"name"),
+            Column::new(ColumnType::MYSQL_TYPE_VARCHAR).with_name(b"Name"),
+        ]
+        .into();
+        assert!(ColumnNameIndex::new(&columns).matches(&columns));
+        assert!(!ColumnNameIndex::new(&other).matches(&columns));
+        assert!(!ColumnNameIndex::new(&columns[..2]).matches(&columns));
+    }
+
+    #[test]
+    #[should_panic(expected = "doesn't match")]
+    fn should_reject_foreign_name_index() {
+        let columns: Arc<[Column]> =
+            vec![Column::new(ColumnType::MYSQL_TYPE_LONG).with_name(b"a")].into();
+        let other = [Column::new(ColumnType::MYSQL_TYPE_LONG).with_name(b"b")];
+        new_row(vec![Value::Int(1)], columns)
+            .with_name_index(Arc::new(ColumnNameIndex::new(&other)));
+    }
+
+    #[test]
+    fn should_attach_name_index_to_deserialized_rows() {
+        let columns: Arc<[Column]> = vec![
+            Column::new(ColumnType::MYSQL_TYPE_LONG).with_name(b"id"),
+            Column::new(ColumnType::MYSQL_TYPE_VARCHAR).with_name(b"Name"),
+        ]
+        .into();
+        let name_index = Arc::new(ColumnNameIndex::new(&columns));
+        let payload = b"\x011\x03foo";
+
+        let row = IndexedRowDeserializer::<(), Text>::deserialize(
+            (columns.clone(), name_index.clone()),
+            &mut ParseBuf(payload),
+        )
+        .unwrap()
+        .into_inner();
+        assert!(Arc::ptr_eq(row.name_index().unwrap(), &name_index));
+        assert_eq!(
+            row,
+            RowDeserializer::<(), Text>::deserialize(columns, &mut ParseBuf(payload))
+                .unwrap()
+                .into_inner()
+        );
+        assert_eq!(row.column_index_ignore_case("name"), Some(1));
+
+        let other: Arc<[Column]> =
+            vec![Column::new(ColumnType::MYSQL_TYPE_LONG).with_name(b"x")].into();
+        let err = IndexedRowDeserializer::<(), Text>::deserialize(
+            (other, name_index),
+            &mut ParseBuf(payload),
+        )
+        .unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
     }
 }
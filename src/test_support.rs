@@ -0,0 +1,347 @@
+// Copyright (c) 2023 Anatoly Ikorsky
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Compatibility corpus of binlog files used by tests of this crate.
+//!
+//! The corpus consists of binlog files written by various MySql and MariaDB versions
+//! (`test-data/binlogs`) and of golden expectations for every file (`test-data/golden`),
+//! so that downstream crates (drivers, CDC tools) can run the same corpus against
+//! their integration layers.
+//!
+//! Golden expectations list every event of a file as `log_pos event_size event_type data`,
+//! where `event_type` is the raw type code and `data` is either `ok` or `error` depending
+//! on whether the event data could be parsed (see [`Event::read_data`]). Lines starting
+//! with `#` are comments.
+//!
+//! ```no_run
+//! # fn main() -> std::io::Result<()> {
+//! use mysql_common::test_support::Corpus;
+//!
+//! for fixture in Corpus::bundled().fixtures()? {
+//!     let data = fixture.read()?;
+//!     for expected in fixture.golden()? {
+//!         // feed `data` to the integration layer and compare events with `expected`
+//!     }
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use std::{
+    fmt, fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+use crate::binlog::{consts::BinlogVersion, events::Event, BinlogFile};
+
+/// Directory of the corpus bundled with this crate.
+pub const BUNDLED_CORPUS_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/test-data");
+
+/// Set of binlog files with golden expectations.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct Corpus {
+    dir: PathBuf,
+}
+
+impl Corpus {
+    /// Creates a corpus located in the given directory.
+    ///
+    /// Binlog files are expected in the `binlogs` subdirectory and golden expectations
+    /// in the `golden` subdirectory (named after binlog files with the `.golden` extension).
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// Returns the corpus bundled with this crate (see [`BUNDLED_CORPUS_DIR`]).
+    pub fn bundled() -> Self {
+        Self::new(BUNDLED_CORPUS_DIR)
+    }
+
+    /// Returns the directory of the corpus.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Returns all fixtures of the corpus (in the order of names).
+    pub fn fixtures(&self) -> io::Result<Vec<Fixture>> {
+        let mut fixtures = Vec::new();
+        for entry in fs::read_dir(self.dir.join("binlogs"))? {
+            let entry = entry?;
+            if !entry.file_type()?.is_file() {
+                continue;
+            }
+            if let Ok(name) = entry.file_name().into_string() {
+                fixtures.push(self.make_fixture(name));
+            }
+        }
+        fixtures.sort_unstable_by(|a, b| a.name.cmp(&b.name));
+        Ok(fixtures)
+    }
+
+    /// Returns the fixture with the given name.
+    ///
+    /// Returns a `NotFound` error if there is no such binlog file.
+    pub fn fixture(&self, name: &str) -> io::Result<Fixture> {
+        let fixture = self.make_fixture(name.to_owned());
+        if fixture.path.is_file() {
+            Ok(fixture)
+        } else {
+            Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("unknown fixture {}", name),
+            ))
+        }
+    }
+
+    fn make_fixture(&self, name: String) -> Fixture {
+        Fixture {
+            path: self.dir.join("binlogs").join(&name),
+            golden_path: self.dir.join("golden").join(format!("{}.golden", name)),
+            name,
+        }
+    }
+}
+
+/// Binlog file of a [`Corpus`].
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct Fixture {
+    name: String,
+    path: PathBuf,
+    golden_path: PathBuf,
+}
+
+impl Fixture {
+    /// Returns the name of the binlog file.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the path of the binlog file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns the path of the golden expectations.
+    pub fn golden_path(&self) -> &Path {
+        &self.golden_path
+    }
+
+    /// Reads the binlog file.
+    pub fn read(&self) -> io::Result<Vec<u8>> {
+        fs::read(&self.path)
+    }
+
+    /// Reads golden expectations of the binlog file.
+    pub fn golden(&self) -> io::Result<Vec<GoldenEvent>> {
+        GoldenEvent::parse_all(&fs::read_to_string(&self.golden_path)?)
+    }
+
+    /// Reads events of the binlog file using this crate and summarizes them
+    /// (see [`GoldenEvent::from_event`]).
+    pub fn summarize(&self) -> io::Result<Vec<GoldenEvent>> {
+        let data = self.read()?;
+        BinlogFile::new(BinlogVersion::Version4, &data[..])?
+            .map(|event| event.map(|event| GoldenEvent::from_event(&event)))
+            .collect()
+    }
+
+    /// Checks that this crate reads the binlog file as expected.
+    ///
+    /// Returns an `InvalidData` error that describes the first mismatch.
+    pub fn check(&self) -> io::Result<()> {
+        let expected = self.golden()?;
+        let actual = self.summarize()?;
+        let mismatch = |msg: String| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("{}: {}", self.name, msg),
+            )
+        };
+        for (i, (expected, actual)) in expected.iter().zip(&actual).enumerate() {
+            if expected != actual {
+                return Err(mismatch(format!(
+                    "event {}: expected `{}`, got `{}`",
+                    i, expected, actual
+                )));
+            }
+        }
+        if expected.len() != actual.len() {
+            return Err(mismatch(format!(
+                "expected {} events, got {}",
+                expected.len(),
+                actual.len()
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Expected properties of an event of a [`Fixture`].
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub struct GoldenEvent {
+    log_pos: u32,
+    event_size: u32,
+    event_type: u8,
+    data_parsed: bool,
+}
+
+impl GoldenEvent {
+    /// Creates a new instance.
+    pub fn new(log_pos: u32, event_size: u32, event_type: u8, data_parsed: bool) -> Self {
+        Self {
+            log_pos,
+            event_size,
+            event_type,
+            data_parsed,
+        }
+    }
+
+    /// Summarizes the given event.
+    pub fn from_event(event: &Event<'_>) -> Self {
+        let header = event.header();
+        Self::new(
+            header.log_pos(),
+            header.event_size(),
+            header.event_type_raw(),
+            event.read_data().is_ok(),
+        )
+    }
+
+    /// Parses golden expectations (see the [module docs](self)).
+    ///
+    /// Returns an `InvalidData` error if a line is malformed.
+    pub fn parse_all(golden: &str) -> io::Result<Vec<Self>> {
+        golden
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(Self::parse)
+            .collect()
+    }
+
+    /// Writes golden expectations (see the [module docs](self)).
+    pub fn write_all<W: Write>(events: &[Self], mut write: W) -> io::Result<()> {
+        writeln!(write, "# log_pos event_size event_type data")?;
+        for event in events {
+            writeln!(write, "{}", event)?;
+        }
+        Ok(())
+    }
+
+    fn parse(line: &str) -> io::Result<Self> {
+        let invalid = || {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("malformed golden event `{}`", line),
+            )
+        };
+        let mut fields = line.split_whitespace();
+        let mut next = || fields.next().ok_or_else(invalid);
+        let log_pos = next()?.parse().map_err(|_| invalid())?;
+        let event_size = next()?.parse().map_err(|_| invalid())?;
+        let event_type = next()?.parse().map_err(|_| invalid())?;
+        let data_parsed = match next()? {
+            "ok" => true,
+            "error" => false,
+            _ => return Err(invalid()),
+        };
+        if fields.next().is_some() {
+            return Err(invalid());
+        }
+        Ok(Self::new(log_pos, event_size, event_type, data_parsed))
+    }
+
+    /// Returns the expected `log_pos` of the event.
+    pub fn log_pos(&self) -> u32 {
+        self.log_pos
+    }
+
+    /// Returns the expected size of the event.
+    pub fn event_size(&self) -> u32 {
+        self.event_size
+    }
+
+    /// Returns the expected raw event type.
+    pub fn event_type(&self) -> u8 {
+        self.event_type
+    }
+
+    /// Returns `true` if event data is expected to be parsed successfully.
+    pub fn data_parsed(&self) -> bool {
+        self.data_parsed
+    }
+}
+
+impl fmt::Display for GoldenEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {} {} {}",
+            self.log_pos,
+            self.event_size,
+            self.event_type,
+            if self.data_parsed { "ok" } else { "error" }
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+
+    use super::{Corpus, GoldenEvent};
+
+    #[test]
+    fn bundled_corpus_matches_golden() -> io::Result<()> {
+        let fixtures = Corpus::bundled().fixtures()?;
+        assert!(fixtures.len() > 30);
+        for fixture in fixtures {
+            fixture.check()?;
+        }
+
+        let fixture = Corpus::bundled().fixture("ver_5_1-wl2325_r.001")?;
+        assert!(fixture.golden()?.iter().any(|x| !x.data_parsed()));
+        assert_eq!(
+            Corpus::bundled().fixture("unknown").unwrap_err().kind(),
+            io::ErrorKind::NotFound
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn should_parse_golden_events() -> io::Result<()> {
+        let events = vec![
+            GoldenEvent::new(120, 116, 15, true),
+            GoldenEvent::new(200, 80, 2, false),
+        ];
+        let mut golden = Vec::new();
+        GoldenEvent::write_all(&events, &mut golden)?;
+        let golden = String::from_utf8(golden).unwrap();
+        assert_eq!(
+            golden,
+            "# log_pos event_size event_type data\n120 116 15 ok\n200 80 2 error\n"
+        );
+        assert_eq!(GoldenEvent::parse_all(&golden)?, events);
+
+        for malformed in &[
+            "120 116 15",
+            "120 116 15 maybe",
+            "120 116 15 ok 1",
+            "x 1 2 ok",
+        ] {
+            assert_eq!(
+                GoldenEvent::parse_all(malformed).unwrap_err().kind(),
+                io::ErrorKind::InvalidData
+            );
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,453 @@
+// Copyright (c) 2023 Anatoly Ikorsky
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! This module implements conversion from/to `Value` for `geo_types` geometries.
+
+#![cfg(feature = "geo-types")]
+
+use std::convert::TryFrom;
+
+use byteorder::{BigEndian, ByteOrder, LittleEndian};
+use geo_types::{
+    Coord, Geometry as GeoGeometry, GeometryCollection, LineString, MultiLineString, MultiPoint,
+    MultiPolygon, Point, Polygon,
+};
+
+use crate::{
+    constants::GeometryType,
+    value::{
+        geometry::{Geometry, GeometryError},
+        Value,
+    },
+};
+
+use super::{FromValue, FromValueError, ParseIr};
+
+/// Reader of WKB geometries.
+struct WkbReader<'a> {
+    wkb: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> WkbReader<'a> {
+    /// Maximum nesting depth of geometry collections.
+    const MAX_DEPTH: usize = 32;
+
+    fn malformed(&self) -> GeometryError {
+        GeometryError::MalformedWkb(self.pos)
+    }
+
+    fn eat(&mut self, n: usize) -> Result<&'a [u8], GeometryError> {
+        match self.wkb.get(self.pos..self.pos + n) {
+            Some(bytes) => {
+                self.pos += n;
+                Ok(bytes)
+            }
+            None => Err(self.malformed()),
+        }
+    }
+
+    fn eat_u32(&mut self, big_endian: bool) -> Result<u32, GeometryError> {
+        let bytes = self.eat(4)?;
+        if big_endian {
+            Ok(BigEndian::read_u32(bytes))
+        } else {
+            Ok(LittleEndian::read_u32(bytes))
+        }
+    }
+
+    fn eat_f64(&mut self, big_endian: bool) -> Result<f64, GeometryError> {
+        let bytes = self.eat(8)?;
+        if big_endian {
+            Ok(BigEndian::read_f64(bytes))
+        } else {
+            Ok(LittleEndian::read_f64(bytes))
+        }
+    }
+
+    /// Reads the number of elements, each of at least `min_len` bytes.
+    fn eat_count(&mut self, big_endian: bool, min_len: usize) -> Result<usize, GeometryError> {
+        let count = self.eat_u32(big_endian)? as usize;
+        if count.saturating_mul(min_len) > self.wkb.len() - self.pos {
+            return Err(self.malformed());
+        }
+        Ok(count)
+    }
+
+    fn eat_coord(&mut self, big_endian: bool) -> Result<Coord<f64>, GeometryError> {
+        let x = self.eat_f64(big_endian)?;
+        let y = self.eat_f64(big_endian)?;
+        Ok(Coord { x, y })
+    }
+
+    fn eat_line_string(&mut self, big_endian: bool) -> Result<LineString<f64>, GeometryError> {
+        let count = self.eat_count(big_endian, 16)?;
+        (0..count)
+            .map(|_| self.eat_coord(big_endian))
+            .collect::<Result<Vec<_>, _>>()
+            .map(LineString::new)
+    }
+
+    fn eat_polygon(&mut self, big_endian: bool) -> Result<Polygon<f64>, GeometryError> {
+        let count = self.eat_count(big_endian, 4)?;
+        let mut rings = (0..count)
+            .map(|_| self.eat_line_string(big_endian))
+            .collect::<Result<Vec<_>, _>>()?
+            .into_iter();
+        let exterior = rings.next().unwrap_or_else(|| LineString::new(Vec::new()));
+        Ok(Polygon::new(exterior, rings.collect()))
+    }
+
+    /// Reads `count` nested geometries of the given type.
+    fn eat_nested<T>(
+        &mut self,
+        big_endian: bool,
+        depth: usize,
+        expected: Option<GeometryType>,
+        f: impl Fn(GeoGeometry<f64>) -> Option<T>,
+    ) -> Result<Vec<T>, GeometryError> {
+        let count = self.eat_count(big_endian, 5)?;
+        let mut items = Vec::with_capacity(count);
+        for _ in 0..count {
+            let pos = self.pos;
+            let (ty, geometry) = self.eat_geometry(depth + 1)?;
+            if expected.map(|x| x != ty).unwrap_or(false) {
+                return Err(GeometryError::MalformedWkb(pos));
+            }
+            items.push(f(geometry).ok_or(GeometryError::MalformedWkb(pos))?);
+        }
+        Ok(items)
+    }
+
+    /// Reads a geometry nested in `depth` collections.
+    fn eat_geometry(
+        &mut self,
+        depth: usize,
+    ) -> Result<(GeometryType, GeoGeometry<f64>), GeometryError> {
+        if depth > Self::MAX_DEPTH {
+            return Err(self.malformed());
+        }
+        let big_endian = match self.eat(1)?[0] {
+            0 => true,
+            1 => false,
+            order => return Err(GeometryError::InvalidByteOrder(order)),
+        };
+        let raw_ty = self.eat_u32(big_endian)?;
+        let ty = u8::try_from(raw_ty)
+            .ok()
+            .and_then(|x| GeometryType::try_from(x).ok())
+            .ok_or(GeometryError::UnknownType(raw_ty))?;
+
+        let geometry = match ty {
+            GeometryType::GEOM_GEOMETRY => return Err(GeometryError::UnknownType(raw_ty)),
+            GeometryType::GEOM_POINT => Point(self.eat_coord(big_endian)?).into(),
+            GeometryType::GEOM_LINESTRING => self.eat_line_string(big_endian)?.into(),
+            GeometryType::GEOM_POLYGON => self.eat_polygon(big_endian)?.into(),
+            GeometryType::GEOM_MULTIPOINT => {
+                let points =
+                    self.eat_nested(big_endian, depth, Some(GeometryType::GEOM_POINT), |x| {
+                        Point::try_from(x).ok()
+                    })?;
+                MultiPoint::new(points).into()
+            }
+            GeometryType::GEOM_MULTILINESTRING => {
+                let line_strings = self.eat_nested(
+                    big_endian,
+                    depth,
+                    Some(GeometryType::GEOM_LINESTRING),
+                    |x| LineString::try_from(x).ok(),
+                )?;
+                MultiLineString::new(line_strings).into()
+            }
+            GeometryType::GEOM_MULTIPOLYGON => {
+                let polygons =
+                    self.eat_nested(big_endian, depth, Some(GeometryType::GEOM_POLYGON), |x| {
+                        Polygon::try_from(x).ok()
+                    })?;
+                MultiPolygon::new(polygons).into()
+            }
+            GeometryType::GEOM_GEOMETRYCOLLECTION => GeoGeometry::GeometryCollection(
+                GeometryCollection::new_from(self.eat_nested(big_endian, depth, None, Some)?),
+            ),
+        };
+
+        Ok((ty, geometry))
+    }
+}
+
+/// Writes the given geometry as little-endian WKB.
+fn put_geometry(wkb: &mut Vec<u8>, geometry: &GeoGeometry<f64>) {
+    fn put_header(wkb: &mut Vec<u8>, ty: GeometryType) {
+        wkb.push(1);
+        wkb.extend_from_slice(&(ty as u32).to_le_bytes());
+    }
+
+    fn put_count(wkb: &mut Vec<u8>, count: usize) {
+        wkb.extend_from_slice(&(count as u32).to_le_bytes());
+    }
+
+    fn put_coord(wkb: &mut Vec<u8>, coord: Coord<f64>) {
+        wkb.extend_from_slice(&coord.x.to_le_bytes());
+        wkb.extend_from_slice(&coord.y.to_le_bytes());
+    }
+
+    fn put_line_string(wkb: &mut Vec<u8>, line_string: &LineString<f64>) {
+        put_count(wkb, line_string.0.len());
+        for coord in &line_string.0 {
+            put_coord(wkb, *coord);
+        }
+    }
+
+    fn put_polygon(wkb: &mut Vec<u8>, polygon: &Polygon<f64>) {
+        put_count(wkb, 1 + polygon.interiors().len());
+        put_line_string(wkb, polygon.exterior());
+        for interior in polygon.interiors() {
+            put_line_string(wkb, interior);
+        }
+    }
+
+    match geometry {
+        GeoGeometry::Point(point) => {
+            put_header(wkb, GeometryType::GEOM_POINT);
+            put_coord(wkb, point.0);
+        }
+        GeoGeometry::Line(line) => {
+            put_header(wkb, GeometryType::GEOM_LINESTRING);
+            put_line_string(wkb, &LineString::from(*line));
+        }
+        GeoGeometry::LineString(line_string) => {
+            put_header(wkb, GeometryType::GEOM_LINESTRING);
+            put_line_string(wkb, line_string);
+        }
+        GeoGeometry::Polygon(polygon) => {
+            put_header(wkb, GeometryType::GEOM_POLYGON);
+            put_polygon(wkb, polygon);
+        }
+        GeoGeometry::Rect(rect) => {
+            put_header(wkb, GeometryType::GEOM_POLYGON);
+            put_polygon(wkb, &rect.to_polygon());
+        }
+        GeoGeometry::Triangle(triangle) => {
+            put_header(wkb, GeometryType::GEOM_POLYGON);
+            put_polygon(wkb, &triangle.to_polygon());
+        }
+        GeoGeometry::MultiPoint(multi_point) => {
+            put_header(wkb, GeometryType::GEOM_MULTIPOINT);
+            put_count(wkb, multi_point.0.len());
+            for point in &multi_point.0 {
+                put_header(wkb, GeometryType::GEOM_POINT);
+                put_coord(wkb, point.0);
+            }
+        }
+        GeoGeometry::MultiLineString(multi_line_string) => {
+            put_header(wkb, GeometryType::GEOM_MULTILINESTRING);
+            put_count(wkb, multi_line_string.0.len());
+            for line_string in &multi_line_string.0 {
+                put_header(wkb, GeometryType::GEOM_LINESTRING);
+                put_line_string(wkb, line_string);
+            }
+        }
+        GeoGeometry::MultiPolygon(multi_polygon) => {
+            put_header(wkb, GeometryType::GEOM_MULTIPOLYGON);
+            put_count(wkb, multi_polygon.0.len());
+            for polygon in &multi_polygon.0 {
+                put_header(wkb, GeometryType::GEOM_POLYGON);
+                put_polygon(wkb, polygon);
+            }
+        }
+        GeoGeometry::GeometryCollection(collection) => {
+            put_header(wkb, GeometryType::GEOM_GEOMETRYCOLLECTION);
+            put_count(wkb, collection.0.len());
+            for geometry in &collection.0 {
+                put_geometry(wkb, geometry);
+            }
+        }
+    }
+}
+
+/// Parses the WKB representation of the given geometry (the SRID is ignored).
+#[cfg_attr(docsrs, doc(cfg(feature = "geo-types")))]
+impl TryFrom<&Geometry> for GeoGeometry<f64> {
+    type Error = GeometryError;
+
+    fn try_from(geometry: &Geometry) -> Result<Self, Self::Error> {
+        let mut reader = WkbReader {
+            wkb: geometry.wkb(),
+            pos: 0,
+        };
+        let (_, geometry) = reader.eat_geometry(0)?;
+        if reader.pos != reader.wkb.len() {
+            return Err(reader.malformed());
+        }
+        Ok(geometry)
+    }
+}
+
+/// Converts the given geometry to WKB with SRID `0` (see [`Geometry::with_srid`]).
+///
+/// `Line`, `Rect` and `Triangle` are written as `LINESTRING` and `POLYGON`.
+#[cfg_attr(docsrs, doc(cfg(feature = "geo-types")))]
+impl From<&GeoGeometry<f64>> for Geometry {
+    fn from(geometry: &GeoGeometry<f64>) -> Self {
+        let mut wkb = Vec::new();
+        put_geometry(&mut wkb, geometry);
+        Geometry::new(0, wkb).expect("valid WKB header")
+    }
+}
+
+#[cfg_attr(docsrs, doc(cfg(feature = "geo-types")))]
+impl From<GeoGeometry<f64>> for Value {
+    fn from(geometry: GeoGeometry<f64>) -> Self {
+        Geometry::from(&geometry).into()
+    }
+}
+
+#[cfg_attr(docsrs, doc(cfg(feature = "geo-types")))]
+impl FromValue for GeoGeometry<f64> {
+    type Intermediate = ParseIr<GeoGeometry<f64>>;
+}
+
+#[cfg_attr(docsrs, doc(cfg(feature = "geo-types")))]
+impl TryFrom<Value> for ParseIr<GeoGeometry<f64>> {
+    type Error = FromValueError;
+
+    fn try_from(v: Value) -> Result<Self, Self::Error> {
+        let geometry = match v {
+            Value::Bytes(ref bytes) => Geometry::from_geometry_bytes(bytes)
+                .and_then(|x| GeoGeometry::try_from(&x))
+                .ok(),
+            _ => None,
+        };
+        match geometry {
+            Some(geometry) => Ok(ParseIr(geometry, v)),
+            None => Err(FromValueError(v)),
+        }
+    }
+}
+
+#[cfg_attr(docsrs, doc(cfg(feature = "geo-types")))]
+impl From<ParseIr<GeoGeometry<f64>>> for GeoGeometry<f64> {
+    fn from(value: ParseIr<GeoGeometry<f64>>) -> Self {
+        value.commit()
+    }
+}
+
+#[cfg_attr(docsrs, doc(cfg(feature = "geo-types")))]
+impl From<ParseIr<GeoGeometry<f64>>> for Value {
+    fn from(value: ParseIr<GeoGeometry<f64>>) -> Self {
+        value.rollback()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::TryFrom;
+
+    use geo_types::{
+        line_string, point, polygon, Geometry as GeoGeometry, GeometryCollection, MultiLineString,
+        MultiPoint, MultiPolygon, Rect,
+    };
+
+    use crate::value::{
+        convert::from_value,
+        geometry::{Geometry, GeometryError},
+        Value,
+    };
+
+    #[test]
+    fn should_convert_geo_types() {
+        let polygon = polygon![
+            exterior: [(x: 0.0, y: 0.0), (x: 4.0, y: 0.0), (x: 4.0, y: 4.0), (x: 0.0, y: 0.0)],
+            interiors: [[(x: 1.0, y: 1.0), (x: 2.0, y: 1.0), (x: 2.0, y: 2.0), (x: 1.0, y: 1.0)]],
+        ];
+        let line_string = line_string![(x: 0.0, y: 0.0), (x: 1.0, y: 1.0)];
+        let geometries: Vec<GeoGeometry<f64>> = vec![
+            point!(x: 1.5, y: -2.0).into(),
+            line_string.clone().into(),
+            polygon.clone().into(),
+            MultiPoint::new(vec![point!(x: 1.0, y: 2.0), point!(x: 3.0, y: 4.0)]).into(),
+            MultiLineString::new(vec![line_string.clone()]).into(),
+            MultiPolygon::new(vec![polygon.clone(), polygon.clone()]).into(),
+            GeoGeometry::GeometryCollection(GeometryCollection::new_from(vec![
+                point!(x: 1.0, y: 1.0).into(),
+                GeoGeometry::GeometryCollection(GeometryCollection::new_from(vec![line_string
+                    .clone()
+                    .into()])),
+            ])),
+        ];
+
+        for geo in geometries {
+            let geometry = Geometry::from(&geo).with_srid(4326);
+            assert_eq!(GeoGeometry::try_from(&geometry), Ok(geo.clone()));
+
+            let value = Value::from(geo.clone());
+            assert_eq!(from_value::<GeoGeometry<f64>>(value), geo);
+        }
+
+        // SELECT ST_GeomFromText('LINESTRING(0 0, 1 1)', 4326)
+        let linestring = b"\xe6\x10\x00\x00\x01\x02\x00\x00\x00\x02\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\xf0\x3f\x00\x00\x00\x00\x00\x00\xf0\x3f";
+        let geometry = Geometry::from_geometry_bytes(linestring).unwrap();
+        assert_eq!(
+            GeoGeometry::try_from(&geometry),
+            Ok(line_string.clone().into())
+        );
+
+        // big-endian WKB
+        let mut wkb = vec![0, 0, 0, 0, 1];
+        wkb.extend_from_slice(&1.5_f64.to_be_bytes());
+        wkb.extend_from_slice(&(-2.0_f64).to_be_bytes());
+        let geometry = Geometry::new(0, wkb).unwrap();
+        assert_eq!(
+            GeoGeometry::try_from(&geometry),
+            Ok(point!(x: 1.5, y: -2.0).into())
+        );
+
+        // rect is written as a polygon
+        let rect = Rect::new((0.0, 0.0), (1.0, 1.0));
+        let geometry = Geometry::from(&GeoGeometry::from(rect));
+        assert_eq!(
+            GeoGeometry::try_from(&geometry),
+            Ok(rect.to_polygon().into())
+        );
+
+        // malformed
+        let geometry = Geometry::from_geometry_bytes(&linestring[..linestring.len() - 1]).unwrap();
+        assert_eq!(
+            GeoGeometry::try_from(&geometry),
+            Err(GeometryError::MalformedWkb(9))
+        );
+        let mut wkb = Geometry::from(&GeoGeometry::from(line_string)).into_wkb();
+        wkb.push(0);
+        let geometry = Geometry::new(0, wkb).unwrap();
+        assert_eq!(
+            GeoGeometry::try_from(&geometry),
+            Err(GeometryError::MalformedWkb(41))
+        );
+    }
+
+    #[test]
+    fn should_limit_nesting_depth() {
+        let nested = |depth: usize| {
+            let mut wkb = Vec::new();
+            for _ in 0..depth {
+                wkb.extend_from_slice(&[1, 7, 0, 0, 0, 1, 0, 0, 0]);
+            }
+            wkb.extend_from_slice(&[1, 1, 0, 0, 0]);
+            wkb.extend_from_slice(&[0; 16]);
+            Geometry::new(0, wkb).unwrap()
+        };
+
+        assert!(GeoGeometry::try_from(&nested(32)).is_ok());
+        for depth in [33, 100_000] {
+            assert_eq!(
+                GeoGeometry::try_from(&nested(depth)),
+                Err(GeometryError::MalformedWkb(33 * 9))
+            );
+        }
+    }
+}
@@ -24,6 +24,7 @@ pub mod bigdecimal02;
 pub mod bigint;
 pub mod chrono;
 pub mod decimal;
+pub mod geo_types;
 pub mod time;
 pub mod time02;
 pub mod uuid;
@@ -171,8 +172,24 @@ impl<T> ParseIrOpt<T> {
     }
 }
 
+/// Parses an integer from a MySql `DECIMAL` string.
+///
+/// Unlike `parse`, it'll accept a fractional part as long as it only consists of zeroes
+/// (e.g. `DECIMAL(39,2)` column value `"42.00"`).
+fn parse_decimal_integer<T: lexical::FromLexical>(bytes: &[u8]) -> lexical::Result<T> {
+    match bytes.iter().position(|x| *x == b'.') {
+        Some(pos) if pos + 1 < bytes.len() && bytes[pos + 1..].iter().all(|x| *x == b'0') => {
+            parse(&bytes[..pos])
+        }
+        _ => parse(bytes),
+    }
+}
+
 macro_rules! impl_from_value_num {
     ($ty:ident) => {
+        impl_from_value_num!($ty, parse);
+    };
+    ($ty:ident, $parse:ident) => {
         impl TryFrom<Value> for ParseIrOpt<$ty> {
             type Error = FromValueError;
 
@@ -184,7 +201,7 @@ macro_rules! impl_from_value_num {
                     Value::UInt(x) => $ty::try_from(x)
                         .map(ParseIrOpt::Ready)
                         .map_err(|_| FromValueError(Value::UInt(x))),
-                    Value::Bytes(bytes) => match parse(&*bytes) {
+                    Value::Bytes(bytes) => match $parse(&*bytes) {
                         Ok(x) => Ok(ParseIrOpt::Parsed(x, Value::Bytes(bytes))),
                         _ => Err(FromValueError(Value::Bytes(bytes))),
                     },
@@ -221,8 +238,8 @@ impl_from_value_num!(i64);
 impl_from_value_num!(u64);
 impl_from_value_num!(isize);
 impl_from_value_num!(usize);
-impl_from_value_num!(i128);
-impl_from_value_num!(u128);
+impl_from_value_num!(i128, parse_decimal_integer);
+impl_from_value_num!(u128, parse_decimal_integer);
 
 impl TryFrom<Value> for ParseIrOpt<bool> {
     type Error = FromValueError;
@@ -448,10 +465,120 @@ where
     }
 }
 
+/// SQL `NULL` is converted to `None`, any other value is converted to `Some(T)`.
+///
+/// Note that:
+///
+/// *   an empty string is not `NULL`, so `Option<String>` gives `Some("")` for it,
+///     while `Option<i64>` fails to convert it (see [`Nullable`] if empty strings
+///     must be handled separately);
+/// *   `Option<Option<T>>` never gives `Some(None)` – the inner `Option` is only useful
+///     with `Row::get`/`Row::take` that return `None` for a missing column, i.e.
+///     `row.get::<Option<T>, _>(idx)` is `None` for a missing column, `Some(None)`
+///     for `NULL` and `Some(Some(T))` otherwise.
 impl<T: FromValue> FromValue for Option<T> {
     type Intermediate = OptionIr2<T>;
 }
 
+/// Value of a nullable column that keeps SQL `NULL` apart from an empty string.
+///
+/// Useful for columns where `NULL` and `''` have different meaning, or for non-string
+/// types where `''` would otherwise fail to convert (e.g. `Nullable<i64>`).
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Nullable<T> {
+    /// SQL `NULL`.
+    #[default]
+    Null,
+    /// Empty string.
+    Empty,
+    /// Any other value.
+    Value(T),
+}
+
+impl<T> Nullable<T> {
+    /// Returns `true` if this is an SQL `NULL`.
+    pub fn is_null(&self) -> bool {
+        matches!(self, Nullable::Null)
+    }
+
+    /// Returns `true` if this is an empty string.
+    pub fn is_empty(&self) -> bool {
+        matches!(self, Nullable::Empty)
+    }
+
+    /// Returns a reference to the value (if any).
+    pub fn value(&self) -> Option<&T> {
+        match self {
+            Nullable::Value(x) => Some(x),
+            _ => None,
+        }
+    }
+
+    /// Returns the value (if any), so both `NULL` and an empty string give `None`.
+    pub fn into_option(self) -> Option<T> {
+        match self {
+            Nullable::Value(x) => Some(x),
+            _ => None,
+        }
+    }
+
+    /// Maps the value (if any).
+    pub fn map<U, F: FnOnce(T) -> U>(self, f: F) -> Nullable<U> {
+        match self {
+            Nullable::Null => Nullable::Null,
+            Nullable::Empty => Nullable::Empty,
+            Nullable::Value(x) => Nullable::Value(f(x)),
+        }
+    }
+}
+
+/// Intermediate result of a Value-to-Nullable<T> conversion.
+#[derive(Debug, Clone, PartialEq)]
+pub enum NullableIr<T: FromValue> {
+    Null,
+    Empty,
+    Value(T::Intermediate),
+}
+
+impl<T: FromValue> TryFrom<Value> for NullableIr<T> {
+    type Error = <<T as FromValue>::Intermediate as TryFrom<Value>>::Error;
+
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        match value {
+            Value::NULL => Ok(Self::Null),
+            Value::Bytes(ref x) if x.is_empty() => Ok(Self::Empty),
+            v => <T as FromValue>::Intermediate::try_from(v).map(Self::Value),
+        }
+    }
+}
+
+impl<T: FromValue> From<NullableIr<T>> for Nullable<T> {
+    fn from(ir: NullableIr<T>) -> Self {
+        match ir {
+            NullableIr::Null => Nullable::Null,
+            NullableIr::Empty => Nullable::Empty,
+            NullableIr::Value(ir) => Nullable::Value(ir.into()),
+        }
+    }
+}
+
+impl<T: FromValue> From<NullableIr<T>> for Value
+where
+    <T as FromValue>::Intermediate: Into<Value>,
+{
+    fn from(ir: NullableIr<T>) -> Self {
+        match ir {
+            NullableIr::Null => Value::NULL,
+            NullableIr::Empty => Value::Bytes(Vec::new()),
+            NullableIr::Value(ir) => ir.into(),
+        }
+    }
+}
+
+impl<T: FromValue> FromValue for Nullable<T> {
+    type Intermediate = NullableIr<T>;
+}
+
 // TODO: rustc is unable to conclude that Infallible equals FromValueError
 #[derive(Debug, Clone, PartialEq, PartialOrd)]
 #[repr(transparent)]
@@ -535,6 +662,21 @@ impl<const N: usize> FromValue for [u8; N] {
     type Intermediate = [u8; N];
 }
 
+impl TryFrom<Value> for bytes::Bytes {
+    type Error = FromValueError;
+
+    fn try_from(v: Value) -> Result<Self, Self::Error> {
+        match v {
+            Value::Bytes(x) => Ok(x.into()),
+            v => Err(FromValueError(v)),
+        }
+    }
+}
+
+impl FromValue for bytes::Bytes {
+    type Intermediate = bytes::Bytes;
+}
+
 fn parse_micros(micros_bytes: &[u8]) -> u32 {
     let mut micros = parse(micros_bytes).unwrap();
 
@@ -554,7 +696,7 @@ fn parse_micros(micros_bytes: &[u8]) -> u32 {
 }
 
 /// Returns (is_neg, hours, minutes, seconds, microseconds)
-fn parse_mysql_time_string(mut bytes: &[u8]) -> Option<(bool, u32, u8, u8, u32)> {
+pub(crate) fn parse_mysql_time_string(mut bytes: &[u8]) -> Option<(bool, u32, u8, u8, u32)> {
     #[derive(PartialEq, Eq, PartialOrd, Ord)]
     #[repr(u8)]
     enum TimeKind {
@@ -649,6 +791,16 @@ impl<T: Into<Value>> From<Option<T>> for Value {
     }
 }
 
+impl<T: Into<Value>> From<Nullable<T>> for Value {
+    fn from(x: Nullable<T>) -> Value {
+        match x {
+            Nullable::Null => Value::NULL,
+            Nullable::Empty => Value::Bytes(Vec::new()),
+            Nullable::Value(x) => x.into(),
+        }
+    }
+}
+
 macro_rules! into_value_impl (
     (signed $t:ty) => (
         impl From<$t> for Value {
@@ -761,6 +913,12 @@ impl<const N: usize> From<[u8; N]> for Value {
     }
 }
 
+impl From<bytes::Bytes> for Value {
+    fn from(x: bytes::Bytes) -> Value {
+        Value::Bytes(x.into())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -809,6 +967,18 @@ mod tests {
             assert_eq!(Value::from(from_value::<Vec<u8>>(val.clone())), val);
         }
 
+        #[test]
+        fn bytes_crate_roundtrip(s: Vec<u8>) {
+            let val = Value::Bytes(s);
+            assert_eq!(Value::from(from_value::<bytes::Bytes>(val.clone())), val);
+        }
+
+        #[test]
+        fn byte_array_roundtrip(s: [u8; 16]) {
+            let val = Value::Bytes(s.to_vec());
+            assert_eq!(Value::from(from_value::<[u8; 16]>(val.clone())), val);
+        }
+
         #[test]
         fn string_roundtrip(s: String) {
             let val = Value::Bytes(s.as_bytes().to_vec());
@@ -957,6 +1127,59 @@ mod tests {
         assert!(from_value_opt::<i128>(value).is_err());
     }
 
+    #[test]
+    fn from_value_should_fail_on_byte_array_length_mismatch() {
+        let value = Value::Bytes(b"0123456789abcdef"[..].into());
+        assert_eq!(
+            from_value_opt::<[u8; 15]>(value.clone()),
+            Err(FromValueError(value.clone()))
+        );
+        assert_eq!(
+            from_value_opt::<[u8; 17]>(value.clone()),
+            Err(FromValueError(value.clone()))
+        );
+        assert_eq!(from_value_opt::<[u8; 16]>(value), Ok(*b"0123456789abcdef"));
+        assert!(from_value_opt::<[u8; 1]>(Value::Int(1)).is_err());
+    }
+
+    #[test]
+    fn i128_from_decimal_string() {
+        let value = Value::Bytes(b"170141183460469231731687303715884105727.000"[..].into());
+        assert_eq!(from_value_opt::<i128>(value.clone()), Ok(i128::MAX));
+        assert_eq!(
+            Value::from(i128::MAX),
+            Value::Bytes(b"170141183460469231731687303715884105727"[..].into())
+        );
+
+        let value = Value::Bytes(b"-170141183460469231731687303715884105728.0"[..].into());
+        assert_eq!(from_value_opt::<i128>(value), Ok(i128::MIN));
+
+        let value = Value::Bytes(b"340282366920938463463374607431768211455.00"[..].into());
+        assert_eq!(from_value_opt::<u128>(value), Ok(u128::MAX));
+
+        for bytes in [
+            &b"170141183460469231731687303715884105728.0"[..],
+            b"-170141183460469231731687303715884105729.0",
+            b"42.5",
+            b"42.",
+            b"42.0.0",
+            b".0",
+        ] {
+            assert!(from_value_opt::<i128>(Value::Bytes(bytes.into())).is_err());
+        }
+
+        for bytes in [
+            &b"340282366920938463463374607431768211456.0"[..],
+            b"-1.0",
+            b"0.01",
+        ] {
+            assert!(from_value_opt::<u128>(Value::Bytes(bytes.into())).is_err());
+        }
+
+        // other integer types still require an integer string
+        assert!(from_value_opt::<i64>(Value::Bytes(b"42.0"[..].into())).is_err());
+    }
+
     #[cfg(feature = "nightly")]
     #[cfg(feature = "chrono")]
     #[bench]
@@ -992,6 +1215,41 @@ mod tests {
         assert_eq!(converted_f64, original_f32 as f64);
     }
 
+    #[test]
+    fn nullable_conversions_work() {
+        assert!(Value::NULL.is_null());
+        assert!(!Value::Bytes(Vec::new()).is_null());
+
+        assert_eq!(
+            from_value::<Option<String>>(Value::Bytes(Vec::new())),
+            Some(String::new())
+        );
+        assert!(from_value_opt::<Option<i64>>(Value::Bytes(Vec::new())).is_err());
+        assert_eq!(from_value::<Option<Option<i64>>>(Value::NULL), None);
+
+        assert_eq!(from_value::<Nullable<i64>>(Value::NULL), Nullable::Null);
+        assert_eq!(
+            from_value::<Nullable<i64>>(Value::Bytes(Vec::new())),
+            Nullable::Empty
+        );
+        assert_eq!(
+            from_value::<Nullable<i64>>(Value::Bytes(b"42".to_vec())),
+            Nullable::Value(42)
+        );
+        assert!(from_value_opt::<Nullable<i64>>(Value::Bytes(b"foo".to_vec())).is_err());
+
+        for value in [
+            Value::NULL,
+            Value::Bytes(Vec::new()),
+            Value::Bytes(b"foo".to_vec()),
+        ] {
+            let nullable = from_value::<Nullable<String>>(value.clone());
+            assert_eq!(Value::from(nullable), value);
+        }
+        assert_eq!(Nullable::Value(1_u8).map(u64::from).into_option(), Some(1));
+        assert_eq!(Nullable::<u8>::Empty.into_option(), None);
+    }
+
     #[test]
     fn value_double_read_conversions_work() {
         let original_f64 = std::f64::consts::PI;
@@ -0,0 +1,258 @@
+// Copyright (c) 2023 Anatoly Ikorsky
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! MySql `GEOMETRY` value (SRID + WKB).
+
+use byteorder::{BigEndian, ByteOrder, LittleEndian};
+
+use std::convert::TryFrom;
+
+use crate::{
+    constants::GeometryType,
+    value::{
+        convert::{FromValue, FromValueError, ParseIr},
+        point::Point,
+        Value,
+    },
+};
+
+/// Length of the SRID prefix of the MySql internal geometry format.
+const SRID_LEN: usize = 4;
+
+/// Length of the WKB header: byte order (1) + WKB type (4).
+const WKB_HEADER_LEN: usize = 5;
+
+/// `Geometry` conversion error.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, thiserror::Error)]
+pub enum GeometryError {
+    #[error("Invalid geometry value length {}", _0)]
+    InvalidLength(usize),
+    #[error("Invalid WKB byte order {}", _0)]
+    InvalidByteOrder(u8),
+    #[error("Unknown WKB geometry type {}", _0)]
+    UnknownType(u32),
+    #[error("Malformed WKB at offset {}", _0)]
+    MalformedWkb(usize),
+}
+
+/// MySql `GEOMETRY` value of any type.
+///
+/// Values are read from and written to MySql internal geometry format, i.e. a little-endian SRID
+/// followed by the WKB representation. Only the WKB header is validated, the geometry itself
+/// is kept as is (see [`Point`] for a parsed point, or enable the `geo-types` feature to convert
+/// into `geo_types::Geometry`).
+///
+/// ```
+/// # use mysql_common::{constants::GeometryType, value::{geometry::Geometry, point::Point, Value}};
+/// # use std::convert::TryFrom;
+/// let geometry = Geometry::from(Point::with_srid(1.5, -2.0, 4326));
+/// assert_eq!(geometry.srid(), 4326);
+/// assert_eq!(geometry.geometry_type(), GeometryType::GEOM_POINT);
+/// assert_eq!(Point::try_from(&geometry), Ok(Point::with_srid(1.5, -2.0, 4326)));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Geometry {
+    srid: u32,
+    wkb: Vec<u8>,
+}
+
+impl Geometry {
+    /// Creates a new geometry from the given SRID and WKB representation.
+    ///
+    /// Returns an error if the WKB header is invalid.
+    pub fn new(srid: u32, wkb: Vec<u8>) -> Result<Self, GeometryError> {
+        wkb_type(&wkb)?;
+        Ok(Self { srid, wkb })
+    }
+
+    /// Parses a geometry from MySql internal geometry format.
+    pub fn from_geometry_bytes(bytes: &[u8]) -> Result<Self, GeometryError> {
+        if bytes.len() < SRID_LEN + WKB_HEADER_LEN {
+            return Err(GeometryError::InvalidLength(bytes.len()));
+        }
+        let srid = LittleEndian::read_u32(&bytes[..SRID_LEN]);
+        Self::new(srid, bytes[SRID_LEN..].to_vec())
+    }
+
+    /// Serializes this geometry into MySql internal geometry format.
+    pub fn to_geometry_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(SRID_LEN + self.wkb.len());
+        bytes.extend_from_slice(&self.srid.to_le_bytes());
+        bytes.extend_from_slice(&self.wkb);
+        bytes
+    }
+
+    /// Returns the spatial reference system identifier (`0` if not specified).
+    pub fn srid(&self) -> u32 {
+        self.srid
+    }
+
+    /// Returns modified `self` with the given SRID.
+    pub fn with_srid(mut self, srid: u32) -> Self {
+        self.srid = srid;
+        self
+    }
+
+    /// Returns the WKB representation of this geometry.
+    pub fn wkb(&self) -> &[u8] {
+        &self.wkb
+    }
+
+    /// Returns the WKB representation of this geometry.
+    pub fn into_wkb(self) -> Vec<u8> {
+        self.wkb
+    }
+
+    /// Returns the type of this geometry.
+    pub fn geometry_type(&self) -> GeometryType {
+        // validated in the constructor
+        let ty = wkb_type(&self.wkb).expect("valid WKB header");
+        GeometryType::try_from(ty as u8).expect("known WKB type")
+    }
+}
+
+/// Returns the WKB type of the given WKB.
+fn wkb_type(wkb: &[u8]) -> Result<u32, GeometryError> {
+    if wkb.len() < WKB_HEADER_LEN {
+        return Err(GeometryError::InvalidLength(SRID_LEN + wkb.len()));
+    }
+    let ty = match wkb[0] {
+        0 => BigEndian::read_u32(&wkb[1..5]),
+        1 => LittleEndian::read_u32(&wkb[1..5]),
+        order => return Err(GeometryError::InvalidByteOrder(order)),
+    };
+    match ty {
+        1..=7 => Ok(ty),
+        _ => Err(GeometryError::UnknownType(ty)),
+    }
+}
+
+impl From<Point> for Geometry {
+    fn from(point: Point) -> Self {
+        let bytes = point.to_geometry_bytes();
+        Self {
+            srid: point.srid,
+            wkb: bytes[SRID_LEN..].to_vec(),
+        }
+    }
+}
+
+impl TryFrom<&Geometry> for Point {
+    type Error = GeometryError;
+
+    fn try_from(geometry: &Geometry) -> Result<Self, Self::Error> {
+        Point::from_geometry_bytes(&geometry.to_geometry_bytes())
+            .map_err(|_| GeometryError::UnknownType(geometry.geometry_type() as u32))
+    }
+}
+
+impl TryFrom<&[u8]> for Geometry {
+    type Error = GeometryError;
+
+    fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+        Self::from_geometry_bytes(bytes)
+    }
+}
+
+impl From<Geometry> for Value {
+    fn from(geometry: Geometry) -> Self {
+        Value::Bytes(geometry.to_geometry_bytes())
+    }
+}
+
+impl TryFrom<Value> for ParseIr<Geometry> {
+    type Error = FromValueError;
+
+    fn try_from(v: Value) -> Result<Self, Self::Error> {
+        match v {
+            Value::Bytes(ref bytes) => match Geometry::from_geometry_bytes(bytes) {
+                Ok(geometry) => Ok(ParseIr(geometry, v)),
+                Err(_) => Err(FromValueError(v)),
+            },
+            v => Err(FromValueError(v)),
+        }
+    }
+}
+
+impl From<ParseIr<Geometry>> for Geometry {
+    fn from(value: ParseIr<Geometry>) -> Self {
+        value.commit()
+    }
+}
+
+impl From<ParseIr<Geometry>> for Value {
+    fn from(value: ParseIr<Geometry>) -> Self {
+        value.rollback()
+    }
+}
+
+impl FromValue for Geometry {
+    type Intermediate = ParseIr<Geometry>;
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::TryFrom;
+
+    use super::{Geometry, GeometryError};
+    use crate::{
+        constants::GeometryType,
+        value::{
+            convert::{from_value, from_value_opt},
+            point::Point,
+            Value,
+        },
+    };
+
+    #[test]
+    fn should_parse_geometry_bytes() {
+        // SELECT ST_GeomFromText('LINESTRING(0 0, 1 1)', 4326)
+        let linestring = b"\xe6\x10\x00\x00\x01\x02\x00\x00\x00\x02\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\xf0\x3f\x00\x00\x00\x00\x00\x00\xf0\x3f";
+        let geometry = Geometry::from_geometry_bytes(linestring).unwrap();
+        assert_eq!(geometry.srid(), 4326);
+        assert_eq!(geometry.geometry_type(), GeometryType::GEOM_LINESTRING);
+        assert_eq!(geometry.wkb(), &linestring[4..]);
+        assert_eq!(geometry.to_geometry_bytes(), &linestring[..]);
+        assert_eq!(
+            Point::try_from(&geometry),
+            Err(GeometryError::UnknownType(2))
+        );
+
+        let point = Point::with_srid(1.0, -2.0, 4326);
+        let geometry = Geometry::from(point);
+        assert_eq!(geometry.geometry_type(), GeometryType::GEOM_POINT);
+        assert_eq!(geometry.to_geometry_bytes(), point.to_geometry_bytes());
+        assert_eq!(Point::try_from(&geometry), Ok(point));
+
+        let mut bad_order = linestring.to_vec();
+        bad_order[4] = 2;
+        assert_eq!(
+            Geometry::from_geometry_bytes(&bad_order),
+            Err(GeometryError::InvalidByteOrder(2))
+        );
+        let mut bad_type = linestring.to_vec();
+        bad_type[5] = 8;
+        assert_eq!(
+            Geometry::from_geometry_bytes(&bad_type),
+            Err(GeometryError::UnknownType(8))
+        );
+        assert_eq!(
+            Geometry::from_geometry_bytes(&linestring[..8]),
+            Err(GeometryError::InvalidLength(8))
+        );
+
+        let value = Value::Bytes(linestring.to_vec());
+        assert_eq!(
+            from_value::<Geometry>(value.clone()).to_geometry_bytes(),
+            &linestring[..]
+        );
+        assert_eq!(Value::from(from_value::<Geometry>(value.clone())), value);
+        assert!(from_value_opt::<Geometry>(Value::Bytes(bad_type)).is_err());
+        assert!(from_value_opt::<Geometry>(Value::Int(1)).is_err());
+    }
+}
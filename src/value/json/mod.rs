@@ -8,6 +8,11 @@
 
 use serde::{Deserialize, Serialize};
 
+#[cfg(feature = "serde_json")]
+pub(crate) use self::serde_integration::{json_to_value, value_to_json};
+
+#[cfg(feature = "serde_json")]
+#[cfg_attr(docsrs, doc(cfg(feature = "serde_json")))]
 pub mod serde_integration;
 
 /// Use it to pass `T: Serialize` as JSON to a prepared statement.
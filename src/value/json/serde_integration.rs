@@ -8,6 +8,7 @@
 
 use std::convert::TryFrom;
 
+use base64::{engine::general_purpose::STANDARD, Engine};
 use serde::{de::DeserializeOwned, Serialize};
 use serde_json::{self, Value as Json};
 
@@ -88,3 +89,127 @@ impl<T: Serialize> From<Serialized<T>> for Value {
         Value::Bytes(serde_json::to_string(&x.0).unwrap().into())
     }
 }
+
+/// Converts a JSON value into a `Value` suitable for a statement parameter.
+///
+/// Scalars are mapped to the corresponding `Value` variants (JSON strings are passed as is),
+/// while arrays and objects are passed as serialized JSON text.
+pub(crate) fn json_to_value(json: Json) -> Value {
+    match json {
+        Json::Null => Value::NULL,
+        Json::Bool(x) => Value::from(x),
+        Json::Number(x) => {
+            if let Some(x) = x.as_i64() {
+                Value::Int(x)
+            } else if let Some(x) = x.as_u64() {
+                Value::UInt(x)
+            } else {
+                Value::Double(x.as_f64().unwrap_or_default())
+            }
+        }
+        Json::String(x) => Value::from(x),
+        x @ Json::Array(_) | x @ Json::Object(_) => Value::from(x),
+    }
+}
+
+/// Converts a `Value` into a JSON value.
+///
+/// *   non-UTF-8 bytes are encoded as a base64 string;
+/// *   dates and times are formatted as MySql literals (without quotes);
+/// *   non-finite floats are converted to `null`.
+pub(crate) fn value_to_json(value: &Value) -> Json {
+    match *value {
+        Value::NULL => Json::Null,
+        Value::Bytes(ref bytes) => match std::str::from_utf8(bytes) {
+            Ok(string) => Json::String(string.into()),
+            Err(_) => Json::String(STANDARD.encode(bytes)),
+        },
+        Value::Int(x) => x.into(),
+        Value::UInt(x) => x.into(),
+        Value::Float(x) => f64::from(x).into(),
+        Value::Double(x) => x.into(),
+        Value::Date(..) | Value::Time(..) => {
+            let literal = value.as_sql(false);
+            Json::String(literal.trim_matches('\'').into())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use serde_json::json;
+
+    use crate::{
+        constants::ColumnType, packets::Column, params::Params, row::new_row, value::Value,
+    };
+
+    #[test]
+    fn params_from_json() {
+        let json = json!({
+            "id": 42,
+            "big": u64::MAX,
+            "ratio": 0.5,
+            "name": "foo",
+            "flag": true,
+            "none": null,
+            "tags": ["a", "b"],
+        });
+
+        let params = match json {
+            serde_json::Value::Object(map) => Params::from_json(map),
+            _ => unreachable!(),
+        };
+
+        assert_eq!(
+            params,
+            Params::from(vec![
+                ("id", Value::Int(42)),
+                ("big", Value::UInt(u64::MAX)),
+                ("ratio", Value::Double(0.5)),
+                ("name", Value::Bytes(b"foo".to_vec())),
+                ("flag", Value::Int(1)),
+                ("none", Value::NULL),
+                ("tags", Value::Bytes(br#"["a","b"]"#.to_vec())),
+            ])
+        );
+
+        assert_eq!(Params::from_json(Default::default()), Params::Empty);
+    }
+
+    #[test]
+    fn row_to_json() {
+        let columns = vec![
+            Column::new(ColumnType::MYSQL_TYPE_LONG).with_name(b"id"),
+            Column::new(ColumnType::MYSQL_TYPE_BLOB).with_name(b"name"),
+            Column::new(ColumnType::MYSQL_TYPE_BLOB).with_name(b"raw"),
+            Column::new(ColumnType::MYSQL_TYPE_DATETIME).with_name(b"created"),
+            Column::new(ColumnType::MYSQL_TYPE_NULL).with_name(b"none"),
+        ];
+        let mut row = new_row(
+            vec![
+                Value::Int(42),
+                Value::Bytes(b"foo".to_vec()),
+                Value::Bytes(vec![0xff, 0x00]),
+                Value::Date(2023, 6, 19, 12, 30, 0, 0),
+                Value::NULL,
+            ],
+            Arc::from(columns.into_boxed_slice()),
+        );
+
+        assert_eq!(
+            serde_json::Value::Object(row.to_json()),
+            json!({
+                "id": 42,
+                "name": "foo",
+                "raw": "/wA=",
+                "created": "2023-06-19 12:30:00",
+                "none": null,
+            })
+        );
+
+        let _: Option<i32> = row.take("id");
+        assert!(!row.to_json().contains_key("id"));
+    }
+}
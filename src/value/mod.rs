@@ -23,7 +23,10 @@ use crate::{
 };
 
 pub mod convert;
+pub mod geometry;
 pub mod json;
+pub mod mysql_time;
+pub mod point;
 
 /// Side of MySql value serialization.
 pub trait SerializationSide {
@@ -222,6 +225,14 @@ macro_rules! de_num {
 }
 
 impl Value {
+    /// Returns `true` if this is an SQL `NULL`.
+    ///
+    /// Note that an empty string (`Value::Bytes(vec![])`) is not `NULL`
+    /// (see [`convert::Nullable`]).
+    pub fn is_null(&self) -> bool {
+        matches!(self, Value::NULL)
+    }
+
     /// Returns length in binary serialized form.
     pub fn bin_len(&self) -> u64 {
         match self {
@@ -0,0 +1,381 @@
+// Copyright (c) 2023 Anatoly Ikorsky
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! MySql `TIME` value that covers the whole `-838:59:59` – `838:59:59` range.
+
+use std::{cmp::Ordering, convert::TryFrom, fmt, str::FromStr, time::Duration};
+
+use crate::value::{
+    convert::{parse_mysql_time_string, FromValue, FromValueError, ParseIr},
+    Value,
+};
+
+const MICROS_PER_SECOND: i64 = 1_000_000;
+const MICROS_PER_MINUTE: i64 = 60 * MICROS_PER_SECOND;
+const MICROS_PER_HOUR: i64 = 60 * MICROS_PER_MINUTE;
+
+/// `MysqlTime` conversion error.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, thiserror::Error)]
+pub enum MysqlTimeError {
+    #[error("Value is out of MySql TIME range (-838:59:59 to 838:59:59)")]
+    OutOfRange,
+    #[error("Invalid MySql TIME string")]
+    InvalidString,
+}
+
+/// MySql `TIME` value.
+///
+/// Unlike `Duration` types this one keeps the sign and the MySql range invariant:
+/// any conversion that would leave the `-838:59:59` – `838:59:59` range is an error
+/// rather than a silent wrap.
+///
+/// ```
+/// # use mysql_common::value::mysql_time::MysqlTime;
+/// let time: MysqlTime = "-100:30:00.5".parse().unwrap();
+/// assert!(time.is_negative());
+/// assert_eq!(time.hours(), 100);
+/// assert_eq!(time.micros(), 500_000);
+/// assert_eq!(time.to_string(), "-100:30:00.500000");
+///
+/// assert!("839:00:00".parse::<MysqlTime>().is_err());
+/// ```
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct MysqlTime {
+    negative: bool,
+    hours: u16,
+    minutes: u8,
+    seconds: u8,
+    micros: u32,
+}
+
+impl MysqlTime {
+    /// Maximum number of hours in a MySql `TIME` value.
+    pub const MAX_HOURS: u16 = 838;
+
+    /// Maximum MySql `TIME` value (`838:59:59`).
+    pub const MAX: MysqlTime = MysqlTime {
+        negative: false,
+        hours: Self::MAX_HOURS,
+        minutes: 59,
+        seconds: 59,
+        micros: 0,
+    };
+
+    /// Minimum MySql `TIME` value (`-838:59:59`).
+    pub const MIN: MysqlTime = MysqlTime {
+        negative: true,
+        hours: Self::MAX_HOURS,
+        minutes: 59,
+        seconds: 59,
+        micros: 0,
+    };
+
+    /// Creates a new instance.
+    ///
+    /// Returns an error if any component is out of range or the value
+    /// is out of MySql `TIME` range.
+    pub fn new(
+        negative: bool,
+        hours: u16,
+        minutes: u8,
+        seconds: u8,
+        micros: u32,
+    ) -> Result<Self, MysqlTimeError> {
+        if minutes > 59 || seconds > 59 || micros > 999_999 {
+            return Err(MysqlTimeError::OutOfRange);
+        }
+
+        let total_micros = i64::from(hours) * MICROS_PER_HOUR
+            + i64::from(minutes) * MICROS_PER_MINUTE
+            + i64::from(seconds) * MICROS_PER_SECOND
+            + i64::from(micros);
+
+        Self::from_micros(if negative {
+            -total_micros
+        } else {
+            total_micros
+        })
+    }
+
+    /// Creates a new instance from a signed number of microseconds.
+    pub fn from_micros(micros: i64) -> Result<Self, MysqlTimeError> {
+        if micros.unsigned_abs() > Self::MAX.as_micros().unsigned_abs() {
+            return Err(MysqlTimeError::OutOfRange);
+        }
+
+        let abs = micros.abs();
+        Ok(Self {
+            negative: micros < 0,
+            hours: (abs / MICROS_PER_HOUR) as u16,
+            minutes: ((abs % MICROS_PER_HOUR) / MICROS_PER_MINUTE) as u8,
+            seconds: ((abs % MICROS_PER_MINUTE) / MICROS_PER_SECOND) as u8,
+            micros: (abs % MICROS_PER_SECOND) as u32,
+        })
+    }
+
+    /// Returns the signed number of microseconds.
+    pub const fn as_micros(&self) -> i64 {
+        let abs = self.hours as i64 * MICROS_PER_HOUR
+            + self.minutes as i64 * MICROS_PER_MINUTE
+            + self.seconds as i64 * MICROS_PER_SECOND
+            + self.micros as i64;
+        if self.negative {
+            -abs
+        } else {
+            abs
+        }
+    }
+
+    /// Returns `true` if this value is less than zero.
+    pub fn is_negative(&self) -> bool {
+        self.negative
+    }
+
+    /// Returns the hours component (`0..=838`).
+    pub fn hours(&self) -> u16 {
+        self.hours
+    }
+
+    /// Returns the minutes component (`0..=59`).
+    pub fn minutes(&self) -> u8 {
+        self.minutes
+    }
+
+    /// Returns the seconds component (`0..=59`).
+    pub fn seconds(&self) -> u8 {
+        self.seconds
+    }
+
+    /// Returns the microseconds component (`0..=999999`).
+    pub fn micros(&self) -> u32 {
+        self.micros
+    }
+
+    /// Converts `Value::Time` components into `MysqlTime`.
+    fn from_value_parts(
+        negative: bool,
+        days: u32,
+        hours: u8,
+        minutes: u8,
+        seconds: u8,
+        micros: u32,
+    ) -> Result<Self, MysqlTimeError> {
+        let hours = u16::try_from(u64::from(days) * 24 + u64::from(hours))
+            .map_err(|_| MysqlTimeError::OutOfRange)?;
+        Self::new(negative, hours, minutes, seconds, micros)
+    }
+}
+
+impl PartialOrd for MysqlTime {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for MysqlTime {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.as_micros().cmp(&other.as_micros())
+    }
+}
+
+impl fmt::Display for MysqlTime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.negative {
+            f.write_str("-")?;
+        }
+        write!(
+            f,
+            "{:02}:{:02}:{:02}",
+            self.hours, self.minutes, self.seconds
+        )?;
+        if self.micros > 0 {
+            write!(f, ".{:06}", self.micros)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for MysqlTime {
+    type Err = MysqlTimeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (negative, hours, minutes, seconds, micros) =
+            parse_mysql_time_string(s.as_bytes()).ok_or(MysqlTimeError::InvalidString)?;
+        let hours = u16::try_from(hours).map_err(|_| MysqlTimeError::OutOfRange)?;
+        Self::new(negative, hours, minutes, seconds, micros)
+    }
+}
+
+impl TryFrom<Duration> for MysqlTime {
+    type Error = MysqlTimeError;
+
+    /// Sub-microsecond precision is truncated.
+    fn try_from(duration: Duration) -> Result<Self, Self::Error> {
+        i64::try_from(duration.as_micros())
+            .map_err(|_| MysqlTimeError::OutOfRange)
+            .and_then(Self::from_micros)
+    }
+}
+
+impl TryFrom<MysqlTime> for Duration {
+    type Error = MysqlTimeError;
+
+    /// Negative values are out of `Duration` range.
+    fn try_from(time: MysqlTime) -> Result<Self, Self::Error> {
+        u64::try_from(time.as_micros())
+            .map(Duration::from_micros)
+            .map_err(|_| MysqlTimeError::OutOfRange)
+    }
+}
+
+#[cfg(feature = "chrono")]
+#[cfg_attr(docsrs, doc(cfg(feature = "chrono")))]
+impl TryFrom<chrono::Duration> for MysqlTime {
+    type Error = MysqlTimeError;
+
+    /// Sub-microsecond precision is truncated.
+    fn try_from(duration: chrono::Duration) -> Result<Self, Self::Error> {
+        duration
+            .num_microseconds()
+            .ok_or(MysqlTimeError::OutOfRange)
+            .and_then(Self::from_micros)
+    }
+}
+
+#[cfg(feature = "chrono")]
+#[cfg_attr(docsrs, doc(cfg(feature = "chrono")))]
+impl From<MysqlTime> for chrono::Duration {
+    fn from(time: MysqlTime) -> Self {
+        chrono::Duration::microseconds(time.as_micros())
+    }
+}
+
+impl From<MysqlTime> for Value {
+    fn from(time: MysqlTime) -> Self {
+        Value::Time(
+            time.negative,
+            u32::from(time.hours / 24),
+            (time.hours % 24) as u8,
+            time.minutes,
+            time.seconds,
+            time.micros,
+        )
+    }
+}
+
+impl TryFrom<Value> for ParseIr<MysqlTime> {
+    type Error = FromValueError;
+
+    fn try_from(v: Value) -> Result<Self, Self::Error> {
+        let time = match v {
+            Value::Time(negative, days, hours, minutes, seconds, micros) => {
+                MysqlTime::from_value_parts(negative, days, hours, minutes, seconds, micros)
+            }
+            Value::Bytes(ref bytes) => std::str::from_utf8(bytes)
+                .map_err(|_| MysqlTimeError::InvalidString)
+                .and_then(MysqlTime::from_str),
+            _ => Err(MysqlTimeError::InvalidString),
+        };
+
+        match time {
+            Ok(time) => Ok(ParseIr(time, v)),
+            Err(_) => Err(FromValueError(v)),
+        }
+    }
+}
+
+impl From<ParseIr<MysqlTime>> for MysqlTime {
+    fn from(value: ParseIr<MysqlTime>) -> Self {
+        value.commit()
+    }
+}
+
+impl From<ParseIr<MysqlTime>> for Value {
+    fn from(value: ParseIr<MysqlTime>) -> Self {
+        value.rollback()
+    }
+}
+
+impl FromValue for MysqlTime {
+    type Intermediate = ParseIr<MysqlTime>;
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{convert::TryFrom, time::Duration};
+
+    use proptest::prelude::*;
+
+    use super::{MysqlTime, MysqlTimeError};
+    use crate::value::{
+        convert::{from_value, from_value_opt},
+        Value,
+    };
+
+    #[test]
+    fn should_respect_mysql_range() {
+        assert_eq!(MysqlTime::MAX.to_string(), "838:59:59");
+        assert_eq!(MysqlTime::MIN.to_string(), "-838:59:59");
+        assert_eq!("838:59:59".parse::<MysqlTime>(), Ok(MysqlTime::MAX));
+        assert_eq!("-838:59:59".parse::<MysqlTime>(), Ok(MysqlTime::MIN));
+        assert_eq!(
+            "838:59:59.000001".parse::<MysqlTime>(),
+            Err(MysqlTimeError::OutOfRange)
+        );
+        assert_eq!(
+            "839:00:00".parse::<MysqlTime>(),
+            Err(MysqlTimeError::OutOfRange)
+        );
+        assert_eq!(
+            "12:60:00".parse::<MysqlTime>(),
+            Err(MysqlTimeError::InvalidString)
+        );
+        assert_eq!(
+            MysqlTime::new(false, 1, 60, 0, 0),
+            Err(MysqlTimeError::OutOfRange)
+        );
+
+        assert_eq!(
+            MysqlTime::try_from(Duration::from_secs(838 * 3600 + 59 * 60 + 59)),
+            Ok(MysqlTime::MAX)
+        );
+        assert_eq!(
+            MysqlTime::try_from(Duration::from_secs(839 * 3600)),
+            Err(MysqlTimeError::OutOfRange)
+        );
+        assert_eq!(
+            Duration::try_from(MysqlTime::MIN),
+            Err(MysqlTimeError::OutOfRange)
+        );
+
+        assert!(from_value_opt::<MysqlTime>(Value::Time(false, 35, 0, 0, 0, 0)).is_err());
+        assert!(from_value_opt::<MysqlTime>(Value::Int(1)).is_err());
+    }
+
+    #[test]
+    fn should_convert_value_time() {
+        let value = Value::Time(true, 34, 22, 59, 59, 0);
+        assert_eq!(from_value::<MysqlTime>(value.clone()), MysqlTime::MIN);
+        assert_eq!(Value::from(MysqlTime::MIN), value);
+        assert_eq!("-00:00:00".parse::<MysqlTime>(), Ok(MysqlTime::default()));
+    }
+
+    proptest! {
+        #[test]
+        fn micros_roundtrip(micros in -3_020_399_000_000_i64..=3_020_399_000_000) {
+            let time = MysqlTime::from_micros(micros).unwrap();
+            prop_assert_eq!(time.as_micros(), micros);
+            prop_assert_eq!(time.to_string().parse::<MysqlTime>(), Ok(time));
+            prop_assert_eq!(from_value::<MysqlTime>(Value::from(time)), time);
+            prop_assert_eq!(
+                from_value::<MysqlTime>(Value::Bytes(time.to_string().into_bytes())),
+                time
+            );
+        }
+    }
+}
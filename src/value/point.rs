@@ -0,0 +1,211 @@
+// Copyright (c) 2023 Anatoly Ikorsky
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! MySql `POINT` value stored in a `GEOMETRY` column.
+
+use byteorder::{BigEndian, ByteOrder, LittleEndian};
+
+use std::convert::TryFrom;
+
+use crate::value::{
+    convert::{FromValue, FromValueError, ParseIr},
+    Value,
+};
+
+/// WKB geometry type code of a point.
+const WKB_POINT: u32 = 1;
+
+/// Length of a point in the MySql internal geometry format:
+/// SRID (4) + byte order (1) + WKB type (4) + X (8) + Y (8).
+const POINT_LEN: usize = 25;
+
+/// `Point` conversion error.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, thiserror::Error)]
+pub enum PointError {
+    #[error("Invalid geometry value length {}, expected {}", _0, POINT_LEN)]
+    InvalidLength(usize),
+    #[error("Invalid WKB byte order {}", _0)]
+    InvalidByteOrder(u8),
+    #[error("Geometry is not a POINT (WKB type {})", _0)]
+    NotAPoint(u32),
+}
+
+/// MySql `POINT` value.
+///
+/// Covers the common case of a `POINT` (or a `GEOMETRY` that only holds points) column
+/// without pulling in a geometry library. Values are read from and written to MySql
+/// internal geometry format, i.e. a little-endian SRID followed by the WKB representation.
+///
+/// ```
+/// # use mysql_common::value::{convert::from_value, point::Point, Value};
+/// let point = Point::with_srid(1.5, -2.0, 4326);
+/// let value = Value::from(point);
+/// assert_eq!(from_value::<Point>(value), point);
+/// ```
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct Point {
+    /// X coordinate.
+    pub x: f64,
+    /// Y coordinate.
+    pub y: f64,
+    /// Spatial reference system identifier (`0` if not specified).
+    pub srid: u32,
+}
+
+impl Point {
+    /// Creates a new point with SRID `0`.
+    pub fn new(x: f64, y: f64) -> Self {
+        Self::with_srid(x, y, 0)
+    }
+
+    /// Creates a new point with the given SRID.
+    pub fn with_srid(x: f64, y: f64, srid: u32) -> Self {
+        Self { x, y, srid }
+    }
+
+    /// Parses a point from MySql internal geometry format.
+    pub fn from_geometry_bytes(bytes: &[u8]) -> Result<Self, PointError> {
+        if bytes.len() != POINT_LEN {
+            return Err(PointError::InvalidLength(bytes.len()));
+        }
+
+        let srid = LittleEndian::read_u32(&bytes[0..4]);
+        let wkb = &bytes[4..];
+        match wkb[0] {
+            0 => Self::read_wkb::<BigEndian>(wkb, srid),
+            1 => Self::read_wkb::<LittleEndian>(wkb, srid),
+            order => Err(PointError::InvalidByteOrder(order)),
+        }
+    }
+
+    /// Serializes this point into MySql internal geometry format.
+    pub fn to_geometry_bytes(&self) -> Vec<u8> {
+        let mut bytes = vec![0_u8; POINT_LEN];
+        LittleEndian::write_u32(&mut bytes[0..4], self.srid);
+        bytes[4] = 1;
+        LittleEndian::write_u32(&mut bytes[5..9], WKB_POINT);
+        LittleEndian::write_f64(&mut bytes[9..17], self.x);
+        LittleEndian::write_f64(&mut bytes[17..25], self.y);
+        bytes
+    }
+
+    fn read_wkb<B: ByteOrder>(wkb: &[u8], srid: u32) -> Result<Self, PointError> {
+        match B::read_u32(&wkb[1..5]) {
+            WKB_POINT => Ok(Self {
+                x: B::read_f64(&wkb[5..13]),
+                y: B::read_f64(&wkb[13..21]),
+                srid,
+            }),
+            other => Err(PointError::NotAPoint(other)),
+        }
+    }
+}
+
+impl TryFrom<&[u8]> for Point {
+    type Error = PointError;
+
+    fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+        Self::from_geometry_bytes(bytes)
+    }
+}
+
+impl From<Point> for Value {
+    fn from(point: Point) -> Self {
+        Value::Bytes(point.to_geometry_bytes())
+    }
+}
+
+impl TryFrom<Value> for ParseIr<Point> {
+    type Error = FromValueError;
+
+    fn try_from(v: Value) -> Result<Self, Self::Error> {
+        match v {
+            Value::Bytes(ref bytes) => match Point::from_geometry_bytes(bytes) {
+                Ok(point) => Ok(ParseIr(point, v)),
+                Err(_) => Err(FromValueError(v)),
+            },
+            v => Err(FromValueError(v)),
+        }
+    }
+}
+
+impl From<ParseIr<Point>> for Point {
+    fn from(value: ParseIr<Point>) -> Self {
+        value.commit()
+    }
+}
+
+impl From<ParseIr<Point>> for Value {
+    fn from(value: ParseIr<Point>) -> Self {
+        value.rollback()
+    }
+}
+
+impl FromValue for Point {
+    type Intermediate = ParseIr<Point>;
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::{Point, PointError};
+    use crate::value::{
+        convert::{from_value, from_value_opt},
+        Value,
+    };
+
+    #[test]
+    fn should_parse_geometry_bytes() {
+        // SELECT ST_GeomFromText('POINT(1 -2)', 4326)
+        let little_endian = b"\xe6\x10\x00\x00\x01\x01\x00\x00\x00\x00\x00\x00\x00\x00\x00\xf0\x3f\x00\x00\x00\x00\x00\x00\x00\xc0";
+        assert_eq!(
+            Point::from_geometry_bytes(little_endian),
+            Ok(Point::with_srid(1.0, -2.0, 4326))
+        );
+        assert_eq!(
+            Point::with_srid(1.0, -2.0, 4326).to_geometry_bytes(),
+            &little_endian[..]
+        );
+
+        let big_endian = b"\x00\x00\x00\x00\x00\x00\x00\x00\x01\x3f\xf0\x00\x00\x00\x00\x00\x00\xc0\x00\x00\x00\x00\x00\x00\x00";
+        assert_eq!(
+            Point::from_geometry_bytes(big_endian),
+            Ok(Point::new(1.0, -2.0))
+        );
+
+        let mut linestring = little_endian.to_vec();
+        linestring[5] = 2;
+        assert_eq!(
+            Point::from_geometry_bytes(&linestring),
+            Err(PointError::NotAPoint(2))
+        );
+        let mut bad_order = little_endian.to_vec();
+        bad_order[4] = 2;
+        assert_eq!(
+            Point::from_geometry_bytes(&bad_order),
+            Err(PointError::InvalidByteOrder(2))
+        );
+        assert_eq!(
+            Point::from_geometry_bytes(&little_endian[..24]),
+            Err(PointError::InvalidLength(24))
+        );
+
+        assert!(from_value_opt::<Point>(Value::Bytes(linestring)).is_err());
+        assert!(from_value_opt::<Point>(Value::Int(1)).is_err());
+    }
+
+    proptest! {
+        #[test]
+        fn point_roundtrip(x: f64, y: f64, srid: u32) {
+            prop_assume!(!x.is_nan() && !y.is_nan());
+            let point = Point::with_srid(x, y, srid);
+            prop_assert_eq!(from_value::<Point>(Value::from(point)), point);
+        }
+    }
+}